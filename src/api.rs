@@ -10,6 +10,7 @@
 //! - [`AskAsync`]: Core trait defining async LLM interaction
 //! - [`AskFnWrapper`]: Wraps the `awful_aj` library's `ask` function
 //! - [`RetryAsk`]: Decorator that adds retry logic to any `AskAsync` implementation
+//! - [`ObservedAsk`]: Decorator that reports each attempt to an [`AdaptiveConcurrency`]
 //!
 //! # Retry Strategy
 //!
@@ -17,15 +18,108 @@
 //! - Exponential backoff starting at 1 second
 //! - Maximum delay capped at 30 seconds
 //! - Random jitter (0-250ms) added to prevent thundering herd
+//!
+//! # Retry Logging
+//!
+//! During a provider outage every article retries at once, and a warning
+//! per attempt floods the log. By default (`--retry-log summary`) each
+//! failed attempt is logged at debug only, and a [`RetryLog`] turns them
+//! into one warning per [`RETRY_SUMMARY_WINDOW`]: "N articles retrying, M
+//! attempts in last 10s". `--retry-log each` restores one warning per
+//! attempt. An ask that exhausts its retries is always logged as an error.
+//!
+//! # Adaptive Concurrency
+//!
+//! A local model server that copes with 8 parallel articles can fall over
+//! with 150. [`AdaptiveConcurrency`] limits the articles in flight with an
+//! AIMD controller: it starts at the configured level, halves the level
+//! after [`OVERLOAD_STREAK`] consecutive overload errors (HTTP 429/503 or
+//! timeouts) and adds one slot back after [`GROWTH_STREAK`] consecutive
+//! successes, never going above the configured level or below one. Every
+//! attempt, including retries, counts as an event. Level changes are kept
+//! with their time so the run can report how concurrency evolved.
+//!
+//! # Basic Auth Endpoints
+//!
+//! `awful_aj::api::ask` always authenticates with a bearer token. For LLM
+//! gateways behind HTTP basic auth, [`AskFnWrapper`] can be given a
+//! [`BasicAuth`]; requests then go straight to the `/chat/completions`
+//! endpoint with an `Authorization: Basic` header instead. The password is
+//! never written to logs.
+//!
+//! # Template Variables
+//!
+//! The template is loaded once per run, but the prompt can refer to the
+//! edition being built. Before each article is sent, [`render_template_vars`]
+//! replaces these placeholders in the system prompt, the example messages
+//! and the pre/post user content:
+//!
+//! | Placeholder | Value |
+//! |-------------|-------|
+//! | `{{current_date}}` | Edition date, `YYYY-MM-DD` |
+//! | `{{time_of_day}}` | Edition label (`morning`, `afternoon`, `evening`) |
+//! | `{{weekday}}` | Weekday of the edition date (e.g. `Tuesday`) |
+//! | `{{source}}` | Outlet of the article (e.g. `cnn`) |
+//!
+//! Any other `{{...}}` text is left untouched. The bundled `news_parser`
+//! template names the edition's weekday and date ahead of each article, so
+//! relative references such as "yesterday" are anchored to the edition.
+//!
+//! # Summary Length
+//!
+//! With `--summary-words N`, [`render_template_vars`] also appends a line to
+//! the system prompt asking for a summary of about N words (see
+//! [`summary_length_hint`]). This is best-effort: the model is asked, not
+//! held to it, and replies of any length are accepted.
 
 use awful_aj::api::ask;
 use awful_aj::{config::AwfulJadeConfig, template::ChatTemplate};
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use once_cell::sync::{Lazy, OnceCell};
 use rand::{rng, Rng};
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::{json, Value};
 use std::error::Error;
 use std::fmt;
+use std::sync::Mutex;
 use std::time::{Duration as StdDuration, Instant};
+use tokio::sync::{Semaphore, SemaphorePermit};
 use tokio::time::sleep;
-use tracing::{error, info, instrument, warn};
+use tracing::{debug, error, info, instrument, warn};
+
+/// HTTP client for direct chat-completion requests (basic auth path).
+///
+/// LLM responses can take minutes, so the timeout is much longer than the
+/// scrapers' client.
+static LLM_CLIENT: Lazy<Client> = Lazy::new(|| {
+    Client::builder()
+        .timeout(StdDuration::from_secs(600))
+        .build()
+        .expect("failed to build reqwest client")
+});
+
+/// HTTP basic auth credentials for the LLM endpoint.
+///
+/// The `Debug` implementation redacts the password so the credentials can
+/// be traced safely.
+#[derive(Clone)]
+pub struct BasicAuth {
+    /// Basic auth username.
+    pub username: String,
+    /// Basic auth password.
+    pub password: String,
+}
+
+impl fmt::Debug for BasicAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BasicAuth")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
 
 /// Trait for async LLM interaction.
 ///
@@ -47,6 +141,106 @@ pub trait AskAsync {
     async fn ask(&self, text: &str) -> Result<Self::Response, Box<dyn Error>>;
 }
 
+/// How failed LLM attempts are logged (`--retry-log`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum RetryLogMode {
+    /// One warning per [`RETRY_SUMMARY_WINDOW`] summing up the retries;
+    /// each attempt at debug.
+    #[default]
+    Summary,
+    /// One warning per failed attempt.
+    Each,
+}
+
+static RETRY_LOG_MODE: OnceCell<RetryLogMode> = OnceCell::new();
+
+/// Set how failed attempts are logged.
+///
+/// Must be called before the first request; later calls are ignored.
+pub fn init_retry_log(mode: RetryLogMode) {
+    let _ = RETRY_LOG_MODE.set(mode);
+}
+
+fn retry_log_mode() -> RetryLogMode {
+    RETRY_LOG_MODE.get().copied().unwrap_or_default()
+}
+
+/// Period of the retry summaries of [`RetryLog`].
+pub const RETRY_SUMMARY_WINDOW: StdDuration = StdDuration::from_secs(10);
+
+/// Retries summed up over one window of a [`RetryLog`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetrySummary {
+    /// Asks that have failed at least once and are still retrying.
+    pub retrying: usize,
+    /// Failed attempts during the window.
+    pub attempts: usize,
+    /// Length of the window.
+    pub window: StdDuration,
+}
+
+#[derive(Debug)]
+struct RetryWindow {
+    started: Instant,
+    retrying: usize,
+    attempts: usize,
+}
+
+/// Aggregates failed attempts of concurrent asks into periodic summaries.
+#[derive(Debug)]
+pub struct RetryLog {
+    window: StdDuration,
+    state: Mutex<RetryWindow>,
+}
+
+impl RetryLog {
+    /// An aggregator summarizing every `window`.
+    pub fn new(window: StdDuration) -> Self {
+        RetryLog {
+            window,
+            state: Mutex::new(RetryWindow {
+                started: Instant::now(),
+                retrying: 0,
+                attempts: 0,
+            }),
+        }
+    }
+
+    /// Count a failed attempt at `now`; `first` for an ask's first failure.
+    ///
+    /// # Returns
+    ///
+    /// The summary of the window when it has run its course; the next
+    /// window starts at `now`.
+    pub fn failed(&self, first: bool, now: Instant) -> Option<RetrySummary> {
+        let mut state = self.state.lock().unwrap();
+        if first {
+            state.retrying += 1;
+        }
+        state.attempts += 1;
+        if now.saturating_duration_since(state.started) < self.window {
+            return None;
+        }
+        let summary = RetrySummary {
+            retrying: state.retrying,
+            attempts: state.attempts,
+            window: now.saturating_duration_since(state.started),
+        };
+        state.started = now;
+        state.attempts = 0;
+        Some(summary)
+    }
+
+    /// An ask that had failed stopped retrying, successful or not.
+    pub fn finished(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.retrying = state.retrying.saturating_sub(1);
+    }
+}
+
+/// Retry summaries of the run's LLM requests.
+static RETRY_LOG: Lazy<RetryLog> = Lazy::new(|| RetryLog::new(RETRY_SUMMARY_WINDOW));
+
 /// Wrapper that adds exponential backoff retry logic to any [`AskAsync`] implementation.
 ///
 /// This decorator transparently adds retry logic with exponential backoff
@@ -123,6 +317,9 @@ where
             let attempt_t0 = Instant::now();
             match self.inner.ask(text).await {
                 Ok(resp) => {
+                    if attempt > 0 {
+                        RETRY_LOG.finished();
+                    }
                     return Ok(resp);
                 }
                 Err(e) => {
@@ -131,6 +328,9 @@ where
                     let total_dt = total_t0.elapsed();
 
                     if attempt > self.max_retries {
+                        if attempt > 1 {
+                            RETRY_LOG.finished();
+                        }
                         error!(
                             attempt,
                             max = self.max_retries,
@@ -150,15 +350,38 @@ where
                     let jitter_ms: u64 = rng().random_range(0..=250);
                     let delay = delay + StdDuration::from_millis(jitter_ms);
 
-                    warn!(
-                        attempt,
-                        max = self.max_retries,
-                        elapsed_ms_attempt = attempt_dt.as_millis() as u128,
-                        elapsed_ms_total = total_dt.as_millis() as u128,
-                        ?delay,
-                        error = %e,
-                        "ask() attempt failed; backing off"
-                    );
+                    let summary = RETRY_LOG.failed(attempt == 1, Instant::now());
+                    match retry_log_mode() {
+                        RetryLogMode::Each => warn!(
+                            attempt,
+                            max = self.max_retries,
+                            elapsed_ms_attempt = attempt_dt.as_millis() as u128,
+                            elapsed_ms_total = total_dt.as_millis() as u128,
+                            ?delay,
+                            error = %e,
+                            "ask() attempt failed; backing off"
+                        ),
+                        RetryLogMode::Summary => {
+                            debug!(
+                                attempt,
+                                max = self.max_retries,
+                                elapsed_ms_attempt = attempt_dt.as_millis(),
+                                ?delay,
+                                error = %e,
+                                "ask() attempt failed; backing off"
+                            );
+                            if let Some(summary) = summary {
+                                warn!(
+                                    retrying = summary.retrying,
+                                    attempts = summary.attempts,
+                                    "{} articles retrying, {} attempts in last {}s",
+                                    summary.retrying,
+                                    summary.attempts,
+                                    summary.window.as_secs()
+                                );
+                            }
+                        }
+                    }
                     sleep(delay).await;
                 }
             }
@@ -181,6 +404,9 @@ pub struct AskFnWrapper<'a> {
     pub config: &'a AwfulJadeConfig,
     /// Reference to the chat template defining the conversation structure.
     pub template: &'a ChatTemplate,
+    /// Basic auth credentials; when set, requests bypass `awful_aj` and carry
+    /// an `Authorization: Basic` header.
+    pub auth: Option<&'a BasicAuth>,
 }
 
 impl<'a> AskAsync for AskFnWrapper<'a> {
@@ -189,17 +415,376 @@ impl<'a> AskAsync for AskFnWrapper<'a> {
     #[instrument(level = "info", skip_all)]
     async fn ask(&self, text: &str) -> Result<Self::Response, Box<dyn Error>> {
         let t0 = Instant::now();
-        let res = ask(self.config, text.to_string(), self.template, None, None, false).await;
+        let res = match self.auth {
+            Some(auth) => ask_with_basic_auth(self.config, text, self.template, auth).await,
+            None => ask(self.config, text.to_string(), self.template, None, None, false).await,
+        };
         let dt = t0.elapsed();
 
         match &res {
             Ok(_) => {}
-            Err(e) => warn!(elapsed_ms = dt.as_millis() as u128, error = %e, "API call failed"),
+            Err(e) if retry_log_mode() == RetryLogMode::Each => {
+                warn!(elapsed_ms = dt.as_millis() as u128, error = %e, "API call failed")
+            }
+            Err(e) => debug!(elapsed_ms = dt.as_millis(), error = %e, "API call failed"),
         }
         res
     }
 }
 
+/// Consecutive overload errors after which the concurrency level is halved.
+pub const OVERLOAD_STREAK: usize = 2;
+
+/// Consecutive successes after which the concurrency level grows by one.
+pub const GROWTH_STREAK: usize = 10;
+
+/// How a single LLM request attempt ended, as seen by [`AdaptiveConcurrency`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestOutcome {
+    /// The request succeeded.
+    Success,
+    /// The backend is under stress: rate limited, unavailable or timed out.
+    Overloaded,
+    /// Any other error; breaks a success streak but doesn't shrink the level.
+    Failed,
+}
+
+impl RequestOutcome {
+    /// Classify a request error.
+    pub fn of_error(e: &(dyn Error + 'static)) -> Self {
+        if let Some(e) = e.downcast_ref::<reqwest::Error>() {
+            let overloaded_status = e.status().is_some_and(|s| s.as_u16() == 429 || s.as_u16() == 503);
+            if overloaded_status || e.is_timeout() {
+                return RequestOutcome::Overloaded;
+            }
+        }
+        // `awful_aj` errors only carry the message
+        let message = e.to_string().to_lowercase();
+        let stressed = ["429", "too many requests", "503", "service unavailable", "timed out", "timeout"];
+        if stressed.iter().any(|m| message.contains(m)) {
+            RequestOutcome::Overloaded
+        } else {
+            RequestOutcome::Failed
+        }
+    }
+}
+
+/// A change of the concurrency level during the run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LevelChange {
+    /// Milliseconds since the controller was created.
+    pub elapsed_ms: u64,
+    /// The new level.
+    pub level: usize,
+}
+
+#[derive(Debug)]
+struct AimdState {
+    level: usize,
+    overloads: usize,
+    successes: usize,
+    /// Permits to retire as soon as they are released, because the level
+    /// dropped while they were in use.
+    debt: usize,
+    history: Vec<LevelChange>,
+}
+
+/// AIMD limit on LLM requests in flight (see the module docs).
+#[derive(Debug)]
+pub struct AdaptiveConcurrency {
+    max_level: usize,
+    semaphore: Semaphore,
+    state: Mutex<AimdState>,
+    started: Instant,
+}
+
+/// A slot of an [`AdaptiveConcurrency`], released on drop.
+#[derive(Debug)]
+pub struct AdaptivePermit<'a> {
+    permit: Option<SemaphorePermit<'a>>,
+    controller: &'a AdaptiveConcurrency,
+}
+
+impl Drop for AdaptivePermit<'_> {
+    fn drop(&mut self) {
+        let mut state = self.controller.state.lock().unwrap();
+        if state.debt > 0
+            && let Some(permit) = self.permit.take()
+        {
+            state.debt -= 1;
+            permit.forget();
+        }
+    }
+}
+
+impl Default for AdaptiveConcurrency {
+    fn default() -> Self {
+        AdaptiveConcurrency::new(crate::concurrency::DEFAULT_MODEL_CONCURRENCY)
+    }
+}
+
+impl AdaptiveConcurrency {
+    /// Start at (and never exceed) `max_level` requests in flight.
+    pub fn new(max_level: usize) -> Self {
+        let max_level = max_level.max(1);
+        AdaptiveConcurrency {
+            max_level,
+            semaphore: Semaphore::new(max_level),
+            state: Mutex::new(AimdState {
+                level: max_level,
+                overloads: 0,
+                successes: 0,
+                debt: 0,
+                history: vec![LevelChange { elapsed_ms: 0, level: max_level }],
+            }),
+            started: Instant::now(),
+        }
+    }
+
+    /// Wait for a slot at the current level.
+    pub async fn acquire(&self) -> AdaptivePermit<'_> {
+        let permit = self.semaphore.acquire().await.expect("adaptive semaphore is never closed");
+        AdaptivePermit {
+            permit: Some(permit),
+            controller: self,
+        }
+    }
+
+    /// The current concurrency level.
+    pub fn level(&self) -> usize {
+        self.state.lock().unwrap().level
+    }
+
+    /// Every level the run went through, starting with the initial one.
+    pub fn history(&self) -> Vec<LevelChange> {
+        self.state.lock().unwrap().history.clone()
+    }
+
+    /// Feed the outcome of one request attempt to the controller.
+    pub fn record(&self, outcome: RequestOutcome) {
+        let mut state = self.state.lock().unwrap();
+        let old_level = state.level;
+        match outcome {
+            RequestOutcome::Success => {
+                state.overloads = 0;
+                state.successes += 1;
+                if state.successes >= GROWTH_STREAK && state.level < self.max_level {
+                    state.successes = 0;
+                    state.level += 1;
+                    if state.debt > 0 {
+                        state.debt -= 1;
+                    } else {
+                        self.semaphore.add_permits(1);
+                    }
+                }
+            }
+            RequestOutcome::Overloaded => {
+                state.successes = 0;
+                state.overloads += 1;
+                if state.overloads >= OVERLOAD_STREAK {
+                    state.overloads = 0;
+                    let level = (state.level / 2).max(1);
+                    let shrink = state.level - level;
+                    // Permits in use are retired when released
+                    state.debt += shrink - self.semaphore.forget_permits(shrink);
+                    state.level = level;
+                }
+            }
+            RequestOutcome::Failed => state.successes = 0,
+        }
+        if state.level != old_level {
+            let change = LevelChange {
+                elapsed_ms: self.started.elapsed().as_millis() as u64,
+                level: state.level,
+            };
+            if state.level < old_level {
+                warn!(from = old_level, to = state.level, "LLM backend under stress; reducing concurrency");
+            } else {
+                debug!(from = old_level, to = state.level, "Growing concurrency after sustained successes");
+            }
+            state.history.push(change);
+        }
+    }
+}
+
+/// Decorator that reports the outcome of every attempt to an
+/// [`AdaptiveConcurrency`] controller.
+#[derive(Debug)]
+pub struct ObservedAsk<'a, T> {
+    /// The underlying LLM client.
+    pub inner: T,
+    /// Controller receiving the outcomes.
+    pub controller: &'a AdaptiveConcurrency,
+}
+
+impl<T: AskAsync> AskAsync for ObservedAsk<'_, T> {
+    type Response = T::Response;
+
+    async fn ask(&self, text: &str) -> Result<Self::Response, Box<dyn Error>> {
+        let res = self.inner.ask(text).await;
+        self.controller.record(match &res {
+            Ok(_) => RequestOutcome::Success,
+            Err(e) => RequestOutcome::of_error(e.as_ref()),
+        });
+        res
+    }
+}
+
+/// Values for the placeholders in a template (see the module docs).
+#[derive(Debug, Clone, Default)]
+pub struct TemplateContext {
+    /// Edition date, `YYYY-MM-DD`.
+    pub current_date: String,
+    /// Edition label (`morning`, `afternoon`, `evening`).
+    pub time_of_day: String,
+    /// Outlet of the article being processed (e.g. `cnn`).
+    pub source: String,
+    /// Approximate summary length to ask for, in words.
+    pub summary_words: Option<usize>,
+}
+
+impl TemplateContext {
+    /// Weekday of [`current_date`](Self::current_date), if it is a date.
+    pub fn weekday(&self) -> Option<String> {
+        NaiveDate::parse_from_str(&self.current_date, "%Y-%m-%d")
+            .ok()
+            .map(|date| date.format("%A").to_string())
+    }
+
+    fn substitute(&self, text: &str) -> String {
+        if !text.contains("{{") {
+            return text.to_string();
+        }
+        text.replace("{{current_date}}", &self.current_date)
+            .replace("{{time_of_day}}", &self.time_of_day)
+            .replace("{{weekday}}", &self.weekday().unwrap_or_default())
+            .replace("{{source}}", &self.source)
+    }
+
+    fn substitute_value(&self, value: &mut Value) {
+        match value {
+            Value::String(s) => *s = self.substitute(s),
+            Value::Array(items) => items.iter_mut().for_each(|v| self.substitute_value(v)),
+            Value::Object(map) => map.values_mut().for_each(|v| self.substitute_value(v)),
+            _ => {}
+        }
+    }
+}
+
+/// The system prompt line asking for a summary of about `words` words.
+pub fn summary_length_hint(words: usize) -> String {
+    format!("Keep summaryOfNewsArticle to approximately {} words.", words)
+}
+
+/// Clone `template` with its placeholders filled in from `ctx`, plus the
+/// summary length hint if one is set.
+///
+/// Example messages are rewritten through their JSON form; if that fails
+/// they are kept as-is.
+pub fn render_template_vars(template: &ChatTemplate, ctx: &TemplateContext) -> ChatTemplate {
+    let mut rendered = template.clone();
+    rendered.system_prompt = ctx.substitute(&template.system_prompt);
+    if let Some(words) = ctx.summary_words {
+        rendered.system_prompt = format!("{}\n\n{}", rendered.system_prompt.trim_end(), summary_length_hint(words));
+    }
+    rendered.pre_user_message_content = template.pre_user_message_content.as_deref().map(|s| ctx.substitute(s));
+    rendered.post_user_message_content = template.post_user_message_content.as_deref().map(|s| ctx.substitute(s));
+    if let Ok(mut messages) = serde_json::to_value(&template.messages) {
+        ctx.substitute_value(&mut messages);
+        match serde_json::from_value(messages) {
+            Ok(messages) => rendered.messages = messages,
+            Err(e) => warn!(error = %e, "Could not substitute template variables in example messages"),
+        }
+    }
+    rendered
+}
+
+/// Build the chat messages for a single article from a template.
+///
+/// Mirrors what `awful_aj` sends: the system prompt, the template's example
+/// messages, then the article wrapped in the template's pre/post content.
+fn build_messages(template: &ChatTemplate, text: &str) -> Result<Value, Box<dyn Error>> {
+    let mut messages = vec![json!({ "role": "system", "content": template.system_prompt })];
+    if let Value::Array(examples) = serde_json::to_value(&template.messages)? {
+        messages.extend(examples);
+    }
+    let user = format!(
+        "{}{}{}",
+        template.pre_user_message_content.as_deref().unwrap_or(""),
+        text,
+        template.post_user_message_content.as_deref().unwrap_or("")
+    );
+    messages.push(json!({ "role": "user", "content": user }));
+    Ok(Value::Array(messages))
+}
+
+/// Build a chat completion request body with the sampling parameters of
+/// `config`, as `ask` would send them.
+///
+/// # Arguments
+///
+/// * `config` - LLM configuration (model, temperature, stop words)
+/// * `messages` - Chat messages as built by [`build_messages`]
+/// * `json_schema` - Optional JSON schema for structured output
+fn chat_request_body(config: &AwfulJadeConfig, messages: Value, json_schema: Option<Value>) -> Value {
+    let mut body = json!({ "model": config.model, "messages": messages });
+    if let Some(temperature) = config.temperature {
+        body["temperature"] = json!(temperature);
+    }
+    if !config.stop_words.is_empty() {
+        body["stop"] = json!(config.stop_words);
+    }
+    if let Some(schema) = json_schema.filter(|s| !s.is_null()) {
+        body["response_format"] = json!({ "type": "json_schema", "json_schema": schema });
+    }
+    body
+}
+
+/// Send a chat completion request with basic auth and return the reply text.
+///
+/// # Arguments
+///
+/// * `client` - The HTTP client to send the request with
+/// * `api_base` - Base URL of the OpenAI-compatible API (e.g. `https://gw/v1`)
+/// * `body` - Request body as built by [`chat_request_body`]
+/// * `auth` - Basic auth credentials
+async fn chat_completion_basic_auth(
+    client: &Client,
+    api_base: &str,
+    body: &Value,
+    auth: &BasicAuth,
+) -> Result<String, Box<dyn Error>> {
+    let url = format!("{}/chat/completions", api_base.trim_end_matches('/'));
+
+    debug!(%url, username = %auth.username, "Sending chat completion with basic auth");
+    let res = client
+        .post(&url)
+        .basic_auth(&auth.username, Some(&auth.password))
+        .json(body)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    let reply: Value = res.json().await?;
+    reply["choices"][0]["message"]["content"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| "chat completion response had no message content".into())
+}
+
+/// Ask the LLM through a basic-auth protected endpoint.
+async fn ask_with_basic_auth(
+    config: &AwfulJadeConfig,
+    text: &str,
+    template: &ChatTemplate,
+    auth: &BasicAuth,
+) -> Result<String, Box<dyn Error>> {
+    let messages = build_messages(template, text)?;
+    let json_schema = serde_json::to_value(&template.response_format)?;
+    let body = chat_request_body(config, messages, Some(json_schema));
+    chat_completion_basic_auth(&LLM_CLIENT, &config.api_base, &body, auth).await
+}
+
 /// High-level function to call LLM with exponential backoff retry logic.
 ///
 /// This is the primary entry point for sending article content to the LLM.
@@ -211,6 +796,8 @@ impl<'a> AskAsync for AskFnWrapper<'a> {
 /// * `config` - LLM configuration (API endpoint, model, etc.)
 /// * `article` - The article text to process
 /// * `template` - The chat template defining the conversation structure
+/// * `auth` - Optional basic auth credentials for the LLM endpoint
+/// * `adaptive` - Controller that is told the outcome of every attempt
 ///
 /// # Returns
 ///
@@ -225,11 +812,16 @@ impl<'a> AskAsync for AskFnWrapper<'a> {
 #[instrument(level = "info", skip_all)]
 pub async fn ask_with_backoff(
     config: &AwfulJadeConfig,
-    article: &String,
+    article: &str,
     template: &ChatTemplate,
+    auth: Option<&BasicAuth>,
+    adaptive: &AdaptiveConcurrency,
 ) -> Result<String, Box<dyn Error>> {
     let t0 = Instant::now();
-    let client = AskFnWrapper { config, template };
+    let client = ObservedAsk {
+        inner: AskFnWrapper { config, template, auth },
+        controller: adaptive,
+    };
     let api = RetryAsk::new(client, 5, StdDuration::from_secs(1));
     let res = api.ask(article).await;
     let dt = t0.elapsed();
@@ -245,3 +837,315 @@ pub async fn ask_with_backoff(
     }
     res
 }
+
+/// Upper bound on a single [`ask_once`] attempt.
+pub const ASK_ONCE_TIMEOUT: StdDuration = StdDuration::from_secs(180);
+
+/// Ask the LLM exactly once, without the retry wrapper.
+///
+/// Used for secondary asks (the re-ask of a truncated response), so an
+/// article the backend keeps failing on costs one extra attempt instead of
+/// another full round of backoff. The attempt is bounded by
+/// [`ASK_ONCE_TIMEOUT`].
+#[instrument(level = "info", skip_all)]
+pub async fn ask_once(
+    config: &AwfulJadeConfig,
+    text: &str,
+    template: &ChatTemplate,
+    auth: Option<&BasicAuth>,
+    adaptive: &AdaptiveConcurrency,
+) -> Result<String, Box<dyn Error>> {
+    let client = ObservedAsk {
+        inner: AskFnWrapper { config, template, auth },
+        controller: adaptive,
+    };
+    match tokio::time::timeout(ASK_ONCE_TIMEOUT, client.ask(text)).await {
+        Ok(res) => res,
+        Err(_) => {
+            adaptive.record(RequestOutcome::Overloaded);
+            Err(format!("LLM request timed out after {}s", ASK_ONCE_TIMEOUT.as_secs()).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn auth() -> BasicAuth {
+        BasicAuth {
+            username: "user".to_string(),
+            password: "hunter2".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_basic_auth_header_is_attached() {
+        let server = MockServer::start().await;
+        // base64("user:hunter2")
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(header("authorization", "Basic dXNlcjpodW50ZXIy"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{ "message": { "role": "assistant", "content": "{\"ok\":true}" } }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let body = json!({ "model": "test-model", "messages": [{ "role": "user", "content": "hi" }] });
+        let reply = chat_completion_basic_auth(&Client::new(), &format!("{}/v1/", server.uri()), &body, &auth())
+            .await
+            .unwrap();
+
+        assert_eq!(reply, "{\"ok\":true}");
+    }
+
+    #[tokio::test]
+    async fn test_basic_auth_requests_keep_the_configured_sampling() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_partial_json(json!({ "temperature": 0.25, "stop": ["<|im_end|>"] })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{ "message": { "role": "assistant", "content": "{}" } }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut config: AwfulJadeConfig = serde_yaml::from_str(include_str!("../config.yaml")).unwrap();
+        config.api_base = server.uri();
+        config.temperature = Some(0.25);
+        config.stop_words = vec!["<|im_end|>".to_string()];
+        let template: ChatTemplate = serde_yaml::from_str(include_str!("../template/news_parser.yaml")).unwrap();
+        assert_eq!(ask_with_basic_auth(&config, "hi", &template, &auth()).await.unwrap(), "{}");
+
+        // Unset parameters are left to the server's defaults
+        config.temperature = None;
+        config.stop_words.clear();
+        let body = chat_request_body(&config, json!([]), None);
+        assert!(body.get("temperature").is_none() && body.get("stop").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_basic_auth_rejection_is_an_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&server)
+            .await;
+
+        let body = json!({ "model": "m", "messages": [] });
+        let res = chat_completion_basic_auth(&Client::new(), &server.uri(), &body, &auth()).await;
+        assert!(res.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ask_once_makes_a_single_attempt() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+        let mut config: AwfulJadeConfig = serde_yaml::from_str(include_str!("../config.yaml")).unwrap();
+        config.api_base = server.uri();
+        let template: ChatTemplate = serde_yaml::from_str(include_str!("../template/news_parser.yaml")).unwrap();
+        let adaptive = AdaptiveConcurrency::new(4);
+
+        let res = ask_once(&config, "Body", &template, Some(&auth()), &adaptive).await;
+        assert!(res.is_err());
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_summary_length_hint_reaches_the_model() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{ "message": { "role": "assistant", "content": "{}" } }]
+            })))
+            .mount(&server)
+            .await;
+        let mut config: AwfulJadeConfig = serde_yaml::from_str(include_str!("../config.yaml")).unwrap();
+        config.api_base = server.uri();
+        let ctx = TemplateContext {
+            summary_words: Some(50),
+            ..context()
+        };
+        let hinted = render_template_vars(&template("Summarize.", None), &ctx);
+
+        ask_once(&config, "Body", &hinted, Some(&auth()), &AdaptiveConcurrency::new(1)).await.unwrap();
+        let requests = server.received_requests().await.unwrap();
+        let body: Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(
+            body["messages"][0]["content"],
+            "Summarize.\n\nKeep summaryOfNewsArticle to approximately 50 words."
+        );
+
+        // No hint unless asked for
+        let plain = render_template_vars(&template("Summarize.", None), &context());
+        assert_eq!(plain.system_prompt, "Summarize.");
+    }
+
+    #[test]
+    fn test_basic_auth_debug_redacts_password() {
+        let rendered = format!("{:?}", auth());
+        assert!(rendered.contains("user"));
+        assert!(!rendered.contains("hunter2"));
+    }
+
+    fn template(system_prompt: &str, post: Option<&str>) -> ChatTemplate {
+        let mut template: ChatTemplate = serde_yaml::from_str(include_str!("../template/news_parser.yaml")).unwrap();
+        template.system_prompt = system_prompt.to_string();
+        template.post_user_message_content = post.map(str::to_string);
+        template
+    }
+
+    fn context() -> TemplateContext {
+        TemplateContext {
+            current_date: "2025-05-06".to_string(),
+            time_of_day: "evening".to_string(),
+            source: "npr".to_string(),
+            summary_words: None,
+        }
+    }
+
+    #[test]
+    fn test_render_template_vars_replaces_placeholders() {
+        let original = template(
+            "Today is {{current_date}}, {{time_of_day}} edition.",
+            Some("Article from {{source}}, dated relative to {{current_date}}."),
+        );
+        let rendered = render_template_vars(&original, &context());
+        assert_eq!(rendered.system_prompt, "Today is 2025-05-06, evening edition.");
+        assert_eq!(
+            rendered.post_user_message_content.as_deref(),
+            Some("Article from npr, dated relative to 2025-05-06.")
+        );
+        // The shared template is not modified
+        assert!(original.system_prompt.contains("{{current_date}}"));
+    }
+
+    #[test]
+    fn test_weekday_placeholder() {
+        let rendered = render_template_vars(&template("It is {{weekday}}, {{current_date}}.", None), &context());
+        assert_eq!(rendered.system_prompt, "It is Tuesday, 2025-05-06.");
+
+        let undated = TemplateContext::default();
+        assert_eq!(render_template_vars(&template("It is {{weekday}}.", None), &undated).system_prompt, "It is .");
+    }
+
+    #[test]
+    fn test_render_template_vars_leaves_unknown_placeholders() {
+        let original = template("Use {{locale}} and {{ current_date }}; no vars here.", None);
+        let rendered = render_template_vars(&original, &context());
+        assert_eq!(rendered.system_prompt, "Use {{locale}} and {{ current_date }}; no vars here.");
+        assert_eq!(rendered.post_user_message_content, None);
+    }
+
+    fn levels(controller: &AdaptiveConcurrency) -> Vec<usize> {
+        controller.history().iter().map(|c| c.level).collect()
+    }
+
+    #[test]
+    fn test_retry_log_sums_up_each_window() {
+        let log = RetryLog::new(StdDuration::from_secs(10));
+        let t0 = log.state.lock().unwrap().started;
+        let at = |secs| t0 + StdDuration::from_secs(secs);
+
+        // Three articles fail, one of them twice, within the window
+        assert_eq!(log.failed(true, at(1)), None);
+        assert_eq!(log.failed(true, at(2)), None);
+        assert_eq!(log.failed(false, at(3)), None);
+        assert_eq!(log.failed(true, at(4)), None);
+        let summary = log.failed(false, at(10)).unwrap();
+        assert_eq!((summary.retrying, summary.attempts), (3, 5));
+        assert_eq!(summary.window, StdDuration::from_secs(10));
+
+        // The next window counts attempts afresh; finished asks stop counting
+        log.finished();
+        log.finished();
+        assert_eq!(log.failed(false, at(15)), None);
+        let summary = log.failed(true, at(21)).unwrap();
+        assert_eq!((summary.retrying, summary.attempts), (2, 2));
+    }
+
+    #[test]
+    fn test_aimd_levels_follow_event_sequence() {
+        use RequestOutcome::*;
+        let controller = AdaptiveConcurrency::new(8);
+        let feed = |events: &[RequestOutcome]| events.iter().for_each(|e| controller.record(*e));
+
+        feed(&[Overloaded, Overloaded]);
+        assert_eq!(controller.level(), 4);
+        // A success in between breaks the overload streak
+        feed(&[Overloaded, Success, Overloaded]);
+        assert_eq!(controller.level(), 4);
+        feed(&[Overloaded, Overloaded, Overloaded, Overloaded, Overloaded]);
+        assert_eq!(controller.level(), 1);
+        // Never below one
+        feed(&[Overloaded, Overloaded]);
+        assert_eq!(controller.level(), 1);
+
+        feed(&[Success; GROWTH_STREAK]);
+        assert_eq!(controller.level(), 2);
+        // Other failures reset the success streak
+        feed(&[Success; GROWTH_STREAK - 1]);
+        feed(&[Failed]);
+        feed(&[Success; GROWTH_STREAK - 1]);
+        assert_eq!(controller.level(), 2);
+        feed(&[Success]);
+        assert_eq!(controller.level(), 3);
+
+        assert_eq!(levels(&controller), vec![8, 4, 2, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_growth_stops_at_configured_level() {
+        let controller = AdaptiveConcurrency::new(2);
+        for _ in 0..GROWTH_STREAK * 3 {
+            controller.record(RequestOutcome::Success);
+        }
+        assert_eq!(controller.level(), 2);
+        assert_eq!(controller.semaphore.available_permits(), 2);
+        assert_eq!(levels(&controller), vec![2]);
+    }
+
+    #[tokio::test]
+    async fn test_permits_in_use_are_retired_when_level_drops() {
+        let controller = AdaptiveConcurrency::new(4);
+        let mut permits = Vec::new();
+        for _ in 0..4 {
+            permits.push(controller.acquire().await);
+        }
+        controller.record(RequestOutcome::Overloaded);
+        controller.record(RequestOutcome::Overloaded);
+        assert_eq!(controller.level(), 2);
+
+        // The first two released permits pay off the shrink
+        permits.pop();
+        permits.pop();
+        assert_eq!(controller.semaphore.available_permits(), 0);
+        permits.clear();
+        assert_eq!(controller.semaphore.available_permits(), 2);
+
+        for _ in 0..GROWTH_STREAK {
+            controller.record(RequestOutcome::Success);
+        }
+        assert_eq!(controller.semaphore.available_permits(), 3);
+    }
+
+    #[test]
+    fn test_request_outcome_classification() {
+        let outcome = |msg: &str| {
+            let e: Box<dyn Error> = msg.into();
+            RequestOutcome::of_error(e.as_ref())
+        };
+        assert_eq!(outcome("HTTP status client error (429 Too Many Requests)"), RequestOutcome::Overloaded);
+        assert_eq!(outcome("operation timed out"), RequestOutcome::Overloaded);
+        assert_eq!(outcome("missing field `choices`"), RequestOutcome::Failed);
+    }
+}
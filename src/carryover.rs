@@ -0,0 +1,211 @@
+//! Carry-over of articles that failed processing in the previous run.
+//!
+//! Without this, an article the LLM failed on in the evening run is simply
+//! gone: the next run sees it as already indexed, or the outlet has rotated
+//! it off the homepage. With `--carry-over-failures`, every run writes the
+//! articles it failed to process (with their scraped content) to
+//! `failures.json` next to the state file, and the next run loads them back
+//! into its processing queue. Articles processed this way are tagged
+//! `carriedOver` and rendered under "In case you missed it".
+//!
+//! Failures are only carried for [`MAX_CARRY_OVER_AGE_HOURS`] after they
+//! first failed, so an article the model keeps choking on is eventually
+//! dropped. A missing or corrupt file is treated as an empty list.
+
+use crate::models::NewsArticle;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tracing::{debug, info, warn};
+
+/// How long a failed article stays eligible for carry-over.
+pub const MAX_CARRY_OVER_AGE_HOURS: i64 = 24;
+
+/// File name of the failure list, next to the state file.
+const FAILURES_FILE: &str = "failures.json";
+
+/// An article that failed processing.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FailedArticle {
+    /// When the article first failed; kept across repeated failures.
+    pub failed_at: DateTime<Utc>,
+    /// The scraped article as it was queued for processing.
+    pub article: NewsArticle,
+}
+
+/// The failures of one run.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct FailureList {
+    /// Articles that failed, in queue order.
+    pub articles: Vec<FailedArticle>,
+}
+
+/// Path of the failure list for a given `--state-file`.
+pub fn failures_path(state_file: &str) -> PathBuf {
+    Path::new(state_file).with_file_name(FAILURES_FILE)
+}
+
+impl FailureList {
+    /// Load the failure list, treating a missing or corrupt file as empty.
+    pub async fn load(path: &Path) -> Self {
+        match fs::read_to_string(path).await {
+            Ok(raw) => match serde_json::from_str::<FailureList>(&raw) {
+                Ok(list) => list,
+                Err(e) => {
+                    warn!(path = %path.display(), error = %e, "Failure list is corrupt; nothing to carry over");
+                    FailureList::default()
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => FailureList::default(),
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "Failed to read failure list; nothing to carry over");
+                FailureList::default()
+            }
+        }
+    }
+
+    /// Write the failure list to disk as JSON.
+    pub async fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json).await?;
+        Ok(())
+    }
+
+    /// Build the list for this run's `failed` articles.
+    ///
+    /// Articles that were themselves carried over keep their original
+    /// failure time from `previous`, so the age limit counts from the first
+    /// failure.
+    pub fn from_failed(failed: &[&NewsArticle], previous: &FailureList, now: DateTime<Utc>) -> Self {
+        let articles = failed
+            .iter()
+            .map(|article| FailedArticle {
+                failed_at: previous
+                    .articles
+                    .iter()
+                    .find(|f| f.article.source == article.source)
+                    .map_or(now, |f| f.failed_at),
+                article: (*article).clone(),
+            })
+            .collect();
+        FailureList { articles }
+    }
+
+    /// Failed articles still within the carry-over window at `now`.
+    pub fn recent(&self, now: DateTime<Utc>) -> Vec<NewsArticle> {
+        let cutoff = now - Duration::hours(MAX_CARRY_OVER_AGE_HOURS);
+        self.articles
+            .iter()
+            .filter(|f| {
+                let fresh = f.failed_at >= cutoff;
+                if !fresh {
+                    debug!(source = %f.article.source, failed_at = %f.failed_at, "Failure too old to carry over");
+                }
+                fresh
+            })
+            .map(|f| f.article.clone())
+            .collect()
+    }
+}
+
+/// Append carried-over articles to the processing queue.
+///
+/// Articles whose URL is already queued (re-indexed this run) are not added
+/// twice. Returns the URLs that were added, for tagging after processing.
+pub fn merge_into_queue(queue: &mut Vec<NewsArticle>, carried: Vec<NewsArticle>) -> HashSet<String> {
+    let queued: HashSet<String> = queue.iter().map(|a| a.source.clone()).collect();
+    let mut added = HashSet::new();
+    for article in carried {
+        if !queued.contains(&article.source) && added.insert(article.source.clone()) {
+            queue.push(article);
+        }
+    }
+    if !added.is_empty() {
+        info!(count = added.len(), "Carried over failed articles from the previous run");
+    }
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn article(source: &str) -> NewsArticle {
+        NewsArticle {
+            source: source.to_string(),
+            content: format!("Content of {}", source).into(),
+            published_at: None,
+            authors: vec![],
+            fetched_at: None,
+            fetched_from: None,
+        }
+    }
+
+    fn at(rfc3339: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap().with_timezone(&Utc)
+    }
+
+    #[tokio::test]
+    async fn test_prior_failures_are_loaded_within_age_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = failures_path(dir.path().join("state.json").to_str().unwrap());
+        assert_eq!(path, dir.path().join("failures.json"));
+
+        // Simulate the previous evening run's failure file
+        let evening = at("2025-05-05T21:00:00Z");
+        let old = at("2025-05-04T20:00:00Z");
+        let list = FailureList {
+            articles: vec![
+                FailedArticle { failed_at: evening, article: article("https://text.npr.org/1") },
+                FailedArticle { failed_at: old, article: article("https://text.npr.org/old") },
+            ],
+        };
+        list.save(&path).await.unwrap();
+
+        let loaded = FailureList::load(&path).await;
+        let carried = loaded.recent(at("2025-05-06T07:00:00Z"));
+        assert_eq!(carried.len(), 1);
+        assert_eq!(carried[0].source, "https://text.npr.org/1");
+        assert_eq!(&*carried[0].content, "Content of https://text.npr.org/1");
+    }
+
+    #[test]
+    fn test_merge_skips_urls_already_queued() {
+        let mut queue = vec![article("https://lite.cnn.com/a")];
+        let added = merge_into_queue(
+            &mut queue,
+            vec![article("https://lite.cnn.com/a"), article("https://text.npr.org/1")],
+        );
+        assert_eq!(queue.len(), 2);
+        assert_eq!(added, HashSet::from(["https://text.npr.org/1".to_string()]));
+    }
+
+    #[test]
+    fn test_repeated_failure_keeps_first_failure_time() {
+        let first = at("2025-05-05T21:00:00Z");
+        let previous = FailureList {
+            articles: vec![FailedArticle { failed_at: first, article: article("https://text.npr.org/1") }],
+        };
+        let again = article("https://text.npr.org/1");
+        let fresh = article("https://text.npr.org/2");
+        let now = at("2025-05-06T07:00:00Z");
+
+        let list = FailureList::from_failed(&[&again, &fresh], &previous, now);
+        assert_eq!(list.articles[0].failed_at, first);
+        assert_eq!(list.articles[1].failed_at, now);
+        // Dropped once a day has passed since the first failure
+        assert_eq!(list.recent(at("2025-05-06T22:00:00Z")).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_missing_or_corrupt_file_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("failures.json");
+        assert!(FailureList::load(&path).await.articles.is_empty());
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(FailureList::load(&path).await.articles.is_empty());
+    }
+}
@@ -3,7 +3,40 @@
 //! This module defines the CLI arguments and options using the `clap` crate.
 //! All arguments can be provided via command-line flags or environment variables.
 
-use clap::Parser;
+use crate::api::{BasicAuth, RetryLogMode};
+use crate::concurrency::{parse_model_limit, DEFAULT_MODEL_CONCURRENCY};
+use crate::diff::{parse_correction_threshold, DEFAULT_CORRECTION_THRESHOLD};
+use crate::doctor::DEFAULT_MIN_URLS;
+use crate::filters::{EditionFilter, KeywordFilter};
+use crate::models::{
+    ArticleLimits, DEFAULT_MAX_TAGS, DEFAULT_MAX_TAG_WORDS, DEFAULT_MAX_TAKEAWAYS, DEFAULT_MAX_TAKEAWAY_CHARS,
+    DEFAULT_MIN_TAKEAWAY_CHARS,
+};
+use crate::ordering::ArticleOrder;
+use crate::outputs::book::DEFAULT_MDBOOK_TIMEOUT_SECS;
+use crate::outputs::indexes::DEFAULT_TAKEAWAYS_RETENTION_DAYS;
+use crate::outputs::json::ContentPolicy;
+use crate::outputs::labels::{parse_source_label, SourceLabels};
+use crate::outputs::markdown::{AnchorMode, EntityStyle, MarkdownOptions, MarkdownTheme, DEFAULT_EMPTY_EDITION_MESSAGE};
+use crate::outputs::plaintext::DEFAULT_TEXT_WIDTH;
+use crate::pipeline::LlmInputHeader;
+use crate::publish::DEFAULT_EDITION_EVENT_KEY;
+use crate::salience::{parse_salience_weight, SalienceWeights};
+use crate::sampling::parse_source_weight;
+use crate::schema::ArticleField;
+use crate::scrapers::http::{
+    DEFAULT_MAX_BODY_BYTES, DEFAULT_POOL_IDLE_TIMEOUT_SECS, DEFAULT_POOL_MAX_IDLE_PER_HOST, DEFAULT_RESPONSE_CACHE_BYTES,
+    DEFAULT_SCRAPE_TIMEOUT_SECS,
+};
+use crate::scrapers::parse_source_tag;
+use crate::top_stories::DEFAULT_TOP_N;
+use crate::utils::{DuplicatePreference, EditionScheme};
+use crate::weekly::DEFAULT_STORIES_PER_CATEGORY;
+use awful_aj::config_dir;
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand};
+use std::error::Error;
+use std::path::PathBuf;
 
 /// Command-line arguments for the Awful Text News application.
 ///
@@ -22,6 +55,13 @@ use clap::Parser;
 ///
 /// # With message bus enabled
 /// awful_text_news -j ./json -m ./markdown --amqp-url amqp://localhost:5672
+///
+/// # Index and fetch on one machine, summarize on another
+/// awful_text_news -j ./json -m ./markdown fetch ./raw
+/// awful_text_news -j ./json -m ./markdown process ./raw/2025-05-06/morning
+///
+/// # Week in review of the seven days ending 2025-05-11
+/// awful_text_news -j ./json -m ./markdown weekly --week-ending 2025-05-11
 /// ```
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
@@ -30,14 +70,418 @@ pub struct Cli {
     #[arg(short, long)]
     pub json_output_dir: String,
 
+    /// Also copy the JSON output to this directory (repeatable); hard-linked
+    /// when on the same filesystem. A failing mirror doesn't fail the run
+    #[arg(long, value_name = "DIR", env = "JSON_MIRROR_DIR", value_delimiter = ',')]
+    pub json_mirror_dir: Vec<String>,
+
     /// Output directory for the Markdown file
     #[arg(short, long)]
     pub markdown_output_dir: String,
 
+    /// Append each article to `{edition}.md.partial` as it completes, so a
+    /// crash late in the run leaves the articles done so far
+    #[arg(long)]
+    pub incremental_markdown: bool,
+
+    /// Append each article to `{date}/{edition}.ndjson` under
+    /// --json-output-dir as it completes, one JSON line per article
+    #[arg(long)]
+    pub journal: bool,
+
+    /// Create book.toml and placeholder pages when --markdown-output-dir is
+    /// not an mdBook yet (asked interactively when run from a terminal)
+    #[arg(long)]
+    pub scaffold_book: bool,
+
+    /// Run `mdbook build` on the book once the Markdown and indexes are written
+    #[arg(long)]
+    pub mdbook_build: bool,
+
+    /// The mdbook executable for --mdbook-build
+    #[arg(long, value_name = "PATH", env = "MDBOOK_PATH", default_value = "mdbook")]
+    pub mdbook_path: String,
+
+    /// Where --mdbook-build writes the site; defaults to the book's build-dir
+    #[arg(long, value_name = "DIR", requires = "mdbook_build")]
+    pub mdbook_dest_dir: Option<String>,
+
+    /// Seconds --mdbook-build may take before mdbook is killed
+    #[arg(long, value_name = "SECS", default_value_t = DEFAULT_MDBOOK_TIMEOUT_SECS)]
+    pub mdbook_timeout_secs: u64,
+
+    /// Write the edition's JSON here when the JSON or Markdown output can't
+    /// be written (disk full, permission denied); the run then exits with 75
+    #[arg(long, value_name = "DIR", env = "FALLBACK_OUTPUT_DIR")]
+    pub fallback_output_dir: Option<String>,
+
+    /// Refuse to start unless the JSON and Markdown output directories each
+    /// have at least this many MiB free
+    #[arg(long, value_name = "MB", env = "MIN_FREE_MB")]
+    pub min_free_mb: Option<u64>,
+
+    /// Also write one JSON file per category under `{date}/{edition}/`
+    #[arg(long)]
+    pub split_by_category: bool,
+
+    /// Rebuild {date}/day.json with every edition of the date after writing JSON
+    #[arg(long)]
+    pub day_digest: bool,
+
+    /// Keep takeaways.md, a page of the key takeaways of the latest editions, and list it in SUMMARY.md
+    #[arg(long)]
+    pub takeaways_digest: bool,
+
+    /// Days of takeaways kept in takeaways.md before the current date
+    #[arg(long, value_name = "DAYS", default_value_t = DEFAULT_TAKEAWAYS_RETENTION_DAYS)]
+    pub takeaways_retention_days: u32,
+
+    /// Leave each article's scraped text out of the JSON output; summaries and metadata stay
+    #[arg(long, conflicts_with = "content_max_chars")]
+    pub omit_content: bool,
+
+    /// Cut each article's scraped text in the JSON output to an excerpt of at most N characters
+    #[arg(long, value_name = "N", env = "CONTENT_MAX_CHARS")]
+    pub content_max_chars: Option<usize>,
+
+    /// How to name editions: by time of day (morning/afternoon/evening) or by hour (e.g. 14h)
+    #[arg(long, value_enum, default_value_t = EditionScheme::Tod, env = "EDITION_SCHEME")]
+    pub edition_scheme: EditionScheme,
+
+    /// How to order articles within the edition
+    #[arg(long, value_enum, default_value_t = ArticleOrder::Category)]
+    pub article_order: ArticleOrder,
+
+    /// Order the articles of the JSON edition this way instead of by
+    /// --article-order; the Markdown keeps --article-order
+    #[arg(long, value_enum, value_name = "ORDER")]
+    pub json_sort: Option<ArticleOrder>,
+
+    /// Shard an edition JSON larger than BYTES into numbered parts
+    /// (`evening.part1.json`, ...) listed by the edition file; unlimited by default
+    #[arg(long, value_name = "BYTES", env = "JSON_MAX_BYTES")]
+    pub json_max_bytes: Option<usize>,
+
+    /// Which copy to keep when a source lists the same article under several URLs
+    #[arg(long, value_enum, default_value_t = DuplicatePreference::ShortestUrl)]
+    pub dedup_prefer: DuplicatePreference,
+
+    /// Sources whose copy to keep when several carry identical text, such as
+    /// syndicated wire copy; most preferred first
+    #[arg(long, value_name = "TAG", env = "DEDUP_PREFER_SOURCES", value_delimiter = ',', default_value = "apnews", value_parser = parse_source_tag)]
+    pub dedup_prefer_sources: Vec<String>,
+
+    /// Fetch and summarize at most N of the indexed articles, sampled at random
+    #[arg(long, value_name = "N", env = "MAX_ARTICLES")]
+    pub max_articles: Option<usize>,
+
+    /// Share of --max-articles per source, e.g. `cnn=3,npr=2`; sources left
+    /// out get none
+    #[arg(long, value_name = "TAG=WEIGHT", env = "SOURCE_WEIGHTS", value_delimiter = ',', value_parser = parse_source_weight, requires = "max_articles")]
+    pub source_weights: Vec<(String, f64)>,
+
+    /// Seed for the random sampling of --max-articles, to repeat a draw
+    #[arg(long, env = "SEED")]
+    pub seed: Option<u64>,
+
+    /// Also dump scraped articles into this directory before LLM processing
+    /// (a `run` only; `fetch` takes its own)
+    #[arg(long, env = "RAW_DUMP_DIR")]
+    pub raw_dump_dir: Option<String>,
+
+    /// Gzip each article file of a raw dump
+    #[arg(long)]
+    pub raw_dump_compress: bool,
+
+    /// Save the raw HTML of every fetched article page here, named by a hash of its URL
+    #[arg(long, value_name = "DIR", env = "SNAPSHOT_DIR")]
+    pub snapshot_dir: Option<String>,
+
+    /// Write every article skipped during the run, with the reason, to this JSON file
+    #[arg(long, env = "SKIP_REPORT", value_name = "PATH")]
+    pub skip_report: Option<String>,
+
+    /// Output directory for a hard-wrapped plain-text edition (gopher mirror)
+    #[arg(long, env = "TEXT_OUTPUT_DIR")]
+    pub text_output_dir: Option<String>,
+
+    /// Line width of the plain-text edition, in terminal columns
+    #[arg(long, default_value_t = DEFAULT_TEXT_WIDTH)]
+    pub text_width: usize,
+
+    /// Output directory for a gemtext edition (gemini mirror)
+    #[arg(long, env = "GEMTEXT_OUTPUT_DIR")]
+    pub gemtext_output_dir: Option<String>,
+
+    /// Text shown in the Markdown, plain-text and gemtext editions when no
+    /// articles were processed
+    #[arg(long, env = "EMPTY_EDITION_MESSAGE", default_value = DEFAULT_EMPTY_EDITION_MESSAGE)]
+    pub empty_edition_message: String,
+
+    /// Render the edition time in Markdown and its index files at second precision, so unchanged editions diff cleanly
+    #[arg(long)]
+    pub stable_timestamps: bool,
+
+    /// How article anchors are written in the Markdown edition; `both` (the
+    /// default) also keeps the old title-slug anchors for existing links
+    #[arg(long, value_enum, default_value_t = AnchorMode::Both)]
+    pub anchor_mode: AnchorMode,
+
+    /// How named entities are shown in the Markdown edition; `footnotes` links
+    /// their first mention in the summary to a footnote
+    #[arg(long, value_enum, default_value_t = EntityStyle::List)]
+    pub entity_style: EntityStyle,
+
+    /// Layout of the Markdown edition; `details` puts each article in a
+    /// collapsible block
+    #[arg(long, value_enum, default_value_t = MarkdownTheme::Headings)]
+    pub markdown_theme: MarkdownTheme,
+
+    /// Show a label with an emoji per source (e.g. `📻 NPR`) instead of the
+    /// raw source tag in the Markdown edition and TOC
+    #[arg(long)]
+    pub source_labels: bool,
+
+    /// Label for a source tag, as TAG=LABEL (repeatable); overrides the defaults
+    #[arg(long, value_name = "TAG=LABEL", env = "SOURCE_LABEL", value_delimiter = ',', value_parser = parse_source_label, requires = "source_labels")]
+    pub source_label: Vec<(String, String)>,
+
+    /// Keep only articles located in one of these countries or regions
+    /// (repeatable, case-insensitive)
+    #[arg(long, value_name = "LOCATION", env = "REGION", value_delimiter = ',')]
+    pub region: Vec<String>,
+
+    /// Keep only articles in one of these categories (repeatable, case-insensitive)
+    #[arg(long, value_name = "CATEGORY", env = "INCLUDE_CATEGORIES", value_delimiter = ',')]
+    pub include_categories: Vec<String>,
+
+    /// Drop articles in these categories (repeatable, case-insensitive)
+    #[arg(long, value_name = "CATEGORY", env = "EXCLUDE_CATEGORIES", value_delimiter = ',')]
+    pub exclude_categories: Vec<String>,
+
+    /// Keep only articles with at least one of these tags (repeatable, case-insensitive)
+    #[arg(long, value_name = "TAG", env = "INCLUDE_TAGS", value_delimiter = ',')]
+    pub include_tags: Vec<String>,
+
+    /// Drop articles with any of these tags (repeatable, case-insensitive);
+    /// wins over --include-tags
+    #[arg(long, value_name = "TAG", env = "EXCLUDE_TAGS", value_delimiter = ',')]
+    pub exclude_tags: Vec<String>,
+
+    /// Fetch-time filter: keep only articles whose title or text mentions one
+    /// of these words or phrases (repeatable, case-insensitive, whole words)
+    #[arg(long, value_name = "KEYWORD", env = "INCLUDE_KEYWORDS", value_delimiter = ',')]
+    pub include_keywords: Vec<String>,
+
+    /// Fetch-time filter: drop articles whose title or text mentions any of
+    /// these words or phrases (repeatable, case-insensitive, whole words);
+    /// wins over --include-keywords
+    #[arg(long, value_name = "KEYWORD", env = "EXCLUDE_KEYWORDS", value_delimiter = ',')]
+    pub exclude_keywords: Vec<String>,
+
+    /// Keep at most N articles per category; the rest are listed under "Also today"
+    #[arg(long, value_name = "N")]
+    pub max_per_category: Option<usize>,
+
+    /// Feature the N most important articles in a "Top Stories" block (0 disables)
+    #[arg(long, value_name = "N", env = "TOP_N", default_value_t = DEFAULT_TOP_N)]
+    pub top_n: usize,
+
+    /// Record a correction when an article's summary differs from an earlier
+    /// edition of the day by more than this share of its words (0-1; 1 disables)
+    #[arg(long, value_name = "SHARE", env = "CORRECTION_THRESHOLD", default_value_t = DEFAULT_CORRECTION_THRESHOLD, value_parser = parse_correction_threshold)]
+    pub correction_threshold: f64,
+
+    /// Weight of a salience component, e.g. `related=3` (repeatable;
+    /// entities, related, takeaways, length, sources). The most salient
+    /// article is rendered first as the lede
+    #[arg(long, value_name = "NAME=WEIGHT", env = "SALIENCE_WEIGHTS", value_delimiter = ',', value_parser = parse_salience_weight)]
+    pub salience_weight: Vec<(String, f32)>,
+
+    /// Show a progress bar on stderr while articles are processed (TTY only)
+    #[arg(long)]
+    pub progress: bool,
+
+    /// Print a summary table of the run to stdout at the end, even with logging off
+    #[arg(long)]
+    pub print_stats: bool,
+
+    /// Optional article fields the LLM is asked to extract (comma-separated);
+    /// title, category and summary are always extracted
+    #[arg(
+        long,
+        env = "EXTRACT_FIELDS",
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = ArticleField::ALL.to_vec()
+    )]
+    pub extract_fields: Vec<ArticleField>,
+
+    /// Drop articles whose LLM response looks truncated instead of asking again
+    #[arg(long)]
+    pub no_reask: bool,
+
+    /// Give the truncation re-ask the full retry backoff instead of a single attempt
+    #[arg(long)]
+    pub reask_with_retries: bool,
+
+    /// How failed LLM attempts are logged: `summary` warns once per 10s with
+    /// the number of articles retrying, `each` warns for every attempt
+    #[arg(long, value_enum, env = "RETRY_LOG", default_value_t = RetryLogMode::Summary)]
+    pub retry_log: RetryLogMode,
+
+    /// Header sent to the LLM in front of each article: the outlet name by
+    /// default, `full` adds the URL, `off` sends the bare text
+    #[arg(long, value_enum, env = "LLM_INPUT_HEADER", default_value_t = LlmInputHeader::Source)]
+    pub llm_input_header: LlmInputHeader,
+
+    /// Ask the LLM for summaries of about N words; best-effort, replies of
+    /// any length are kept
+    #[arg(long, value_name = "N", env = "SUMMARY_WORDS")]
+    pub summary_words: Option<usize>,
+
+    /// Concurrent LLM requests allowed for a model, as MODEL=N (repeatable)
+    #[arg(long, value_name = "MODEL=N", env = "MODEL_CONCURRENCY", value_delimiter = ',', value_parser = parse_model_limit)]
+    pub model_concurrency: Vec<(String, usize)>,
+
+    /// Concurrent LLM requests allowed for models without a --model-concurrency limit
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MODEL_CONCURRENCY)]
+    pub default_model_concurrency: usize,
+
+    /// Keep at most N key takeaways per article
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_TAKEAWAYS)]
+    pub max_takeaways: usize,
+
+    /// Drop key takeaways shorter than N characters
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MIN_TAKEAWAY_CHARS)]
+    pub min_takeaway_chars: usize,
+
+    /// Drop key takeaways longer than N characters
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_TAKEAWAY_CHARS)]
+    pub max_takeaway_chars: usize,
+
+    /// Keep at most N tags per article
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_TAGS)]
+    pub max_tags: usize,
+
+    /// Drop tags with more than N words (shorter multi-word tags become kebab-case)
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_TAG_WORDS)]
+    pub max_tag_words: usize,
+
     /// Optional path to config.yaml file
     #[arg(short, long)]
     pub config: Option<String>,
 
+    /// Check output dirs, config, template and API keys, print a report and exit
+    #[arg(long)]
+    pub validate_only: bool,
+
+    /// Re-summarize a stored edition (a JSON file, or every edition of a
+    /// YYYY-MM-DD date under --json-output-dir) with the current template
+    /// instead of scraping, then rewrite its JSON and Markdown
+    #[arg(long, value_name = "PATH|DATE")]
+    pub reprocess: Option<String>,
+
+    /// Rebuild the statistics rollups (see the `stats` subcommand) at the
+    /// end of a scraping run
+    #[arg(long)]
+    pub update_stats: bool,
+
+    /// Ask the LLM for a headline and blurb summing up the edition
+    #[arg(long)]
+    pub editorial: bool,
+
+    /// Path to a state file caching homepage ETag/Last-Modified validators between runs
+    #[arg(long, env = "INDEX_CACHE_FILE")]
+    pub index_cache_file: Option<String>,
+
+    /// Path to a state file caching article pages with their ETag/Last-Modified
+    /// validators between runs
+    #[arg(long, env = "PAGE_CACHE_FILE")]
+    pub page_cache_file: Option<String>,
+
+    /// Skip indexed URLs disallowed by each site's robots.txt
+    #[arg(long)]
+    pub respect_robots: bool,
+
+    /// Timeout in seconds for each scraper HTTP request
+    #[arg(long, value_name = "SECS", env = "SCRAPE_TIMEOUT_SECS", default_value_t = DEFAULT_SCRAPE_TIMEOUT_SECS)]
+    pub scrape_timeout_secs: u64,
+
+    /// Largest response body in bytes the scrapers read; larger pages are abandoned
+    #[arg(long, value_name = "BYTES", env = "MAX_BODY_BYTES", default_value_t = DEFAULT_MAX_BODY_BYTES)]
+    pub max_body_bytes: usize,
+
+    /// Bytes of page bodies kept in memory for repeat fetches within the run (0 turns it off)
+    #[arg(long, value_name = "BYTES", env = "RESPONSE_CACHE_BYTES", default_value_t = DEFAULT_RESPONSE_CACHE_BYTES)]
+    pub response_cache_bytes: usize,
+
+    /// Idle connections the scraper client keeps open per host
+    #[arg(long, value_name = "N", env = "POOL_MAX_IDLE_PER_HOST", default_value_t = DEFAULT_POOL_MAX_IDLE_PER_HOST)]
+    pub pool_max_idle_per_host: usize,
+
+    /// Seconds an idle scraper connection is kept open for reuse
+    #[arg(long, value_name = "SECS", env = "POOL_IDLE_TIMEOUT_SECS", default_value_t = DEFAULT_POOL_IDLE_TIMEOUT_SECS)]
+    pub pool_idle_timeout_secs: u64,
+
+    /// Retries of a scraper request on 429/502/503/504 or a connection error
+    #[arg(long, value_name = "N", env = "SCRAPE_RETRIES", default_value_t = 2)]
+    pub scrape_retries: u32,
+
+    /// Delay in milliseconds before the first scraper retry; doubles per retry
+    #[arg(long, value_name = "MS", env = "SCRAPE_RETRY_DELAY_MS", default_value_t = 500)]
+    pub scrape_retry_delay_ms: u64,
+
+    /// Minimum delay in milliseconds between two requests to the same host (0 disables)
+    #[arg(long, value_name = "MS", env = "PER_HOST_DELAY_MS", default_value_t = 0)]
+    pub per_host_delay_ms: u64,
+
+    /// Time budget in seconds for indexing plus fetching each source; on expiry the articles fetched so far are kept
+    #[arg(long, value_name = "SECS", env = "SOURCE_BUDGET_SECS")]
+    pub source_budget_secs: Option<u64>,
+
+    /// Wall-clock budget in seconds for the whole run; once spent no further
+    /// articles are sent to the LLM, those in flight are abandoned, and the
+    /// edition is written with what's done
+    #[arg(long, value_name = "SECS", env = "MAX_RUN_TIME")]
+    pub max_run_time: Option<u64>,
+
+    /// YAML file with extra per-source boilerplate phrases/patterns to strip from articles
+    #[arg(long, env = "BOILERPLATE_FILE")]
+    pub boilerplate_file: Option<String>,
+
+    /// YAML file overriding the scrapers' CSS selectors per source (`scrapers.<source>.<key>`)
+    #[arg(long, env = "SELECTORS_FILE")]
+    pub selectors_file: Option<String>,
+
+    /// Extra phrases marking a page as paywalled, on top of the built-in ones (case-insensitive)
+    #[arg(long = "paywall-phrases", value_name = "PHRASE", env = "PAYWALL_PHRASES", value_delimiter = ',')]
+    pub paywall_phrases: Vec<String>,
+
+    /// Path to a state file remembering processed URLs and their content hashes
+    #[arg(long, env = "STATE_FILE")]
+    pub state_file: Option<String>,
+
+    /// Skip indexed URLs processed in any earlier run (requires --state-file)
+    #[arg(long, requires = "state_file")]
+    pub only_new_since_last_run: bool,
+
+    /// Forget state file entries last seen more than N days ago
+    #[arg(long, value_name = "DAYS", env = "STATE_RETENTION_DAYS", default_value_t = 30)]
+    pub state_retention_days: u64,
+
+    /// Retry articles that failed in the previous run (within 24h) under "In case you missed it" (requires --state-file)
+    #[arg(long, requires = "state_file")]
+    pub carry_over_failures: bool,
+
+    /// Username for an LLM endpoint behind HTTP basic auth
+    #[arg(long, env = "LLM_BASIC_AUTH_USER", requires = "llm_basic_auth_password")]
+    pub llm_basic_auth_user: Option<String>,
+
+    /// Password for an LLM endpoint behind HTTP basic auth
+    #[arg(long, env = "LLM_BASIC_AUTH_PASSWORD", hide_env_values = true, requires = "llm_basic_auth_user")]
+    pub llm_basic_auth_password: Option<String>,
+
     /// New York Times API key
     #[arg(long, env = "NYT_API_KEY")]
     pub nyt_api_key: Option<String>,
@@ -49,6 +493,199 @@ pub struct Cli {
     /// Message bus exchange name (only used when `publish` feature is enabled)
     #[arg(long, env = "MESSAGE_BUS_EXCHANGE", default_value = "events")]
     pub message_bus_exchange: String,
+
+    /// Routing key carried by the `edition.published` event (only used when `publish` feature is enabled)
+    #[arg(long, env = "EDITION_EVENT_KEY", default_value = DEFAULT_EDITION_EVENT_KEY)]
+    pub edition_event_key: String,
+
+    /// Publish only edition metadata and file paths, not the articles
+    #[arg(long)]
+    pub no_edition_payload: bool,
+
+    /// Mastodon instance to post the edition's headline to, e.g. `https://mastodon.social` (only used when `social` feature is enabled)
+    #[arg(long, value_name = "URL", env = "MASTODON_INSTANCE", requires = "mastodon_token")]
+    pub mastodon_instance: Option<String>,
+
+    /// Access token of the Mastodon account posting the headline (needs the `write:statuses` scope)
+    #[arg(long, value_name = "TOKEN", env = "MASTODON_TOKEN", hide_env_values = true, requires = "mastodon_instance")]
+    pub mastodon_token: Option<String>,
+
+    /// What to run; a `run` if not given
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// What a run does.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Index, fetch and summarize the articles, then write the edition
+    /// (the default)
+    Run,
+
+    /// Index and fetch the articles and write them to a raw dump for
+    /// `process`, without any LLM processing
+    Fetch(FetchArgs),
+
+    /// Summarize the articles of a raw dump written by `fetch`, then write
+    /// the edition they were fetched for
+    Process(ProcessArgs),
+
+    /// Build the week in review from the stored editions of the last seven
+    /// days instead of scraping, then write `{date}_weekly.md` and
+    /// `{date}/weekly.json`
+    Weekly(WeeklyArgs),
+
+    /// Run the --validate-only checks, then index each source live and fetch
+    /// one article from it; print a pass/fail report and exit
+    Doctor(DoctorArgs),
+
+    /// Write statistics rollups of the stored editions to
+    /// `{json-output-dir}/stats/` instead of scraping
+    Stats,
+
+    /// Check every relative link and anchor of the Markdown files under
+    /// --markdown-output-dir, print the broken ones and exit
+    CheckLinks {
+        /// Remove dead entries from the index files
+        #[arg(long)]
+        fix: bool,
+    },
+}
+
+/// Options of the `fetch` subcommand.
+#[derive(Args, Debug)]
+pub struct FetchArgs {
+    /// Directory to write the dump to; the articles go under
+    /// `{DIR}/{date}/{edition}`
+    #[arg(value_name = "DIR")]
+    pub dump_dir: String,
+}
+
+/// Options of the `process` subcommand.
+#[derive(Args, Debug)]
+pub struct ProcessArgs {
+    /// The dump's edition directory, `{DIR}/{date}/{edition}` of `fetch`
+    #[arg(value_name = "EDITION_DIR")]
+    pub dump_dir: String,
+}
+
+/// Options of the `weekly` subcommand.
+#[derive(Args, Debug)]
+pub struct WeeklyArgs {
+    /// Last day of the week (YYYY-MM-DD); defaults to today
+    #[arg(long, value_name = "DATE")]
+    pub week_ending: Option<NaiveDate>,
+
+    /// Stories per category in the week in review
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_STORIES_PER_CATEGORY)]
+    pub top: usize,
+
+    /// Ask the LLM for an overview paragraph of the week in review
+    #[arg(long)]
+    pub overview: bool,
+}
+
+/// Options of the `doctor` subcommand.
+#[derive(Args, Debug)]
+pub struct DoctorArgs {
+    /// Fewest URLs a source's index may yield
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MIN_URLS)]
+    pub min_urls: usize,
+
+    /// Also send the LLM one tiny prompt
+    #[arg(long)]
+    pub llm: bool,
+}
+
+impl Cli {
+    /// The mode flag given alongside a subcommand, if any; a run does one or
+    /// the other.
+    pub fn conflicting_mode_flag(&self) -> Option<&'static str> {
+        self.command.as_ref()?;
+        [
+            (self.validate_only, "--validate-only"),
+            (self.reprocess.is_some(), "--reprocess"),
+        ]
+        .into_iter()
+        .find_map(|(given, flag)| given.then_some(flag))
+    }
+
+    /// Path of the awful_aj `config.yaml`: `--config` if given, otherwise
+    /// the one in awful_aj's config directory.
+    pub fn config_path(&self) -> Result<PathBuf, Box<dyn Error>> {
+        match &self.config {
+            Some(path) => Ok(PathBuf::from(path)),
+            None => Ok(config_dir()?.join("config.yaml")),
+        }
+    }
+
+    /// Basic auth credentials for the LLM endpoint, if both were given.
+    pub fn llm_auth(&self) -> Option<BasicAuth> {
+        match (&self.llm_basic_auth_user, &self.llm_basic_auth_password) {
+            (Some(username), Some(password)) => Some(BasicAuth {
+                username: username.clone(),
+                password: password.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Caps on takeaways and tags.
+    pub fn article_limits(&self) -> ArticleLimits {
+        ArticleLimits {
+            max_takeaways: self.max_takeaways,
+            min_takeaway_chars: self.min_takeaway_chars,
+            max_takeaway_chars: self.max_takeaway_chars,
+            max_tags: self.max_tags,
+            max_tag_words: self.max_tag_words,
+        }
+    }
+
+    /// Category and tag filters for the final edition.
+    pub fn edition_filter(&self) -> EditionFilter {
+        EditionFilter {
+            include_categories: self.include_categories.clone(),
+            exclude_categories: self.exclude_categories.clone(),
+            include_tags: self.include_tags.clone(),
+            exclude_tags: self.exclude_tags.clone(),
+        }
+    }
+
+    /// Keyword filters for the fetched articles.
+    pub fn keyword_filter(&self) -> KeywordFilter {
+        KeywordFilter::new(&self.include_keywords, &self.exclude_keywords)
+    }
+
+    /// Salience weights, the defaults overridden by --salience-weight.
+    pub fn salience_weights(&self) -> SalienceWeights {
+        SalienceWeights::with(&self.salience_weight)
+    }
+
+    /// How much article text the JSON output carries.
+    pub fn content_policy(&self) -> ContentPolicy {
+        match (self.omit_content, self.content_max_chars) {
+            (true, _) => ContentPolicy::Omit,
+            (false, Some(max_chars)) => ContentPolicy::Excerpt(max_chars),
+            (false, None) => ContentPolicy::Full,
+        }
+    }
+
+    /// Styles of the Markdown edition.
+    pub fn markdown_options(&self) -> MarkdownOptions {
+        MarkdownOptions {
+            empty_message: self.empty_edition_message.clone(),
+            anchors: self.anchor_mode,
+            entities: self.entity_style,
+            theme: self.markdown_theme,
+            labels: if self.source_labels {
+                SourceLabels::new(&self.source_label)
+            } else {
+                SourceLabels::default()
+            },
+            stable_timestamps: self.stable_timestamps,
+            ..Default::default()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -82,4 +719,92 @@ mod tests {
         assert_eq!(cli.json_output_dir, "/tmp/json");
         assert_eq!(cli.markdown_output_dir, "/tmp/markdown");
     }
+
+    #[test]
+    fn test_markdown_keeps_slug_anchors_by_default() {
+        let cli = Cli::parse_from(["awful_text_news", "-j", "./json", "-m", "./markdown"]);
+        assert_eq!(cli.markdown_options().anchors, AnchorMode::Both);
+
+        let cli = Cli::parse_from(["awful_text_news", "-j", "./json", "-m", "./markdown", "--anchor-mode", "id"]);
+        assert_eq!(cli.markdown_options().anchors, AnchorMode::Id);
+    }
+
+    #[test]
+    fn test_llm_input_header_names_only_the_outlet_by_default() {
+        let cli = Cli::parse_from(["awful_text_news", "-j", "./json", "-m", "./markdown"]);
+        assert_eq!(cli.llm_input_header, LlmInputHeader::Source);
+    }
+
+    #[test]
+    fn test_article_limits_default_like_the_pipeline() {
+        let cli = Cli::parse_from(["awful_text_news", "-j", "./json", "-m", "./markdown"]);
+        assert_eq!(cli.article_limits(), ArticleLimits::default());
+    }
+
+    #[test]
+    fn test_weekly_subcommand_takes_its_own_options() {
+        let cli = Cli::parse_from([
+            "awful_text_news", "-j", "./json", "-m", "./markdown", "weekly", "--week-ending", "2025-05-11", "--top", "3",
+        ]);
+        let Some(Command::Weekly(weekly)) = &cli.command else {
+            panic!("expected the weekly subcommand, got {:?}", cli.command);
+        };
+        assert_eq!(weekly.week_ending, NaiveDate::from_ymd_opt(2025, 5, 11));
+        assert_eq!(weekly.top, 3);
+        assert!(!weekly.overview);
+        assert_eq!(cli.conflicting_mode_flag(), None);
+
+        // Weekly options mean nothing to a scraping run
+        assert!(Cli::try_parse_from(["awful_text_news", "-j", "./json", "-m", "./markdown", "--top", "3"]).is_err());
+
+        let cli = Cli::parse_from(["awful_text_news", "-j", "./json", "-m", "./markdown", "--validate-only", "weekly"]);
+        assert_eq!(cli.conflicting_mode_flag(), Some("--validate-only"));
+
+        let cli = Cli::parse_from(["awful_text_news", "-j", "./json", "-m", "./markdown", "stats"]);
+        assert!(matches!(cli.command, Some(Command::Stats)));
+    }
+
+    #[test]
+    fn test_fetch_and_process_take_a_dump_dir() {
+        let base = ["awful_text_news", "-j", "./json", "-m", "./markdown"];
+        assert!(Cli::try_parse_from(base.iter().copied().chain(["fetch"])).is_err());
+        assert!(Cli::try_parse_from(base.iter().copied().chain(["process"])).is_err());
+
+        let cli = Cli::parse_from(base.iter().copied().chain(["fetch", "./raw"]));
+        assert!(matches!(&cli.command, Some(Command::Fetch(fetch)) if fetch.dump_dir == "./raw"));
+
+        let cli = Cli::parse_from(base.iter().copied().chain(["process", "./raw/2025-05-06/morning"]));
+        assert!(matches!(&cli.command, Some(Command::Process(process)) if process.dump_dir == "./raw/2025-05-06/morning"));
+
+        let cli = Cli::parse_from(base.iter().copied().chain(["--reprocess", "2025-05-06", "run"]));
+        assert_eq!(cli.conflicting_mode_flag(), Some("--reprocess"));
+        assert!(Cli::parse_from(base).command.is_none());
+    }
+
+    #[test]
+    fn test_doctor_subcommand_takes_its_own_options() {
+        let base = ["awful_text_news", "-j", "./json", "-m", "./markdown"];
+        assert!(Cli::try_parse_from(base.iter().copied().chain(["--llm"])).is_err());
+
+        let cli = Cli::parse_from(base.iter().copied().chain(["doctor", "--min-urls", "3"]));
+        let Some(Command::Doctor(doctor)) = &cli.command else {
+            panic!("expected the doctor subcommand, got {:?}", cli.command);
+        };
+        assert_eq!(doctor.min_urls, 3);
+        assert!(!doctor.llm);
+        assert_eq!(cli.conflicting_mode_flag(), None);
+
+        let cli = Cli::parse_from(base.iter().copied().chain(["--validate-only", "doctor"]));
+        assert_eq!(cli.conflicting_mode_flag(), Some("--validate-only"));
+    }
+
+    #[test]
+    fn test_fix_belongs_to_check_links() {
+        let base = ["awful_text_news", "-j", "./json", "-m", "./markdown"];
+        assert!(Cli::try_parse_from(base.iter().copied().chain(["--fix"])).is_err());
+        let cli = Cli::parse_from(base.iter().copied().chain(["check-links", "--fix"]));
+        assert!(matches!(cli.command, Some(Command::CheckLinks { fix: true })));
+        let cli = Cli::parse_from(base.iter().copied().chain(["check-links"]));
+        assert!(matches!(cli.command, Some(Command::CheckLinks { fix: false })));
+    }
 }
@@ -0,0 +1,166 @@
+//! Per-model limits on concurrent LLM requests.
+//!
+//! A local model and a hosted one have very different rate limits, so a
+//! single global concurrency is either too timid for one or too aggressive
+//! for the other. [`ModelConcurrency`] keeps one semaphore per model name;
+//! every request to the LLM holds a permit of its model's semaphore for the
+//! duration of the call (retries and re-asks included).
+//!
+//! # Specifying Limits
+//!
+//! Limits are given as `MODEL=N` pairs, keyed by the `model` value of the
+//! awful_aj config the request is sent with:
+//!
+//! ```sh
+//! awful_text_news -j ./json -m ./md \
+//!     --model-concurrency Qwen_Qwen2.5-3B-Instruct-GGUF=4 \
+//!     --model-concurrency gpt-4o-mini=16
+//! # or
+//! MODEL_CONCURRENCY="Qwen_Qwen2.5-3B-Instruct-GGUF=4,gpt-4o-mini=16" awful_text_news ...
+//! ```
+//!
+//! Models without an explicit limit use `--default-model-concurrency`
+//! ([`DEFAULT_MODEL_CONCURRENCY`] unless set).
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::debug;
+
+/// Concurrent requests allowed per model without an explicit limit.
+pub const DEFAULT_MODEL_CONCURRENCY: usize = 12;
+
+/// Parse a `MODEL=N` limit from the command line.
+pub fn parse_model_limit(raw: &str) -> Result<(String, usize), String> {
+    let (model, limit) = raw
+        .rsplit_once('=')
+        .ok_or_else(|| format!("expected MODEL=N, got `{}`", raw))?;
+    let model = model.trim();
+    if model.is_empty() {
+        return Err(format!("missing model name in `{}`", raw));
+    }
+    let limit: usize = limit
+        .trim()
+        .parse()
+        .map_err(|e| format!("invalid limit in `{}`: {}", raw, e))?;
+    if limit == 0 {
+        return Err(format!("limit must be at least 1 in `{}`", raw));
+    }
+    Ok((model.to_string(), limit))
+}
+
+/// Independent request limits per model name.
+#[derive(Debug)]
+pub struct ModelConcurrency {
+    default_limit: usize,
+    limits: HashMap<String, usize>,
+    semaphores: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl Default for ModelConcurrency {
+    fn default() -> Self {
+        ModelConcurrency::new(DEFAULT_MODEL_CONCURRENCY, &[])
+    }
+}
+
+impl ModelConcurrency {
+    /// Create limits from `MODEL=N` pairs, with `default_limit` for any
+    /// other model.
+    pub fn new(default_limit: usize, limits: &[(String, usize)]) -> Self {
+        ModelConcurrency {
+            default_limit: default_limit.max(1),
+            limits: limits.iter().cloned().collect(),
+            semaphores: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The limit that applies to `model`.
+    pub fn limit(&self, model: &str) -> usize {
+        self.limits.get(model).copied().unwrap_or(self.default_limit)
+    }
+
+    /// Upper bound on requests in flight to `models`, the models a run
+    /// sends to, used to size the processing stream. Limits of models the
+    /// run doesn't use leave it unchanged.
+    pub fn total_for<'a>(&self, models: impl IntoIterator<Item = &'a str>) -> usize {
+        let models: HashSet<&str> = models.into_iter().collect();
+        models.into_iter().map(|model| self.limit(model)).sum()
+    }
+
+    fn semaphore(&self, model: &str) -> Arc<Semaphore> {
+        let mut semaphores = self.semaphores.lock().unwrap();
+        let semaphore = semaphores
+            .entry(model.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(self.limit(model))));
+        Arc::clone(semaphore)
+    }
+
+    /// Wait for a request slot for `model`; the slot is released when the
+    /// permit is dropped.
+    pub async fn acquire(&self, model: &str) -> OwnedSemaphorePermit {
+        let semaphore = self.semaphore(model);
+        if semaphore.available_permits() == 0 {
+            debug!(model, limit = self.limit(model), "Waiting for a model request slot");
+        }
+        semaphore.acquire_owned().await.expect("model semaphore is never closed")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_model_limit() {
+        assert_eq!(parse_model_limit("gpt-4o-mini=16"), Ok(("gpt-4o-mini".to_string(), 16)));
+        assert_eq!(parse_model_limit("org/model=v2=3"), Ok(("org/model=v2".to_string(), 3)));
+        assert!(parse_model_limit("gpt-4o-mini").is_err());
+        assert!(parse_model_limit("=4").is_err());
+        assert!(parse_model_limit("local=0").is_err());
+        assert!(parse_model_limit("local=many").is_err());
+    }
+
+    /// Run `tasks` requests against `model` and report the peak in flight.
+    async fn peak_in_flight(limits: &ModelConcurrency, model: &str, tasks: usize) -> usize {
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let requests = (0..tasks).map(|_| async {
+            let _permit = limits.acquire(model).await;
+            let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+        });
+        futures::future::join_all(requests).await;
+        peak.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn test_models_are_limited_independently() {
+        let limits = ModelConcurrency::new(3, &[("local".to_string(), 1), ("cloud".to_string(), 4)]);
+
+        // Both models run at the same time; neither borrows the other's slots
+        let (local, cloud, other) = tokio::join!(
+            peak_in_flight(&limits, "local", 5),
+            peak_in_flight(&limits, "cloud", 8),
+            peak_in_flight(&limits, "unlisted", 6),
+        );
+        assert_eq!(local, 1);
+        assert_eq!(cloud, 4);
+        assert_eq!(other, 3);
+    }
+
+    #[test]
+    fn test_total_counts_only_the_models_routed_to() {
+        let limits = ModelConcurrency::new(3, &[("local".to_string(), 1), ("cloud".to_string(), 4)]);
+        assert_eq!(limits.total_for(["local"]), 1);
+        assert_eq!(limits.total_for(["unlisted"]), 3);
+        assert_eq!(limits.total_for(["local", "cloud", "local"]), 5);
+
+        // Limits for other models don't change a run's batch size
+        let more = ModelConcurrency::new(3, &[("local".to_string(), 1), ("other".to_string(), 32)]);
+        assert_eq!(more.total_for(["local"]), limits.total_for(["local"]));
+    }
+}
@@ -0,0 +1,441 @@
+//! Changes since the previous edition of the day.
+//!
+//! After an edition is built, the latest earlier edition of the same date
+//! (afternoon compares against morning; evening against afternoon, or
+//! morning if there was no afternoon run) is read back from the JSON output
+//! and compared by article:
+//!
+//! | In previous | In current | Listed as |
+//! |-------------|------------|-----------|
+//! | no | yes | new |
+//! | yes, different content | yes | updated |
+//! | yes | no | dropped ("no longer covered") |
+//!
+//! Articles are matched by source URL, falling back to the title for
+//! articles without one. An article counts as updated when it is flagged
+//! `updateOf` or its stored content differs; repeats with unchanged content
+//! are not listed. The result is stored as the edition's `diff` and rendered
+//! as "Since the last edition" at the top of the Markdown.
+//!
+//! The first edition of a day has nothing to compare against and gets no
+//! diff; a missing or corrupt previous edition is skipped the same way.
+//!
+//! # Developing Stories
+//!
+//! "Live updates" pages keep their URL while the story grows through the
+//! day. Before the diff, every earlier edition of the date is read back and
+//! [`mark_developing`] flags an article as `updateOf` the latest earlier
+//! edition that carried its URL, if its content is longer now. This works
+//! without `--state-file`; editions stored without content (see
+//! `--omit-content`) can't be compared and are not flagged. The flag is
+//! rendered as an "Updated" badge in the Markdown.
+//!
+//! # Corrections
+//!
+//! A story the model summarizes again in a later edition can come out
+//! saying something else. [`find_corrections`] compares each article's
+//! summary with the one the latest earlier edition carrying its URL gave
+//! it; past `--correction-threshold` (see [`summary_divergence`]) the
+//! edition records a [`Correction`] with the earlier summary's hash and the
+//! fields that changed, rendered as a "Corrected" note on the article.
+
+use crate::models::{AwfulNewsArticle, Correction, EditionDiff, FrontPage, StoryRef};
+use crate::outputs::json::read_edition;
+use crate::outputs::sink::FileSystem;
+use crate::utils::{content_hash, normalize_url, EditionScheme};
+use std::collections::{HashMap, HashSet};
+use tracing::{debug, warn};
+
+/// Default `--correction-threshold`.
+pub const DEFAULT_CORRECTION_THRESHOLD: f64 = 0.5;
+
+/// Key matching an article across editions.
+pub fn story_key(article: &AwfulNewsArticle) -> &str {
+    article.source.as_deref().unwrap_or(&article.title)
+}
+
+fn story_ref(article: &AwfulNewsArticle) -> StoryRef {
+    StoryRef {
+        title: article.title.clone(),
+        source: article.source.clone(),
+    }
+}
+
+/// Compare `current` with the `previous` edition of the same date.
+pub fn diff_editions(previous: &FrontPage, current: &FrontPage) -> EditionDiff {
+    let before: HashMap<&str, &AwfulNewsArticle> = previous.articles.iter().map(|a| (story_key(a), a)).collect();
+    let now: HashMap<&str, &AwfulNewsArticle> = current.articles.iter().map(|a| (story_key(a), a)).collect();
+
+    let mut diff = EditionDiff {
+        previous_edition: previous.time_of_day.clone(),
+        ..Default::default()
+    };
+    for article in &current.articles {
+        match before.get(story_key(article)) {
+            None => diff.new.push(story_ref(article)),
+            Some(old) => {
+                let changed = matches!((&old.content, &article.content), (Some(a), Some(b)) if a != b);
+                if article.updateOf.is_some() || changed {
+                    diff.updated.push(story_ref(article));
+                }
+            }
+        }
+    }
+    diff.dropped = previous
+        .articles
+        .iter()
+        .filter(|a| !now.contains_key(story_key(a)))
+        .map(story_ref)
+        .collect();
+    diff
+}
+
+/// The latest of the `earlier` editions (oldest first) carrying an article
+/// with the same URL as `source`, and that article.
+fn latest_with_url<'a>(earlier: &'a [FrontPage], source: &str) -> Option<(&'a FrontPage, &'a AwfulNewsArticle)> {
+    let url = normalize_url(source);
+    earlier.iter().rev().find_map(|edition| {
+        edition
+            .articles
+            .iter()
+            .find(|a| a.source.as_deref().is_some_and(|s| normalize_url(s) == url))
+            .map(|a| (edition, a))
+    })
+}
+
+/// Flag the articles of `current` whose content grew since the latest of
+/// the `earlier` editions (oldest first) carrying the same URL.
+///
+/// Articles already flagged `updateOf` are left alone.
+///
+/// # Returns
+///
+/// The number of articles flagged.
+pub fn mark_developing(earlier: &[FrontPage], current: &mut FrontPage) -> usize {
+    let mut marked = 0;
+    for article in current.articles.iter_mut().filter(|a| a.updateOf.is_none()) {
+        let (Some(source), Some(content)) = (&article.source, &article.content) else {
+            continue;
+        };
+        if let Some((edition, old)) = latest_with_url(earlier, source)
+            && let Some(old_content) = &old.content
+            && content.chars().count() > old_content.chars().count()
+        {
+            debug!(%source, previous = %edition.time_of_day, "Story grew since an earlier edition");
+            article.updateOf = Some(edition.time_of_day.clone());
+            marked += 1;
+        }
+    }
+    marked
+}
+
+/// Parse a `--correction-threshold` value between 0 and 1.
+pub fn parse_correction_threshold(raw: &str) -> Result<f64, String> {
+    let threshold: f64 = raw
+        .trim()
+        .parse()
+        .map_err(|e| format!("invalid threshold `{}`: {}", raw, e))?;
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(format!("threshold must be between 0 and 1, got `{}`", raw));
+    }
+    Ok(threshold)
+}
+
+/// How far apart two summaries are: the share of their distinct words
+/// (lowercased, surrounding punctuation dropped) that only one of them
+/// uses. `0.0` for the same words in any order, `1.0` for none in common.
+pub fn summary_divergence(old: &str, new: &str) -> f64 {
+    let words = |text: &str| -> HashSet<String> {
+        text.split_whitespace()
+            .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase())
+            .filter(|word| !word.is_empty())
+            .collect()
+    };
+    let (old, new) = (words(old), words(new));
+    let union = old.union(&new).count();
+    if union == 0 {
+        return 0.0;
+    }
+    1.0 - old.intersection(&new).count() as f64 / union as f64
+}
+
+/// The model-written fields that differ between two versions of an
+/// article, by their JSON names.
+pub fn changed_fields(old: &AwfulNewsArticle, new: &AwfulNewsArticle) -> Vec<&'static str> {
+    [
+        ("title", old.title != new.title),
+        ("category", old.category != new.category),
+        ("summaryOfNewsArticle", old.summaryOfNewsArticle != new.summaryOfNewsArticle),
+        ("keyTakeAways", old.keyTakeAways != new.keyTakeAways),
+        ("namedEntities", old.namedEntities != new.namedEntities),
+        ("importantDates", old.importantDates != new.importantDates),
+        ("importantTimeframes", old.importantTimeframes != new.importantTimeframes),
+        ("tags", old.tags != new.tags),
+        ("locations", old.locations != new.locations),
+    ]
+    .into_iter()
+    .filter_map(|(field, changed)| changed.then_some(field))
+    .collect()
+}
+
+/// Corrections for the articles of `current` whose summary diverges by more
+/// than `threshold` from the latest of the `earlier` editions (oldest
+/// first) carrying the same URL.
+pub fn find_corrections(earlier: &[FrontPage], current: &FrontPage, threshold: f64) -> Vec<Correction> {
+    current
+        .articles
+        .iter()
+        .filter_map(|article| {
+            let (edition, old) = latest_with_url(earlier, article.source.as_deref()?)?;
+            let divergence = summary_divergence(&old.summaryOfNewsArticle, &article.summaryOfNewsArticle);
+            if divergence <= threshold {
+                return None;
+            }
+            debug!(title = %article.title, previous = %edition.time_of_day, divergence, "Summary diverged from an earlier edition");
+            Some(Correction {
+                story: story_ref(article),
+                earlier_edition: edition.time_of_day.clone(),
+                previous_summary_hash: content_hash(&old.summaryOfNewsArticle),
+                fields: changed_fields(old, article).into_iter().map(str::to_string).collect(),
+            })
+        })
+        .collect()
+}
+
+/// Every edition before `current` written under `json_output_dir` for the
+/// same date, oldest first.
+///
+/// Missing editions are skipped; unreadable or corrupt ones are skipped
+/// with a warning.
+pub async fn load_earlier_editions(json_output_dir: &str, current: &FrontPage) -> Vec<FrontPage> {
+    let Some(editions) = EditionScheme::of_label(&current.time_of_day).map(|s| s.labels()) else {
+        return vec![];
+    };
+    let position = editions.iter().position(|e| *e == current.time_of_day).unwrap_or_default();
+    let date = current.edition_date();
+    let mut earlier = Vec::new();
+    for edition in &editions[..position] {
+        let path = format!("{}/{}/{}.json", json_output_dir, date, edition);
+        match read_edition(&FileSystem, &path).await {
+            Ok(Some(page)) => earlier.push(page),
+            Ok(None) => {}
+            Err(e) => warn!(%path, error = %e, "Unreadable or corrupt earlier edition; skipping it"),
+        }
+    }
+    earlier
+}
+
+/// Load the latest edition before `current` written under `json_output_dir`
+/// for the same date.
+///
+/// `None` for the first edition of the day, or if the previous edition
+/// can't be read or parsed (logged).
+pub async fn load_previous_edition(json_output_dir: &str, current: &FrontPage) -> Option<FrontPage> {
+    let editions = EditionScheme::of_label(&current.time_of_day)?.labels();
+    let position = editions.iter().position(|e| *e == current.time_of_day)?;
+    let date = current.edition_date();
+    for edition in editions[..position].iter().rev() {
+        let path = format!("{}/{}/{}.json", json_output_dir, date, edition);
+        return match read_edition(&FileSystem, &path).await {
+            Ok(Some(previous)) => Some(previous),
+            Ok(None) => continue,
+            Err(e) => {
+                warn!(%path, error = %e, "Unreadable or corrupt previous edition; skipping the edition diff");
+                None
+            }
+        };
+    }
+    debug!(edition = %current.time_of_day, %date, "No earlier edition today");
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn article(source: Option<&str>, title: &str, content: &str) -> AwfulNewsArticle {
+        AwfulNewsArticle {
+            source: source.map(str::to_string),
+            summaryOfNewsArticle: format!("{} summary.", title),
+            content: Some(content.into()),
+            ..test_support::article(title, "World News")
+        }
+    }
+
+    fn edition(time_of_day: &str, articles: Vec<AwfulNewsArticle>) -> FrontPage {
+        FrontPage {
+            articles,
+            ..test_support::front_page(time_of_day, "18:00:00")
+        }
+    }
+
+    fn titles(refs: &[StoryRef]) -> Vec<&str> {
+        refs.iter().map(|r| r.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_diff_lists_new_updated_and_dropped_stories() {
+        let morning = edition(
+            "morning",
+            vec![
+                article(Some("https://lite.cnn.com/talks"), "Talks begin", "Day one."),
+                article(Some("https://text.npr.org/probe"), "Probe launched", "Liftoff."),
+                article(Some("https://apnews.com/article/storm"), "Storm nears", "Winds rise."),
+                article(None, "Wire brief", "Brief."),
+            ],
+        );
+        let mut flagged = article(Some("https://apnews.com/article/storm"), "Storm nears coast", "Winds rise.");
+        flagged.updateOf = Some("morning".to_string());
+        let afternoon = edition(
+            "afternoon",
+            vec![
+                // Reworded title and new content under the same URL
+                article(Some("https://lite.cnn.com/talks"), "Talks stall", "Day one. Then a pause."),
+                article(Some("https://text.npr.org/probe"), "Probe launched", "Liftoff."),
+                flagged,
+                article(Some("https://www.bbc.com/news/vote"), "Vote called", "Ballots."),
+            ],
+        );
+
+        let diff = diff_editions(&morning, &afternoon);
+        assert_eq!(diff.previous_edition, "morning");
+        assert_eq!(titles(&diff.new), vec!["Vote called"]);
+        assert_eq!(titles(&diff.updated), vec!["Talks stall", "Storm nears coast"]);
+        assert_eq!(titles(&diff.dropped), vec!["Wire brief"]);
+        assert_eq!(diff.dropped[0].source, None);
+
+        // Stored as the edition's `diff` block
+        let page = FrontPage {
+            diff: Some(diff),
+            ..afternoon
+        };
+        let json = serde_json::to_value(&page).unwrap();
+        assert_eq!(json["diff"]["new"][0]["source"], "https://www.bbc.com/news/vote");
+        assert!(json["diff"]["dropped"][0].get("source").is_none());
+    }
+
+    #[test]
+    fn test_diff_of_identical_editions_is_empty() {
+        let articles = || vec![article(Some("https://text.npr.org/probe"), "Probe launched", "Liftoff.")];
+        let diff = diff_editions(&edition("afternoon", articles()), &edition("evening", articles()));
+        assert_eq!(
+            diff,
+            EditionDiff {
+                previous_edition: "afternoon".to_string(),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_grown_story_is_marked_updated() {
+        let dir = tempfile::tempdir().unwrap();
+        let json_dir = dir.path().to_str().unwrap();
+        let live = "https://www.aljazeera.com/news/liveblog/2025/5/6/live";
+        let morning = edition(
+            "morning",
+            vec![
+                article(Some(live), "Live: talks begin", "Delegations arrive."),
+                article(Some("https://text.npr.org/probe"), "Probe launched", "Liftoff and a long first burn."),
+            ],
+        );
+        std::fs::create_dir_all(dir.path().join("2025-05-06")).unwrap();
+        std::fs::write(dir.path().join("2025-05-06/morning.json"), serde_json::to_string(&morning).unwrap()).unwrap();
+
+        let mut evening = edition(
+            "evening",
+            vec![
+                article(Some(&format!("{}?utm_source=feed", live)), "Live: talks stall", "Delegations arrive. Talks stall by noon."),
+                article(Some("https://text.npr.org/probe"), "Probe launched", "Liftoff."),
+                article(Some("https://lite.cnn.com/vote"), "Vote called", "Ballots."),
+            ],
+        );
+        let earlier = load_earlier_editions(json_dir, &evening).await;
+        assert_eq!(earlier.len(), 1);
+        assert_eq!(mark_developing(&earlier, &mut evening), 1);
+        assert_eq!(evening.articles[0].updateOf.as_deref(), Some("morning"));
+        // Shorter content and stories new today are not updates
+        assert!(evening.articles[1..].iter().all(|a| a.updateOf.is_none()));
+
+        let md = crate::outputs::markdown::front_page_to_markdown(&evening, &Default::default());
+        assert!(md.contains("- <small>`Updated`</small> _since the Morning edition_"));
+    }
+
+    #[test]
+    fn test_summary_divergence_and_changed_fields() {
+        assert_eq!(summary_divergence("Talks resume in Doha.", "talks RESUME in doha"), 0.0);
+        assert_eq!(summary_divergence("Talks resume.", "Ceasefire collapses."), 1.0);
+        assert_eq!(summary_divergence("", ""), 0.0);
+        // 2 shared words out of 4
+        assert_eq!(summary_divergence("talks resume", "talks resume again today"), 0.5);
+
+        let old = article(Some("https://lite.cnn.com/talks"), "Talks resume", "Day one.");
+        assert!(changed_fields(&old, &old.clone()).is_empty());
+        let mut new = old.clone();
+        new.summaryOfNewsArticle = "Talks collapsed.".to_string();
+        new.keyTakeAways = vec!["No deal".to_string()];
+        new.content = Some("Day two.".into());
+        assert_eq!(changed_fields(&old, &new), vec!["summaryOfNewsArticle", "keyTakeAways"]);
+    }
+
+    #[test]
+    fn test_diverging_summary_records_a_correction() {
+        let talks = "https://lite.cnn.com/talks";
+        let probe = "https://text.npr.org/probe";
+        let mut first = article(Some(talks), "Talks resume", "Day one.");
+        first.summaryOfNewsArticle = "Negotiators agreed to resume talks in Doha on Tuesday.".to_string();
+        let earlier = vec![edition("morning", vec![first, article(Some(probe), "Probe launched", "Liftoff.")])];
+        let mut revised = article(Some(talks), "Talks resume", "Day one.");
+        revised.summaryOfNewsArticle = "The ceasefire collapsed overnight and both delegations left.".to_string();
+        let mut reworded = article(Some(probe), "Probe launched", "Liftoff.");
+        reworded.summaryOfNewsArticle = "Probe launched summary!".to_string();
+        let mut afternoon = edition(
+            "afternoon",
+            vec![revised, reworded, article(Some("https://lite.cnn.com/vote"), "Vote called", "Ballots.")],
+        );
+
+        afternoon.corrections = find_corrections(&earlier, &afternoon, DEFAULT_CORRECTION_THRESHOLD);
+        assert_eq!(
+            afternoon.corrections,
+            vec![Correction {
+                story: story_ref(&afternoon.articles[0]),
+                earlier_edition: "morning".to_string(),
+                previous_summary_hash: content_hash(&earlier[0].articles[0].summaryOfNewsArticle),
+                fields: vec!["summaryOfNewsArticle".to_string()],
+            }]
+        );
+        // A threshold of 1 never records a correction
+        assert!(find_corrections(&earlier, &afternoon, 1.0).is_empty());
+
+        let json = serde_json::to_value(&afternoon).unwrap();
+        assert_eq!(json["corrections"][0]["earlier_edition"], "morning");
+        let md = crate::outputs::markdown::front_page_to_markdown(&afternoon, &Default::default());
+        assert_eq!(md.matches("- <small>`Corrected`</small> _from the Morning edition_").count(), 1);
+
+        assert_eq!(parse_correction_threshold("0.3"), Ok(0.3));
+        assert!(parse_correction_threshold("1.5").is_err());
+        assert!(parse_correction_threshold("half").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_previous_edition_is_the_latest_earlier_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let json_dir = dir.path().to_str().unwrap();
+        let date_dir = dir.path().join("2025-05-06");
+        std::fs::create_dir_all(&date_dir).unwrap();
+        let morning = edition("morning", vec![article(None, "Early", "x")]);
+        std::fs::write(date_dir.join("morning.json"), serde_json::to_string(&morning).unwrap()).unwrap();
+
+        // First edition of the day
+        assert!(load_previous_edition(json_dir, &morning).await.is_none());
+        // No afternoon run: evening falls back to the morning
+        let evening = edition("evening", vec![]);
+        let previous = load_previous_edition(json_dir, &evening).await.unwrap();
+        assert_eq!(previous.time_of_day, "morning");
+
+        // A corrupt afternoon edition is skipped rather than failing the run
+        std::fs::write(date_dir.join("afternoon.json"), "{\"local_date\": ").unwrap();
+        assert!(load_previous_edition(json_dir, &evening).await.is_none());
+    }
+}
@@ -0,0 +1,361 @@
+//! Live smoke test of every scraper for the `doctor` subcommand.
+//!
+//! Meant to run shortly before the scheduled editions: on top of the
+//! `--validate-only` preflight checks (see [`crate::validate`]) it takes
+//! each scraper in [`SCRAPERS`] through a real index and one real article
+//! fetch, and reports:
+//!
+//! | Check | Fails when | Warns when |
+//! |-------|------------|------------|
+//! | index | indexing errors, or fewer than `--min-urls` URLs | |
+//! | article | the first URL can't be fetched, or has under [`MIN_CONTENT_CHARS`] of text | no title or date was extracted; NYT served the API abstract |
+//!
+//! With `--llm` it also sends the LLM one tiny prompt. NYT without
+//! an API key is reported as a warning and not contacted.
+
+use crate::api::{self, AdaptiveConcurrency};
+use crate::cli::{Cli, DoctorArgs};
+use crate::models::NewsArticle;
+use crate::scrapers::budget::ArticleSink;
+use crate::scrapers::{self, ScraperInfo, SCRAPERS};
+use crate::skips::SkipLog;
+use crate::validate::{self, Report, Status};
+use awful_aj::{config, template};
+use std::error::Error;
+use std::time::Instant;
+
+/// Default for `doctor --min-urls`.
+pub const DEFAULT_MIN_URLS: usize = 5;
+
+/// Least article text, in characters, that counts as a real article.
+pub const MIN_CONTENT_CHARS: usize = 500;
+
+/// Prompt of the `doctor --llm` round trip.
+const LLM_PROMPT: &str = "Reply with the single word OK.";
+
+/// What one source produced: its index, and the first indexed article.
+#[derive(Debug)]
+pub struct Sample {
+    /// Number of URLs indexed.
+    pub indexed: usize,
+    /// The first article, or why it couldn't be fetched. `None` if nothing
+    /// was indexed.
+    pub article: Option<Result<NewsArticle, String>>,
+}
+
+/// A source the doctor can sample.
+pub trait Source {
+    /// Name shown in the report.
+    fn name(&self) -> &str;
+
+    /// Index the source and fetch its first article.
+    async fn sample(&self) -> Result<Sample, Box<dyn Error>>;
+}
+
+/// A registered scraper, run against the live site.
+#[derive(Debug, Clone, Copy)]
+pub struct LiveSource<'a> {
+    pub info: &'static ScraperInfo,
+    pub nyt_api_key: Option<&'a str>,
+}
+
+/// Fetch `url` through a scraper's `fetch_articles`.
+async fn fetch_first<F, Fut>(url: &str, fetch: F) -> Result<NewsArticle, String>
+where
+    F: FnOnce(ArticleSink, SkipLog) -> Fut,
+    Fut: std::future::Future<Output = (ArticleSink, SkipLog)>,
+{
+    let (sink, skips) = fetch(ArticleSink::default(), SkipLog::default()).await;
+    if let Some(article) = sink.into_inner().unwrap().pop() {
+        return Ok(article);
+    }
+    Err(match skips.entries().first() {
+        Some(skip) => format!("{} was skipped ({})", url, skip.reason.as_str()),
+        None => format!("{} produced no article", url),
+    })
+}
+
+impl Source for LiveSource<'_> {
+    fn name(&self) -> &str {
+        self.info.name
+    }
+
+    async fn sample(&self) -> Result<Sample, Box<dyn Error>> {
+        use scrapers::{aljazeera, apnews, bbcnews, cnn, npr, nyt, wapo};
+
+        if self.info.name == "nyt" {
+            let stories = nyt::index_articles(self.nyt_api_key).await?;
+            let article = match stories.first() {
+                Some(story) => Some(
+                    fetch_first(&story.url, |sink, skips| async move {
+                        nyt::fetch_articles(vec![story.clone()], &sink, &skips).await;
+                        (sink, skips)
+                    })
+                    .await,
+                ),
+                None => None,
+            };
+            return Ok(Sample { indexed: stories.len(), article });
+        }
+
+        let urls = match self.info.name {
+            "cnn" => cnn::index_articles().await?,
+            "npr" => npr::index_articles().await?,
+            "apnews" => apnews::index_articles().await?,
+            "aljazeera" => aljazeera::index_articles().await?,
+            "bbcnews" => bbcnews::index_articles().await?,
+            "wapo" => wapo::index_articles().await?,
+            other => return Err(format!("no doctor support for scraper {}", other).into()),
+        };
+        let article = match urls.first() {
+            Some(url) => Some(
+                fetch_first(url, |sink, skips| async move {
+                    let urls = vec![url.clone()];
+                    match self.info.name {
+                        "cnn" => cnn::fetch_articles(urls, &sink, &skips).await,
+                        "npr" => npr::fetch_articles(urls, &sink, &skips).await,
+                        "apnews" => apnews::fetch_articles(urls, &sink, &skips).await,
+                        "aljazeera" => aljazeera::fetch_articles(urls, &sink, &skips).await,
+                        "wapo" => wapo::fetch_articles(urls, &sink, &skips).await,
+                        _ => bbcnews::fetch_articles(urls, &sink, &skips).await,
+                    }
+                    (sink, skips)
+                })
+                .await,
+            ),
+            None => None,
+        };
+        Ok(Sample { indexed: urls.len(), article })
+    }
+}
+
+/// Lines of `content` after the `Published...` lines the scrapers prepend.
+fn body_lines(content: &str) -> impl Iterator<Item = &str> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("Published"))
+}
+
+/// Whether the scraper put a headline in `content`: a `Title:` or `# `
+/// line, or a short first line that doesn't read like a sentence.
+fn has_title(content: &str) -> bool {
+    let mut lines = body_lines(content);
+    let Some(first) = lines.next() else {
+        return false;
+    };
+    first.starts_with("Title:")
+        || first.starts_with("# ")
+        || (first.chars().count() <= 200 && !first.ends_with('.'))
+}
+
+/// Whether a publication date was extracted.
+fn has_date(article: &NewsArticle) -> bool {
+    article.published_at.is_some() || article.content.lines().any(|line| line.starts_with("Published"))
+}
+
+/// Sample `source` and add its index and article checks to `report`.
+pub async fn check_source(report: &mut Report, source: &impl Source, min_urls: usize) {
+    let name = source.name().to_string();
+    let started = Instant::now();
+    let sample = match source.sample().await {
+        Ok(sample) => sample,
+        Err(e) => {
+            report.push(format!("{} index", name), Status::Fail, format!("indexing failed: {}", e));
+            return;
+        }
+    };
+    let elapsed = format!("{:.1}s", started.elapsed().as_secs_f64());
+
+    let index_status = if sample.indexed >= min_urls { Status::Ok } else { Status::Fail };
+    report.push(
+        format!("{} index", name),
+        index_status,
+        format!("{} URLs (need {})", sample.indexed, min_urls),
+    );
+
+    let (status, detail) = match sample.article {
+        None => (Status::Fail, "nothing indexed to fetch".to_string()),
+        Some(Err(e)) => (Status::Fail, e),
+        Some(Ok(article)) => {
+            let chars = article.content.chars().count();
+            let mut missing = Vec::new();
+            if !has_title(&article.content) {
+                missing.push("title");
+            }
+            if !has_date(&article) {
+                missing.push("date");
+            }
+            let abstract_only = article.content.starts_with(scrapers::nyt::ABSTRACT_ONLY_NOTE);
+            let mut detail = format!("{} chars from {} in {}", chars, article.source, elapsed);
+            if !missing.is_empty() {
+                detail.push_str(&format!("; no {} extracted", missing.join(" or ")));
+            }
+            if abstract_only {
+                detail.push_str("; served from the API abstract");
+            }
+            let status = if chars < MIN_CONTENT_CHARS && !abstract_only {
+                detail.push_str(&format!(" (need {})", MIN_CONTENT_CHARS));
+                Status::Fail
+            } else if !missing.is_empty() || abstract_only {
+                Status::Warn
+            } else {
+                Status::Ok
+            };
+            (status, detail)
+        }
+    };
+    report.push(format!("{} article", name), status, detail);
+}
+
+/// Send the LLM [`LLM_PROMPT`] once through the `news_parser` template.
+async fn check_llm(report: &mut Report, args: &Cli) {
+    let result: Result<String, Box<dyn Error>> = async {
+        let template = template::load_template("news_parser").await?;
+        let config = config::load_config(&args.config_path()?.to_string_lossy())?;
+        let started = Instant::now();
+        let auth = args.llm_auth();
+        let reply = api::ask_once(&config, LLM_PROMPT, &template, auth.as_ref(), &AdaptiveConcurrency::new(1)).await?;
+        Ok(format!(
+            "{} replied with {} chars in {:.1}s",
+            config.model,
+            reply.chars().count(),
+            started.elapsed().as_secs_f64()
+        ))
+    }
+    .await;
+    match result {
+        Ok(detail) => report.push("LLM round trip", Status::Ok, detail),
+        Err(e) => report.push("LLM round trip", Status::Fail, e.to_string()),
+    }
+}
+
+/// Run the preflight checks, then smoke-test every scraper (and the LLM
+/// with `--llm`).
+pub async fn run(args: &Cli, options: &DoctorArgs) -> Report {
+    let mut report = validate::run(args).await;
+    if options.llm {
+        check_llm(&mut report, args).await;
+    }
+    let nyt_api_key = args.nyt_api_key.as_deref().filter(|key| !key.trim().is_empty());
+    for info in SCRAPERS {
+        if info.name == "nyt" && nyt_api_key.is_none() {
+            report.push("nyt index", Status::Warn, "skipped: no NYT API key");
+            continue;
+        }
+        check_source(&mut report, &LiveSource { info, nyt_api_key }, options.min_urls).await;
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+
+    /// A scraper in fixture mode: a canned index and article.
+    struct Fixture {
+        indexed: usize,
+        article: Option<Result<NewsArticle, String>>,
+        fails: bool,
+    }
+
+    impl Source for Fixture {
+        fn name(&self) -> &str {
+            "fixture"
+        }
+
+        async fn sample(&self) -> Result<Sample, Box<dyn Error>> {
+            if self.fails {
+                return Err("connection refused".into());
+            }
+            Ok(Sample {
+                indexed: self.indexed,
+                article: self.article.clone(),
+            })
+        }
+    }
+
+    fn article(content: &str, dated: bool) -> NewsArticle {
+        NewsArticle {
+            source: "https://lite.cnn.com/2025/05/06/talks".to_string(),
+            content: content.into(),
+            published_at: dated.then(|| DateTime::parse_from_rfc3339("2025-05-06T08:00:00+00:00").unwrap()),
+            authors: vec![],
+            fetched_at: None,
+            fetched_from: None,
+        }
+    }
+
+    fn full_text() -> String {
+        format!("Talks resume in Geneva\n{}", "Negotiators met again on Tuesday. ".repeat(20))
+    }
+
+    async fn statuses(fixture: Fixture) -> Vec<(String, Status)> {
+        let mut report = Report::default();
+        check_source(&mut report, &fixture, 5).await;
+        report.checks.into_iter().map(|c| (c.name, c.status)).collect()
+    }
+
+    #[tokio::test]
+    async fn test_healthy_source_passes() {
+        let fixture = Fixture {
+            indexed: 12,
+            article: Some(Ok(article(&full_text(), true))),
+            fails: false,
+        };
+        assert_eq!(
+            statuses(fixture).await,
+            vec![("fixture index".to_string(), Status::Ok), ("fixture article".to_string(), Status::Ok)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_broken_sources_fail() {
+        let index_error = Fixture { indexed: 0, article: None, fails: true };
+        assert_eq!(statuses(index_error).await, vec![("fixture index".to_string(), Status::Fail)]);
+
+        let few_urls = Fixture {
+            indexed: 2,
+            article: Some(Ok(article(&full_text(), true))),
+            fails: false,
+        };
+        assert_eq!(statuses(few_urls).await[0].1, Status::Fail);
+
+        let skipped = Fixture {
+            indexed: 12,
+            article: Some(Err("https://lite.cnn.com/a was skipped (fetch_error)".to_string())),
+            fails: false,
+        };
+        assert_eq!(statuses(skipped).await[1].1, Status::Fail);
+
+        let short = Fixture {
+            indexed: 12,
+            article: Some(Ok(article("Talks resume\nNegotiators met.", true))),
+            fails: false,
+        };
+        assert_eq!(statuses(short).await[1].1, Status::Fail);
+
+        let nothing_indexed = Fixture { indexed: 0, article: None, fails: false };
+        assert_eq!(statuses(nothing_indexed).await[1].1, Status::Fail);
+    }
+
+    #[tokio::test]
+    async fn test_missing_title_or_date_warns() {
+        let undated = Fixture {
+            indexed: 12,
+            article: Some(Ok(article(&full_text(), false))),
+            fails: false,
+        };
+        let mut report = Report::default();
+        check_source(&mut report, &undated, 5).await;
+        assert_eq!(report.checks[1].status, Status::Warn);
+        assert!(report.checks[1].detail.contains("no date extracted"));
+        assert!(report.passed());
+
+        let body_only = "Negotiators met again on Tuesday. ".repeat(20);
+        assert!(!has_title(&body_only));
+        assert!(has_title("Published: 2025-05-06T08:00:00+00:00\n\nTitle: Talks resume\n\nBody."));
+        assert!(has_date(&article("Published(raw): May 6\n\nBody.", false)));
+    }
+}
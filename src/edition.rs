@@ -0,0 +1,843 @@
+//! The processing half of a run: turn fetched articles into an edition.
+//!
+//! [`process`] takes the articles of [`crate::fetch`], fetched just now by
+//! the default `run` or read back from a raw dump by `process`, together
+//! with the edition they were fetched for, and runs every stage after
+//! fetching:
+//!
+//! 1. History: articles unchanged since an earlier edition of the day are
+//!    skipped, updated ones are marked, and with `--carry-over-failures`
+//!    the previous run's failures are queued again
+//! 2. [`summarize`]: every article goes to the LLM (see [`crate::pipeline`]),
+//!    and each one summarized is recorded in the history, whether or not the
+//!    edition filters keep it
+//! 3. [`assemble`]: the summaries are filtered, sorted, capped, scored and
+//!    compared with the day's earlier editions
+//! 4. [`write_outputs`]: JSON, Markdown, mirrors, indexes and the book
+//!
+//! It then saves the history, hands the edition to the message bus and
+//! Mastodon, and reports the run.
+//!
+//! Summarized articles share their text with the scraped ones (see
+//! [`crate::pipeline`]), and are streamed out as they complete with
+//! `--incremental-markdown` and `--journal`. Once the LLM stage is done the
+//! scraped articles are dropped, but for failures kept to carry over.
+
+use crate::api::{self, BasicAuth, LevelChange, TemplateContext};
+use crate::carryover::{self, FailureList};
+use crate::cli::Cli;
+use crate::concurrency::ModelConcurrency;
+use crate::diff;
+use crate::editorial;
+use crate::fetch::Fetched;
+use crate::filters::{self, EditionFilter, Filtered};
+use crate::memory;
+use crate::models::{AwfulNewsArticle, FrontPage, NewsArticle, RunSummary, SkipReason};
+use crate::ordering;
+use crate::outputs::journal::Journal;
+use crate::outputs::partial::PartialMarkdown;
+use crate::outputs::sink::OutputSink;
+use crate::outputs::{book, indexes, json, markdown, plaintext};
+use crate::pipeline::{self, ProcessContext};
+use crate::progress::Progress;
+use crate::report;
+use crate::salience;
+use crate::schema::{self, ArticleField};
+use crate::scrapers;
+use crate::skips::{self, SkipLog};
+use crate::social;
+use crate::state::{self, HistoryState, UpdateDecision};
+use crate::stats;
+use crate::top_stories;
+use crate::utils::{content_hash, write_with_fallback, Fallback, Written};
+use crate::{publish, publish_error, publish_info};
+use awful_aj::config::{self, AwfulJadeConfig};
+use awful_aj::template::{self, ChatTemplate};
+use chrono::Utc;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+/// What every stage of a run shares.
+pub struct Run<'a, S: OutputSink> {
+    pub args: &'a Cli,
+    /// Every edition, index and registry file is stored through this sink
+    pub sink: &'a S,
+    /// The writable `--json-mirror-dir` directories
+    pub json_mirrors: &'a [String],
+    /// Every article left out of the edition, with the reason
+    pub skips: &'a SkipLog,
+    /// When the run started; `--max-run-time` counts from here
+    pub started: Instant,
+}
+
+/// The model, prompt template and credentials of every LLM call of a run.
+pub struct LlmSetup {
+    pub config: AwfulJadeConfig,
+    pub template: ChatTemplate,
+    pub auth: Option<BasicAuth>,
+}
+
+impl LlmSetup {
+    /// Load the `news_parser` template and the config named by `args`.
+    pub async fn load(args: &Cli) -> Result<Self, Box<dyn Error>> {
+        let template = template::load_template("news_parser").await?;
+        info!("Loaded template: news_parser");
+        let conf_file = args.config_path()?;
+        let config_path = conf_file.to_str().expect("Not a valid config filename");
+        let config = config::load_config(config_path)?;
+        info!(config_path, "Loaded configuration");
+        Self::new(args, config, template)
+    }
+
+    /// Restrict `template` to `--extract-fields` and pick up the basic auth
+    /// credentials of `args`.
+    pub fn new(args: &Cli, config: AwfulJadeConfig, mut template: ChatTemplate) -> Result<Self, Box<dyn Error>> {
+        if args.extract_fields.len() < ArticleField::ALL.len() {
+            schema::restrict_template(&mut template, &args.extract_fields)?;
+        }
+        let auth = args.llm_auth();
+        if let Some(auth) = &auth {
+            info!(username = %auth.username, "Using basic auth for LLM endpoint");
+        }
+        Ok(Self { config, template, auth })
+    }
+}
+
+/// Summarize `fetched` into `front_page` and write the edition.
+///
+/// # Returns
+///
+/// Where the edition was written instead, if its JSON or Markdown only
+/// made it to `--fallback-output-dir`.
+pub async fn process(
+    run: &Run<'_, impl OutputSink>,
+    llm: &LlmSetup,
+    mut front_page: FrontPage,
+    fetched: Fetched,
+    mut history: Option<HistoryState>,
+) -> Result<Option<String>, Box<dyn Error>> {
+    let args = run.args;
+    let skips = run.skips;
+    // Heap use from here on is what this edition costs
+    let allocated_before = memory::ALLOCATED.reset_peak();
+    let Fetched {
+        articles,
+        runs,
+        deduped_same_source,
+        deduped_identical,
+    } = fetched;
+
+    // ---- Skip or mark articles already processed earlier today ----
+    let (mut articles, update_of) = match &history {
+        Some(history) => apply_history(history, articles, &front_page.local_date, skips),
+        None => (articles, HashMap::new()),
+    };
+
+    // ---- Retry articles that failed in the previous run ----
+    let carry_over = CarryOver::load(args, &mut articles).await;
+
+    let summarized = summarize(run, llm, &front_page, &articles, &update_of, &carry_over.carried).await;
+    let total_articles = articles.len();
+    front_page.articles = summarized.articles;
+    let successful_count = front_page.articles.len();
+    // ---- Remember processed URLs for the next edition ----
+    // Before the edition filters, so articles they drop aren't summarized again
+    if let Some(history) = history.as_mut() {
+        for article in &front_page.articles {
+            if let (Some(source), Some(content)) = (&article.source, &article.content) {
+                history.record(source, &content_hash(content), &front_page.local_date, &front_page.time_of_day);
+            }
+        }
+    }
+    // Sources the model summarized, whether or not --region keeps them
+    let processed: HashSet<&str> = front_page.articles.iter().filter_map(|a| a.source.as_deref()).collect();
+    // Only failures to carry over still need their scraped article
+    let failed: Vec<NewsArticle> = if carry_over.path.is_some() {
+        articles.into_iter().filter(|a| !processed.contains(a.source.as_str())).collect()
+    } else {
+        Vec::new()
+    };
+
+    #[allow(unused_variables)]
+    let (edition_filter, filtered_out) = assemble(args, llm, skips, &mut front_page).await;
+
+    let failed_count = total_articles - successful_count;
+    info!(
+        total = total_articles,
+        successful = successful_count,
+        failed = failed_count,
+        skipped = ?skips.counts(),
+        "Completed parallel article processing"
+    );
+    let concurrency_history = summarized.concurrency;
+    let start_concurrency = concurrency_history.first().map_or(0, |c| c.level);
+    let min_concurrency = concurrency_history.iter().map(|c| c.level).min().unwrap_or(start_concurrency);
+    let final_concurrency = summarized.final_concurrency;
+    info!(
+        start = start_concurrency,
+        min = min_concurrency,
+        end = final_concurrency,
+        changes = concurrency_history.len().saturating_sub(1),
+        history = ?concurrency_history,
+        "Adaptive concurrency over the run"
+    );
+
+    // Remember this run's failures for the next run to retry
+    carry_over.save(&failed).await;
+
+    let peak_additional_bytes = memory::ALLOCATED.peak().saturating_sub(allocated_before);
+    info!(peak_additional_bytes, "Peak heap use of the edition");
+
+    publish_info!(
+        "awful_text_news",
+        event_kind = "processing.completed",
+        total_articles = total_articles,
+        successful = successful_count,
+        failed = failed_count,
+        min_concurrency = min_concurrency,
+        final_concurrency = final_concurrency,
+        concurrency_changes = &concurrency_history,
+        skipped = skips.counts(),
+        peak_additional_bytes = peak_additional_bytes,
+        filters = &edition_filter,
+        filtered_out = &filtered_out,
+        "Article processing completed"
+    );
+
+    if front_page.articles.is_empty() {
+        warn!(total = total_articles, "No articles survived processing; writing placeholder edition");
+        publish_info!(
+            "awful_text_news",
+            event_kind = "processing.empty",
+            total_articles = total_articles,
+            "No articles available for this edition"
+        );
+    }
+
+    let run_summary = RunSummary {
+        analyzed: total_articles,
+        succeeded: successful_count,
+        failed: failed_count,
+        skipped: skips.counts(),
+        peak_additional_bytes,
+    };
+    let outputs = write_outputs(run, &front_page, &run_summary, summarized.partial_markdown).await?;
+
+    // ---- Save the history for the next edition ----
+    if let (Some(history), Some(path)) = (&history, args.state_file.as_deref())
+        && let Err(e) = history.save(path).await
+    {
+        error!(path, error = %e, "Failed to save history state");
+    }
+
+    // ---- Hand the finished edition to downstream consumers ----
+    publish::publish_edition(
+        &args.edition_event_key,
+        &front_page,
+        &outputs.written_files,
+        !args.no_edition_payload,
+    );
+    social::post_digest(args.mastodon_instance.as_deref(), args.mastodon_token.as_deref(), &front_page).await;
+
+    if let Some(path) = &args.skip_report {
+        skips::write_report(skips, path).await;
+    }
+
+    let elapsed = run.started.elapsed();
+    info!(
+        ?elapsed,
+        secs = elapsed.as_secs(),
+        millis = elapsed.subsec_millis(),
+        "Execution complete"
+    );
+
+    publish_info!(
+        "awful_text_news",
+        event_kind = "application.completed",
+        duration_secs = elapsed.as_secs(),
+        duration_millis = elapsed.subsec_millis(),
+        articles_processed = successful_count,
+        articles_failed = failed_count,
+        articles_skipped = skips.total(),
+        skipped = skips.counts(),
+        edition = front_page.time_of_day.clone(),
+        date = front_page.edition_date(),
+        "Application completed successfully"
+    );
+
+    if args.print_stats {
+        let sources = scrapers::SCRAPERS
+            .iter()
+            .map(|scraper| {
+                let counts = runs.get(scraper.name).map(|run| report::SourceCounts {
+                    indexed: run.indexed,
+                    fetched: run.fetched,
+                    processed: front_page
+                        .articles
+                        .iter()
+                        .filter(|a| a.source_tag().as_deref() == Some(scraper.tag))
+                        .count(),
+                });
+                (scraper.name, counts)
+            })
+            .collect();
+        report::print(&report::RunReport {
+            sources,
+            failed: failed_count,
+            skipped: skips.counts(),
+            deduped_same_source,
+            deduped_identical,
+            elapsed,
+            outputs: &outputs.written_files,
+        });
+    }
+
+    Ok(outputs.fallback_used)
+}
+
+/// Skip articles unchanged since an earlier edition of `date`, and map
+/// updated ones to the edition that first carried them.
+fn apply_history(
+    history: &HistoryState,
+    articles: Vec<NewsArticle>,
+    date: &str,
+    skips: &SkipLog,
+) -> (Vec<NewsArticle>, HashMap<String, String>) {
+    let mut update_of = HashMap::new();
+    let before = articles.len();
+    let mut kept = Vec::with_capacity(before);
+    for article in articles {
+        let hash = content_hash(&article.content);
+        match state::decide(history, &article.source, &hash, date) {
+            UpdateDecision::New => kept.push(article),
+            UpdateDecision::Unchanged => {
+                debug!(source = %article.source, "Unchanged since an earlier edition today; skipping");
+                skips.record(&article.source, SkipReason::Duplicate);
+            }
+            UpdateDecision::Updated { previous_edition } => {
+                info!(source = %article.source, %previous_edition, "Article updated since an earlier edition");
+                update_of.insert(article.source.clone(), previous_edition);
+                kept.push(article);
+            }
+        }
+    }
+    info!(
+        skipped = before - kept.len(),
+        updated = update_of.len(),
+        remaining = kept.len(),
+        "Applied history state"
+    );
+    (kept, update_of)
+}
+
+/// The previous run's failures, under `--carry-over-failures`.
+struct CarryOver {
+    /// Where failures are kept between runs; `None` when not carrying over
+    path: Option<PathBuf>,
+    previous: FailureList,
+    /// Sources queued again this run
+    carried: HashSet<String>,
+}
+
+impl CarryOver {
+    /// Queue the recent failures of the previous run into `articles`.
+    async fn load(args: &Cli, articles: &mut Vec<NewsArticle>) -> Self {
+        let path = args
+            .state_file
+            .as_deref()
+            .filter(|_| args.carry_over_failures)
+            .map(carryover::failures_path);
+        let (previous, carried) = match &path {
+            Some(path) => {
+                let previous = FailureList::load(path).await;
+                let carried = carryover::merge_into_queue(articles, previous.recent(Utc::now()));
+                (previous, carried)
+            }
+            None => (FailureList::default(), HashSet::new()),
+        };
+        Self { path, previous, carried }
+    }
+
+    /// Save the `failed` articles for the next run.
+    async fn save(&self, failed: &[NewsArticle]) {
+        let Some(path) = &self.path else { return };
+        let failed: Vec<&NewsArticle> = failed.iter().collect();
+        let failures = FailureList::from_failed(&failed, &self.previous, Utc::now());
+        match failures.save(path).await {
+            Ok(()) => debug!(path = %path.display(), failed = failed.len(), "Saved failure list"),
+            Err(e) => error!(path = %path.display(), error = %e, "Failed to save failure list"),
+        }
+    }
+}
+
+/// The LLM stage's results.
+struct Summarized {
+    /// Summarized articles, in completion order
+    articles: Vec<AwfulNewsArticle>,
+    /// The `--incremental-markdown` copy, finished once the edition is out
+    partial_markdown: Option<PartialMarkdown>,
+    /// Concurrency levels over the stage, starting with the initial one
+    concurrency: Vec<LevelChange>,
+    /// The concurrency level the stage ended at
+    final_concurrency: usize,
+}
+
+/// Send `articles` to the LLM in parallel, limited per model and shrunk
+/// while the backend is under stress, until `--max-run-time` runs out.
+async fn summarize(
+    run: &Run<'_, impl OutputSink>,
+    llm: &LlmSetup,
+    front_page: &FrontPage,
+    articles: &[NewsArticle],
+    update_of: &HashMap<String, String>,
+    carried_over: &HashSet<String>,
+) -> Summarized {
+    let args = run.args;
+    let model_concurrency = ModelConcurrency::new(args.default_model_concurrency, &args.model_concurrency);
+    let parallel_batch_size = model_concurrency.total_for([llm.config.model.as_str()]);
+    // Shrinks the articles in flight while the backend is under stress
+    let adaptive = api::AdaptiveConcurrency::new(parallel_batch_size);
+
+    let total_articles = articles.len();
+    info!(
+        parallel_batch_size,
+        model = %llm.config.model,
+        model_limit = model_concurrency.limit(&llm.config.model),
+        "Starting parallel article processing"
+    );
+
+    publish_info!(
+        "awful_text_news",
+        event_kind = "processing.started",
+        total_articles = total_articles,
+        batch_size = parallel_batch_size,
+        "Starting article processing"
+    );
+
+    let progress = Progress::new(args.progress, total_articles);
+    let template_vars = TemplateContext {
+        current_date: front_page.local_date.clone(),
+        time_of_day: front_page.time_of_day.clone(),
+        source: String::new(),
+        summary_words: args.summary_words,
+    };
+    // Shared by reference across the concurrent tasks
+    let ctx = ProcessContext {
+        config: &llm.config,
+        template: &llm.template,
+        template_vars: &template_vars,
+        auth: llm.auth.as_ref(),
+        update_of,
+        carried_over,
+        extract_fields: &args.extract_fields,
+        reask: !args.no_reask,
+        reask_with_retries: args.reask_with_retries,
+        input_header: args.llm_input_header,
+        limits: args.article_limits(),
+        concurrency: &model_concurrency,
+        adaptive: &adaptive,
+        progress: &progress,
+        skips: run.skips,
+    };
+    // The whole run's wall-clock budget, counted from startup
+    let deadline = args.max_run_time.map(|secs| run.started + Duration::from_secs(secs));
+    let partial_markdown = if args.incremental_markdown {
+        match PartialMarkdown::create(&args.markdown_output_dir, front_page, args.markdown_options()).await {
+            Ok(partial) => {
+                info!(path = %partial.path().display(), "Writing partial Markdown as articles complete");
+                Some(partial)
+            }
+            Err(e) => {
+                warn!(dir = %args.markdown_output_dir, error = %e, "Failed starting the partial Markdown; writing it at the end only");
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let journal = if args.journal {
+        match Journal::create(&args.json_output_dir, front_page, args.content_policy()).await {
+            Ok(journal) => {
+                info!(path = %journal.path().display(), "Writing the journal as articles complete");
+                Some(journal)
+            }
+            Err(e) => {
+                warn!(dir = %args.json_output_dir, error = %e, "Failed starting the journal; writing the edition at the end only");
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let results = pipeline::process_all(
+        &ctx,
+        articles,
+        parallel_batch_size,
+        deadline,
+        partial_markdown.as_ref(),
+        journal.as_ref(),
+    )
+    .await;
+    progress.finish();
+
+    let skip_counts = run.skips.counts();
+    let not_sent = skip_counts.get(&SkipReason::Deadline).copied().unwrap_or(0);
+    let cancelled = skip_counts.get(&SkipReason::Cancelled).copied().unwrap_or(0);
+    if not_sent + cancelled > 0 {
+        warn!(
+            max_run_time_secs = args.max_run_time,
+            not_sent,
+            cancelled,
+            "Run time budget exceeded; writing the edition with the articles done so far"
+        );
+        publish_info!(
+            "awful_text_news",
+            event_kind = "run.time_budget_exceeded",
+            max_run_time_secs = args.max_run_time,
+            articles_done = results.iter().filter(|r| r.is_some()).count(),
+            articles_not_sent = not_sent,
+            articles_cancelled = cancelled,
+            "Run time budget exceeded"
+        );
+    }
+
+    Summarized {
+        articles: results.into_iter().flatten().collect(),
+        partial_markdown,
+        concurrency: adaptive.history(),
+        final_concurrency: adaptive.level(),
+    }
+}
+
+/// Filter, order and cap the summarized articles of `front_page`, pick its
+/// lede, top stories and editorial, and compare it with the day's earlier
+/// editions.
+///
+/// # Returns
+///
+/// The category and tag filter, and the articles it dropped per reason.
+async fn assemble(
+    args: &Cli,
+    llm: &LlmSetup,
+    skips: &SkipLog,
+    front_page: &mut FrontPage,
+) -> (EditionFilter, BTreeMap<Filtered, usize>) {
+    front_page.articles = filters::filter_regions(std::mem::take(&mut front_page.articles), &args.region, skips);
+
+    let edition_filter = args.edition_filter();
+    let (kept, filtered_out) = edition_filter.apply(std::mem::take(&mut front_page.articles), skips);
+    front_page.articles = kept;
+    if !edition_filter.is_empty() {
+        info!(
+            filters = ?edition_filter,
+            kept = front_page.articles.len(),
+            filtered_out = ?filtered_out,
+            "Applied category and tag filters"
+        );
+    }
+
+    // Completion order is nondeterministic; sort before any output is written
+    ordering::sort_articles(&mut front_page.articles, args.article_order);
+
+    if let Some(max) = args.max_per_category {
+        let flags = ordering::partition_overflow(&front_page.articles, max);
+        let overflowed = flags.iter().filter(|o| **o).count();
+        for (article, overflow) in front_page.articles.iter_mut().zip(flags) {
+            article.overflow = overflow;
+        }
+        info!(max_per_category = max, overflowed, "Applied per-category cap");
+    }
+
+    // Salience picks the lede; scored after the cap so overflow is never the lede
+    salience::score(&mut front_page.articles, &args.salience_weights());
+
+    // Featured above the categories; picked after the cap so they render in full
+    front_page.top = top_stories::select_top(&front_page.articles, args.top_n);
+
+    if args.editorial {
+        match editorial::editorial_template().await {
+            Ok(template) => {
+                front_page.editorial =
+                    editorial::write_editorial(&llm.config, &template, llm.auth.as_ref(), front_page).await;
+            }
+            Err(e) => warn!(error = %e, "Cannot ask for an editorial; leaving it out"),
+        }
+    }
+
+    // ---- Flag stories that grew since an earlier edition of the day ----
+    let earlier_editions = diff::load_earlier_editions(&args.json_output_dir, front_page).await;
+    let developing = diff::mark_developing(&earlier_editions, front_page);
+    if developing > 0 {
+        info!(developing, "Flagged stories updated since an earlier edition");
+    }
+    front_page.corrections = diff::find_corrections(&earlier_editions, front_page, args.correction_threshold);
+    if !front_page.corrections.is_empty() {
+        info!(corrections = front_page.corrections.len(), "Recorded corrections to earlier summaries");
+    }
+
+    // ---- Compare with the previous edition of the day ----
+    if let Some(previous) = diff::load_previous_edition(&args.json_output_dir, front_page).await {
+        let edition_diff = diff::diff_editions(&previous, front_page);
+        info!(
+            previous = %edition_diff.previous_edition,
+            new = edition_diff.new.len(),
+            updated = edition_diff.updated.len(),
+            dropped = edition_diff.dropped.len(),
+            "Compared with the previous edition"
+        );
+        front_page.diff = Some(edition_diff);
+    }
+
+    (edition_filter, filtered_out)
+}
+
+/// The files an edition was written to.
+struct Outputs {
+    /// Paths of every edition file written, reported in edition.published
+    written_files: Vec<String>,
+    /// Where the edition went instead, if the output directories failed it
+    fallback_used: Option<String>,
+}
+
+/// Write the edition's JSON, run summary, Markdown, mirrors and indexes,
+/// and build the book.
+async fn write_outputs(
+    run: &Run<'_, impl OutputSink>,
+    front_page: &FrontPage,
+    run_summary: &RunSummary,
+    partial_markdown: Option<PartialMarkdown>,
+) -> Result<Outputs, Box<dyn Error>> {
+    let args = run.args;
+    let output_sink = run.sink;
+
+    // Write final JSON after all articles processed
+    publish_info!(
+        "awful_text_news",
+        event_kind = "output.json.started",
+        "Writing JSON output"
+    );
+    let mut written_files: Vec<String> = Vec::new();
+
+    // Should the JSON or Markdown write fail for lack of space or permission,
+    // the edition's JSON goes to --fallback-output-dir instead of being lost
+    let content_policy = args.content_policy();
+    // Only serialized when there is a fallback directory to write it to
+    let fallback_json = match &args.fallback_output_dir {
+        Some(_) => Some(json::to_public_json(&*json::sorted_for_json(front_page, args.json_sort), content_policy)?.into_bytes()),
+        None => None,
+    };
+    let fallback_name = format!("{}.json", front_page.edition_stem());
+    let fallback = args.fallback_output_dir.as_deref().zip(fallback_json.as_deref()).map(|(dir, contents)| Fallback {
+        dir,
+        file_name: &fallback_name,
+        contents,
+    });
+    let mut fallback_used: Option<String> = None;
+    let mut use_fallback = |output: &str, path: String| {
+        error!(
+            %path,
+            output,
+            "!!! EDITION WRITTEN TO FALLBACK LOCATION: {} — recover it into the output directory !!!",
+            path
+        );
+        publish_error!(
+            "awful_text_news",
+            event_kind = "output.fallback_used",
+            output = output,
+            path = path.clone(),
+            "Edition written to the fallback output directory"
+        );
+        fallback_used = Some(path);
+    };
+
+    match write_with_fallback(json::write_frontpage(output_sink, front_page, &args.json_output_dir, content_policy, args.json_sort, args.json_max_bytes), fallback).await {
+        Ok(Written::Fallback(path)) => use_fallback("json", path),
+        Ok(Written::Primary(paths)) => {
+            publish_info!(
+                "awful_text_news",
+                event_kind = "output.json.completed",
+                article_count = front_page.articles.len(),
+                "JSON output written successfully"
+            );
+            written_files.extend(paths);
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to write final JSON");
+            publish_error!(
+                "awful_text_news",
+                event_kind = "output.json.failed",
+                "Failed to write JSON output"
+            );
+        }
+    }
+
+    if args.split_by_category {
+        match json::write_category_files(output_sink, front_page, &args.json_output_dir, content_policy).await {
+            Ok(paths) => written_files.extend(paths),
+            Err(e) => error!(error = %e, "Failed to write per-category JSON"),
+        }
+    }
+
+    match json::write_run_summary(output_sink, front_page, run_summary, &args.json_output_dir).await {
+        Ok(path) => written_files.push(path),
+        Err(e) => error!(error = %e, "Failed to write run summary"),
+    }
+
+    if args.day_digest {
+        match json::write_day_digest(output_sink, &args.json_output_dir, &front_page.edition_date()).await {
+            Ok(path) => written_files.push(path),
+            Err(e) => error!(error = %e, "Failed to write day digest"),
+        }
+    }
+
+    if args.update_stats {
+        match stats::run(output_sink, &args.json_output_dir).await {
+            Ok(paths) => written_files.extend(paths),
+            Err(e) => error!(error = %e, "Failed to update statistics rollups"),
+        }
+    }
+
+    if !run.json_mirrors.is_empty() {
+        // Everything written so far is JSON under the primary directory
+        json::write_mirrors(&written_files, &args.json_output_dir, run.json_mirrors).await;
+    }
+
+    // ---- Markdown output ----
+    let markdown_options = args.markdown_options();
+    let md = markdown::front_page_to_markdown(front_page, &markdown_options);
+    // Every artifact of the edition shares the same date (see FrontPage::edition_date)
+    let markdown_filename = format!("{}.md", front_page.edition_stem());
+    let output_markdown_filename = format!("{}/{}", args.markdown_output_dir, markdown_filename);
+
+    info!(path = %output_markdown_filename, "Writing Markdown");
+    publish_info!(
+        "awful_text_news",
+        event_kind = "output.markdown.started",
+        "Writing Markdown output"
+    );
+    let written = write_with_fallback(output_sink.write(&output_markdown_filename, md.as_bytes()), fallback).await;
+    let markdown_written = matches!(written, Ok(Written::Primary(())));
+    // With the edition out, the partial copy is no longer needed
+    if let (Some(partial), true) = (partial_markdown, written.is_ok()) {
+        partial.finish().await;
+    }
+    if let Err(e) = &written {
+        error!(path = %output_markdown_filename, error = %e, "Failed writing Markdown");
+        publish_error!(
+            "awful_text_news",
+            event_kind = "output.markdown.failed",
+            path = output_markdown_filename.clone(),
+            "Failed to write Markdown output"
+        );
+    } else if let Ok(Written::Fallback(path)) = written {
+        use_fallback("markdown", path);
+    } else {
+        info!(path = %output_markdown_filename, "Wrote FrontPage Markdown");
+        publish_info!(
+            "awful_text_news",
+            event_kind = "output.markdown.completed",
+            path = output_markdown_filename.clone(),
+            "Markdown output written successfully"
+        );
+        written_files.push(output_markdown_filename.clone());
+    }
+
+    // ---- Plain-text and gemtext mirrors ----
+    let mirrors = [
+        (args.text_output_dir.as_deref(), "txt"),
+        (args.gemtext_output_dir.as_deref(), "gmi"),
+    ];
+    for (dir, extension) in mirrors {
+        let Some(dir) = dir else { continue };
+        let rendered = if extension == "txt" {
+            plaintext::front_page_to_text(front_page, args.text_width, &args.empty_edition_message)
+        } else {
+            plaintext::front_page_to_gemtext(front_page, &args.empty_edition_message)
+        };
+        let path = format!("{}/{}.{}", dir, front_page.edition_stem(), extension);
+        match output_sink.write(&path, rendered.as_bytes()).await {
+            Ok(()) => {
+                info!(%path, "Wrote mirror edition");
+                written_files.push(path);
+            }
+            Err(e) => error!(%path, error = %e, "Failed writing mirror edition"),
+        }
+    }
+
+    // ---- Index updates ----
+
+    let indexes_written = match indexes::update_edition_indexes(
+        output_sink,
+        &args.markdown_output_dir,
+        front_page,
+        &markdown_filename,
+        &markdown_options,
+    )
+    .await
+    {
+        Ok(()) => true,
+        Err(e) => {
+            error!(error = %e, "Failed to update Markdown indexes; left them unchanged");
+            false
+        }
+    };
+
+    if args.takeaways_digest
+        && let Err(e) = indexes::update_takeaways_digest(
+            output_sink,
+            &args.markdown_output_dir,
+            front_page,
+            &markdown_filename,
+            &markdown_options.labels,
+            args.takeaways_retention_days,
+        )
+        .await
+    {
+        error!(error = %e, "Failed to update takeaways.md; left it unchanged");
+    }
+
+    // ---- Build the book ----
+    if args.mdbook_build {
+        if markdown_written && indexes_written {
+            build_book(args).await;
+        } else {
+            warn!("Markdown or index writes failed; not building the book");
+        }
+    }
+
+    Ok(Outputs {
+        written_files,
+        fallback_used,
+    })
+}
+
+/// Run `mdbook build` for `--mdbook-build` and report the outcome.
+async fn build_book(args: &Cli) {
+    publish_info!("awful_text_news", event_kind = "output.book.started", "Building the book");
+    let timeout = Duration::from_secs(args.mdbook_timeout_secs);
+    let started = Instant::now();
+    match book::build(&book::Subprocess, &args.mdbook_path, &args.markdown_output_dir, args.mdbook_dest_dir.as_deref(), timeout).await {
+        Ok(elapsed) => {
+            info!(?elapsed, "Built the book");
+            publish_info!(
+                "awful_text_news",
+                event_kind = "output.book.completed",
+                duration_millis = elapsed.as_millis(),
+                "Book built successfully"
+            );
+        }
+        Err(e) => {
+            error!(error = %e, elapsed = ?started.elapsed(), "Failed to build the book");
+            publish_error!(
+                "awful_text_news",
+                event_kind = "output.book.failed",
+                duration_millis = started.elapsed().as_millis(),
+                error = e.to_string(),
+                "Failed to build the book"
+            );
+        }
+    }
+}
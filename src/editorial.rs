@@ -0,0 +1,163 @@
+//! The editor's top story of the day (`--editorial`).
+//!
+//! After the articles of an edition are processed, their titles and
+//! summaries are sent to the LLM once more with the `editorial` template
+//! (from the config directory, or the built-in one), asking for a single
+//! headline and blurb that sum up the edition. The answer is stored in the
+//! edition's `editorial` field and rendered under the Markdown header.
+//!
+//! A failed, empty or unparseable answer is logged and the edition is
+//! written without it.
+
+use crate::api::{ask_once, AdaptiveConcurrency, BasicAuth};
+use crate::models::{Editorial, FrontPage};
+use crate::utils::repair_json;
+use awful_aj::config::AwfulJadeConfig;
+use awful_aj::template::{self, ChatTemplate};
+use std::error::Error;
+use std::fmt::Write;
+use tracing::{debug, info, warn};
+
+/// The built-in editorial template, used when the config directory has no
+/// `editorial` template.
+const BUILTIN_EDITORIAL_TEMPLATE: &str = include_str!("../template/editorial.yaml");
+
+/// The LLM input for the editorial: one line per article, "Also today"
+/// overflow left out.
+pub fn editorial_input(front_page: &FrontPage) -> String {
+    let mut input = format!("The {} edition of {}.\n\n", front_page.time_of_day, front_page.edition_date());
+    for article in front_page.articles.iter().filter(|a| !a.overflow) {
+        writeln!(
+            input,
+            "- [{}] {}: {}",
+            article.category,
+            article.title,
+            article.summaryOfNewsArticle.trim()
+        )
+        .unwrap();
+    }
+    input
+}
+
+/// Parse the LLM's answer, repairing a wrapped or lightly malformed object.
+fn parse_editorial(raw: &str) -> Option<Editorial> {
+    let editorial: Editorial = serde_json::from_str(raw)
+        .ok()
+        .or_else(|| repair_json(raw).and_then(|json| serde_json::from_str(&json).ok()))?;
+    let (headline, blurb) = (editorial.headline.trim(), editorial.blurb.trim());
+    (!headline.is_empty()).then(|| Editorial {
+        headline: headline.to_string(),
+        blurb: blurb.to_string(),
+    })
+}
+
+/// Ask the LLM for the edition's headline and blurb.
+///
+/// `None` if the edition has no articles or the request fails or comes
+/// back empty or unparseable (logged).
+pub async fn write_editorial(
+    config: &AwfulJadeConfig,
+    template: &ChatTemplate,
+    auth: Option<&BasicAuth>,
+    front_page: &FrontPage,
+) -> Option<Editorial> {
+    if front_page.articles.is_empty() {
+        return None;
+    }
+    let adaptive = AdaptiveConcurrency::new(1);
+    match ask_once(config, &editorial_input(front_page), template, auth, &adaptive).await {
+        Ok(text) => match parse_editorial(&text) {
+            Some(editorial) => {
+                info!(headline = %editorial.headline, "Wrote the edition's editorial");
+                Some(editorial)
+            }
+            None => {
+                warn!(response = %text, "LLM returned no usable editorial; leaving it out");
+                None
+            }
+        },
+        Err(e) => {
+            warn!(error = %e, "Editorial failed; leaving it out");
+            None
+        }
+    }
+}
+
+/// The `editorial` template from the config directory, or the built-in one.
+pub async fn editorial_template() -> Result<ChatTemplate, Box<dyn Error>> {
+    match template::load_template("editorial").await {
+        Ok(template) => Ok(template),
+        Err(e) => {
+            debug!(error = %e, "No editorial template; using the built-in one");
+            Ok(serde_yaml::from_str(BUILTIN_EDITORIAL_TEMPLATE)?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use crate::models::AwfulNewsArticle;
+    use crate::outputs::markdown::front_page_to_markdown;
+    use crate::test_support::{write_config, FixtureLlm};
+    use awful_aj::config;
+    use serde_json::json;
+
+    fn article(title: &str, category: &str) -> AwfulNewsArticle {
+        AwfulNewsArticle {
+            source: Some(format!("https://text.npr.org/{}", title.len())),
+            summaryOfNewsArticle: format!("{} summary.", title),
+            ..test_support::article(title, category)
+        }
+    }
+
+    fn front_page(articles: Vec<AwfulNewsArticle>) -> FrontPage {
+        FrontPage {
+            articles,
+            ..test_support::front_page("morning", "08:00:00")
+        }
+    }
+
+    #[test]
+    fn test_parse_editorial() {
+        let parsed = parse_editorial("```json\n{\"headline\": \" Talks stall \", \"blurb\": \"Both sides walked out.\",}\n```");
+        assert_eq!(
+            parsed,
+            Some(Editorial {
+                headline: "Talks stall".to_string(),
+                blurb: "Both sides walked out.".to_string(),
+            })
+        );
+        assert_eq!(parse_editorial(r#"{"headline": "", "blurb": "No headline."}"#), None);
+        assert_eq!(parse_editorial("Talks stall"), None);
+    }
+
+    #[tokio::test]
+    async fn test_editorial_is_stored_and_rendered() {
+        let reply = json!({ "headline": "Ceasefire talks stall in Cairo", "blurb": "Negotiators left without a deal." });
+        let llm = FixtureLlm::start(vec![("Talks stall", reply)]).await;
+        let dir = tempfile::tempdir().unwrap();
+        let config = config::load_config(write_config(dir.path(), &llm.api_base()).to_str().unwrap()).unwrap();
+        let template: ChatTemplate = serde_yaml::from_str(BUILTIN_EDITORIAL_TEMPLATE).unwrap();
+
+        let mut page = front_page(vec![article("Talks stall", "World News"), article("Rover lands", "Science & Technology")]);
+        page.editorial = write_editorial(&config, &template, None, &page).await;
+        assert_eq!(page.editorial.as_ref().map(|e| e.headline.as_str()), Some("Ceasefire talks stall in Cairo"));
+
+        let md = front_page_to_markdown(&page, &Default::default());
+        assert!(md.starts_with(
+            "# Awful Times\n\n#### Edition published at 08:00:00\n\n\
+             ## Ceasefire talks stall in Cairo\n\nNegotiators left without a deal.\n\n"
+        ));
+        let json = serde_json::to_value(&page).unwrap();
+        assert_eq!(json["editorial"]["blurb"], "Negotiators left without a deal.");
+
+        // No canned reply: the editorial is left out; an empty edition sends no request
+        let other = front_page(vec![article("Rover lands", "Science & Technology")]);
+        assert_eq!(write_editorial(&config, &template, None, &other).await, None);
+        let requests = llm.server.received_requests().await.unwrap().len();
+        assert_eq!(write_editorial(&config, &template, None, &front_page(vec![])).await, None);
+        assert_eq!(llm.server.received_requests().await.unwrap().len(), requests);
+    }
+}
@@ -0,0 +1,283 @@
+//! The network half of a run: index and fetch every source.
+//!
+//! [`run`] indexes every scraper of [`scrapers::SCRAPERS`], samples and
+//! fetches the listings, and drops duplicates and filtered-out articles
+//! before they cost any tokens. The edition is decided once fetching is done
+//! ([`new_edition`]), so a raw dump carries the edition it was fetched for.
+//!
+//! The `fetch` subcommand stops after writing the articles to a raw dump;
+//! `process` replaces this half with [`read_raw_dump`]; the default `run`
+//! hands the articles straight to [`crate::edition`].
+
+use crate::cli::Cli;
+use crate::models::{FrontPage, NewsArticle};
+use crate::outputs::json;
+use crate::outputs::raw;
+use crate::publish_info;
+use crate::sampling;
+use crate::scrapers::budget::{ArticleSink, SourceBudget};
+use crate::scrapers::{self, Source};
+use crate::skips::SkipLog;
+use crate::state::HistoryState;
+use crate::utils::{dedupe_identical_content, dedupe_same_source, time_of_day, EditionScheme};
+use chrono::Local;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use tracing::{error, info};
+
+/// Apply the scraping settings of `args`: HTTP client, caches, boilerplate,
+/// paywall and selector rules.
+pub async fn init_scrapers(args: &Cli) -> Result<(), Box<dyn Error>> {
+    scrapers::http::init_scrape_timeout(args.scrape_timeout_secs);
+    scrapers::http::init_max_body_bytes(args.max_body_bytes);
+    scrapers::http::init_response_cache_bytes(args.response_cache_bytes);
+    scrapers::http::init_pool_settings(scrapers::http::PoolSettings {
+        max_idle_per_host: args.pool_max_idle_per_host,
+        idle_timeout: std::time::Duration::from_secs(args.pool_idle_timeout_secs),
+    });
+    scrapers::http::init_retry_policy(scrapers::http::RetryPolicy {
+        max_retries: args.scrape_retries,
+        base_delay: std::time::Duration::from_millis(args.scrape_retry_delay_ms),
+    });
+    scrapers::http::init_per_host_delay(args.per_host_delay_ms);
+    scrapers::http::init_index_cache(args.index_cache_file.as_deref()).await;
+    scrapers::http::init_page_cache(args.page_cache_file.as_deref()).await;
+    scrapers::http::init_snapshot_dir(args.snapshot_dir.as_deref()).await;
+    scrapers::boilerplate::init(args.boilerplate_file.as_deref()).await;
+    scrapers::paywall::init(&args.paywall_phrases);
+    scrapers::selectors::init(args.selectors_file.as_deref()).await?;
+    Ok(())
+}
+
+/// Articles indexed and fetched for an edition, with what each scraper did.
+pub struct Fetched {
+    pub articles: Vec<NewsArticle>,
+    pub runs: HashMap<&'static str, json::SourceRun>,
+    pub deduped_same_source: usize,
+    pub deduped_identical: usize,
+}
+
+/// Index and fetch every source.
+///
+/// Articles are sampled, fetched, de-duplicated and keyword-filtered;
+/// everything left out is recorded in `skips`. With
+/// `--only-new-since-last-run`, URLs in `history` aren't fetched.
+pub async fn run(args: &Cli, skips: &SkipLog, history: Option<&HistoryState>) -> Result<Fetched, Box<dyn Error>> {
+    publish_info!(
+        "awful_text_news",
+        event_kind = "indexing.started",
+        "Starting article indexing from all sources"
+    );
+
+    init_scrapers(args).await?;
+
+    /// One scraper's share of the run.
+    struct SourceWork {
+        scraper: &'static scrapers::ScraperInfo,
+        // Its index and fetch share one time budget
+        budget: SourceBudget,
+        listings: Vec<scrapers::Listing>,
+        indexed: usize,
+        ran: bool,
+    }
+
+    let mut sources = Vec::with_capacity(scrapers::SCRAPERS.len());
+    for scraper in scrapers::SCRAPERS {
+        let mut budget = SourceBudget::new(scraper.name, args.source_budget_secs);
+        let nyt_api_key = args.nyt_api_key.as_deref();
+        // NYT is optional: a missing or rejected API key leaves it out of the edition
+        let (listings, ran) = match budget.index(scraper.index(nyt_api_key)).await {
+            Ok(listings) if scraper.source == Source::Nyt => {
+                (listings, nyt_api_key.is_some_and(|key| !key.trim().is_empty()))
+            }
+            Ok(listings) => (listings, true),
+            Err(e) if scraper.source == Source::Nyt => {
+                error!(error = %e, "NYT indexing failed; continuing without NYT");
+                (Vec::new(), false)
+            }
+            Err(e) => return Err(e),
+        };
+        sources.push(SourceWork {
+            scraper,
+            budget,
+            indexed: listings.len(),
+            listings,
+            ran,
+        });
+    }
+
+    scrapers::http::persist_index_cache().await;
+
+    // Skip URLs processed in any earlier run before spending requests on them
+    if let (true, Some(history)) = (args.only_new_since_last_run, history) {
+        let mut skipped = 0;
+        for source in &mut sources {
+            let listings = std::mem::take(&mut source.listings);
+            let (kept, n) = history.filter_unseen(source.scraper.name, listings, scrapers::Listing::url, skips);
+            source.listings = kept;
+            skipped += n;
+        }
+        info!(skipped, "Skipped URLs processed in earlier runs");
+    }
+
+    if args.respect_robots {
+        for source in &mut sources {
+            let listings = std::mem::take(&mut source.listings);
+            source.listings =
+                scrapers::robots::filter_allowed(source.scraper.name, listings, scrapers::Listing::url, skips).await;
+        }
+    }
+
+    // Sample down to the total limit before any article is fetched
+    if let Some(max_articles) = args.max_articles {
+        let mut rng = sampling::rng(args.seed);
+        let available: Vec<(&str, usize)> = sources.iter().map(|s| (s.scraper.tag, s.listings.len())).collect();
+        let weights = (!args.source_weights.is_empty()).then_some(args.source_weights.as_slice());
+        let quotas = sampling::quotas(&available, weights, max_articles, &mut rng);
+        for (source, quota) in sources.iter_mut().zip(&quotas) {
+            let listings = std::mem::take(&mut source.listings);
+            source.listings = sampling::sample(listings, *quota, scrapers::Listing::url, &mut rng, skips);
+        }
+        info!(max_articles, ?quotas, seed = ?args.seed, "Sampled indexed articles");
+    }
+
+    #[allow(unused_variables)]
+    let to_fetch: BTreeMap<&str, usize> = sources.iter().map(|s| (s.scraper.name, s.listings.len())).collect();
+    publish_info!(
+        "awful_text_news",
+        event_kind = "indexing.completed",
+        total_urls = to_fetch.values().sum::<usize>(),
+        source_counts = to_fetch,
+        "Article indexing completed"
+    );
+
+    publish_info!(
+        "awful_text_news",
+        event_kind = "fetching.started",
+        "Starting article content fetching"
+    );
+
+    let sink = ArticleSink::default();
+    let mut fetched_by_source = Vec::with_capacity(sources.len());
+    for source in &mut sources {
+        let listings = std::mem::take(&mut source.listings);
+        let articles = source
+            .budget
+            .fetch(&sink, skips, &listings, source.scraper.fetch(listings.clone(), &sink, skips))
+            .await;
+        fetched_by_source.push(articles);
+    }
+    scrapers::http::persist_page_cache().await;
+
+    // Drop stories listed under several URLs by the same outlet
+    let mut deduped_same_source = 0;
+    let fetched_by_source: Vec<Vec<NewsArticle>> = fetched_by_source
+        .into_iter()
+        .map(|source_articles| {
+            let (kept, dropped) = dedupe_same_source(source_articles, args.dedup_prefer, skips);
+            deduped_same_source += dropped;
+            kept
+        })
+        .collect();
+    if deduped_same_source > 0 {
+        info!(deduped_same_source, "Dropped same-source duplicate articles");
+    }
+
+    // Capture per-source counts before flattening
+    let fetched: Vec<usize> = fetched_by_source.iter().map(Vec::len).collect();
+    let articles = fetched_by_source.into_iter().flatten().collect::<Vec<_>>();
+
+    // Collapse wire copy carried word for word by several outlets
+    let (articles, deduped_identical) =
+        dedupe_identical_content(articles, &args.dedup_prefer_sources, args.dedup_prefer, skips);
+    if deduped_identical > 0 {
+        info!(deduped_identical, "Dropped identical articles from other sources");
+    }
+    // Drop unwanted topics before they cost any tokens
+    let articles = args.keyword_filter().apply(articles, skips);
+    info!(count = articles.len(), skipped = ?skips.counts(), "Total articles to analyze");
+
+    // Bytes on the wire vs after decoding, per source
+    let transfer = scrapers::http::transfer_stats();
+    let compressed_bytes: BTreeMap<&str, u64> = transfer.iter().map(|(s, t)| (s.as_str(), t.compressed)).collect();
+    let decompressed_bytes: BTreeMap<&str, u64> = transfer.iter().map(|(s, t)| (s.as_str(), t.decompressed)).collect();
+    let bodies_too_large = scrapers::http::bodies_too_large();
+    let response_cache_hits = scrapers::http::response_cache_hits();
+    info!(?compressed_bytes, ?decompressed_bytes, bodies_too_large, response_cache_hits, "Page transfer sizes");
+
+    publish_info!(
+        "awful_text_news",
+        event_kind = "fetching.completed",
+        total_articles = articles.len(),
+        source_counts = sources
+            .iter()
+            .zip(&fetched)
+            .map(|(s, n)| (s.scraper.name, *n))
+            .collect::<BTreeMap<_, _>>(),
+        deduped_same_source = deduped_same_source,
+        deduped_identical = deduped_identical,
+        compressed_bytes = compressed_bytes,
+        decompressed_bytes = decompressed_bytes,
+        bodies_too_large = bodies_too_large,
+        response_cache_hits = response_cache_hits,
+        skipped = skips.counts(),
+        "Article fetching completed"
+    );
+
+    let runs = sources
+        .iter()
+        .zip(fetched)
+        .filter(|(source, _)| source.ran)
+        .map(|(source, fetched)| {
+            let run = json::SourceRun {
+                ran: true,
+                indexed: source.indexed,
+                fetched,
+            };
+            (source.scraper.name, run)
+        })
+        .collect();
+
+    Ok(Fetched {
+        articles,
+        runs,
+        deduped_same_source,
+        deduped_identical,
+    })
+}
+
+/// A new edition for the time of day of `scheme`, dated now.
+pub fn new_edition(scheme: EditionScheme) -> FrontPage {
+    FrontPage {
+        time_of_day: time_of_day(scheme),
+        local_time: Local::now().time().to_string(),
+        local_date: Local::now().date_naive().to_string(),
+        articles: Vec::new(),
+        diff: None,
+        top: vec![],
+        editorial: None,
+        corrections: vec![],
+    }
+}
+
+/// Read back the articles of a raw dump's edition directory and the edition
+/// they were fetched for, in place of [`run`].
+///
+/// Skips and de-duplication counts stay with the fetching run.
+pub async fn read_raw_dump(dump_dir: &str) -> Result<(FrontPage, Fetched), Box<dyn Error>> {
+    let (manifest, articles) = raw::read_raw_dump(dump_dir).await?;
+    info!(
+        path = %dump_dir,
+        articles = articles.len(),
+        local_date = %manifest.local_date,
+        time_of_day = %manifest.time_of_day,
+        "Read raw dump; skipping indexing and fetching"
+    );
+    let fetched = Fetched {
+        articles,
+        runs: manifest.source_runs(),
+        deduped_same_source: 0,
+        deduped_identical: 0,
+    };
+    Ok((manifest.edition(), fetched))
+}
@@ -0,0 +1,386 @@
+//! Region, category, tag and keyword filters.
+//!
+//! Every article a filter drops is recorded in the run's [`SkipLog`] with
+//! the matching [`SkipReason`].
+//!
+//! # Edition Filters
+//!
+//! Applied after LLM processing and before any output is written, so a
+//! filtered article appears in none of the outputs:
+//!
+//! | Flag | Keeps |
+//! |------|-------|
+//! | `--region` | articles located in one of these places ([`filter_regions`]) |
+//! | `--include-categories` | articles in one of these categories |
+//! | `--exclude-categories` | articles in none of these categories |
+//! | `--include-tags` | articles with at least one of these tags |
+//! | `--exclude-tags` | articles with none of these tags |
+//!
+//! Matching ignores case; tags are compared in their normalized form
+//! (lowercase, words joined with `-`), so `--include-tags "Climate Change"`
+//! matches the tag `climate-change`. An article must pass every filter that
+//! is set, and an exclusion wins over an inclusion of the same value.
+//! Dropped articles are recorded as [`SkipReason::RegionFiltered`],
+//! [`SkipReason::CategoryFiltered`] or [`SkipReason::TagFiltered`].
+//!
+//! # Keyword Filters
+//!
+//! `--include-keywords` and `--exclude-keywords` are checked against the
+//! scraped title and text of each article right after fetching, so rejected
+//! articles cost no LLM tokens. Matching ignores case and only counts whole
+//! words: `man` matches "the man said" but not "command". An article is kept
+//! if it mentions none of the excluded keywords and, when inclusions are
+//! set, at least one included keyword. Dropped articles are recorded as
+//! [`SkipReason::KeywordFiltered`].
+
+use crate::models::{AwfulNewsArticle, NewsArticle, SkipReason};
+use crate::skips::SkipLog;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tracing::{debug, info};
+
+/// The edition filters of a run; empty lists are not applied.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct EditionFilter {
+    pub include_categories: Vec<String>,
+    pub exclude_categories: Vec<String>,
+    pub include_tags: Vec<String>,
+    pub exclude_tags: Vec<String>,
+}
+
+/// Why an article was filtered out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Filtered {
+    Category,
+    Tag,
+}
+
+fn normalize_category(category: &str) -> String {
+    category.trim().to_lowercase()
+}
+
+/// Same form as the article tags (see `AwfulNewsArticle::sanitize_tags`).
+fn normalize_tag(tag: &str) -> String {
+    tag.to_lowercase().split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+impl EditionFilter {
+    /// Whether no filter is set.
+    pub fn is_empty(&self) -> bool {
+        self.include_categories.is_empty()
+            && self.exclude_categories.is_empty()
+            && self.include_tags.is_empty()
+            && self.exclude_tags.is_empty()
+    }
+
+    /// Why `article` is filtered out, or `None` if it stays.
+    pub fn check(&self, article: &AwfulNewsArticle) -> Option<Filtered> {
+        let category = normalize_category(&article.category);
+        let has_category = |list: &[String]| list.iter().any(|c| normalize_category(c) == category);
+        if has_category(&self.exclude_categories)
+            || (!self.include_categories.is_empty() && !has_category(&self.include_categories))
+        {
+            return Some(Filtered::Category);
+        }
+
+        let tags: Vec<String> = article.tags.iter().map(|t| normalize_tag(t)).collect();
+        let has_tag = |list: &[String]| list.iter().any(|t| tags.contains(&normalize_tag(t)));
+        if has_tag(&self.exclude_tags) || (!self.include_tags.is_empty() && !has_tag(&self.include_tags)) {
+            return Some(Filtered::Tag);
+        }
+        None
+    }
+
+    /// Drop the articles the filters reject, keeping the order of the rest;
+    /// dropped ones are recorded in `skips`.
+    ///
+    /// # Returns
+    ///
+    /// The kept articles and the number dropped per reason.
+    pub fn apply(
+        &self,
+        articles: Vec<AwfulNewsArticle>,
+        skips: &SkipLog,
+    ) -> (Vec<AwfulNewsArticle>, BTreeMap<Filtered, usize>) {
+        let mut dropped = BTreeMap::new();
+        let kept = articles
+            .into_iter()
+            .filter(|article| match self.check(article) {
+                Some(reason) => {
+                    *dropped.entry(reason).or_insert(0) += 1;
+                    let skip = match reason {
+                        Filtered::Category => SkipReason::CategoryFiltered,
+                        Filtered::Tag => SkipReason::TagFiltered,
+                    };
+                    skips.record(article.source.as_deref().unwrap_or_default(), skip);
+                    false
+                }
+                None => true,
+            })
+            .collect();
+        (kept, dropped)
+    }
+}
+
+/// Keep the summarized articles located in one of `regions` (`--region`),
+/// in order; dropped ones are recorded in `skips`. An empty list keeps
+/// everything.
+pub fn filter_regions(articles: Vec<AwfulNewsArticle>, regions: &[String], skips: &SkipLog) -> Vec<AwfulNewsArticle> {
+    if regions.is_empty() {
+        return articles;
+    }
+    let before = articles.len();
+    let kept: Vec<AwfulNewsArticle> = articles
+        .into_iter()
+        .filter(|article| {
+            let keep = article.in_regions(regions);
+            if !keep {
+                skips.record(article.source.as_deref().unwrap_or_default(), SkipReason::RegionFiltered);
+            }
+            keep
+        })
+        .collect();
+    info!(?regions, kept = kept.len(), dropped = before - kept.len(), "Applied region filter");
+    kept
+}
+
+/// Keyword filters for fetched articles; an empty list is not applied.
+#[derive(Debug, Clone, Default)]
+pub struct KeywordFilter {
+    include: Option<Regex>,
+    exclude: Option<Regex>,
+}
+
+/// Case-insensitive matcher for any of `keywords` as whole words, or `None`
+/// without keywords.
+///
+/// A word boundary is only required where the keyword itself starts or ends
+/// with a word character, so `C++` and `#metoo` still match.
+fn keyword_regex(keywords: &[String]) -> Option<Regex> {
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    let alternatives: Vec<String> = keywords
+        .iter()
+        .map(|k| k.trim())
+        .filter(|k| !k.is_empty())
+        .map(|k| {
+            let start = if is_word(k.chars().next()) { r"\b" } else { "" };
+            let end = if is_word(k.chars().last()) { r"\b" } else { "" };
+            format!("{}{}{}", start, regex::escape(k), end)
+        })
+        .collect();
+    if alternatives.is_empty() {
+        return None;
+    }
+    Regex::new(&format!("(?i)(?:{})", alternatives.join("|"))).ok()
+}
+
+impl KeywordFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Self {
+        Self {
+            include: keyword_regex(include),
+            exclude: keyword_regex(exclude),
+        }
+    }
+
+    /// Whether no keyword is set.
+    pub fn is_empty(&self) -> bool {
+        self.include.is_none() && self.exclude.is_none()
+    }
+
+    /// Whether an article with this title and text stays.
+    pub fn keeps(&self, text: &str) -> bool {
+        !self.exclude.as_ref().is_some_and(|re| re.is_match(text))
+            && self.include.as_ref().is_none_or(|re| re.is_match(text))
+    }
+
+    /// Drop the fetched articles the keywords reject, keeping the order of
+    /// the rest; dropped ones are recorded in `skips`.
+    pub fn apply(&self, articles: Vec<NewsArticle>, skips: &SkipLog) -> Vec<NewsArticle> {
+        if self.is_empty() {
+            return articles;
+        }
+        let before = articles.len();
+        let kept: Vec<NewsArticle> = articles
+            .into_iter()
+            .filter(|article| {
+                // The scraped text starts with the title (and publication date)
+                let keep = self.keeps(&article.content);
+                if !keep {
+                    debug!(source = %article.source, "Dropped by keyword filter");
+                    skips.record(&article.source, SkipReason::KeywordFiltered);
+                }
+                keep
+            })
+            .collect();
+        info!(dropped = before - kept.len(), kept = kept.len(), "Applied keyword filters");
+        kept
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn article(title: &str, category: &str, tags: &[&str]) -> AwfulNewsArticle {
+        AwfulNewsArticle {
+            summaryOfNewsArticle: format!("{} summary.", title),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..test_support::article(title, category)
+        }
+    }
+
+    fn edition() -> Vec<AwfulNewsArticle> {
+        vec![
+            article("Budget vote", "Politics & Governance", &["budget", "congress"]),
+            article("Probe launch", "Science & Technology", &["space", "nasa"]),
+            article("Heat wave", "Environment & Climate", &["climate-change", "heat"]),
+            article("Vaccine trial", "Science & Technology", &["health", "climate-change"]),
+        ]
+    }
+
+    fn titles(articles: &[AwfulNewsArticle]) -> Vec<&str> {
+        articles.iter().map(|a| a.title.as_str()).collect()
+    }
+
+    fn list(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_no_filters_keep_everything() {
+        let filter = EditionFilter::default();
+        assert!(filter.is_empty());
+        let (kept, dropped) = filter.apply(edition(), &SkipLog::default());
+        assert_eq!(kept.len(), 4);
+        assert!(dropped.is_empty());
+    }
+
+    #[test]
+    fn test_category_filters_ignore_case() {
+        let filter = EditionFilter {
+            include_categories: list(&["science & technology", " ENVIRONMENT & CLIMATE "]),
+            ..Default::default()
+        };
+        let (kept, dropped) = filter.apply(edition(), &SkipLog::default());
+        assert_eq!(titles(&kept), vec!["Probe launch", "Heat wave", "Vaccine trial"]);
+        assert_eq!(dropped, BTreeMap::from([(Filtered::Category, 1)]));
+
+        let filter = EditionFilter {
+            exclude_categories: list(&["Politics & Governance"]),
+            ..Default::default()
+        };
+        assert_eq!(titles(&filter.apply(edition(), &SkipLog::default()).0), vec!["Probe launch", "Heat wave", "Vaccine trial"]);
+    }
+
+    #[test]
+    fn test_tag_filters_match_normalized_tags() {
+        let filter = EditionFilter {
+            include_tags: list(&["Climate Change"]),
+            exclude_tags: list(&["health"]),
+            ..Default::default()
+        };
+        let (kept, dropped) = filter.apply(edition(), &SkipLog::default());
+        assert_eq!(titles(&kept), vec!["Heat wave"]);
+        assert_eq!(dropped, BTreeMap::from([(Filtered::Tag, 3)]));
+    }
+
+    #[test]
+    fn test_filters_combine_and_exclusion_wins() {
+        // Same category both ways: excluded
+        let filter = EditionFilter {
+            include_categories: list(&["Science & Technology", "Politics & Governance"]),
+            exclude_categories: list(&["politics & governance"]),
+            exclude_tags: list(&["nasa"]),
+            ..Default::default()
+        };
+        let (kept, dropped) = filter.apply(edition(), &SkipLog::default());
+        assert_eq!(titles(&kept), vec!["Vaccine trial"]);
+        // Categories are checked first
+        assert_eq!(dropped, BTreeMap::from([(Filtered::Category, 2), (Filtered::Tag, 1)]));
+
+        let filter = EditionFilter {
+            include_tags: list(&["space"]),
+            exclude_tags: list(&["space"]),
+            ..Default::default()
+        };
+        assert!(filter.apply(edition(), &SkipLog::default()).0.is_empty());
+    }
+
+    fn keywords(include: &[&str], exclude: &[&str]) -> KeywordFilter {
+        KeywordFilter::new(&list(include), &list(exclude))
+    }
+
+    #[test]
+    fn test_keywords_match_whole_words_ignoring_case() {
+        let filter = keywords(&[], &["man", "Lakers"]);
+        assert!(!filter.keeps("Title: Police question a man\n\nThe man said..."));
+        assert!(!filter.keeps("The LAKERS won again."));
+        // "man" inside "command" or "Manhattan" is not a match
+        assert!(filter.keeps("The command center in Manhattan opened."));
+        assert!(!keywords(&[], &["C++"]).keeps("Written in C++ mostly"));
+        assert!(keywords(&[], &[" ", ""]).is_empty());
+    }
+
+    #[test]
+    fn test_keyword_inclusion_and_exclusion() {
+        let filter = keywords(&["climate", "heat wave"], &["sports"]);
+        assert!(filter.keeps("A heat wave hits the coast."));
+        assert!(!filter.keeps("Sports: a heat wave delays the match."));
+        assert!(!filter.keeps("Budget vote passes."));
+
+        let article = |source: &str, content: &str| NewsArticle {
+            source: source.to_string(),
+            content: content.into(),
+            published_at: None,
+            authors: vec![],
+            fetched_at: None,
+            fetched_from: None,
+        };
+        let skips = SkipLog::default();
+        let kept = filter.apply(
+            vec![
+                article("https://text.npr.org/1", "Title: Climate talks\n\nDelegates met."),
+                article("https://text.npr.org/2", "Title: Sports and climate\n\nA climate-friendly stadium."),
+                article("https://text.npr.org/3", "Title: Budget vote\n\nIt passed."),
+            ],
+            &skips,
+        );
+        assert_eq!(kept.iter().map(|a| a.source.as_str()).collect::<Vec<_>>(), vec!["https://text.npr.org/1"]);
+        assert_eq!(skips.counts(), BTreeMap::from([(SkipReason::KeywordFiltered, 2)]));
+    }
+
+    #[test]
+    fn test_edition_and_region_filters_record_skips() {
+        let mut articles = edition();
+        for (n, article) in articles.iter_mut().enumerate() {
+            article.source = Some(format!("https://text.npr.org/{}", n));
+        }
+        articles[1].locations = vec!["Florida".to_string()];
+        articles[2].locations = vec!["Spain".to_string()];
+        let skips = SkipLog::default();
+
+        let kept = filter_regions(articles, &list(&["spain", "Florida"]), &skips);
+        assert_eq!(titles(&kept), vec!["Probe launch", "Heat wave"]);
+        let filter = EditionFilter {
+            exclude_tags: list(&["nasa"]),
+            ..Default::default()
+        };
+        let (kept, _) = filter.apply(kept, &skips);
+        assert_eq!(titles(&kept), vec!["Heat wave"]);
+
+        let skipped: Vec<(String, SkipReason)> = skips.entries().into_iter().map(|s| (s.source, s.reason)).collect();
+        assert_eq!(
+            skipped,
+            vec![
+                ("https://text.npr.org/0".to_string(), SkipReason::RegionFiltered),
+                ("https://text.npr.org/3".to_string(), SkipReason::RegionFiltered),
+                ("https://text.npr.org/1".to_string(), SkipReason::TagFiltered),
+            ]
+        );
+        // No regions keeps everything
+        assert_eq!(filter_regions(edition(), &[], &skips).len(), 4);
+    }
+}
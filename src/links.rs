@@ -0,0 +1,387 @@
+//! Link check of the Markdown book for the `check-links` subcommand.
+//!
+//! Index files (`SUMMARY.md`, `daily_news.md` and the date TOCs) keep
+//! pointing at editions after those are deleted or renamed, and mdBook then
+//! fails to build. The check scans every `.md` file under the Markdown
+//! output directory, follows each relative link and reports those whose
+//! file is missing or whose `#anchor` isn't in that file. External links
+//! (`https://…`, `mailto:`) are not checked.
+//!
+//! A file's anchors are its `{#id}` heading attributes, its
+//! `<a id="…"></a>` tags and the slugs of its other headings, made with the
+//! same [`slugify_title`] the edition and TOC writers use.
+//!
+//! With `--fix`, dead entries of index files are removed: an entry with
+//! nested entries under it becomes an mdBook draft chapter (`[Title]()`)
+//! instead, so its children keep their place. Broken links anywhere else
+//! are only reported.
+
+use crate::utils::slugify_title;
+use crate::validate::{Report, Status};
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tracing::info;
+
+/// A relative link whose target doesn't resolve.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokenLink {
+    /// The file holding the link, relative to the book directory.
+    pub file: String,
+    /// 1-based line of the link.
+    pub line: usize,
+    /// The link target as written, e.g. `./2025-05-06_morning.md#a-3f9c2d`.
+    pub target: String,
+    /// What is missing: `"file"` or `"anchor"`.
+    pub missing: &'static str,
+}
+
+/// Result of a link check.
+#[derive(Debug, Default)]
+pub struct LinkCheck {
+    /// Markdown files scanned.
+    pub files: usize,
+    /// Relative links followed.
+    pub links: usize,
+    /// Links whose target doesn't resolve, by file and line.
+    pub broken: Vec<BrokenLink>,
+}
+
+/// Whether `target` points inside the book.
+fn is_local(target: &str) -> bool {
+    !target.is_empty() && !target.contains("://") && !target.starts_with("mailto:") && !target.starts_with('/')
+}
+
+/// Lines outside fenced code blocks, numbered from 1.
+fn prose_lines(markdown: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut in_code = false;
+    markdown.lines().enumerate().filter_map(move |(i, line)| {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            return None;
+        }
+        (!in_code).then_some((i + 1, line))
+    })
+}
+
+/// Relative link targets of `markdown`, with their line numbers.
+fn links(markdown: &str) -> Vec<(usize, String)> {
+    let mut found = Vec::new();
+    for (n, line) in prose_lines(markdown) {
+        let mut rest = line;
+        while let Some(start) = rest.find("](") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find(')') else {
+                break;
+            };
+            // Drop an optional link title: [text](target "title")
+            let target = after[..end].split_whitespace().next().unwrap_or_default();
+            if is_local(target) {
+                found.push((n, target.to_string()));
+            }
+            rest = &after[end..];
+        }
+    }
+    found
+}
+
+/// Anchors a link into `markdown` may point at.
+fn anchors(markdown: &str) -> HashSet<String> {
+    let mut found = HashSet::new();
+    for (_, line) in prose_lines(markdown) {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('#') {
+            let text = trimmed.trim_start_matches('#').trim();
+            match text.strip_suffix('}').and_then(|t| t.rsplit_once("{#")) {
+                Some((_, id)) => found.insert(id.to_string()),
+                None => found.insert(slugify_title(text)),
+            };
+        }
+        let mut rest = line;
+        while let Some(start) = rest.find("id=\"") {
+            let after = &rest[start + 4..];
+            let Some(end) = after.find('"') else {
+                break;
+            };
+            found.insert(after[..end].to_string());
+            rest = &after[end..];
+        }
+    }
+    found
+}
+
+/// Whether `file` is one of the index files `--fix` may edit.
+fn is_index(file: &str) -> bool {
+    let name = Path::new(file).file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    let is_date_toc = name
+        .strip_suffix(".md")
+        .is_some_and(|stem| chrono::NaiveDate::parse_from_str(stem, "%Y-%m-%d").is_ok());
+    name == "SUMMARY.md" || name == "daily_news.md" || is_date_toc
+}
+
+/// Every `.md` file under `dir`, sorted.
+async fn markdown_files(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let mut entries = fs::read_dir(&current).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if entry.file_type().await?.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|e| e == "md") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Check every relative link of the Markdown files under `dir`.
+///
+/// # Errors
+///
+/// `dir` or one of its files can't be read.
+pub async fn check(dir: &Path) -> Result<LinkCheck, Box<dyn Error>> {
+    let files = markdown_files(dir).await?;
+    let mut contents = BTreeMap::new();
+    for file in &files {
+        contents.insert(file.clone(), fs::read_to_string(file).await?);
+    }
+
+    let mut result = LinkCheck {
+        files: files.len(),
+        ..LinkCheck::default()
+    };
+    for (file, markdown) in &contents {
+        let relative = file.strip_prefix(dir).unwrap_or(file).to_string_lossy().to_string();
+        for (line, target) in links(markdown) {
+            result.links += 1;
+            let (path, anchor) = match target.split_once('#') {
+                Some((path, anchor)) => (path, Some(anchor)),
+                None => (target.as_str(), None),
+            };
+            let resolved = if path.is_empty() {
+                file.clone()
+            } else {
+                file.parent().unwrap_or(dir).join(path.trim_start_matches("./"))
+            };
+
+            let missing = match contents.get(&resolved) {
+                Some(target_markdown) => anchor
+                    .filter(|anchor| !anchor.is_empty() && !anchors(target_markdown).contains(*anchor))
+                    .map(|_| "anchor"),
+                None if fs::try_exists(&resolved).await.unwrap_or(false) => None,
+                None => Some("file"),
+            };
+            if let Some(missing) = missing {
+                result.broken.push(BrokenLink {
+                    file: relative.clone(),
+                    line,
+                    target: target.clone(),
+                    missing,
+                });
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Remove the entries of `markdown` on `dead` lines; an entry with nested
+/// entries becomes a draft chapter instead.
+fn remove_entries(markdown: &str, dead: &HashSet<usize>) -> String {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let mut out = String::with_capacity(markdown.len());
+    for (i, line) in lines.iter().enumerate() {
+        if dead.contains(&(i + 1)) {
+            let has_children = lines.get(i + 1).is_some_and(|next| {
+                next.trim_start().starts_with("- ") && indent(next) > indent(line)
+            });
+            if !has_children {
+                continue;
+            }
+            let mut draft = String::new();
+            let mut rest = *line;
+            while let Some(start) = rest.find("](") {
+                let Some(end) = rest[start..].find(')') else {
+                    break;
+                };
+                draft.push_str(&rest[..start + 2]);
+                rest = &rest[start + end..];
+            }
+            draft.push_str(rest);
+            out.push_str(&draft);
+        } else {
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Remove the dead entries of index files among `broken`.
+///
+/// # Returns
+///
+/// The broken links fixed this way.
+///
+/// # Errors
+///
+/// An index file can't be read or written.
+pub async fn fix(dir: &Path, broken: &[BrokenLink]) -> Result<Vec<BrokenLink>, Box<dyn Error>> {
+    let mut by_file: BTreeMap<&str, Vec<&BrokenLink>> = BTreeMap::new();
+    for link in broken.iter().filter(|l| is_index(&l.file)) {
+        by_file.entry(&link.file).or_default().push(link);
+    }
+
+    let mut fixed = Vec::new();
+    for (file, links) in by_file {
+        let path = dir.join(file);
+        let markdown = fs::read_to_string(&path).await?;
+        let lines: Vec<&str> = markdown.lines().collect();
+        // Only list entries are removed
+        let entries: Vec<&BrokenLink> = links
+            .into_iter()
+            .filter(|l| lines.get(l.line - 1).is_some_and(|line| line.trim_start().starts_with("- ")))
+            .collect();
+        if entries.is_empty() {
+            continue;
+        }
+        let dead: HashSet<usize> = entries.iter().map(|l| l.line).collect();
+        fs::write(&path, remove_entries(&markdown, &dead)).await?;
+        info!(file, removed = dead.len(), "Removed dead index entries");
+        fixed.extend(entries.into_iter().cloned());
+    }
+    Ok(fixed)
+}
+
+/// Check the links of the book in `dir`, fixing dead index entries first
+/// with `fix_index`, and report every broken link.
+pub async fn run(dir: &str, fix_index: bool) -> Report {
+    let mut report = Report::default();
+    let dir = Path::new(dir);
+    let result = match check(dir).await {
+        Ok(result) => result,
+        Err(e) => {
+            report.push("links", Status::Fail, format!("{}: {}", dir.display(), e));
+            return report;
+        }
+    };
+
+    let fixed = if fix_index {
+        match fix(dir, &result.broken).await {
+            Ok(fixed) => fixed,
+            Err(e) => {
+                report.push("links", Status::Fail, format!("--fix failed: {}", e));
+                Vec::new()
+            }
+        }
+    } else {
+        Vec::new()
+    };
+
+    for link in &result.broken {
+        let (status, verb) = if fixed.contains(link) {
+            (Status::Warn, "removed")
+        } else {
+            (Status::Fail, "broken")
+        };
+        report.push(
+            format!("{}:{}", link.file, link.line),
+            status,
+            format!("{} link to {} (missing {})", verb, link.target, link.missing),
+        );
+    }
+    let unfixed = result.broken.len() - fixed.len();
+    let status = if unfixed == 0 { Status::Ok } else { Status::Fail };
+    report.push(
+        "links",
+        status,
+        format!("{} links in {} files, {} broken", result.links, result.files, unfixed),
+    );
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EDITION: &str = "# Awful Times\n\n# Politics & Governance\n\n\
+## Council approves budget - <small>`npr`</small> {#a-3f9c2d}\n\nThe council met.\n\n\
+<a id=\"old-slug\"></a>\n\n## Storm closes schools {#a-77aa01}\n";
+
+    async fn book() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: &str| std::fs::write(dir.path().join(name), content).unwrap();
+        write("2025-05-06_morning.md", EDITION);
+        write(
+            "2025-05-06.md",
+            "# Editions published on 2025-05-06\n\n\
+- [Morning](./2025-05-06_morning.md)\n\
+\t- [**Politics & Governance**](2025-05-06_morning.md#politics--governance)\n\
+\t\t- [Council approves budget](2025-05-06_morning.md#a-3f9c2d)\n\
+\t\t- [Storm](2025-05-06_morning.md#a-deadbe)\n\
+- [Evening](./2025-05-06_evening.md)\n\
+\t- [**World**](2025-05-06_evening.md#world)\n",
+        );
+        write(
+            "daily_news.md",
+            "# Daily News\n\n- [2025-05-06](./2025-05-06.md)\n- [2025-05-05](./2025-05-05.md)\n\n\
+See [old stories](2025-05-06_morning.md#old-slug) or [the site](https://example.com).\n\n\
+```\n[not a link](./nowhere.md)\n```\n",
+        );
+        dir
+    }
+
+    #[test]
+    fn test_anchors_use_ids_and_heading_slugs() {
+        let anchors = anchors(EDITION);
+        for anchor in ["politics--governance", "a-3f9c2d", "a-77aa01", "old-slug", "awful-times"] {
+            assert!(anchors.contains(anchor), "{}", anchor);
+        }
+        assert!(!anchors.contains("council-approves-budget"));
+    }
+
+    #[tokio::test]
+    async fn test_broken_files_and_anchors_are_reported() {
+        let dir = book().await;
+        let result = check(dir.path()).await.unwrap();
+        assert_eq!(result.files, 3);
+        let broken: Vec<(&str, usize, &str)> =
+            result.broken.iter().map(|l| (l.file.as_str(), l.line, l.missing)).collect();
+        assert_eq!(
+            broken,
+            vec![
+                ("2025-05-06.md", 6, "anchor"),
+                ("2025-05-06.md", 7, "file"),
+                ("2025-05-06.md", 8, "file"),
+                ("daily_news.md", 4, "file"),
+            ]
+        );
+
+        let report = run(&dir.path().to_string_lossy(), false).await;
+        assert!(!report.passed());
+        assert!(report.render().contains("broken link to ./2025-05-06_evening.md (missing file)"));
+    }
+
+    #[tokio::test]
+    async fn test_fix_removes_dead_index_entries() {
+        let dir = book().await;
+        let report = run(&dir.path().to_string_lossy(), true).await;
+        assert!(report.passed(), "{}", report.render());
+
+        let toc = std::fs::read_to_string(dir.path().join("2025-05-06.md")).unwrap();
+        assert!(!toc.contains("#a-deadbe"));
+        assert!(toc.contains("#a-3f9c2d"));
+        // The dead edition keeps its place as a draft; its dead child goes
+        assert!(toc.contains("- [Evening]()\n"));
+        assert!(!toc.contains("[**World**]"));
+        let daily = std::fs::read_to_string(dir.path().join("daily_news.md")).unwrap();
+        assert!(daily.contains("- [2025-05-06](./2025-05-06.md)\n\nSee"));
+
+        assert!(check(dir.path()).await.unwrap().broken.is_empty());
+    }
+}
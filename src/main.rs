@@ -7,10 +7,12 @@
 //! ## Features
 //!
 //! - Scrapes articles from multiple news sources (CNN Lite, NPR Text, AP News,
-//!   Al Jazeera, BBC News, and New York Times)
+//!   Al Jazeera, BBC News, New York Times, and Washington Post)
 //! - Processes articles through an OpenAI-compatible LLM API for summarization
 //! - Extracts named entities, key takeaways, important dates, and timeframes
 //! - Outputs JSON API files and Markdown documents for mdBook integration
+//! - Builds a weekly "week in review" edition from stored editions (`weekly`)
+//! - Writes statistics rollups of the stored editions for dashboards (`stats`)
 //! - Supports optional event publishing via RabbitMQ message bus
 //!
 //! ## Usage
@@ -26,32 +28,69 @@
 //! 2. **Fetching**: Download article content from discovered URLs
 //! 3. **Processing**: Send articles to LLM for summarization (parallel, 12 at a time)
 //! 4. **Output**: Write JSON API files and Markdown reports
+//!
+//! Steps 1 and 2 live in [`fetch`], steps 3 and 4 in [`edition`]. A run
+//! (the `run` subcommand, or none) chains both; `fetch` stops after writing
+//! the articles to a raw dump, and `process` picks the dump up, possibly on
+//! another machine.
 
-use awful_aj::{config, config_dir, template};
 use chrono::Local;
-use clap::Parser;
-use itertools::Itertools;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
 use std::error::Error;
-use tracing::{debug, error, info, instrument, warn};
+use std::process::ExitCode;
+use tracing::{debug, error, info, instrument};
 use tracing_subscriber::{fmt as tfmt, EnvFilter};
 
 mod api;
+mod carryover;
 mod cli;
+mod concurrency;
+mod diff;
+mod doctor;
+mod edition;
+mod editorial;
+mod fetch;
+mod filters;
+mod links;
+mod memory;
 mod models;
+mod ordering;
 mod outputs;
+mod pipeline;
+mod progress;
 mod publish;
+mod report;
+mod reprocess;
+mod salience;
+mod sampling;
+mod schema;
 mod scrapers;
+mod skips;
+mod social;
+mod state;
+mod stats;
+#[cfg(test)]
+mod test_support;
+mod top_stories;
 mod utils;
+mod validate;
+mod weekly;
+
+use cli::{Cli, Command};
+use outputs::sink::FileSystem;
+use outputs::{book, json, raw};
+use skips::SkipLog;
+use state::HistoryState;
+use utils::{available_space, ensure_free_space, ensure_writable_dir, FALLBACK_EXIT_CODE};
 
-use api::ask_with_backoff;
-use cli::Cli;
-use models::{AwfulNewsArticle, FrontPage, ImportantDate, ImportantTimeframe, NamedEntity};
-use outputs::{indexes, json, markdown};
-use utils::{ensure_writable_dir, looks_truncated, time_of_day, truncate_for_log};
+// Counts heap usage for the run summary
+#[global_allocator]
+static GLOBAL: memory::CountingAllocator = memory::CountingAllocator;
 
 #[tokio::main]
 #[instrument]
-async fn main() -> Result<(), Box<dyn Error>> {
+async fn main() -> Result<ExitCode, Box<dyn Error>> {
     // --- Tracing init ---
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
     tfmt()
@@ -60,14 +99,59 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .with_file(false)
         .with_line_number(false)
         .with_timer(tracing_subscriber::fmt::time::UtcTime::rfc_3339())
+        .with_writer(progress::LogWriter)
         .init();
 
     let start_time = std::time::Instant::now();
     info!("news_update starting up");
 
     // Parse CLI
-    let args = Cli::parse();
+    let mut args = Cli::parse();
     debug!(?args.json_output_dir, ?args.markdown_output_dir, "Parsed CLI arguments");
+    if let Some(flag) = args.conflicting_mode_flag() {
+        Cli::command()
+            .error(ErrorKind::ArgumentConflict, format!("{flag} cannot be used with a subcommand"))
+            .exit();
+    }
+
+    // --- Preflight only: report and exit before any scraping or LLM work ---
+    if args.validate_only {
+        let report = validate::run(&args).await;
+        print!("{}", report.render());
+        if !report.passed() {
+            return Err("validation failed".into());
+        }
+        info!("Validation passed");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // --- Live smoke test of every scraper: report and exit ---
+    if let Some(Command::Doctor(options)) = &args.command {
+        fetch::init_scrapers(&args).await?;
+        let report = doctor::run(&args, options).await;
+        print!("{}", report.render());
+        if !report.passed() {
+            return Err("doctor found failures".into());
+        }
+        info!("Doctor passed");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // --- Link check of the Markdown book: report and exit ---
+    if let Some(Command::CheckLinks { fix }) = args.command {
+        let report = links::run(&args.markdown_output_dir, fix).await;
+        print!("{}", report.render());
+        if !report.passed() {
+            return Err("broken links found".into());
+        }
+        info!("No broken links");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // Editions go into the book's source directory; scaffold a book if asked
+    args.markdown_output_dir = book::resolve(&args.markdown_output_dir, args.scaffold_book).await?;
+
+    api::init_retry_log(args.retry_log);
 
     // --- Initialize message bus (if configured) ---
     publish::init(args.amqp_url.as_ref(), &args.message_bus_exchange).await;
@@ -96,328 +180,162 @@ async fn main() -> Result<(), Box<dyn Error>> {
         );
         return Err(e);
     }
-
-    // ---- Index and fetch articles ----
-    publish_info!(
-        "awful_text_news",
-        event_kind = "indexing.started",
-        "Starting article indexing from all sources"
-    );
-
-    let cnn_urls = scrapers::cnn::index_articles().await?;
-    let npr_urls = scrapers::npr::index_articles().await?;
-    let apnews_urls = scrapers::apnews::index_articles().await?;
-    let aljazeera_urls = scrapers::aljazeera::index_articles().await?;
-    let bbcnews_urls = scrapers::bbcnews::index_articles().await?;
-    let nyt_articles_with_titles = scrapers::nyt::index_articles(args.nyt_api_key.as_deref()).await?;
-
-    #[allow(unused_variables)]
-    let total_indexed = cnn_urls.len() + npr_urls.len() + apnews_urls.len()
-        + aljazeera_urls.len() + bbcnews_urls.len() + nyt_articles_with_titles.len();
-    publish_info!(
-        "awful_text_news",
-        event_kind = "indexing.completed",
-        total_urls = total_indexed,
-        cnn_count = cnn_urls.len(),
-        npr_count = npr_urls.len(),
-        apnews_count = apnews_urls.len(),
-        aljazeera_count = aljazeera_urls.len(),
-        bbcnews_count = bbcnews_urls.len(),
-        nyt_count = nyt_articles_with_titles.len(),
-        "Article indexing completed"
-    );
-
-    publish_info!(
-        "awful_text_news",
-        event_kind = "fetching.started",
-        "Starting article content fetching"
-    );
-
-    let cnn_articles = scrapers::cnn::fetch_articles(cnn_urls).await;
-    let npr_articles = scrapers::npr::fetch_articles(npr_urls).await;
-    let apnews_articles = scrapers::apnews::fetch_articles(apnews_urls).await;
-    let aljazeera_articles = scrapers::aljazeera::fetch_articles(aljazeera_urls).await;
-    let bbcnews_articles = scrapers::bbcnews::fetch_articles(bbcnews_urls).await;
-    let nyt_articles = scrapers::nyt::fetch_articles(nyt_articles_with_titles).await;
-
-    // Capture per-source counts before flattening (used by publish feature)
-    #[allow(unused_variables)]
-    let (cnn_fetched, npr_fetched, apnews_fetched, aljazeera_fetched, bbcnews_fetched, nyt_fetched) = (
-        cnn_articles.len(),
-        npr_articles.len(),
-        apnews_articles.len(),
-        aljazeera_articles.len(),
-        bbcnews_articles.len(),
-        nyt_articles.len(),
-    );
-
-    let articles = vec![cnn_articles, npr_articles, apnews_articles, aljazeera_articles, bbcnews_articles, nyt_articles]
-        .into_iter()
-        .flatten()
-        .collect::<Vec<_>>();
-    info!(count = articles.len(), "Total articles to analyze");
-
-    publish_info!(
-        "awful_text_news",
-        event_kind = "fetching.completed",
-        total_articles = articles.len(),
-        cnn_count = cnn_fetched,
-        npr_count = npr_fetched,
-        apnews_count = apnews_fetched,
-        aljazeera_count = aljazeera_fetched,
-        bbcnews_count = bbcnews_fetched,
-        nyt_count = nyt_fetched,
-        "Article fetching completed"
-    );
-
-    // ---- Load template & config ----
-    let template = template::load_template("news_parser").await?;
-    info!("Loaded template: news_parser");
-    let conf_file = config_dir()?.join("config.yaml");
-    let config_path = conf_file.to_str().expect("Not a valid config filename");
-    let config = config::load_config(config_path).unwrap();
-    info!(config_path, "Loaded configuration");
-
-    // Wrap config and template in Arc for sharing across parallel tasks
-    use std::sync::Arc;
-    let config = Arc::new(config);
-    let template = Arc::new(template);
-
-    // ---- Build front page ----
-    let local_date = Local::now().date_naive().to_string();
-    let local_time = Local::now().time().to_string();
-    let mut front_page = FrontPage {
-        time_of_day: time_of_day(),
-        local_time,
-        local_date,
-        articles: Vec::new(),
-    };
-    info!(time_of_day = %front_page.time_of_day, local_date = %front_page.local_date, local_time = %front_page.local_time, "FrontPage initialized");
-
-    // ---- Analyze articles in parallel (12 at a time) ----
-    use futures::stream::{self, StreamExt};
-    const PARALLEL_BATCH_SIZE: usize = 12;
-
-    let total_articles = articles.len();
-    info!(parallel_batch_size = PARALLEL_BATCH_SIZE, "Starting parallel article processing");
-
-    publish_info!(
-        "awful_text_news",
-        event_kind = "processing.started",
-        total_articles = total_articles,
-        batch_size = PARALLEL_BATCH_SIZE,
-        "Starting article processing"
-    );
-
-    // Process articles concurrently
-    let results: Vec<Option<AwfulNewsArticle>> = stream::iter(articles.iter().enumerate())
-        .map(|(i, article)| {
-            let config = Arc::clone(&config);
-            let template = Arc::clone(&template);
-            async move {
-                debug!(index = i, source = %article.source, "Analyzing article");
-
-                // First ask
-                match ask_with_backoff(&config, &article.content, &template).await {
-                    Ok(response_json) => {
-                        // Try parse
-                        let mut parsed = serde_json::from_str::<AwfulNewsArticle>(&response_json);
-
-                        // If the parse failed due to EOF (truncation), re-ask ONCE
-                        if let Err(ref e) = parsed {
-                            if looks_truncated(e) {
-                                warn!(index = i, error = %e, "EOF while parsing; re-asking once");
-                                match ask_with_backoff(&config, &article.content, &template).await {
-                                    Ok(r2) => {
-                                        parsed = serde_json::from_str::<AwfulNewsArticle>(&r2);
-                                    }
-                                    Err(e2) => {
-                                        warn!(index = i, error = %e2, "Re-ask failed; will skip article");
-                                    }
-                                }
-                            }
-                        }
-
-                        match parsed {
-                            Ok(mut awful_news_article) => {
-                                awful_news_article.source = Some(article.source.clone());
-                                awful_news_article.content = Some(article.content.clone());
-
-                                // dedupe
-                                awful_news_article.namedEntities = awful_news_article
-                                    .namedEntities
-                                    .into_iter()
-                                    .unique_by(|e| e.name.clone())
-                                    .collect::<Vec<NamedEntity>>();
-                                awful_news_article.importantDates = awful_news_article
-                                    .importantDates
-                                    .into_iter()
-                                    .unique_by(|e| e.descriptionOfWhyDateIsRelevant.clone())
-                                    .collect::<Vec<ImportantDate>>();
-                                awful_news_article.importantTimeframes = awful_news_article
-                                    .importantTimeframes
-                                    .into_iter()
-                                    .unique_by(|e| e.descriptionOfWhyTimeFrameIsRelevant.clone())
-                                    .collect::<Vec<ImportantTimeframe>>();
-                                awful_news_article.keyTakeAways = awful_news_article
-                                    .keyTakeAways
-                                    .into_iter()
-                                    .unique()
-                                    .collect::<Vec<String>>();
-
-                                info!(index = i, "Successfully processed article");
-                                Some(awful_news_article)
-                            }
-                            Err(e) => {
-                                warn!(
-                                    index = i,
-                                    error = %e,
-                                    response_preview = %truncate_for_log(&response_json, 300),
-                                    "Model returned non-conforming JSON; skipping article"
-                                );
-                                None
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        error!(index = i, source = %article.source, error = %e, "API call failed; skipping article");
-                        None
-                    }
-                }
+    // Don't start an edition the disks can't hold
+    if let Some(min_free_mb) = args.min_free_mb {
+        for dir in [&args.json_output_dir, &args.markdown_output_dir] {
+            if let Err(e) = ensure_free_space(dir, min_free_mb, available_space) {
+                error!(path = %dir, error = %e, "Not enough free space in output directory");
+                publish_error!(
+                    "awful_text_news",
+                    event_kind = "application.failed",
+                    reason = "insufficient_free_space",
+                    path = dir.clone(),
+                    min_free_mb = min_free_mb,
+                    "Application failed: not enough free space in output directory"
+                );
+                return Err(e);
             }
-        })
-        .buffer_unordered(PARALLEL_BATCH_SIZE)
-        .collect()
-        .await;
-
-    // Add successful results to front_page
-    for result in results.into_iter().flatten() {
-        front_page.articles.push(result);
+        }
     }
-
-    let successful_count = front_page.articles.len();
-    let failed_count = total_articles - successful_count;
-    info!(
-        total = total_articles,
-        successful = successful_count,
-        failed = failed_count,
-        "Completed parallel article processing"
-    );
-
-    publish_info!(
-        "awful_text_news",
-        event_kind = "processing.completed",
-        total_articles = total_articles,
-        successful = successful_count,
-        failed = failed_count,
-        "Article processing completed"
-    );
-
-    // Write final JSON after all articles processed
-    publish_info!(
-        "awful_text_news",
-        event_kind = "output.json.started",
-        "Writing JSON output"
-    );
-    if let Err(e) = json::write_frontpage(&front_page, &args.json_output_dir).await {
-        error!(error = %e, "Failed to write final JSON");
-        publish_error!(
-            "awful_text_news",
-            event_kind = "output.json.failed",
-            "Failed to write JSON output"
-        );
-    } else {
+    // Every edition, index and registry file is stored through this sink
+    let output_sink = FileSystem;
+    // Mirrors are optional: unwritable ones are reported and skipped
+    let json_mirrors = json::writable_mirrors(&args.json_mirror_dir).await;
+
+    // ---- Re-summarize stored editions instead of scraping ----
+    if let Some(target) = &args.reprocess {
+        let reprocessed = reprocess::run(&output_sink, &args, target, &json_mirrors).await?;
+        let elapsed = start_time.elapsed();
+        info!(?elapsed, editions = reprocessed, "Reprocessing complete");
         publish_info!(
             "awful_text_news",
-            event_kind = "output.json.completed",
-            article_count = front_page.articles.len(),
-            "JSON output written successfully"
+            event_kind = "application.completed",
+            duration_secs = elapsed.as_secs(),
+            duration_millis = elapsed.subsec_millis(),
+            reprocess = true,
+            editions_reprocessed = reprocessed,
+            "Reprocessing run completed"
         );
+        return Ok(ExitCode::SUCCESS);
     }
 
-    // ---- Markdown output ----
-    let md = markdown::front_page_to_markdown(&front_page);
-    let output_markdown_filename = format!(
-        "{}/{}_{}.md",
-        args.markdown_output_dir, front_page.local_date, front_page.time_of_day
-    );
-
-    info!(path = %output_markdown_filename, "Writing Markdown");
-    publish_info!(
-        "awful_text_news",
-        event_kind = "output.markdown.started",
-        "Writing Markdown output"
-    );
-    if let Err(e) = tokio::fs::write(&output_markdown_filename, md).await {
-        error!(path = %output_markdown_filename, error = %e, "Failed writing Markdown");
-        publish_error!(
+    // ---- Week in review from stored editions ----
+    if let Some(Command::Weekly(options)) = &args.command {
+        let path = weekly::run(&output_sink, &args, options, &json_mirrors).await?;
+        let elapsed = start_time.elapsed();
+        info!(?elapsed, %path, "Weekly edition complete");
+        publish_info!(
             "awful_text_news",
-            event_kind = "output.markdown.failed",
-            path = output_markdown_filename.clone(),
-            "Failed to write Markdown output"
+            event_kind = "application.completed",
+            duration_secs = elapsed.as_secs(),
+            duration_millis = elapsed.subsec_millis(),
+            weekly = true,
+            path = path.clone(),
+            "Weekly run completed"
         );
-    } else {
-        info!(path = %output_markdown_filename, "Wrote FrontPage Markdown");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // ---- Statistics rollups from stored editions ----
+    if let Some(Command::Stats) = args.command {
+        let paths = stats::run(&output_sink, &args.json_output_dir).await?;
+        let elapsed = start_time.elapsed();
+        info!(?elapsed, files = paths.len(), "Statistics rollups complete");
         publish_info!(
             "awful_text_news",
-            event_kind = "output.markdown.completed",
-            path = output_markdown_filename.clone(),
-            "Markdown output written successfully"
+            event_kind = "application.completed",
+            duration_secs = elapsed.as_secs(),
+            duration_millis = elapsed.subsec_millis(),
+            stats = true,
+            "Statistics run completed"
         );
+        return Ok(ExitCode::SUCCESS);
     }
 
-    // ---- Index updates ----
-    let markdown_filename = format!("{}_{}.md", front_page.local_date, front_page.time_of_day);
+    // Every article left out of the edition from here on, with the reason
+    let skips = SkipLog::default();
 
-    if let Err(e) = indexes::update_date_toc_file(
-        &args.markdown_output_dir,
-        &front_page,
-        &markdown_filename,
-    )
-    .await
-    {
-        error!(error = %e, "Failed to update date TOC file");
-    }
+    let history = match &args.state_file {
+        Some(path) => {
+            let mut history = HistoryState::load(path).await;
+            let pruned = history.prune(Local::now().date_naive(), args.state_retention_days);
+            if pruned > 0 {
+                info!(pruned, retention_days = args.state_retention_days, "Pruned old history entries");
+            }
+            Some(history)
+        }
+        None => None,
+    };
+
+    // ---- Articles: indexed and fetched now, or read back from a raw dump ----
+    let (front_page, fetched) = match &args.command {
+        Some(Command::Process(process)) => fetch::read_raw_dump(&process.dump_dir).await?,
+        _ => {
+            let fetched = fetch::run(&args, &skips, history.as_ref()).await?;
+            // The edition is decided once fetching is done, and carried by any raw dump
+            (fetch::new_edition(args.edition_scheme), fetched)
+        }
+    };
+    info!(time_of_day = %front_page.time_of_day, local_date = %front_page.local_date, local_time = %front_page.local_time, "FrontPage initialized");
 
-    if let Err(e) = indexes::update_summary_md(
-        &args.markdown_output_dir,
-        &front_page,
-        &markdown_filename,
-    )
-    .await
-    {
-        error!(error = %e, "Failed to update SUMMARY.md");
+    // ---- Source registry for downstream tools ----
+    if let Err(e) = json::write_sources(&output_sink, &args.json_output_dir, &fetched.runs).await {
+        error!(error = %e, "Failed to write source registry");
     }
 
-    if let Err(e) = indexes::update_daily_news_index(
-        &args.markdown_output_dir,
-        &front_page,
-        &markdown_filename,
-    )
-    .await
-    {
-        error!(error = %e, "Failed to update daily_news.md index");
+    // ---- Fetch only: the raw dump is the whole run ----
+    if let Some(Command::Fetch(fetch_args)) = &args.command {
+        let manifest = raw::write_raw_dump(
+            &fetched.articles,
+            &fetch_args.dump_dir,
+            &front_page,
+            &fetched.runs,
+            args.raw_dump_compress,
+        )
+        .await?;
+        if let Some(path) = &args.skip_report {
+            skips::write_report(&skips, path).await;
+        }
+        let elapsed = start_time.elapsed();
+        info!(
+            ?elapsed,
+            articles = manifest.articles.len(),
+            path = %fetch_args.dump_dir,
+            "Fetch complete; skipping LLM processing"
+        );
+        publish_info!(
+            "awful_text_news",
+            event_kind = "application.completed",
+            duration_secs = elapsed.as_secs(),
+            duration_millis = elapsed.subsec_millis(),
+            fetch_only = true,
+            articles_fetched = manifest.articles.len(),
+            articles_skipped = skips.total(),
+            skipped = skips.counts(),
+            "Fetch run completed"
+        );
+        return Ok(ExitCode::SUCCESS);
     }
 
-    let elapsed = start_time.elapsed();
-    info!(
-        ?elapsed,
-        secs = elapsed.as_secs(),
-        millis = elapsed.subsec_millis(),
-        "Execution complete"
-    );
+    // Dump the pre-LLM corpus before anything downstream can fail; a dump
+    // being processed is already on disk
+    if let (None | Some(Command::Run), Some(raw_dump_dir)) = (&args.command, &args.raw_dump_dir) {
+        raw::dump_raw_articles(&fetched.articles, raw_dump_dir, &front_page, &fetched.runs, args.raw_dump_compress).await;
+    }
 
-    publish_info!(
-        "awful_text_news",
-        event_kind = "application.completed",
-        duration_secs = elapsed.as_secs(),
-        duration_millis = elapsed.subsec_millis(),
-        articles_processed = successful_count,
-        articles_failed = failed_count,
-        edition = front_page.time_of_day.clone(),
-        date = front_page.local_date.clone(),
-        "Application completed successfully"
-    );
+    // ---- Summarize and write the edition ----
+    let llm = edition::LlmSetup::load(&args).await?;
+    let run = edition::Run {
+        args: &args,
+        sink: &output_sink,
+        json_mirrors: &json_mirrors,
+        skips: &skips,
+        started: start_time,
+    };
+    if let Some(path) = edition::process(&run, &llm, front_page, fetched, history).await? {
+        error!(%path, code = FALLBACK_EXIT_CODE, "Edition only written to the fallback location; exiting for recovery");
+        return Ok(ExitCode::from(FALLBACK_EXIT_CODE));
+    }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
@@ -0,0 +1,119 @@
+//! Heap usage of the run, for the run summary.
+//!
+//! [`CountingAllocator`] is the global allocator: it hands every request to
+//! the system allocator and keeps count of the bytes currently allocated and
+//! the most allocated at once. [`edition::process`](crate::edition::process)
+//! starts a new peak when processing begins and reports how far above that
+//! starting point the heap went as `peak_additional_bytes` in the
+//! [`RunSummary`](crate::models::RunSummary), to show what an edition costs
+//! in memory on top of what the process already held.
+//!
+//! The counts are process-wide: anything else allocating at the same time
+//! counts too.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Bytes allocated now and at most, since the last [`Counter::reset_peak`].
+#[derive(Debug, Default)]
+pub struct Counter {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl Counter {
+    pub const fn new() -> Self {
+        Counter {
+            current: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        }
+    }
+
+    fn add(&self, bytes: usize) {
+        let now = self.current.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.peak.fetch_max(now, Ordering::Relaxed);
+    }
+
+    fn sub(&self, bytes: usize) {
+        self.current.fetch_sub(bytes, Ordering::Relaxed);
+    }
+
+    /// The most bytes allocated at once since the last reset.
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    /// Start a new peak from what is allocated now, and return that.
+    pub fn reset_peak(&self) -> usize {
+        let now = self.current.load(Ordering::Relaxed);
+        self.peak.store(now, Ordering::Relaxed);
+        now
+    }
+}
+
+/// What the global allocator has counted.
+pub static ALLOCATED: Counter = Counter::new();
+
+/// The system allocator, counting into [`ALLOCATED`].
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            ALLOCATED.add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            ALLOCATED.add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        ALLOCATED.sub(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            if new_size > layout.size() {
+                ALLOCATED.add(new_size - layout.size());
+            } else {
+                ALLOCATED.sub(layout.size() - new_size);
+            }
+        }
+        new_ptr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peak_counts_from_the_last_reset() {
+        let counter = Counter::new();
+        counter.add(100);
+        counter.add(50);
+        counter.sub(120);
+        assert_eq!(counter.peak(), 150);
+
+        // A new peak starts from what is still allocated
+        assert_eq!(counter.reset_peak(), 30);
+        counter.add(20);
+        counter.sub(40);
+        assert_eq!(counter.peak(), 50);
+    }
+
+    #[test]
+    fn test_global_allocator_counts_allocations() {
+        let buffer = vec![1u8; 1 << 20];
+        assert!(ALLOCATED.peak() >= buffer.len());
+    }
+}
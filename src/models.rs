@@ -3,13 +3,22 @@
 //! This module defines the core data structures used throughout the application:
 //! - [`NewsArticle`]: Raw scraped article data from news sources
 //! - [`FrontPage`]: Collection of processed articles for a single edition
+//! - [`DayDigest`]: All editions of a single date
+//! - [`RunSummary`]: Article counts of the run that wrote an edition
+//! - [`EditionDiff`]: Changes since the previous edition of the date
+//! - [`Editorial`]: The LLM's headline of the day for an edition
 //! - [`AwfulNewsArticle`]: LLM-processed article with extracted metadata
 //! - Entity types: [`NamedEntity`], [`ImportantDate`], [`ImportantTimeframe`]
+//! - [`normalize_timeframes`]: Merges overlapping or duplicate timeframes
 //!
 //! The models use camelCase field names to match the JSON schema expected by
 //! the LLM, hence the `#[allow(non_snake_case)]` attributes.
 
+use crate::utils::{content_hash, normalize_url};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
 
 /// A raw news article as scraped from a news source.
 ///
@@ -20,12 +29,124 @@ use serde::{Deserialize, Serialize};
 ///
 /// * `source` - The URL where the article was scraped from
 /// * `content` - The raw text content of the article
-#[derive(Debug)]
+/// * `published_at` - Publication timestamp found in the page markup, if any
+/// * `authors` - Byline names found in the page markup, if any
+/// * `fetched_at` - When the scraper downloaded the page
+/// * `fetched_from` - The URL downloaded, when the source is its canonical URL
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct NewsArticle {
     /// The source URL of the article.
     pub source: String,
-    /// The raw text content scraped from the article.
-    pub content: String,
+    /// The raw text content scraped from the article, shared with the
+    /// processed [`AwfulNewsArticle::content`] rather than copied.
+    pub content: Arc<str>,
+    /// Publication timestamp extracted from the page (see [`crate::scrapers::meta`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<DateTime<FixedOffset>>,
+    /// Author names from the page's byline (see [`crate::scrapers::meta`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+    /// When the page was downloaded; `None` for articles not fetched in this
+    /// run (e.g. re-read from a stored edition).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetched_at: Option<DateTime<Utc>>,
+    /// The URL the page was downloaded from, when it differs from the
+    /// canonical `source` (see [`NewsArticle::with_canonical`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetched_from: Option<String>,
+}
+
+/// Why an indexed or fetched article did not make it into the edition.
+///
+/// Every filtering and failure site records one of these per article (see
+/// [`crate::skips`]); serialized in `snake_case`, e.g. `"llm_parse_error"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// The article page could not be downloaded.
+    FetchError,
+    /// The page was downloaded but no article text was found.
+    EmptyContent,
+    /// The extracted text was too short to be the article.
+    TooShort,
+    /// The page was a paywall or subscription gate (see
+    /// [`crate::scrapers::paywall`]).
+    Paywalled,
+    /// The article was published too long ago. Reserved for an age filter;
+    /// no site records it yet.
+    TooOld,
+    /// `robots.txt` disallows the URL.
+    Robots,
+    /// The article was already covered: same story under another URL, or
+    /// processed in an earlier run or edition.
+    Duplicate,
+    /// The article is not in a wanted language. Reserved for a language
+    /// filter; no site records it yet.
+    LanguageFiltered,
+    /// The article matched `--exclude-keywords`, or none of
+    /// `--include-keywords` (see [`crate::filters::KeywordFilter`]).
+    KeywordFiltered,
+    /// The summarized article has none of the `--region` locations.
+    RegionFiltered,
+    /// The summarized article's category was filtered out by
+    /// `--include-categories` or `--exclude-categories`.
+    CategoryFiltered,
+    /// The summarized article's tags were filtered out by `--include-tags`
+    /// or `--exclude-tags`.
+    TagFiltered,
+    /// The article was not drawn when sampling down to `--max-articles`.
+    NotSampled,
+    /// The LLM request failed.
+    LlmApiError,
+    /// The LLM response was not valid JSON, even after repair or re-ask.
+    LlmParseError,
+    /// The LLM response was JSON but not a valid article.
+    ValidationFailed,
+    /// The source's time budget ran out before the article was fetched.
+    Budget,
+    /// The run's deadline passed before the article was sent to the LLM.
+    Deadline,
+    /// The run was cancelled, or its deadline passed, before the article was done.
+    Cancelled,
+}
+
+impl SkipReason {
+    /// The serialized name of the reason.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SkipReason::FetchError => "fetch_error",
+            SkipReason::EmptyContent => "empty_content",
+            SkipReason::TooShort => "too_short",
+            SkipReason::Paywalled => "paywalled",
+            SkipReason::TooOld => "too_old",
+            SkipReason::Robots => "robots",
+            SkipReason::Duplicate => "duplicate",
+            SkipReason::LanguageFiltered => "language_filtered",
+            SkipReason::KeywordFiltered => "keyword_filtered",
+            SkipReason::RegionFiltered => "region_filtered",
+            SkipReason::CategoryFiltered => "category_filtered",
+            SkipReason::TagFiltered => "tag_filtered",
+            SkipReason::NotSampled => "not_sampled",
+            SkipReason::LlmApiError => "llm_api_error",
+            SkipReason::LlmParseError => "llm_parse_error",
+            SkipReason::ValidationFailed => "validation_failed",
+            SkipReason::Budget => "budget",
+            SkipReason::Deadline => "deadline",
+            SkipReason::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// An article left out of the edition, and why.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SkippedArticle {
+    /// The article URL.
+    pub source: String,
+    /// Why it was left out.
+    pub reason: SkipReason,
+    /// Finer cause within the reason, such as `body_too_large` for a fetch error.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
 }
 
 /// A collection of processed articles representing a single news edition.
@@ -39,7 +160,11 @@ pub struct NewsArticle {
 /// - `"morning"`: 00:00 - 08:00
 /// - `"afternoon"`: 08:00 - 16:00
 /// - `"evening"`: 16:00 - 24:00
-#[derive(Debug, Deserialize, Serialize)]
+///
+/// An evening run that finishes after midnight belongs to the previous
+/// day; every output path uses [`FrontPage::edition_date`] rather than
+/// `local_date` so the JSON API and the Markdown book agree.
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct FrontPage {
     /// The date of publication in `YYYY-MM-DD` format.
     pub local_date: String,
@@ -49,6 +174,185 @@ pub struct FrontPage {
     pub local_time: String,
     /// The collection of processed articles in this edition.
     pub articles: Vec<AwfulNewsArticle>,
+    /// Changes since the previous edition of the same date; absent for the
+    /// day's first edition (see [`crate::diff`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<EditionDiff>,
+    /// The edition's most important articles, best first (see
+    /// [`crate::top_stories`]); empty with `--top-n 0`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub top: Vec<FeaturedStory>,
+    /// The editor's top story of the day, written by the LLM with
+    /// `--editorial` (see [`crate::editorial`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editorial: Option<Editorial>,
+    /// Articles summarized differently than in an earlier edition of the
+    /// same date (see [`crate::diff::find_corrections`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub corrections: Vec<Correction>,
+}
+
+impl FrontPage {
+    /// The date this edition is filed under, `YYYY-MM-DD`.
+    ///
+    /// Same as `local_date`, except for an evening edition stamped before
+    /// the evening starts (16:00): that run crossed midnight and is grouped
+    /// with the previous day.
+    pub fn edition_date(&self) -> String {
+        let evening_start = NaiveTime::from_hms_opt(16, 0, 0).unwrap();
+        let rolled_over = self.time_of_day == "evening"
+            && self
+                .local_time
+                .get(..8)
+                .and_then(|t| NaiveTime::parse_from_str(t, "%H:%M:%S").ok())
+                .is_some_and(|t| t < evening_start);
+        match NaiveDate::parse_from_str(&self.local_date, "%Y-%m-%d") {
+            Ok(date) if rolled_over => (date - Duration::days(1)).to_string(),
+            _ => self.local_date.clone(),
+        }
+    }
+
+    /// File stem shared by the edition's outputs, e.g. `2025-05-06_evening`.
+    pub fn edition_stem(&self) -> String {
+        format!("{}_{}", self.edition_date(), self.time_of_day)
+    }
+}
+
+/// A story referenced by an [`EditionDiff`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct StoryRef {
+    /// The article title.
+    pub title: String,
+    /// The source URL, if the article has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+/// An article whose summary diverged from the one an earlier edition of
+/// the same date gave it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Correction {
+    /// The corrected article.
+    pub story: StoryRef,
+    /// The edition the earlier summary appeared in, e.g. `"morning"`.
+    pub earlier_edition: String,
+    /// [`content_hash`] of the earlier summary.
+    pub previous_summary_hash: String,
+    /// The article fields that changed, by their JSON names.
+    pub fields: Vec<String>,
+}
+
+/// An article featured in an edition's top stories.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct FeaturedStory {
+    /// The article title.
+    pub title: String,
+    /// The source URL, if the article has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// The article's category.
+    pub category: String,
+    /// Importance score the story was selected by.
+    pub score: usize,
+}
+
+/// One headline and blurb summing up an edition.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Editorial {
+    /// The overarching headline of the day.
+    pub headline: String,
+    /// A few sentences explaining it.
+    pub blurb: String,
+}
+
+/// What changed between an edition and the previous edition of its date.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct EditionDiff {
+    /// The edition compared against, e.g. `"morning"`.
+    pub previous_edition: String,
+    /// Articles the previous edition did not have.
+    pub new: Vec<StoryRef>,
+    /// Articles of the previous edition whose content changed since.
+    pub updated: Vec<StoryRef>,
+    /// Stories of the previous edition this edition no longer covers.
+    pub dropped: Vec<StoryRef>,
+}
+
+/// Every edition published on a single date.
+///
+/// Rebuilt from the per-edition JSON files at the end of a run with
+/// `--day-digest` and written to `{json_output_dir}/{date}/day.json`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DayDigest {
+    /// The date in `YYYY-MM-DD` format.
+    pub local_date: String,
+    /// The editions of the day in publication order (morning, afternoon, evening).
+    pub editions: Vec<FrontPage>,
+}
+
+/// Article counts of the run that wrote an edition.
+///
+/// Written next to the edition as `{json_output_dir}/{date}/{time_of_day}.run.json`
+/// and read back by the `stats` subcommand for failure rates.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RunSummary {
+    /// Articles sent to the LLM.
+    pub analyzed: usize,
+    /// Articles that made it into the edition.
+    pub succeeded: usize,
+    /// Articles the LLM step failed on.
+    pub failed: usize,
+    /// Articles left out before or during processing, by reason.
+    pub skipped: BTreeMap<SkipReason, usize>,
+    /// Most heap bytes allocated at once on top of what was allocated when
+    /// processing started (see [`crate::memory`]).
+    pub peak_additional_bytes: usize,
+}
+
+/// A story picked for the week in review.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct WeeklyStory {
+    /// Title of the latest version of the story.
+    pub title: String,
+    /// Category of the latest version of the story.
+    pub category: String,
+    /// The source URL, if the article has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Summary of the latest version of the story.
+    pub summary: String,
+    /// Number of editions of the week that carried the story.
+    pub coverage: usize,
+    /// Named entities of the story shared with other stories of the week.
+    pub shared_entities: usize,
+    /// Selection score (see [`crate::weekly`]).
+    pub score: usize,
+    /// Markdown file of the latest edition carrying the story, e.g.
+    /// `2025-05-06_evening.md`.
+    pub edition_file: String,
+    /// Anchor of the story within `edition_file`.
+    pub anchor: String,
+}
+
+/// An aggregate edition of the top stories of a week.
+///
+/// Written by the `weekly` subcommand to `{json_output_dir}/{week_end}/weekly.json` and
+/// `{markdown_output_dir}/{week_end}_weekly.md`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct WeeklyEdition {
+    /// First date of the week, `YYYY-MM-DD`.
+    pub week_start: String,
+    /// Last date of the week, `YYYY-MM-DD`.
+    pub week_end: String,
+    /// Dates of the week that had at least one edition.
+    pub days_covered: Vec<String>,
+    /// Overview paragraph written by the LLM, if requested and successful.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overview: Option<String>,
+    /// The selected stories, grouped by category in category order and
+    /// ranked within each category.
+    pub stories: Vec<WeeklyStory>,
 }
 
 /// A fully processed news article with LLM-extracted metadata.
@@ -63,13 +367,15 @@ pub struct FrontPage {
 /// LLM template. This ensures consistent serialization/deserialization
 /// when communicating with the LLM API.
 #[allow(non_snake_case)]
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct AwfulNewsArticle {
     /// The original source URL (added after LLM processing).
     pub source: Option<String>,
     /// The article's publication date as extracted by the LLM.
+    #[serde(default)]
     pub dateOfPublication: String,
     /// The article's publication time as extracted by the LLM.
+    #[serde(default)]
     pub timeOfPublication: String,
     /// The article title/headline.
     pub title: String,
@@ -78,41 +384,277 @@ pub struct AwfulNewsArticle {
     /// A concise summary of the article content.
     pub summaryOfNewsArticle: String,
     /// Key points or takeaways from the article.
+    #[serde(default)]
     pub keyTakeAways: Vec<String>,
     /// People, organizations, and other entities mentioned in the article.
+    #[serde(default)]
     pub namedEntities: Vec<NamedEntity>,
     /// Significant dates mentioned in the article.
+    #[serde(default)]
     pub importantDates: Vec<ImportantDate>,
     /// Significant time periods or ranges mentioned in the article.
+    #[serde(default)]
     pub importantTimeframes: Vec<ImportantTimeframe>,
     /// Topic tags assigned by the LLM.
+    #[serde(default)]
     pub tags: Vec<String>,
-    /// The original article content (added after LLM processing).
-    pub content: Option<String>,
+    /// Countries or regions the article is about, as extracted by the LLM.
+    #[serde(default)]
+    pub locations: Vec<String>,
+    /// Byline names: scraped from the page, or extracted by the LLM when
+    /// the page has no byline.
+    #[serde(default)]
+    pub authors: Vec<String>,
+    /// The original article content (added after LLM processing); the same
+    /// allocation as the scraped [`NewsArticle::content`].
+    pub content: Option<Arc<str>>,
+    /// Edition label of an earlier version of this article from the same day,
+    /// set when an updated URL is re-processed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updateOf: Option<String>,
+    /// Whether the article was moved to the compact "Also today" list by
+    /// `--max-per-category`.
+    #[serde(default)]
+    pub overflow: bool,
+    /// Whether the article failed processing in the previous run and was
+    /// retried by `--carry-over-failures`; rendered under "In case you
+    /// missed it".
+    #[serde(default)]
+    pub carriedOver: bool,
+    /// Which scraper produced the content, for tracing a bad summary back to
+    /// the code and selectors that scraped it. Absent from editions written
+    /// before it was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scrapeMeta: Option<ScrapeMeta>,
+    /// How central the article is to the edition (see [`crate::salience`]);
+    /// the highest-scoring article is the edition's lede. `0` in editions
+    /// written before it was scored.
+    #[serde(default)]
+    pub salience: f32,
+}
+
+/// Provenance of an article's scraped content (see
+/// [`AwfulNewsArticle::scrapeMeta`]).
+///
+/// Serialized in camelCase like the rest of the article; the snake_case
+/// names of earlier editions are still read.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScrapeMeta {
+    /// The scraper that fetched the page, e.g. `"bbcnews"`.
+    pub scraper: String,
+    /// Version of awful_text_news that ran it.
+    pub version: String,
+    /// When the page was downloaded, if known.
+    #[serde(default, alias = "fetched_at", skip_serializing_if = "Option::is_none")]
+    pub fetched_at: Option<DateTime<Utc>>,
+    /// Length of the scraped content, in characters.
+    #[serde(alias = "content_length")]
+    pub content_length: usize,
+    /// Fingerprint of the scraper's selectors (see
+    /// [`crate::scrapers::selectors::Selectors::fingerprint`]).
+    #[serde(alias = "selector_hash")]
+    pub selector_hash: String,
+    /// The URL downloaded, when the article's source is the page's
+    /// canonical URL instead.
+    #[serde(default, alias = "fetched_from", skip_serializing_if = "Option::is_none")]
+    pub fetched_from: Option<String>,
+}
+
+impl NewsArticle {
+    /// Short outlet name derived from the source URL (see [`source_tag_of`]).
+    pub fn source_tag(&self) -> Option<String> {
+        source_tag_of(&self.source)
+    }
+
+    /// Make `canonical` (the page's canonical URL, if it has one) the
+    /// source, with tracking parameters stripped (see [`normalize_url`]).
+    /// The URL actually downloaded is kept in `fetched_from` if it differs.
+    pub fn with_canonical(mut self, canonical: Option<String>) -> Self {
+        let canonical = normalize_url(canonical.as_deref().unwrap_or(&self.source));
+        if canonical != self.source {
+            self.fetched_from = Some(std::mem::replace(&mut self.source, canonical));
+        }
+        self
+    }
+
+    /// The URL the page was downloaded from.
+    pub fn fetched_url(&self) -> &str {
+        self.fetched_from.as_deref().unwrap_or(&self.source)
+    }
 }
 
 impl AwfulNewsArticle {
     /// Extract the domain name (before .com/.org/etc) from the source URL
     /// For example: "https://lite.cnn.com/article" -> "cnn"
     pub fn source_tag(&self) -> Option<String> {
-        self.source.as_ref().and_then(|url| {
-            // Parse the URL and extract the host
-            if let Ok(parsed) = url::Url::parse(url) {
-                if let Some(host) = parsed.host_str() {
-                    // Split by dots and get the domain before the TLD
-                    let parts: Vec<&str> = host.split('.').collect();
-                    // Handle cases like "lite.cnn.com" -> "cnn" or "cnn.com" -> "cnn"
-                    if parts.len() >= 2 {
-                        // Get the second-to-last part (domain before TLD)
-                        return Some(parts[parts.len() - 2].to_string());
-                    }
-                }
-            }
-            None
-        })
+        self.source.as_deref().and_then(source_tag_of)
+    }
+
+    /// Whether any of the article's locations is one of `regions`,
+    /// ignoring case (`--region`).
+    pub fn in_regions(&self, regions: &[String]) -> bool {
+        self.locations
+            .iter()
+            .any(|location| regions.iter().any(|region| region.trim().eq_ignore_ascii_case(location.trim())))
+    }
+
+    /// Lowercased, de-duplicated entity names of the article.
+    pub fn entity_names(&self) -> BTreeSet<String> {
+        self.namedEntities
+            .iter()
+            .map(|e| e.name.trim().to_lowercase())
+            .filter(|name| !name.is_empty())
+            .collect()
+    }
+
+    /// Stable Markdown anchor for the article, e.g. `a-3f9c2d`.
+    ///
+    /// Derived from the source URL (falling back to the title), so links to
+    /// an article survive regenerating the edition even if the LLM words the
+    /// title differently.
+    pub fn anchor_id(&self) -> String {
+        let key = self.source.as_deref().unwrap_or(&self.title);
+        format!("a-{}", &content_hash(key)[..6])
     }
 }
 
+/// Anchor ids of one edition's articles, unique within the edition.
+///
+/// Two articles can share an [`AwfulNewsArticle::anchor_id`] (the same URL
+/// listed twice, or identical titles without a source). Repeats get `-2`,
+/// `-3`, ... in edition order, so the Markdown body and the TOC always agree
+/// on which article an anchor points to.
+#[derive(Debug)]
+pub struct EditionAnchors<'a> {
+    articles: &'a [AwfulNewsArticle],
+    ids: Vec<String>,
+}
+
+impl<'a> EditionAnchors<'a> {
+    /// Assign anchors to the articles of an edition, in edition order.
+    pub fn new(articles: &'a [AwfulNewsArticle]) -> Self {
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        let ids = articles
+            .iter()
+            .map(|article| {
+                let base = article.anchor_id();
+                let count = seen.entry(base.clone()).or_insert(0);
+                *count += 1;
+                if *count == 1 { base } else { format!("{}-{}", base, count) }
+            })
+            .collect();
+        EditionAnchors { articles, ids }
+    }
+
+    /// Anchor of `article`, which must belong to this edition; articles
+    /// from elsewhere get their plain [`AwfulNewsArticle::anchor_id`].
+    pub fn id(&self, article: &AwfulNewsArticle) -> String {
+        self.articles
+            .iter()
+            .position(|a| std::ptr::eq(a, article))
+            .map_or_else(|| article.anchor_id(), |i| self.ids[i].clone())
+    }
+}
+
+/// Limits applied to the model's takeaways and tags after parsing.
+///
+/// Configured from the CLI (`--max-takeaways`, `--max-tags`, ...); the
+/// defaults keep the Markdown edition readable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArticleLimits {
+    /// Maximum number of key takeaways kept (the first N).
+    pub max_takeaways: usize,
+    /// Takeaways shorter than this many characters are dropped.
+    pub min_takeaway_chars: usize,
+    /// Takeaways longer than this many characters are dropped.
+    pub max_takeaway_chars: usize,
+    /// Maximum number of tags kept (the first N).
+    pub max_tags: usize,
+    /// Tags with more words than this are dropped; shorter multi-word tags
+    /// are converted to kebab-case.
+    pub max_tag_words: usize,
+}
+
+/// Key takeaways kept per article when `--max-takeaways` is not given.
+pub const DEFAULT_MAX_TAKEAWAYS: usize = 7;
+
+/// Shortest takeaway kept when `--min-takeaway-chars` is not given.
+pub const DEFAULT_MIN_TAKEAWAY_CHARS: usize = 10;
+
+/// Longest takeaway kept when `--max-takeaway-chars` is not given.
+pub const DEFAULT_MAX_TAKEAWAY_CHARS: usize = 300;
+
+/// Tags kept per article when `--max-tags` is not given.
+pub const DEFAULT_MAX_TAGS: usize = 8;
+
+/// Most words in a tag when `--max-tag-words` is not given.
+pub const DEFAULT_MAX_TAG_WORDS: usize = 3;
+
+impl Default for ArticleLimits {
+    fn default() -> Self {
+        ArticleLimits {
+            max_takeaways: DEFAULT_MAX_TAKEAWAYS,
+            min_takeaway_chars: DEFAULT_MIN_TAKEAWAY_CHARS,
+            max_takeaway_chars: DEFAULT_MAX_TAKEAWAY_CHARS,
+            max_tags: DEFAULT_MAX_TAGS,
+            max_tag_words: DEFAULT_MAX_TAG_WORDS,
+        }
+    }
+}
+
+impl AwfulNewsArticle {
+    /// Drop takeaways outside the length bounds, then keep the first
+    /// `max_takeaways`.
+    pub fn sanitize_takeaways(&mut self, limits: &ArticleLimits) {
+        let takeaways = std::mem::take(&mut self.keyTakeAways);
+        self.keyTakeAways = takeaways
+            .into_iter()
+            .map(|t| t.trim().to_string())
+            .filter(|t| {
+                let chars = t.chars().count();
+                chars >= limits.min_takeaway_chars && chars <= limits.max_takeaway_chars
+            })
+            .take(limits.max_takeaways)
+            .collect();
+    }
+
+    /// Lowercase and kebab-case tags, drop sentence-like tags, dedupe, and
+    /// keep the first `max_tags`.
+    pub fn sanitize_tags(&mut self, limits: &ArticleLimits) {
+        let mut seen = std::collections::HashSet::new();
+        let tags = std::mem::take(&mut self.tags);
+        self.tags = tags
+            .into_iter()
+            .filter_map(|tag| {
+                let lower = tag.to_lowercase();
+                let words: Vec<&str> = lower.split_whitespace().collect();
+                (!words.is_empty() && words.len() <= limits.max_tag_words).then(|| words.join("-"))
+            })
+            .filter(|tag| seen.insert(tag.clone()))
+            .take(limits.max_tags)
+            .collect();
+    }
+}
+
+/// Extract the outlet's domain name (before .com/.co.uk/etc) from a URL.
+///
+/// The suffix is looked up in the [Public Suffix List](https://publicsuffix.org/),
+/// so multi-part suffixes are skipped whole.
+/// For example: "https://lite.cnn.com/article" -> "cnn",
+/// "https://news.bbc.co.uk/article" -> "bbc"
+pub fn source_tag_of(url: &str) -> Option<String> {
+    let parsed = url::Url::parse(url).ok()?;
+    let host = parsed.host_str()?;
+    // The registrable domain, e.g. "bbc.co.uk", starts with the name
+    if let Some(domain) = psl::domain_str(host) {
+        return domain.split('.').next().map(str::to_string);
+    }
+    // Hosts without one (a bare suffix) fall back to the label before the last
+    let parts: Vec<&str> = host.split('.').collect();
+    (parts.len() >= 2).then(|| parts[parts.len() - 2].to_string())
+}
+
 /// A named entity (person, organization, place, etc.) extracted from an article.
 ///
 /// Named entities help readers quickly identify key players and organizations
@@ -124,7 +666,7 @@ impl AwfulNewsArticle {
 /// - Organization: "NATO" - "Military alliance"
 /// - Place: "Kyiv" - "Capital city of Ukraine"
 #[allow(non_snake_case)]
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct NamedEntity {
     /// The name of the entity.
     pub name: String,
@@ -132,6 +674,31 @@ pub struct NamedEntity {
     pub whatIsThisEntity: String,
     /// Explanation of why this entity is relevant to the article.
     pub whyIsThisEntityRelevantToTheArticle: String,
+    /// The kind of entity, one of [`ENTITY_TYPES`] or "Other" as asked of
+    /// the model; absent in editions written before entities were typed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entityType: Option<String>,
+}
+
+/// Entity types the model classifies entities as, in display order.
+pub const ENTITY_TYPES: &[&str] = &["Person", "Organization", "Place", "Event"];
+
+impl NamedEntity {
+    /// The entity's type as one of [`ENTITY_TYPES`], also recognizing other
+    /// spellings ("org", "location", ...) in any case; other types are kept
+    /// as given. `None` when untyped or "Other".
+    pub fn entity_type(&self) -> Option<String> {
+        let raw = self.entityType.as_deref()?.trim();
+        let canonical = match raw.to_lowercase().as_str() {
+            "" | "other" | "unknown" => return None,
+            "person" | "people" => "Person",
+            "organization" | "organisation" | "org" => "Organization",
+            "place" | "location" | "country" | "city" => "Place",
+            "event" => "Event",
+            _ => return Some(raw.to_string()),
+        };
+        Some(canonical.to_string())
+    }
 }
 
 /// A significant date mentioned in an article.
@@ -139,7 +706,7 @@ pub struct NamedEntity {
 /// Important dates help readers understand the timeline of events
 /// and when key moments occurred or are scheduled to occur.
 #[allow(non_snake_case)]
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct ImportantDate {
     /// The date as mentioned in the article (may be in various formats).
     pub dateMentionedInArticle: String,
@@ -153,7 +720,7 @@ pub struct ImportantDate {
 /// of time that are relevant to the story, such as policy windows,
 /// event durations, or historical periods.
 #[allow(non_snake_case)]
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct ImportantTimeframe {
     /// The start of the time period.
     pub approximateTimeFrameStart: String,
@@ -163,28 +730,165 @@ pub struct ImportantTimeframe {
     pub descriptionOfWhyTimeFrameIsRelevant: String,
 }
 
+/// Parse one timeframe bound into the span of days it covers.
+///
+/// Understands full dates (`2025-05-06`, `May 6, 2025`, `6 May 2025`),
+/// months (`2025-05`, `May 2025`) and bare years (`2025`).
+fn parse_bound(raw: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let raw = raw.trim().trim_end_matches('.');
+    for fmt in ["%Y-%m-%d", "%B %d, %Y", "%b %d, %Y", "%d %B %Y", "%d %b %Y"] {
+        if let Ok(day) = NaiveDate::parse_from_str(raw, fmt) {
+            return Some((day, day));
+        }
+    }
+
+    let month_start = NaiveDate::parse_from_str(&format!("{}-01", raw), "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(&format!("1 {}", raw), "%d %B %Y"))
+        .or_else(|_| NaiveDate::parse_from_str(&format!("1 {}", raw), "%d %b %Y"));
+    if let Ok(start) = month_start {
+        let next = if start.month() == 12 {
+            NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)?
+        } else {
+            NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)?
+        };
+        return Some((start, next - Duration::days(1)));
+    }
+
+    if raw.len() == 4 && raw.chars().all(|c| c.is_ascii_digit()) {
+        let year = raw.parse().ok()?;
+        return Some((NaiveDate::from_ymd_opt(year, 1, 1)?, NaiveDate::from_ymd_opt(year, 12, 31)?));
+    }
+    None
+}
+
+/// The date range covered by a timeframe, if both bounds parse and are ordered.
+fn timeframe_range(tf: &ImportantTimeframe) -> Option<(NaiveDate, NaiveDate)> {
+    let (start, _) = parse_bound(&tf.approximateTimeFrameStart)?;
+    let (_, end) = parse_bound(&tf.approximateTimeFrameEnd)?;
+    (start <= end).then_some((start, end))
+}
+
+fn description_words(description: &str) -> Vec<String> {
+    description
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Whether two descriptions are about the same thing.
+///
+/// True when one word set contains the other ("ceasefire period" vs "the
+/// ceasefire period in Gaza") or they overlap by at least half (Jaccard
+/// index).
+fn descriptions_similar(a: &str, b: &str) -> bool {
+    let a: std::collections::BTreeSet<_> = description_words(a).into_iter().collect();
+    let b: std::collections::BTreeSet<_> = description_words(b).into_iter().collect();
+    if a.is_empty() || b.is_empty() {
+        return a == b;
+    }
+    let shared = a.intersection(&b).count();
+    let total = a.union(&b).count();
+    shared == a.len().min(b.len()) || shared * 2 >= total
+}
+
+/// Deduplicate and merge timeframes extracted from one article.
+///
+/// Timeframes whose bounds parse are merged when their ranges overlap or are
+/// adjacent (one day apart) and their descriptions are similar; the merged
+/// entry spans both ranges and keeps the longer description. Timeframes with
+/// unparseable bounds fall back to exact (case- and punctuation-insensitive)
+/// description comparison. Input order is otherwise preserved.
+pub fn normalize_timeframes(timeframes: Vec<ImportantTimeframe>) -> Vec<ImportantTimeframe> {
+    let mut out: Vec<(ImportantTimeframe, Option<(NaiveDate, NaiveDate)>)> = Vec::new();
+
+    for tf in timeframes {
+        let range = timeframe_range(&tf);
+        let existing = out.iter_mut().find(|(other, other_range)| match (range, *other_range) {
+            (Some((start, end)), Some((other_start, other_end))) => {
+                start <= other_end + Duration::days(1)
+                    && other_start <= end + Duration::days(1)
+                    && descriptions_similar(
+                        &tf.descriptionOfWhyTimeFrameIsRelevant,
+                        &other.descriptionOfWhyTimeFrameIsRelevant,
+                    )
+            }
+            _ => {
+                description_words(&tf.descriptionOfWhyTimeFrameIsRelevant)
+                    == description_words(&other.descriptionOfWhyTimeFrameIsRelevant)
+            }
+        });
+
+        let Some((kept, kept_range)) = existing else {
+            out.push((tf, range));
+            continue;
+        };
+
+        if let (Some((start, end)), Some((kept_start, kept_end))) = (range, *kept_range) {
+            if start < kept_start {
+                kept.approximateTimeFrameStart = tf.approximateTimeFrameStart.clone();
+            }
+            if end > kept_end {
+                kept.approximateTimeFrameEnd = tf.approximateTimeFrameEnd.clone();
+            }
+            *kept_range = Some((start.min(kept_start), end.max(kept_end)));
+        }
+        if tf.descriptionOfWhyTimeFrameIsRelevant.len() > kept.descriptionOfWhyTimeFrameIsRelevant.len() {
+            kept.descriptionOfWhyTimeFrameIsRelevant = tf.descriptionOfWhyTimeFrameIsRelevant;
+        }
+    }
+
+    out.into_iter().map(|(tf, _)| tf).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support;
 
     #[test]
     fn test_news_article_creation() {
         let article = NewsArticle {
             source: "https://example.com".to_string(),
-            content: "Test content".to_string(),
+            content: "Test content".into(),
+            published_at: None,
+            authors: vec![],
+            fetched_at: None,
+            fetched_from: None,
         };
         assert_eq!(article.source, "https://example.com");
-        assert_eq!(article.content, "Test content");
+        assert_eq!(&*article.content, "Test content");
     }
 
     #[test]
-    fn test_frontpage_serialization() {
-        let frontpage = FrontPage {
-            local_date: "2025-05-06".to_string(),
-            time_of_day: "evening".to_string(),
-            local_time: "20:30:00".to_string(),
-            articles: vec![],
+    fn test_shared_content_serializes_as_a_plain_string() {
+        let article = NewsArticle {
+            source: "https://text.npr.org/1".to_string(),
+            content: "Line one.\n\nLine \"two\".".into(),
+            published_at: None,
+            authors: vec![],
+            fetched_at: None,
+            fetched_from: None,
         };
+        let json = serde_json::to_string(&article).unwrap();
+        assert_eq!(json, r#"{"source":"https://text.npr.org/1","content":"Line one.\n\nLine \"two\"."}"#);
+        let restored: NewsArticle = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.content, article.content);
+
+        let processed: AwfulNewsArticle = serde_json::from_str(
+            r#"{"title": "T", "category": "C", "summaryOfNewsArticle": "S.", "content": "Line one."}"#,
+        )
+        .unwrap();
+        assert_eq!(processed.content.as_deref(), Some("Line one."));
+        let value = serde_json::to_value(&processed).unwrap();
+        assert_eq!(value["content"], "Line one.");
+        let value = serde_json::to_value(AwfulNewsArticle { content: None, ..processed }).unwrap();
+        assert!(value["content"].is_null());
+    }
+
+    #[test]
+    fn test_frontpage_serialization() {
+        let frontpage = test_support::front_page("evening", "20:30:00");
 
         let json = serde_json::to_string(&frontpage).unwrap();
         assert!(json.contains("2025-05-06"));
@@ -210,21 +914,18 @@ mod tests {
     fn test_awful_news_article_with_entities() {
         let article = AwfulNewsArticle {
             source: Some("https://example.com".to_string()),
-            dateOfPublication: "2025-05-06".to_string(),
             timeOfPublication: "14:30:00".to_string(),
-            title: "Test Article".to_string(),
-            category: "Politics & Governance".to_string(),
             summaryOfNewsArticle: "Summary here".to_string(),
             keyTakeAways: vec!["Key point 1".to_string()],
             namedEntities: vec![NamedEntity {
                 name: "Entity Name".to_string(),
                 whatIsThisEntity: "Description".to_string(),
                 whyIsThisEntityRelevantToTheArticle: "Relevance".to_string(),
+                entityType: None,
             }],
-            importantDates: vec![],
-            importantTimeframes: vec![],
             tags: vec!["politics".to_string(), "news".to_string()],
-            content: Some("Full content".to_string()),
+            content: Some("Full content".into()),
+            ..test_support::article("Test Article", "Politics & Governance")
         };
 
         assert_eq!(article.title, "Test Article");
@@ -234,12 +935,99 @@ mod tests {
         assert_eq!(article.namedEntities[0].name, "Entity Name");
     }
 
+    #[test]
+    fn test_locations_deserialize_when_present_or_absent() {
+        let with = r#"{
+            "title": "Grain deal extended",
+            "category": "Economy & Business",
+            "summaryOfNewsArticle": "Shipments continue.",
+            "locations": ["Ukraine", "Black Sea"]
+        }"#;
+        let article: AwfulNewsArticle = serde_json::from_str(with).unwrap();
+        assert_eq!(article.locations, vec!["Ukraine", "Black Sea"]);
+
+        // Editions written before locations existed still load
+        let without = r#"{
+            "source": "https://text.npr.org/1",
+            "title": "Grain deal extended",
+            "category": "Economy & Business",
+            "summaryOfNewsArticle": "Shipments continue.",
+            "content": null
+        }"#;
+        let article: AwfulNewsArticle = serde_json::from_str(without).unwrap();
+        assert!(article.locations.is_empty());
+    }
+
+    #[test]
+    fn test_scrape_meta_round_trips_and_is_optional() {
+        let json = r#"{"title": "T", "category": "C", "summaryOfNewsArticle": "S."}"#;
+        let mut article: AwfulNewsArticle = serde_json::from_str(json).unwrap();
+        // Editions written before scrapeMeta existed still load, and don't gain it
+        assert!(article.scrapeMeta.is_none());
+        assert!(serde_json::to_value(&article).unwrap().get("scrapeMeta").is_none());
+
+        article.scrapeMeta = Some(ScrapeMeta {
+            scraper: "bbcnews".to_string(),
+            version: "1.2.3".to_string(),
+            fetched_at: DateTime::parse_from_rfc3339("2025-05-06T08:00:00Z").ok().map(|t| t.to_utc()),
+            content_length: 4210,
+            selector_hash: "0123456789ab".to_string(),
+            fetched_from: Some("https://www.bbc.co.uk/news/articles/c1?at_medium=RSS".to_string()),
+        });
+        let value = serde_json::to_value(&article).unwrap();
+        assert_eq!(
+            value["scrapeMeta"],
+            serde_json::json!({
+                "scraper": "bbcnews",
+                "version": "1.2.3",
+                "fetchedAt": "2025-05-06T08:00:00Z",
+                "contentLength": 4210,
+                "selectorHash": "0123456789ab",
+                "fetchedFrom": "https://www.bbc.co.uk/news/articles/c1?at_medium=RSS"
+            })
+        );
+        let restored: AwfulNewsArticle = serde_json::from_value(value).unwrap();
+        assert_eq!(restored.scrapeMeta, article.scrapeMeta);
+
+        // Editions written with snake_case names still load
+        let old: ScrapeMeta = serde_json::from_value(serde_json::json!({
+            "scraper": "bbcnews",
+            "version": "1.2.3",
+            "fetched_at": "2025-05-06T08:00:00Z",
+            "content_length": 4210,
+            "selector_hash": "0123456789ab",
+            "fetched_from": "https://www.bbc.co.uk/news/articles/c1?at_medium=RSS"
+        }))
+        .unwrap();
+        assert_eq!(Some(old), article.scrapeMeta);
+    }
+
+    #[test]
+    fn test_in_regions_matches_any_location_ignoring_case() {
+        let located = |locations: &[&str]| -> AwfulNewsArticle {
+            serde_json::from_value(serde_json::json!({
+                "title": "T", "category": "C", "summaryOfNewsArticle": "S", "locations": locations
+            }))
+            .unwrap()
+        };
+        let regions = vec!["ukraine".to_string(), " Middle East ".to_string()];
+
+        assert!(located(&["Ukraine", "Poland"]).in_regions(&regions));
+        assert!(located(&["MIDDLE EAST"]).in_regions(&regions));
+        assert!(!located(&["Poland"]).in_regions(&regions));
+        // Partial names don't match
+        assert!(!located(&["Ukrainian border"]).in_regions(&regions));
+        // Articles without locations never match a region
+        assert!(!located(&[]).in_regions(&regions));
+    }
+
     #[test]
     fn test_named_entity_serialization() {
         let entity = NamedEntity {
             name: "John Doe".to_string(),
             whatIsThisEntity: "A person".to_string(),
             whyIsThisEntityRelevantToTheArticle: "Main subject".to_string(),
+            entityType: None,
         };
 
         let json = serde_json::to_string(&entity).unwrap();
@@ -247,6 +1035,37 @@ mod tests {
         assert_eq!(deserialized.name, "John Doe");
     }
 
+    #[test]
+    fn test_named_entity_type_is_optional() {
+        let typed: NamedEntity = serde_json::from_str(
+            r#"{"name": "NATO", "whatIsThisEntity": "An alliance", "whyIsThisEntityRelevantToTheArticle": "It met", "entityType": "organisation"}"#,
+        )
+        .unwrap();
+        assert_eq!(typed.entityType.as_deref(), Some("organisation"));
+        assert_eq!(typed.entity_type().as_deref(), Some("Organization"));
+        assert!(serde_json::to_string(&typed).unwrap().contains(r#""entityType":"organisation""#));
+
+        let untyped: NamedEntity = serde_json::from_str(
+            r#"{"name": "NATO", "whatIsThisEntity": "An alliance", "whyIsThisEntityRelevantToTheArticle": "It met"}"#,
+        )
+        .unwrap();
+        assert_eq!(untyped.entityType, None);
+        assert_eq!(untyped.entity_type(), None);
+        // Older editions stay byte for byte the same when written back
+        assert!(!serde_json::to_string(&untyped).unwrap().contains("entityType"));
+
+        let other = NamedEntity {
+            entityType: Some("Other".to_string()),
+            ..untyped.clone()
+        };
+        assert_eq!(other.entity_type(), None);
+        let custom = NamedEntity {
+            entityType: Some("Treaty".to_string()),
+            ..untyped
+        };
+        assert_eq!(custom.entity_type().as_deref(), Some("Treaty"));
+    }
+
     #[test]
     fn test_important_date_structure() {
         let date = ImportantDate {
@@ -269,21 +1088,25 @@ mod tests {
         assert_eq!(timeframe.approximateTimeFrameEnd, "2025-12-31");
     }
 
+    #[test]
+    fn test_source_tag_of_skips_multi_part_suffixes() {
+        assert_eq!(source_tag_of("https://news.bbc.co.uk/2/hi/world/1.stm").as_deref(), Some("bbc"));
+        assert_eq!(source_tag_of("https://www.independent.co.uk/news/world").as_deref(), Some("independent"));
+        assert_eq!(source_tag_of("https://bbc.co.uk/news").as_deref(), Some("bbc"));
+        assert_eq!(source_tag_of("https://lite.cnn.com/2025/05/06/article").as_deref(), Some("cnn"));
+        assert_eq!(source_tag_of("https://text.npr.org/nx-s1-1").as_deref(), Some("npr"));
+        assert_eq!(source_tag_of("https://www.abc.net.au/news").as_deref(), Some("abc"));
+        assert_eq!(source_tag_of("https://localhost/news"), None);
+        assert_eq!(source_tag_of("not a url"), None);
+    }
+
     #[test]
     fn test_source_tag_cnn() {
         let article = AwfulNewsArticle {
             source: Some("https://lite.cnn.com/2025/05/06/article".to_string()),
-            dateOfPublication: "2025-05-06".to_string(),
             timeOfPublication: "14:30:00".to_string(),
-            title: "Test".to_string(),
-            category: "Politics & Governance".to_string(),
             summaryOfNewsArticle: "Summary".to_string(),
-            keyTakeAways: vec![],
-            namedEntities: vec![],
-            importantDates: vec![],
-            importantTimeframes: vec![],
-            tags: vec![],
-            content: None,
+            ..test_support::article("Test", "Politics & Governance")
         };
 
         assert_eq!(article.source_tag(), Some("cnn".to_string()));
@@ -293,17 +1116,9 @@ mod tests {
     fn test_source_tag_npr() {
         let article = AwfulNewsArticle {
             source: Some("https://text.npr.org/article".to_string()),
-            dateOfPublication: "2025-05-06".to_string(),
             timeOfPublication: "14:30:00".to_string(),
-            title: "Test".to_string(),
-            category: "Politics & Governance".to_string(),
             summaryOfNewsArticle: "Summary".to_string(),
-            keyTakeAways: vec![],
-            namedEntities: vec![],
-            importantDates: vec![],
-            importantTimeframes: vec![],
-            tags: vec![],
-            content: None,
+            ..test_support::article("Test", "Politics & Governance")
         };
 
         assert_eq!(article.source_tag(), Some("npr".to_string()));
@@ -312,18 +1127,9 @@ mod tests {
     #[test]
     fn test_source_tag_no_source() {
         let article = AwfulNewsArticle {
-            source: None,
-            dateOfPublication: "2025-05-06".to_string(),
             timeOfPublication: "14:30:00".to_string(),
-            title: "Test".to_string(),
-            category: "Politics & Governance".to_string(),
             summaryOfNewsArticle: "Summary".to_string(),
-            keyTakeAways: vec![],
-            namedEntities: vec![],
-            importantDates: vec![],
-            importantTimeframes: vec![],
-            tags: vec![],
-            content: None,
+            ..test_support::article("Test", "Politics & Governance")
         };
 
         assert_eq!(article.source_tag(), None);
@@ -333,19 +1139,150 @@ mod tests {
     fn test_source_tag_simple_domain() {
         let article = AwfulNewsArticle {
             source: Some("https://example.com/article".to_string()),
-            dateOfPublication: "2025-05-06".to_string(),
             timeOfPublication: "14:30:00".to_string(),
-            title: "Test".to_string(),
-            category: "Politics & Governance".to_string(),
             summaryOfNewsArticle: "Summary".to_string(),
-            keyTakeAways: vec![],
-            namedEntities: vec![],
-            importantDates: vec![],
-            importantTimeframes: vec![],
-            tags: vec![],
-            content: None,
+            ..test_support::article("Test", "Politics & Governance")
         };
 
         assert_eq!(article.source_tag(), Some("example".to_string()));
     }
+
+    fn tf(start: &str, end: &str, description: &str) -> ImportantTimeframe {
+        ImportantTimeframe {
+            approximateTimeFrameStart: start.to_string(),
+            approximateTimeFrameEnd: end.to_string(),
+            descriptionOfWhyTimeFrameIsRelevant: description.to_string(),
+        }
+    }
+
+    fn bounds(timeframes: &[ImportantTimeframe]) -> Vec<(&str, &str)> {
+        timeframes
+            .iter()
+            .map(|t| (t.approximateTimeFrameStart.as_str(), t.approximateTimeFrameEnd.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_normalize_timeframes_merges_overlapping_ranges() {
+        let merged = normalize_timeframes(vec![
+            tf("2025-05-01", "2025-05-10", "Ceasefire period"),
+            tf("2025-05-08", "2025-05-20", "The ceasefire period in Gaza"),
+        ]);
+        assert_eq!(bounds(&merged), vec![("2025-05-01", "2025-05-20")]);
+        assert_eq!(merged[0].descriptionOfWhyTimeFrameIsRelevant, "The ceasefire period in Gaza");
+    }
+
+    #[test]
+    fn test_normalize_timeframes_merges_adjacent_mixed_formats() {
+        let merged = normalize_timeframes(vec![
+            tf("May 2025", "May 2025", "Budget negotiations"),
+            tf("2025-06-01", "June 30, 2025", "Budget negotiations"),
+        ]);
+        assert_eq!(bounds(&merged), vec![("May 2025", "June 30, 2025")]);
+    }
+
+    #[test]
+    fn test_normalize_timeframes_keeps_distinct_ranges_and_topics() {
+        let merged = normalize_timeframes(vec![
+            tf("2025-01-01", "2025-01-31", "Trial phase one"),
+            tf("2025-03-01", "2025-03-31", "Trial phase one"),
+            tf("2025-01-15", "2025-02-15", "Election campaign"),
+        ]);
+        assert_eq!(merged.len(), 3);
+    }
+
+    #[test]
+    fn test_normalize_timeframes_drops_exact_duplicates() {
+        let merged = normalize_timeframes(vec![
+            tf("2024", "2025", "Drought years"),
+            tf("2024", "2025", "Drought years"),
+        ]);
+        assert_eq!(bounds(&merged), vec![("2024", "2025")]);
+    }
+
+    #[test]
+    fn test_normalize_timeframes_unparseable_falls_back_to_description() {
+        let merged = normalize_timeframes(vec![
+            tf("the early 1990s", "late 1990s", "Dot-com boom."),
+            tf("1995", "2000", "dot-com boom"),
+            tf("ongoing", "unknown", "Recovery effort"),
+        ]);
+        assert_eq!(bounds(&merged), vec![("the early 1990s", "late 1990s"), ("ongoing", "unknown")]);
+    }
+
+    fn with_lists(takeaways: &[&str], tags: &[&str]) -> AwfulNewsArticle {
+        AwfulNewsArticle {
+            dateOfPublication: String::new(),
+            summaryOfNewsArticle: "S".to_string(),
+            keyTakeAways: takeaways.iter().map(|s| s.to_string()).collect(),
+            tags: tags.iter().map(|s| s.to_string()).collect(),
+            ..test_support::article("T", "C")
+        }
+    }
+
+    #[test]
+    fn test_sanitize_takeaways_caps_count_keeping_first() {
+        let many: Vec<String> = (0..25).map(|i| format!("Takeaway number {}", i)).collect();
+        let many: Vec<&str> = many.iter().map(String::as_str).collect();
+        let mut article = with_lists(&many, &[]);
+        article.sanitize_takeaways(&ArticleLimits::default());
+        assert_eq!(article.keyTakeAways.len(), 7);
+        assert_eq!(article.keyTakeAways[0], "Takeaway number 0");
+        assert_eq!(article.keyTakeAways[6], "Takeaway number 6");
+    }
+
+    #[test]
+    fn test_sanitize_takeaways_drops_too_short_and_too_long() {
+        let long = "x".repeat(301);
+        let mut article = with_lists(&["Too short", &long, "  Just the right length.  "], &[]);
+        article.sanitize_takeaways(&ArticleLimits::default());
+        assert_eq!(article.keyTakeAways, vec!["Just the right length."]);
+    }
+
+    #[test]
+    fn test_sanitize_tags_lowercases_and_dedupes() {
+        let mut article = with_lists(&[], &["Economy", "economy", "ECONOMY ", "Trade"]);
+        article.sanitize_tags(&ArticleLimits::default());
+        assert_eq!(article.tags, vec!["economy", "trade"]);
+    }
+
+    #[test]
+    fn test_sanitize_tags_kebab_cases_and_drops_sentences() {
+        let mut article = with_lists(
+            &[],
+            &["Interest Rates", "federal reserve board", "the central bank raised rates again", "  "],
+        );
+        article.sanitize_tags(&ArticleLimits::default());
+        assert_eq!(article.tags, vec!["interest-rates", "federal-reserve-board"]);
+    }
+
+    #[test]
+    fn test_sanitize_tags_caps_count() {
+        let mut article = with_lists(&[], &["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"]);
+        article.sanitize_tags(&ArticleLimits { max_tags: 3, ..ArticleLimits::default() });
+        assert_eq!(article.tags, vec!["a", "b", "c"]);
+    }
+
+    fn edition(local_date: &str, time_of_day: &str, local_time: &str) -> FrontPage {
+        FrontPage {
+            local_date: local_date.to_string(),
+            ..test_support::front_page(time_of_day, local_time)
+        }
+    }
+
+    #[test]
+    fn test_edition_date_rolls_post_midnight_evening_back() {
+        let page = edition("2025-05-07", "evening", "00:30:12.123456");
+        assert_eq!(page.edition_date(), "2025-05-06");
+        assert_eq!(page.edition_stem(), "2025-05-06_evening");
+        // Month and year boundaries
+        assert_eq!(edition("2025-01-01", "evening", "00:05:00").edition_date(), "2024-12-31");
+    }
+
+    #[test]
+    fn test_edition_date_keeps_regular_editions() {
+        assert_eq!(edition("2025-05-06", "evening", "20:00:00.5").edition_date(), "2025-05-06");
+        assert_eq!(edition("2025-05-07", "morning", "00:30:00").edition_date(), "2025-05-07");
+        assert_eq!(edition("2025-05-06", "evening", "garbage").edition_date(), "2025-05-06");
+    }
 }
@@ -0,0 +1,280 @@
+//! Deterministic ordering of articles within an edition.
+//!
+//! Articles come out of the processing stage in whatever order the parallel
+//! LLM calls happened to complete. This module sorts them into a stable,
+//! reproducible order before any output is written, so identical inputs
+//! always produce identical JSON and Markdown.
+//!
+//! # Strategies
+//!
+//! | Strategy | Primary | Secondary | Tertiary |
+//! |----------|---------|-----------|----------|
+//! | `category` (default) | Category priority | Publication time (newest first) | Source, then title |
+//! | `time` | Publication time (newest first) | Category priority | Source, then title |
+//! | `source` | Source | Category priority | Publication time, then title |
+//!
+//! Category priority follows the order of the category enum in the
+//! `news_parser` template; unknown categories sort after the known ones.
+//!
+//! `--json-sort` orders the JSON edition by another strategy than
+//! `--article-order`; the Markdown keeps `--article-order` (see
+//! [`sorted_for_json`](crate::outputs::json::sorted_for_json)).
+//!
+//! # Category Caps
+//!
+//! With `--max-per-category N`, [`partition_overflow`] keeps the N most
+//! recent articles of each category and flags the rest as overflow.
+//! Overflow articles stay in the JSON but are rendered only as a compact
+//! "Also today" list in Markdown.
+
+use crate::models::AwfulNewsArticle;
+use clap::ValueEnum;
+use std::cmp::Ordering;
+
+/// Category names in display priority order.
+///
+/// Mirrors the `category` enum in the `news_parser` template.
+pub const CATEGORY_PRIORITY: &[&str] = &[
+    "Politics & Governance",
+    "Economy & Business",
+    "Law & Justice",
+    "Conflict & War",
+    "Environment & Climate",
+    "Science & Technology",
+    "Health & Public Safety",
+    "Society & Culture",
+    "Human Rights & Immigration",
+    "Special Interest / Other",
+];
+
+/// Strategy used to order the articles of an edition.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ArticleOrder {
+    /// Group by category priority, newest first within each category.
+    #[default]
+    Category,
+    /// Newest first across the whole edition.
+    Time,
+    /// Group by source outlet.
+    Source,
+}
+
+/// Position of a category in [`CATEGORY_PRIORITY`], or past the end if unknown.
+pub fn category_rank(category: &str) -> usize {
+    CATEGORY_PRIORITY
+        .iter()
+        .position(|c| c.eq_ignore_ascii_case(category.trim()))
+        .unwrap_or(CATEGORY_PRIORITY.len())
+}
+
+fn by_category(a: &AwfulNewsArticle, b: &AwfulNewsArticle) -> Ordering {
+    category_rank(&a.category)
+        .cmp(&category_rank(&b.category))
+        .then_with(|| a.category.cmp(&b.category))
+}
+
+fn by_time_desc(a: &AwfulNewsArticle, b: &AwfulNewsArticle) -> Ordering {
+    (&b.dateOfPublication, &b.timeOfPublication).cmp(&(&a.dateOfPublication, &a.timeOfPublication))
+}
+
+fn by_source(a: &AwfulNewsArticle, b: &AwfulNewsArticle) -> Ordering {
+    a.source_tag().cmp(&b.source_tag())
+}
+
+/// Title, falling back to the full source URL so distinct articles never tie.
+fn by_title(a: &AwfulNewsArticle, b: &AwfulNewsArticle) -> Ordering {
+    a.title.cmp(&b.title).then_with(|| a.source.cmp(&b.source))
+}
+
+/// Sort articles in place using the given strategy.
+///
+/// The sort is stable, so articles that compare equal on every key keep
+/// their relative order.
+pub fn sort_articles(articles: &mut [AwfulNewsArticle], order: ArticleOrder) {
+    match order {
+        ArticleOrder::Category => articles.sort_by(|a, b| {
+            by_category(a, b)
+                .then_with(|| by_time_desc(a, b))
+                .then_with(|| by_source(a, b))
+                .then_with(|| by_title(a, b))
+        }),
+        ArticleOrder::Time => articles.sort_by(|a, b| {
+            by_time_desc(a, b)
+                .then_with(|| by_category(a, b))
+                .then_with(|| by_source(a, b))
+                .then_with(|| by_title(a, b))
+        }),
+        ArticleOrder::Source => articles.sort_by(|a, b| {
+            by_source(a, b)
+                .then_with(|| by_category(a, b))
+                .then_with(|| by_time_desc(a, b))
+                .then_with(|| by_title(a, b))
+        }),
+    }
+}
+
+/// Decide which articles exceed the per-category cap.
+///
+/// Within each category the `max_per_category` most recent articles are
+/// kept; ties keep their edition order. Carried-over articles have their own
+/// section and are never flagged. Returns one flag per input article, `true`
+/// meaning the article overflows.
+pub fn partition_overflow(articles: &[AwfulNewsArticle], max_per_category: usize) -> Vec<bool> {
+    let mut by_category: Vec<(&str, Vec<usize>)> = Vec::new();
+    for (i, article) in articles.iter().enumerate().filter(|(_, a)| !a.carriedOver) {
+        match by_category.iter_mut().find(|(c, _)| *c == article.category) {
+            Some((_, indices)) => indices.push(i),
+            None => by_category.push((&article.category, vec![i])),
+        }
+    }
+
+    let mut overflow = vec![false; articles.len()];
+    for (_, mut indices) in by_category {
+        indices.sort_by(|&a, &b| by_time_desc(&articles[a], &articles[b]));
+        for &i in indices.iter().skip(max_per_category) {
+            overflow[i] = true;
+        }
+    }
+    overflow
+}
+
+/// Group articles by category, preserving the order in which categories and
+/// articles first appear.
+///
+/// Renderers use this instead of an alphabetical map so that the Markdown
+/// edition and the TOC both follow the edition's sort order. Overflow and
+/// carried-over articles are left out; they are rendered separately.
+pub fn group_by_category(articles: &[AwfulNewsArticle]) -> Vec<(&str, Vec<&AwfulNewsArticle>)> {
+    let mut groups: Vec<(&str, Vec<&AwfulNewsArticle>)> = Vec::new();
+    for article in articles.iter().filter(|a| !a.overflow && !a.carriedOver) {
+        match groups.iter_mut().find(|(c, _)| *c == article.category) {
+            Some((_, list)) => list.push(article),
+            None => groups.push((&article.category, vec![article])),
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn article(title: &str, category: &str, source: &str, date: &str, time: &str) -> AwfulNewsArticle {
+        AwfulNewsArticle {
+            source: Some(source.to_string()),
+            dateOfPublication: date.to_string(),
+            timeOfPublication: time.to_string(),
+            ..test_support::article(title, category)
+        }
+    }
+
+    fn titles(articles: &[AwfulNewsArticle]) -> Vec<&str> {
+        articles.iter().map(|a| a.title.as_str()).collect()
+    }
+
+    fn sample() -> Vec<AwfulNewsArticle> {
+        vec![
+            article("Storm", "Environment & Climate", "https://text.npr.org/1", "2025-05-06", "09:00:00"),
+            article("Vote", "Politics & Governance", "https://lite.cnn.com/1", "2025-05-06", "08:00:00"),
+            article("Budget", "Politics & Governance", "https://text.npr.org/2", "2025-05-06", "12:00:00"),
+            article("Oddity", "Unlisted Category", "https://www.bbc.com/news/articles/x", "2025-05-07", "01:00:00"),
+            article("Ruling", "Law & Justice", "https://lite.cnn.com/2", "2025-05-06", "12:00:00"),
+        ]
+    }
+
+    #[test]
+    fn test_category_order_uses_priority_then_newest() {
+        let mut articles = sample();
+        sort_articles(&mut articles, ArticleOrder::Category);
+        assert_eq!(titles(&articles), vec!["Budget", "Vote", "Ruling", "Storm", "Oddity"]);
+    }
+
+    #[test]
+    fn test_time_order_is_newest_first() {
+        let mut articles = sample();
+        sort_articles(&mut articles, ArticleOrder::Time);
+        assert_eq!(titles(&articles), vec!["Oddity", "Budget", "Ruling", "Storm", "Vote"]);
+    }
+
+    #[test]
+    fn test_source_order_groups_outlets() {
+        let mut articles = sample();
+        sort_articles(&mut articles, ArticleOrder::Source);
+        assert_eq!(titles(&articles), vec!["Oddity", "Vote", "Ruling", "Budget", "Storm"]);
+    }
+
+    #[test]
+    fn test_ties_break_on_source_then_title() {
+        let mut articles = vec![
+            article("B", "Law & Justice", "https://text.npr.org/1", "2025-05-06", "10:00:00"),
+            article("A", "Law & Justice", "https://text.npr.org/1", "2025-05-06", "10:00:00"),
+            article("C", "Law & Justice", "https://lite.cnn.com/1", "2025-05-06", "10:00:00"),
+        ];
+        sort_articles(&mut articles, ArticleOrder::Category);
+        assert_eq!(titles(&articles), vec!["C", "A", "B"]);
+    }
+
+    #[test]
+    fn test_sort_is_independent_of_input_order() {
+        let mut forward = sample();
+        let mut reversed = sample();
+        reversed.reverse();
+        for order in [ArticleOrder::Category, ArticleOrder::Time, ArticleOrder::Source] {
+            sort_articles(&mut forward, order);
+            sort_articles(&mut reversed, order);
+            assert_eq!(titles(&forward), titles(&reversed));
+        }
+    }
+
+    #[test]
+    fn test_group_by_category_preserves_order() {
+        let mut articles = sample();
+        sort_articles(&mut articles, ArticleOrder::Time);
+        let groups = group_by_category(&articles);
+        let categories: Vec<&str> = groups.iter().map(|(c, _)| *c).collect();
+        assert_eq!(
+            categories,
+            vec!["Unlisted Category", "Politics & Governance", "Law & Justice", "Environment & Climate"]
+        );
+        assert_eq!(groups[1].1.len(), 2);
+    }
+
+    #[test]
+    fn test_partition_overflow_uneven_categories() {
+        let mut articles = vec![
+            article("P1", "Politics & Governance", "https://a.com/1", "2025-05-06", "08:00:00"),
+            article("P2", "Politics & Governance", "https://a.com/2", "2025-05-06", "12:00:00"),
+            article("S1", "Science & Technology", "https://a.com/3", "2025-05-06", "09:00:00"),
+            article("P3", "Politics & Governance", "https://a.com/4", "2025-05-06", "10:00:00"),
+            article("P4", "Politics & Governance", "https://a.com/5", "2025-05-05", "23:00:00"),
+            article("L1", "Law & Justice", "https://a.com/6", "2025-05-06", "07:00:00"),
+            article("L2", "Law & Justice", "https://a.com/7", "2025-05-06", "07:30:00"),
+        ];
+
+        let flags = partition_overflow(&articles, 2);
+        let overflowed: Vec<&str> = articles
+            .iter()
+            .zip(&flags)
+            .filter(|(_, o)| **o)
+            .map(|(a, _)| a.title.as_str())
+            .collect();
+        assert_eq!(overflowed, vec!["P1", "P4"]);
+
+        // Overflow articles drop out of the category groups
+        for (article, overflow) in articles.iter_mut().zip(flags) {
+            article.overflow = overflow;
+        }
+        let groups = group_by_category(&articles);
+        assert_eq!(groups[0].1.len(), 2);
+        assert_eq!(groups.iter().map(|(_, a)| a.len()).sum::<usize>(), 5);
+    }
+
+    #[test]
+    fn test_partition_overflow_under_cap_and_zero() {
+        let articles = sample();
+        assert!(partition_overflow(&articles, 5).iter().all(|o| !o));
+        assert!(partition_overflow(&articles, 0).iter().all(|o| *o));
+        assert!(partition_overflow(&[], 3).is_empty());
+    }
+}
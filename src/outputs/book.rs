@@ -0,0 +1,390 @@
+//! mdBook layout of the Markdown output directory.
+//!
+//! The editions and index files are written flat into one directory, and
+//! `SUMMARY.md` links `./home.md`, `./pgp.md` and `./contact.md` next to
+//! it, so that directory must be the book's source directory. At startup,
+//! `--markdown-output-dir` is checked against the `book.toml` found in it
+//! or its parent:
+//!
+//! - a book root whose `[book] src` is a subdirectory (mdBook's default
+//!   `src/`): the output goes to that subdirectory;
+//! - the book's source directory (`src = "."`, or named by the parent's
+//!   `book.toml`): the output goes there as given;
+//! - no `book.toml`: `mdbook build` would fail, so with `--scaffold-book`
+//!   (or a yes at the prompt when run from a terminal) a minimal book is
+//!   created. Otherwise a warning is logged and the run continues.
+//!
+//! # Scaffold
+//!
+//! ```text
+//! markdown_output_dir/
+//! ├── book.toml       # title, src = "src"
+//! └── src/
+//!     ├── SUMMARY.md
+//!     ├── home.md
+//!     ├── pgp.md
+//!     └── contact.md
+//! ```
+//!
+//! A directory already holding a `SUMMARY.md` keeps its flat layout and
+//! gets `src = "."` instead, so earlier editions stay where they are.
+//! Existing pages are never overwritten.
+//!
+//! # Build
+//!
+//! With `--mdbook-build`, `mdbook build` (`--mdbook-path`, optionally with
+//! `--mdbook-dest-dir`) runs on the book once the edition and its indexes
+//! are written, so a broken book shows up in the run instead of a stale
+//! site. Its output is captured; a non-zero exit or a run longer than
+//! `--mdbook-timeout-secs` (the process is killed) fails the build, which is
+//! reported as `output.book.failed` but never fails the run. The command is
+//! run through a [`CommandRunner`], so tests can fake it.
+
+use crate::outputs::indexes::DEFAULT_SUMMARY;
+use std::error::Error;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::fs;
+use tokio::process::Command;
+use tracing::{debug, info, warn};
+
+/// Title of a scaffolded book, the same as the edition header.
+pub const BOOK_TITLE: &str = "Awful Times";
+
+/// Seconds `mdbook build` may take unless `--mdbook-timeout-secs` says otherwise.
+pub const DEFAULT_MDBOOK_TIMEOUT_SECS: u64 = 120;
+
+/// Source directory mdBook uses when `book.toml` names none.
+const DEFAULT_SRC: &str = "src";
+
+/// Placeholder pages linked from the default `SUMMARY.md`.
+const PLACEHOLDER_PAGES: &[(&str, &str)] = &[
+    ("home.md", "# Awful Times\n\nSummaries of the day's news.\n"),
+    ("pgp.md", "# PGP\n\nPublish your public key here.\n"),
+    ("contact.md", "# Contact\n\nTell readers how to reach you here.\n"),
+];
+
+/// How the Markdown output directory relates to an mdBook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Layout {
+    /// The directory is the book's source directory.
+    Source,
+    /// The directory is a book root; the sources are in `src`.
+    Root { src: PathBuf },
+    /// No `book.toml` in the directory or its parent.
+    Missing,
+}
+
+/// The `src` setting of the `[book]` table of a `book.toml`, or mdBook's
+/// default.
+pub fn book_src(book_toml: &str) -> String {
+    let mut in_book = false;
+    for line in book_toml.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_book = line == "[book]";
+            continue;
+        }
+        if in_book
+            && let Some((key, value)) = line.split_once('=')
+            && key.trim() == "src"
+        {
+            let value = value.split('#').next().unwrap_or_default().trim();
+            return value.trim_matches(|c| c == '"' || c == '\'').to_string();
+        }
+    }
+    DEFAULT_SRC.to_string()
+}
+
+/// Whether `src` names the directory `book.toml` is in.
+fn is_book_dir(src: &str) -> bool {
+    matches!(src.trim_end_matches('/'), "" | ".")
+}
+
+/// Find the mdBook `dir` belongs to.
+pub async fn detect(dir: &Path) -> Layout {
+    if let Ok(toml) = fs::read_to_string(dir.join("book.toml")).await {
+        let src = book_src(&toml);
+        return if is_book_dir(&src) {
+            Layout::Source
+        } else {
+            Layout::Root { src: dir.join(src) }
+        };
+    }
+    if let Some(parent) = dir.parent()
+        && let Ok(toml) = fs::read_to_string(parent.join("book.toml")).await
+        && parent.join(book_src(&toml)) == dir
+    {
+        return Layout::Source;
+    }
+    Layout::Missing
+}
+
+/// `book.toml` of a scaffolded book whose sources are in `src`.
+fn book_toml(src: &str) -> String {
+    format!("[book]\ntitle = \"{}\"\nlanguage = \"en\"\nsrc = \"{}\"\n", BOOK_TITLE, src)
+}
+
+/// Write `contents` to `path` unless the file exists.
+async fn create_new(path: &Path, contents: &str) -> io::Result<()> {
+    if fs::try_exists(path).await? {
+        return Ok(());
+    }
+    fs::write(path, contents).await
+}
+
+/// Create a minimal mdBook in `dir`.
+///
+/// # Returns
+///
+/// The book's source directory, where the Markdown output goes.
+pub async fn scaffold(dir: &Path) -> io::Result<PathBuf> {
+    let (src, sources) = if fs::try_exists(dir.join("SUMMARY.md")).await? {
+        (".", dir.to_path_buf())
+    } else {
+        (DEFAULT_SRC, dir.join(DEFAULT_SRC))
+    };
+    fs::create_dir_all(&sources).await?;
+    create_new(&dir.join("book.toml"), &book_toml(src)).await?;
+    create_new(&sources.join("SUMMARY.md"), DEFAULT_SUMMARY).await?;
+    for (name, contents) in PLACEHOLDER_PAGES {
+        create_new(&sources.join(name), contents).await?;
+    }
+    Ok(sources)
+}
+
+/// Ask on the terminal whether to scaffold a book in `dir`; `false` when
+/// not run from a terminal.
+fn confirm_scaffold(dir: &Path) -> bool {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return false;
+    }
+    eprint!("{} is not an mdBook. Create book.toml and placeholder pages? [y/N] ", dir.display());
+    let _ = io::stderr().flush();
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// The directory the Markdown output goes to for `--markdown-output-dir`
+/// `dir`, scaffolding a book first if there is none and `scaffold_book` is
+/// set (or confirmed at the prompt).
+pub async fn resolve(dir: &str, scaffold_book: bool) -> Result<String, Box<dyn Error>> {
+    let path = Path::new(dir);
+    match detect(path).await {
+        Layout::Source => Ok(dir.to_string()),
+        Layout::Root { src } => {
+            info!(book = %dir, src = %src.display(), "Writing Markdown to the book's source directory");
+            Ok(src.to_string_lossy().into_owned())
+        }
+        Layout::Missing if scaffold_book || confirm_scaffold(path) => {
+            let src = scaffold(path).await?;
+            info!(book = %dir, src = %src.display(), "Scaffolded an mdBook");
+            Ok(src.to_string_lossy().into_owned())
+        }
+        Layout::Missing => {
+            warn!(path = %dir, "Markdown output directory has no book.toml; mdbook build will fail (see --scaffold-book)");
+            Ok(dir.to_string())
+        }
+    }
+}
+
+/// What a finished command printed and how it exited.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandOutput {
+    /// Exit code; `None` if the process was killed by a signal.
+    pub code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Runs external commands.
+pub trait CommandRunner {
+    /// Run `program` with `args` to completion, capturing its output.
+    async fn run(&self, program: &str, args: &[String]) -> io::Result<CommandOutput>;
+}
+
+/// Runs commands as child processes; a process still running when the
+/// future is dropped (on timeout) is killed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Subprocess;
+
+impl CommandRunner for Subprocess {
+    async fn run(&self, program: &str, args: &[String]) -> io::Result<CommandOutput> {
+        let output = Command::new(program).args(args).kill_on_drop(true).output().await?;
+        Ok(CommandOutput {
+            code: output.status.code(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+}
+
+/// The book root (the directory holding `book.toml`) of the source
+/// directory `markdown_dir`.
+async fn book_root(markdown_dir: &Path) -> PathBuf {
+    if !fs::try_exists(markdown_dir.join("book.toml")).await.unwrap_or(false)
+        && let Some(parent) = markdown_dir.parent()
+        && fs::try_exists(parent.join("book.toml")).await.unwrap_or(false)
+    {
+        return parent.to_path_buf();
+    }
+    markdown_dir.to_path_buf()
+}
+
+/// Run `{mdbook} build` on the book of `markdown_dir`, into `dest_dir` if
+/// given, for at most `timeout`.
+///
+/// # Returns
+///
+/// How long the build took, or an error with mdbook's output if it could
+/// not start, exited with a failure or timed out.
+pub async fn build(
+    runner: &impl CommandRunner,
+    mdbook: &str,
+    markdown_dir: &str,
+    dest_dir: Option<&str>,
+    timeout: Duration,
+) -> Result<Duration, Box<dyn Error>> {
+    let root = book_root(Path::new(markdown_dir)).await;
+    let mut args = vec!["build".to_string(), root.to_string_lossy().into_owned()];
+    if let Some(dest_dir) = dest_dir {
+        args.extend(["--dest-dir".to_string(), dest_dir.to_string()]);
+    }
+
+    info!(%mdbook, book = %root.display(), "Building the book");
+    let started = Instant::now();
+    let output = match tokio::time::timeout(timeout, runner.run(mdbook, &args)).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => return Err(format!("could not run {}: {}", mdbook, e).into()),
+        Err(_) => return Err(format!("{} build timed out after {}s", mdbook, timeout.as_secs()).into()),
+    };
+    debug!(stdout = %output.stdout, stderr = %output.stderr, "mdbook output");
+    if output.code != Some(0) {
+        let code = output.code.map_or("a signal".to_string(), |c| format!("status {}", c));
+        return Err(format!("{} build exited with {}: {}", mdbook, code, output.stderr.trim()).into());
+    }
+    Ok(started.elapsed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_book_src() {
+        assert_eq!(book_src("[book]\ntitle = \"News\"\n"), "src");
+        assert_eq!(book_src("[book]\nsrc = \"pages\" # sources\n"), "pages");
+        assert_eq!(book_src("[build]\nsrc = \"nope\"\n[book]\nsrc = '.'\n"), ".");
+    }
+
+    #[tokio::test]
+    async fn test_scaffold_creates_a_book_the_indexes_fit() {
+        let dir = tempfile::tempdir().unwrap();
+        let book = dir.path().join("news");
+        assert_eq!(detect(&book).await, Layout::Missing);
+
+        let src = resolve(book.to_str().unwrap(), true).await.unwrap();
+        assert_eq!(Path::new(&src), book.join("src"));
+        assert_eq!(book_src(&std::fs::read_to_string(book.join("book.toml")).unwrap()), "src");
+        // Every page SUMMARY.md links is there
+        let summary = std::fs::read_to_string(book.join("src/SUMMARY.md")).unwrap();
+        for name in ["home.md", "pgp.md", "contact.md"] {
+            assert!(summary.contains(&format!("(./{})", name)));
+            assert!(book.join("src").join(name).is_file(), "{}", name);
+        }
+
+        // Pointing at the root or the sources both land in src/
+        assert_eq!(detect(&book).await, Layout::Root { src: book.join("src") });
+        assert_eq!(detect(&book.join("src")).await, Layout::Source);
+        assert_eq!(resolve(&src, false).await.unwrap(), src);
+    }
+
+    #[tokio::test]
+    async fn test_scaffold_keeps_flat_output_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("SUMMARY.md"), "# Summary\n\n- [Daily News](./daily_news.md)\n").unwrap();
+        std::fs::write(dir.path().join("home.md"), "# My home\n").unwrap();
+
+        assert_eq!(scaffold(dir.path()).await.unwrap(), dir.path());
+        assert_eq!(book_src(&std::fs::read_to_string(dir.path().join("book.toml")).unwrap()), ".");
+        assert_eq!(std::fs::read_to_string(dir.path().join("home.md")).unwrap(), "# My home\n");
+        assert!(std::fs::read_to_string(dir.path().join("SUMMARY.md")).unwrap().contains("daily_news"));
+        assert!(dir.path().join("pgp.md").is_file());
+        assert_eq!(detect(dir.path()).await, Layout::Source);
+    }
+
+    /// Fake runner: answers with `output` after `delay`, recording the call.
+    struct FakeRunner {
+        output: CommandOutput,
+        delay: Duration,
+        calls: std::sync::Mutex<Vec<(String, Vec<String>)>>,
+    }
+
+    impl FakeRunner {
+        fn new(output: CommandOutput, delay: Duration) -> Self {
+            Self {
+                output,
+                delay,
+                calls: Default::default(),
+            }
+        }
+    }
+
+    impl CommandRunner for FakeRunner {
+        async fn run(&self, program: &str, args: &[String]) -> io::Result<CommandOutput> {
+            self.calls.lock().unwrap().push((program.to_string(), args.to_vec()));
+            tokio::time::sleep(self.delay).await;
+            Ok(self.output.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_build_runs_mdbook_on_the_book_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = scaffold(dir.path()).await.unwrap();
+        let runner = FakeRunner::new(
+            CommandOutput {
+                code: Some(0),
+                ..Default::default()
+            },
+            Duration::ZERO,
+        );
+
+        build(&runner, "mdbook", src.to_str().unwrap(), Some("/srv/www"), Duration::from_secs(5))
+            .await
+            .unwrap();
+        let root = dir.path().to_string_lossy().into_owned();
+        assert_eq!(
+            runner.calls.lock().unwrap()[0],
+            (
+                "mdbook".to_string(),
+                vec!["build".to_string(), root, "--dest-dir".to_string(), "/srv/www".to_string()]
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_build_reports_a_failed_exit() {
+        let runner = FakeRunner::new(
+            CommandOutput {
+                code: Some(101),
+                stdout: String::new(),
+                stderr: "ERROR Chapter file not found, ./pgp.md\n".to_string(),
+            },
+            Duration::ZERO,
+        );
+        let err = build(&runner, "mdbook", "/tmp/news", None, Duration::from_secs(5)).await.unwrap_err();
+        assert_eq!(err.to_string(), "mdbook build exited with status 101: ERROR Chapter file not found, ./pgp.md");
+        assert_eq!(runner.calls.lock().unwrap()[0].1, vec!["build", "/tmp/news"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_build_times_out() {
+        let runner = FakeRunner::new(CommandOutput::default(), Duration::from_secs(600));
+        let err = build(&runner, "mdbook", "/tmp/news", None, Duration::from_secs(30)).await.unwrap_err();
+        assert_eq!(err.to_string(), "mdbook build timed out after 30s");
+    }
+}
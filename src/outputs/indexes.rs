@@ -10,149 +10,180 @@
 //! - **SUMMARY.md**: mdBook navigation file with hierarchical structure
 //! - **daily_news.md**: Master index of all dates and editions
 //!
+//! With `--takeaways-digest`, **takeaways.md** collects the key takeaways
+//! of the recent editions as one skimmable page (see
+//! [`update_takeaways_digest`]).
+//!
+//! Weekly editions (the `weekly` subcommand) are listed newest first under a "Weekly"
+//! heading at the end of `SUMMARY.md` and `daily_news.md`.
+//!
 //! # Append vs Replace
 //!
 //! All functions in this module use append semantics to support multiple
-//! executions per day (morning, afternoon, evening editions).
+//! executions per day (morning, afternoon, evening editions): each reads
+//! the current index back through the [`OutputSink`] and writes it out
+//! with the new entries.
+//!
+//! Entries are labelled with the edition and its time, e.g. `Morning
+//! (08:00:00)`, at second precision with `--stable-timestamps` (see
+//! [`edition_time`]). An edition that is already listed, found by the file
+//! it links to, has its entry replaced rather than appended again, so a
+//! rerun never leaves a link under a stale title or time.
+//!
+//! An edition's three index updates are all or nothing: the new contents
+//! are computed first ([`date_toc_content`], [`summary_content`],
+//! [`daily_news_content`]) and a failed write puts back the files already
+//! written.
 
-use crate::models::FrontPage;
+use crate::models::{EditionAnchors, FrontPage};
+use crate::outputs::labels::SourceLabels;
+use crate::outputs::markdown::{edition_time, MarkdownOptions};
+use crate::outputs::sink::OutputSink;
+use crate::ordering::group_by_category;
 use crate::utils::{slugify_title, upcase};
+use chrono::{Duration, NaiveDate};
 use std::error::Error;
 use std::fmt::Write;
-use std::path::Path;
-use tokio::fs;
-use tokio::io::AsyncWriteExt;
-use tracing::{info, instrument};
+use tracing::{error, info, instrument, warn};
 
-/// Update the date-specific table of contents file.
-///
-/// Appends the current edition to a date-specific TOC file that lists all
-/// editions and articles for that day. Creates the file if it doesn't exist.
+/// Category and article links for one edition in the date TOC.
 ///
-/// # Arguments
-///
-/// * `markdown_output_dir` - Directory containing Markdown files
-/// * `front_page` - The processed articles for this edition
-/// * `markdown_filename` - Filename of the edition Markdown file
-///
-/// # Output
-///
-/// Appends to `{markdown_output_dir}/{date}.md` with edition links and
-/// article listings grouped by category.
-#[instrument(level = "info", skip_all, fields(%markdown_output_dir, date = %front_page.local_date, file = %markdown_filename))]
-pub async fn update_date_toc_file(
-    markdown_output_dir: &str,
-    front_page: &FrontPage,
-    markdown_filename: &str,
-) -> Result<(), Box<dyn Error>> {
-    let toc_path = format!("{}/{}.md", markdown_output_dir, front_page.local_date);
+/// Articles link to their stable id anchor (see
+/// [`AwfulNewsArticle::anchor_id`](crate::models::AwfulNewsArticle::anchor_id))
+/// while displaying the title, de-duplicated within the edition exactly as
+/// in the Markdown body (see [`EditionAnchors`]).
+fn edition_toc_entries(front_page: &FrontPage, markdown_filename: &str, labels: &SourceLabels) -> String {
+    let ids = EditionAnchors::new(&front_page.articles);
     let mut toc_md = String::new();
 
-    if !Path::new(&toc_path).exists() {
-        writeln!(
-            toc_md,
-            "# Editions published on {}\n",
-            front_page.local_date
-        )
-        .unwrap();
-    }
-
-    writeln!(
-        toc_md,
-        "- [{}](./{})",
-        upcase(&front_page.time_of_day),
-        markdown_filename
-    )
-    .unwrap();
-
-    // Group articles by category
-    use std::collections::BTreeMap;
-    let mut articles_by_category: BTreeMap<String, Vec<&crate::models::AwfulNewsArticle>> = BTreeMap::new();
-    
-    for article in &front_page.articles {
-        articles_by_category
-            .entry(article.category.clone())
-            .or_insert_with(Vec::new)
-            .push(article);
-    }
-
-    // Write articles organized by category (alphabetically)
-    for (category, articles) in articles_by_category {
-        let category_slug = slugify_title(&category);
+    // Write articles organized by category, in the same order as the edition
+    for (category, articles) in group_by_category(&front_page.articles) {
+        let category_slug = slugify_title(category);
         writeln!(toc_md, "\t- [**{}**]({}#{})", category, markdown_filename, category_slug).unwrap();
-        
+
         for article in articles {
-            let mut slug = slugify_title(&article.title);
-            let source_tag = article.source_tag()
-                .map(|tag| format!(" <small>`{}`</small>", tag))
+            let source_tag = labels.of(article)
+                .map(|label| format!(" <small>`{}`</small>", label))
                 .unwrap_or_default();
-            
-            // Append source tag to slug for proper anchor linking
-            if let Some(tag) = article.source_tag() {
-                slug.push_str("---");
-                slug.push_str(&tag);
-            }
-            
+
             writeln!(
                 toc_md,
                 "\t\t- {} - [{}]({}#{})",
-                source_tag, article.title, markdown_filename, slug
+                source_tag,
+                article.title,
+                markdown_filename,
+                ids.id(article)
             )
             .unwrap();
         }
     }
 
-    let mut file = tokio::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&toc_path)
-        .await?;
-    file.write_all(toc_md.as_bytes()).await?;
-    info!(path = %toc_path, "Updated TOC file");
-    Ok(())
+    let carried: Vec<_> = front_page.articles.iter().filter(|a| a.carriedOver).collect();
+    if !carried.is_empty() {
+        writeln!(toc_md, "\t- [**In case you missed it**]({}#in-case-you-missed-it)", markdown_filename).unwrap();
+        for article in carried {
+            writeln!(toc_md, "\t\t- [{}]({}#{})", article.title, markdown_filename, ids.id(article)).unwrap();
+        }
+    }
+
+    if front_page.articles.iter().any(|a| a.overflow) {
+        writeln!(toc_md, "\t- [**Also today**]({}#also-today)", markdown_filename).unwrap();
+    }
+
+    toc_md
 }
 
-/// Update the SUMMARY.md file for mdBook navigation.
-///
-/// Adds entries to the mdBook SUMMARY.md file to enable navigation to the
-/// new edition. Creates a default SUMMARY.md structure if the file doesn't exist.
-///
-/// # Arguments
-///
-/// * `markdown_output_dir` - Directory containing Markdown files
-/// * `front_page` - The processed articles for this edition
-/// * `markdown_filename` - Filename of the edition Markdown file
+/// The link text of an edition in the indexes, e.g. `Morning (08:00:00)`.
+fn edition_label(front_page: &FrontPage, stable_timestamps: bool) -> String {
+    format!(
+        "{} ({})",
+        upcase(&front_page.time_of_day),
+        edition_time(&front_page.local_time, stable_timestamps)
+    )
+}
+
+/// Whether `line` is an index entry linking to `markdown_filename`.
+fn links_to(line: &str, markdown_filename: &str) -> bool {
+    let line = line.trim();
+    line.starts_with("- [") && line.ends_with(&format!("](./{})", markdown_filename))
+}
+
+/// The date TOC with `front_page` listed.
 ///
-/// # Structure
+/// `existing` is the current `{date}.md`, if any; a new file starts with an
+/// "Editions published on" heading. The edition is listed with its articles
+/// grouped by category; an edition already listed has its block replaced in
+/// place, otherwise it is appended.
+pub fn date_toc_content(
+    existing: Option<&str>,
+    front_page: &FrontPage,
+    markdown_filename: &str,
+    options: &MarkdownOptions,
+) -> String {
+    let mut block = String::new();
+    writeln!(
+        block,
+        "- [{}](./{})",
+        edition_label(front_page, options.stable_timestamps),
+        markdown_filename
+    )
+    .unwrap();
+    block.push_str(&edition_toc_entries(front_page, markdown_filename, &options.labels));
+
+    let existing = existing.unwrap_or_default();
+    if existing.is_empty() {
+        return format!("# Editions published on {}\n\n{}", front_page.edition_date(), block);
+    }
+
+    let lines: Vec<&str> = existing.lines().collect();
+    let Some(start) = lines.iter().position(|l| links_to(l, markdown_filename)) else {
+        let mut toc_md = existing.to_string();
+        if !toc_md.ends_with('\n') {
+            toc_md.push('\n');
+        }
+        toc_md.push_str(&block);
+        return toc_md;
+    };
+    let end = start + 1 + lines[start + 1..].iter().take_while(|l| l.starts_with('\t')).count();
+
+    let mut toc_md = String::new();
+    for line in &lines[..start] {
+        writeln!(toc_md, "{}", line).unwrap();
+    }
+    toc_md.push_str(&block);
+    for line in &lines[end..] {
+        writeln!(toc_md, "{}", line).unwrap();
+    }
+    toc_md
+}
+
+/// SUMMARY.md written when the output directory has none yet.
+pub const DEFAULT_SUMMARY: &str =
+    "# Summary\n\n[Home](./home.md)\n- [PGP](./pgp.md)\n- [Contact](./contact.md)\n- [Daily News](./daily_news.md)\n";
+
+/// SUMMARY.md with `front_page` listed for mdBook navigation.
 ///
-/// Entries are added under the "Daily News" section with hierarchical nesting:
+/// `existing` is the current SUMMARY.md, if any; otherwise a default one is
+/// started. Entries go under the "Daily News" section, and an edition
+/// already listed has its entry replaced:
 /// ```text
 /// - [Daily News](./daily_news.md)
 ///     - [2025-05-06](./2025-05-06.md)
-///         - [Morning](./2025-05-06_morning.md)
+///         - [Morning (08:00:00)](./2025-05-06_morning.md)
 /// ```
-#[instrument(level = "info", skip_all, fields(%markdown_output_dir, date = %front_page.local_date, file = %markdown_filename))]
-pub async fn update_summary_md(
-    markdown_output_dir: &str,
+pub fn summary_content(
+    existing: Option<&str>,
     front_page: &FrontPage,
     markdown_filename: &str,
-) -> Result<(), Box<dyn Error>> {
-    let summary_path = format!("{}/SUMMARY.md", markdown_output_dir);
-    let mut summary = String::new();
+    stable_timestamps: bool,
+) -> String {
+    let summary = existing.unwrap_or(DEFAULT_SUMMARY);
 
-    if Path::new(&summary_path).exists() {
-        summary = fs::read_to_string(&summary_path).await?;
-    } else {
-        summary.push_str("# Summary\n\n[Home](./home.md)\n- [PGP](./pgp.md)\n- [Contact](./contact.md)\n- [Daily News](./daily_news.md)\n");
-    }
-
-    let date_heading = format!(
-        "    - [{}](./{}.md)",
-        front_page.local_date, front_page.local_date
-    );
+    let date = front_page.edition_date();
+    let date_heading = format!("    - [{}](./{}.md)", date, date);
     let edition_heading = format!(
         "        - [{}](./{})",
-        upcase(&front_page.time_of_day),
+        edition_label(front_page, stable_timestamps),
         markdown_filename
     );
 
@@ -165,13 +196,15 @@ pub async fn update_summary_md(
             let mut j = i + 1;
             let mut found_edition = false;
             while j < lines.len() && lines[j].starts_with("        - ") {
-                if lines[j].trim() == edition_heading.trim() {
+                if links_to(&lines[j], markdown_filename) {
                     found_edition = true;
                     break;
                 }
                 j += 1;
             }
-            if !found_edition {
+            if found_edition {
+                lines[j] = edition_heading.clone();
+            } else {
                 lines.insert(j, edition_heading.clone());
             }
             inserted = true;
@@ -188,54 +221,37 @@ pub async fn update_summary_md(
         }
     }
 
-    fs::write(&summary_path, lines.join("\n")).await?;
-    info!(path = %summary_path, "Updated SUMMARY.md");
-    Ok(())
+    lines.join("\n")
 }
 
-/// Update the daily_news.md master index file.
-///
-/// Adds entries to the master daily news index that lists all dates and
-/// editions. Creates the file with a header if it doesn't exist.
-///
-/// # Arguments
-///
-/// * `markdown_output_dir` - Directory containing Markdown files
-/// * `front_page` - The processed articles for this edition
-/// * `markdown_filename` - Filename of the edition Markdown file
-///
-/// # Structure
+/// daily_news.md written when the output directory has none yet.
+const DEFAULT_INDEX: &str = "# Awful News Index\n\n";
+
+/// daily_news.md with `front_page` listed in the master index.
 ///
-/// Entries are organized by date with nested edition links:
+/// `existing` is the current daily_news.md, if any; otherwise it starts
+/// with a header. Entries are organized by date, newest date first, and an
+/// edition already listed has its entry replaced:
 /// ```text
 /// # Awful News Index
 ///
 /// - [**2025-05-06**](./2025-05-06.md)
-///     - [Morning](./2025-05-06_morning.md)
-///     - [Evening](./2025-05-06_evening.md)
+///     - [Morning (08:00:00)](./2025-05-06_morning.md)
+///     - [Evening (18:00:00)](./2025-05-06_evening.md)
 /// ```
-#[instrument(level = "info", skip_all, fields(%markdown_output_dir, date = %front_page.local_date, file = %markdown_filename))]
-pub async fn update_daily_news_index(
-    markdown_output_dir: &str,
+pub fn daily_news_content(
+    existing: Option<&str>,
     front_page: &FrontPage,
     markdown_filename: &str,
-) -> Result<(), Box<dyn Error>> {
-    let index_path = format!("{}/daily_news.md", markdown_output_dir);
-    let mut content = String::new();
+    stable_timestamps: bool,
+) -> String {
+    let content = existing.unwrap_or(DEFAULT_INDEX);
 
-    if Path::new(&index_path).exists() {
-        content = fs::read_to_string(&index_path).await?;
-    } else {
-        content.push_str("# Awful News Index\n\n");
-    }
-
-    let date_heading = format!(
-        "- [**{}**](./{}.md)",
-        front_page.local_date, front_page.local_date
-    );
+    let date = front_page.edition_date();
+    let date_heading = format!("- [**{}**](./{}.md)", date, date);
     let edition_entry = format!(
         "    - [{}](./{})",
-        upcase(&front_page.time_of_day),
+        edition_label(front_page, stable_timestamps),
         markdown_filename
     );
 
@@ -248,13 +264,15 @@ pub async fn update_daily_news_index(
             let mut j = i + 1;
             let mut found_edition = false;
             while j < lines.len() && lines[j].starts_with("    - ") {
-                if lines[j].trim() == edition_entry.trim() {
+                if links_to(&lines[j], markdown_filename) {
                     found_edition = true;
                     break;
                 }
                 j += 1;
             }
-            if !found_edition {
+            if found_edition {
+                lines[j] = edition_entry.clone();
+            } else {
                 lines.insert(j, edition_entry.clone());
             }
             inserted = true;
@@ -278,7 +296,677 @@ pub async fn update_daily_news_index(
         }
     }
 
-    fs::write(&index_path, lines.join("\n")).await?;
-    info!(path = %index_path, "Updated daily_news.md index");
+    lines.join("\n")
+}
+
+/// One index file to replace: its new contents and what it held before
+/// (`None` if it didn't exist).
+struct IndexUpdate {
+    path: String,
+    prior: Option<String>,
+    content: String,
+}
+
+impl IndexUpdate {
+    /// Read the current contents of `path` and compute the new ones from it.
+    async fn read(
+        sink: &impl OutputSink,
+        path: String,
+        compute: impl FnOnce(Option<&str>) -> String,
+    ) -> Result<Self, Box<dyn Error>> {
+        let prior = sink.read_to_string(&path).await?;
+        let content = compute(prior.as_deref());
+        Ok(IndexUpdate { path, prior, content })
+    }
+}
+
+/// Write `updates` in order, all or nothing.
+///
+/// If a write fails, the files already written get their prior contents
+/// back (or are removed if they didn't exist) before the error is returned,
+/// so the indexes never disagree about which editions exist.
+async fn write_all(sink: &impl OutputSink, updates: &[IndexUpdate]) -> Result<(), Box<dyn Error>> {
+    for (i, update) in updates.iter().enumerate() {
+        if let Err(e) = sink.write(&update.path, update.content.as_bytes()).await {
+            for done in updates[..i].iter().rev() {
+                let restored = match &done.prior {
+                    Some(prior) => sink.write(&done.path, prior.as_bytes()).await,
+                    None => sink.remove(&done.path).await,
+                };
+                match restored {
+                    Ok(()) => warn!(path = %done.path, "Rolled back index file"),
+                    Err(e) => error!(path = %done.path, error = %e, "Failed to roll back index file"),
+                }
+            }
+            return Err(format!("failed to write {}: {}", update.path, e).into());
+        }
+    }
+    for update in updates {
+        info!(path = %update.path, "Updated index file");
+    }
     Ok(())
 }
+
+/// List a new edition in the date TOC, SUMMARY.md and daily_news.md.
+///
+/// All three new contents are computed before anything is written, and the
+/// writes are all or nothing (see [`write_all`]): a failure part way leaves
+/// the indexes as they were.
+///
+/// # Arguments
+///
+/// * `sink` - Where the index files are stored
+/// * `markdown_output_dir` - Directory containing Markdown files
+/// * `front_page` - The processed articles for this edition
+/// * `markdown_filename` - Filename of the edition Markdown file
+/// * `options` - The edition's Markdown options: source labels shown next
+///   to articles in the date TOC, and whether times are second precision
+#[instrument(level = "info", skip_all, fields(%markdown_output_dir, date = %front_page.edition_date(), file = %markdown_filename))]
+pub async fn update_edition_indexes(
+    sink: &impl OutputSink,
+    markdown_output_dir: &str,
+    front_page: &FrontPage,
+    markdown_filename: &str,
+    options: &MarkdownOptions,
+) -> Result<(), Box<dyn Error>> {
+    let updates = [
+        IndexUpdate::read(
+            sink,
+            format!("{}/{}.md", markdown_output_dir, front_page.edition_date()),
+            |toc| date_toc_content(toc, front_page, markdown_filename, options),
+        )
+        .await?,
+        IndexUpdate::read(sink, format!("{}/SUMMARY.md", markdown_output_dir), |summary| {
+            summary_content(summary, front_page, markdown_filename, options.stable_timestamps)
+        })
+        .await?,
+        IndexUpdate::read(sink, format!("{}/daily_news.md", markdown_output_dir), |index| {
+            daily_news_content(index, front_page, markdown_filename, options.stable_timestamps)
+        })
+        .await?,
+    ];
+    write_all(sink, &updates).await
+}
+
+/// List `entry` first under `heading`, adding the heading at the end of
+/// `content` if it is missing. An entry already listed is left in place.
+fn insert_under_heading(content: &str, heading: &str, entry: &str) -> String {
+    let mut lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+    if lines.iter().any(|l| l.trim() == entry) {
+        return lines.join("\n");
+    }
+    let pos = match lines.iter().position(|l| l.trim() == heading) {
+        Some(pos) => pos,
+        None => {
+            while lines.last().is_some_and(|l| l.trim().is_empty()) {
+                lines.pop();
+            }
+            lines.push(String::new());
+            lines.push(heading.to_string());
+            lines.len() - 1
+        }
+    };
+    lines.insert(pos + 1, entry.to_string());
+    lines.join("\n")
+}
+
+/// List a weekly edition under "Weekly" in SUMMARY.md and daily_news.md.
+///
+/// # Arguments
+///
+/// * `sink` - Where the index files are stored
+/// * `markdown_output_dir` - Directory containing Markdown files
+/// * `week_end` - Last date of the week, `YYYY-MM-DD`
+/// * `markdown_filename` - Filename of the weekly Markdown file
+///
+/// # Structure
+///
+/// ```text
+/// # Weekly
+/// - [Week ending 2025-05-11](./2025-05-11_weekly.md)
+/// - [Week ending 2025-05-04](./2025-05-04_weekly.md)
+/// ```
+///
+/// `daily_news.md` uses a `## Weekly` heading.
+#[instrument(level = "info", skip_all, fields(%markdown_output_dir, %week_end, file = %markdown_filename))]
+pub async fn update_weekly_indexes(
+    sink: &impl OutputSink,
+    markdown_output_dir: &str,
+    week_end: &str,
+    markdown_filename: &str,
+) -> Result<(), Box<dyn Error>> {
+    let entry = format!("- [Week ending {}](./{})", week_end, markdown_filename);
+
+    let updates = [
+        IndexUpdate::read(sink, format!("{}/SUMMARY.md", markdown_output_dir), |summary| {
+            insert_under_heading(summary.unwrap_or(DEFAULT_SUMMARY), "# Weekly", &entry)
+        })
+        .await?,
+        IndexUpdate::read(sink, format!("{}/daily_news.md", markdown_output_dir), |index| {
+            insert_under_heading(index.unwrap_or(DEFAULT_INDEX), "## Weekly", &entry)
+        })
+        .await?,
+    ];
+    write_all(sink, &updates).await
+}
+
+/// Days of takeaways kept before the current date when
+/// `--takeaways-retention-days` is not given.
+pub const DEFAULT_TAKEAWAYS_RETENTION_DAYS: u32 = 1;
+
+/// Heading of takeaways.md.
+const TAKEAWAYS_HEADING: &str = "# Key Takeaways";
+
+/// The SUMMARY.md entry of takeaways.md.
+const TAKEAWAYS_ENTRY: &str = "- [Key Takeaways](./takeaways.md)";
+
+/// One date of takeaways.md: its categories, each with its bullets, in the
+/// order they were first listed.
+type TakeawayDay = Vec<(String, Vec<String>)>;
+
+/// The dates of an existing takeaways.md, as written by [`takeaways_content`].
+fn parse_takeaways(content: &str) -> Vec<(NaiveDate, TakeawayDay)> {
+    let mut days: Vec<(NaiveDate, TakeawayDay)> = Vec::new();
+    for line in content.lines() {
+        if let Some(date) = line.strip_prefix("## ").and_then(|d| d.trim().parse().ok()) {
+            days.push((date, Vec::new()));
+        } else if let Some(category) = line.strip_prefix("### ")
+            && let Some((_, categories)) = days.last_mut()
+        {
+            categories.push((category.trim().to_string(), Vec::new()));
+        } else if line.starts_with("- ")
+            && let Some((_, categories)) = days.last_mut()
+            && let Some((_, bullets)) = categories.last_mut()
+        {
+            bullets.push(line.to_string());
+        }
+    }
+    days
+}
+
+/// takeaways.md with the key takeaways of `front_page` merged in.
+///
+/// `existing` is the current takeaways.md, if any. Takeaways are listed
+/// under the edition date, grouped by category, each linking to its
+/// article's anchor with the article's source label:
+/// ```text
+/// # Key Takeaways
+///
+/// ## 2025-05-06
+///
+/// ### Politics & Governance
+///
+/// - Vote was 7-2 ([→](./2025-05-06_morning.md#a-1b2c3d4e)) <small>`cnn`</small>
+/// ```
+///
+/// Bullets an earlier write of the same edition left are replaced, so
+/// running an edition again changes nothing; a takeaway already listed for
+/// the date by another edition isn't repeated. Dates more than
+/// `retention_days` before the edition date are dropped, and dates are
+/// listed newest first. "Also today" overflow articles have no anchor and
+/// are left out; categories follow the order articles appear in.
+pub fn takeaways_content(
+    existing: Option<&str>,
+    front_page: &FrontPage,
+    markdown_filename: &str,
+    labels: &SourceLabels,
+    retention_days: u32,
+) -> String {
+    let edition_date = front_page.edition_date();
+    let Ok(date) = edition_date.parse::<NaiveDate>() else {
+        warn!(date = %edition_date, "Edition date is not YYYY-MM-DD; leaving takeaways.md alone");
+        return existing.unwrap_or(TAKEAWAYS_HEADING).to_string();
+    };
+    let mut days = parse_takeaways(existing.unwrap_or_default());
+    let oldest = date - Duration::days(i64::from(retention_days));
+    days.retain(|(day, _)| *day >= oldest);
+
+    let index = match days.iter().position(|(day, _)| *day == date) {
+        Some(index) => index,
+        None => {
+            days.push((date, Vec::new()));
+            days.len() - 1
+        }
+    };
+    let categories = &mut days[index].1;
+    let link_prefix = format!("(./{}#", markdown_filename);
+    for (_, bullets) in categories.iter_mut() {
+        bullets.retain(|b| !b.contains(&link_prefix));
+    }
+
+    let ids = EditionAnchors::new(&front_page.articles);
+    for article in front_page.articles.iter().filter(|a| !a.overflow) {
+        let label = labels
+            .of(article)
+            .map(|label| format!(" <small>`{}`</small>", label))
+            .unwrap_or_default();
+        for takeaway in &article.keyTakeAways {
+            let text = takeaway.split_whitespace().collect::<Vec<_>>().join(" ");
+            if text.is_empty() {
+                continue;
+            }
+            let listed = format!("- {} (", text);
+            if categories.iter().flat_map(|(_, b)| b).any(|b| b.starts_with(&listed)) {
+                continue;
+            }
+            let bullet = format!("- {} ([→]{}{})){}", text, link_prefix, ids.id(article), label);
+            match categories.iter_mut().find(|(c, _)| *c == article.category) {
+                Some((_, bullets)) => bullets.push(bullet),
+                None => categories.push((article.category.clone(), vec![bullet])),
+            }
+        }
+    }
+    categories.retain(|(_, bullets)| !bullets.is_empty());
+
+    days.sort_by_key(|(day, _)| std::cmp::Reverse(*day));
+    let mut md = format!("{}
+", TAKEAWAYS_HEADING);
+    for (day, categories) in days.iter().filter(|(_, c)| !c.is_empty()) {
+        writeln!(md, "\n## {}", day).unwrap();
+        for (category, bullets) in categories {
+            writeln!(md, "\n### {}\n", category).unwrap();
+            for bullet in bullets {
+                writeln!(md, "{}", bullet).unwrap();
+            }
+        }
+    }
+    md
+}
+
+/// SUMMARY.md with takeaways.md listed once, just before "Daily News" (or
+/// at the end if there is no "Daily News" entry).
+fn summary_with_takeaways(existing: Option<&str>) -> String {
+    let summary = existing.unwrap_or(DEFAULT_SUMMARY);
+    let mut lines: Vec<String> = summary.lines().map(|l| l.to_string()).collect();
+    if lines.iter().any(|l| l.trim() == TAKEAWAYS_ENTRY) {
+        return summary.to_string();
+    }
+    match lines.iter().position(|l| l.starts_with("- [Daily News]")) {
+        Some(pos) => lines.insert(pos, TAKEAWAYS_ENTRY.to_string()),
+        None => lines.push(TAKEAWAYS_ENTRY.to_string()),
+    }
+    lines.join("\n")
+}
+
+/// Merge the key takeaways of a new edition into takeaways.md and list the
+/// page in SUMMARY.md (see [`takeaways_content`]).
+///
+/// Both files are written all or nothing, like the edition indexes.
+///
+/// # Arguments
+///
+/// * `sink` - Where the index files are stored
+/// * `markdown_output_dir` - Directory containing Markdown files
+/// * `front_page` - The processed articles for this edition
+/// * `markdown_filename` - Filename of the edition Markdown file
+/// * `labels` - Source labels shown next to each takeaway
+/// * `retention_days` - Days kept before the edition date
+#[instrument(level = "info", skip_all, fields(%markdown_output_dir, date = %front_page.edition_date(), file = %markdown_filename))]
+pub async fn update_takeaways_digest(
+    sink: &impl OutputSink,
+    markdown_output_dir: &str,
+    front_page: &FrontPage,
+    markdown_filename: &str,
+    labels: &SourceLabels,
+    retention_days: u32,
+) -> Result<(), Box<dyn Error>> {
+    let updates = [
+        IndexUpdate::read(sink, format!("{}/takeaways.md", markdown_output_dir), |digest| {
+            takeaways_content(digest, front_page, markdown_filename, labels, retention_days)
+        })
+        .await?,
+        IndexUpdate::read(sink, format!("{}/SUMMARY.md", markdown_output_dir), summary_with_takeaways).await?,
+    ];
+    write_all(sink, &updates).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use crate::models::AwfulNewsArticle;
+    use crate::outputs::sink::{FileSystem, MemorySink};
+    use crate::outputs::markdown::{
+        front_page_to_markdown, AnchorMode, MarkdownOptions, MarkdownTheme,
+    };
+
+    fn article(source: &str, title: &str, category: &str) -> AwfulNewsArticle {
+        AwfulNewsArticle {
+            source: Some(source.to_string()),
+            timeOfPublication: "09:00:00".to_string(),
+            summaryOfNewsArticle: "Summary.".to_string(),
+            ..test_support::article(title, category)
+        }
+    }
+
+    fn front_page() -> FrontPage {
+        FrontPage {
+            articles: vec![
+                article("https://lite.cnn.com/a", "Talks resume: what's next?", "Politics & Governance"),
+                article("https://text.npr.org/b", "New probe launched", "Science & Technology"),
+            ],
+            ..test_support::front_page("morning", "08:00:00")
+        }
+    }
+
+    /// Anchors linked from the TOC for `file` (article links only).
+    fn article_anchors(toc: &str, file: &str) -> Vec<String> {
+        toc.lines()
+            .filter(|l| l.starts_with("\t\t- "))
+            .filter_map(|l| l.split(&format!("]({}#", file)).nth(1))
+            .map(|rest| rest.trim_end_matches(')').to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_toc_links_resolve_to_id_anchors() {
+        let page = front_page();
+        let toc = edition_toc_entries(&page, "2025-05-06_morning.md", &SourceLabels::default());
+        let anchors = article_anchors(&toc, "2025-05-06_morning.md");
+        assert_eq!(anchors.len(), 2);
+        assert!(toc.contains("[Talks resume: what's next?](2025-05-06_morning.md#a-"));
+
+        let md = front_page_to_markdown(&page, &MarkdownOptions::default());
+        for anchor in &anchors {
+            assert!(md.contains(&format!("{{#{}}}", anchor)), "missing anchor {anchor}");
+        }
+
+        let md = front_page_to_markdown(
+            &page,
+            &MarkdownOptions {
+                anchors: AnchorMode::Both,
+                ..Default::default()
+            },
+        );
+        for anchor in &anchors {
+            assert!(md.contains(&format!("<a id=\"{}\"></a>", anchor)), "missing anchor {anchor}");
+        }
+    }
+
+    #[test]
+    fn test_anchor_is_stable_across_title_changes() {
+        let mut page = front_page();
+        let before = edition_toc_entries(&page, "e.md", &SourceLabels::default());
+        page.articles[0].title = "Talks resume; what comes next".to_string();
+        let after = edition_toc_entries(&page, "e.md", &SourceLabels::default());
+        assert_eq!(article_anchors(&before, "e.md"), article_anchors(&after, "e.md"));
+    }
+
+    #[test]
+    fn test_duplicate_titles_get_distinct_anchors_matching_the_body() {
+        let mut page = front_page();
+        // Same title, no source: both fall back to the title for their id
+        for category in ["World News", "Politics & Governance"] {
+            let mut live = article("", "Live updates", category);
+            live.source = None;
+            page.articles.push(live);
+        }
+        let toc = edition_toc_entries(&page, "e.md", &SourceLabels::default());
+        let anchors = article_anchors(&toc, "e.md");
+        let live: Vec<&String> = anchors.iter().filter(|a| a.starts_with(&page.articles[2].anchor_id())).collect();
+        assert_eq!(live.len(), 2);
+        assert_ne!(live[0], live[1]);
+        assert!(live.iter().any(|a| a.ends_with("-2")));
+
+        for theme in [MarkdownTheme::Headings, MarkdownTheme::Details] {
+            let md = front_page_to_markdown(
+                &page,
+                &MarkdownOptions {
+                    anchors: AnchorMode::Both,
+                    theme,
+                    ..Default::default()
+                },
+            );
+            for anchor in &anchors {
+                assert_eq!(md.matches(&format!("<a id=\"{}\"></a>", anchor)).count(), 1, "anchor {anchor}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_toc_and_body_use_source_labels() {
+        let mut page = front_page();
+        page.articles.push(article("https://www.reuters.com/c", "Markets rally", "Economy & Business"));
+        let labels = SourceLabels::new(&[]);
+
+        let toc = edition_toc_entries(&page, "e.md", &labels);
+        assert!(toc.contains("<small>`🔴 CNN`</small> - [Talks resume"));
+        assert!(toc.contains("<small>`reuters`</small> - [Markets rally"));
+
+        let md = front_page_to_markdown(
+            &page,
+            &MarkdownOptions {
+                labels,
+                ..Default::default()
+            },
+        );
+        assert!(md.contains("## New probe launched - <small>`📻 NPR`</small>"));
+        assert!(md.contains("## Markets rally - <small>`reuters`</small>"));
+    }
+
+    #[tokio::test]
+    async fn test_weekly_editions_are_listed_under_weekly() {
+        let dir = tempfile::tempdir().unwrap();
+        let md_dir = dir.path().to_str().unwrap();
+        let page = front_page();
+        update_edition_indexes(&FileSystem, md_dir, &page, "2025-05-06_morning.md", &MarkdownOptions::default())
+            .await
+            .unwrap();
+
+        update_weekly_indexes(&FileSystem, md_dir, "2025-05-04", "2025-05-04_weekly.md").await.unwrap();
+        update_weekly_indexes(&FileSystem, md_dir, "2025-05-11", "2025-05-11_weekly.md").await.unwrap();
+        // Registering the same week again changes nothing
+        update_weekly_indexes(&FileSystem, md_dir, "2025-05-11", "2025-05-11_weekly.md").await.unwrap();
+
+        let summary = std::fs::read_to_string(dir.path().join("SUMMARY.md")).unwrap();
+        assert!(summary.ends_with(
+            "        - [Morning (08:00:00)](./2025-05-06_morning.md)\n\n# Weekly\n\
+             - [Week ending 2025-05-11](./2025-05-11_weekly.md)\n\
+             - [Week ending 2025-05-04](./2025-05-04_weekly.md)"
+        ));
+
+        // A later daily edition still goes under Daily News
+        let mut next = front_page();
+        next.local_date = "2025-05-12".to_string();
+        update_edition_indexes(&FileSystem, md_dir, &next, "2025-05-12_morning.md", &MarkdownOptions::default())
+            .await
+            .unwrap();
+        let index = std::fs::read_to_string(dir.path().join("daily_news.md")).unwrap();
+        assert!(index.starts_with("# Awful News Index\n\n- [**2025-05-12**](./2025-05-12.md)"));
+        assert!(index.ends_with(
+            "## Weekly\n- [Week ending 2025-05-11](./2025-05-11_weekly.md)\n- [Week ending 2025-05-04](./2025-05-04_weekly.md)"
+        ));
+    }
+
+    /// Memory sink whose writes to `fail` are refused.
+    struct FailingSink {
+        files: MemorySink,
+        fail: String,
+    }
+
+    impl OutputSink for FailingSink {
+        async fn write(&self, path: &str, bytes: &[u8]) -> std::io::Result<()> {
+            if path == self.fail {
+                return Err(std::io::Error::other("disk full"));
+            }
+            self.files.write(path, bytes).await
+        }
+
+        async fn read(&self, path: &str) -> std::io::Result<Option<Vec<u8>>> {
+            self.files.read(path).await
+        }
+
+        async fn remove(&self, path: &str) -> std::io::Result<()> {
+            self.files.remove(path).await
+        }
+    }
+
+    #[test]
+    fn test_index_contents_are_computed_without_a_sink() {
+        let page = front_page();
+        let toc = date_toc_content(None, &page, "2025-05-06_morning.md", &MarkdownOptions::default());
+        assert!(toc.starts_with("# Editions published on 2025-05-06\n\n- [Morning (08:00:00)](./2025-05-06_morning.md)\n"));
+
+        let summary = summary_content(None, &page, "2025-05-06_morning.md", false);
+        assert!(summary.ends_with(
+            "- [Daily News](./daily_news.md)\n    - [2025-05-06](./2025-05-06.md)\n        - [Morning (08:00:00)](./2025-05-06_morning.md)"
+        ));
+        // Listing the same edition again changes nothing
+        assert_eq!(summary_content(Some(&summary), &page, "2025-05-06_morning.md", false), summary);
+
+        let index = daily_news_content(None, &page, "2025-05-06_morning.md", false);
+        assert_eq!(
+            index,
+            "# Awful News Index\n\n- [**2025-05-06**](./2025-05-06.md)\n    - [Morning (08:00:00)](./2025-05-06_morning.md)\n"
+        );
+        assert_eq!(daily_news_content(Some(&index), &page, "2025-05-06_morning.md", false), index.trim_end());
+    }
+
+    #[tokio::test]
+    async fn test_rerun_edition_replaces_its_index_entries() {
+        let sink = MemorySink::default();
+        let md_dir = "/md";
+        let mut morning = front_page();
+        morning.local_time = "08:00:00.123456".to_string();
+        let mut evening = front_page();
+        evening.time_of_day = "evening".to_string();
+        evening.local_time = "18:00:00.654321".to_string();
+        update_edition_indexes(&sink, md_dir, &morning, "2025-05-06_morning.md", &MarkdownOptions::default())
+            .await
+            .unwrap();
+        update_edition_indexes(&sink, md_dir, &evening, "2025-05-06_evening.md", &MarkdownOptions::default())
+            .await
+            .unwrap();
+        assert!(sink.text("/md/SUMMARY.md").contains("- [Morning (08:00:00.123456)](./2025-05-06_morning.md)"));
+
+        // The morning edition runs again with second precision and a new headline
+        morning.local_time = "08:05:00.999999".to_string();
+        morning.articles[1].title = "Probe reaches orbit".to_string();
+        let stable = MarkdownOptions {
+            stable_timestamps: true,
+            ..Default::default()
+        };
+        update_edition_indexes(&sink, md_dir, &morning, "2025-05-06_morning.md", &stable).await.unwrap();
+
+        let toc = sink.text("/md/2025-05-06.md");
+        assert!(toc.contains("Probe reaches orbit"));
+        assert_eq!(toc.matches("[New probe launched](2025-05-06_morning.md").count(), 0);
+        // Replaced in place, ahead of the evening edition
+        let morning_at = toc.find("- [Morning (08:05:00)](./2025-05-06_morning.md)").unwrap();
+        assert!(morning_at < toc.find("- [Evening (18:00:00.654321)](./2025-05-06_evening.md)").unwrap());
+
+        for file in ["/md/SUMMARY.md", "/md/daily_news.md"] {
+            let index = sink.text(file);
+            assert_eq!(index.matches("](./2025-05-06_morning.md)").count(), 1, "{file}");
+            assert!(index.contains("- [Morning (08:05:00)](./2025-05-06_morning.md)"), "{file}");
+            assert!(!index.contains("08:00:00.123456"), "{file}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_index_write_rolls_back_the_others() {
+        let md_dir = "/md";
+        let morning = front_page();
+        let mut afternoon = front_page();
+        afternoon.time_of_day = "afternoon".to_string();
+
+        let sink = FailingSink {
+            files: MemorySink::default(),
+            fail: String::new(),
+        };
+        update_edition_indexes(&sink, md_dir, &morning, "2025-05-06_morning.md", &MarkdownOptions::default())
+            .await
+            .unwrap();
+        let before = sink.files.files.lock().unwrap().clone();
+
+        // daily_news.md is written last: the TOC and SUMMARY.md get their prior bytes back
+        let sink = FailingSink {
+            files: MemorySink {
+                files: std::sync::Mutex::new(before.clone()),
+            },
+            fail: "/md/daily_news.md".to_string(),
+        };
+        let err = update_edition_indexes(&sink, md_dir, &afternoon, "2025-05-06_afternoon.md", &MarkdownOptions::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("/md/daily_news.md"));
+        assert_eq!(*sink.files.files.lock().unwrap(), before);
+
+        // Files that didn't exist before are removed again
+        let sink = FailingSink {
+            files: MemorySink::default(),
+            fail: "/md/SUMMARY.md".to_string(),
+        };
+        assert!(update_edition_indexes(&sink, md_dir, &morning, "2025-05-06_morning.md", &MarkdownOptions::default())
+            .await
+            .is_err());
+        assert!(sink.files.paths().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_takeaways_merge_across_editions() {
+        let sink = MemorySink::default();
+        let md_dir = "/md";
+        let mut morning = front_page();
+        morning.articles[0].keyTakeAways = vec!["Both sides returned".to_string(), "No deadline set".to_string()];
+        morning.articles[1].keyTakeAways = vec!["Launch is set for June".to_string()];
+        let mut afternoon = front_page();
+        afternoon.time_of_day = "afternoon".to_string();
+        afternoon.articles.truncate(1);
+        afternoon.articles[0].keyTakeAways = vec!["No deadline set".to_string(), "A draft is circulating".to_string()];
+        let labels = SourceLabels::default();
+
+        update_takeaways_digest(&sink, md_dir, &morning, "2025-05-06_morning.md", &labels, 1).await.unwrap();
+        update_takeaways_digest(&sink, md_dir, &afternoon, "2025-05-06_afternoon.md", &labels, 1).await.unwrap();
+        let digest = sink.text("/md/takeaways.md");
+        let id = morning.articles[0].anchor_id();
+        assert_eq!(
+            digest,
+            format!(
+                "# Key Takeaways\n\n## 2025-05-06\n\n### Politics & Governance\n\n\
+                 - Both sides returned ([→](./2025-05-06_morning.md#{id})) <small>`cnn`</small>\n\
+                 - No deadline set ([→](./2025-05-06_morning.md#{id})) <small>`cnn`</small>\n\
+                 - A draft is circulating ([→](./2025-05-06_afternoon.md#{id})) <small>`cnn`</small>\n\n\
+                 ### Science & Technology\n\n\
+                 - Launch is set for June ([→](./2025-05-06_morning.md#{})) <small>`npr`</small>\n",
+                morning.articles[1].anchor_id()
+            )
+        );
+
+        // Running an edition again changes nothing
+        update_takeaways_digest(&sink, md_dir, &afternoon, "2025-05-06_afternoon.md", &labels, 1).await.unwrap();
+        assert_eq!(sink.text("/md/takeaways.md"), digest);
+
+        // Listed in SUMMARY.md once, ahead of the dated entries
+        update_edition_indexes(&sink, md_dir, &morning, "2025-05-06_morning.md", &MarkdownOptions::default()).await.unwrap();
+        let summary = sink.text("/md/SUMMARY.md");
+        assert_eq!(summary.matches(TAKEAWAYS_ENTRY).count(), 1);
+        assert!(summary.contains("- [Key Takeaways](./takeaways.md)\n- [Daily News](./daily_news.md)\n    - [2025-05-06]"));
+    }
+
+    #[test]
+    fn test_takeaways_prune_dates_past_retention() {
+        let mut page = front_page();
+        page.articles[0].keyTakeAways = vec!["Day one".to_string()];
+        let labels = SourceLabels::default();
+        let mut digest: Option<String> = None;
+        for date in ["2025-05-04", "2025-05-05", "2025-05-06"] {
+            page.local_date = date.to_string();
+            let file = format!("{}_morning.md", date);
+            digest = Some(takeaways_content(digest.as_deref(), &page, &file, &labels, 1));
+        }
+        let digest = digest.unwrap();
+        let dates: Vec<&str> = digest.lines().filter_map(|l| l.strip_prefix("## ")).collect();
+        // Newest first; 2025-05-04 is more than a day old
+        assert_eq!(dates, vec!["2025-05-06", "2025-05-05"]);
+
+        // A longer retention keeps it, a zero retention keeps only the edition's date
+        page.local_date = "2025-05-04".to_string();
+        let kept = takeaways_content(None, &page, "2025-05-04_morning.md", &labels, 1);
+        page.local_date = "2025-05-06".to_string();
+        let kept = takeaways_content(Some(&kept), &page, "2025-05-06_morning.md", &labels, 2);
+        assert!(kept.contains("## 2025-05-04"));
+        let today = takeaways_content(Some(&digest), &page, "2025-05-06_morning.md", &labels, 0);
+        assert_eq!(today.lines().filter(|l| l.starts_with("## ")).count(), 1);
+    }
+}
@@ -0,0 +1,108 @@
+//! Summarized articles streamed to an NDJSON journal (`--journal`).
+//!
+//! The edition JSON is only written once every article is summarized. With
+//! `--journal`, each article is also appended to
+//! `{json_output_dir}/{date}/{time_of_day}.ndjson` as one line of JSON as
+//! soon as the model is done with it, so consumers can follow a long run and
+//! a crash keeps the articles done so far. Lines are in completion order and
+//! hold articles as summarized, before `--region` and the other edition
+//! filters, with `content` cut under the same policy as the edition (see
+//! [`ContentPolicy`]).
+//!
+//! The journal stays next to the edition once it is written; a rerun of the
+//! same edition starts it over. Like the partial Markdown, it is written
+//! directly rather than through the output sink, and best effort: failures
+//! are logged and never abort the run.
+
+use crate::models::{AwfulNewsArticle, FrontPage};
+use crate::outputs::json::{article_to_public_json, ContentPolicy};
+use std::io;
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, File};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+/// The NDJSON journal of an edition being processed.
+#[derive(Debug)]
+pub struct Journal {
+    path: PathBuf,
+    /// Appends from concurrent completions are serialized on this.
+    file: Mutex<File>,
+    content: ContentPolicy,
+}
+
+impl Journal {
+    /// Start the journal of `front_page` under `json_output_dir`, replacing
+    /// any left by an earlier run of the edition.
+    pub async fn create(json_output_dir: &str, front_page: &FrontPage, content: ContentPolicy) -> io::Result<Self> {
+        let dir = Path::new(json_output_dir).join(front_page.edition_date());
+        fs::create_dir_all(&dir).await?;
+        let path = dir.join(format!("{}.ndjson", front_page.time_of_day));
+        let file = File::create(&path).await?;
+        Ok(Journal {
+            path,
+            file: Mutex::new(file),
+            content,
+        })
+    }
+
+    /// Where the journal is written.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append `article` to the journal as one line.
+    pub async fn append(&self, article: &AwfulNewsArticle) {
+        let line = match article_to_public_json(article, self.content) {
+            Ok(json) => json + "\n",
+            Err(e) => {
+                warn!(title = %article.title, error = %e, "Failed serializing an article for the journal");
+                return;
+            }
+        };
+        let mut file = self.file.lock().await;
+        let written = async {
+            file.write_all(line.as_bytes()).await?;
+            file.flush().await
+        };
+        if let Err(e) = written.await {
+            warn!(path = %self.path.display(), title = %article.title, error = %e, "Failed appending to the journal");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[tokio::test]
+    async fn test_journal_holds_one_line_per_completed_article() {
+        let dir = tempfile::tempdir().unwrap();
+        let json_dir = dir.path().to_str().unwrap();
+        let page = test_support::front_page("morning", "08:00:00");
+        let talks = AwfulNewsArticle {
+            content: Some("word ".repeat(20).into()),
+            ..test_support::article("Talks resume", "Politics & Governance")
+        };
+        let probe = test_support::article("Probe launched", "Science & Technology");
+
+        let journal = Journal::create(json_dir, &page, ContentPolicy::Excerpt(10)).await.unwrap();
+        assert_eq!(journal.path(), dir.path().join("2025-05-06/morning.ndjson"));
+        journal.append(&probe).await;
+        journal.append(&talks).await;
+
+        // Readable line by line while the run goes on, in completion order
+        let ndjson = std::fs::read_to_string(journal.path()).unwrap();
+        let lines: Vec<AwfulNewsArticle> = ndjson.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        let titles: Vec<&str> = lines.iter().map(|a| a.title.as_str()).collect();
+        assert_eq!(titles, vec!["Probe launched", "Talks resume"]);
+        assert_eq!(lines[1].content.as_deref(), Some("word word…"));
+
+        // A rerun of the edition starts over
+        let journal = Journal::create(json_dir, &page, ContentPolicy::Full).await.unwrap();
+        journal.append(&talks).await;
+        assert_eq!(std::fs::read_to_string(journal.path()).unwrap().lines().count(), 1);
+    }
+}
@@ -1,7 +1,8 @@
 //! JSON output generation for the API.
 //!
 //! This module serializes processed articles to JSON format for consumption
-//! by external clients and APIs.
+//! by external clients and APIs. Files are stored through an
+//! [`OutputSink`](crate::outputs::sink::OutputSink).
 //!
 //! # Output Structure
 //!
@@ -14,68 +15,898 @@
 //!     └── evening.json
 //! ```
 //!
+//! With `--split-by-category`, each edition additionally gets a directory of
+//! per-category files so clients can load categories lazily:
+//! ```text
+//! json_output_dir/
+//! └── 2025-05-06/
+//!     ├── evening.json
+//!     └── evening/
+//!         ├── politics--governance.json
+//!         └── science--technology.json
+//! ```
+//!
+//! With `--json-max-bytes`, an edition whose JSON would exceed the limit is
+//! sharded into numbered parts, each a valid edition with a slice of the
+//! articles, and the edition file becomes an index listing them (see
+//! [`write_frontpage`]):
+//! ```text
+//! json_output_dir/
+//! └── 2025-05-06/
+//!     ├── evening.json         {"local_date": ..., "parts": ["evening.part1.json", ...]}
+//!     ├── evening.part1.json
+//!     └── evening.part2.json
+//! ```
+//! The index keeps the edition's `diff`, `top` and `editorial`. Readers of
+//! stored editions go through [`read_edition`], which puts sharded editions
+//! back together.
+//!
+//! With `--day-digest`, every edition file of the date is read back after
+//! the run and consolidated into `{date}/day.json` (a [`DayDigest`]); the
+//! per-edition files are kept.
+//!
+//! The week in review (the `weekly` subcommand, see [`crate::weekly`]) is written to
+//! `{week_end}/weekly.json`.
+//!
+//! Every scraping run also writes its article counts next to the edition as
+//! `{date}/{time_of_day}.run.json` (a [`RunSummary`]), which the statistics
+//! rollups (`stats`, see [`crate::stats`]) read for failure rates.
+//!
+//! With `--journal`, articles are also streamed to
+//! `{date}/{time_of_day}.ndjson` as they are summarized (see
+//! [`crate::outputs::journal`]).
+//!
+//! # Article Content
+//!
+//! The scraped text of each article (`content`) can be kept out of the
+//! published files, which carry summaries and metadata either way:
+//! `--omit-content` drops the field and `--content-max-chars N` cuts it to
+//! an excerpt (see [`ContentPolicy`]). The policy is applied when an edition
+//! or its category files are serialized, so the run itself (history hashes,
+//! raw dumps, the bus payload) still works from the full text. Editions
+//! written without content can't be re-summarized with `--reprocess`, and
+//! the edition diff can then only spot updates flagged `updateOf`.
+//!
+//! # Source Registry
+//!
+//! Every scraping run replaces `sources.json` at the top of the output
+//! directory with one entry per registered scraper (see
+//! [`SCRAPERS`](crate::scrapers::SCRAPERS)): its name, tag, outlet,
+//! homepage and index method, whether it ran this time, and how many
+//! articles it indexed and fetched. Scrapers that didn't run are listed
+//! with `"ran": false`. The file is written to a temporary name and renamed
+//! into place, so readers never see a partial file.
+//!
+//! # Mirrors
+//!
+//! Each `--json-mirror-dir` receives the files written under the primary
+//! directory at the same relative paths, hard-linked when the mirror is on
+//! the same filesystem and copied otherwise. Mirrors are checked for
+//! writability at startup and fail independently: a failing mirror is
+//! logged and reported as `output.json.mirror_failed`, but never fails the
+//! run or the other mirrors.
+//!
 //! # Evening Edge Case
 //!
-//! If an "evening" edition runs just after midnight (before the date changes),
-//! it uses yesterday's date to keep the edition logically grouped with the
-//! correct day's news.
+//! If an "evening" edition finishes just after midnight, it is filed under
+//! the previous day (see [`FrontPage::edition_date`]) to keep the edition
+//! grouped with the correct day's news, the same as the Markdown indexes.
 
-use crate::models::FrontPage;
-use chrono::{Duration, Local, NaiveTime};
+use crate::models::{AwfulNewsArticle, DayDigest, FrontPage, RunSummary, WeeklyEdition};
+use crate::ordering::{sort_articles, ArticleOrder};
+use crate::outputs::sink::OutputSink;
+use crate::scrapers::{ScraperInfo, SCRAPERS};
+use crate::utils::{all_edition_labels, ensure_writable_dir, slugify_title};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
+use std::path::Path;
 use tokio::fs;
-use tracing::{error, info, instrument};
+use tracing::{error, info, instrument, warn};
 
 /// Write a [`FrontPage`] to a JSON file with date-based directory structure.
 ///
-/// Creates the necessary directory structure and writes the serialized
-/// `FrontPage` as JSON. The file path is determined by the date and
-/// time-of-day from the `FrontPage` data.
+/// Writes the serialized `FrontPage` as JSON through `sink`. The file path
+/// is determined by the date and time-of-day from the `FrontPage` data.
 ///
 /// # Arguments
 ///
+/// * `sink` - Where the file is stored
 /// * `front_page` - The processed articles to serialize
 /// * `json_output_dir` - Base directory for JSON output
+/// * `content` - How much article text to include
+/// * `sort` - Order of the articles in the file, if not the edition's own
+/// * `max_bytes` - Size above which the edition is sharded; `None` for no limit
 ///
 /// # Returns
 ///
-/// `Ok(())` on success, or an error if directory creation or file writing fails.
+/// The paths of the written files, the edition file first, or an error if
+/// writing fails.
 ///
 /// # Output Path
 ///
-/// The file is written to: `{json_output_dir}/{date}/{time_of_day}.json`
+/// The file is written to: `{json_output_dir}/{edition_date}/{time_of_day}.json`.
+/// A sharded edition's parts go next to it as `{time_of_day}.part{N}.json`,
+/// and are written before the index so it never lists a missing part. Parts
+/// left over from an earlier, larger write of the edition are removed.
 #[instrument(level = "info", skip_all, fields(json_output_dir = %json_output_dir))]
 pub async fn write_frontpage(
+    sink: &impl OutputSink,
     front_page: &FrontPage,
     json_output_dir: &str,
-) -> Result<(), Box<dyn Error>> {
-    let json = serde_json::to_string(front_page)?;
+    content: ContentPolicy,
+    sort: Option<ArticleOrder>,
+    max_bytes: Option<usize>,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let json = to_public_json(&*sorted_for_json(front_page, sort), content)?;
 
-    let midnight = NaiveTime::from_hms_opt(23, 59, 59).unwrap();
-    let now = Local::now().time();
-    let yesterday = Local::now().date_naive() - Duration::days(1);
+    let edition_dir = format!("{}/{}", json_output_dir, front_page.edition_date());
+    let output_json_filename = format!("{}/{}.json", edition_dir, front_page.time_of_day);
+    let stale_parts = match sink.read_to_string(&output_json_filename).await {
+        Ok(Some(raw)) => part_names(&raw),
+        _ => vec![],
+    };
 
-    let full_json_dir = if front_page.time_of_day == "evening" && (now >= midnight) {
-        format!("{}/{}", json_output_dir, yesterday.to_string())
-    } else {
-        format!("{}/{}", json_output_dir, front_page.local_date)
+    let (json, parts) = match max_bytes {
+        Some(max_bytes) if json.len() > max_bytes => shard(&json, &front_page.time_of_day, max_bytes)?,
+        _ => (json, vec![]),
     };
+    let mut written = vec![output_json_filename.clone()];
+    for (name, part) in &parts {
+        let path = format!("{}/{}", edition_dir, name);
+        sink.write(&path, part.as_bytes()).await?;
+        written.push(path);
+    }
+    if !parts.is_empty() {
+        info!(path = %output_json_filename, parts = parts.len(), "Sharded JSON edition");
+    }
 
-    info!(%full_json_dir, "Ensuring JSON directory exists");
-    if let Err(e) = fs::create_dir_all(&full_json_dir).await {
-        error!(%full_json_dir, error = %e, "Failed to create JSON dir");
+    info!(path = %output_json_filename, "Writing JSON");
+    if let Err(e) = sink.write(&output_json_filename, json.as_bytes()).await {
+        error!(path = %output_json_filename, error = %e, "Failed to write JSON");
         return Err(e.into());
     }
+    info!(path = %output_json_filename, "Wrote JSON API file");
+
+    for name in stale_parts.iter().filter(|name| !parts.iter().any(|(part, _)| part == *name)) {
+        let path = format!("{}/{}", edition_dir, name);
+        if let Err(e) = sink.remove(&path).await {
+            warn!(%path, error = %e, "Failed to remove stale edition part");
+        }
+    }
+
+    Ok(written)
+}
+
+/// File name and contents of one part of a sharded edition.
+type Part = (String, String);
 
-    let output_json_filename = if front_page.time_of_day == "evening" && (now >= midnight) {
-        format!("{}/{}.json", full_json_dir, yesterday.to_string())
-    } else {
-        format!("{}/{}.json", full_json_dir, front_page.time_of_day)
+/// Split the serialized edition `json` into parts of at most `max_bytes`
+/// each, named after `time_of_day`.
+///
+/// Every part is the edition with a run of consecutive articles; an article
+/// too large for a part of its own still gets one. Returns the index that
+/// replaces the edition, and the name and contents of each part.
+fn shard(json: &str, time_of_day: &str, max_bytes: usize) -> Result<(String, Vec<Part>), Box<dyn Error>> {
+    let mut edition: serde_json::Map<String, Value> = serde_json::from_str(json)?;
+    let articles = match edition.remove("articles") {
+        Some(Value::Array(articles)) => articles,
+        _ => vec![],
     };
+    let mut header = serde_json::Map::new();
+    for key in ["local_date", "time_of_day", "local_time"] {
+        if let Some(value) = edition.get(key) {
+            header.insert(key.to_string(), value.clone());
+        }
+    }
+    // `"articles":[]` plus a comma before it
+    let empty_part = serde_json::to_string(&header)?.len() + r#","articles":[]"#.len();
 
-    info!(path = %output_json_filename, "Writing JSON");
-    fs::write(&output_json_filename, json).await?;
-    info!(path = %output_json_filename, "Wrote JSON API file");
+    let mut groups: Vec<Vec<Value>> = vec![];
+    let mut size = 0;
+    for article in articles {
+        let bytes = serde_json::to_string(&article)?.len();
+        match groups.last_mut() {
+            Some(group) if size + 1 + bytes <= max_bytes => {
+                group.push(article);
+                size += 1 + bytes;
+            }
+            _ => {
+                groups.push(vec![article]);
+                size = empty_part + bytes;
+            }
+        }
+    }
+
+    let mut parts = Vec::with_capacity(groups.len());
+    for (i, articles) in groups.into_iter().enumerate() {
+        let mut part = header.clone();
+        part.insert("articles".to_string(), Value::Array(articles));
+        parts.push((format!("{}.part{}.json", time_of_day, i + 1), serde_json::to_string(&part)?));
+    }
+    let names = parts.iter().map(|(name, _)| Value::String(name.clone())).collect();
+    edition.insert("parts".to_string(), Value::Array(names));
+    Ok((serde_json::to_string(&edition)?, parts))
+}
+
+/// File names of the parts listed by a sharded edition index; empty for a
+/// whole edition or unparseable JSON.
+fn part_names(raw: &str) -> Vec<String> {
+    serde_json::from_str::<Value>(raw)
+        .ok()
+        .and_then(|index| index.get("parts").and_then(Value::as_array).cloned())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|name| name.as_str().map(str::to_string))
+        .collect()
+}
+
+/// Read a stored edition file, putting a sharded edition back together.
+///
+/// `Ok(None)` if there is no file at `path`; an error if it, or one of its
+/// parts, can't be read or parsed.
+pub async fn read_edition<T: DeserializeOwned>(sink: &impl OutputSink, path: &str) -> Result<Option<T>, Box<dyn Error>> {
+    let Some(raw) = sink.read_to_string(path).await? else {
+        return Ok(None);
+    };
+    let mut edition: Value = serde_json::from_str(&raw)?;
+    if let Some(Value::Array(names)) = edition.as_object_mut().and_then(|e| e.remove("parts")) {
+        let dir = Path::new(path).parent().map(|p| p.to_string_lossy().into_owned()).unwrap_or_default();
+        let mut articles = Vec::new();
+        for name in names.iter().filter_map(Value::as_str) {
+            let part_path = format!("{}/{}", dir, name);
+            let raw = sink
+                .read_to_string(&part_path)
+                .await?
+                .ok_or_else(|| format!("edition part {} is missing", part_path))?;
+            if let Value::Object(mut part) = serde_json::from_str(&raw)?
+                && let Some(Value::Array(part_articles)) = part.remove("articles")
+            {
+                articles.extend(part_articles);
+            }
+        }
+        edition["articles"] = Value::Array(articles);
+    }
+    Ok(Some(serde_json::from_value(edition)?))
+}
+
+/// Read back every edition of `local_date` written under `json_output_dir`.
+///
+/// Missing editions are skipped; unreadable or corrupt edition files are
+/// skipped with a warning.
+pub async fn build_day_digest(sink: &impl OutputSink, json_output_dir: &str, local_date: &str) -> DayDigest {
+    let mut editions = Vec::new();
+    for edition in all_edition_labels() {
+        let path = format!("{}/{}/{}.json", json_output_dir, local_date, edition);
+        match read_edition(sink, &path).await {
+            Ok(Some(front_page)) => editions.push(front_page),
+            Ok(None) => {}
+            Err(e) => warn!(%path, error = %e, "Unreadable or corrupt edition; leaving it out of the day digest"),
+        }
+    }
+    DayDigest {
+        local_date: local_date.to_string(),
+        editions,
+    }
+}
+
+/// (Re)build `{json_output_dir}/{date}/day.json` from the date's edition files.
+///
+/// # Returns
+///
+/// The path of the written digest.
+#[instrument(level = "info", skip_all, fields(json_output_dir = %json_output_dir, %local_date))]
+pub async fn write_day_digest(
+    sink: &impl OutputSink,
+    json_output_dir: &str,
+    local_date: &str,
+) -> Result<String, Box<dyn Error>> {
+    let digest = build_day_digest(sink, json_output_dir, local_date).await;
+    let path = format!("{}/{}/day.json", json_output_dir, local_date);
+    sink.write(&path, serde_json::to_string(&digest)?.as_bytes()).await?;
+    info!(%path, editions = digest.editions.len(), "Wrote day digest");
+    Ok(path)
+}
+
+/// Write a [`WeeklyEdition`] to `{json_output_dir}/{week_end}/weekly.json`.
+///
+/// # Returns
+///
+/// The path of the written file.
+#[instrument(level = "info", skip_all, fields(json_output_dir = %json_output_dir, week_end = %weekly.week_end))]
+pub async fn write_weekly(
+    sink: &impl OutputSink,
+    weekly: &WeeklyEdition,
+    json_output_dir: &str,
+) -> Result<String, Box<dyn Error>> {
+    let path = format!("{}/{}/weekly.json", json_output_dir, weekly.week_end);
+    sink.write(&path, serde_json::to_string(weekly)?.as_bytes()).await?;
+    info!(%path, stories = weekly.stories.len(), "Wrote weekly JSON");
+    Ok(path)
+}
+
+/// Write the [`RunSummary`] of the run behind `front_page` to
+/// `{json_output_dir}/{edition_date}/{time_of_day}.run.json`.
+///
+/// # Returns
+///
+/// The path of the written file.
+pub async fn write_run_summary(
+    sink: &impl OutputSink,
+    front_page: &FrontPage,
+    summary: &RunSummary,
+    json_output_dir: &str,
+) -> Result<String, Box<dyn Error>> {
+    let path = format!(
+        "{}/{}/{}.run.json",
+        json_output_dir,
+        front_page.edition_date(),
+        front_page.time_of_day
+    );
+    sink.write(&path, serde_json::to_string(summary)?.as_bytes()).await?;
+    info!(%path, failed = summary.failed, "Wrote run summary");
+    Ok(path)
+}
+
+/// How much of each article's scraped text goes into the JSON output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ContentPolicy {
+    /// The full text.
+    #[default]
+    Full,
+    /// At most this many characters, cut at a word boundary.
+    Excerpt(usize),
+    /// No `content` field at all.
+    Omit,
+}
+
+impl ContentPolicy {
+    /// Apply the policy to every article of a serialized edition or
+    /// category page.
+    fn apply(self, page: &mut Value) {
+        if self == ContentPolicy::Full {
+            return;
+        }
+        let Some(Value::Array(articles)) = page.get_mut("articles") else {
+            return;
+        };
+        for article in articles.iter_mut() {
+            self.apply_to_article(article);
+        }
+    }
+
+    /// Apply the policy to one serialized article.
+    fn apply_to_article(self, article: &mut Value) {
+        let Some(article) = article.as_object_mut() else {
+            return;
+        };
+        match self {
+            ContentPolicy::Full => {}
+            ContentPolicy::Omit => {
+                article.remove("content");
+            }
+            ContentPolicy::Excerpt(max_chars) => {
+                if let Some(Value::String(content)) = article.get_mut("content")
+                    && let Some(cut) = excerpt(content, max_chars)
+                {
+                    *content = cut;
+                }
+            }
+        }
+    }
+}
+
+/// The first `max_chars` characters of `text`, ending on a whole word, or
+/// `None` if the text is short enough already.
+pub fn excerpt(text: &str, max_chars: usize) -> Option<String> {
+    let (cut, _) = text.char_indices().nth(max_chars)?;
+    let head = &text[..cut];
+    let head = head.rfind(char::is_whitespace).map_or(head, |i| &head[..i]);
+    Some(format!("{}…", head.trim_end()))
+}
+
+/// `front_page` with its articles in `sort` order for the JSON output.
+///
+/// The Markdown keeps the edition's own order, so the articles are sorted
+/// on a copy; without `sort` the edition is used as is.
+pub fn sorted_for_json(front_page: &FrontPage, sort: Option<ArticleOrder>) -> Cow<'_, FrontPage> {
+    match sort {
+        Some(order) => {
+            let mut sorted = front_page.clone();
+            sort_articles(&mut sorted.articles, order);
+            Cow::Owned(sorted)
+        }
+        None => Cow::Borrowed(front_page),
+    }
+}
+
+/// Serialize a page with `articles` for publishing, under `content`.
+pub fn to_public_json<T: Serialize>(page: &T, content: ContentPolicy) -> serde_json::Result<String> {
+    let mut value = serde_json::to_value(page)?;
+    content.apply(&mut value);
+    serde_json::to_string(&value)
+}
+
+/// Serialize a single article for publishing, under `content`.
+pub fn article_to_public_json(article: &AwfulNewsArticle, content: ContentPolicy) -> serde_json::Result<String> {
+    let mut value = serde_json::to_value(article)?;
+    content.apply_to_article(&mut value);
+    serde_json::to_string(&value)
+}
+
+/// What a scraper did in this run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SourceRun {
+    /// Whether the scraper ran (NYT doesn't without an API key).
+    pub ran: bool,
+    /// Article URLs found while indexing.
+    pub indexed: usize,
+    /// Articles fetched with usable content.
+    pub fetched: usize,
+}
+
+/// One entry of `sources.json`.
+#[derive(Debug, Serialize)]
+pub struct SourceStatus {
+    #[serde(flatten)]
+    pub scraper: ScraperInfo,
+    #[serde(flatten)]
+    pub run: SourceRun,
+}
+
+/// Status of every registered scraper; those missing from `runs` (keyed by
+/// scraper name) didn't run.
+pub fn source_registry(runs: &HashMap<&str, SourceRun>) -> Vec<SourceStatus> {
+    SCRAPERS
+        .iter()
+        .map(|scraper| SourceStatus {
+            scraper: *scraper,
+            run: runs.get(scraper.name).copied().unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// Replace `{json_output_dir}/sources.json` with the status of every
+/// registered scraper.
+///
+/// # Returns
+///
+/// The path of the written file.
+pub async fn write_sources(
+    sink: &impl OutputSink,
+    json_output_dir: &str,
+    runs: &HashMap<&str, SourceRun>,
+) -> Result<String, Box<dyn Error>> {
+    let sources = source_registry(runs);
+    let path = format!("{}/sources.json", json_output_dir);
+    sink.write(&path, serde_json::to_string_pretty(&sources)?.as_bytes()).await?;
+    info!(%path, sources = sources.len(), "Wrote source registry");
+    Ok(path)
+}
+
+/// The subset of an edition belonging to a single category.
+///
+/// Serialized into `{date}/{edition}/{category_slug}.json` when the edition
+/// is split by category.
+#[derive(Debug, Serialize)]
+pub struct CategoryPage<'a> {
+    /// The date of publication in `YYYY-MM-DD` format.
+    pub local_date: &'a str,
+    /// The edition name (time of day).
+    pub time_of_day: &'a str,
+    /// The exact local time of publication.
+    pub local_time: &'a str,
+    /// The category these articles belong to.
+    pub category: &'a str,
+    /// The articles in this category, in edition order.
+    pub articles: Vec<&'a AwfulNewsArticle>,
+}
+
+/// Group an edition's articles into per-category pages keyed by category slug.
+///
+/// Categories whose names slugify identically share a page; the first
+/// category name seen is used as the page's `category`.
+pub fn split_by_category(front_page: &FrontPage) -> BTreeMap<String, CategoryPage<'_>> {
+    let mut pages: BTreeMap<String, CategoryPage<'_>> = BTreeMap::new();
+    for article in &front_page.articles {
+        pages
+            .entry(slugify_title(&article.category))
+            .or_insert_with(|| CategoryPage {
+                local_date: &front_page.local_date,
+                time_of_day: &front_page.time_of_day,
+                local_time: &front_page.local_time,
+                category: &article.category,
+                articles: Vec::new(),
+            })
+            .articles
+            .push(article);
+    }
+    pages
+}
 
-    Ok(())
+/// Write one JSON file per category for a [`FrontPage`].
+///
+/// # Arguments
+///
+/// * `sink` - Where the files are stored
+/// * `front_page` - The processed articles to split
+/// * `json_output_dir` - Base directory for JSON output
+/// * `content` - How much article text to include
+///
+/// # Output Path
+///
+/// Files are written to: `{json_output_dir}/{date}/{time_of_day}/{category_slug}.json`
+///
+/// # Returns
+///
+/// The paths of the written files.
+#[instrument(level = "info", skip_all, fields(json_output_dir = %json_output_dir))]
+pub async fn write_category_files(
+    sink: &impl OutputSink,
+    front_page: &FrontPage,
+    json_output_dir: &str,
+    content: ContentPolicy,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let edition_dir = format!(
+        "{}/{}/{}",
+        json_output_dir,
+        front_page.edition_date(),
+        front_page.time_of_day
+    );
+    let pages = split_by_category(front_page);
+    let mut written = Vec::with_capacity(pages.len());
+    for (slug, page) in &pages {
+        let path = format!("{}/{}.json", edition_dir, slug);
+        sink.write(&path, to_public_json(page, content)?.as_bytes()).await?;
+        info!(%path, articles = page.articles.len(), "Wrote category JSON");
+        written.push(path);
+    }
+
+    info!(%edition_dir, categories = pages.len(), "Wrote per-category JSON files");
+    Ok(written)
+}
+
+/// Copy `files`, written under `json_output_dir`, to the same relative
+/// paths under `mirror_dir`, hard-linking where the filesystem allows.
+///
+/// # Returns
+///
+/// The paths written in the mirror.
+pub async fn mirror_files(
+    files: &[String],
+    json_output_dir: &str,
+    mirror_dir: &str,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut mirrored = Vec::with_capacity(files.len());
+    for file in files {
+        let relative = Path::new(file)
+            .strip_prefix(json_output_dir)
+            .map_err(|_| format!("{} is not under {}", file, json_output_dir))?;
+        let target = Path::new(mirror_dir).join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        // An earlier run's file would make the hard link fail
+        match fs::remove_file(&target).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        if fs::hard_link(file, &target).await.is_err() {
+            fs::copy(file, &target).await?;
+        }
+        mirrored.push(target.to_string_lossy().into_owned());
+    }
+    Ok(mirrored)
+}
+
+fn mirror_failed(mirror_dir: &str, error: &dyn Error) {
+    warn!(%mirror_dir, %error, "JSON mirror failed; continuing without it");
+    crate::publish_error!(
+        "awful_text_news",
+        event_kind = "output.json.mirror_failed",
+        path = mirror_dir.to_string(),
+        error = error.to_string(),
+        "Failed to write JSON mirror"
+    );
+}
+
+/// The mirror directories that are writable; the others are reported and
+/// left out of the run.
+pub async fn writable_mirrors(mirror_dirs: &[String]) -> Vec<String> {
+    let mut usable = Vec::with_capacity(mirror_dirs.len());
+    for mirror_dir in mirror_dirs {
+        match ensure_writable_dir(mirror_dir).await {
+            Ok(()) => usable.push(mirror_dir.clone()),
+            Err(e) => mirror_failed(mirror_dir, e.as_ref()),
+        }
+    }
+    usable
+}
+
+/// Mirror `files` into every directory of `mirror_dirs`.
+///
+/// # Returns
+///
+/// The number of mirrors that received every file.
+#[instrument(level = "info", skip_all, fields(json_output_dir = %json_output_dir))]
+pub async fn write_mirrors(files: &[String], json_output_dir: &str, mirror_dirs: &[String]) -> usize {
+    let mut complete = 0;
+    for mirror_dir in mirror_dirs {
+        match mirror_files(files, json_output_dir, mirror_dir).await {
+            Ok(mirrored) => {
+                info!(%mirror_dir, files = mirrored.len(), "Mirrored JSON output");
+                complete += 1;
+            }
+            Err(e) => mirror_failed(mirror_dir, e.as_ref()),
+        }
+    }
+    complete
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use crate::outputs::sink::FileSystem;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn article(title: &str, category: &str) -> AwfulNewsArticle {
+        AwfulNewsArticle {
+            source: Some(format!("https://example.com/{}", slugify_title(title))),
+            timeOfPublication: "14:30:00".to_string(),
+            summaryOfNewsArticle: "Summary".to_string(),
+            ..test_support::article(title, category)
+        }
+    }
+
+    fn edition(time_of_day: &str, titles: &[&str]) -> FrontPage {
+        FrontPage {
+            articles: titles.iter().map(|t| article(t, "Politics & Governance")).collect(),
+            ..test_support::front_page(time_of_day, "08:00:00")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_day_digest_from_morning_and_afternoon() {
+        let dir = tempfile::tempdir().unwrap();
+        let json_output_dir = dir.path().to_str().unwrap();
+        // Written out of order; the digest follows publication order
+        write_frontpage(&FileSystem, &edition("afternoon", &["Three"]), json_output_dir, ContentPolicy::Full, None, None).await.unwrap();
+        write_frontpage(&FileSystem, &edition("morning", &["One", "Two"]), json_output_dir, ContentPolicy::Full, None, None).await.unwrap();
+
+        let path = write_day_digest(&FileSystem, json_output_dir, "2025-05-06").await.unwrap();
+        assert!(path.ends_with("2025-05-06/day.json"));
+        assert!(dir.path().join("2025-05-06/morning.json").is_file());
+
+        let digest: DayDigest = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(digest.local_date, "2025-05-06");
+        let editions: Vec<&str> = digest.editions.iter().map(|e| e.time_of_day.as_str()).collect();
+        assert_eq!(editions, vec!["morning", "afternoon"]);
+        assert_eq!(digest.editions[0].articles.len(), 2);
+        assert_eq!(digest.editions[1].articles[0].title, "Three");
+    }
+
+    #[tokio::test]
+    async fn test_day_digest_skips_corrupt_edition() {
+        let dir = tempfile::tempdir().unwrap();
+        let json_output_dir = dir.path().to_str().unwrap();
+        write_frontpage(&FileSystem, &edition("morning", &["One"]), json_output_dir, ContentPolicy::Full, None, None).await.unwrap();
+        std::fs::write(dir.path().join("2025-05-06/evening.json"), "{ truncated").unwrap();
+
+        let digest = build_day_digest(&FileSystem, json_output_dir, "2025-05-06").await;
+        assert_eq!(digest.editions.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_category_files_contain_every_article_once() {
+        let front_page = FrontPage {
+            articles: vec![
+                article("One", "Politics & Governance"),
+                article("Two", "Science & Technology"),
+                article("Three", "Politics & Governance"),
+                article("Four", "Conflict & War"),
+            ],
+            ..test_support::front_page("morning", "08:00:00")
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let json_output_dir = dir.path().to_str().unwrap();
+        write_category_files(&FileSystem, &front_page, json_output_dir, ContentPolicy::Full).await.unwrap();
+
+        let edition_dir = dir.path().join("2025-05-06").join("morning");
+        let mut titles = Vec::new();
+        for entry in std::fs::read_dir(&edition_dir).unwrap() {
+            let raw = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            let page: serde_json::Value = serde_json::from_str(&raw).unwrap();
+            for a in page["articles"].as_array().unwrap() {
+                assert_eq!(a["category"], page["category"]);
+                titles.push(a["title"].as_str().unwrap().to_string());
+            }
+        }
+        titles.sort();
+
+        assert_eq!(titles, vec!["Four", "One", "Three", "Two"]);
+        assert!(edition_dir.join("politics--governance.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_content_policy_applies_to_published_json_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let json_output_dir = dir.path().to_str().unwrap();
+        let mut front_page = edition("morning", &["One"]);
+        front_page.articles[0].content = Some("The council met on Tuesday to vote.".into());
+        let read = |path: String| -> Value { serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap() };
+
+        let public = read(write_frontpage(&FileSystem, &front_page, json_output_dir, ContentPolicy::Omit, None, None).await.unwrap().remove(0));
+        let article = public["articles"][0].as_object().unwrap();
+        assert!(!article.contains_key("content"));
+        assert_eq!(article["summaryOfNewsArticle"], "Summary");
+        let categories = write_category_files(&FileSystem, &front_page, json_output_dir, ContentPolicy::Omit).await.unwrap();
+        assert!(read(categories[0].clone())["articles"][0].get("content").is_none());
+        // The run keeps the full text for everything else
+        assert_eq!(front_page.articles[0].content.as_deref(), Some("The council met on Tuesday to vote."));
+
+        let excerpted = read(write_frontpage(&FileSystem, &front_page, json_output_dir, ContentPolicy::Excerpt(20), None, None).await.unwrap().remove(0));
+        assert_eq!(excerpted["articles"][0]["content"], "The council met on…");
+        let full = read(write_frontpage(&FileSystem, &front_page, json_output_dir, ContentPolicy::Full, None, None).await.unwrap().remove(0));
+        assert_eq!(full["articles"][0]["content"], "The council met on Tuesday to vote.");
+    }
+
+    #[tokio::test]
+    async fn test_json_sort_leaves_the_edition_order_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let json_output_dir = dir.path().to_str().unwrap();
+        let mut front_page = edition("morning", &["Old", "New", "Middle"]);
+        front_page.articles[1].category = "Science & Technology".to_string();
+        for (article, date) in front_page.articles.iter_mut().zip(["2025-05-04", "2025-05-06", "2025-05-05"]) {
+            article.dateOfPublication = date.to_string();
+        }
+
+        let paths = write_frontpage(&FileSystem, &front_page, json_output_dir, ContentPolicy::Full, Some(ArticleOrder::Time), None)
+            .await
+            .unwrap();
+        let written: FrontPage = serde_json::from_str(&std::fs::read_to_string(&paths[0]).unwrap()).unwrap();
+        let titles = |page: &FrontPage| page.articles.iter().map(|a| a.title.clone()).collect::<Vec<_>>();
+        assert_eq!(titles(&written), vec!["New", "Middle", "Old"]);
+        assert_eq!(titles(&front_page), vec!["Old", "New", "Middle"]);
+    }
+
+    #[tokio::test]
+    async fn test_large_edition_shards_into_parts() {
+        let dir = tempfile::tempdir().unwrap();
+        let json_output_dir = dir.path().to_str().unwrap();
+        let titles: Vec<String> = (1..=10).map(|i| format!("Story {}", i)).collect();
+        let mut front_page = edition("evening", &titles.iter().map(String::as_str).collect::<Vec<_>>());
+        front_page.local_time = "18:00:00".to_string();
+        for article in &mut front_page.articles {
+            article.content = Some("word ".repeat(100).into());
+        }
+        let article_bytes = serde_json::to_string(&front_page.articles[0]).unwrap().len();
+
+        // Room for three articles per part
+        let max_bytes = 3 * article_bytes + 200;
+        let paths = write_frontpage(&FileSystem, &front_page, json_output_dir, ContentPolicy::Full, None, Some(max_bytes))
+            .await
+            .unwrap();
+        assert_eq!(paths.len(), 5);
+        assert!(paths[0].ends_with("2025-05-06/evening.json"));
+        assert!(paths[4].ends_with("2025-05-06/evening.part4.json"));
+
+        let index: Value = serde_json::from_str(&std::fs::read_to_string(&paths[0]).unwrap()).unwrap();
+        assert_eq!(index["parts"], serde_json::json!(["evening.part1.json", "evening.part2.json", "evening.part3.json", "evening.part4.json"]));
+        assert!(index.get("articles").is_none());
+        let mut union = Vec::new();
+        for path in &paths[1..] {
+            let raw = std::fs::read_to_string(path).unwrap();
+            assert!(raw.len() <= max_bytes, "{} is {} bytes", path, raw.len());
+            let part: FrontPage = serde_json::from_str(&raw).unwrap();
+            assert_eq!(part.time_of_day, "evening");
+            union.extend(part.articles);
+        }
+        assert_eq!(union.iter().map(|a| a.title.clone()).collect::<Vec<_>>(), titles);
+
+        let whole: FrontPage = read_edition(&FileSystem, &paths[0]).await.unwrap().unwrap();
+        assert_eq!(serde_json::to_value(&whole).unwrap(), serde_json::to_value(&front_page).unwrap());
+
+        // A smaller edition goes back to a single file and drops the old parts
+        front_page.articles.truncate(2);
+        let paths = write_frontpage(&FileSystem, &front_page, json_output_dir, ContentPolicy::Full, None, Some(max_bytes))
+            .await
+            .unwrap();
+        assert_eq!(paths.len(), 1);
+        assert!(!dir.path().join("2025-05-06/evening.part1.json").exists());
+        let whole: FrontPage = read_edition(&FileSystem, &paths[0]).await.unwrap().unwrap();
+        assert_eq!(whole.articles.len(), 2);
+    }
+
+    #[test]
+    fn test_excerpt_cuts_at_a_word_boundary() {
+        assert_eq!(excerpt("Short text", 20), None);
+        assert_eq!(excerpt("Short text", 10), None);
+        assert_eq!(excerpt("Short text", 8).as_deref(), Some("Short…"));
+        assert_eq!(excerpt("Unbroken", 4).as_deref(), Some("Unbr…"));
+        assert_eq!(excerpt("Übergröße ist", 11).as_deref(), Some("Übergröße…"));
+    }
+
+    #[tokio::test]
+    async fn test_sources_lists_every_registered_scraper() {
+        let dir = tempfile::tempdir().unwrap();
+        let json_dir = dir.path().to_str().unwrap();
+        let runs: HashMap<&str, SourceRun> = SCRAPERS
+            .iter()
+            .filter(|s| s.name != "nyt")
+            .map(|s| (s.name, SourceRun { ran: true, indexed: 10, fetched: 8 }))
+            .collect();
+        // A previous run's file is replaced
+        std::fs::write(dir.path().join("sources.json"), "[]").unwrap();
+
+        let path = write_sources(&FileSystem, json_dir, &runs).await.unwrap();
+        let sources: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let names: Vec<&str> = sources.as_array().unwrap().iter().map(|s| s["name"].as_str().unwrap()).collect();
+        assert_eq!(names, SCRAPERS.iter().map(|s| s.name).collect::<Vec<_>>());
+
+        assert_eq!(sources[0]["tag"], "cnn");
+        assert_eq!(sources[0]["homepage"], "https://lite.cnn.com");
+        assert_eq!(sources[0]["method"], "html");
+        assert_eq!(sources[0]["ran"], true);
+        assert_eq!(sources[0]["fetched"], 8);
+        // Didn't run this time, still listed
+        let nyt = &sources[5];
+        assert_eq!(nyt["name"], "nyt");
+        assert_eq!(nyt["method"], "api");
+        assert_eq!(nyt["ran"], false);
+        assert_eq!(nyt["indexed"], 0);
+        assert!(!dir.path().join("sources.json.tmp").exists());
+    }
+
+    #[tokio::test]
+    async fn test_mirrors_fail_independently() {
+        let primary = tempfile::tempdir().unwrap();
+        let json_output_dir = primary.path().to_str().unwrap();
+        let front_page = edition("morning", &["One", "Two"]);
+        let mut files = write_frontpage(&FileSystem, &front_page, json_output_dir, ContentPolicy::Full, None, None).await.unwrap();
+        files.extend(write_category_files(&FileSystem, &front_page, json_output_dir, ContentPolicy::Full).await.unwrap());
+
+        let good = tempfile::tempdir().unwrap();
+        let read_only = tempfile::tempdir().unwrap();
+        // A file where the date directory goes keeps the mirror unwritable
+        // even for root, which ignores the read-only permission
+        std::fs::write(read_only.path().join("2025-05-06"), "").unwrap();
+        std::fs::set_permissions(read_only.path(), std::fs::Permissions::from_mode(0o555)).unwrap();
+
+        let mirrors = vec![
+            read_only.path().to_str().unwrap().to_string(),
+            good.path().to_str().unwrap().to_string(),
+        ];
+        assert_eq!(write_mirrors(&files, json_output_dir, &mirrors).await, 1);
+
+        let mirrored = std::fs::read_to_string(good.path().join("2025-05-06/morning.json")).unwrap();
+        assert_eq!(mirrored, std::fs::read_to_string(&files[0]).unwrap());
+        assert!(good.path().join("2025-05-06/morning/politics--governance.json").is_file());
+        assert!(std::fs::metadata(read_only.path()).unwrap().is_dir());
+        assert!(!read_only.path().join("2025-05-06").is_dir());
+
+        // Rewriting the edition replaces the mirrored file
+        let front_page = edition("morning", &["Three"]);
+        let files = write_frontpage(&FileSystem, &front_page, json_output_dir, ContentPolicy::Full, None, None).await.unwrap();
+        assert_eq!(write_mirrors(&files, json_output_dir, &mirrors[1..]).await, 1);
+        let mirrored = std::fs::read_to_string(good.path().join("2025-05-06/morning.json")).unwrap();
+        assert!(mirrored.contains("Three"));
+
+        std::fs::set_permissions(read_only.path(), std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_writable_mirrors_drops_unwritable_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let blocker = dir.path().join("blocker");
+        std::fs::write(&blocker, "").unwrap();
+        let good = dir.path().join("backup").to_str().unwrap().to_string();
+        let bad = blocker.join("backup").to_str().unwrap().to_string();
+
+        assert_eq!(writable_mirrors(&[bad, good.clone()]).await, vec![good]);
+    }
 }
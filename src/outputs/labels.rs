@@ -0,0 +1,85 @@
+//! Display labels for source tags.
+//!
+//! Editions show each article's outlet as its [`source_tag`] (`cnn`,
+//! `bbc`, ...). With `--source-labels`, the Markdown edition and the date
+//! TOC show a short label with an emoji instead, e.g. `📻 NPR`. The built-in
+//! sources have defaults ([`DEFAULT_SOURCE_LABELS`]); `--source-label
+//! TAG=LABEL` adds or overrides a label. Sources without a label keep their
+//! raw tag.
+//!
+//! [`source_tag`]: crate::models::AwfulNewsArticle::source_tag
+
+use crate::models::AwfulNewsArticle;
+use std::collections::HashMap;
+
+/// Labels of the built-in sources, keyed by source tag.
+pub const DEFAULT_SOURCE_LABELS: &[(&str, &str)] = &[
+    ("cnn", "🔴 CNN"),
+    ("npr", "📻 NPR"),
+    ("apnews", "📰 AP"),
+    ("aljazeera", "🌍 Al Jazeera"),
+    ("bbc", "🇬🇧 BBC"),
+    ("nytimes", "🗽 NYT"),
+    ("washingtonpost", "🏛️ WaPo"),
+];
+
+/// Parse a `TAG=LABEL` pair from the command line.
+pub fn parse_source_label(raw: &str) -> Result<(String, String), String> {
+    let (tag, label) = raw
+        .split_once('=')
+        .ok_or_else(|| format!("expected TAG=LABEL, got `{}`", raw))?;
+    let (tag, label) = (tag.trim(), label.trim());
+    if tag.is_empty() || label.is_empty() {
+        return Err(format!("empty tag or label in `{}`", raw));
+    }
+    Ok((tag.to_lowercase(), label.to_string()))
+}
+
+/// Mapping from source tag to display label.
+///
+/// The default mapping is empty, so every source shows its raw tag.
+#[derive(Debug, Clone, Default)]
+pub struct SourceLabels {
+    labels: HashMap<String, String>,
+}
+
+impl SourceLabels {
+    /// The built-in labels with `overrides` applied on top.
+    pub fn new(overrides: &[(String, String)]) -> Self {
+        let mut labels: HashMap<String, String> = DEFAULT_SOURCE_LABELS
+            .iter()
+            .map(|(tag, label)| (tag.to_string(), label.to_string()))
+            .collect();
+        labels.extend(overrides.iter().cloned());
+        SourceLabels { labels }
+    }
+
+    /// Label for `tag`, or the tag itself.
+    pub fn label(&self, tag: &str) -> String {
+        self.labels.get(tag).cloned().unwrap_or_else(|| tag.to_string())
+    }
+
+    /// Label for the outlet of `article`, if its URL has a source tag.
+    pub fn of(&self, article: &AwfulNewsArticle) -> Option<String> {
+        article.source_tag().map(|tag| self.label(&tag))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_labels_apply_overrides_and_fall_back_to_tag() {
+        let labels = SourceLabels::new(&[parse_source_label("bbc=BBC World").unwrap()]);
+        assert_eq!(labels.label("npr"), "📻 NPR");
+        assert_eq!(labels.label("bbc"), "BBC World");
+        assert_eq!(labels.label("reuters"), "reuters");
+        // Disabled labels keep every raw tag
+        assert_eq!(SourceLabels::default().label("npr"), "npr");
+
+        assert!(parse_source_label("bbc").is_err());
+        assert!(parse_source_label("=BBC").is_err());
+        assert_eq!(parse_source_label("Reuters = 🟠 Reuters"), Ok(("reuters".to_string(), "🟠 Reuters".to_string())));
+    }
+}
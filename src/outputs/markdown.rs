@@ -5,14 +5,19 @@
 //!
 //! # Output Format
 //!
-//! Articles are grouped by category (alphabetically) and include:
+//! The edition opens with the editor's headline of the day (see
+//! [`crate::editorial`]), the top stories (see [`crate::top_stories`]),
+//! what changed since the last edition and the lede, the most salient
+//! article rendered in full and expanded (see [`crate::salience`]). The
+//! other articles are then grouped by category (in edition order) and
+//! include:
 //! - Title with source tag
-//! - Publication date/time
+//! - Publication date/time and byline
 //! - Summary
 //! - Key takeaways
 //! - Named entities with descriptions
 //! - Important dates and timeframes
-//! - Topic tags
+//! - Locations and topic tags
 //!
 //! # Example Output
 //!
@@ -27,189 +32,1392 @@
 //! - _Published: 2025-05-06 12:00:00_
 //! ...
 //! ```
+//!
+//! # Anchors
+//!
+//! Each article heading carries a stable id derived from its source URL
+//! (see [`AwfulNewsArticle::anchor_id`]), written as an mdBook heading
+//! attribute: `## Title - <small>`cnn`</small> {#a-3f9c2d}`. The date TOC
+//! links to these ids, so inbound links survive regenerating an edition
+//! even if the LLM rewords a title. Ids repeated within an edition get a
+//! `-2`, `-3`, ... suffix (see [`EditionAnchors`]). With [`AnchorMode::Both`] the heading
+//! keeps its title-derived slug and the id is emitted as an
+//! `<a id="..."></a>` tag instead, so old slug links keep working during
+//! the transition. `--anchor-mode` defaults to `both` for this release.
+//!
+//! # Entity Footnotes
+//!
+//! By default named entities are listed under "Named Entities" after the
+//! summary. With [`EntityStyle::Footnotes`], the first mention of each entity
+//! in the summary (matched case-insensitively, on word boundaries) gets a
+//! footnote reference instead, and the entity's descriptions become the
+//! footnote body at the end of the article. Longer names are linked first and
+//! a mention is never linked twice, so "New York Times" wins over "New
+//! York". Entities the summary doesn't mention stay in the list.
+//!
+//! When the model typed the entities (`entityType`), the list is grouped
+//! under _People_, _Organizations_, _Places_ and _Events_, then any other
+//! types, then _Other_; an untyped list (older editions) stays flat.
+//!
+//! # Themes
+//!
+//! The data walk over an edition (categories, article fields, the trailing
+//! sections) is shared; a [`MarkdownTheme`] only decides how an article is
+//! opened and closed and how its sections are headed:
+//!
+//! | Theme | Article | Sections |
+//! |-------|---------|----------|
+//! | [`MarkdownTheme::Headings`] (default) | `##` heading, closed by `---` | `###` headings |
+//! | [`MarkdownTheme::Details`] | collapsible `<details>` with the title as `<summary>` | bold labels |
+//!
+//! With `details` the article id is always emitted as an `<a id>` tag before
+//! the block, since there is no heading to carry it.
+//!
+//! An edition with no articles renders a short placeholder paragraph after
+//! the header instead of an empty document. Articles flagged as overflow by
+//! `--max-per-category` are listed compactly under "Also today" at the end.
+//! Articles carried over from the previous run's failures
+//! (`--carry-over-failures`) follow the categories in full under
+//! "In case you missed it".
+//!
+//! The header time is `FrontPage.local_time` as recorded, down to the
+//! microsecond, so two runs with the same articles still produce different
+//! Markdown. With `--stable-timestamps` it is cut to whole seconds (see
+//! [`edition_time`]); the JSON output keeps full precision either way.
+//!
+//! An edition with a diff against the day's previous edition (see
+//! [`crate::diff`]) opens with "Since the last edition": new and updated
+//! stories link to their article below, stories no longer covered link to
+//! their source. Articles the edition corrected get a "Corrected" note
+//! naming the earlier edition.
+//!
+//! # Week in Review
+//!
+//! [`weekly_to_markdown`] renders a [`WeeklyEdition`] (see
+//! [`crate::weekly`]): the week's dates and any days without editions, the
+//! optional overview, then each category's selected stories with their
+//! summary, linked to the article in the latest edition that carried them.
+//!
+//! # Structure
+//!
+//! An edition is assembled from small renderers: the header, one section per
+//! category, each article (with its entity, date and footnote blocks) and the
+//! footer holding the trailing sections. [`MarkdownOptions`] picks the styles
+//! above and which blocks appear; its default renders the full edition. The
+//! output of each renderer is pinned by golden files under
+//! `src/outputs/testdata/markdown/`. [`partial_header`] and
+//! [`article_to_markdown`] expose the header and article renderers on their
+//! own for editions written as articles complete (see
+//! [`crate::outputs::partial`]).
 
-use crate::models::FrontPage;
+use crate::models::{
+    source_tag_of, AwfulNewsArticle, Correction, EditionAnchors, EditionDiff, FrontPage, NamedEntity, StoryRef, WeeklyEdition,
+    ENTITY_TYPES,
+};
+use crate::outputs::labels::SourceLabels;
+use crate::ordering::group_by_category;
+use crate::salience;
+use crate::utils::upcase;
+use crate::weekly::week_dates;
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use regex::Regex;
 use std::fmt::Write;
 use tracing::{debug, instrument};
 
-/// Convert a [`FrontPage`] to Markdown format.
-///
-/// Generates a complete Markdown document with all articles grouped by
-/// category, including full metadata (entities, dates, takeaways, etc.).
-///
-/// # Arguments
-///
-/// * `front_page` - The processed articles to render
+/// Placeholder text written when an edition contains no articles.
+pub const DEFAULT_EMPTY_EDITION_MESSAGE: &str = "No articles were available for this edition.";
+
+/// How article anchors are emitted in the Markdown edition.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum AnchorMode {
+    /// Only the stable id anchor, as a heading attribute.
+    #[default]
+    Id,
+    /// The id anchor as an `<a id>` tag, keeping the title slug on the
+    /// heading for links made before id anchors existed.
+    Both,
+}
+
+/// How named entities are rendered in the Markdown edition.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum EntityStyle {
+    /// A "Named Entities" list after the summary.
+    #[default]
+    List,
+    /// Footnote references on the first mention in the summary.
+    Footnotes,
+}
+
+/// Rendering style of the Markdown edition.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum MarkdownTheme {
+    /// Flat headings per article and section, separated by rules.
+    #[default]
+    Headings,
+    /// Each article in a collapsible `<details>` block.
+    Details,
+}
+
+impl MarkdownTheme {
+    /// Open an article: its title with source tag and anchor.
+    /// An `open` article starts expanded in the `details` theme.
+    fn open_article(
+        self,
+        md: &mut String,
+        article: &AwfulNewsArticle,
+        label: Option<&str>,
+        id: &str,
+        anchors: AnchorMode,
+        open: bool,
+    ) {
+        match self {
+            MarkdownTheme::Headings => write_heading(md, article, label, id, anchors),
+            MarkdownTheme::Details => {
+                writeln!(md, "<a id=\"{}\"></a>\n", id).unwrap();
+                writeln!(md, "{}", if open { "<details open>" } else { "<details>" }).unwrap();
+                match label {
+                    Some(label) => writeln!(
+                        md,
+                        "<summary><strong>{}</strong> - <small><code>{}</code></small></summary>\n",
+                        article.title, label
+                    )
+                    .unwrap(),
+                    None => writeln!(md, "<summary><strong>{}</strong></summary>\n", article.title).unwrap(),
+                }
+            }
+        }
+    }
+
+    /// Start a section of an article, e.g. "Key Takeaways".
+    fn section(self, md: &mut String, title: &str) {
+        match self {
+            MarkdownTheme::Headings => writeln!(md, "### {}", title).unwrap(),
+            MarkdownTheme::Details => writeln!(md, "**{}**", title).unwrap(),
+        }
+    }
+
+    /// Close an article.
+    fn close_article(self, md: &mut String) {
+        match self {
+            MarkdownTheme::Headings => writeln!(md, "---\n").unwrap(),
+            MarkdownTheme::Details => writeln!(md, "</details>\n").unwrap(),
+        }
+    }
+}
+
+/// Case-insensitive matcher for an entity name, bounded by word edges where
+/// the name itself starts or ends with a word character.
+fn entity_regex(name: &str) -> Option<Regex> {
+    let name = name.trim();
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    if name.is_empty() {
+        return None;
+    }
+    let start = if is_word(name.chars().next()) { r"\b" } else { "" };
+    let end = if is_word(name.chars().last()) { r"\b" } else { "" };
+    Regex::new(&format!("(?i){}{}{}", start, regex::escape(name), end)).ok()
+}
+
+/// Link the first mention of each entity in `summary` to a footnote.
 ///
-/// # Returns
+/// Returns the summary with `[^label]` references inserted, the footnotes
+/// as `(label, entity)` in order of appearance, and the entities that were
+/// not mentioned. Labels are prefixed with `prefix` (the article anchor) so
+/// they are unique across the edition.
+fn link_entity_footnotes<'a>(
+    summary: &str,
+    entities: &'a [NamedEntity],
+    prefix: &str,
+) -> (String, Vec<(String, &'a NamedEntity)>, Vec<&'a NamedEntity>) {
+    // Longest names first, so a shorter name never claims part of a longer one
+    let mut by_length: Vec<&NamedEntity> = entities.iter().collect();
+    by_length.sort_by_key(|e| std::cmp::Reverse(e.name.trim().len()));
+
+    let mut spans: Vec<(usize, usize, &NamedEntity)> = Vec::new();
+    let mut unlinked = Vec::new();
+    for entity in by_length {
+        let found = entity_regex(&entity.name).and_then(|re| {
+            re.find_iter(summary)
+                .map(|m| (m.start(), m.end()))
+                .find(|&(start, end)| spans.iter().all(|&(s, e, _)| end <= s || start >= e))
+        });
+        match found {
+            Some((start, end)) => spans.push((start, end, entity)),
+            None => unlinked.push(entity),
+        }
+    }
+    spans.sort_by_key(|&(start, _, _)| start);
+    // Keep the list in the model's order
+    unlinked.sort_by_key(|u| entities.iter().position(|e| std::ptr::eq(e, *u)));
+
+    let mut linked = String::with_capacity(summary.len() + spans.len() * 12);
+    let mut footnotes = Vec::with_capacity(spans.len());
+    let mut last = 0;
+    for (n, (_, end, entity)) in spans.into_iter().enumerate() {
+        let label = format!("{}-{}", prefix, n + 1);
+        write!(linked, "{}[^{}]", &summary[last..end], label).unwrap();
+        footnotes.push((label, entity));
+        last = end;
+    }
+    linked.push_str(&summary[last..]);
+    (linked, footnotes, unlinked)
+}
+
+/// Write an article heading with its source `label` and anchor `id`.
+fn write_heading(md: &mut String, article: &AwfulNewsArticle, label: Option<&str>, id: &str, anchors: AnchorMode) {
+    let mut heading = match label {
+        Some(label) => format!("## {} - <small>`{}`</small>", article.title, label),
+        None => format!("## {}", article.title),
+    };
+    match anchors {
+        AnchorMode::Id => write!(heading, " {{#{}}}", id).unwrap(),
+        AnchorMode::Both => writeln!(md, "<a id=\"{}\"></a>\n", id).unwrap(),
+    }
+    writeln!(md, "{}\n", heading).unwrap();
+}
+
+/// Which blocks of the Markdown edition appear, and how they are rendered.
 ///
-/// A Markdown string ready for writing to a file or rendering.
-#[instrument(level = "debug", skip_all)]
-pub fn front_page_to_markdown(front_page: &FrontPage) -> String {
-    let mut md = String::new();
+/// [`MarkdownOptions::default`] renders every block with the default
+/// styles, which is the edition the CLI writes unless told otherwise.
+#[derive(Debug, Clone)]
+pub struct MarkdownOptions {
+    /// Text shown in place of articles when the edition is empty.
+    pub empty_message: String,
+    /// How article anchors are emitted.
+    pub anchors: AnchorMode,
+    /// How named entities are rendered.
+    pub entities: EntityStyle,
+    /// How articles and their sections are laid out.
+    pub theme: MarkdownTheme,
+    /// Display labels of the source tags.
+    pub labels: SourceLabels,
+    /// Render the edition time at second precision.
+    pub stable_timestamps: bool,
+    /// Render each article's "Key Takeaways".
+    pub show_takeaways: bool,
+    /// Render named entities, as a list or as footnotes.
+    pub show_entities: bool,
+    /// Render "Important Dates" and "Important Timeframes".
+    pub show_dates: bool,
+    /// Render "In case you missed it" and "Also today" after the categories.
+    pub show_footer: bool,
+    /// Render "Since the last edition" when the edition has a diff.
+    pub show_diff: bool,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        MarkdownOptions {
+            empty_message: DEFAULT_EMPTY_EDITION_MESSAGE.to_string(),
+            anchors: AnchorMode::default(),
+            entities: EntityStyle::default(),
+            theme: MarkdownTheme::default(),
+            labels: SourceLabels::default(),
+            stable_timestamps: false,
+            show_takeaways: true,
+            show_entities: true,
+            show_dates: true,
+            show_footer: true,
+            show_diff: true,
+        }
+    }
+}
+
+/// Rendering state shared by every article of an edition.
+#[derive(Debug, Clone, Copy)]
+struct Style<'a> {
+    options: &'a MarkdownOptions,
+    /// Anchor ids, de-duplicated across the edition.
+    ids: &'a EditionAnchors<'a>,
+    /// The edition's corrections to earlier summaries.
+    corrections: &'a [Correction],
+}
 
+/// Title and edition time, plus the placeholder of an empty edition.
+fn render_header(md: &mut String, front_page: &FrontPage, options: &MarkdownOptions) {
     writeln!(md, "# Awful Times\n").unwrap();
-    writeln!(md, "#### Edition published at {}\n", front_page.local_time).unwrap();
-
-    // Group articles by category
-    use std::collections::BTreeMap;
-    let mut articles_by_category: BTreeMap<String, Vec<&crate::models::AwfulNewsArticle>> = BTreeMap::new();
-    
-    for article in &front_page.articles {
-        articles_by_category
-            .entry(article.category.clone())
-            .or_insert_with(Vec::new)
-            .push(article);
-    }
-
-    // Process each category in alphabetical order
-    for (category, articles) in articles_by_category {
-        writeln!(md, "# {}\n", category).unwrap();
-
-        for article in articles {
-            // Title with source tag
-            if let Some(tag) = article.source_tag() {
-                writeln!(
-                    md,
-                    "## {} - <small>`{}`</small>\n",
-                    article.title, tag
-                )
-                .unwrap();
-            } else {
-                writeln!(md, "## {}\n", article.title).unwrap();
-            }
+    writeln!(
+        md,
+        "#### Edition published at {}\n",
+        edition_time(&front_page.local_time, options.stable_timestamps)
+    )
+    .unwrap();
 
-            // Source link
-            if let Some(source) = &article.source {
-                writeln!(md, "- [source]({})", source).unwrap();
-            }
+    if front_page.articles.is_empty() {
+        writeln!(md, "_{}_\n", options.empty_message.trim()).unwrap();
+    }
+}
 
-            // Publication date/time
-            writeln!(
-                md,
-                "- _Published: {} {}_",
-                article.dateOfPublication, article.timeOfPublication
-            )
-            .unwrap();
+/// "Top Stories": the featured articles, best first, linked to their entries
+/// in the category sections.
+fn render_top_stories(md: &mut String, front_page: &FrontPage, style: Style) {
+    writeln!(md, "# Top Stories\n").unwrap();
+    for (rank, story) in front_page.top.iter().enumerate() {
+        let article = front_page.articles.iter().find(|a| {
+            !a.overflow && a.source == story.source && (story.source.is_some() || a.title == story.title)
+        });
+        let Some(article) = article else {
+            writeln!(md, "{}. {}", rank + 1, story.title).unwrap();
+            continue;
+        };
+        let label = style
+            .options
+            .labels
+            .of(article)
+            .map(|label| format!(" - <small>`{}`</small>", label))
+            .unwrap_or_default();
+        writeln!(md, "{}. [{}](#{}){}", rank + 1, article.title, style.ids.id(article), label).unwrap();
+        writeln!(md, "   {}", article.summaryOfNewsArticle.trim()).unwrap();
+    }
+    writeln!(md).unwrap();
+}
 
-            // Category
-            writeln!(md, "- **{}**", article.category).unwrap();
+/// "Since the last edition": what changed since the day's previous edition.
+fn render_since_last_edition(md: &mut String, front_page: &FrontPage, diff: &EditionDiff, style: Style) {
+    let previous = upcase(&diff.previous_edition);
+    writeln!(md, "# Since the last edition\n").unwrap();
+    if diff.new.is_empty() && diff.updated.is_empty() && diff.dropped.is_empty() {
+        writeln!(md, "_Nothing changed since the {} edition._\n", previous).unwrap();
+        return;
+    }
+    writeln!(
+        md,
+        "_Compared with the {} edition: {} new, {} updated, {} no longer covered._\n",
+        previous,
+        diff.new.len(),
+        diff.updated.len(),
+        diff.dropped.len()
+    )
+    .unwrap();
 
-            // Tags
-            if !article.tags.is_empty() {
-                let tags_str = article.tags.join(", ");
-                writeln!(md, "- <small>tags: `{}`</small>\n", tags_str).unwrap();
-            } else {
-                writeln!(md).unwrap();
-            }
+    // Stories still in the edition link to their article, unless only listed under "Also today"
+    let link = |story: &StoryRef| {
+        let article = front_page.articles.iter().find(|a| {
+            !a.overflow && a.source == story.source && (story.source.is_some() || a.title == story.title)
+        });
+        match (article, &story.source) {
+            (Some(article), _) => format!("[{}](#{})", story.title, style.ids.id(article)),
+            (None, Some(source)) => format!("[{}]({})", story.title, source),
+            (None, None) => story.title.clone(),
+        }
+    };
+    for (label, stories) in [("New", &diff.new), ("Updated", &diff.updated), ("No longer covered", &diff.dropped)] {
+        for story in stories {
+            writeln!(md, "- {}: {}", label, link(story)).unwrap();
+        }
+    }
+    writeln!(md).unwrap();
+}
 
-            // Summary
-            writeln!(md, "### Summary\n").unwrap();
-            writeln!(md, "{}\n", article.summaryOfNewsArticle.trim()).unwrap();
+/// A category heading followed by its articles in full.
+fn render_category_section(md: &mut String, category: &str, articles: &[&AwfulNewsArticle], style: Style) {
+    writeln!(md, "# {}\n", category).unwrap();
+    for article in articles {
+        render_article(md, article, style);
+    }
+}
 
-        if !article.keyTakeAways.is_empty() {
-            writeln!(md, "### Key Takeaways").unwrap();
-            for takeaway in &article.keyTakeAways {
-                writeln!(md, "  - {}", takeaway).unwrap();
-            }
-            writeln!(md).unwrap();
+/// `A`, `A and B`, `A, B and C`.
+fn join_names(names: &[String]) -> String {
+    match names {
+        [] => String::new(),
+        [only] => only.clone(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+/// One article: heading, metadata, summary and the enabled blocks.
+fn render_article(md: &mut String, article: &AwfulNewsArticle, style: Style) {
+    write_article(md, article, style, false);
+}
+
+/// "Lede": the edition's most salient article, in full and expanded.
+fn render_lede(md: &mut String, article: &AwfulNewsArticle, style: Style) {
+    writeln!(md, "# Lede\n").unwrap();
+    write_article(md, article, style, true);
+}
+
+/// Body of [`render_article`]; `open` expands it in the `details` theme.
+fn write_article(md: &mut String, article: &AwfulNewsArticle, style: Style, open: bool) {
+    let options = style.options;
+    let theme = options.theme;
+    let id = style.ids.id(article);
+    let label = options.labels.of(article);
+
+    // Title with source label and anchor
+    theme.open_article(md, article, label.as_deref(), &id, options.anchors, open);
+
+    // Source link
+    if let Some(source) = &article.source {
+        writeln!(md, "- [source]({})", source).unwrap();
+    }
+
+    // Publication date/time
+    let published = format!("{} {}", article.dateOfPublication, article.timeOfPublication);
+    writeln!(md, "- _Published: {}_", published.trim()).unwrap();
+
+    // Byline
+    if !article.authors.is_empty() {
+        writeln!(md, "- _By {}_", join_names(&article.authors)).unwrap();
+    }
+
+    // Badge for stories updated since an earlier edition today
+    if let Some(edition) = &article.updateOf {
+        writeln!(md, "- <small>`Updated`</small> _since the {} edition_", upcase(edition)).unwrap();
+    }
+
+    // Note on stories summarized differently earlier today
+    if let Some(correction) = style
+        .corrections
+        .iter()
+        .find(|c| c.story.source.is_some() && c.story.source == article.source)
+    {
+        writeln!(md, "- <small>`Corrected`</small> _from the {} edition_", upcase(&correction.earlier_edition)).unwrap();
+    }
+
+    // Category
+    writeln!(md, "- **{}**", article.category).unwrap();
+
+    // Locations
+    if !article.locations.is_empty() {
+        writeln!(md, "- _Locations: {}_", article.locations.join(", ")).unwrap();
+    }
+
+    // Tags
+    if !article.tags.is_empty() {
+        let tags_str = article.tags.join(", ");
+        writeln!(md, "- <small>tags: `{}`</small>\n", tags_str).unwrap();
+    } else {
+        writeln!(md).unwrap();
+    }
+
+    // Summary, with entity footnote references if requested
+    let summary = article.summaryOfNewsArticle.trim();
+    let (summary, footnotes, listed) = match (options.show_entities, options.entities) {
+        (false, _) => (summary.to_string(), Vec::new(), Vec::new()),
+        (true, EntityStyle::List) => (summary.to_string(), Vec::new(), article.namedEntities.iter().collect()),
+        (true, EntityStyle::Footnotes) => link_entity_footnotes(summary, &article.namedEntities, &id),
+    };
+    theme.section(md, "Summary");
+    writeln!(md, "\n{}\n", summary).unwrap();
+
+    if options.show_takeaways && !article.keyTakeAways.is_empty() {
+        theme.section(md, "Key Takeaways");
+        for takeaway in &article.keyTakeAways {
+            writeln!(md, "  - {}", takeaway).unwrap();
         }
+        writeln!(md).unwrap();
+    }
 
-        if !article.namedEntities.is_empty() {
-            writeln!(md, "### Named Entities").unwrap();
-            for entity in &article.namedEntities {
-                writeln!(md, "- **{}**", entity.name).unwrap();
-                writeln!(md, "    - {}", entity.whatIsThisEntity).unwrap();
-                writeln!(md, "    - {}", entity.whyIsThisEntityRelevantToTheArticle).unwrap();
-            }
-            writeln!(md).unwrap();
+    render_entities(md, &listed, theme);
+    if options.show_dates {
+        render_dates(md, article, theme);
+    }
+    render_footnotes(md, &footnotes);
+
+    theme.close_article(md);
+}
+
+/// Heading of an entity type group in the "Named Entities" list.
+fn entity_group_heading(entity_type: Option<&str>) -> String {
+    match entity_type {
+        Some("Person") => "People".to_string(),
+        Some("Organization") => "Organizations".to_string(),
+        Some("Place") => "Places".to_string(),
+        Some("Event") => "Events".to_string(),
+        Some(other) => other.to_string(),
+        None => "Other".to_string(),
+    }
+}
+
+/// `entities` grouped by [`NamedEntity::entity_type`]: the [`ENTITY_TYPES`]
+/// in their order, then other types as they first appear, then untyped
+/// entities. Entities keep their order within a group.
+fn group_entities<'a>(entities: &[&'a NamedEntity]) -> Vec<(Option<String>, Vec<&'a NamedEntity>)> {
+    let mut groups: Vec<(Option<String>, Vec<&NamedEntity>)> =
+        ENTITY_TYPES.iter().map(|t| (Some(t.to_string()), Vec::new())).collect();
+    for entity in entities {
+        let entity_type = entity.entity_type();
+        match groups.iter_mut().find(|(t, _)| *t == entity_type) {
+            Some((_, group)) => group.push(entity),
+            None => groups.push((entity_type, vec![entity])),
         }
+    }
+    groups.retain(|(_, group)| !group.is_empty());
+    // Stable: untyped entities go last, everything else keeps its place
+    groups.sort_by_key(|(t, _)| t.is_none());
+    groups
+}
 
-        if !article.importantDates.is_empty() {
-            writeln!(md, "### Important Dates").unwrap();
-            for date in &article.importantDates {
-                writeln!(md, "  - **{}**", date.dateMentionedInArticle).unwrap();
-                writeln!(md, "    - {}", date.descriptionOfWhyDateIsRelevant).unwrap();
-            }
+/// The "Named Entities" list; nothing when `entities` is empty.
+///
+/// Typed entities are listed under a heading per type (see
+/// [`group_entities`]); a list without any types is rendered flat.
+fn render_entities(md: &mut String, entities: &[&NamedEntity], theme: MarkdownTheme) {
+    if entities.is_empty() {
+        return;
+    }
+    theme.section(md, "Named Entities");
+    let typed = entities.iter().any(|e| e.entity_type().is_some());
+    for (entity_type, group) in group_entities(entities) {
+        if typed {
+            writeln!(md, "_{}_\n", entity_group_heading(entity_type.as_deref())).unwrap();
+        }
+        for entity in &group {
+            writeln!(md, "- **{}**", entity.name).unwrap();
+            writeln!(md, "    - {}", entity.whatIsThisEntity).unwrap();
+            writeln!(md, "    - {}", entity.whyIsThisEntityRelevantToTheArticle).unwrap();
+        }
+        if typed {
             writeln!(md).unwrap();
         }
+    }
+    if !typed {
+        writeln!(md).unwrap();
+    }
+}
+
+/// "Important Dates" and "Important Timeframes", each only when present.
+fn render_dates(md: &mut String, article: &AwfulNewsArticle, theme: MarkdownTheme) {
+    if !article.importantDates.is_empty() {
+        theme.section(md, "Important Dates");
+        for date in &article.importantDates {
+            writeln!(md, "  - **{}**", date.dateMentionedInArticle).unwrap();
+            writeln!(md, "    - {}", date.descriptionOfWhyDateIsRelevant).unwrap();
+        }
+        writeln!(md).unwrap();
+    }
+
+    if !article.importantTimeframes.is_empty() {
+        theme.section(md, "Important Timeframes");
+        for timeframe in &article.importantTimeframes {
+            writeln!(
+                md,
+                "  - **From _{}_ to _{}_**",
+                timeframe.approximateTimeFrameStart, timeframe.approximateTimeFrameEnd
+            )
+            .unwrap();
+            writeln!(
+                md,
+                "    - {}",
+                timeframe.descriptionOfWhyTimeFrameIsRelevant
+            )
+            .unwrap();
+        }
+        writeln!(md).unwrap();
+    }
+}
+
+/// Footnote bodies of the entities linked from the summary.
+fn render_footnotes(md: &mut String, footnotes: &[(String, &NamedEntity)]) {
+    for (label, entity) in footnotes {
+        writeln!(
+            md,
+            "[^{}]: **{}**: {} {}",
+            label,
+            entity.name.trim(),
+            entity.whatIsThisEntity.trim(),
+            entity.whyIsThisEntityRelevantToTheArticle.trim()
+        )
+        .unwrap();
+    }
+    if !footnotes.is_empty() {
+        writeln!(md).unwrap();
+    }
+}
 
-        if !article.importantTimeframes.is_empty() {
-            writeln!(md, "### Important Timeframes").unwrap();
-            for timeframe in &article.importantTimeframes {
-                writeln!(
-                    md,
-                    "  - **From _{}_ to _{}_**",
-                    timeframe.approximateTimeFrameStart, timeframe.approximateTimeFrameEnd
-                )
-                .unwrap();
-                writeln!(
-                    md,
-                    "    - {}",
-                    timeframe.descriptionOfWhyTimeFrameIsRelevant
-                )
-                .unwrap();
+/// The sections after the categories: carried-over articles in full, then
+/// overflow articles as a compact list.
+fn render_footer(md: &mut String, front_page: &FrontPage, style: Style) {
+    // Articles retried from the previous run's failures
+    let carried: Vec<_> = front_page.articles.iter().filter(|a| a.carriedOver).collect();
+    if !carried.is_empty() {
+        writeln!(md, "# In case you missed it\n").unwrap();
+        for article in carried {
+            render_article(md, article, style);
+        }
+    }
+
+    // Overflow articles: title and source link only
+    let overflow: Vec<_> = front_page.articles.iter().filter(|a| a.overflow).collect();
+    if !overflow.is_empty() {
+        writeln!(md, "# Also today\n").unwrap();
+        for article in overflow {
+            let tag = style
+                .options
+                .labels
+                .of(article)
+                .map(|label| format!(" - <small>`{}`</small>", label))
+                .unwrap_or_default();
+            match &article.source {
+                Some(source) => writeln!(md, "- [{}]({}){}", article.title, source, tag).unwrap(),
+                None => writeln!(md, "- {}{}", article.title, tag).unwrap(),
             }
-            writeln!(md).unwrap();
         }
+        writeln!(md).unwrap();
+    }
+}
+
+/// Convert a [`FrontPage`] to Markdown format.
+///
+/// Generates a complete Markdown document with all articles grouped by
+/// category, assembled from the header, one section per category and the
+/// footer. `options` selects the blocks and styles (see [`MarkdownOptions`]).
+///
+/// # Returns
+///
+/// A Markdown string ready for writing to a file or rendering.
+#[instrument(level = "debug", skip_all)]
+pub fn front_page_to_markdown(front_page: &FrontPage, options: &MarkdownOptions) -> String {
+    let ids = EditionAnchors::new(&front_page.articles);
+    let style = Style {
+        options,
+        ids: &ids,
+        corrections: &front_page.corrections,
+    };
+    let mut md = String::new();
+
+    render_header(&mut md, front_page, options);
+    if let Some(editorial) = &front_page.editorial {
+        writeln!(md, "## {}\n\n{}\n", editorial.headline, editorial.blurb).unwrap();
+    }
+    if !front_page.top.is_empty() {
+        render_top_stories(&mut md, front_page, style);
+    }
+    if let (true, Some(diff)) = (options.show_diff, &front_page.diff) {
+        render_since_last_edition(&mut md, front_page, diff, style);
+    }
 
-            writeln!(md, "---\n").unwrap();
+    let lede = salience::lede(&front_page.articles).map(|i| &front_page.articles[i]);
+    if let Some(article) = lede {
+        render_lede(&mut md, article, style);
+    }
+
+    // Process each category in edition order; the lede is already out front
+    for (category, mut articles) in group_by_category(&front_page.articles) {
+        articles.retain(|a| !lede.is_some_and(|lede| std::ptr::eq(*a, lede)));
+        if !articles.is_empty() {
+            render_category_section(&mut md, category, &articles, style);
         }
     }
 
+    if options.show_footer {
+        render_footer(&mut md, front_page, style);
+    }
+
     debug!(chars = md.len(), "Rendered Markdown length");
     md
 }
 
+/// An edition time as rendered: `local_time` without its fractional
+/// seconds when `stable` is set, as is otherwise.
+pub fn edition_time(local_time: &str, stable: bool) -> &str {
+    match local_time.split_once('.') {
+        Some((seconds, _)) if stable => seconds,
+        _ => local_time,
+    }
+}
+
+/// The heading of an edition still being processed (see
+/// [`crate::outputs::partial`]).
+pub fn partial_header(front_page: &FrontPage) -> String {
+    format!("# Awful Times\n\n#### Edition in progress, started at {}\n\n", front_page.local_time)
+}
+
+/// One article on its own, as [`front_page_to_markdown`] renders it under
+/// its category, for appending to a partial edition.
+pub fn article_to_markdown(article: &AwfulNewsArticle, options: &MarkdownOptions) -> String {
+    let ids = EditionAnchors::new(std::slice::from_ref(article));
+    let mut md = String::new();
+    render_article(
+        &mut md,
+        article,
+        Style {
+            options,
+            ids: &ids,
+            corrections: &[],
+        },
+    );
+    md
+}
+
+/// Convert a [`WeeklyEdition`] to Markdown.
+///
+/// Stories are grouped by category in the edition's order; only the
+/// source labels of `options` apply.
+#[instrument(level = "debug", skip_all)]
+pub fn weekly_to_markdown(weekly: &WeeklyEdition, options: &MarkdownOptions) -> String {
+    let mut md = String::new();
+    writeln!(md, "# Week in Review\n").unwrap();
+    writeln!(md, "#### {} to {}\n", weekly.week_start, weekly.week_end).unwrap();
+
+    if weekly.days_covered.is_empty() {
+        writeln!(md, "_No editions were published this week._\n").unwrap();
+        return md;
+    }
+    let missing: Vec<String> = NaiveDate::parse_from_str(&weekly.week_end, "%Y-%m-%d")
+        .map(|end| week_dates(end).iter().map(|d| d.to_string()).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|date| !weekly.days_covered.contains(date))
+        .collect();
+    if !missing.is_empty() {
+        writeln!(md, "_No editions on {}._\n", missing.join(", ")).unwrap();
+    }
+
+    if let Some(overview) = &weekly.overview {
+        writeln!(md, "# Overview\n\n{}\n", overview.trim()).unwrap();
+    }
+
+    let mut category: Option<&str> = None;
+    for story in &weekly.stories {
+        if category != Some(story.category.as_str()) {
+            writeln!(md, "# {}\n", story.category).unwrap();
+            category = Some(&story.category);
+        }
+        let tag = story
+            .source
+            .as_deref()
+            .and_then(source_tag_of)
+            .map(|tag| format!(" - <small>`{}`</small>", options.labels.label(&tag)))
+            .unwrap_or_default();
+        writeln!(md, "## [{}](./{}#{}){}\n", story.title, story.edition_file, story.anchor, tag).unwrap();
+        if story.coverage > 1 {
+            writeln!(md, "_Covered in {} editions_\n", story.coverage).unwrap();
+        }
+        writeln!(md, "{}\n", story.summary.trim()).unwrap();
+    }
+    md
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::AwfulNewsArticle;
+    use crate::test_support;
+    use crate::models::{FeaturedStory, ImportantDate, ImportantTimeframe, ScrapeMeta, WeeklyStory};
 
     #[test]
     fn test_empty_frontpage_markdown() {
-        let frontpage = FrontPage {
-            local_date: "2025-05-06".to_string(),
-            time_of_day: "evening".to_string(),
-            local_time: "20:30:00".to_string(),
-            articles: vec![],
-        };
+        let frontpage = test_support::front_page("evening", "20:30:00");
 
-        let md = front_page_to_markdown(&frontpage);
+        let md = front_page_to_markdown(&frontpage, &MarkdownOptions::default());
         assert!(md.contains("# Awful Times"));
         assert!(md.contains("20:30:00"));
+        assert!(md.contains("_No articles were available for this edition._"));
+    }
+
+    #[test]
+    fn test_empty_frontpage_custom_placeholder() {
+        let frontpage = test_support::front_page("morning", "08:00:00");
+
+        let md = front_page_to_markdown(
+            &frontpage,
+            &MarkdownOptions {
+                empty_message: "Quiet news day.".to_string(),
+                ..Default::default()
+            },
+        );
+        assert!(md.contains("_Quiet news day._"));
+        assert!(!md.contains(DEFAULT_EMPTY_EDITION_MESSAGE));
+    }
+
+    #[test]
+    fn test_stable_timestamps_drop_microseconds() {
+        let frontpage = test_support::front_page("evening", "20:30:05.123456");
+
+        let md = front_page_to_markdown(
+            &frontpage,
+            &MarkdownOptions {
+                stable_timestamps: true,
+                ..Default::default()
+            },
+        );
+        assert!(md.contains("#### Edition published at 20:30:05\n"));
+        assert!(!md.contains(".123456"));
+
+        let md = front_page_to_markdown(&frontpage, &MarkdownOptions::default());
+        assert!(md.contains("#### Edition published at 20:30:05.123456\n"));
+        assert_eq!(edition_time("20:30:05", true), "20:30:05");
     }
 
     #[test]
     fn test_frontpage_with_article() {
         let article = AwfulNewsArticle {
             source: Some("https://example.com/article".to_string()),
-            dateOfPublication: "2025-05-06".to_string(),
             timeOfPublication: "14:30:00".to_string(),
-            title: "Test Article".to_string(),
-            category: "Science & Technology".to_string(),
             summaryOfNewsArticle: "Test summary.".to_string(),
             keyTakeAways: vec!["Point 1".to_string()],
-            namedEntities: vec![],
-            importantDates: vec![],
-            importantTimeframes: vec![],
             tags: vec!["tech".to_string(), "science".to_string()],
-            content: None,
+            locations: vec!["Japan".to_string(), "East Asia".to_string()],
+            authors: vec!["Jane Doe".to_string(), "John Roe".to_string(), "Ann Poe".to_string()],
+            scrapeMeta: Some(ScrapeMeta {
+                scraper: "cnn".to_string(),
+                version: "0.0.0-test".to_string(),
+                fetched_at: None,
+                content_length: 1200,
+                selector_hash: "0123456789ab".to_string(),
+                fetched_from: None,
+            }),
+            ..test_support::article("Test Article", "Science & Technology")
         };
 
         let frontpage = FrontPage {
-            local_date: "2025-05-06".to_string(),
-            time_of_day: "morning".to_string(),
-            local_time: "08:00:00".to_string(),
             articles: vec![article],
+            ..test_support::front_page("morning", "08:00:00")
         };
 
-        let md = front_page_to_markdown(&frontpage);
-        assert!(md.contains("## Test Article - <small>`example`</small>"));
+        let md = front_page_to_markdown(&frontpage, &MarkdownOptions::default());
+        assert!(!md.contains(DEFAULT_EMPTY_EDITION_MESSAGE));
+        assert!(md.contains("## Test Article - <small>`example`</small> {#a-"));
         assert!(md.contains("`example`"));  // source tag
         assert!(md.contains("**Science & Technology**"));  // category
+        assert!(md.contains("- _By Jane Doe, John Roe and Ann Poe_"));  // byline
+        assert!(md.contains("- _Locations: Japan, East Asia_"));  // locations
         assert!(md.contains("tags: `tech, science`"));  // tags
         assert!(md.contains("Test summary"));
         assert!(md.contains("Point 1"));
+        assert!(!md.contains("Updated since"));
+        // Provenance is JSON only
+        assert!(!md.contains("0123456789ab") && !md.contains("0.0.0-test"));
+    }
+
+    #[test]
+    fn test_anchor_modes() {
+        let article = AwfulNewsArticle {
+            source: Some("https://text.npr.org/1".to_string()),
+            summaryOfNewsArticle: "Summary.".to_string(),
+            ..test_support::article("Reworded Title", "Science & Technology")
+        };
+        let id = article.anchor_id();
+        let frontpage = FrontPage {
+            articles: vec![article],
+            ..test_support::front_page("morning", "08:00:00")
+        };
+
+        let md = front_page_to_markdown(&frontpage, &MarkdownOptions::default());
+        assert!(md.contains(&format!("## Reworded Title - <small>`npr`</small> {{#{}}}", id)));
+        assert!(!md.contains("<a id="));
+
+        let md = front_page_to_markdown(
+            &frontpage,
+            &MarkdownOptions {
+                anchors: AnchorMode::Both,
+                ..Default::default()
+            },
+        );
+        assert!(md.contains(&format!("<a id=\"{}\"></a>\n\n## Reworded Title - <small>`npr`</small>\n", id)));
+        assert!(!md.contains("{#"));
+    }
+
+    #[test]
+    fn test_overflow_articles_render_as_also_today() {
+        let article = |title: &str, overflow: bool| AwfulNewsArticle {
+            source: Some(format!("https://lite.cnn.com/{}", title)),
+            timeOfPublication: "14:30:00".to_string(),
+            summaryOfNewsArticle: format!("{} summary", title),
+            overflow,
+            ..test_support::article(title, "Politics & Governance")
+        };
+        let frontpage = FrontPage {
+            articles: vec![article("Kept", false), article("Extra", true)],
+            ..test_support::front_page("evening", "20:00:00")
+        };
+
+        let md = front_page_to_markdown(&frontpage, &MarkdownOptions::default());
+        assert!(md.contains("## Kept - <small>`cnn`</small> {#a-"));
+        assert!(!md.contains("## Extra"));
+        assert!(!md.contains("Extra summary"));
+        let also_today = md.split("# Also today").nth(1).unwrap();
+        assert!(also_today.contains("- [Extra](https://lite.cnn.com/Extra) - <small>`cnn`</small>"));
+    }
+
+    #[test]
+    fn test_carried_over_articles_render_in_case_you_missed_it() {
+        let article = |title: &str, carried_over: bool| AwfulNewsArticle {
+            source: Some(format!("https://text.npr.org/{}", title)),
+            dateOfPublication: "2025-05-05".to_string(),
+            timeOfPublication: "19:00:00".to_string(),
+            summaryOfNewsArticle: format!("{} summary", title),
+            carriedOver: carried_over,
+            ..test_support::article(title, "World News")
+        };
+        let frontpage = FrontPage {
+            articles: vec![article("Fresh", false), article("Overnight", true)],
+            ..test_support::front_page("morning", "07:00:00")
+        };
+
+        let md = front_page_to_markdown(&frontpage, &MarkdownOptions::default());
+        let (categories, missed) = md.split_once("# In case you missed it").unwrap();
+        assert!(categories.contains("## Fresh"));
+        assert!(!categories.contains("Overnight"));
+        assert!(missed.contains("## Overnight - <small>`npr`</small> {#a-"));
+        assert!(missed.contains("Overnight summary"));
+    }
+
+    fn entity(name: &str) -> NamedEntity {
+        NamedEntity {
+            name: name.to_string(),
+            whatIsThisEntity: format!("What {} is.", name),
+            whyIsThisEntityRelevantToTheArticle: format!("Why {} matters.", name),
+            entityType: None,
+        }
+    }
+
+    #[test]
+    fn test_typed_entities_are_grouped_by_type() {
+        let typed = |name: &str, entity_type: &str| NamedEntity {
+            entityType: Some(entity_type.to_string()),
+            ..entity(name)
+        };
+        let entities = [
+            typed("Kyiv", "Place"),
+            entity("Untyped"),
+            typed("Treaty of Rome", "Treaty"),
+            typed("NATO", "org"),
+            typed("Jens Stoltenberg", "Person"),
+            typed("Summit", "Other"),
+            typed("Lviv", "place"),
+        ];
+        let refs: Vec<&NamedEntity> = entities.iter().collect();
+        let mut md = String::new();
+        render_entities(&mut md, &refs, MarkdownTheme::Headings);
+
+        let headings: Vec<&str> = md.lines().filter(|l| l.starts_with('_')).collect();
+        assert_eq!(headings, vec!["_People_", "_Organizations_", "_Places_", "_Treaty_", "_Other_"]);
+        let names: Vec<&str> = md.lines().filter_map(|l| l.strip_prefix("- **")).collect();
+        assert_eq!(
+            names,
+            vec!["Jens Stoltenberg**", "NATO**", "Kyiv**", "Lviv**", "Treaty of Rome**", "Untyped**", "Summit**"]
+        );
+        assert!(md.contains("_Places_\n\n- **Kyiv**\n    - What Kyiv is.\n    - Why Kyiv matters.\n- **Lviv**"));
+
+        // Without any types the list stays flat
+        let untyped = [entity("Kyiv"), entity("NATO")];
+        let mut flat = String::new();
+        render_entities(&mut flat, &untyped.iter().collect::<Vec<_>>(), MarkdownTheme::Headings);
+        assert!(!flat.lines().any(|l| l.starts_with('_')));
+        assert!(flat.ends_with("- **NATO**\n    - What NATO is.\n    - Why NATO matters.\n\n"));
+    }
+
+    #[test]
+    fn test_footnotes_link_first_mention_case_insensitively() {
+        let entities = vec![entity("Federal Reserve"), entity("Jerome Powell"), entity("IMF")];
+        let summary = "The federal reserve held rates. Powell said the Federal Reserve would wait; Jerome Powell spoke.";
+        let (linked, footnotes, unlinked) = link_entity_footnotes(summary, &entities, "a-1");
+
+        assert_eq!(
+            linked,
+            "The federal reserve[^a-1-1] held rates. Powell said the Federal Reserve would wait; Jerome Powell[^a-1-2] spoke."
+        );
+        let labels: Vec<(&str, &str)> = footnotes.iter().map(|(l, e)| (l.as_str(), e.name.as_str())).collect();
+        assert_eq!(labels, vec![("a-1-1", "Federal Reserve"), ("a-1-2", "Jerome Powell")]);
+        assert_eq!(unlinked.len(), 1);
+        assert_eq!(unlinked[0].name, "IMF");
+    }
+
+    #[test]
+    fn test_footnotes_never_double_link_overlapping_names() {
+        let entities = vec![entity("New York"), entity("New York Times"), entity("Times")];
+        let summary = "The New York Times reported from New York.";
+        let (linked, footnotes, unlinked) = link_entity_footnotes(summary, &entities, "a-2");
+
+        assert_eq!(linked, "The New York Times[^a-2-1] reported from New York[^a-2-2].");
+        assert_eq!(footnotes.len(), 2);
+        // "Times" only occurs inside the already linked name
+        assert_eq!(unlinked.iter().map(|e| e.name.as_str()).collect::<Vec<_>>(), vec!["Times"]);
+        // Matches respect word boundaries
+        let (linked, _, _) = link_entity_footnotes("Timescale data.", &entities, "a-3");
+        assert_eq!(linked, "Timescale data.");
+    }
+
+    #[test]
+    fn test_footnote_style_renders_footnote_bodies() {
+        let article = AwfulNewsArticle {
+            source: Some("https://text.npr.org/nx-1".to_string()),
+            summaryOfNewsArticle: "The Federal Reserve held rates steady.".to_string(),
+            namedEntities: vec![entity("Federal Reserve"), entity("Treasury")],
+            ..test_support::article("Rates held", "Economy & Business")
+        };
+        let anchor = article.anchor_id();
+        let frontpage = FrontPage {
+            articles: vec![article],
+            ..test_support::front_page("evening", "20:00:00")
+        };
+
+        let md = front_page_to_markdown(
+            &frontpage,
+            &MarkdownOptions {
+                entities: EntityStyle::Footnotes,
+                ..Default::default()
+            },
+        );
+        assert!(md.contains(&format!("The Federal Reserve[^{}-1] held rates steady.", anchor)));
+        assert!(md.contains(&format!(
+            "[^{}-1]: **Federal Reserve**: What Federal Reserve is. Why Federal Reserve matters.",
+            anchor
+        )));
+        // Unmentioned entities stay in the list
+        let list = md.split("### Named Entities").nth(1).unwrap();
+        assert!(list.contains("- **Treasury**"));
+        assert!(!list.contains("- **Federal Reserve**"));
+
+        let md = front_page_to_markdown(&frontpage, &MarkdownOptions::default());
+        assert!(!md.contains("[^"));
+    }
+
+    /// One fully populated article, shared by the theme snapshots.
+    fn themed_front_page() -> FrontPage {
+        let article = AwfulNewsArticle {
+            source: Some("https://text.npr.org/nx-7".to_string()),
+            timeOfPublication: "09:15:00".to_string(),
+            summaryOfNewsArticle: "The city council approved the budget.".to_string(),
+            keyTakeAways: vec!["Vote was 7-2".to_string()],
+            namedEntities: vec![entity("City Council")],
+            importantDates: vec![ImportantDate {
+                dateMentionedInArticle: "2025-07-01".to_string(),
+                descriptionOfWhyDateIsRelevant: "Budget takes effect".to_string(),
+            }],
+            importantTimeframes: vec![ImportantTimeframe {
+                approximateTimeFrameStart: "2025-07-01".to_string(),
+                approximateTimeFrameEnd: "2026-06-30".to_string(),
+                descriptionOfWhyTimeFrameIsRelevant: "Fiscal year".to_string(),
+            }],
+            tags: vec!["budget".to_string()],
+            ..test_support::article("Council approves budget", "Politics & Governance")
+        };
+        FrontPage {
+            articles: vec![article],
+            ..test_support::front_page("morning", "08:00:00")
+        }
+    }
+
+    #[test]
+    fn test_headings_theme_snapshot() {
+        let frontpage = themed_front_page();
+        let id = frontpage.articles[0].anchor_id();
+        let md = front_page_to_markdown(&frontpage, &MarkdownOptions::default());
+        let expected = format!(
+            "# Awful Times\n\n#### Edition published at 08:00:00\n\n\
+             # Politics & Governance\n\n\
+             ## Council approves budget - <small>`npr`</small> {{#{id}}}\n\n\
+             - [source](https://text.npr.org/nx-7)\n\
+             - _Published: 2025-05-06 09:15:00_\n\
+             - **Politics & Governance**\n\
+             - <small>tags: `budget`</small>\n\n\
+             ### Summary\n\nThe city council approved the budget.\n\n\
+             ### Key Takeaways\n  - Vote was 7-2\n\n\
+             ### Named Entities\n- **City Council**\n    - What City Council is.\n    - Why City Council matters.\n\n\
+             ### Important Dates\n  - **2025-07-01**\n    - Budget takes effect\n\n\
+             ### Important Timeframes\n  - **From _2025-07-01_ to _2026-06-30_**\n    - Fiscal year\n\n\
+             ---\n\n"
+        );
+        assert_eq!(md, expected);
+    }
+
+    #[test]
+    fn test_details_theme_snapshot() {
+        let frontpage = themed_front_page();
+        let id = frontpage.articles[0].anchor_id();
+        let md = front_page_to_markdown(
+            &frontpage,
+            &MarkdownOptions {
+                theme: MarkdownTheme::Details,
+                ..Default::default()
+            },
+        );
+        let expected = format!(
+            "# Awful Times\n\n#### Edition published at 08:00:00\n\n\
+             # Politics & Governance\n\n\
+             <a id=\"{id}\"></a>\n\n\
+             <details>\n\
+             <summary><strong>Council approves budget</strong> - <small><code>npr</code></small></summary>\n\n\
+             - [source](https://text.npr.org/nx-7)\n\
+             - _Published: 2025-05-06 09:15:00_\n\
+             - **Politics & Governance**\n\
+             - <small>tags: `budget`</small>\n\n\
+             **Summary**\n\nThe city council approved the budget.\n\n\
+             **Key Takeaways**\n  - Vote was 7-2\n\n\
+             **Named Entities**\n- **City Council**\n    - What City Council is.\n    - Why City Council matters.\n\n\
+             **Important Dates**\n  - **2025-07-01**\n    - Budget takes effect\n\n\
+             **Important Timeframes**\n  - **From _2025-07-01_ to _2026-06-30_**\n    - Fiscal year\n\n\
+             </details>\n\n"
+        );
+        assert_eq!(md, expected);
+    }
+
+    /// A mixed edition covering every block: two categories, an update, a
+    /// source-less article, a repeated anchor, a carried-over article and
+    /// overflow.
+    fn golden_front_page() -> FrontPage {
+        let article = |source: Option<&str>, title: &str, category: &str| AwfulNewsArticle {
+            source: source.map(str::to_string),
+            timeOfPublication: "10:00:00".to_string(),
+            summaryOfNewsArticle: format!("{} summary.", title),
+            ..test_support::article(title, category)
+        };
+        let mut full = themed_front_page().articles.remove(0);
+        full.updateOf = Some("morning".to_string());
+        full.summaryOfNewsArticle = "The City Council approved the budget after the Mayor signed off.".to_string();
+        full.namedEntities.push(entity("Mayor"));
+        full.namedEntities.push(entity("Treasury"));
+
+        let mut talks = article(Some("https://lite.cnn.com/talks"), "Talks resume", "World News");
+        talks.timeOfPublication = String::new();
+        talks.keyTakeAways = vec!["Both sides returned".to_string(), "No deadline set".to_string()];
+        talks.importantTimeframes = vec![ImportantTimeframe {
+            approximateTimeFrameStart: "2025-05".to_string(),
+            approximateTimeFrameEnd: "2025-06".to_string(),
+            descriptionOfWhyTimeFrameIsRelevant: "Negotiation window".to_string(),
+        }];
+        let mut repeated = article(Some("https://lite.cnn.com/talks"), "Talks resume, again", "World News");
+        repeated.tags = vec!["diplomacy".to_string(), "talks".to_string()];
+        let sourceless = article(None, "Wire brief", "Politics & Governance");
+        let mut carried = article(Some("https://www.bbc.com/news/late"), "Late night vote", "Politics & Governance");
+        carried.carriedOver = true;
+        carried.importantDates = vec![ImportantDate {
+            dateMentionedInArticle: "2025-05-05".to_string(),
+            descriptionOfWhyDateIsRelevant: "Vote held".to_string(),
+        }];
+        let mut extra = article(Some("https://apnews.com/article/extra"), "Extra story", "World News");
+        extra.overflow = true;
+        let mut extra_sourceless = article(None, "Extra brief", "World News");
+        extra_sourceless.overflow = true;
+
+        FrontPage {
+            articles: vec![full, talks, sourceless, repeated, carried, extra, extra_sourceless],
+            ..test_support::front_page("evening", "20:00:00")
+        }
+    }
+
+    const GOLDEN_FRONT_PAGE: &str = include_str!("testdata/markdown/front_page.md");
+    const GOLDEN_FRONT_PAGE_DETAILS: &str = include_str!("testdata/markdown/front_page_details.md");
+    const GOLDEN_HEADER: &str = include_str!("testdata/markdown/header.md");
+    const GOLDEN_CATEGORY_SECTION: &str = include_str!("testdata/markdown/category_section.md");
+    const GOLDEN_ARTICLE: &str = include_str!("testdata/markdown/article.md");
+    const GOLDEN_ENTITIES: &str = include_str!("testdata/markdown/entities.md");
+    const GOLDEN_DATES: &str = include_str!("testdata/markdown/dates.md");
+    const GOLDEN_FOOTER: &str = include_str!("testdata/markdown/footer.md");
+    const GOLDEN_SINCE_LAST_EDITION: &str = include_str!("testdata/markdown/since_last_edition.md");
+    const GOLDEN_WEEKLY: &str = include_str!("testdata/markdown/weekly.md");
+
+    #[test]
+    fn test_front_page_golden() {
+        let frontpage = golden_front_page();
+        assert_eq!(front_page_to_markdown(&frontpage, &MarkdownOptions::default()), GOLDEN_FRONT_PAGE);
+
+        let options = MarkdownOptions {
+            anchors: AnchorMode::Both,
+            entities: EntityStyle::Footnotes,
+            theme: MarkdownTheme::Details,
+            labels: SourceLabels::new(&[]),
+            ..Default::default()
+        };
+        assert_eq!(front_page_to_markdown(&frontpage, &options), GOLDEN_FRONT_PAGE_DETAILS);
+    }
+
+    #[test]
+    fn test_header_golden() {
+        let frontpage = FrontPage {
+            articles: vec![],
+            ..golden_front_page()
+        };
+        let options = MarkdownOptions {
+            empty_message: "  Quiet news day.  ".to_string(),
+            ..Default::default()
+        };
+        let mut md = String::new();
+        render_header(&mut md, &frontpage, &options);
+        assert_eq!(md, GOLDEN_HEADER);
+    }
+
+    #[test]
+    fn test_category_section_golden() {
+        let frontpage = golden_front_page();
+        let options = MarkdownOptions::default();
+        let ids = EditionAnchors::new(&frontpage.articles);
+        let style = Style { options: &options, ids: &ids, corrections: &[] };
+        let (category, articles) = group_by_category(&frontpage.articles).remove(1);
+        let mut md = String::new();
+        render_category_section(&mut md, category, &articles, style);
+        assert_eq!(md, GOLDEN_CATEGORY_SECTION);
+    }
+
+    #[test]
+    fn test_article_golden() {
+        let frontpage = golden_front_page();
+        let options = MarkdownOptions {
+            entities: EntityStyle::Footnotes,
+            ..Default::default()
+        };
+        let ids = EditionAnchors::new(&frontpage.articles);
+        let mut md = String::new();
+        render_article(&mut md, &frontpage.articles[0], Style { options: &options, ids: &ids, corrections: &[] });
+        assert_eq!(md, GOLDEN_ARTICLE);
+    }
+
+    #[test]
+    fn test_entities_golden() {
+        let article = &golden_front_page().articles[0];
+        let entities: Vec<&NamedEntity> = article.namedEntities.iter().collect();
+        let mut md = String::new();
+        render_entities(&mut md, &entities, MarkdownTheme::Headings);
+        render_entities(&mut md, &[], MarkdownTheme::Headings);
+        render_entities(&mut md, &entities[..1], MarkdownTheme::Details);
+        assert_eq!(md, GOLDEN_ENTITIES);
+    }
+
+    #[test]
+    fn test_dates_golden() {
+        let frontpage = golden_front_page();
+        let mut md = String::new();
+        render_dates(&mut md, &frontpage.articles[0], MarkdownTheme::Headings);
+        render_dates(&mut md, &frontpage.articles[2], MarkdownTheme::Headings);
+        render_dates(&mut md, &frontpage.articles[1], MarkdownTheme::Details);
+        assert_eq!(md, GOLDEN_DATES);
+    }
+
+    #[test]
+    fn test_footer_golden() {
+        let frontpage = golden_front_page();
+        let options = MarkdownOptions {
+            labels: SourceLabels::new(&[]),
+            ..Default::default()
+        };
+        let ids = EditionAnchors::new(&frontpage.articles);
+        let mut md = String::new();
+        render_footer(&mut md, &frontpage, Style { options: &options, ids: &ids, corrections: &[] });
+        assert_eq!(md, GOLDEN_FOOTER);
+    }
+
+    #[test]
+    fn test_since_last_edition_golden() {
+        let story = |title: &str, source: Option<&str>| StoryRef {
+            title: title.to_string(),
+            source: source.map(str::to_string),
+        };
+        let mut frontpage = golden_front_page();
+        frontpage.diff = Some(EditionDiff {
+            previous_edition: "afternoon".to_string(),
+            new: vec![story("Wire brief", None), story("Extra story", Some("https://apnews.com/article/extra"))],
+            updated: vec![story("Council approves budget", Some("https://text.npr.org/nx-7"))],
+            dropped: vec![story("Bridge reopens", Some("https://text.npr.org/nx-2")), story("Wire digest", None)],
+        });
+        let options = MarkdownOptions::default();
+        let ids = EditionAnchors::new(&frontpage.articles);
+        let mut md = String::new();
+        render_since_last_edition(&mut md, &frontpage, frontpage.diff.as_ref().unwrap(), Style { options: &options, ids: &ids, corrections: &[] });
+        assert_eq!(md, GOLDEN_SINCE_LAST_EDITION);
+
+        // The section opens the edition, and only when enabled
+        let md = front_page_to_markdown(&frontpage, &options);
+        assert!(md.starts_with(&format!("# Awful Times\n\n#### Edition published at 20:00:00\n\n{}", GOLDEN_SINCE_LAST_EDITION)));
+        let hidden = MarkdownOptions {
+            show_diff: false,
+            ..Default::default()
+        };
+        assert_eq!(front_page_to_markdown(&frontpage, &hidden), GOLDEN_FRONT_PAGE);
+
+        frontpage.diff = Some(EditionDiff {
+            previous_edition: "morning".to_string(),
+            ..Default::default()
+        });
+        let md = front_page_to_markdown(&frontpage, &options);
+        assert!(md.contains("# Since the last edition\n\n_Nothing changed since the Morning edition._\n\n# Politics"));
+    }
+
+    #[test]
+    fn test_top_stories_block() {
+        let mut frontpage = golden_front_page();
+        let featured = |article: &AwfulNewsArticle, score: usize| FeaturedStory {
+            title: article.title.clone(),
+            source: article.source.clone(),
+            category: article.category.clone(),
+            score,
+        };
+        frontpage.top = vec![featured(&frontpage.articles[2], 4), featured(&frontpage.articles[1], 2)];
+        let options = MarkdownOptions::default();
+        let ids = EditionAnchors::new(&frontpage.articles);
+        let mut md = String::new();
+        render_top_stories(&mut md, &frontpage, Style { options: &options, ids: &ids, corrections: &[] });
+        let block = format!(
+            "# Top Stories\n\n1. [Wire brief](#{})\n   Wire brief summary.\n2. [Talks resume](#{}) - <small>`cnn`</small>\n   Talks resume summary.\n\n",
+            ids.id(&frontpage.articles[2]),
+            ids.id(&frontpage.articles[1])
+        );
+        assert_eq!(md, block);
+
+        // Above the category sections, which still list the featured articles in full
+        let md = front_page_to_markdown(&frontpage, &options);
+        assert!(md.starts_with(&format!("# Awful Times\n\n#### Edition published at 20:00:00\n\n{}", block)));
+        assert_eq!(md.replacen(&block, "", 1), GOLDEN_FRONT_PAGE);
+    }
+
+    #[test]
+    fn test_weekly_golden() {
+        let story = |title: &str, category: &str, source: Option<&str>, coverage: usize| WeeklyStory {
+            title: title.to_string(),
+            category: category.to_string(),
+            source: source.map(str::to_string),
+            summary: format!("{} summary.", title),
+            coverage,
+            shared_entities: 0,
+            score: 3 * coverage,
+            edition_file: "2025-05-09_evening.md".to_string(),
+            anchor: "a-3f9c2d".to_string(),
+        };
+        let mut weekly = WeeklyEdition {
+            week_start: "2025-05-05".to_string(),
+            week_end: "2025-05-11".to_string(),
+            days_covered: ["2025-05-05", "2025-05-06", "2025-05-07", "2025-05-09", "2025-05-10"]
+                .map(str::to_string)
+                .to_vec(),
+            overview: Some("A week of stalled talks and a successful landing.".to_string()),
+            stories: vec![
+                story("Talks stall", "Politics & Governance", Some("https://lite.cnn.com/talks"), 4),
+                story("Wire brief", "Politics & Governance", None, 1),
+                story("Rover lands", "Science & Technology", Some("https://text.npr.org/rover"), 2),
+            ],
+        };
+        let options = MarkdownOptions {
+            labels: SourceLabels::new(&[]),
+            ..Default::default()
+        };
+        assert_eq!(weekly_to_markdown(&weekly, &options), GOLDEN_WEEKLY);
+
+        weekly.days_covered.clear();
+        weekly.stories.clear();
+        assert_eq!(
+            weekly_to_markdown(&weekly, &options),
+            "# Week in Review\n\n#### 2025-05-05 to 2025-05-11\n\n_No editions were published this week._\n\n"
+        );
+    }
+
+    #[test]
+    fn test_options_drop_disabled_blocks() {
+        let frontpage = golden_front_page();
+        let options = MarkdownOptions {
+            show_takeaways: false,
+            show_entities: false,
+            show_dates: false,
+            show_footer: false,
+            ..Default::default()
+        };
+        let md = front_page_to_markdown(&frontpage, &options);
+        assert!(md.contains("## Council approves budget"));
+        assert!(md.contains("### Summary"));
+        for dropped in ["Key Takeaways", "Named Entities", "Important Dates", "Important Timeframes", "In case you missed it", "Also today", "[^"] {
+            assert!(!md.contains(dropped), "{dropped} should be dropped");
+        }
+
+        let options = MarkdownOptions {
+            entities: EntityStyle::Footnotes,
+            show_entities: false,
+            ..Default::default()
+        };
+        let md = front_page_to_markdown(&frontpage, &options);
+        assert!(md.contains("The City Council approved the budget after the Mayor signed off."));
+        assert!(!md.contains("[^"));
     }
 }
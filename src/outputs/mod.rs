@@ -7,7 +7,14 @@
 //!
 //! - [`json`]: Writes `FrontPage` data to JSON files for API consumption
 //! - [`markdown`]: Converts `FrontPage` to Markdown format for reading
+//! - [`book`]: mdBook layout of the Markdown output directory (`--scaffold-book`)
 //! - [`indexes`]: Updates various index files for navigation (TOC, SUMMARY.md, etc.)
+//! - [`journal`]: Summarized articles streamed to NDJSON as they complete (`--journal`)
+//! - [`labels`]: Display labels for source tags (`--source-labels`)
+//! - [`partial`]: Edition Markdown appended as articles complete (`--incremental-markdown`)
+//! - [`plaintext`]: Converts `FrontPage` to wrapped plain text and gemtext for mirrors
+//! - [`raw`]: Dumps scraped articles before LLM processing (`--raw-dump-dir`)
+//! - [`sink`]: Where output files are stored ([`sink::OutputSink`])
 //!
 //! # Output Structure
 //!
@@ -25,6 +32,13 @@
 //! └── SUMMARY.md             # mdBook navigation
 //! ```
 
+pub mod book;
 pub mod indexes;
+pub mod journal;
 pub mod json;
+pub mod labels;
 pub mod markdown;
+pub mod partial;
+pub mod plaintext;
+pub mod raw;
+pub mod sink;
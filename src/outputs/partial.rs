@@ -0,0 +1,126 @@
+//! Edition Markdown written as articles complete (`--incremental-markdown`).
+//!
+//! Nothing of an edition is written until every article is summarized, so a
+//! crash late in a long run loses all of it. With `--incremental-markdown`,
+//! `{markdown_output_dir}/{date}_{edition}.md.partial` is started with the
+//! edition header before processing, and each article is appended in
+//! completion order as soon as the model is done with it. Every append is
+//! flushed whole, so the file is readable Markdown whenever the run dies.
+//!
+//! The partial file holds articles as summarized, before `--region`,
+//! deduplication and the other edition filters. Once the full edition
+//! Markdown is written the partial file is removed; one left behind is from
+//! a run that didn't get that far, and the next run of the same edition
+//! starts it over.
+//!
+//! Like mirrors, the partial file is local scratch and doesn't go through
+//! the output sink. Writing it is best effort: failures are logged and never
+//! abort the run.
+
+use crate::models::{AwfulNewsArticle, FrontPage};
+use crate::outputs::markdown::{article_to_markdown, partial_header, MarkdownOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, File};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+/// The partial Markdown of an edition being processed.
+#[derive(Debug)]
+pub struct PartialMarkdown {
+    path: PathBuf,
+    /// Appends from concurrent completions are serialized on this.
+    file: Mutex<File>,
+    options: MarkdownOptions,
+}
+
+impl PartialMarkdown {
+    /// Start the partial file of `front_page` under `markdown_output_dir`,
+    /// replacing any left by an earlier run.
+    pub async fn create(markdown_output_dir: &str, front_page: &FrontPage, options: MarkdownOptions) -> io::Result<Self> {
+        let path = Path::new(markdown_output_dir).join(format!("{}.md.partial", front_page.edition_stem()));
+        fs::create_dir_all(markdown_output_dir).await?;
+        let mut file = File::create(&path).await?;
+        file.write_all(partial_header(front_page).as_bytes()).await?;
+        file.flush().await?;
+        Ok(PartialMarkdown {
+            path,
+            file: Mutex::new(file),
+            options,
+        })
+    }
+
+    /// Where the partial file is written.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append `article` to the partial file.
+    pub async fn append(&self, article: &AwfulNewsArticle) {
+        let md = article_to_markdown(article, &self.options);
+        let mut file = self.file.lock().await;
+        let written = async {
+            file.write_all(md.as_bytes()).await?;
+            file.flush().await
+        };
+        if let Err(e) = written.await {
+            warn!(path = %self.path.display(), title = %article.title, error = %e, "Failed appending to the partial Markdown");
+        }
+    }
+
+    /// Remove the partial file once the edition has been written in full.
+    pub async fn finish(self) {
+        drop(self.file);
+        if let Err(e) = fs::remove_file(&self.path).await {
+            warn!(path = %self.path.display(), error = %e, "Failed removing the partial Markdown");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, canned_article};
+
+    fn article(title: &str, source: &str) -> AwfulNewsArticle {
+        let mut article: AwfulNewsArticle = serde_json::from_value(canned_article(title, "World News")).unwrap();
+        article.source = Some(source.to_string());
+        article
+    }
+
+    #[tokio::test]
+    async fn test_partial_completion_leaves_readable_markdown() {
+        let dir = tempfile::tempdir().unwrap();
+        let md_dir = dir.path().join("md");
+        let md_dir = md_dir.to_str().unwrap();
+        let page = FrontPage {
+            articles: vec![
+                article("Talks resume", "https://lite.cnn.com/talks"),
+                article("Probe launched", "https://text.npr.org/probe"),
+            ],
+            ..test_support::front_page("morning", "08:00:00")
+        };
+
+        let partial = PartialMarkdown::create(md_dir, &page, MarkdownOptions::default()).await.unwrap();
+        assert_eq!(partial.path(), Path::new(md_dir).join("2025-05-06_morning.md.partial"));
+        partial.append(&page.articles[1]).await;
+        partial.append(&page.articles[0]).await;
+
+        // The run dies here: what's on disk is the header and whole articles
+        let md = std::fs::read_to_string(partial.path()).unwrap();
+        assert!(md.starts_with("# Awful Times\n\n#### Edition in progress, started at "));
+        let second = md.find(&page.articles[1].title).unwrap();
+        let first = md.find(&page.articles[0].title).unwrap();
+        assert!(second < first);
+        assert!(md.ends_with("\n\n"));
+        assert_eq!(md.matches(&page.articles[0].anchor_id()).count(), 1);
+
+        // A rerun starts over
+        let rerun = PartialMarkdown::create(md_dir, &page, MarkdownOptions::default()).await.unwrap();
+        assert!(!std::fs::read_to_string(rerun.path()).unwrap().contains(&page.articles[1].title));
+        let path = rerun.path().to_path_buf();
+        rerun.finish().await;
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,452 @@
+//! Plain-text and gemtext renditions of an edition.
+//!
+//! These formats feed the gopher and gemini mirrors, where Markdown syntax
+//! would show up as noise.
+//!
+//! # Plain Text
+//!
+//! [`front_page_to_text`] hard-wraps every paragraph to a fixed column width
+//! (72 by default). Headings are underlined with `=` (categories) or `-`
+//! (articles), key takeaways are numbered, and source links are bare URLs.
+//! Wrapping and underlines are measured in terminal columns, so wide CJK
+//! characters and emoji are accounted for.
+//!
+//! # Gemtext
+//!
+//! [`front_page_to_gemtext`] uses gemtext line types: `#`/`##`/`###`
+//! headings, `* ` list items and `=> url label` links. Gemini clients wrap
+//! text themselves, so paragraphs are left unwrapped.
+//!
+//! An edition without articles shows `--empty-edition-message` in both
+//! formats, as the Markdown edition does.
+
+use crate::models::{AwfulNewsArticle, FrontPage};
+use crate::ordering::group_by_category;
+use crate::utils::upcase;
+use std::fmt::Write;
+use textwrap::{Options, WrapAlgorithm};
+use tracing::{debug, instrument};
+use unicode_width::UnicodeWidthStr;
+
+/// Default line width for the plain-text edition.
+pub const DEFAULT_TEXT_WIDTH: usize = 72;
+
+/// Append `text` wrapped to `width` columns with the given indents.
+fn push_wrapped(out: &mut String, text: &str, width: usize, initial: &str, subsequent: &str) {
+    let options = Options::new(width.max(1))
+        .initial_indent(initial)
+        .subsequent_indent(subsequent)
+        .wrap_algorithm(WrapAlgorithm::FirstFit);
+    for line in textwrap::wrap(text.trim(), options) {
+        writeln!(out, "{}", line.trim_end()).unwrap();
+    }
+}
+
+/// Append a heading underlined with `underline`, measured in columns.
+fn push_heading(out: &mut String, heading: &str, underline: char, width: usize) {
+    push_wrapped(out, heading, width, "", "");
+    let rule = heading.width().clamp(1, width.max(1));
+    writeln!(out, "{}", underline.to_string().repeat(rule)).unwrap();
+}
+
+fn tag_suffix(article: &AwfulNewsArticle) -> String {
+    article
+        .source_tag()
+        .map(|tag| format!(" [{}]", tag))
+        .unwrap_or_default()
+}
+
+fn published_line(article: &AwfulNewsArticle) -> String {
+    format!("{} {}", article.dateOfPublication, article.timeOfPublication)
+        .trim()
+        .to_string()
+}
+
+fn article_to_text(out: &mut String, article: &AwfulNewsArticle, width: usize) {
+    push_heading(out, &format!("{}{}", article.title, tag_suffix(article)), '-', width);
+    push_wrapped(out, &format!("Published: {}", published_line(article)), width, "", "  ");
+    if let Some(edition) = &article.updateOf {
+        push_wrapped(out, &format!("Updated since the {} edition", upcase(edition)), width, "", "");
+    }
+    if !article.tags.is_empty() {
+        push_wrapped(out, &format!("Tags: {}", article.tags.join(", ")), width, "", "  ");
+    }
+    if let Some(source) = &article.source {
+        writeln!(out, "{}", source).unwrap();
+    }
+    writeln!(out).unwrap();
+
+    push_wrapped(out, &article.summaryOfNewsArticle, width, "", "");
+    writeln!(out).unwrap();
+
+    if !article.keyTakeAways.is_empty() {
+        writeln!(out, "Key takeaways:").unwrap();
+        let digits = article.keyTakeAways.len().to_string().len();
+        let hanging = " ".repeat(digits + 4);
+        for (i, takeaway) in article.keyTakeAways.iter().enumerate() {
+            let number = format!("  {:>digits$}. ", i + 1);
+            push_wrapped(out, takeaway, width, &number, &hanging);
+        }
+        writeln!(out).unwrap();
+    }
+
+    if !article.namedEntities.is_empty() {
+        writeln!(out, "Named entities:").unwrap();
+        for entity in &article.namedEntities {
+            let line = format!(
+                "{}: {} {}",
+                entity.name, entity.whatIsThisEntity, entity.whyIsThisEntityRelevantToTheArticle
+            );
+            push_wrapped(out, &line, width, "  - ", "    ");
+        }
+        writeln!(out).unwrap();
+    }
+
+    if !article.importantDates.is_empty() {
+        writeln!(out, "Important dates:").unwrap();
+        for date in &article.importantDates {
+            let line = format!("{}: {}", date.dateMentionedInArticle, date.descriptionOfWhyDateIsRelevant);
+            push_wrapped(out, &line, width, "  - ", "    ");
+        }
+        writeln!(out).unwrap();
+    }
+
+    if !article.importantTimeframes.is_empty() {
+        writeln!(out, "Important timeframes:").unwrap();
+        for timeframe in &article.importantTimeframes {
+            let line = format!(
+                "From {} to {}: {}",
+                timeframe.approximateTimeFrameStart,
+                timeframe.approximateTimeFrameEnd,
+                timeframe.descriptionOfWhyTimeFrameIsRelevant
+            );
+            push_wrapped(out, &line, width, "  - ", "    ");
+        }
+        writeln!(out).unwrap();
+    }
+}
+
+/// Convert a [`FrontPage`] to hard-wrapped plain text.
+///
+/// # Arguments
+///
+/// * `front_page` - The processed articles to render
+/// * `width` - Maximum line width in terminal columns
+/// * `empty_message` - Text shown when the edition has no articles
+///
+/// Bare URLs longer than `width` are kept on one line rather than broken.
+#[instrument(level = "debug", skip(front_page, empty_message))]
+pub fn front_page_to_text(front_page: &FrontPage, width: usize, empty_message: &str) -> String {
+    let mut out = String::new();
+
+    push_heading(&mut out, "Awful Times", '=', width);
+    writeln!(out).unwrap();
+    push_wrapped(
+        &mut out,
+        &format!(
+            "{} edition, {} {}",
+            upcase(&front_page.time_of_day),
+            front_page.local_date,
+            front_page.local_time
+        ),
+        width,
+        "",
+        "",
+    );
+    writeln!(out).unwrap();
+
+    if front_page.articles.is_empty() {
+        push_wrapped(&mut out, empty_message, width, "", "");
+        writeln!(out).unwrap();
+    }
+
+    for (category, articles) in group_by_category(&front_page.articles) {
+        writeln!(out).unwrap();
+        push_heading(&mut out, category, '=', width);
+        writeln!(out).unwrap();
+        for article in articles {
+            article_to_text(&mut out, article, width);
+        }
+    }
+
+    let carried: Vec<_> = front_page.articles.iter().filter(|a| a.carriedOver).collect();
+    if !carried.is_empty() {
+        writeln!(out).unwrap();
+        push_heading(&mut out, "In case you missed it", '=', width);
+        writeln!(out).unwrap();
+        for article in carried {
+            article_to_text(&mut out, article, width);
+        }
+    }
+
+    let overflow: Vec<_> = front_page.articles.iter().filter(|a| a.overflow).collect();
+    if !overflow.is_empty() {
+        writeln!(out).unwrap();
+        push_heading(&mut out, "Also today", '=', width);
+        writeln!(out).unwrap();
+        for article in overflow {
+            push_wrapped(&mut out, &format!("{}{}", article.title, tag_suffix(article)), width, "- ", "  ");
+            if let Some(source) = &article.source {
+                writeln!(out, "  {}", source).unwrap();
+            }
+        }
+    }
+
+    debug!(chars = out.len(), "Rendered plain-text length");
+    out
+}
+
+fn article_to_gemtext(out: &mut String, article: &AwfulNewsArticle) {
+    writeln!(out, "### {}{}", article.title, tag_suffix(article)).unwrap();
+    if let Some(source) = &article.source {
+        writeln!(out, "=> {} Source", source).unwrap();
+    }
+    writeln!(out, "Published: {}", published_line(article)).unwrap();
+    if let Some(edition) = &article.updateOf {
+        writeln!(out, "Updated since the {} edition", upcase(edition)).unwrap();
+    }
+    if !article.tags.is_empty() {
+        writeln!(out, "Tags: {}", article.tags.join(", ")).unwrap();
+    }
+    writeln!(out).unwrap();
+    writeln!(out, "{}", article.summaryOfNewsArticle.trim()).unwrap();
+    writeln!(out).unwrap();
+
+    if !article.keyTakeAways.is_empty() {
+        writeln!(out, "Key takeaways:").unwrap();
+        for (i, takeaway) in article.keyTakeAways.iter().enumerate() {
+            writeln!(out, "* {}. {}", i + 1, takeaway).unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+
+    if !article.namedEntities.is_empty() {
+        writeln!(out, "Named entities:").unwrap();
+        for entity in &article.namedEntities {
+            writeln!(
+                out,
+                "* {}: {} {}",
+                entity.name, entity.whatIsThisEntity, entity.whyIsThisEntityRelevantToTheArticle
+            )
+            .unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+
+    if !article.importantDates.is_empty() {
+        writeln!(out, "Important dates:").unwrap();
+        for date in &article.importantDates {
+            writeln!(out, "* {}: {}", date.dateMentionedInArticle, date.descriptionOfWhyDateIsRelevant).unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+
+    if !article.importantTimeframes.is_empty() {
+        writeln!(out, "Important timeframes:").unwrap();
+        for timeframe in &article.importantTimeframes {
+            writeln!(
+                out,
+                "* From {} to {}: {}",
+                timeframe.approximateTimeFrameStart,
+                timeframe.approximateTimeFrameEnd,
+                timeframe.descriptionOfWhyTimeFrameIsRelevant
+            )
+            .unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+}
+
+/// Convert a [`FrontPage`] to gemtext for the gemini mirror, with
+/// `empty_message` shown when it has no articles.
+#[instrument(level = "debug", skip_all)]
+pub fn front_page_to_gemtext(front_page: &FrontPage, empty_message: &str) -> String {
+    let mut out = String::new();
+
+    writeln!(out, "# Awful Times").unwrap();
+    writeln!(
+        out,
+        "{} edition, {} {}",
+        upcase(&front_page.time_of_day),
+        front_page.local_date,
+        front_page.local_time
+    )
+    .unwrap();
+    writeln!(out).unwrap();
+
+    if front_page.articles.is_empty() {
+        writeln!(out, "{}", empty_message).unwrap();
+    }
+
+    for (category, articles) in group_by_category(&front_page.articles) {
+        writeln!(out, "## {}", category).unwrap();
+        writeln!(out).unwrap();
+        for article in articles {
+            article_to_gemtext(&mut out, article);
+        }
+    }
+
+    let carried: Vec<_> = front_page.articles.iter().filter(|a| a.carriedOver).collect();
+    if !carried.is_empty() {
+        writeln!(out, "## In case you missed it").unwrap();
+        writeln!(out).unwrap();
+        for article in carried {
+            article_to_gemtext(&mut out, article);
+        }
+    }
+
+    let overflow: Vec<_> = front_page.articles.iter().filter(|a| a.overflow).collect();
+    if !overflow.is_empty() {
+        writeln!(out, "## Also today").unwrap();
+        writeln!(out).unwrap();
+        for article in overflow {
+            match &article.source {
+                Some(source) => writeln!(out, "=> {} {}{}", source, article.title, tag_suffix(article)).unwrap(),
+                None => writeln!(out, "* {}{}", article.title, tag_suffix(article)).unwrap(),
+            }
+        }
+    }
+
+    debug!(chars = out.len(), "Rendered gemtext length");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use crate::models::{ImportantDate, NamedEntity};
+
+    fn front_page() -> FrontPage {
+        let article = |title: &str, overflow: bool| AwfulNewsArticle {
+            source: Some(format!("https://text.npr.org/{}", title.len())),
+            timeOfPublication: "12:00:00".to_string(),
+            summaryOfNewsArticle: "Researchers announced a battery chemistry that charges in minutes and \
+                                   survives thousands of cycles without measurable degradation."
+                .to_string(),
+            keyTakeAways: vec![
+                "Charging takes under ten minutes.".to_string(),
+                "The cells retained ninety-five percent capacity after three thousand cycles.".to_string(),
+            ],
+            namedEntities: vec![NamedEntity {
+                name: "東京大学".to_string(),
+                whatIsThisEntity: "A university in Tokyo.".to_string(),
+                whyIsThisEntityRelevantToTheArticle: "It led the study.".to_string(),
+                entityType: None,
+            }],
+            importantDates: vec![ImportantDate {
+                dateMentionedInArticle: "2025-06-01".to_string(),
+                descriptionOfWhyDateIsRelevant: "Pilot production begins.".to_string(),
+            }],
+            tags: vec!["batteries".to_string(), "energy".to_string()],
+            overflow,
+            ..test_support::article(title, "Science & Technology")
+        };
+
+        FrontPage {
+            articles: vec![article("Fast-charging battery unveiled", false), article("Rocket test delayed", true)],
+            ..test_support::front_page("evening", "20:30:00")
+        }
+    }
+
+    const GOLDEN_TEXT: &str = "\
+Awful Times
+===========
+
+Evening edition, 2025-05-06 20:30:00
+
+
+Science & Technology
+====================
+
+Fast-charging battery unveiled [npr]
+------------------------------------
+Published: 2025-05-06 12:00:00
+Tags: batteries, energy
+https://text.npr.org/30
+
+Researchers announced a battery chemistry that
+charges in minutes and survives thousands of
+cycles without measurable degradation.
+
+Key takeaways:
+  1. Charging takes under ten minutes.
+  2. The cells retained ninety-five percent
+     capacity after three thousand cycles.
+
+Named entities:
+  - 東京大学: A university in Tokyo. It led the
+    study.
+
+Important dates:
+  - 2025-06-01: Pilot production begins.
+
+
+Also today
+==========
+
+- Rocket test delayed [npr]
+  https://text.npr.org/19
+";
+
+    const GOLDEN_GEMTEXT: &str = "\
+# Awful Times
+Evening edition, 2025-05-06 20:30:00
+
+## Science & Technology
+
+### Fast-charging battery unveiled [npr]
+=> https://text.npr.org/30 Source
+Published: 2025-05-06 12:00:00
+Tags: batteries, energy
+
+Researchers announced a battery chemistry that charges in minutes and survives thousands of cycles without measurable degradation.
+
+Key takeaways:
+* 1. Charging takes under ten minutes.
+* 2. The cells retained ninety-five percent capacity after three thousand cycles.
+
+Named entities:
+* 東京大学: A university in Tokyo. It led the study.
+
+Important dates:
+* 2025-06-01: Pilot production begins.
+
+## Also today
+
+=> https://text.npr.org/19 Rocket test delayed [npr]
+";
+
+    #[test]
+    fn test_plaintext_golden() {
+        assert_eq!(front_page_to_text(&front_page(), 48, ""), GOLDEN_TEXT);
+    }
+
+    #[test]
+    fn test_gemtext_golden() {
+        assert_eq!(front_page_to_gemtext(&front_page(), ""), GOLDEN_GEMTEXT);
+    }
+
+    #[test]
+    fn test_empty_edition_shows_the_message() {
+        let mut empty = front_page();
+        empty.articles.clear();
+        let message = "Nothing new this evening; the wires were quiet.";
+        assert_eq!(
+            front_page_to_text(&empty, 30, message),
+            "Awful Times\n===========\n\nEvening edition, 2025-05-06\n20:30:00\n\nNothing new this evening; the\nwires were quiet.\n\n"
+        );
+        assert_eq!(
+            front_page_to_gemtext(&empty, message),
+            format!("# Awful Times\nEvening edition, 2025-05-06 20:30:00\n\n{}\n", message)
+        );
+    }
+
+    #[test]
+    fn test_plaintext_respects_width_in_columns() {
+        let text = front_page_to_text(&front_page(), 48, "");
+        for line in text.lines().filter(|l| !l.starts_with("http") && !l.trim_start().starts_with("http")) {
+            assert!(line.width() <= 48, "line too wide: {line:?}");
+        }
+    }
+}
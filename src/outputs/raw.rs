@@ -0,0 +1,438 @@
+//! Raw dump of scraped articles before LLM processing.
+//!
+//! With `--raw-dump-dir`, every fetched [`NewsArticle`] is written to disk
+//! right after fetching, so the pre-LLM corpus is available for research and
+//! post-mortems even when processing later fails.
+//!
+//! # Output Structure
+//!
+//! ```text
+//! raw_dump_dir/
+//! └── 2025-05-06/
+//!     └── morning/
+//!         ├── manifest.json
+//!         ├── cnn/
+//!         │   └── 3f2a9c0d1e4b5a6f.json
+//!         └── npr/
+//!             └── 9b8c7d6e5f4a3b2c.json
+//! ```
+//!
+//! File names are derived from a hash of the article URL. With
+//! `--raw-dump-compress` each article file is gzipped (`.json.gz`); the
+//! manifest is always plain JSON.
+//!
+//! Article files deserialize directly into [`NewsArticle`] (extra fields
+//! are ignored), so a dump can be fed back into experiments offline.
+//!
+//! # Fetching and Processing Apart
+//!
+//! The `fetch` subcommand stops a run after the dump, and `process` starts
+//! one from a dump's edition directory (`raw_dump_dir/{date}/{edition}`),
+//! so scraping and the LLM can run on different machines. The manifest
+//! carries the edition decided at fetch time (date, time of day and local
+//! time) and what each scraper indexed and fetched, so an edition processed
+//! hours later is filed, and its `sources.json` written, as if the two
+//! halves had run in one go. Skips and de-duplication counts of the fetch
+//! stay in the fetching run's logs and skip report.
+//!
+//! Dumping is best effort: failures are logged as warnings and never abort
+//! the run. The exception is `fetch`, where the dump is the whole
+//! point of the run and a failure is returned as an error.
+
+use crate::models::{FrontPage, NewsArticle};
+use crate::outputs::json::SourceRun;
+use crate::scrapers::SCRAPERS;
+use crate::utils::content_hash;
+use chrono::{DateTime, FixedOffset, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::io::{Read, Write};
+use tokio::fs;
+use tracing::{info, instrument, warn};
+
+/// Tag used for articles whose URL has no recognizable outlet.
+const UNKNOWN_SOURCE_TAG: &str = "unknown";
+
+/// A single dumped article as written to disk.
+#[derive(Debug, Serialize)]
+struct RawArticle<'a> {
+    source: &'a str,
+    title: Option<String>,
+    content: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    published_at: Option<DateTime<FixedOffset>>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    authors: &'a [String],
+    /// When the page was downloaded, or else when the dump was written.
+    fetched_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fetched_from: Option<&'a str>,
+}
+
+/// One entry of the per-edition manifest.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RawManifestEntry {
+    /// The article URL.
+    pub source: String,
+    /// Outlet tag the file was filed under.
+    pub source_tag: String,
+    /// Title, when one could be recovered from the scraped content.
+    pub title: Option<String>,
+    /// Path of the article file relative to the edition directory.
+    pub file: String,
+    /// Hash of the article content (see [`content_hash`]).
+    pub content_hash: String,
+    /// Length of the scraped content in bytes.
+    pub content_bytes: usize,
+}
+
+/// Index of everything dumped for one edition.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RawManifest {
+    /// The date of the edition in `YYYY-MM-DD` format.
+    pub local_date: String,
+    /// The edition name (time of day).
+    pub time_of_day: String,
+    /// The local time the edition is stamped with; absent in dumps written
+    /// before editions were processed from them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_time: Option<String>,
+    /// When the dump was written (RFC 3339, UTC).
+    pub fetched_at: String,
+    /// Whether article files are gzipped.
+    pub compressed: bool,
+    /// What each scraper that ran indexed and fetched, by scraper name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sources: BTreeMap<String, SourceRun>,
+    /// The dumped articles.
+    pub articles: Vec<RawManifestEntry>,
+}
+
+impl RawManifest {
+    /// An empty edition stamped as decided at fetch time. Older dumps
+    /// without a local time get the local time the dump was written.
+    pub fn edition(&self) -> FrontPage {
+        let local_time = self.local_time.clone().unwrap_or_else(|| {
+            DateTime::parse_from_rfc3339(&self.fetched_at)
+                .map(|t| t.with_timezone(&chrono::Local).time().to_string())
+                .unwrap_or_default()
+        });
+        FrontPage {
+            local_date: self.local_date.clone(),
+            time_of_day: self.time_of_day.clone(),
+            local_time,
+            articles: Vec::new(),
+            diff: None,
+            top: vec![],
+            editorial: None,
+            corrections: vec![],
+        }
+    }
+
+    /// [`Self::sources`] keyed like a fetching run's (see [`SCRAPERS`]).
+    pub fn source_runs(&self) -> HashMap<&'static str, SourceRun> {
+        SCRAPERS
+            .iter()
+            .filter_map(|scraper| Some((scraper.name, *self.sources.get(scraper.name)?)))
+            .collect()
+    }
+}
+
+/// Recover a title from scraped content that starts with a `# Title` line.
+fn title_from_content(content: &str) -> Option<String> {
+    content
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .and_then(|line| line.trim().strip_prefix("# "))
+        .map(|title| title.trim().to_string())
+        .filter(|title| !title.is_empty())
+}
+
+fn gzip(bytes: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(bytes)?;
+    Ok(encoder.finish()?)
+}
+
+/// Write every article and the manifest of `edition` (its date, time of
+/// day and local time; articles are not read) under `raw_dump_dir`, with
+/// the scraper `runs` that fetched them.
+///
+/// Individual article files that fail to write are skipped with a warning;
+/// errors creating the edition directory or writing the manifest are
+/// returned.
+///
+/// # Returns
+///
+/// The manifest that was written.
+#[instrument(level = "info", skip(articles, edition, runs), fields(local_date = %edition.local_date, time_of_day = %edition.time_of_day))]
+pub async fn write_raw_dump(
+    articles: &[NewsArticle],
+    raw_dump_dir: &str,
+    edition: &FrontPage,
+    runs: &HashMap<&str, SourceRun>,
+    compress: bool,
+) -> Result<RawManifest, Box<dyn Error>> {
+    let edition_dir = format!("{}/{}/{}", raw_dump_dir, edition.local_date, edition.time_of_day);
+    fs::create_dir_all(&edition_dir).await?;
+
+    let fetched_at = Utc::now().to_rfc3339();
+    let mut manifest = RawManifest {
+        local_date: edition.local_date.clone(),
+        time_of_day: edition.time_of_day.clone(),
+        local_time: Some(edition.local_time.clone()),
+        fetched_at: fetched_at.clone(),
+        compressed: compress,
+        sources: runs.iter().map(|(name, run)| (name.to_string(), *run)).collect(),
+        articles: Vec::with_capacity(articles.len()),
+    };
+
+    for article in articles {
+        let tag = article.source_tag().unwrap_or_else(|| UNKNOWN_SOURCE_TAG.to_string());
+        let name = &content_hash(&article.source)[..16];
+        let file = if compress {
+            format!("{}/{}.json.gz", tag, name)
+        } else {
+            format!("{}/{}.json", tag, name)
+        };
+        let title = title_from_content(&article.content);
+
+        let record = RawArticle {
+            source: &article.source,
+            title: title.clone(),
+            content: &article.content,
+            published_at: article.published_at,
+            authors: &article.authors,
+            fetched_at: article.fetched_at.map_or_else(|| fetched_at.clone(), |t| t.to_rfc3339()),
+            fetched_from: article.fetched_from.as_deref(),
+        };
+        let json = serde_json::to_vec_pretty(&record)?;
+        let bytes = if compress { gzip(&json)? } else { json };
+
+        let path = format!("{}/{}", edition_dir, file);
+        let written = match fs::create_dir_all(format!("{}/{}", edition_dir, tag)).await {
+            Ok(()) => fs::write(&path, bytes).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = written {
+            warn!(%path, error = %e, "Failed to write raw article; skipping");
+            continue;
+        }
+
+        manifest.articles.push(RawManifestEntry {
+            source: article.source.clone(),
+            source_tag: tag,
+            title,
+            file,
+            content_hash: content_hash(&article.content),
+            content_bytes: article.content.len(),
+        });
+    }
+
+    let manifest_path = format!("{}/manifest.json", edition_dir);
+    fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?).await?;
+    info!(path = %manifest_path, articles = manifest.articles.len(), "Wrote raw article dump");
+
+    Ok(manifest)
+}
+
+/// Dump scraped articles, logging instead of failing.
+///
+/// # Returns
+///
+/// `true` if the dump was written, `false` if it failed (a warning is logged).
+pub async fn dump_raw_articles(
+    articles: &[NewsArticle],
+    raw_dump_dir: &str,
+    edition: &FrontPage,
+    runs: &HashMap<&str, SourceRun>,
+    compress: bool,
+) -> bool {
+    match write_raw_dump(articles, raw_dump_dir, edition, runs, compress).await {
+        Ok(_) => true,
+        Err(e) => {
+            warn!(%raw_dump_dir, error = %e, "Failed to write raw article dump; continuing");
+            false
+        }
+    }
+}
+
+/// Read back the manifest and articles of a dump's edition directory
+/// (`raw_dump_dir/{date}/{edition}`), in manifest order.
+///
+/// Article files that are missing or corrupt are skipped with a warning;
+/// a missing or corrupt manifest is an error.
+pub async fn read_raw_dump(edition_dir: &str) -> Result<(RawManifest, Vec<NewsArticle>), Box<dyn Error>> {
+    let manifest_path = format!("{}/manifest.json", edition_dir);
+    let manifest: RawManifest = serde_json::from_str(&fs::read_to_string(&manifest_path).await?)
+        .map_err(|e| format!("corrupt raw dump manifest {}: {}", manifest_path, e))?;
+
+    let mut articles = Vec::with_capacity(manifest.articles.len());
+    for entry in &manifest.articles {
+        let path = format!("{}/{}", edition_dir, entry.file);
+        let read = async {
+            let bytes = fs::read(&path).await?;
+            let json = if manifest.compressed {
+                let mut json = Vec::new();
+                GzDecoder::new(&bytes[..]).read_to_end(&mut json)?;
+                json
+            } else {
+                bytes
+            };
+            Ok::<NewsArticle, Box<dyn Error>>(serde_json::from_slice(&json)?)
+        };
+        match read.await {
+            Ok(article) => articles.push(article),
+            Err(e) => warn!(%path, error = %e, "Unreadable raw article; skipping"),
+        }
+    }
+    Ok((manifest, articles))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn edition(time_of_day: &str) -> FrontPage {
+        test_support::front_page(time_of_day, "08:00:00")
+    }
+
+    fn sample() -> Vec<NewsArticle> {
+        vec![
+            NewsArticle {
+                source: "https://lite.cnn.com/2025/05/06/a".to_string(),
+                content: "Plain body".into(),
+                published_at: None,
+                authors: vec![],
+                fetched_at: None,
+                fetched_from: None,
+            },
+            NewsArticle {
+                source: "https://www.nytimes.com/2025/05/06/b.html".to_string(),
+                content: "# Headline Here\n\nBody".into(),
+                published_at: DateTime::parse_from_rfc3339("2025-05-06T09:15:00-04:00").ok(),
+                authors: vec!["Jane Doe".to_string()],
+                fetched_at: None,
+                fetched_from: None,
+            },
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_raw_dump_layout_and_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_str().unwrap();
+
+        let manifest = write_raw_dump(&sample(), root, &edition("morning"), &HashMap::new(), false)
+            .await
+            .unwrap();
+
+        let edition = dir.path().join("2025-05-06/morning");
+        assert!(edition.join("manifest.json").is_file());
+        assert_eq!(manifest.articles.len(), 2);
+
+        let cnn = &manifest.articles[0];
+        assert_eq!(cnn.source_tag, "cnn");
+        assert!(cnn.file.starts_with("cnn/") && cnn.file.ends_with(".json"));
+        assert_eq!(cnn.title, None);
+        let on_disk: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(edition.join(&cnn.file)).unwrap()).unwrap();
+        assert_eq!(on_disk["content"], "Plain body");
+        assert!(on_disk["fetched_at"].is_string());
+
+        let nyt = &manifest.articles[1];
+        assert_eq!(nyt.source_tag, "nytimes");
+        assert_eq!(nyt.title.as_deref(), Some("Headline Here"));
+
+        let written: RawManifest =
+            serde_json::from_str(&std::fs::read_to_string(edition.join("manifest.json")).unwrap()).unwrap();
+        assert_eq!(written.articles.len(), 2);
+        assert!(!written.compressed);
+    }
+
+    #[tokio::test]
+    async fn test_raw_dump_compressed() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_str().unwrap();
+
+        let manifest = write_raw_dump(&sample(), root, &edition("evening"), &HashMap::new(), true)
+            .await
+            .unwrap();
+
+        let file = &manifest.articles[0].file;
+        assert!(file.ends_with(".json.gz"));
+        let gz = std::fs::read(dir.path().join("2025-05-06/evening").join(file)).unwrap();
+        let mut json = String::new();
+        GzDecoder::new(&gz[..]).read_to_string(&mut json).unwrap();
+        assert!(json.contains("Plain body"));
+
+        let (_, articles) = read_raw_dump(&format!("{}/2025-05-06/evening", root)).await.unwrap();
+        assert_eq!(&*articles[0].content, "Plain body");
+    }
+
+    #[tokio::test]
+    async fn test_raw_dump_failure_only_warns() {
+        // A regular file where the dump directory should be makes
+        // create_dir_all fail regardless of permissions.
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let root = file.path().to_str().unwrap();
+
+        assert!(!dump_raw_articles(&sample(), root, &edition("morning"), &HashMap::new(), false).await);
+    }
+
+    #[tokio::test]
+    async fn test_dumped_files_round_trip_to_news_article() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_str().unwrap();
+        let articles = sample();
+
+        let manifest = write_raw_dump(&articles, root, &edition("morning"), &HashMap::new(), false)
+            .await
+            .unwrap();
+
+        let edition = dir.path().join("2025-05-06/morning");
+        for (entry, original) in manifest.articles.iter().zip(&articles) {
+            let raw = std::fs::read_to_string(edition.join(&entry.file)).unwrap();
+            let restored: NewsArticle = serde_json::from_str(&raw).unwrap();
+            assert_eq!(restored.source, original.source);
+            assert_eq!(restored.content, original.content);
+            assert_eq!(restored.published_at, original.published_at);
+            assert_eq!(restored.authors, original.authors);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_raw_dump_restores_edition_and_articles() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_str().unwrap();
+        let mut articles = sample();
+        articles[0].fetched_at = DateTime::parse_from_rfc3339("2025-05-06T07:59:30.125Z").ok().map(|t| t.to_utc());
+        articles[0].fetched_from = Some("https://lite.cnn.com/2025/05/06/a?utm_source=rss".to_string());
+        let runs = HashMap::from([("cnn", SourceRun { ran: true, indexed: 3, fetched: 1 })]);
+        let mut evening = edition("evening");
+        // Fetched before midnight, processed after: still the evening of the 6th
+        evening.local_time = "23:50:00".to_string();
+
+        write_raw_dump(&articles, root, &evening, &runs, false).await.unwrap();
+        let edition_dir = dir.path().join("2025-05-06/evening");
+        let (manifest, restored) = read_raw_dump(edition_dir.to_str().unwrap()).await.unwrap();
+
+        let page = manifest.edition();
+        assert_eq!((page.local_date.as_str(), page.time_of_day.as_str(), page.local_time.as_str()), ("2025-05-06", "evening", "23:50:00"));
+        assert_eq!(manifest.source_runs(), HashMap::from([("cnn", runs["cnn"])]));
+        assert_eq!(restored.len(), 2);
+        assert_eq!(restored[0].fetched_at, articles[0].fetched_at);
+        assert_eq!(restored[0].fetched_from, articles[0].fetched_from);
+        assert!(restored[1].fetched_at.is_some());
+
+        // An article file gone missing is skipped, a missing manifest is an error
+        std::fs::remove_file(edition_dir.join(&manifest.articles[1].file)).unwrap();
+        assert_eq!(read_raw_dump(edition_dir.to_str().unwrap()).await.unwrap().1.len(), 1);
+        assert!(read_raw_dump(&format!("{}/2025-05-07/morning", root)).await.is_err());
+    }
+}
@@ -0,0 +1,189 @@
+//! Storage for output files.
+//!
+//! Edition files (JSON, Markdown, plain text, gemtext), the Markdown indexes
+//! and `sources.json` are written through an [`OutputSink`] instead of
+//! straight to disk, so another backend (an object store, say) can hold them
+//! by swapping the sink. Paths
+//! keep their `{output_dir}/...` form whatever the sink; a sink maps them to
+//! its own keys.
+//!
+//! [`FileSystem`] is the default sink. It creates missing parent directories
+//! and writes each file to a temporary name renamed into place, so readers
+//! see either the old file or the new one.
+//!
+//! Mirrors (`--json-mirror-dir`) and the startup writability checks work on
+//! the local filesystem and don't go through the sink.
+
+use crate::utils::write_atomic;
+use std::io;
+use std::path::Path;
+use tokio::fs;
+
+/// Where output files are stored.
+pub trait OutputSink {
+    /// Replace the file at `path` with `bytes`.
+    async fn write(&self, path: &str, bytes: &[u8]) -> io::Result<()>;
+
+    /// The contents of the file at `path`, or `None` if there is none.
+    async fn read(&self, path: &str) -> io::Result<Option<Vec<u8>>>;
+
+    /// Delete the file at `path`; a missing file is not an error.
+    async fn remove(&self, path: &str) -> io::Result<()>;
+
+    /// The file at `path` as UTF-8 text, or `None` if there is none.
+    async fn read_to_string(&self, path: &str) -> io::Result<Option<String>> {
+        match self.read(path).await? {
+            Some(bytes) => String::from_utf8(bytes)
+                .map(Some)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// The local filesystem.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileSystem;
+
+impl OutputSink for FileSystem {
+    async fn write(&self, path: &str, bytes: &[u8]) -> io::Result<()> {
+        if let Some(parent) = Path::new(path).parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent).await?;
+        }
+        write_atomic(path, bytes).await
+    }
+
+    async fn read(&self, path: &str) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(path).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn remove(&self, path: &str) -> io::Result<()> {
+        match fs::remove_file(path).await {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Sink keeping every file in memory, for tests.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct MemorySink {
+    pub files: std::sync::Mutex<std::collections::BTreeMap<String, Vec<u8>>>,
+}
+
+#[cfg(test)]
+impl MemorySink {
+    /// Paths written so far, sorted.
+    pub fn paths(&self) -> Vec<String> {
+        self.files.lock().unwrap().keys().cloned().collect()
+    }
+
+    /// A written file as text.
+    pub fn text(&self, path: &str) -> String {
+        String::from_utf8(self.files.lock().unwrap()[path].clone()).unwrap()
+    }
+}
+
+#[cfg(test)]
+impl OutputSink for MemorySink {
+    async fn write(&self, path: &str, bytes: &[u8]) -> io::Result<()> {
+        self.files.lock().unwrap().insert(path.to_string(), bytes.to_vec());
+        Ok(())
+    }
+
+    async fn read(&self, path: &str) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.files.lock().unwrap().get(path).cloned())
+    }
+
+    async fn remove(&self, path: &str) -> io::Result<()> {
+        self.files.lock().unwrap().remove(path);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use crate::models::{AwfulNewsArticle, FrontPage};
+    use crate::outputs::json::{write_category_files, write_frontpage, write_sources, ContentPolicy};
+    use crate::outputs::{indexes, markdown};
+
+    fn front_page() -> FrontPage {
+        FrontPage {
+            articles: vec![AwfulNewsArticle {
+                source: Some("https://text.npr.org/probe".to_string()),
+                summaryOfNewsArticle: "A probe launched.".to_string(),
+                ..test_support::article("New probe launched", "Science & Technology")
+            }],
+            ..test_support::front_page("morning", "08:00:00")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_outputs_are_written_through_the_sink() {
+        let sink = MemorySink::default();
+        let page = front_page();
+        // Nothing may touch this directory
+        let dir = tempfile::tempdir().unwrap();
+        let json_dir = dir.path().join("json");
+        let md_dir = dir.path().join("md");
+        let (json_dir, md_dir) = (json_dir.to_str().unwrap(), md_dir.to_str().unwrap());
+
+        let json_path = write_frontpage(&sink, &page, json_dir, ContentPolicy::Full, None, None).await.unwrap().remove(0);
+        write_category_files(&sink, &page, json_dir, ContentPolicy::Full).await.unwrap();
+        write_sources(&sink, json_dir, &Default::default()).await.unwrap();
+        let markdown_filename = format!("{}.md", page.edition_stem());
+        let md = markdown::front_page_to_markdown(&page, &Default::default());
+        sink.write(&format!("{}/{}", md_dir, markdown_filename), md.as_bytes()).await.unwrap();
+        indexes::update_edition_indexes(&sink, md_dir, &page, &markdown_filename, &Default::default()).await.unwrap();
+
+        let expected: Vec<String> = [
+            "json/2025-05-06/morning.json",
+            "json/2025-05-06/morning/science--technology.json",
+            "json/sources.json",
+            "md/2025-05-06.md",
+            "md/2025-05-06_morning.md",
+            "md/SUMMARY.md",
+            "md/daily_news.md",
+        ]
+        .iter()
+        .map(|file| format!("{}/{}", dir.path().display(), file))
+        .collect();
+        assert_eq!(sink.paths(), expected);
+        assert!(std::fs::read_dir(dir.path()).unwrap().next().is_none());
+
+        let written: FrontPage = serde_json::from_str(&sink.text(&json_path)).unwrap();
+        assert_eq!(written.articles[0].title, "New probe launched");
+        assert!(sink.text(&expected[6]).contains("- [**2025-05-06**](./2025-05-06.md)"));
+
+        // Indexes are read back through the sink: a second edition appends
+        let mut afternoon = front_page();
+        afternoon.time_of_day = "afternoon".to_string();
+        indexes::update_edition_indexes(&sink, md_dir, &afternoon, "2025-05-06_afternoon.md", &Default::default())
+            .await
+            .unwrap();
+        let toc = sink.text(&expected[3]);
+        assert_eq!(toc.matches("# Editions published on").count(), 1);
+        assert!(toc.contains("- [Morning (08:00:00)](./2025-05-06_morning.md)") && toc.contains("- [Afternoon (08:00:00)](./2025-05-06_afternoon.md)"));
+    }
+
+    #[tokio::test]
+    async fn test_file_system_sink_creates_parents_and_reads_missing_as_none() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a/b/file.json");
+        let path = path.to_str().unwrap();
+        assert_eq!(FileSystem.read(path).await.unwrap(), None);
+        FileSystem.write(path, b"{}").await.unwrap();
+        FileSystem.write(path, b"[]").await.unwrap();
+        assert_eq!(FileSystem.read_to_string(path).await.unwrap().as_deref(), Some("[]"));
+        assert!(!dir.path().join("a/b/file.json.tmp").exists());
+    }
+}
@@ -0,0 +1,689 @@
+//! LLM processing of a single scraped article.
+//!
+//! [`process_article`] asks the model to summarize one [`NewsArticle`],
+//! recovers from malformed responses where possible, and post-processes the
+//! result (source/content, publication grounding, de-duplication and capping
+//! of extracted lists). [`process_all`] runs it concurrently over the whole
+//! edition.
+//!
+//! # Run Deadline
+//!
+//! With `--max-run-time`, [`process_all`] stops sending articles to the model
+//! once the run's wall-clock budget is spent, and the deadline is hard:
+//! articles still in flight when it passes (including their retries) are
+//! dropped and recorded as [`SkipReason::Cancelled`]. The articles never
+//! sent are recorded as [`SkipReason::Deadline`], and the edition is written
+//! with what was done.
+//!
+//! # Model Input
+//!
+//! The article text is sent with a short header naming the outlet (and,
+//! with `--llm-input-header full`, the URL), so summaries can attribute
+//! reporting instead of saying "the network reported". See
+//! [`format_llm_input`]; the header is only part of the request, never of
+//! the stored `content`. The edition's date reaches the model through the
+//! template's placeholders (see [`render_template_vars`]), so "yesterday" in
+//! the article resolves against the right day.
+//!
+//! The stored `content` is the scraped text itself, not a copy: both
+//! articles hold the same `Arc<str>`, so a full edition keeps each article
+//! body in memory once.
+//!
+//! # Parse Recovery
+//!
+//! | Failure | Action |
+//! |---------|--------|
+//! | Truncated | Re-ask once (unless `--no-reask`), then try to repair |
+//! | Repairable | Repair the JSON locally (strip fences/prose, trailing commas) |
+//! | Malformed | Skip the article |
+//!
+//! The re-ask is a single attempt without retries ([`ask_once`]) unless
+//! `--reask-with-retries` is set, so a failing backend can't double the
+//! attempts spent on one article.
+
+use crate::api::{ask_once, ask_with_backoff, render_template_vars, AdaptiveConcurrency, BasicAuth, TemplateContext};
+use crate::concurrency::ModelConcurrency;
+use crate::models::{
+    normalize_timeframes, ArticleLimits, AwfulNewsArticle, ImportantDate, NamedEntity, NewsArticle, SkipReason,
+};
+use crate::outputs::journal::Journal;
+use crate::outputs::partial::PartialMarkdown;
+use crate::progress::Progress;
+use crate::schema::{self, ArticleField};
+use crate::scrapers::{self, outlet_name};
+use crate::skips::SkipLog;
+use crate::utils::{classify_parse_failure, repair_json, truncate_for_log, ParseFailureKind};
+use awful_aj::config::AwfulJadeConfig;
+use awful_aj::template::ChatTemplate;
+use clap::ValueEnum;
+use itertools::Itertools;
+use futures::stream::{self, StreamExt};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{debug, error, info, warn};
+
+/// Header put in front of the article text sent to the model.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum LlmInputHeader {
+    /// Outlet name and URL.
+    Full,
+    /// Outlet name only.
+    #[default]
+    Source,
+    /// The bare article text.
+    Off,
+}
+
+/// Build the text sent to the model for `article`.
+///
+/// With a header, the article text is preceded by a block like
+/// `SOURCE: BBC News\nURL: https://...\n\n`. Outlets without a display name
+/// fall back to their source tag. Without a header the article text is
+/// borrowed as is.
+pub fn format_llm_input(article: &NewsArticle, header: LlmInputHeader) -> Cow<'_, str> {
+    let outlet = || {
+        outlet_name(&article.source)
+            .map(str::to_string)
+            .or_else(|| article.source_tag())
+            .unwrap_or_else(|| "unknown".to_string())
+    };
+    match header {
+        LlmInputHeader::Full => format!("SOURCE: {}\nURL: {}\n\n{}", outlet(), article.source, article.content).into(),
+        LlmInputHeader::Source => format!("SOURCE: {}\n\n{}", outlet(), article.content).into(),
+        LlmInputHeader::Off => Cow::Borrowed(&article.content),
+    }
+}
+
+/// Everything [`process_article`] needs besides the article itself.
+pub struct ProcessContext<'a> {
+    /// LLM endpoint configuration.
+    pub config: &'a AwfulJadeConfig,
+    /// The `news_parser` template (possibly restricted by `--extract-fields`).
+    pub template: &'a ChatTemplate,
+    /// Edition date and label for the template placeholders; the source is
+    /// filled in per article.
+    pub template_vars: &'a TemplateContext,
+    /// Basic auth credentials for the LLM endpoint, if configured.
+    pub auth: Option<&'a BasicAuth>,
+    /// Earlier edition of each URL re-processed after an update.
+    pub update_of: &'a HashMap<String, String>,
+    /// URLs carried over from the previous run's failures.
+    pub carried_over: &'a HashSet<String>,
+    /// Optional fields the model was asked to extract.
+    pub extract_fields: &'a [ArticleField],
+    /// Whether a truncated response is asked again once.
+    pub reask: bool,
+    /// Whether the re-ask gets the full retry backoff instead of one attempt.
+    pub reask_with_retries: bool,
+    /// Header sent in front of the article text.
+    pub input_header: LlmInputHeader,
+    /// Caps on takeaways and tags.
+    pub limits: ArticleLimits,
+    /// Per-model limits on requests in flight.
+    pub concurrency: &'a ModelConcurrency,
+    /// Controller told the outcome of every request attempt.
+    pub adaptive: &'a AdaptiveConcurrency,
+    /// Progress indicator updated on re-asks.
+    pub progress: &'a Progress,
+    /// The run's record of articles that were skipped.
+    pub skips: &'a SkipLog,
+}
+
+/// Ask the model for a truncated article a second time.
+async fn reask(
+    ctx: &ProcessContext<'_>,
+    template: &ChatTemplate,
+    i: usize,
+    input: &str,
+) -> Option<serde_json::Result<AwfulNewsArticle>> {
+    ctx.progress.record_retry();
+    let response = {
+        let _permit = ctx.concurrency.acquire(&ctx.config.model).await;
+        if ctx.reask_with_retries {
+            ask_with_backoff(ctx.config, input, template, ctx.auth, ctx.adaptive).await
+        } else {
+            ask_once(ctx.config, input, template, ctx.auth, ctx.adaptive).await
+        }
+    };
+    match response {
+        Ok(r2) => {
+            let mut parsed = serde_json::from_str::<AwfulNewsArticle>(&r2);
+            if parsed.is_err()
+                && let Some(fixed) = repair_json(&r2)
+            {
+                parsed = serde_json::from_str::<AwfulNewsArticle>(&fixed);
+            }
+            Some(parsed)
+        }
+        Err(e2) => {
+            warn!(index = i, error = %e2, "Re-ask failed; will skip article");
+            None
+        }
+    }
+}
+
+/// Summarize one article with the LLM.
+///
+/// # Returns
+///
+/// The processed article, or `None` if the API call failed or the model's
+/// response could not be parsed (the reason is logged and recorded in
+/// `ctx.skips`).
+pub async fn process_article(ctx: &ProcessContext<'_>, i: usize, article: &NewsArticle) -> Option<AwfulNewsArticle> {
+    debug!(index = i, source = %article.source, "Analyzing article");
+
+    let template = render_template_vars(
+        ctx.template,
+        &TemplateContext {
+            source: article.source_tag().unwrap_or_else(|| article.source.clone()),
+            ..ctx.template_vars.clone()
+        },
+    );
+    let template = &template;
+    let input = format_llm_input(article, ctx.input_header);
+
+    // First ask, holding a request slot of the model
+    let response = {
+        let _permit = ctx.concurrency.acquire(&ctx.config.model).await;
+        ask_with_backoff(ctx.config, &input, template, ctx.auth, ctx.adaptive).await
+    };
+    let response_json = match response {
+        Ok(response_json) => response_json,
+        Err(e) => {
+            error!(index = i, source = %article.source, error = %e, "API call failed; skipping article");
+            ctx.skips.record(&article.source, SkipReason::LlmApiError);
+            return None;
+        }
+    };
+
+    // Try parse
+    let mut parsed = serde_json::from_str::<AwfulNewsArticle>(&response_json);
+
+    // Decide how to recover from a parse failure
+    if let Err(ref e) = parsed {
+        match classify_parse_failure(e, &response_json) {
+            ParseFailureKind::Truncated if ctx.reask => {
+                warn!(index = i, error = %e, "Response looks truncated; re-asking once");
+                if let Some(second) = reask(ctx, template, i, &input).await {
+                    parsed = second;
+                }
+            }
+            ParseFailureKind::Truncated => {
+                warn!(index = i, error = %e, "Response looks truncated; re-ask disabled, dropping article");
+                ctx.skips.record(&article.source, SkipReason::LlmParseError);
+                return None;
+            }
+            ParseFailureKind::Repairable => {
+                if let Some(fixed) = repair_json(&response_json) {
+                    debug!(index = i, error = %e, "Repairing wrapped or malformed JSON");
+                    parsed = serde_json::from_str::<AwfulNewsArticle>(&fixed);
+                }
+            }
+            ParseFailureKind::Malformed => {}
+        }
+    }
+
+    let mut awful_news_article = match parsed {
+        Ok(awful_news_article) => awful_news_article,
+        Err(e) => {
+            warn!(
+                index = i,
+                error = %e,
+                response_preview = %truncate_for_log(&response_json, 300),
+                "Model returned non-conforming JSON; skipping article"
+            );
+            // Well-formed JSON that isn't an article (e.g. a missing summary)
+            let reason = if e.is_data() {
+                SkipReason::ValidationFailed
+            } else {
+                SkipReason::LlmParseError
+            };
+            ctx.skips.record(&article.source, reason);
+            return None;
+        }
+    };
+
+    // Stored content is the scraped text, without the input header
+    awful_news_article.source = Some(article.source.clone());
+    // Shared, not copied: the edition holds each article's text once
+    awful_news_article.content = Some(Arc::clone(&article.content));
+    awful_news_article.updateOf = ctx.update_of.get(&article.source).cloned();
+    awful_news_article.carriedOver = ctx.carried_over.contains(&article.source);
+    awful_news_article.scrapeMeta = scrapers::scrape_meta(article);
+
+    // Drop what the model wasn't asked for before grounding, so scraped
+    // dates and bylines still fill disabled fields
+    schema::clear_disabled_fields(&mut awful_news_article, ctx.extract_fields);
+
+    // Ground publication fields in the scraped timestamp;
+    // without one, drop the (likely hallucinated) time
+    match article.published_at {
+        Some(published) => {
+            awful_news_article.dateOfPublication = published.format("%Y-%m-%d").to_string();
+            awful_news_article.timeOfPublication = published.format("%H:%M:%S").to_string();
+        }
+        None => awful_news_article.timeOfPublication.clear(),
+    }
+
+    // dedupe
+    awful_news_article.namedEntities = awful_news_article
+        .namedEntities
+        .into_iter()
+        .unique_by(|e| e.name.clone())
+        .collect::<Vec<NamedEntity>>();
+    awful_news_article.importantDates = awful_news_article
+        .importantDates
+        .into_iter()
+        .unique_by(|e| e.descriptionOfWhyDateIsRelevant.clone())
+        .collect::<Vec<ImportantDate>>();
+    awful_news_article.importantTimeframes = normalize_timeframes(awful_news_article.importantTimeframes);
+    awful_news_article.keyTakeAways = awful_news_article
+        .keyTakeAways
+        .into_iter()
+        .unique()
+        .collect::<Vec<String>>();
+    awful_news_article.sanitize_takeaways(&ctx.limits);
+    awful_news_article.sanitize_tags(&ctx.limits);
+
+    // A scraped byline beats the model's reading of the text
+    if !article.authors.is_empty() {
+        awful_news_article.authors = article.authors.clone();
+    }
+
+    info!(index = i, "Successfully processed article");
+    Some(awful_news_article)
+}
+
+/// Summarize every article, at most `parallel` at a time.
+///
+/// Results are in completion order. Articles whose turn comes after
+/// `deadline` are not sent to the model: they are recorded as
+/// [`SkipReason::Deadline`] and yield `None`. Articles still being processed
+/// when it passes are abandoned and recorded as [`SkipReason::Cancelled`].
+/// Each summarized article is appended to `partial` and `journal` as soon as
+/// it completes.
+pub async fn process_all(
+    ctx: &ProcessContext<'_>,
+    articles: &[NewsArticle],
+    parallel: usize,
+    deadline: Option<Instant>,
+    partial: Option<&PartialMarkdown>,
+    journal: Option<&Journal>,
+) -> Vec<Option<AwfulNewsArticle>> {
+    stream::iter(articles.iter().enumerate())
+        .map(|(i, article)| async move {
+            let _permit = ctx.adaptive.acquire().await;
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                debug!(index = i, source = %article.source, "Run time budget spent; not sending article");
+                ctx.skips.record(&article.source, SkipReason::Deadline);
+                return None;
+            }
+            let Some(deadline) = deadline else {
+                return process_article(ctx, i, article).await;
+            };
+            match tokio::time::timeout_at(deadline.into(), process_article(ctx, i, article)).await {
+                Ok(result) => result,
+                Err(_) => {
+                    warn!(index = i, source = %article.source, "Run time budget spent; abandoning article in flight");
+                    ctx.skips.record(&article.source, SkipReason::Cancelled);
+                    None
+                }
+            }
+        })
+        .buffer_unordered(parallel)
+        .then(|result| async move {
+            if let (Some(partial), Some(article)) = (partial, &result) {
+                partial.append(article).await;
+            }
+            if let (Some(journal), Some(article)) = (journal, &result) {
+                journal.append(article).await;
+            }
+            result
+        })
+        .inspect(|result| ctx.progress.record(result.is_some()))
+        .collect()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Utc};
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn config(api_base: &str) -> AwfulJadeConfig {
+        let mut config: AwfulJadeConfig = serde_yaml::from_str(include_str!("../config.yaml")).unwrap();
+        config.api_base = api_base.to_string();
+        config
+    }
+
+    fn template() -> ChatTemplate {
+        serde_yaml::from_str(include_str!("../template/news_parser.yaml")).unwrap()
+    }
+
+    fn auth() -> BasicAuth {
+        BasicAuth {
+            username: "user".to_string(),
+            password: "pass".to_string(),
+        }
+    }
+
+    /// What a [`ProcessContext`] borrows, for the mock LLM at `server`.
+    struct LlmFixture {
+        config: AwfulJadeConfig,
+        template: ChatTemplate,
+        template_vars: TemplateContext,
+        update_of: HashMap<String, String>,
+        carried_over: HashSet<String>,
+        concurrency: ModelConcurrency,
+        adaptive: AdaptiveConcurrency,
+        progress: Progress,
+        skips: SkipLog,
+    }
+
+    impl LlmFixture {
+        fn new(server: &MockServer) -> Self {
+            LlmFixture {
+                config: config(&server.uri()),
+                template: template(),
+                template_vars: TemplateContext::default(),
+                update_of: HashMap::new(),
+                carried_over: HashSet::new(),
+                concurrency: ModelConcurrency::default(),
+                adaptive: AdaptiveConcurrency::default(),
+                progress: Progress::new(false, 1),
+                skips: SkipLog::default(),
+            }
+        }
+    }
+
+    /// A context asking `llm` for every field once, without auth and with
+    /// the full input header. Tests change the rest with struct update
+    /// syntax.
+    fn process_context(llm: &LlmFixture) -> ProcessContext<'_> {
+        ProcessContext {
+            config: &llm.config,
+            template: &llm.template,
+            template_vars: &llm.template_vars,
+            auth: None,
+            update_of: &llm.update_of,
+            carried_over: &llm.carried_over,
+            extract_fields: ArticleField::ALL,
+            reask: false,
+            reask_with_retries: false,
+            input_header: LlmInputHeader::Full,
+            limits: ArticleLimits::default(),
+            concurrency: &llm.concurrency,
+            adaptive: &llm.adaptive,
+            progress: &llm.progress,
+            skips: &llm.skips,
+        }
+    }
+
+    /// A mock LLM that always answers with a truncated article.
+    async fn truncating_server() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{ "message": { "role": "assistant", "content": "{\"title\": \"Cut off" } }]
+            })))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    async fn run(server: &MockServer, reask: bool, input_header: LlmInputHeader) -> Option<AwfulNewsArticle> {
+        run_recording(server, reask, input_header, &SkipLog::default()).await
+    }
+
+    async fn run_recording(
+        server: &MockServer,
+        reask: bool,
+        input_header: LlmInputHeader,
+        skips: &SkipLog,
+    ) -> Option<AwfulNewsArticle> {
+        let llm = LlmFixture::new(server);
+        let auth = auth();
+        let ctx = ProcessContext {
+            auth: Some(&auth),
+            reask,
+            input_header,
+            skips,
+            ..process_context(&llm)
+        };
+        process_article(&ctx, 0, &article("https://text.npr.org/1")).await
+    }
+
+    #[tokio::test]
+    async fn test_no_reask_asks_once_and_drops_truncated() {
+        let server = truncating_server().await;
+        assert!(run(&server, false, LlmInputHeader::Full).await.is_none());
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unusable_replies_record_skip_reasons() {
+        let skips = SkipLog::default();
+        let server = truncating_server().await;
+        assert!(run_recording(&server, false, LlmInputHeader::Full, &skips).await.is_none());
+
+        // Valid JSON, but without the summary every article needs
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{ "message": { "role": "assistant", "content": "{\"title\": \"T\", \"category\": \"C\"}" } }]
+            })))
+            .mount(&server)
+            .await;
+        assert!(run_recording(&server, false, LlmInputHeader::Full, &skips).await.is_none());
+
+        let reasons: Vec<SkipReason> = skips.entries().into_iter().map(|s| s.reason).collect();
+        assert_eq!(reasons, vec![SkipReason::LlmParseError, SkipReason::ValidationFailed]);
+        assert_eq!(skips.entries()[0].source, "https://text.npr.org/1");
+    }
+
+    #[tokio::test]
+    async fn test_reask_asks_twice_for_truncated() {
+        let server = truncating_server().await;
+        assert!(run(&server, true, LlmInputHeader::Full).await.is_none());
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    fn article(source: &str) -> NewsArticle {
+        NewsArticle {
+            source: source.to_string(),
+            content: "Body".into(),
+            published_at: None,
+            authors: vec![],
+            fetched_at: None,
+            fetched_from: None,
+        }
+    }
+
+    #[test]
+    fn test_format_llm_input_headers() {
+        let bbc = article("https://www.bbc.com/news/articles/c1");
+        assert_eq!(
+            format_llm_input(&bbc, LlmInputHeader::Full),
+            "SOURCE: BBC News\nURL: https://www.bbc.com/news/articles/c1\n\nBody"
+        );
+        assert_eq!(format_llm_input(&bbc, LlmInputHeader::Source), "SOURCE: BBC News\n\nBody");
+        assert_eq!(format_llm_input(&bbc, LlmInputHeader::Off), "Body");
+
+        // Unknown outlets fall back to the source tag
+        let other = article("https://news.example.org/story");
+        assert!(format_llm_input(&other, LlmInputHeader::Source).starts_with("SOURCE: example\n\n"));
+    }
+
+    #[tokio::test]
+    async fn test_process_all_stops_sending_after_deadline() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({
+                        "choices": [{ "message": { "role": "assistant", "content": "{\"title\": \"Cut off" } }]
+                    }))
+                    .set_delay(std::time::Duration::from_secs(10)),
+            )
+            .mount(&server)
+            .await;
+
+        let llm = LlmFixture::new(&server);
+        let ctx = process_context(&llm);
+        let articles: Vec<NewsArticle> = (1..=3).map(|n| article(&format!("https://text.npr.org/{}", n))).collect();
+
+        // The first article goes out before the deadline; its slow reply outlasts it
+        let started = Instant::now();
+        let deadline = started + std::time::Duration::from_secs(1);
+        let results = process_all(&ctx, &articles, 1, Some(deadline), None, None).await;
+
+        // The deadline is hard: the run doesn't wait for the reply in flight
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(Option::is_none));
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+        let skipped = |reason: SkipReason| -> Vec<String> {
+            llm.skips.entries().into_iter().filter(|s| s.reason == reason).map(|s| s.source).collect()
+        };
+        assert_eq!(skipped(SkipReason::Cancelled), vec!["https://text.npr.org/1"]);
+        assert_eq!(skipped(SkipReason::Deadline), vec!["https://text.npr.org/2", "https://text.npr.org/3"]);
+    }
+
+    #[tokio::test]
+    async fn test_processed_article_shares_the_scraped_text() {
+        let server = MockServer::start().await;
+        let reply = crate::test_support::canned_article("Probe launched", "Science & Technology").to_string();
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{ "message": { "role": "assistant", "content": reply } }]
+            })))
+            .mount(&server)
+            .await;
+
+        let llm = LlmFixture::new(&server);
+        let ctx = process_context(&llm);
+        let fetched_at = Some(Utc::now());
+        let scraped = NewsArticle { fetched_at, ..article("https://text.npr.org/probe") };
+        let processed = process_article(&ctx, 0, &scraped).await.unwrap();
+
+        let meta = processed.scrapeMeta.as_ref().unwrap();
+        assert_eq!(meta.scraper, "npr");
+        assert_eq!(meta.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(meta.fetched_at, fetched_at);
+        assert_eq!(meta.content_length, scraped.content.chars().count());
+        assert_eq!(meta.selector_hash, crate::scrapers::selectors::get("npr").fingerprint);
+
+        // One allocation of the text, referenced by both articles
+        let content = processed.content.as_ref().unwrap();
+        assert!(Arc::ptr_eq(content, &scraped.content));
+        assert_eq!(Arc::strong_count(&scraped.content), 2);
+        drop(processed);
+        assert_eq!(Arc::strong_count(&scraped.content), 1);
+    }
+
+    #[tokio::test]
+    async fn test_scraped_fields_survive_disabled_extraction() {
+        let server = MockServer::start().await;
+        let reply = crate::test_support::canned_article("Probe launched", "Science & Technology").to_string();
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{ "message": { "role": "assistant", "content": reply } }]
+            })))
+            .mount(&server)
+            .await;
+
+        let llm = LlmFixture::new(&server);
+        let ctx = ProcessContext {
+            extract_fields: &[ArticleField::KeyTakeAways],
+            input_header: LlmInputHeader::Off,
+            ..process_context(&llm)
+        };
+        let published_at = Some(DateTime::parse_from_rfc3339("2025-05-04T09:15:00+00:00").unwrap());
+        let scraped = NewsArticle {
+            published_at,
+            authors: vec!["Jane Doe".to_string()],
+            ..article("https://text.npr.org/probe")
+        };
+        let processed = process_article(&ctx, 0, &scraped).await.unwrap();
+
+        // Disabled fields are filled from the scrape, not left blank
+        assert_eq!(processed.dateOfPublication, "2025-05-04");
+        assert_eq!(processed.timeOfPublication, "09:15:00");
+        assert_eq!(processed.authors, vec!["Jane Doe"]);
+        // The model's values for disabled fields are still dropped
+        assert!(processed.tags.is_empty());
+        assert_eq!(processed.keyTakeAways, vec!["Probe launched matters"]);
+    }
+
+    #[tokio::test]
+    async fn test_input_header_reaches_the_model_but_not_content() {
+        let server = truncating_server().await;
+        run(&server, false, LlmInputHeader::Full).await;
+        let requests = server.received_requests().await.unwrap();
+        let body = String::from_utf8_lossy(&requests[0].body);
+        assert!(body.contains("SOURCE: NPR\\nURL: https://text.npr.org/1\\n\\nBody"));
+
+        let server = truncating_server().await;
+        run(&server, false, LlmInputHeader::Off).await;
+        let requests = server.received_requests().await.unwrap();
+        assert!(!String::from_utf8_lossy(&requests[0].body).contains("SOURCE:"));
+    }
+
+    #[tokio::test]
+    async fn test_edition_date_reaches_the_model() {
+        let server = truncating_server().await;
+        let llm = LlmFixture {
+            template_vars: TemplateContext {
+                current_date: "2025-05-06".to_string(),
+                time_of_day: "morning".to_string(),
+                ..Default::default()
+            },
+            ..LlmFixture::new(&server)
+        };
+        let auth = auth();
+        let ctx = ProcessContext {
+            auth: Some(&auth),
+            input_header: LlmInputHeader::Source,
+            ..process_context(&llm)
+        };
+        process_article(&ctx, 0, &article("https://text.npr.org/1")).await;
+        let requests = server.received_requests().await.unwrap();
+        let body = String::from_utf8_lossy(&requests[0].body);
+        assert!(body.contains("collected from npr for the morning edition of Tuesday, 2025-05-06."), "{}", body);
+        assert!(body.contains("SOURCE: NPR\\n\\nBody"), "{}", body);
+    }
+
+    #[tokio::test]
+    async fn test_reask_is_a_single_attempt_on_failing_backend() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{ "message": { "role": "assistant", "content": "{\"title\": \"Cut off" } }]
+            })))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(500))
+            .with_priority(2)
+            .mount(&server)
+            .await;
+
+        // Primary ask (truncated) plus exactly one failed re-ask
+        assert!(run(&server, true, LlmInputHeader::Full).await.is_none());
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,155 @@
+//! Optional progress indicator for LLM processing.
+//!
+//! With `--progress`, a status line on stderr tracks articles as they
+//! complete: done/total plus success, failure and re-ask tallies. It is
+//! purely observational and never changes the data flow.
+//!
+//! The bar is only drawn when stderr is a terminal, so scheduled runs stay
+//! quiet even if the flag is set. Log lines go through [`LogWriter`], which
+//! suspends the bar while a line is written so the two never interleave.
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use once_cell::sync::Lazy;
+use std::io::{self, IsTerminal, Write};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// The bar currently on screen, if any; consulted by [`LogWriter`].
+static ACTIVE: Lazy<Mutex<Option<ProgressBar>>> = Lazy::new(|| Mutex::new(None));
+
+/// Run `f` with the active bar (if any) cleared from the screen.
+fn suspended<R>(f: impl FnOnce() -> R) -> R {
+    let active = ACTIVE.lock().ok().and_then(|bar| bar.clone());
+    match active {
+        Some(bar) => bar.suspend(f),
+        None => f(),
+    }
+}
+
+/// Tracing writer that keeps log lines from clobbering the progress bar.
+///
+/// Writes to stdout like the default `fmt` writer.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogWriter;
+
+/// A single log write, see [`LogWriter`].
+#[derive(Debug)]
+pub struct LogLine;
+
+impl Write for LogLine {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        suspended(|| io::stdout().lock().write_all(buf))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+}
+
+impl<'a> MakeWriter<'a> for LogWriter {
+    type Writer = LogLine;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LogLine
+    }
+}
+
+/// Progress of the article processing stage.
+///
+/// Tallies are kept even when no bar is drawn.
+#[derive(Debug)]
+pub struct Progress {
+    bar: Option<ProgressBar>,
+    succeeded: AtomicUsize,
+    failed: AtomicUsize,
+    retried: AtomicUsize,
+}
+
+impl Progress {
+    /// Start tracking `total` articles.
+    ///
+    /// A bar is drawn only if `enabled` and stderr is a terminal.
+    pub fn new(enabled: bool, total: usize) -> Self {
+        let bar = (enabled && io::stderr().is_terminal()).then(|| {
+            let bar = ProgressBar::with_draw_target(Some(total as u64), ProgressDrawTarget::stderr());
+            bar.set_style(
+                ProgressStyle::with_template("{spinner} [{elapsed_precise}] {bar:30} {pos}/{len} {msg}")
+                    .unwrap_or_else(|_| ProgressStyle::default_bar()),
+            );
+            bar.enable_steady_tick(std::time::Duration::from_millis(250));
+            if let Ok(mut active) = ACTIVE.lock() {
+                *active = Some(bar.clone());
+            }
+            bar
+        });
+        let progress = Progress {
+            bar,
+            succeeded: AtomicUsize::new(0),
+            failed: AtomicUsize::new(0),
+            retried: AtomicUsize::new(0),
+        };
+        progress.refresh();
+        progress
+    }
+
+    /// Status text shown next to the bar.
+    pub fn message(&self) -> String {
+        format!(
+            "ok {} | failed {} | re-asked {}",
+            self.succeeded.load(Ordering::Relaxed),
+            self.failed.load(Ordering::Relaxed),
+            self.retried.load(Ordering::Relaxed)
+        )
+    }
+
+    fn refresh(&self) {
+        if let Some(bar) = &self.bar {
+            bar.set_message(self.message());
+        }
+    }
+
+    /// Record a finished article.
+    pub fn record(&self, success: bool) {
+        let counter = if success { &self.succeeded } else { &self.failed };
+        counter.fetch_add(1, Ordering::Relaxed);
+        if let Some(bar) = &self.bar {
+            bar.inc(1);
+        }
+        self.refresh();
+    }
+
+    /// Record that an article had to be asked again.
+    pub fn record_retry(&self) {
+        self.retried.fetch_add(1, Ordering::Relaxed);
+        self.refresh();
+    }
+
+    /// Remove the bar from the screen.
+    pub fn finish(self) {
+        if let Some(bar) = self.bar {
+            bar.finish_and_clear();
+            if let Ok(mut active) = ACTIVE.lock() {
+                *active = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tallies_without_bar() {
+        let progress = Progress::new(false, 3);
+        progress.record(true);
+        progress.record_retry();
+        progress.record(true);
+        progress.record(false);
+        assert_eq!(progress.message(), "ok 2 | failed 1 | re-asked 1");
+        assert!(progress.bar.is_none());
+        progress.finish();
+    }
+}
@@ -41,6 +41,7 @@
 //! | `output.markdown.started` | Beginning Markdown file write |
 //! | `output.markdown.completed` | Markdown file written successfully |
 //! | `output.markdown.failed` | Markdown file write failed |
+//! | `edition.published` | The finished edition, see [`publish_edition`] |
 //!
 //! # Usage
 //!
@@ -114,15 +115,161 @@ pub async fn init(amqp_url: Option<&String>, exchange: &str) -> bool {
 }
 
 /// Initialize the message bus connection (no-op when `publish` feature is disabled).
+///
+/// Warns if an AMQP URL was given, since it has no effect in this build.
 #[cfg(not(feature = "publish"))]
-pub async fn init(_amqp_url: Option<&String>, _exchange: &str) -> bool {
+pub async fn init(amqp_url: Option<&String>, _exchange: &str) -> bool {
+    if amqp_url.is_some() {
+        tracing::warn!("--amqp-url is ignored: built without the `publish` feature");
+    }
     false
 }
 
+/// Send one event to the bus; the expansion target of the publish macros.
+///
+/// In test builds every event is also recorded so tests can inspect what
+/// the macros emitted (see [`captured`]).
+#[cfg(feature = "publish")]
+#[doc(hidden)]
+pub fn emit(
+    service: &str,
+    level: tracing::Level,
+    message: &'static str,
+    fields: Vec<(&'static str, serde_json::Value)>,
+) {
+    #[cfg(test)]
+    captured::record(service, level, message, &fields);
+    awful_publish::publish(service, level, message, fields)
+}
+
+/// Events emitted on the current thread, recorded for tests.
+#[cfg(all(test, feature = "publish"))]
+pub mod captured {
+    use std::cell::RefCell;
+
+    /// An event as passed to [`super::emit`].
+    #[derive(Debug, Clone)]
+    pub struct Event {
+        pub service: String,
+        pub level: tracing::Level,
+        pub message: &'static str,
+        pub fields: Vec<(&'static str, serde_json::Value)>,
+    }
+
+    thread_local! {
+        static EVENTS: RefCell<Vec<Event>> = const { RefCell::new(Vec::new()) };
+    }
+
+    pub(super) fn record(
+        service: &str,
+        level: tracing::Level,
+        message: &'static str,
+        fields: &[(&'static str, serde_json::Value)],
+    ) {
+        EVENTS.with(|events| {
+            events.borrow_mut().push(Event {
+                service: service.to_string(),
+                level,
+                message,
+                fields: fields.to_vec(),
+            })
+        });
+    }
+
+    /// Take all events recorded on this thread so far.
+    pub fn take() -> Vec<Event> {
+        EVENTS.with(|events| events.borrow_mut().drain(..).collect())
+    }
+}
+
+/// Default routing key of the `edition.published` event.
+pub const DEFAULT_EDITION_EVENT_KEY: &str = "awful_text_news.edition";
+
+/// Build the payload of the `edition.published` event.
+///
+/// Always carries the edition metadata and the paths of the files written.
+/// With `include_articles`, the serialized [`FrontPage`] is added under
+/// `front_page` with each article's scraped `content` removed, which would
+/// otherwise dominate the message size.
+///
+/// [`FrontPage`]: crate::models::FrontPage
+#[cfg_attr(not(feature = "publish"), allow(dead_code))]
+pub fn edition_payload(
+    front_page: &crate::models::FrontPage,
+    files: &[String],
+    include_articles: bool,
+) -> Result<serde_json::Value, serde_json::Error> {
+    use serde_json::{json, Value};
+
+    let mut payload = json!({
+        "local_date": front_page.local_date,
+        "time_of_day": front_page.time_of_day,
+        "local_time": front_page.local_time,
+        "article_count": front_page.articles.len(),
+        "files": files,
+    });
+
+    if include_articles {
+        let mut page = serde_json::to_value(front_page)?;
+        if let Some(Value::Array(articles)) = page.get_mut("articles") {
+            for article in articles.iter_mut().filter_map(Value::as_object_mut) {
+                article.remove("content");
+            }
+        }
+        payload["front_page"] = page;
+    }
+
+    Ok(payload)
+}
+
+/// Publish the finished edition as an `edition.published` event.
+///
+/// Like every other event, it is published by the `awful_text_news`
+/// service. `awful_publish::publish` takes no routing argument beyond the
+/// service, so `routing_key` (see `--edition-event-key`) travels as the
+/// event's `routing_key` field for consumers to select editions on. If the
+/// edition cannot be serialized, a warning is logged and a metadata-only
+/// payload is sent instead; the run is never aborted.
+#[cfg(feature = "publish")]
+pub fn publish_edition(
+    routing_key: &str,
+    front_page: &crate::models::FrontPage,
+    files: &[String],
+    include_articles: bool,
+) {
+    let payload = edition_payload(front_page, files, include_articles).or_else(|e| {
+        tracing::warn!(error = %e, "Failed to serialize edition payload; publishing metadata only");
+        edition_payload(front_page, files, false)
+    });
+    match payload {
+        Ok(payload) => emit(
+            "awful_text_news",
+            tracing::Level::INFO,
+            "Edition published",
+            vec![
+                ("event_kind", serde_json::json!("edition.published")),
+                ("routing_key", serde_json::json!(routing_key)),
+                ("edition", payload),
+            ],
+        ),
+        Err(e) => tracing::warn!(error = %e, "Failed to build edition payload; not publishing"),
+    }
+}
+
+/// Publish the finished edition (no-op when `publish` feature is disabled).
+#[cfg(not(feature = "publish"))]
+pub fn publish_edition(
+    _routing_key: &str,
+    _front_page: &crate::models::FrontPage,
+    _files: &[String],
+    _include_articles: bool,
+) {
+}
+
 /// Publish an info-level event to the message bus.
 ///
-/// This macro calls `awful_publish::publish()` directly when the `publish` feature
-/// is enabled. When disabled, it expands to an empty block.
+/// This macro forwards to `awful_publish::publish()` (via [`emit`]) when the
+/// `publish` feature is enabled. When disabled, it expands to an empty block.
 ///
 /// # Syntax
 ///
@@ -152,7 +299,7 @@ pub async fn init(_amqp_url: Option<&String>, _exchange: &str) -> bool {
 #[macro_export]
 macro_rules! publish_info {
     ($service:expr, $($($k:ident).+ = $val:expr),+ , $msg:literal) => {
-        awful_publish::publish(
+        $crate::publish::emit(
             $service,
             tracing::Level::INFO,
             $msg,
@@ -162,7 +309,7 @@ macro_rules! publish_info {
         )
     };
     ($service:expr, $msg:literal) => {
-        awful_publish::publish(
+        $crate::publish::emit(
             $service,
             tracing::Level::INFO,
             $msg,
@@ -180,8 +327,8 @@ macro_rules! publish_info {
 
 /// Publish an error-level event to the message bus.
 ///
-/// This macro calls `awful_publish::publish()` directly when the `publish` feature
-/// is enabled. When disabled, it expands to an empty block.
+/// This macro forwards to `awful_publish::publish()` (via [`emit`]) when the
+/// `publish` feature is enabled. When disabled, it expands to an empty block.
 ///
 /// # Syntax
 ///
@@ -211,7 +358,7 @@ macro_rules! publish_info {
 #[macro_export]
 macro_rules! publish_error {
     ($service:expr, $($($k:ident).+ = $val:expr),+ , $msg:literal) => {
-        awful_publish::publish(
+        $crate::publish::emit(
             $service,
             tracing::Level::ERROR,
             $msg,
@@ -221,7 +368,7 @@ macro_rules! publish_error {
         )
     };
     ($service:expr, $msg:literal) => {
-        awful_publish::publish(
+        $crate::publish::emit(
             $service,
             tracing::Level::ERROR,
             $msg,
@@ -242,3 +389,112 @@ macro_rules! publish_error {
 pub use publish_error;
 #[allow(unused_imports)]
 pub use publish_info;
+
+#[cfg(all(test, feature = "publish"))]
+mod tests {
+    use serde_json::json;
+
+    #[test]
+    fn test_macros_emit_events_with_fields() {
+        let count = 3;
+        publish_info!(
+            "awful_text_news",
+            event_kind = "processing.completed",
+            succeeded = count,
+            "Processing completed"
+        );
+        publish_error!("awful_text_news", "Something failed");
+
+        let events = super::captured::take();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].service, "awful_text_news");
+        assert_eq!(events[0].level, tracing::Level::INFO);
+        assert_eq!(events[0].message, "Processing completed");
+        assert_eq!(
+            events[0].fields,
+            vec![
+                ("event_kind", json!("processing.completed")),
+                ("succeeded", json!(3)),
+            ]
+        );
+        assert_eq!(events[1].level, tracing::Level::ERROR);
+        assert!(events[1].fields.is_empty());
+    }
+
+    #[test]
+    fn test_publish_edition_event() {
+        let page = super::payload_tests::front_page();
+        let files = vec!["json/2025-05-06/morning.json".to_string()];
+        super::publish_edition("news.edition", &page, &files, true);
+        super::publish_edition("news.edition", &page, &files, false);
+
+        let events = super::captured::take();
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| e.service == "awful_text_news"));
+        assert_eq!(events[0].fields[0], ("event_kind", json!("edition.published")));
+        assert_eq!(events[0].fields[1], ("routing_key", json!("news.edition")));
+        let (name, full) = &events[0].fields[2];
+        assert_eq!(*name, "edition");
+        assert_eq!(full["files"], json!(files));
+        assert_eq!(full["front_page"]["articles"][0]["title"], "Headline");
+        assert!(full["front_page"]["articles"][0].get("content").is_none());
+        assert!(events[1].fields[2].1.get("front_page").is_none());
+    }
+}
+
+#[cfg(all(test, not(feature = "publish")))]
+mod tests {
+    #[test]
+    fn test_macros_are_no_ops_without_feature() {
+        publish_info!("awful_text_news", event_kind = "x", count = 1, "Info");
+        publish_error!("awful_text_news", "Error");
+    }
+
+    #[tokio::test]
+    async fn test_init_without_feature_is_disabled() {
+        let url = "amqp://localhost:5672".to_string();
+        assert!(!super::init(Some(&url), "events").await);
+    }
+}
+
+#[cfg(test)]
+mod payload_tests {
+    use super::edition_payload;
+    use crate::models::{AwfulNewsArticle, FrontPage};
+    use crate::test_support;
+
+    pub(super) fn front_page() -> FrontPage {
+        FrontPage {
+            articles: vec![AwfulNewsArticle {
+                source: Some("https://text.npr.org/1".to_string()),
+                summaryOfNewsArticle: "Summary.".to_string(),
+                content: Some("Very long scraped body".into()),
+                ..test_support::article("Headline", "Science & Technology")
+            }],
+            ..test_support::front_page("morning", "08:00:00")
+        }
+    }
+
+    #[test]
+    fn test_edition_payload_strips_content() {
+        let files = vec!["json/2025-05-06/morning.json".to_string()];
+        let payload = edition_payload(&front_page(), &files, true).unwrap();
+
+        assert_eq!(payload["local_date"], "2025-05-06");
+        assert_eq!(payload["time_of_day"], "morning");
+        assert_eq!(payload["article_count"], 1);
+        assert_eq!(payload["files"][0], "json/2025-05-06/morning.json");
+        let article = &payload["front_page"]["articles"][0];
+        assert_eq!(article["title"], "Headline");
+        assert_eq!(article["summaryOfNewsArticle"], "Summary.");
+        assert!(article.get("content").is_none());
+    }
+
+    #[test]
+    fn test_edition_payload_metadata_only() {
+        let payload = edition_payload(&front_page(), &[], false).unwrap();
+        assert!(payload.get("front_page").is_none());
+        assert_eq!(payload["article_count"], 1);
+        assert_eq!(payload["files"], serde_json::json!([]));
+    }
+}
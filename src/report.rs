@@ -0,0 +1,159 @@
+//! End-of-run summary table (`--print-stats`).
+//!
+//! The same counts that go into the run's log lines and bus events, printed
+//! to stdout as a table once the edition is written:
+//!
+//! ```text
+//! Source     Indexed  Fetched  Processed
+//! cnn             12       10          9
+//! aljazeera        8        8          7
+//! nyt              -        -          -
+//! Total           20       18         16
+//!
+//! Failed:        1
+//! Skipped:       3 (fetch_error 2, duplicate 1)
+//! Deduplicated:  2 same source, 1 identical
+//! Elapsed:       83.4s
+//! Outputs:
+//!   json/2025-05-06/evening.json
+//! ```
+//!
+//! It is printed with `println!` rather than through tracing, so it shows up
+//! with logging turned off. Sources that didn't run (NYT without an API key)
+//! are listed with dashes.
+
+use crate::models::SkipReason;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Counts of one scraper in the run; `None` if it didn't run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SourceCounts {
+    pub indexed: usize,
+    pub fetched: usize,
+    /// Articles of the source in the edition.
+    pub processed: usize,
+}
+
+/// Everything the summary table shows.
+#[derive(Debug, Default)]
+pub struct RunReport<'a> {
+    /// Per scraper, in the order they run.
+    pub sources: Vec<(&'a str, Option<SourceCounts>)>,
+    /// Articles the LLM step failed on.
+    pub failed: usize,
+    pub skipped: BTreeMap<SkipReason, usize>,
+    pub deduped_same_source: usize,
+    pub deduped_identical: usize,
+    pub elapsed: Duration,
+    /// Files written for the edition.
+    pub outputs: &'a [String],
+}
+
+/// Render `report` as the summary table.
+pub fn format_table(report: &RunReport) -> String {
+    let width = report
+        .sources
+        .iter()
+        .map(|(name, _)| name.len())
+        .chain(["Source".len(), "Total".len()])
+        .max()
+        .unwrap_or_default();
+    let mut table = String::new();
+    let mut row = |name: &str, cells: [String; 3]| {
+        let _ = writeln!(table, "{:<width$}  {:>7}  {:>7}  {:>9}", name, cells[0], cells[1], cells[2]);
+    };
+
+    row("Source", ["Indexed".into(), "Fetched".into(), "Processed".into()]);
+    let mut total = SourceCounts::default();
+    for (name, counts) in &report.sources {
+        match counts {
+            Some(counts) => {
+                total.indexed += counts.indexed;
+                total.fetched += counts.fetched;
+                total.processed += counts.processed;
+                row(name, [counts.indexed.to_string(), counts.fetched.to_string(), counts.processed.to_string()]);
+            }
+            None => row(name, ["-".into(), "-".into(), "-".into()]),
+        }
+    }
+    row("Total", [total.indexed.to_string(), total.fetched.to_string(), total.processed.to_string()]);
+
+    let skipped: usize = report.skipped.values().sum();
+    let reasons: Vec<String> = report
+        .skipped
+        .iter()
+        .map(|(reason, count)| format!("{} {}", reason.as_str(), count))
+        .collect();
+    let _ = writeln!(table);
+    let _ = writeln!(table, "Failed:        {}", report.failed);
+    if reasons.is_empty() {
+        let _ = writeln!(table, "Skipped:       {}", skipped);
+    } else {
+        let _ = writeln!(table, "Skipped:       {} ({})", skipped, reasons.join(", "));
+    }
+    let _ = writeln!(
+        table,
+        "Deduplicated:  {} same source, {} identical",
+        report.deduped_same_source, report.deduped_identical
+    );
+    let _ = writeln!(table, "Elapsed:       {:.1}s", report.elapsed.as_secs_f64());
+    let _ = writeln!(table, "Outputs:");
+    if report.outputs.is_empty() {
+        let _ = writeln!(table, "  (none)");
+    }
+    for path in report.outputs {
+        let _ = writeln!(table, "  {}", path);
+    }
+    table
+}
+
+/// Print the summary table to stdout.
+pub fn print(report: &RunReport) {
+    println!("\n{}", format_table(report).trim_end());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_table_over_sample_stats() {
+        let outputs = vec!["json/2025-05-06/evening.json".to_string(), "book/2025-05-06_evening.md".to_string()];
+        let report = RunReport {
+            sources: vec![
+                ("cnn", Some(SourceCounts { indexed: 12, fetched: 10, processed: 9 })),
+                ("aljazeera", Some(SourceCounts { indexed: 8, fetched: 8, processed: 7 })),
+                ("nyt", None),
+            ],
+            failed: 1,
+            skipped: BTreeMap::from([(SkipReason::FetchError, 2), (SkipReason::Duplicate, 1)]),
+            deduped_same_source: 2,
+            deduped_identical: 1,
+            elapsed: Duration::from_millis(83_420),
+            outputs: &outputs,
+        };
+
+        let expected = "\
+Source     Indexed  Fetched  Processed
+cnn             12       10          9
+aljazeera        8        8          7
+nyt              -        -          -
+Total           20       18         16
+
+Failed:        1
+Skipped:       3 (fetch_error 2, duplicate 1)
+Deduplicated:  2 same source, 1 identical
+Elapsed:       83.4s
+Outputs:
+  json/2025-05-06/evening.json
+  book/2025-05-06_evening.md
+";
+        assert_eq!(format_table(&report), expected);
+
+        let empty = format_table(&RunReport::default());
+        assert!(empty.contains("Skipped:       0\n"));
+        assert!(empty.ends_with("Outputs:\n  (none)\n"));
+    }
+}
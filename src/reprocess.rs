@@ -0,0 +1,313 @@
+//! Re-summarizing stored editions with the current template (`--reprocess`).
+//!
+//! Edition JSON keeps each article's scraped `content`, so after a prompt
+//! change an edition can be summarized again without re-scraping.
+//! `--reprocess` takes an edition file, or a `YYYY-MM-DD` date meaning every
+//! edition of that date under `--json-output-dir`. Each stored article goes
+//! through [`process_article`] with the current template, and the edition's
+//! JSON and Markdown are rewritten under the output directories.
+//!
+//! Fields describing the edition rather than the model's answer are kept
+//! from the stored article: publication date and time (the scraped
+//! timestamp is not stored), `updateOf`, overflow and carried-over flags,
+//! and the edition's diff against the previous edition and its corrections.
+//! Articles without stored content, or whose reprocessing fails, keep their
+//! previous summary. Top stories are picked again from the new summaries
+//! with the current `--top-n`. Article order is kept, and the date TOC and indexes
+//! are left alone since their links use URL-based anchors.
+
+use crate::api::{AdaptiveConcurrency, TemplateContext};
+use crate::cli::Cli;
+use crate::concurrency::ModelConcurrency;
+use crate::models::{AwfulNewsArticle, FrontPage, NewsArticle};
+use crate::outputs::json::{read_edition, write_frontpage, write_mirrors};
+use crate::outputs::markdown::front_page_to_markdown;
+use crate::outputs::sink::OutputSink;
+use crate::pipeline::{process_article, ProcessContext};
+use crate::progress::Progress;
+use crate::salience;
+use crate::schema::{self, ArticleField};
+use crate::skips::SkipLog;
+use crate::top_stories;
+use crate::utils::all_edition_labels;
+use awful_aj::{config, template};
+use chrono::NaiveDate;
+use futures::stream::{self, StreamExt};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Edition files named by `target`: the file itself, or every edition of a
+/// date found under `json_output_dir`.
+pub fn edition_files(target: &str, json_output_dir: &str) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    if Path::new(target).is_file() {
+        return Ok(vec![PathBuf::from(target)]);
+    }
+    if NaiveDate::parse_from_str(target, "%Y-%m-%d").is_err() {
+        return Err(format!("`{}` is neither an edition file nor a YYYY-MM-DD date", target).into());
+    }
+    let files: Vec<PathBuf> = all_edition_labels()
+        .into_iter()
+        .map(|edition| Path::new(json_output_dir).join(target).join(format!("{}.json", edition)))
+        .filter(|path| path.is_file())
+        .collect();
+    if files.is_empty() {
+        return Err(format!("no editions of {} under {}", target, json_output_dir).into());
+    }
+    Ok(files)
+}
+
+/// The scraped article behind a stored one, if its source and content were kept.
+fn stored_input(article: &AwfulNewsArticle) -> Option<NewsArticle> {
+    Some(NewsArticle {
+        source: article.source.clone()?,
+        content: article.content.clone()?,
+        published_at: None,
+        authors: article.authors.clone(),
+        fetched_at: None,
+        fetched_from: None,
+    })
+}
+
+/// Re-summarize every article of `front_page` with up to `parallel` in flight.
+///
+/// # Returns
+///
+/// The rewritten edition and the number of articles that were re-summarized.
+pub async fn reprocess_front_page(
+    ctx: &ProcessContext<'_>,
+    front_page: FrontPage,
+    parallel: usize,
+) -> (FrontPage, usize) {
+    let FrontPage {
+        local_date,
+        time_of_day,
+        local_time,
+        articles,
+        diff,
+        top,
+        editorial,
+        corrections,
+    } = front_page;
+
+    let results: Vec<(AwfulNewsArticle, bool)> = stream::iter(articles.into_iter().enumerate())
+        .map(|(i, old)| async move {
+            let Some(input) = stored_input(&old) else {
+                warn!(index = i, title = %old.title, "No stored content; keeping the previous summary");
+                return (old, false);
+            };
+            let _permit = ctx.adaptive.acquire().await;
+            match process_article(ctx, i, &input).await {
+                Some(mut new) => {
+                    new.dateOfPublication = old.dateOfPublication;
+                    new.timeOfPublication = old.timeOfPublication;
+                    new.updateOf = old.updateOf;
+                    new.overflow = old.overflow;
+                    new.carriedOver = old.carriedOver;
+                    // The content is the one scraped originally
+                    new.scrapeMeta = old.scrapeMeta;
+                    (new, true)
+                }
+                None => {
+                    warn!(index = i, source = %input.source, "Reprocessing failed; keeping the previous summary");
+                    (old, false)
+                }
+            }
+        })
+        .buffered(parallel.max(1))
+        .collect()
+        .await;
+
+    let reprocessed = results.iter().filter(|(_, fresh)| *fresh).count();
+    let front_page = FrontPage {
+        local_date,
+        time_of_day,
+        local_time,
+        articles: results.into_iter().map(|(article, _)| article).collect(),
+        diff,
+        top,
+        editorial,
+        corrections,
+    };
+    (front_page, reprocessed)
+}
+
+/// Reprocess the editions named by `target` and rewrite their outputs,
+/// including the JSON copies in `json_mirrors`.
+///
+/// # Returns
+///
+/// The number of editions rewritten.
+pub async fn run(sink: &impl OutputSink, args: &Cli, target: &str, json_mirrors: &[String]) -> Result<usize, Box<dyn Error>> {
+    let files = edition_files(target, &args.json_output_dir)?;
+
+    let mut template = template::load_template("news_parser").await?;
+    if args.extract_fields.len() < ArticleField::ALL.len() {
+        schema::restrict_template(&mut template, &args.extract_fields)?;
+    }
+    let config = config::load_config(&args.config_path()?.to_string_lossy())?;
+    let auth = args.llm_auth();
+    let concurrency = ModelConcurrency::new(args.default_model_concurrency, &args.model_concurrency);
+    let parallel = concurrency.total_for([config.model.as_str()]);
+    let adaptive = AdaptiveConcurrency::new(parallel);
+    let markdown_options = args.markdown_options();
+
+    for file in &files {
+        let path = file.to_string_lossy();
+        let front_page: FrontPage = read_edition(sink, &path).await?.ok_or_else(|| format!("{} disappeared", path))?;
+        let total = front_page.articles.len();
+        info!(path = %file.display(), total, "Reprocessing edition");
+
+        let template_vars = TemplateContext {
+            current_date: front_page.local_date.clone(),
+            time_of_day: front_page.time_of_day.clone(),
+            source: String::new(),
+            summary_words: args.summary_words,
+        };
+        let progress = Progress::new(args.progress, total);
+        let skips = SkipLog::default();
+        let ctx = ProcessContext {
+            config: &config,
+            template: &template,
+            template_vars: &template_vars,
+            auth: auth.as_ref(),
+            update_of: &HashMap::new(),
+            carried_over: &HashSet::new(),
+            extract_fields: &args.extract_fields,
+            reask: !args.no_reask,
+            reask_with_retries: args.reask_with_retries,
+            input_header: args.llm_input_header,
+            limits: args.article_limits(),
+            concurrency: &concurrency,
+            adaptive: &adaptive,
+            progress: &progress,
+            skips: &skips,
+        };
+        let (mut front_page, reprocessed) = reprocess_front_page(&ctx, front_page, parallel).await;
+        progress.finish();
+        // New entities can change which stories lead
+        salience::score(&mut front_page.articles, &args.salience_weights());
+        front_page.top = top_stories::select_top(&front_page.articles, args.top_n);
+
+        let json_paths = write_frontpage(
+            sink,
+            &front_page,
+            &args.json_output_dir,
+            args.content_policy(),
+            args.json_sort,
+            args.json_max_bytes,
+        )
+        .await?;
+        write_mirrors(&json_paths, &args.json_output_dir, json_mirrors).await;
+        let json_path = &json_paths[0];
+        let markdown_path = format!("{}/{}.md", args.markdown_output_dir, front_page.edition_stem());
+        sink.write(&markdown_path, front_page_to_markdown(&front_page, &markdown_options).as_bytes()).await?;
+        info!(%json_path, %markdown_path, total, reprocessed, skipped = ?skips.counts(), "Rewrote reprocessed edition");
+    }
+    Ok(files.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{canned_article, write_config, write_template, FixtureLlm};
+    use awful_aj::template::ChatTemplate;
+
+    const FIXTURE_EDITION: &str = r#"{
+        "local_date": "2025-05-06",
+        "time_of_day": "morning",
+        "local_time": "08:00:00",
+        "articles": [
+            {
+                "source": "https://lite.cnn.com/2025/05/06/politics/talks",
+                "dateOfPublication": "2025-05-06",
+                "timeOfPublication": "09:15:00",
+                "title": "Old talks title",
+                "category": "World News",
+                "summaryOfNewsArticle": "Old summary.",
+                "content": "FIXTURE-CNN Negotiators met again on Tuesday.",
+                "overflow": true
+            },
+            {
+                "source": "https://text.npr.org/nx-s1-1",
+                "dateOfPublication": "2025-05-06",
+                "timeOfPublication": "",
+                "title": "Stored without content",
+                "category": "Science & Technology",
+                "summaryOfNewsArticle": "Kept as is.",
+                "content": null
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_edition_files_accepts_a_file_or_a_date() {
+        let dir = tempfile::tempdir().unwrap();
+        let json_dir = dir.path().to_str().unwrap();
+        let date_dir = dir.path().join("2025-05-06");
+        std::fs::create_dir_all(&date_dir).unwrap();
+        for edition in ["evening", "morning"] {
+            std::fs::write(date_dir.join(format!("{}.json", edition)), FIXTURE_EDITION).unwrap();
+        }
+
+        let files = edition_files("2025-05-06", json_dir).unwrap();
+        assert_eq!(files, vec![date_dir.join("morning.json"), date_dir.join("evening.json")]);
+        let file = date_dir.join("evening.json");
+        assert_eq!(edition_files(file.to_str().unwrap(), json_dir).unwrap(), vec![file.clone()]);
+        assert!(edition_files("2025-05-07", json_dir).is_err());
+        assert!(edition_files("yesterday", json_dir).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reprocess_resummarizes_stored_content() {
+        let llm = FixtureLlm::start(vec![(
+            "FIXTURE-CNN",
+            canned_article("Talks resume", "Politics & Governance"),
+        )])
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = write_config(dir.path(), &llm.api_base());
+        let config = config::load_config(config_path.to_str().unwrap()).unwrap();
+        let template_path = write_template(dir.path());
+        let template: ChatTemplate = serde_yaml::from_str(&std::fs::read_to_string(template_path).unwrap()).unwrap();
+
+        let progress = Progress::new(false, 2);
+        let ctx = ProcessContext {
+            config: &config,
+            template: &template,
+            template_vars: &Default::default(),
+            auth: None,
+            update_of: &HashMap::new(),
+            carried_over: &HashSet::new(),
+            extract_fields: ArticleField::ALL,
+            reask: true,
+            reask_with_retries: false,
+            input_header: Default::default(),
+            limits: Default::default(),
+            concurrency: &Default::default(),
+            adaptive: &Default::default(),
+            progress: &progress,
+            skips: &Default::default(),
+        };
+        let front_page: FrontPage = serde_json::from_str(FIXTURE_EDITION).unwrap();
+        let (front_page, reprocessed) = reprocess_front_page(&ctx, front_page, 4).await;
+
+        assert_eq!(reprocessed, 1);
+        // Only the article with stored content reached the model
+        assert_eq!(llm.server.received_requests().await.unwrap().len(), 1);
+        assert_eq!((front_page.local_date.as_str(), front_page.time_of_day.as_str()), ("2025-05-06", "morning"));
+
+        let talks = &front_page.articles[0];
+        assert_eq!(talks.title, "Talks resume");
+        assert_eq!(talks.summaryOfNewsArticle, "Summary of Talks resume.");
+        assert_eq!(talks.content.as_deref(), Some("FIXTURE-CNN Negotiators met again on Tuesday."));
+        // Edition fields survive the new summary
+        assert_eq!(talks.timeOfPublication, "09:15:00");
+        assert!(talks.overflow);
+
+        let kept = &front_page.articles[1];
+        assert_eq!(kept.title, "Stored without content");
+        assert_eq!(kept.summaryOfNewsArticle, "Kept as is.");
+    }
+}
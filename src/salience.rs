@@ -0,0 +1,223 @@
+//! Salience of each article and the edition's lede.
+//!
+//! After processing (and after `--max-per-category`), every article gets a
+//! salience score, stored as its `salience`:
+//!
+//! ```text
+//! salience = entities  * named entities
+//!          + related   * other articles sharing a named entity
+//!          + takeaways * key takeaways
+//!          + length    * thousands of characters of scraped text
+//!          + sources   * outlets covering the story
+//! ```
+//!
+//! The story of an article is the article with every article sharing a
+//! named entity with it; `sources` counts the distinct outlets among them,
+//! so a story carried by several outlets outranks one outlet's scoop.
+//!
+//! The weights default to [`SalienceWeights::default`] and are set with
+//! `--salience-weight NAME=WEIGHT` (`entities`, `related`, `takeaways`,
+//! `length`, `sources`).
+//!
+//! The highest-scoring article is the edition's lede ([`lede`]), rendered
+//! in full at the top of the Markdown. Ties go to the article earlier in
+//! the edition; "Also today" overflow and articles carried over from the
+//! previous run are never the lede, and an edition
+//! whose scores are all `0` (written before scoring) has none.
+
+use crate::models::AwfulNewsArticle;
+use std::collections::BTreeSet;
+
+/// Weights of the salience components.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SalienceWeights {
+    pub entities: f32,
+    pub related: f32,
+    pub takeaways: f32,
+    pub length: f32,
+    pub sources: f32,
+}
+
+impl Default for SalienceWeights {
+    fn default() -> Self {
+        Self {
+            entities: 0.5,
+            related: 3.0,
+            takeaways: 0.5,
+            length: 0.5,
+            sources: 2.0,
+        }
+    }
+}
+
+impl SalienceWeights {
+    /// The default weights with `overrides` applied in order.
+    pub fn with(overrides: &[(String, f32)]) -> Self {
+        let mut weights = Self::default();
+        for (name, weight) in overrides {
+            let slot = match name.as_str() {
+                "entities" => &mut weights.entities,
+                "related" => &mut weights.related,
+                "takeaways" => &mut weights.takeaways,
+                "length" => &mut weights.length,
+                "sources" => &mut weights.sources,
+                _ => continue,
+            };
+            *slot = *weight;
+        }
+        weights
+    }
+}
+
+/// Parse a `NAME=WEIGHT` pair from the command line.
+pub fn parse_salience_weight(raw: &str) -> Result<(String, f32), String> {
+    const NAMES: &[&str] = &["entities", "related", "takeaways", "length", "sources"];
+    let (name, weight) = raw
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=WEIGHT, got `{}`", raw))?;
+    let name = name.trim().to_lowercase();
+    if !NAMES.contains(&name.as_str()) {
+        return Err(format!("unknown salience weight `{}` (expected one of {})", name, NAMES.join(", ")));
+    }
+    let weight: f32 = weight
+        .trim()
+        .parse()
+        .map_err(|e| format!("invalid weight in `{}`: {}", raw, e))?;
+    if !weight.is_finite() || weight < 0.0 {
+        return Err(format!("weight must be a non-negative number in `{}`", raw));
+    }
+    Ok((name, weight))
+}
+
+/// Salience of every article, in edition order.
+pub fn salience_scores(articles: &[AwfulNewsArticle], weights: &SalienceWeights) -> Vec<f32> {
+    let names: Vec<BTreeSet<String>> = articles.iter().map(AwfulNewsArticle::entity_names).collect();
+    let tags: Vec<Option<String>> = articles.iter().map(AwfulNewsArticle::source_tag).collect();
+
+    names
+        .iter()
+        .enumerate()
+        .map(|(i, own)| {
+            let related: Vec<usize> = (0..articles.len())
+                .filter(|&j| j != i && !own.is_disjoint(&names[j]))
+                .collect();
+            let sources: BTreeSet<&str> = std::iter::once(i)
+                .chain(related.iter().copied())
+                .filter_map(|j| tags[j].as_deref())
+                .collect();
+            let article = &articles[i];
+            let kchars = article.content.as_deref().map_or(0, |c| c.chars().count()) as f32 / 1000.0;
+
+            weights.entities * own.len() as f32
+                + weights.related * related.len() as f32
+                + weights.takeaways * article.keyTakeAways.len() as f32
+                + weights.length * kchars
+                + weights.sources * sources.len() as f32
+        })
+        .collect()
+}
+
+/// Store the salience of every article as its `salience`.
+pub fn score(articles: &mut [AwfulNewsArticle], weights: &SalienceWeights) {
+    let scores = salience_scores(articles, weights);
+    for (article, salience) in articles.iter_mut().zip(scores) {
+        article.salience = salience;
+    }
+}
+
+/// Index of the edition's lede: the most salient article outside "Also
+/// today" and "In case you missed it", the earliest on ties; `None` if no
+/// article scores above `0`.
+pub fn lede(articles: &[AwfulNewsArticle]) -> Option<usize> {
+    let mut best: Option<usize> = None;
+    for (i, article) in articles.iter().enumerate() {
+        if article.overflow || article.carriedOver || article.salience <= 0.0 {
+            continue;
+        }
+        if best.is_none_or(|b| article.salience > articles[b].salience) {
+            best = Some(i);
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use crate::models::NamedEntity;
+
+    fn article(source: &str, title: &str, entities: &[&str], takeaways: usize, chars: usize) -> AwfulNewsArticle {
+        AwfulNewsArticle {
+            source: Some(format!("{}/{}", source, title.to_lowercase())),
+            summaryOfNewsArticle: format!("{} summary.", title),
+            keyTakeAways: (0..takeaways).map(|i| format!("Point {}", i)).collect(),
+            namedEntities: entities
+                .iter()
+                .map(|name| NamedEntity {
+                    name: name.to_string(),
+                    whatIsThisEntity: String::new(),
+                    whyIsThisEntityRelevantToTheArticle: String::new(),
+                    entityType: None,
+                })
+                .collect(),
+            content: Some("x".repeat(chars).into()),
+            ..test_support::article(title, "World News")
+        }
+    }
+
+    fn edition() -> Vec<AwfulNewsArticle> {
+        vec![
+            article("https://text.npr.org", "Probe", &["NASA"], 4, 4000),
+            article("https://lite.cnn.com", "Talks", &["Egypt", "Hamas"], 2, 2000),
+            article("https://www.bbc.com/news", "Aid", &["Egypt", "UN"], 1, 1000),
+            article("https://text.npr.org", "Strikes", &["Hamas"], 1, 1000),
+        ]
+    }
+
+    #[test]
+    fn test_salience_components_and_lede() {
+        let mut articles = edition();
+        score(&mut articles, &SalienceWeights::default());
+        let scores: Vec<f32> = articles.iter().map(|a| a.salience).collect();
+        // Talks: 2 entities, 2 related, 2 takeaways, 2k chars, 3 outlets (cnn, bbc, npr)
+        assert_eq!(scores, vec![6.5, 15.0, 9.0, 8.5]);
+        assert_eq!(lede(&articles), Some(1));
+
+        // Only takeaways count: the probe leads
+        let weights = SalienceWeights::with(&[
+            ("entities".to_string(), 0.0),
+            ("related".to_string(), 0.0),
+            ("length".to_string(), 0.0),
+            ("sources".to_string(), 0.0),
+        ]);
+        score(&mut articles, &weights);
+        assert_eq!(lede(&articles), Some(0));
+    }
+
+    #[test]
+    fn test_lede_is_stable_and_breaks_ties_by_edition_order() {
+        // Scoring again gives the same scores
+        let weights = SalienceWeights::default();
+        let mut articles = edition();
+        score(&mut articles, &weights);
+        assert_eq!(salience_scores(&articles, &weights), salience_scores(&edition(), &weights));
+
+        for article in &mut articles {
+            article.salience = 3.0;
+        }
+        assert_eq!(lede(&articles), Some(0));
+        articles[0].overflow = true;
+        assert_eq!(lede(&articles), Some(1));
+        // Unscored editions have no lede
+        assert_eq!(lede(&edition()), None);
+    }
+
+    #[test]
+    fn test_parse_salience_weight() {
+        assert_eq!(parse_salience_weight(" Related = 1.5"), Ok(("related".to_string(), 1.5)));
+        assert!(parse_salience_weight("related").is_err());
+        assert!(parse_salience_weight("recency=1").unwrap_err().starts_with("unknown salience weight"));
+        assert!(parse_salience_weight("length=-1").is_err());
+    }
+}
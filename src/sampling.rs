@@ -0,0 +1,175 @@
+//! Sampling of indexed articles down to a total limit.
+//!
+//! With `--max-articles N`, at most N of the indexed articles are fetched
+//! and summarized. Which ones is decided right after indexing, so the rest
+//! cost no requests:
+//!
+//! - without `--source-weights`, every indexed article is equally likely;
+//! - with `--source-weights cnn=3,npr=2,...`, each slot goes to a source
+//!   with probability proportional to its weight, then to a random article
+//!   of that source. Sources without a weight are left out. A source that
+//!   runs out of articles drops out and the others share its slots.
+//!
+//! Weights are keyed by source tag (see [`crate::scrapers::SCRAPERS`]).
+//! `--seed` makes the draw repeatable; without it every run samples
+//! differently. Articles that aren't drawn are recorded as
+//! [`SkipReason::NotSampled`].
+
+use crate::models::SkipReason;
+use crate::scrapers::parse_source_tag;
+use crate::skips::SkipLog;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Parse a `TAG=WEIGHT` pair from the command line.
+pub fn parse_source_weight(raw: &str) -> Result<(String, f64), String> {
+    let (tag, weight) = raw
+        .split_once('=')
+        .ok_or_else(|| format!("expected TAG=WEIGHT, got `{}`", raw))?;
+    let tag = parse_source_tag(tag)?;
+    let weight: f64 = weight
+        .trim()
+        .parse()
+        .map_err(|e| format!("invalid weight in `{}`: {}", raw, e))?;
+    if !weight.is_finite() || weight < 0.0 {
+        return Err(format!("weight must be a non-negative number in `{}`", raw));
+    }
+    Ok((tag, weight))
+}
+
+/// The random generator of a run: seeded with `seed` if given.
+pub fn rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
+    }
+}
+
+/// How many articles to take from each source.
+///
+/// `available` lists each source tag with its number of indexed articles;
+/// the result has one count per entry, in the same order, summing to at
+/// most `total`. With `weights`, each slot is drawn by source weight; without,
+/// by the articles each source has left.
+pub fn quotas(
+    available: &[(&str, usize)],
+    weights: Option<&[(String, f64)]>,
+    total: usize,
+    rng: &mut impl Rng,
+) -> Vec<usize> {
+    let weight_of = |tag: &str| match weights {
+        Some(weights) => weights.iter().filter(|(t, _)| t == tag).map(|(_, w)| *w).sum(),
+        None => 1.0,
+    };
+    let base: Vec<f64> = available.iter().map(|(tag, _)| weight_of(tag)).collect();
+    let mut taken = vec![0; available.len()];
+
+    for _ in 0..total {
+        // A source with weight 0 or no articles left can't win the slot
+        let slot_weights: Vec<f64> = available
+            .iter()
+            .zip(&base)
+            .zip(&taken)
+            .map(|(((_, n), w), t)| {
+                let left = n - t;
+                match (left, weights) {
+                    (0, _) => 0.0,
+                    (_, Some(_)) => *w,
+                    (left, None) => left as f64,
+                }
+            })
+            .collect();
+        let sum: f64 = slot_weights.iter().sum();
+        if sum <= 0.0 {
+            break;
+        }
+        let mut pick = rng.random_range(0.0..sum);
+        let winner = slot_weights
+            .iter()
+            .position(|w| {
+                pick -= w;
+                pick < 0.0 && *w > 0.0
+            })
+            .unwrap_or_else(|| slot_weights.iter().rposition(|w| *w > 0.0).unwrap());
+        taken[winner] += 1;
+    }
+    taken
+}
+
+/// Keep `n` randomly chosen `items`, in their original order; the rest are
+/// recorded in `skips` as not sampled.
+pub fn sample<T>(items: Vec<T>, n: usize, url: impl Fn(&T) -> &str, rng: &mut impl Rng, skips: &SkipLog) -> Vec<T> {
+    if n >= items.len() {
+        return items;
+    }
+    let chosen = rand::seq::index::sample(rng, items.len(), n).into_vec();
+    let mut keep = vec![false; items.len()];
+    chosen.into_iter().for_each(|i| keep[i] = true);
+    items
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(item, keep)| {
+            if !keep {
+                skips.record(url(&item), SkipReason::NotSampled);
+            }
+            keep.then_some(item)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weights(pairs: &[(&str, f64)]) -> Vec<(String, f64)> {
+        pairs.iter().map(|(t, w)| (t.to_string(), *w)).collect()
+    }
+
+    #[test]
+    fn test_parse_source_weight() {
+        assert_eq!(parse_source_weight(" CNN = 0.3 "), Ok(("cnn".to_string(), 0.3)));
+        assert!(parse_source_weight("cnn").is_err());
+        assert!(parse_source_weight("foxnews=1").unwrap_err().starts_with("unknown source `foxnews`"));
+        assert!(parse_source_weight("npr=-1").is_err());
+        assert!(parse_source_weight("npr=lots").is_err());
+    }
+
+    #[test]
+    fn test_quotas_follow_the_weights() {
+        let available = [("cnn", 5000), ("npr", 5000), ("bbc", 5000), ("apnews", 5000)];
+        let weights = weights(&[("cnn", 3.0), ("npr", 2.0), ("bbc", 5.0)]);
+        let quotas = quotas(&available, Some(&weights), 2000, &mut rng(Some(7)));
+        assert_eq!(quotas.iter().sum::<usize>(), 2000);
+        // Within 3 percentage points of 30/20/50%; unweighted sources get nothing
+        for (quota, share) in quotas.iter().zip([0.3, 0.2, 0.5, 0.0]) {
+            let actual = *quota as f64 / 2000.0;
+            assert!((actual - share).abs() < 0.03, "{:?}", quotas);
+        }
+    }
+
+    #[test]
+    fn test_quotas_are_repeatable_and_capped_by_availability() {
+        let available = [("cnn", 40), ("npr", 3), ("bbc", 0)];
+        let weights = weights(&[("cnn", 1.0), ("npr", 10.0), ("bbc", 10.0)]);
+        let first = quotas(&available, Some(&weights), 20, &mut rng(Some(42)));
+        assert_eq!(first, quotas(&available, Some(&weights), 20, &mut rng(Some(42))));
+        // NPR runs out; CNN takes the remaining slots
+        assert_eq!(first, vec![17, 3, 0]);
+
+        // Fewer articles than the limit: everything is kept
+        assert_eq!(quotas(&available, None, 100, &mut rng(Some(1))), vec![40, 3, 0]);
+    }
+
+    #[test]
+    fn test_sample_keeps_order_and_records_the_rest() {
+        let urls: Vec<String> = (0..10).map(|i| format!("https://text.npr.org/{}", i)).collect();
+        let skips = SkipLog::default();
+        let kept = sample(urls.clone(), 4, String::as_str, &mut rng(Some(3)), &skips);
+        assert_eq!(kept.len(), 4);
+        assert!(kept.windows(2).all(|w| urls.iter().position(|u| u == &w[0]) < urls.iter().position(|u| u == &w[1])));
+        assert_eq!(skips.entries().len(), 6);
+        assert!(skips.entries().iter().all(|s| s.reason == SkipReason::NotSampled && !kept.contains(&s.source)));
+
+        assert_eq!(sample(urls.clone(), 10, String::as_str, &mut rng(None), &skips), urls);
+    }
+}
@@ -0,0 +1,249 @@
+//! Selection of the article fields the LLM is asked to extract.
+//!
+//! The `news_parser` template's `response_format` carries a JSON schema for
+//! [`AwfulNewsArticle`]. Deployments that want lighter (cheaper) extraction
+//! can pass `--extract-fields` to keep only some of the optional fields; the
+//! others are removed from the schema's `properties` and `required` lists
+//! before any request is sent, and parse as their defaults.
+//!
+//! `title`, `category` and `summaryOfNewsArticle` are always extracted.
+
+use crate::models::AwfulNewsArticle;
+use awful_aj::template::ChatTemplate;
+use clap::ValueEnum;
+use serde_json::Value;
+use std::error::Error;
+use tracing::{info, warn};
+
+/// Optional article fields that can be selected for extraction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ArticleField {
+    /// `dateOfPublication`
+    DateOfPublication,
+    /// `timeOfPublication`
+    TimeOfPublication,
+    /// `keyTakeAways`
+    KeyTakeAways,
+    /// `namedEntities`
+    NamedEntities,
+    /// `importantDates`
+    ImportantDates,
+    /// `importantTimeframes`
+    ImportantTimeframes,
+    /// `tags`
+    Tags,
+    /// `locations`
+    Locations,
+    /// `authors`
+    Authors,
+}
+
+impl ArticleField {
+    /// Every optional field, in schema order.
+    pub const ALL: &'static [ArticleField] = &[
+        ArticleField::DateOfPublication,
+        ArticleField::TimeOfPublication,
+        ArticleField::KeyTakeAways,
+        ArticleField::NamedEntities,
+        ArticleField::ImportantDates,
+        ArticleField::ImportantTimeframes,
+        ArticleField::Tags,
+        ArticleField::Locations,
+        ArticleField::Authors,
+    ];
+
+    /// The JSON property name of the field.
+    pub fn json_name(self) -> &'static str {
+        match self {
+            ArticleField::DateOfPublication => "dateOfPublication",
+            ArticleField::TimeOfPublication => "timeOfPublication",
+            ArticleField::KeyTakeAways => "keyTakeAways",
+            ArticleField::NamedEntities => "namedEntities",
+            ArticleField::ImportantDates => "importantDates",
+            ArticleField::ImportantTimeframes => "importantTimeframes",
+            ArticleField::Tags => "tags",
+            ArticleField::Locations => "locations",
+            ArticleField::Authors => "authors",
+        }
+    }
+}
+
+/// Find the object schema describing an article (the one whose
+/// `properties` include `summaryOfNewsArticle`).
+fn article_schema_mut(value: &mut Value) -> Option<&mut serde_json::Map<String, Value>> {
+    let is_article = value
+        .get("properties")
+        .and_then(|p| p.get("summaryOfNewsArticle"))
+        .is_some();
+    if is_article {
+        return value.as_object_mut();
+    }
+    match value {
+        Value::Object(map) => map.values_mut().find_map(article_schema_mut),
+        Value::Array(items) => items.iter_mut().find_map(article_schema_mut),
+        _ => None,
+    }
+}
+
+/// Remove optional fields that are not in `fields` from an article schema.
+///
+/// # Returns
+///
+/// The names of the removed properties, or `None` if no article schema was
+/// found in `schema`.
+pub fn restrict_schema(schema: &mut Value, fields: &[ArticleField]) -> Option<Vec<&'static str>> {
+    let article = article_schema_mut(schema)?;
+    let disabled: Vec<&'static str> = ArticleField::ALL
+        .iter()
+        .filter(|f| !fields.contains(f))
+        .map(|f| f.json_name())
+        .collect();
+
+    if let Some(Value::Object(properties)) = article.get_mut("properties") {
+        for name in &disabled {
+            properties.remove(*name);
+        }
+    }
+    if let Some(Value::Array(required)) = article.get_mut("required") {
+        required.retain(|r| r.as_str().is_none_or(|r| !disabled.contains(&r)));
+    }
+    Some(disabled)
+}
+
+/// Restrict a chat template's response schema to the selected fields.
+///
+/// A template without a recognizable article schema is left unchanged with
+/// a warning.
+pub fn restrict_template(template: &mut ChatTemplate, fields: &[ArticleField]) -> Result<(), Box<dyn Error>> {
+    let mut response_format = serde_json::to_value(&template.response_format)?;
+    match restrict_schema(&mut response_format, fields) {
+        Some(disabled) => {
+            template.response_format = serde_json::from_value(response_format)?;
+            info!(?disabled, "Restricted extraction schema");
+        }
+        None => warn!("Template has no article schema; --extract-fields ignored"),
+    }
+    Ok(())
+}
+
+/// Reset fields the model returned even though they were not requested.
+///
+/// Example messages in the template can still coax the model into filling
+/// every field; clearing them keeps the output consistent with the schema.
+pub fn clear_disabled_fields(article: &mut AwfulNewsArticle, fields: &[ArticleField]) {
+    for field in ArticleField::ALL.iter().filter(|f| !fields.contains(f)) {
+        match field {
+            ArticleField::DateOfPublication => article.dateOfPublication.clear(),
+            ArticleField::TimeOfPublication => article.timeOfPublication.clear(),
+            ArticleField::KeyTakeAways => article.keyTakeAways.clear(),
+            ArticleField::NamedEntities => article.namedEntities.clear(),
+            ArticleField::ImportantDates => article.importantDates.clear(),
+            ArticleField::ImportantTimeframes => article.importantTimeframes.clear(),
+            ArticleField::Tags => article.tags.clear(),
+            ArticleField::Locations => article.locations.clear(),
+            ArticleField::Authors => article.authors.clear(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn response_format() -> Value {
+        json!({
+            "name": "article_summarizer",
+            "strict": true,
+            "schema": {
+                "type": "object",
+                "properties": {
+                    "title": { "type": "string" },
+                    "dateOfPublication": { "type": "string" },
+                    "timeOfPublication": { "type": "string" },
+                    "category": { "type": "string" },
+                    "summaryOfNewsArticle": { "type": "string" },
+                    "keyTakeAways": { "type": "array" },
+                    "namedEntities": { "type": "array" },
+                    "importantDates": { "type": "array" },
+                    "importantTimeframes": { "type": "array" },
+                    "tags": { "type": "array" },
+                    "locations": { "type": "array" },
+                    "authors": { "type": "array" }
+                },
+                "required": [
+                    "title", "dateOfPublication", "timeOfPublication", "category",
+                    "summaryOfNewsArticle", "keyTakeAways", "namedEntities",
+                    "importantDates", "importantTimeframes", "tags", "locations", "authors"
+                ]
+            }
+        })
+    }
+
+    #[test]
+    fn test_restrict_schema_keeps_selected_and_core_fields() {
+        let mut schema = response_format();
+        let disabled = restrict_schema(&mut schema, &[ArticleField::KeyTakeAways]).unwrap();
+        assert_eq!(disabled.len(), ArticleField::ALL.len() - 1);
+
+        let properties = schema["schema"]["properties"].as_object().unwrap();
+        let mut names: Vec<&str> = properties.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["category", "keyTakeAways", "summaryOfNewsArticle", "title"]);
+        assert_eq!(
+            schema["schema"]["required"],
+            json!(["title", "category", "summaryOfNewsArticle", "keyTakeAways"])
+        );
+    }
+
+    #[test]
+    fn test_restrict_schema_with_all_fields_is_a_no_op() {
+        let mut schema = response_format();
+        restrict_schema(&mut schema, ArticleField::ALL).unwrap();
+        assert_eq!(schema, response_format());
+    }
+
+    #[test]
+    fn test_restrict_schema_without_article_schema() {
+        let mut schema = json!({ "name": "other", "schema": { "properties": { "x": {} } } });
+        assert!(restrict_schema(&mut schema, &[]).is_none());
+    }
+
+    #[test]
+    fn test_response_omitting_disabled_fields_parses() {
+        let response = r#"{
+            "title": "Rates held steady",
+            "category": "Economy & Business",
+            "summaryOfNewsArticle": "The central bank held rates.",
+            "keyTakeAways": ["No change this quarter"]
+        }"#;
+        let article: AwfulNewsArticle = serde_json::from_str(response).unwrap();
+        assert_eq!(article.keyTakeAways, vec!["No change this quarter"]);
+        assert!(article.dateOfPublication.is_empty());
+        assert!(article.timeOfPublication.is_empty());
+        assert!(article.namedEntities.is_empty());
+        assert!(article.importantDates.is_empty());
+        assert!(article.importantTimeframes.is_empty());
+        assert!(article.tags.is_empty());
+        assert!(article.locations.is_empty());
+        assert!(article.authors.is_empty());
+    }
+
+    #[test]
+    fn test_response_missing_core_field_fails() {
+        let response = r#"{ "title": "No summary", "category": "Economy & Business" }"#;
+        assert!(serde_json::from_str::<AwfulNewsArticle>(response).is_err());
+    }
+
+    #[test]
+    fn test_clear_disabled_fields() {
+        let response = r#"{
+            "title": "T", "category": "C", "summaryOfNewsArticle": "S",
+            "tags": ["a"], "keyTakeAways": ["k"]
+        }"#;
+        let mut article: AwfulNewsArticle = serde_json::from_str(response).unwrap();
+        clear_disabled_fields(&mut article, &[ArticleField::KeyTakeAways]);
+        assert!(article.tags.is_empty());
+        assert_eq!(article.keyTakeAways, vec!["k"]);
+    }
+}
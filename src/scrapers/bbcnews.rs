@@ -15,32 +15,23 @@
 //! 2. Any anchor links matching the article URL pattern
 //! 3. Regex fallback on raw HTML
 
-use crate::models::NewsArticle;
+use crate::models::{NewsArticle, SkipReason};
+use crate::scrapers::budget::ArticleSink;
+use crate::scrapers::http::CLIENT;
+use crate::scrapers::{boilerplate, http, meta, paywall, selectors};
+use crate::skips::SkipLog;
+use chrono::Utc;
 use futures::stream::{self, StreamExt};
-use once_cell::sync::Lazy;
-use reqwest::{Client, Url};
-use scraper::{ElementRef, Html, Selector};
+use reqwest::Url;
+use scraper::{Html, Selector};
 use std::error::Error;
-use std::time::Duration;
 use tracing::{debug, error, info, instrument, warn};
 
-use chrono::{DateTime, FixedOffset};
-use serde::Deserialize;
-
-/// Global HTTP client with browser-like User-Agent and sensible timeouts.
-static CLIENT: Lazy<Client> = Lazy::new(|| {
-    Client::builder()
-        .user_agent(concat!(
-            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) ",
-            "AppleWebKit/537.36 (KHTML, like Gecko) ",
-            "Chrome/127.0.0.0 Safari/537.36"
-        ))
-        .timeout(Duration::from_secs(20))
-        .pool_idle_timeout(Duration::from_secs(10))
-        .redirect(reqwest::redirect::Policy::limited(10))
-        .build()
-        .expect("failed to build reqwest client")
-});
+/// Elements holding a human-readable date when no timestamp is published.
+const PUBLISHED_TEXT_SELECTOR: &str = r#"[data-testid="timestamp"], time"#;
+
+/// The visible byline.
+const BYLINE_SELECTOR: &str = r#"[data-testid="byline-new"], [data-testid="byline"]"#;
 
 const SECTION_URLS: &[&str] = &[
     // BBC News homepage as the single “section” to pull ~20 article URLs
@@ -53,49 +44,52 @@ pub async fn index_articles() -> Result<Vec<String>, Box<dyn Error>> {
     let mut all = Vec::<String>::new();
 
     for section in SECTION_URLS {
-        let res = CLIENT.get(*section).send().await?;
-        let final_url = res.url().to_string();
-        let html = res.text().await?;
-        let document = Html::parse_document(&html);
-
-        // Primary: the anchors shown in your snippet
-        let sel_internal = Selector::parse(r#"a[data-testid="internal-link"][href]"#).unwrap();
-        // Fallback: any anchors
-        let sel_any_a = Selector::parse(r#"a[href]"#).unwrap();
-
-        let mut urls = Vec::<String>::new();
-
-        // 1) Strict selector first
-        harvest_selector_bbc(&document, &sel_internal, &mut urls);
-
-        // 2) Fallback: any anchors that look like BBC /news/articles/<id>
-        if urls.len() < 20 {
-            for a in document.select(&sel_any_a) {
-                if let Some(href) = a.value().attr("href") {
-                    if let Some(u) = normalize_bbc_link(href) {
-                        if is_bbc_article_url(&u) && !urls.contains(&u) {
-                            urls.push(u);
-                            if urls.len() >= 20 { break; }
+        let urls = http::cached_index(&CLIENT, "bbc", section, |html, final_url| {
+            let document = Html::parse_document(html);
+
+            // Primary: the configured article links
+            let sel_internal = &selectors::get("bbc").index;
+            // Fallback: any anchors
+            let sel_any_a = Selector::parse(r#"a[href]"#).unwrap();
+
+            let mut urls = Vec::<String>::new();
+
+            // 1) Strict selector first
+            for sel in sel_internal {
+                harvest_selector_bbc(&document, sel, &mut urls);
+            }
+
+            // 2) Fallback: any anchors that look like BBC /news/articles/<id>
+            if urls.len() < 20 {
+                for a in document.select(&sel_any_a) {
+                    if let Some(href) = a.value().attr("href") {
+                        if let Some(u) = normalize_bbc_link(href) {
+                            if is_bbc_article_url(&u) && !urls.contains(&u) {
+                                urls.push(u);
+                                if urls.len() >= 20 { break; }
+                            }
                         }
                     }
                 }
             }
-        }
 
-        // 3) Regex fallback from raw HTML
-        if urls.len() < 20 {
-            let mut more = harvest_regex_fallback_bbc(&html);
-            for u in more.drain(..) {
-                if !urls.contains(&u) {
-                    urls.push(u);
-                    if urls.len() >= 20 { break; }
+            // 3) Regex fallback from raw HTML
+            if urls.len() < 20 {
+                let mut more = harvest_regex_fallback_bbc(html);
+                for u in more.drain(..) {
+                    if !urls.contains(&u) {
+                        urls.push(u);
+                        if urls.len() >= 20 { break; }
+                    }
                 }
             }
-        }
 
-        if urls.is_empty() {
-            dump_bbc_debug(*section, &document, &html, &final_url);
-        }
+            if urls.is_empty() {
+                dump_bbc_debug(*section, &document, html, final_url);
+            }
+            urls
+        })
+        .await?;
 
         info!(section = *section, count = urls.len(), "Indexed BBC section URLs");
         debug!(?urls, "BBC URLs");
@@ -182,10 +176,10 @@ fn is_bbc_article_url(u: &str) -> bool {
 
 /// Fetch all BBC articles concurrently
 #[instrument(level = "info", skip_all)]
-pub async fn fetch_articles(urls: Vec<String>) -> Vec<NewsArticle> {
+pub async fn fetch_articles(urls: Vec<String>, sink: &ArticleSink, skips: &SkipLog) {
     let concurrency = 8usize;
 
-    let articles: Vec<NewsArticle> = stream::iter(urls.into_iter())
+    stream::iter(urls.into_iter())
         .map(|url| async move {
             let res = fetch_article(&url).await;
             (url, res)
@@ -199,19 +193,23 @@ pub async fn fetch_articles(urls: Vec<String>) -> Vec<NewsArticle> {
                 }
                 Ok(None) => {
                     warn!(%url, "BBC fetch produced no content");
+                    skips.record(&url, SkipReason::EmptyContent);
                     None
                 }
                 Err(e) => {
                     error!(error = %e, %url, "BBC fetch failed");
+                    skips.record_fetch_error(&url, e.as_ref());
                     None
                 }
             }
         })
-        .collect()
+        .for_each(|article| {
+            sink.lock().unwrap().push(article);
+            std::future::ready(())
+        })
         .await;
 
-    info!(count = articles.len(), "Fetched BBC article contents");
-    articles
+    info!(count = sink.lock().unwrap().len(), "Fetched BBC article contents");
 }
 
 /// Fetch a single BBC article
@@ -224,45 +222,26 @@ async fn fetch_article(url: &str) -> Result<Option<NewsArticle>, Box<dyn Error>>
         return Ok(None);
     }
 
-    let body = CLIENT.get(url).send().await?.text().await?;
+    let body = http::fetch_text("bbc", url).await?;
     let document = Html::parse_document(&body);
 
-    // ----- PUBLISHED AT (robust) -----
-    let (published_dt, published_raw, published_src) = extract_published_at(&document);
-    if let Some(ref raw) = published_raw {
-        info!(
-            source = published_src,
-            raw = %raw,
-            iso = published_dt
-                .as_ref()
-                .map(|d| d.to_rfc3339())
-                .unwrap_or_else(|| "n/a".into()),
-            "Published-at parsed"
-        );
-    } else {
-        info!("Published-at parsed source=none");
-    }
+    // ----- PUBLISHED AT -----
+    let published_at = meta::extract_published(&document);
 
     // ----- TITLE -----
     let title = meta_content(&document, r#"meta[property="og:title"]"#, "content")
-        .or_else(|| text_of_first(&document, r#"h1[data-testid="headline"]"#))
-        .or_else(|| text_of_first(&document, "h1"))
+        .or_else(|| selectors::first_text(&selectors::get("bbc").headline, &document))
         .unwrap_or_default();
 
     // ----- CONTENT EXTRACTION -----
-    let candidates = [
-        r#"main div[data-component="text-block"] p"#,
-        r#"article div[data-component="text-block"] p"#,
-        r#"article p"#,
-        r#"main p"#,
-    ];
+    let candidates = &selectors::get("bbc").article;
 
     let mut content = String::new();
     let mut found = false;
 
-    for sel in candidates.iter().filter_map(|s| Selector::parse(s).ok()) {
+    for sel in candidates {
         let mut parts = Vec::<String>::new();
-        for node in document.select(&sel) {
+        for node in document.select(sel) {
             let text = node.text().collect::<Vec<_>>().join(" ").trim().to_string();
             if !text.is_empty() {
                 parts.push(text);
@@ -279,20 +258,27 @@ async fn fetch_article(url: &str) -> Result<Option<NewsArticle>, Box<dyn Error>>
     if !title.is_empty() {
         content = format!("Title: {}\n\n{}", title, content);
     }
-    if let Some(dt) = published_dt {
+    if let Some(dt) = published_at {
         content = format!("Published: {}\n\n{}", dt.to_rfc3339(), content);
-    } else if let Some(raw) = published_raw {
+    } else if let Some(raw) = meta::published_text(&document, PUBLISHED_TEXT_SELECTOR) {
         content = format!("Published(raw): {}\n\n{}", raw, content);
     }
 
+    let content = boilerplate::strip("bbc", &content);
+    paywall::check(&document, &content)?;
     let len = content.len();
     info!(bytes = len, "Parsed BBC article");
 
     if found && len > 0 {
         Ok(Some(NewsArticle {
             source: url.to_string(),
-            content,
-        }))
+            content: content.into(),
+            published_at,
+            authors: meta::extract_authors(&document, Some(BYLINE_SELECTOR)),
+            fetched_at: Some(Utc::now()),
+            fetched_from: None,
+        }
+        .with_canonical(meta::extract_canonical(&document, url))))
     } else {
         debug!(
             preview = %body.chars().take(600).collect::<String>().replace('\n', " "),
@@ -302,145 +288,6 @@ async fn fetch_article(url: &str) -> Result<Option<NewsArticle>, Box<dyn Error>>
     }
 }
 
-/* -------------------- DATE HELPERS -------------------- */
-
-fn looks_like_placeholder(s: &str) -> bool {
-    let t = s.trim();
-    t.contains('[') && t.contains(']')
-}
-
-fn clean(s: &str) -> String {
-    s.split_whitespace().collect::<Vec<_>>().join(" ")
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct LdArticle {
-    #[serde(default)]
-    date_published: Option<String>,
-    #[serde(default)]
-    date_modified: Option<String>,
-}
-
-fn parse_rfc3339(s: &str) -> Option<DateTime<FixedOffset>> {
-    DateTime::parse_from_rfc3339(s).ok()
-}
-
-/// Extract (published_iso, raw_string, source_hint)
-fn extract_published_at(document: &Html) -> (Option<DateTime<FixedOffset>>, Option<String>, &'static str) {
-    // A) JSON-LD blocks
-    if let Ok(sel) = Selector::parse(r#"script[type="application/ld+json"]"#) {
-        for script in document.select(&sel) {
-            if let Some(js) = script
-                .first_child()
-                .and_then(|n| n.value().as_text())
-                .map(|t| t.to_string())
-            {
-                let txt = js.trim();
-                if let Ok(v) = serde_json::from_str::<serde_json::Value>(txt) {
-                    if let Some((dt, raw)) = scan_jsonld_value(&v) {
-                        let raw_clean = clean(&raw);
-                        if !looks_like_placeholder(&raw_clean) {
-                            if let Some(dt) = parse_rfc3339(&dt) {
-                                return (Some(dt), Some(raw_clean), "jsonld");
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    // B) Meta properties
-    for css in &[
-        r#"meta[property="article:published_time"]"#,
-        r#"meta[name="OriginalPublicationDate"]"#,
-        r#"meta[itemprop="datePublished"]"#,
-        r#"meta[property="og:updated_time"]"#,
-        r#"meta[name="Last-Modified"]"#,
-    ] {
-        if let Some((raw, _)) = first_meta(document, css, "content") {
-            let raw = clean(&raw);
-            if !looks_like_placeholder(&raw) {
-                if let Some(dt) = parse_rfc3339(&raw) {
-                    return (Some(dt), Some(raw), css);
-                }
-            }
-        }
-    }
-
-    // C) <time datetime="...">
-    if let Ok(sel) = Selector::parse(r#"time[datetime]"#) {
-        if let Some(t) = document.select(&sel).next() {
-            if let Some(raw) = t.value().attr("datetime").map(|s| clean(s)) {
-                if !looks_like_placeholder(&raw) {
-                    if let Some(dt) = parse_rfc3339(&raw) {
-                        return (Some(dt), Some(raw), "time[datetime]");
-                    }
-                }
-            }
-        }
-    }
-
-    // D) Textual fallback
-    if let Ok(sel) = Selector::parse(r#"[data-testid="timestamp"], time"#) {
-        if let Some(el) = document.select(&sel).next() {
-            let raw = clean(&el.text().collect::<String>());
-            if !raw.is_empty() && !looks_like_placeholder(&raw) {
-                return (None, Some(raw), "textual");
-            }
-        }
-    }
-
-    (None, None, "none")
-}
-
-fn first_meta<'a>(document: &'a Html, css: &str, attr: &str) -> Option<(String, ElementRef<'a>)> {
-    let sel = Selector::parse(css).ok()?;
-    let n = document.select(&sel).next()?;
-    let v = n.value().attr(attr)?.to_string();
-    Some((v, n))
-}
-
-fn scan_jsonld_value(v: &serde_json::Value) -> Option<(String, String)> {
-    match v {
-        serde_json::Value::Array(arr) => {
-            for item in arr {
-                if let Some(p) = pick_date_from_ld(item) {
-                    return Some(p);
-                }
-            }
-            None
-        }
-        _ => pick_date_from_ld(v),
-    }
-}
-
-fn pick_date_from_ld(v: &serde_json::Value) -> Option<(String, String)> {
-    // Prefer Article-ish types but don’t require @type
-    let is_article = v
-        .get("@type")
-        .and_then(|t| t.as_str())
-        .map(|t| matches!(t, "NewsArticle" | "Article" | "Report" | "BlogPosting"))
-        .unwrap_or(true);
-
-    if is_article {
-        if let Some(raw) = v.get("datePublished").and_then(|x| x.as_str()).map(|s| s.to_string()) {
-            return Some((raw.clone(), raw));
-        }
-        if let Some(raw) = v.get("dateModified").and_then(|x| x.as_str()).map(|s| s.to_string()) {
-            return Some((raw.clone(), raw));
-        }
-    }
-    if let Some(obj) = v.get("article") {
-        if let Some(raw) = obj.get("datePublished").and_then(|x| x.as_str()).map(|s| s.to_string())
-        {
-            return Some((raw.clone(), raw));
-        }
-    }
-    None
-}
-
 /* -------------------- DEBUG (optional) -------------------- */
 
 fn dump_bbc_debug(section: &str, document: &Html, html: &str, final_url: &str) {
@@ -465,12 +312,6 @@ fn dump_bbc_debug(section: &str, document: &Html, html: &str, final_url: &str) {
 
 /* -------------------- MISC HELPERS -------------------- */
 
-fn text_of_first(document: &Html, css: &str) -> Option<String> {
-    let sel = Selector::parse(css).ok()?;
-    let n = document.select(&sel).next()?;
-    Some(n.text().collect::<Vec<_>>().join(" ").trim().to_string())
-}
-
 fn meta_content(document: &Html, css: &str, attr: &str) -> Option<String> {
     let sel = Selector::parse(css).ok()?;
     let n = document.select(&sel).next()?;
@@ -0,0 +1,222 @@
+//! Per-source boilerplate stripping for scraped article text.
+//!
+//! Extracted text often carries navigation crumbs, "Read more" links and
+//! newsletter calls to action that the LLM would otherwise spend tokens on.
+//! Every scraper passes its extracted text through [`strip`] before building
+//! the [`NewsArticle`](crate::models::NewsArticle), so the cleaned text is
+//! what gets stored in `content`.
+//!
+//! # Rules
+//!
+//! Each source has a list of literal `phrases` (matched case-insensitively)
+//! and regex `patterns`; every match is removed. Defaults ship for CNN and
+//! NPR ([`default_rules`]). With `--boilerplate-file`, a YAML file adds
+//! rules per source tag:
+//!
+//! ```yaml
+//! cnn:
+//!   phrases:
+//!     - "Watch the full story"
+//! apnews:
+//!   patterns:
+//!     - "(?i)download the ap news app[^.]*\\."
+//! ```
+//!
+//! Invalid patterns are skipped with a warning.
+
+use once_cell::sync::OnceCell;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use tracing::{debug, info, warn};
+
+/// Boilerplate rules for one source, as written in the rules file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SourceRules {
+    /// Literal phrases to remove (case-insensitive).
+    pub phrases: Vec<String>,
+    /// Regular expressions whose matches are removed.
+    pub patterns: Vec<String>,
+}
+
+/// Compiled rules for one source.
+#[derive(Debug, Default)]
+struct CompiledRules {
+    regexes: Vec<Regex>,
+}
+
+impl CompiledRules {
+    fn extend(&mut self, source: &str, rules: &SourceRules) {
+        let phrases = rules.phrases.iter().map(|p| format!("(?i){}", regex::escape(p)));
+        for pattern in phrases.chain(rules.patterns.iter().cloned()) {
+            match Regex::new(&pattern) {
+                Ok(re) => self.regexes.push(re),
+                Err(e) => warn!(source, %pattern, error = %e, "Invalid boilerplate pattern; skipping"),
+            }
+        }
+    }
+}
+
+/// Built-in rules for sources with known boilerplate.
+pub fn default_rules() -> BTreeMap<String, SourceRules> {
+    let rules = |phrases: &[&str], patterns: &[&str]| SourceRules {
+        phrases: phrases.iter().map(|s| s.to_string()).collect(),
+        patterns: patterns.iter().map(|s| s.to_string()).collect(),
+    };
+    BTreeMap::from([
+        (
+            "cnn".to_string(),
+            rules(
+                &["See Full Web Article", "Go to the full CNN experience"],
+                &[
+                    r"(?i)©\s*\d{4}\s*Cable News Network\.?\s*A Warner Bros\. Discovery Company\.?\s*All Rights Reserved\.?",
+                    r"(?i)CNN Sans\s*™\s*&\s*©\s*\d{4}\s*Cable News Network\.?",
+                    r"(?i)Sign up for CNN['’]s [^.\n]{0,80}newsletter[^.\n]*\.?",
+                ],
+            ),
+        ),
+        (
+            "npr".to_string(),
+            rules(
+                &["Transcript provided by NPR, Copyright NPR.", "Go to the full NPR experience"],
+                &[
+                    r"(?i)Copyright\s*\d{4}\s*NPR\.?",
+                    r"(?i)NPR\s*:\s*National Public Radio",
+                    r"(?i)Sign up for [^.\n]{0,80}newsletter[^.\n]*\.?",
+                ],
+            ),
+        ),
+    ])
+}
+
+static RULES: OnceCell<BTreeMap<String, CompiledRules>> = OnceCell::new();
+
+fn compile(configured: BTreeMap<String, SourceRules>) -> BTreeMap<String, CompiledRules> {
+    let mut compiled: BTreeMap<String, CompiledRules> = BTreeMap::new();
+    for (source, rules) in default_rules().into_iter().chain(configured) {
+        compiled.entry(source.clone()).or_default().extend(&source, &rules);
+    }
+    compiled
+}
+
+/// Load additional rules from a YAML file on top of the defaults.
+///
+/// A missing or unparsable file is logged and the defaults are used alone.
+/// Must be called before the first [`strip`] to take effect.
+pub async fn init(path: Option<&str>) {
+    let mut configured = BTreeMap::new();
+    if let Some(path) = path {
+        match tokio::fs::read_to_string(path).await {
+            Ok(raw) => match serde_yaml::from_str::<BTreeMap<String, SourceRules>>(&raw) {
+                Ok(rules) => {
+                    info!(path, sources = rules.len(), "Loaded boilerplate rules");
+                    configured = rules;
+                }
+                Err(e) => warn!(path, error = %e, "Boilerplate rules file is invalid; using defaults"),
+            },
+            Err(e) => warn!(path, error = %e, "Failed to read boilerplate rules; using defaults"),
+        }
+    }
+    let _ = RULES.set(compile(configured));
+}
+
+fn strip_with(rules: Option<&CompiledRules>, text: &str) -> String {
+    let Some(rules) = rules.filter(|r| !r.regexes.is_empty()) else {
+        return text.to_string();
+    };
+    let mut cleaned = text.to_string();
+    for re in &rules.regexes {
+        cleaned = re.replace_all(&cleaned, " ").into_owned();
+    }
+
+    // Tidy the gaps left behind without disturbing paragraph breaks
+    let lines: Vec<String> = cleaned
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect();
+    let mut out = String::with_capacity(cleaned.len());
+    // Starting "inside" a blank run drops leading blank lines
+    let mut blank_run = 1;
+    for line in lines {
+        if line.is_empty() {
+            blank_run += 1;
+            if blank_run > 1 {
+                continue;
+            }
+        } else {
+            blank_run = 0;
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    while out.ends_with("\n\n") {
+        out.pop();
+    }
+    out
+}
+
+/// Remove the boilerplate configured for `source` (a source tag such as
+/// `"cnn"`) from extracted article text.
+pub fn strip(source: &str, text: &str) -> String {
+    let rules = RULES.get_or_init(|| compile(BTreeMap::new()));
+    let cleaned = strip_with(rules.get(source), text);
+    if cleaned.len() < text.len() {
+        debug!(source, removed_bytes = text.len() - cleaned.len(), "Stripped boilerplate");
+    }
+    cleaned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CNN_FIXTURE: &str = "Talks resume in Geneva\n\
+Negotiators met again on Tuesday. See Full Web Article Officials said progress was slow. \
+Sign up for CNN’s Meanwhile in America newsletter. \
+© 2025 Cable News Network. A Warner Bros. Discovery Company. All Rights Reserved. \
+CNN Sans ™ & © 2016 Cable News Network.\n";
+
+    const NPR_FIXTURE: &str = "NPR : National Public Radio\n\
+A new probe was launched toward the outer planets.\n\n\n\
+Copyright 2025 NPR\n";
+
+    #[test]
+    fn test_cnn_defaults_strip_known_boilerplate() {
+        let rules = compile(BTreeMap::new());
+        let cleaned = strip_with(rules.get("cnn"), CNN_FIXTURE);
+        assert_eq!(
+            cleaned,
+            "Talks resume in Geneva\nNegotiators met again on Tuesday. Officials said progress was slow.\n"
+        );
+    }
+
+    #[test]
+    fn test_npr_defaults_strip_known_boilerplate() {
+        let rules = compile(BTreeMap::new());
+        let cleaned = strip_with(rules.get("npr"), NPR_FIXTURE);
+        assert_eq!(cleaned, "A new probe was launched toward the outer planets.\n");
+    }
+
+    #[test]
+    fn test_source_without_rules_is_unchanged() {
+        let text = "Read more about it.\n\n  Spacing   kept.";
+        assert_eq!(strip_with(None, text), text);
+    }
+
+    #[test]
+    fn test_configured_rules_extend_defaults() {
+        let configured: BTreeMap<String, SourceRules> = serde_yaml::from_str(
+            "cnn:\n  phrases: [\"Watch the full story\"]\napnews:\n  patterns: [\"(?i)download the ap news app[^.]*\\\\.\"]\n  phrases: []\nbbc:\n  patterns: [\"([unclosed\"]\n",
+        )
+        .unwrap();
+        let compiled = compile(configured);
+
+        let cnn = strip_with(compiled.get("cnn"), "A. WATCH THE FULL STORY B. See Full Web Article");
+        assert_eq!(cnn, "A. B.\n");
+        let ap = strip_with(compiled.get("apnews"), "Lead. Download the AP News app today. Rest.");
+        assert_eq!(ap, "Lead. Rest.\n");
+        // The invalid pattern is skipped rather than failing
+        assert!(compiled.get("bbc").unwrap().regexes.is_empty());
+    }
+}
@@ -0,0 +1,195 @@
+//! Per-source time budget for indexing and fetching.
+//!
+//! One slow host (the NYT proxy especially) can otherwise stall a run for
+//! minutes. With `--source-budget-secs`, each source gets a fixed amount of
+//! wall-clock time for its index request and its article fetches together.
+//! The time spent indexing is deducted from the budget and the fetch phase
+//! gets the remainder.
+//!
+//! Scrapers push each fetched article to an [`ArticleSink`] as soon as it
+//! is ready, so when the budget runs out mid-fetch the articles already
+//! fetched are kept and the run moves on to the next source. An exhausted
+//! budget is logged and published as `fetching.source_timeout`, and the URLs
+//! it left unfetched are recorded as [`SkipReason::Budget`].
+
+use crate::models::{NewsArticle, SkipReason};
+use crate::skips::SkipLog;
+use std::error::Error;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::{timeout, Instant};
+use tracing::warn;
+
+/// Collects a source's articles as they are fetched.
+pub type ArticleSink = Mutex<Vec<NewsArticle>>;
+
+/// Remaining time of one source.
+#[derive(Debug)]
+pub struct SourceBudget {
+    source: &'static str,
+    remaining: Option<Duration>,
+}
+
+impl SourceBudget {
+    /// Start the budget of `source`; `None` means unlimited.
+    pub fn new(source: &'static str, secs: Option<u64>) -> Self {
+        SourceBudget {
+            source,
+            remaining: secs.map(Duration::from_secs),
+        }
+    }
+
+    /// Run `fut` within the remaining budget and deduct the time it took.
+    ///
+    /// Returns `None` if the budget ran out first.
+    async fn spend<F: Future>(&mut self, fut: F) -> Option<F::Output> {
+        let Some(remaining) = self.remaining else {
+            return Some(fut.await);
+        };
+        let started = Instant::now();
+        let output = timeout(remaining, fut).await.ok();
+        self.remaining = Some(remaining.saturating_sub(started.elapsed()));
+        output
+    }
+
+    /// Index the source within the budget.
+    ///
+    /// An exhausted budget yields no URLs rather than an error.
+    pub async fn index<T, F>(&mut self, fut: F) -> Result<Vec<T>, Box<dyn Error>>
+    where
+        F: Future<Output = Result<Vec<T>, Box<dyn Error>>>,
+    {
+        match self.spend(fut).await {
+            Some(result) => result,
+            None => {
+                self.exhausted("index", 0);
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    /// Run the source's fetch of `urls` (which pushes into `sink`) within
+    /// the remaining budget and return whatever it fetched.
+    ///
+    /// If the budget runs out, the URLs neither fetched nor already skipped
+    /// are recorded in `skips`.
+    pub async fn fetch<S, F>(&mut self, sink: &ArticleSink, skips: &SkipLog, urls: &[S], fut: F) -> Vec<NewsArticle>
+    where
+        S: AsRef<str>,
+        F: Future<Output = ()>,
+    {
+        let finished = self.spend(fut).await.is_some();
+        let articles = std::mem::take(&mut *sink.lock().unwrap());
+        if !finished {
+            self.exhausted("fetch", articles.len());
+            for url in urls.iter().map(AsRef::as_ref) {
+                if !articles.iter().any(|a| a.fetched_url() == url) && !skips.contains(url) {
+                    skips.record(url, SkipReason::Budget);
+                }
+            }
+        }
+        articles
+    }
+
+    fn exhausted(&self, phase: &'static str, kept: usize) {
+        warn!(source = self.source, phase, kept, "Source time budget exhausted; moving on");
+        crate::publish_info!(
+            "awful_text_news",
+            event_kind = "fetching.source_timeout",
+            source = self.source,
+            phase = phase,
+            kept = kept,
+            "Source time budget exhausted"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::stream::{self, StreamExt};
+
+    fn article(source: &str) -> NewsArticle {
+        NewsArticle {
+            source: source.to_string(),
+            content: format!("Content of {}", source).into(),
+            published_at: None,
+            authors: vec![],
+            fetched_at: None,
+            fetched_from: None,
+        }
+    }
+
+    /// A scraper that takes `secs_each` seconds per article.
+    async fn slow_fetch(urls: Vec<&str>, secs_each: u64, sink: &ArticleSink) {
+        stream::iter(urls)
+            .then(|url| async move {
+                tokio::time::sleep(Duration::from_secs(secs_each)).await;
+                article(url)
+            })
+            .for_each(|article| {
+                sink.lock().unwrap().push(article);
+                std::future::ready(())
+            })
+            .await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_fetch_keeps_articles_fetched_before_budget_runs_out() {
+        let mut budget = SourceBudget::new("nyt", Some(25));
+        let sink = ArticleSink::default();
+        let skips = SkipLog::default();
+        let urls = vec!["https://nytimes.com/1", "https://nytimes.com/2", "https://nytimes.com/3"];
+
+        let started = Instant::now();
+        let articles = budget.fetch(&sink, &skips, &urls, slow_fetch(urls.clone(), 10, &sink)).await;
+        assert_eq!(started.elapsed(), Duration::from_secs(25));
+        let sources: Vec<&str> = articles.iter().map(|a| a.source.as_str()).collect();
+        assert_eq!(sources, vec!["https://nytimes.com/1", "https://nytimes.com/2"]);
+        // Only the article the budget cut off is recorded
+        let skipped = skips.entries();
+        assert_eq!(skipped.len(), 1);
+        assert_eq!((skipped[0].source.as_str(), skipped[0].reason), ("https://nytimes.com/3", SkipReason::Budget));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_index_time_is_deducted_from_fetch() {
+        let mut budget = SourceBudget::new("cnn", Some(30));
+        let urls = budget
+            .index(async {
+                tokio::time::sleep(Duration::from_secs(15)).await;
+                Ok(vec!["https://lite.cnn.com/1", "https://lite.cnn.com/2"])
+            })
+            .await
+            .unwrap();
+
+        let sink = ArticleSink::default();
+        let articles = budget.fetch(&sink, &SkipLog::default(), &urls, slow_fetch(urls.clone(), 10, &sink)).await;
+        assert_eq!(articles.len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_exhausted_index_yields_no_urls() {
+        let mut budget = SourceBudget::new("bbcnews", Some(5));
+        let urls: Vec<String> = budget
+            .index(async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                Ok(vec!["https://www.bbc.com/news/1".to_string()])
+            })
+            .await
+            .unwrap();
+        assert!(urls.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_no_budget_waits_for_every_article() {
+        let mut budget = SourceBudget::new("npr", None);
+        let sink = ArticleSink::default();
+        let skips = SkipLog::default();
+        let urls = vec!["https://text.npr.org/1", "https://text.npr.org/2"];
+        let articles = budget.fetch(&sink, &skips, &urls, slow_fetch(urls.clone(), 600, &sink)).await;
+        assert_eq!(articles.len(), 2);
+        assert_eq!(skips.total(), 0);
+    }
+}
@@ -9,18 +9,26 @@
 //! Articles are linked from the homepage with relative URLs that are resolved
 //! to absolute URLs like `https://lite.cnn.com/2025/05/06/article-slug`.
 
-use crate::models::NewsArticle;
+use crate::models::{NewsArticle, SkipReason};
+use crate::scrapers::budget::ArticleSink;
+use crate::scrapers::selectors::{self, Selectors};
+use crate::scrapers::{boilerplate, http, meta, paywall};
+use crate::skips::SkipLog;
+use chrono::Utc;
 use futures::stream::{self, StreamExt};
-use reqwest::get;
-use scraper::{Html, Selector};
+use scraper::Html;
 use std::error::Error;
 use tracing::{debug, error, info, instrument, warn};
 use url::Url;
 
+/// The visible byline, e.g. `By Jane Doe and John Roe, CNN`.
+const BYLINE_SELECTOR: &str = ".byline--lite";
+
 /// Index CNN Lite homepage to extract article URLs.
 ///
 /// Scrapes the CNN Lite homepage and extracts all article links from elements
-/// matching `.card--lite a[href]`.
+/// matching `.card--lite a[href]`. Uses a conditional request so an unchanged
+/// homepage reuses the previously indexed URLs.
 ///
 /// # Returns
 ///
@@ -30,19 +38,22 @@ pub async fn index_articles() -> Result<Vec<String>, Box<dyn Error>> {
     let cnn_page_url = "https://lite.cnn.com";
     let cnn_base_url = Url::parse(cnn_page_url)?;
 
-    let html = get(cnn_page_url).await?.text().await?;
-    let document = Html::parse_document(&html);
-    let story_selector = Selector::parse(".card--lite a[href]").unwrap();
-    
-    let mut article_urls = Vec::new();
-    for element in document.select(&story_selector) {
-        if let Some(href) = element.value().attr("href") {
-            if let Ok(resolved) = cnn_base_url.join(href) {
-                article_urls.push(resolved.to_string());
+    let article_urls = http::cached_index(&http::CLIENT, "cnn", cnn_page_url, |html, _| {
+        let document = Html::parse_document(html);
+        let story_selectors = &selectors::get("cnn").index;
+
+        let mut article_urls = Vec::new();
+        for element in story_selectors.iter().flat_map(|sel| document.select(sel)) {
+            if let Some(href) = element.value().attr("href") {
+                if let Ok(resolved) = cnn_base_url.join(href) {
+                    article_urls.push(resolved.to_string());
+                }
             }
         }
-    }
-    
+        article_urls
+    })
+    .await?;
+
     info!(
         count = article_urls.len(),
         source = cnn_page_url,
@@ -61,13 +72,12 @@ pub async fn index_articles() -> Result<Vec<String>, Box<dyn Error>> {
 /// # Arguments
 ///
 /// * `urls` - Vector of article URLs to fetch
-///
-/// # Returns
-///
-/// A vector of successfully fetched [`NewsArticle`] objects.
+/// * `sink` - Receives each fetched [`NewsArticle`] as soon as it is ready,
+///   so articles survive if the source's time budget runs out
+/// * `skips` - Records the URLs that failed to fetch or had no content
 #[instrument(level = "info", skip_all)]
-pub async fn fetch_articles(urls: Vec<String>) -> Vec<NewsArticle> {
-    let articles: Vec<NewsArticle> = stream::iter(urls.clone())
+pub async fn fetch_articles(urls: Vec<String>, sink: &ArticleSink, skips: &SkipLog) {
+    stream::iter(urls.clone())
         .then(|url: String| async move {
             match fetch_article(&url).await {
                 Ok(Some(article)) => {
@@ -76,45 +86,86 @@ pub async fn fetch_articles(urls: Vec<String>) -> Vec<NewsArticle> {
                 }
                 Ok(None) => {
                     warn!(%url, "CNN fetch produced no content");
+                    skips.record(&url, SkipReason::EmptyContent);
                     None
                 }
                 Err(e) => {
                     error!(error = %e, %url, "CNN fetch failed");
+                    skips.record_fetch_error(&url, e.as_ref());
                     None
                 }
             }
         })
         .filter(|opt| std::future::ready(opt.is_some()))
         .map(|opt| opt.unwrap())
-        .collect()
+        .for_each(|article| {
+            sink.lock().unwrap().push(article);
+            std::future::ready(())
+        })
         .await;
     
-    info!(count = articles.len(), "Fetched CNN article contents");
-    articles
+    info!(count = sink.lock().unwrap().len(), "Fetched CNN article contents");
 }
 
-/// Fetch a single CNN article
-#[instrument(level = "info", skip_all, fields(%url))]
-async fn fetch_article(url: &str) -> Result<Option<NewsArticle>, Box<dyn Error>> {
-    let body = get(url).await?.text().await?;
-    let document = Html::parse_document(&body);
+/// Headline and body text of an article page, one line per matched element
+fn article_text(document: &Html, selectors: &Selectors) -> String {
     let mut content = String::new();
-    let headline_selector = Selector::parse(".headline--lite")?;
-    let article_selector = Selector::parse(".article--lite")?;
-
-    for element in document
-        .select(&headline_selector)
-        .chain(document.select(&article_selector))
+    for element in selectors::first_match(&selectors.headline, document)
+        .into_iter()
+        .chain(selectors::first_match(&selectors.article, document))
     {
         let text = element.text().collect::<Vec<_>>().join(" ");
         content.push_str(&text);
         content.push_str("\n");
     }
+    content
+}
 
+/// Fetch a single CNN article
+#[instrument(level = "info", skip_all, fields(%url))]
+async fn fetch_article(url: &str) -> Result<Option<NewsArticle>, Box<dyn Error>> {
+    let body = http::fetch_text("cnn", url).await?;
+    let document = Html::parse_document(&body);
+    let content = article_text(&document, selectors::get("cnn"));
+    let content = boilerplate::strip("cnn", &content);
+    paywall::check(&document, &content)?;
     let len = content.len();
     info!(bytes = len, "Parsed CNN article");
     Ok(Some(NewsArticle {
         source: url.to_string(),
-        content,
-    }))
+        content: content.into(),
+        published_at: meta::extract_published(&document),
+        authors: meta::extract_authors(&document, Some(BYLINE_SELECTOR)),
+        fetched_at: Some(Utc::now()),
+        fetched_from: None,
+    }
+    .with_canonical(meta::extract_canonical(&document, url))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A CNN Lite article after a redesign renamed the classes
+    const REDESIGNED_FIXTURE: &str = r#"<html><body>
+<h2 class="headline--lite-v2">Talks resume in Geneva</h2>
+<p class="byline--lite">By Jane Doe, CNN</p>
+<div class="article--lite-v2"><p>Negotiators met again on Tuesday.</p><p>Officials said progress was slow.</p></div>
+</body></html>"#;
+
+    #[test]
+    fn test_article_text_uses_configured_selectors() {
+        let document = Html::parse_document(REDESIGNED_FIXTURE);
+        let defaults = selectors::load("").unwrap();
+        assert_eq!(article_text(&document, &defaults["cnn"]), "");
+
+        let overrides = selectors::load(
+            "scrapers:\n  cnn:\n    headline_selector: \".headline--lite-v2\"\n    article_selector: [\".article--lite\", \".article--lite-v2\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            article_text(&document, &overrides["cnn"]),
+            "Talks resume in Geneva\nNegotiators met again on Tuesday. Officials said progress was slow.\n"
+        );
+    }
 }
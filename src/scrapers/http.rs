@@ -0,0 +1,1610 @@
+//! Shared HTTP helpers for the scrapers.
+//!
+//! This module holds the pieces of HTTP plumbing that every scraper needs:
+//! the shared [`CLIENT`] (with the `--scrape-timeout-secs` per-request
+//! timeout), status-aware retries and conditional requests for homepage
+//! indexing and article pages.
+//!
+//! # Connection Pool
+//!
+//! A run fetches hundreds of pages from a handful of hosts, so the client
+//! keeps idle connections around for reuse. [`PoolSettings`] caps the idle
+//! connections kept per host (`--pool-max-idle-per-host`, default
+//! [`DEFAULT_POOL_MAX_IDLE_PER_HOST`]) and how long an idle connection stays
+//! open (`--pool-idle-timeout-secs`, default
+//! [`DEFAULT_POOL_IDLE_TIMEOUT_SECS`]). The idle timeout is long enough to
+//! span the pauses between a source's index request and its article
+//! fetches.
+//!
+//! # Retries
+//!
+//! Page fetches go through [`send_with_retry`] (or [`fetch_text`]), which
+//! retries transient failures with exponential backoff: `429`, `502`, `503`,
+//! `504` and connection errors. Any other error status, notably `404`, is
+//! permanent and returned right away. The number of retries and the first
+//! delay come from `--scrape-retries` and `--scrape-retry-delay-ms` (see
+//! [`RetryPolicy`]). When a throttled response carries `Retry-After` (in
+//! seconds or as an HTTP date), that delay is used instead, capped at
+//! [`MAX_RETRY_AFTER_SECS`]. This is separate from the LLM backoff in
+//! `api.rs`.
+//!
+//! # Per-Host Delay
+//!
+//! With `--per-host-delay-ms N`, [`send_with_retry`] waits until at least
+//! N ms have passed since the previous request to the same host, retries
+//! included. Each request reserves its slot when it is issued, so
+//! concurrent fetches from one host queue up N ms apart instead of going
+//! out together. Different hosts don't wait for each other. The default of
+//! 0 sends requests as soon as they are made.
+//!
+//! # Conditional Index Requests
+//!
+//! Homepages are fetched several times a day and frequently haven't changed
+//! between runs. [`cached_index`] remembers the `ETag` / `Last-Modified`
+//! validators of each index URL together with the article URLs that were
+//! parsed from it. On the next run it sends `If-None-Match` /
+//! `If-Modified-Since`, and when the server answers `304 Not Modified` the
+//! previously parsed URL list is reused without downloading or re-parsing
+//! the page.
+//!
+//! The cache is persisted to a small JSON state file (see
+//! [`init_index_cache`] and [`persist_index_cache`]). It is capped at
+//! [`MAX_INDEX_CACHE_ENTRIES`] entries and a corrupt or unreadable file is
+//! treated as an empty cache rather than an error.
+//!
+//! # Conditional Article Requests
+//!
+//! Stories stay on a homepage across several editions, so the same article
+//! pages are fetched again and again. [`fetch_text`] keeps the body of each
+//! page together with its validators in a [`PageCache`]; the next fetch of
+//! the URL is conditional, and on `304 Not Modified` the cached body is used
+//! as if it had just been downloaded. Pages without validators are not
+//! cached. The page cache is persisted like the index cache
+//! (`--page-cache-file`, see [`init_page_cache`] and [`persist_page_cache`])
+//! and capped at [`MAX_PAGE_CACHE_ENTRIES`] pages.
+//!
+//! # Compression
+//!
+//! Index and article requests ask for `gzip`, `deflate` or `br` bodies
+//! ([`ACCEPT_ENCODING_VALUE`]) and decode them here rather than in reqwest,
+//! so both sizes are known: [`transfer_stats`] sums, per source, the bytes
+//! received and the bytes after decoding, and the run reports them in its
+//! `fetching.completed` event. A server that ignores the header sends the
+//! body as is. The client offers HTTP/2 during the TLS handshake and falls
+//! back to HTTP/1.1 when the server doesn't take it.
+//!
+//! # Body Size Limit
+//!
+//! Bodies are read chunk by chunk and abandoned with [`BodyTooLarge`] once
+//! they pass `--max-body-bytes` (default [`DEFAULT_MAX_BODY_BYTES`]), or
+//! right away when `Content-Length` already says so. The limit applies to
+//! the bytes received and again to the decoded body, whose decoding stops
+//! as soon as it passes the limit, so a small compressed body can't expand
+//! without bound. Together with the per-request
+//! timeout, which covers reading the body, this keeps a server streaming an
+//! endless response from stalling the run or exhausting memory. Such pages
+//! are skipped as fetch errors with the `body_too_large` detail (see
+//! [`error_detail`]) and counted in [`bodies_too_large`].
+//!
+//! # In-Run Response Cache
+//!
+//! The same article can be linked from several sections of a homepage, or
+//! from two sources' indexes, so one run may ask for a page more than once.
+//! [`fetch_text`] keeps every body fetched in the run in memory, keyed by
+//! the URL it was fetched from (after the scrapers have canonicalized it),
+//! and answers a repeat without going to the network, not even with a
+//! conditional request. Fetches of a URL already in flight wait for it
+//! rather than issuing their own. The cache is capped at
+//! `--response-cache-bytes` of bodies (default
+//! [`DEFAULT_RESPONSE_CACHE_BYTES`], 0 turns it off), evicting the least
+//! recently used pages first, and forgotten when the run ends. Hits are
+//! counted in [`response_cache_hits`] and reported in `fetching.completed`.
+//!
+//! # HTML Snapshots
+//!
+//! With `--snapshot-dir`, [`fetch_text`] also saves each article page as
+//! fetched, before any text extraction, to `{dir}/{sha256 of the URL}.html`
+//! (see [`snapshot_path`]). Failed fetches leave no snapshot, and a failed
+//! snapshot write is logged without failing the fetch.
+
+use crate::utils::content_hash;
+use chrono::{DateTime, Utc};
+use once_cell::sync::{Lazy, OnceCell};
+use flate2::read::{MultiGzDecoder, ZlibDecoder};
+use reqwest::header::{
+    HeaderMap, ACCEPT_ENCODING, CONTENT_ENCODING, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RETRY_AFTER,
+};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::fs;
+use tracing::{debug, info, instrument, warn};
+
+/// Per-request timeout used when `--scrape-timeout-secs` is not given.
+pub const DEFAULT_SCRAPE_TIMEOUT_SECS: u64 = 20;
+
+/// Per-request timeout of [`CLIENT`], set once at startup.
+static SCRAPE_TIMEOUT: OnceCell<Duration> = OnceCell::new();
+
+/// Set the per-request timeout of the shared client.
+///
+/// Must be called before the first request; later calls are ignored.
+pub fn init_scrape_timeout(secs: u64) {
+    if SCRAPE_TIMEOUT.set(Duration::from_secs(secs)).is_err() {
+        warn!(secs, "Scrape timeout already set; ignoring");
+    }
+}
+
+fn scrape_timeout() -> Duration {
+    SCRAPE_TIMEOUT
+        .get()
+        .copied()
+        .unwrap_or(Duration::from_secs(DEFAULT_SCRAPE_TIMEOUT_SECS))
+}
+
+/// Per-request timeouts, in seconds, of sources slower than the rest.
+///
+/// NYT pages come through a paywall proxy that is often slow; they keep the
+/// 30 s their scraper had before sharing [`CLIENT`], unless
+/// `--scrape-timeout-secs` is longer.
+pub const SLOW_SOURCE_TIMEOUTS: &[(&str, u64)] = &[("nytimes", 30)];
+
+/// Timeout of requests for `source`, when it differs from the shared
+/// client's (see [`SLOW_SOURCE_TIMEOUTS`]).
+pub fn source_timeout(source: &str) -> Option<Duration> {
+    SLOW_SOURCE_TIMEOUTS
+        .iter()
+        .find(|(slow, _)| *slow == source)
+        .map(|(_, secs)| Duration::from_secs(*secs).max(scrape_timeout()))
+}
+
+/// Largest response body read when `--max-body-bytes` is not given (5 MB).
+pub const DEFAULT_MAX_BODY_BYTES: usize = 5 * 1024 * 1024;
+
+/// Largest response body read, set once at startup.
+static MAX_BODY_BYTES: OnceCell<usize> = OnceCell::new();
+
+/// Set the largest response body the fetch helpers read.
+pub fn init_max_body_bytes(bytes: usize) {
+    if MAX_BODY_BYTES.set(bytes).is_err() {
+        warn!(bytes, "Maximum body size already set; ignoring");
+    }
+}
+
+fn max_body_bytes() -> usize {
+    MAX_BODY_BYTES.get().copied().unwrap_or(DEFAULT_MAX_BODY_BYTES)
+}
+
+/// A response body larger than the limit was abandoned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyTooLarge {
+    /// The limit in bytes.
+    pub limit: usize,
+}
+
+impl fmt::Display for BodyTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "response body exceeds {} bytes", self.limit)
+    }
+}
+
+impl Error for BodyTooLarge {}
+
+/// Bodies abandoned for their size so far in the run.
+static BODIES_TOO_LARGE: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of response bodies abandoned with [`BodyTooLarge`] so far.
+pub fn bodies_too_large() -> usize {
+    BODIES_TOO_LARGE.load(Ordering::Relaxed)
+}
+
+/// Finer cause of a failed fetch for the skip report, if there is one.
+pub fn error_detail(e: &(dyn Error + 'static)) -> Option<&'static str> {
+    e.is::<BodyTooLarge>().then_some("body_too_large")
+}
+
+/// Idle connections kept per host when `--pool-max-idle-per-host` is not given.
+pub const DEFAULT_POOL_MAX_IDLE_PER_HOST: usize = 8;
+
+/// Idle connection lifetime when `--pool-idle-timeout-secs` is not given.
+pub const DEFAULT_POOL_IDLE_TIMEOUT_SECS: u64 = 30;
+
+/// Connection pool tuning of the shared client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolSettings {
+    /// Idle connections kept open per host.
+    pub max_idle_per_host: usize,
+    /// How long an idle connection is kept before it is closed.
+    pub idle_timeout: Duration,
+}
+
+impl Default for PoolSettings {
+    fn default() -> Self {
+        PoolSettings {
+            max_idle_per_host: DEFAULT_POOL_MAX_IDLE_PER_HOST,
+            idle_timeout: Duration::from_secs(DEFAULT_POOL_IDLE_TIMEOUT_SECS),
+        }
+    }
+}
+
+/// Pool settings of [`CLIENT`], set once at startup.
+static POOL_SETTINGS: OnceCell<PoolSettings> = OnceCell::new();
+
+/// Set the connection pool settings of the shared client.
+///
+/// Must be called before the first request; later calls are ignored.
+pub fn init_pool_settings(settings: PoolSettings) {
+    if POOL_SETTINGS.set(settings).is_err() {
+        warn!("Connection pool settings already set; ignoring");
+    }
+}
+
+/// Build a scraper client with a browser-like User-Agent, the given
+/// per-request `timeout` and connection `pool` settings.
+fn build_client(timeout: Duration, pool: &PoolSettings) -> Client {
+    Client::builder()
+        .user_agent(concat!(
+            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) ",
+            "AppleWebKit/537.36 (KHTML, like Gecko) ",
+            "Chrome/127.0.0.0 Safari/537.36"
+        ))
+        .timeout(timeout)
+        .pool_max_idle_per_host(pool.max_idle_per_host)
+        .pool_idle_timeout(pool.idle_timeout)
+        .redirect(reqwest::redirect::Policy::limited(10))
+        .build()
+        .expect("failed to build reqwest client")
+}
+
+/// Global HTTP client shared by all scrapers, configured by
+/// [`init_scrape_timeout`] and [`init_pool_settings`].
+pub static CLIENT: Lazy<Client> = Lazy::new(|| {
+    build_client(scrape_timeout(), &POOL_SETTINGS.get().copied().unwrap_or_default())
+});
+
+/// Retries of a failed scraper request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt.
+    pub max_retries: u32,
+    /// Delay before the first retry; doubles with each further retry.
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 2,
+            base_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Retry policy of the shared fetch helpers, set once at startup.
+static RETRY_POLICY: OnceCell<RetryPolicy> = OnceCell::new();
+
+/// Set the retry policy of [`fetch_text`] and [`cached_index`].
+pub fn init_retry_policy(policy: RetryPolicy) {
+    if RETRY_POLICY.set(policy).is_err() {
+        warn!("Scrape retry policy already set; ignoring");
+    }
+}
+
+fn retry_policy() -> RetryPolicy {
+    RETRY_POLICY.get().copied().unwrap_or_default()
+}
+
+/// Spaces out requests to the same host by a minimum delay.
+#[derive(Debug)]
+pub struct HostPacer {
+    /// Minimum time between two requests to one host.
+    min_delay: Duration,
+    /// When the latest request to each host was (or will be) sent.
+    next: Mutex<HashMap<String, Instant>>,
+}
+
+impl HostPacer {
+    /// A pacer keeping `min_delay` between requests to one host.
+    pub fn new(min_delay: Duration) -> Self {
+        HostPacer {
+            min_delay,
+            next: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait for the turn of a request to `url`'s host.
+    pub async fn wait(&self, url: &str) {
+        if self.min_delay.is_zero() {
+            return;
+        }
+        let Some(host) = reqwest::Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_string)) else {
+            return;
+        };
+        let now = Instant::now();
+        let slot = {
+            let mut next = self.next.lock().unwrap();
+            let slot = next.get(&host).map_or(now, |last| (*last + self.min_delay).max(now));
+            next.insert(host, slot);
+            slot
+        };
+        if slot > now {
+            debug!(%url, wait = ?(slot - now), "Waiting for the per-host delay");
+            tokio::time::sleep_until(slot.into()).await;
+        }
+    }
+}
+
+/// Per-host pacing of the shared fetch helpers, set once at startup.
+static HOST_PACER: OnceCell<HostPacer> = OnceCell::new();
+
+/// Space requests to the same host at least `ms` milliseconds apart
+/// (`--per-host-delay-ms`); 0 leaves them unpaced.
+pub fn init_per_host_delay(ms: u64) {
+    if HOST_PACER.set(HostPacer::new(Duration::from_millis(ms))).is_err() {
+        warn!("Per-host delay already set; ignoring");
+    }
+}
+
+/// Whether a response status is worth retrying.
+fn is_transient_status(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 429 | 502 | 503 | 504)
+}
+
+/// Longest `Retry-After` delay honored; longer requests are cut to this.
+pub const MAX_RETRY_AFTER_SECS: u64 = 60;
+
+/// Delay requested by a response's `Retry-After` header, in seconds or as
+/// an HTTP date, capped at [`MAX_RETRY_AFTER_SECS`].
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    let secs = match value.parse::<u64>() {
+        Ok(secs) => secs,
+        Err(_) => {
+            let at = DateTime::parse_from_rfc2822(value).ok()?;
+            (at.with_timezone(&Utc) - Utc::now()).num_seconds().max(0) as u64
+        }
+    };
+    Some(Duration::from_secs(secs.min(MAX_RETRY_AFTER_SECS)))
+}
+
+/// Send a request, retrying transient failures according to `policy`.
+///
+/// `build` creates a fresh request for every attempt. The response to the
+/// last attempt is returned whatever its status, so callers decide what an
+/// error status means; only a connection error that outlasts the retries
+/// is returned as an error.
+pub async fn send_with_retry<F>(policy: &RetryPolicy, url: &str, build: F) -> Result<Response, Box<dyn Error>>
+where
+    F: Fn() -> RequestBuilder,
+{
+    let mut attempt = 0;
+    loop {
+        if let Some(pacer) = HOST_PACER.get() {
+            pacer.wait(url).await;
+        }
+        let result = build().send().await;
+        let transient = match &result {
+            Ok(res) if is_transient_status(res.status()) => Some((format!("status {}", res.status()), retry_after(res.headers()))),
+            Err(e) if e.is_connect() || e.is_request() || e.is_body() => Some((e.to_string(), None)),
+            _ => None,
+        };
+        match transient {
+            Some((reason, requested)) if attempt < policy.max_retries => {
+                // The server's requested delay wins over our backoff
+                let delay = requested.unwrap_or_else(|| policy.base_delay.saturating_mul(1 << attempt));
+                attempt += 1;
+                debug!(%url, attempt, max = policy.max_retries, ?delay, %reason, "Retrying scraper request");
+                tokio::time::sleep(delay).await;
+            }
+            _ => return Ok(result?),
+        }
+    }
+}
+
+/// Fetch a page body of `source` (a source tag, for [`transfer_stats`]) with
+/// the shared client and retry policy.
+///
+/// The request is conditional when the page is in the page cache, and a
+/// `304 Not Modified` returns the cached body. A URL already fetched in this
+/// run is answered from the [`ResponseCache`] without a request. Error
+/// statuses (after retries for the transient ones) are errors.
+pub async fn fetch_text(source: &str, url: &str) -> Result<String, Box<dyn Error>> {
+    RESPONSE_CACHE
+        .get_or_fetch(url, || {
+            fetch_text_with(&PAGE_CACHE, &CLIENT, source, url, SNAPSHOT_DIR.get().map(String::as_str))
+        })
+        .await
+}
+
+async fn fetch_text_with(
+    cache: &Mutex<PageCache>,
+    client: &Client,
+    source: &str,
+    url: &str,
+    snapshot_dir: Option<&str>,
+) -> Result<String, Box<dyn Error>> {
+    let body = fetch_page(cache, client, source, url).await?;
+    if let Some(dir) = snapshot_dir {
+        let path = snapshot_path(dir, url);
+        match fs::write(&path, &body).await {
+            Ok(()) => debug!(%url, %path, "Saved HTML snapshot"),
+            Err(e) => warn!(%url, %path, error = %e, "Failed to save HTML snapshot"),
+        }
+    }
+    Ok(body)
+}
+
+/// Bytes of page bodies kept for the run when `--response-cache-bytes` is
+/// not given (32 MB).
+pub const DEFAULT_RESPONSE_CACHE_BYTES: usize = 32 * 1024 * 1024;
+
+/// Size cap of [`RESPONSE_CACHE`], set once at startup.
+static RESPONSE_CACHE_BYTES: OnceCell<usize> = OnceCell::new();
+
+/// Set the size cap of the in-run response cache; 0 turns it off.
+///
+/// Must be called before the first page fetch; later calls are ignored.
+pub fn init_response_cache_bytes(bytes: usize) {
+    if RESPONSE_CACHE_BYTES.set(bytes).is_err() {
+        warn!(bytes, "Response cache size already set; ignoring");
+    }
+}
+
+static RESPONSE_CACHE: Lazy<ResponseCache> = Lazy::new(|| {
+    ResponseCache::new(RESPONSE_CACHE_BYTES.get().copied().unwrap_or(DEFAULT_RESPONSE_CACHE_BYTES))
+});
+
+/// Page fetches answered from the in-run response cache so far.
+pub fn response_cache_hits() -> usize {
+    RESPONSE_CACHE.hits()
+}
+
+/// Page bodies fetched in this run, by URL, evicted least recently used
+/// first once their total size passes the cap.
+#[derive(Debug)]
+pub struct ResponseCache {
+    max_bytes: usize,
+    slots: Mutex<ResponseSlots>,
+    hits: AtomicUsize,
+}
+
+#[derive(Debug, Default)]
+struct ResponseSlots {
+    /// The body of each URL; empty while its first fetch is in flight.
+    map: HashMap<String, ResponseSlot>,
+    /// Sum of the lengths of the bodies held.
+    bytes: usize,
+    /// Bumped on every lookup, for the least-recently-used order.
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct ResponseSlot {
+    body: Arc<tokio::sync::OnceCell<String>>,
+    last_used: u64,
+}
+
+impl ResponseCache {
+    /// An empty cache holding at most `max_bytes` of bodies; 0 caches nothing.
+    pub fn new(max_bytes: usize) -> Self {
+        ResponseCache {
+            max_bytes,
+            slots: Mutex::new(ResponseSlots::default()),
+            hits: AtomicUsize::new(0),
+        }
+    }
+
+    /// Lookups answered without calling `fetch`.
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// The cached body of `url`, or the one `fetch` returns.
+    ///
+    /// Concurrent lookups of a URL wait for the first one's fetch instead of
+    /// issuing their own. Failures are not cached: the next lookup fetches
+    /// again.
+    pub async fn get_or_fetch<F, Fut>(&self, url: &str, fetch: F) -> Result<String, Box<dyn Error>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<String, Box<dyn Error>>>,
+    {
+        if self.max_bytes == 0 {
+            return fetch().await;
+        }
+        let cell = {
+            let mut slots = self.slots.lock().unwrap();
+            slots.clock += 1;
+            let clock = slots.clock;
+            let slot = slots.map.entry(url.to_string()).or_insert_with(|| ResponseSlot {
+                body: Arc::default(),
+                last_used: clock,
+            });
+            slot.last_used = clock;
+            slot.body.clone()
+        };
+
+        let mut fetched = false;
+        let result = cell
+            .get_or_try_init(|| {
+                fetched = true;
+                fetch()
+            })
+            .await
+            .cloned();
+        match &result {
+            Ok(body) if fetched => self.admit(body.len()),
+            Ok(_) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                debug!(%url, "Response cache hit");
+            }
+            Err(_) => {
+                let mut slots = self.slots.lock().unwrap();
+                if slots.map.get(url).is_some_and(|slot| Arc::ptr_eq(&slot.body, &cell) && !cell.initialized()) {
+                    slots.map.remove(url);
+                }
+            }
+        }
+        result
+    }
+
+    /// Account for a newly stored body of `len` bytes, evicting the least
+    /// recently used bodies until the total fits under the cap again.
+    fn admit(&self, len: usize) {
+        let mut slots = self.slots.lock().unwrap();
+        slots.bytes += len;
+        while slots.bytes > self.max_bytes {
+            let lru = slots
+                .map
+                .iter()
+                .filter(|(_, slot)| slot.body.initialized())
+                .min_by_key(|(_, slot)| slot.last_used)
+                .map(|(url, _)| url.clone());
+            let Some(url) = lru else { break };
+            if let Some(slot) = slots.map.remove(&url) {
+                slots.bytes -= slot.body.get().map_or(0, String::len);
+                debug!(%url, "Evicted from the response cache");
+            }
+        }
+    }
+}
+
+static SNAPSHOT_DIR: OnceCell<String> = OnceCell::new();
+
+/// Save fetched article pages under `dir` from now on (`--snapshot-dir`).
+///
+/// The directory is created if missing; if that fails, snapshots stay off.
+pub async fn init_snapshot_dir(dir: Option<&str>) {
+    let Some(dir) = dir else { return };
+    match fs::create_dir_all(dir).await {
+        Ok(()) => {
+            let _ = SNAPSHOT_DIR.set(dir.trim_end_matches('/').to_string());
+        }
+        Err(e) => warn!(dir, error = %e, "Cannot create snapshot directory; not saving HTML snapshots"),
+    }
+}
+
+/// Where the HTML snapshot of `url` is saved under `dir`.
+pub fn snapshot_path(dir: &str, url: &str) -> String {
+    format!("{}/{}.html", dir, content_hash(url))
+}
+
+/// Fetch a page body, conditionally when it is in the page cache.
+async fn fetch_page(cache: &Mutex<PageCache>, client: &Client, source: &str, url: &str) -> Result<String, Box<dyn Error>> {
+    let cached = cache.lock().unwrap().entries.get(url).cloned();
+    let validators = cached.as_ref().map(|e| (e.etag.as_deref(), e.last_modified.as_deref()));
+    let timeout = source_timeout(source);
+    let res = send_with_retry(&retry_policy(), url, || {
+        let request = conditional_get(client, url, validators);
+        match timeout {
+            Some(timeout) => request.timeout(timeout),
+            None => request,
+        }
+    })
+    .await?;
+    if res.status() == StatusCode::NOT_MODIFIED {
+        return match cached {
+            Some(entry) => {
+                debug!(%url, bytes = entry.body.len(), "Page not modified; reusing cached body");
+                Ok(entry.body)
+            }
+            None => Err(format!("got 304 for {} without a cached page", url).into()),
+        };
+    }
+
+    let res = res.error_for_status()?;
+    let (etag, last_modified) = validators_of(res.headers());
+    let body = read_body(res, source).await?;
+    if (etag.is_some() || last_modified.is_some()) && !body.is_empty() {
+        cache.lock().unwrap().insert(
+            url,
+            PageCacheEntry {
+                etag,
+                last_modified,
+                body: body.clone(),
+                stored_at: Utc::now().timestamp(),
+            },
+        );
+    }
+    Ok(body)
+}
+
+/// A GET for `url`, conditional on the `(ETag, Last-Modified)` validators
+/// of a cached response, if any.
+fn conditional_get(client: &Client, url: &str, validators: Option<(Option<&str>, Option<&str>)>) -> RequestBuilder {
+    let mut request = client.get(url).header(ACCEPT_ENCODING, ACCEPT_ENCODING_VALUE);
+    if let Some((etag, last_modified)) = validators {
+        if let Some(etag) = etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+    request
+}
+
+/// Content encodings asked for on index and article requests.
+pub const ACCEPT_ENCODING_VALUE: &str = "gzip, deflate, br";
+
+/// Bytes of one source's responses, as received and after decoding.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct TransferBytes {
+    /// Body bytes as sent by the servers.
+    pub compressed: u64,
+    /// Body bytes after decoding.
+    pub decompressed: u64,
+}
+
+/// Transfer totals of the run, by source tag.
+static TRANSFER: Lazy<Mutex<BTreeMap<String, TransferBytes>>> = Lazy::new(Default::default);
+
+/// Bytes received so far, by source tag (see [`TransferBytes`]).
+pub fn transfer_stats() -> BTreeMap<String, TransferBytes> {
+    TRANSFER.lock().unwrap().clone()
+}
+
+/// Decode a body sent with `Content-Encoding: encoding`.
+///
+/// Decoding stops one byte past `max_bytes`, so a small compressed body
+/// can't expand into an unbounded one; such a body is [`BodyTooLarge`].
+fn decode_body(encoding: &str, body: &[u8], max_bytes: usize) -> Result<Vec<u8>, Box<dyn Error>> {
+    let decoder: Box<dyn Read + '_> = match encoding {
+        "" | "identity" => return Ok(body.to_vec()),
+        "gzip" | "x-gzip" => Box::new(MultiGzDecoder::new(body)),
+        "deflate" => Box::new(ZlibDecoder::new(body)),
+        "br" => Box::new(brotli_decompressor::Decompressor::new(body, 4096)),
+        other => return Err(format!("unsupported Content-Encoding: {}", other).into()),
+    };
+    let mut decoded = Vec::with_capacity((body.len() * 4).min(max_bytes));
+    decoder.take(max_bytes as u64 + 1).read_to_end(&mut decoded)?;
+    if decoded.len() > max_bytes {
+        return Err(BodyTooLarge { limit: max_bytes }.into());
+    }
+    Ok(decoded)
+}
+
+/// Read and decode a response body of `source`, adding its sizes to the
+/// run's [`transfer_stats`].
+///
+/// A body over `--max-body-bytes` is abandoned with [`BodyTooLarge`].
+async fn read_body(res: Response, source: &str) -> Result<String, Box<dyn Error>> {
+    let url = res.url().to_string();
+    let result = read_body_into(&TRANSFER, res, source, max_body_bytes()).await;
+    if let Err(e) = &result
+        && e.is::<BodyTooLarge>()
+    {
+        BODIES_TOO_LARGE.fetch_add(1, Ordering::Relaxed);
+        warn!(%url, source, error = %e, "Abandoned oversized response body");
+    }
+    result
+}
+
+async fn read_body_into(
+    stats: &Mutex<BTreeMap<String, TransferBytes>>,
+    mut res: Response,
+    source: &str,
+    max_bytes: usize,
+) -> Result<String, Box<dyn Error>> {
+    let encoding = res
+        .headers()
+        .get(CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    let version = res.version();
+    if res.content_length().is_some_and(|len| len > max_bytes as u64) {
+        return Err(BodyTooLarge { limit: max_bytes }.into());
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = res.chunk().await? {
+        if body.len() + chunk.len() > max_bytes {
+            return Err(BodyTooLarge { limit: max_bytes }.into());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    let decoded = decode_body(&encoding, &body, max_bytes)?;
+    debug!(source, ?version, %encoding, compressed = body.len(), decompressed = decoded.len(), "Read response body");
+
+    let mut stats = stats.lock().unwrap();
+    let entry = stats.entry(source.to_string()).or_default();
+    entry.compressed += body.len() as u64;
+    entry.decompressed += decoded.len() as u64;
+    Ok(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+/// The `ETag` and `Last-Modified` headers of a response.
+fn validators_of(headers: &HeaderMap) -> (Option<String>, Option<String>) {
+    let header = |name| headers.get(name).and_then(|v| v.to_str().ok()).map(|s| s.to_string());
+    (header(ETAG), header(LAST_MODIFIED))
+}
+
+/// Load a cache file, treating a missing or corrupt file as an empty cache.
+async fn load_cache<T: DeserializeOwned + Default>(path: &str, what: &str) -> T {
+    match fs::read_to_string(path).await {
+        Ok(raw) => match serde_json::from_str::<T>(&raw) {
+            Ok(cache) => {
+                info!(path, cache = what, "Loaded cache");
+                cache
+            }
+            Err(e) => {
+                warn!(path, cache = what, error = %e, "Cache is corrupt; starting empty");
+                T::default()
+            }
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => T::default(),
+        Err(e) => {
+            warn!(path, cache = what, error = %e, "Failed to read cache; starting empty");
+            T::default()
+        }
+    }
+}
+
+/// Drop the oldest entries of `entries` until at most `cap` remain.
+fn evict_oldest<E>(entries: &mut BTreeMap<String, E>, cap: usize, stored_at: fn(&E) -> i64) {
+    while entries.len() > cap {
+        let oldest = entries.iter().min_by_key(|(_, e)| stored_at(e)).map(|(k, _)| k.clone());
+        match oldest {
+            Some(k) => {
+                entries.remove(&k);
+            }
+            None => break,
+        }
+    }
+}
+
+/// Maximum number of index URLs remembered in the cache.
+///
+/// When the cap is exceeded the oldest entries are evicted first.
+pub const MAX_INDEX_CACHE_ENTRIES: usize = 64;
+
+/// Cached validators and parsed article URLs for a single index page.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct IndexCacheEntry {
+    /// The `ETag` header returned with the last full response.
+    pub etag: Option<String>,
+    /// The `Last-Modified` header returned with the last full response.
+    pub last_modified: Option<String>,
+    /// Article URLs parsed from the last full response.
+    pub urls: Vec<String>,
+    /// Unix timestamp (seconds) of when the entry was stored.
+    pub stored_at: i64,
+}
+
+/// Per-URL cache of index page validators.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct IndexCache {
+    /// Entries keyed by the index page URL.
+    pub entries: BTreeMap<String, IndexCacheEntry>,
+}
+
+impl IndexCache {
+    /// Load a cache from disk, treating a missing or corrupt file as empty.
+    pub async fn load(path: &str) -> Self {
+        load_cache(path, "index cache").await
+    }
+
+    /// Write the cache to disk as JSON.
+    pub async fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json).await?;
+        Ok(())
+    }
+
+    /// Insert or replace an entry, evicting the oldest entries past the cap.
+    pub fn insert(&mut self, url: &str, entry: IndexCacheEntry) {
+        self.entries.insert(url.to_string(), entry);
+        evict_oldest(&mut self.entries, MAX_INDEX_CACHE_ENTRIES, |e| e.stored_at);
+    }
+}
+
+static INDEX_CACHE: Lazy<Mutex<IndexCache>> = Lazy::new(|| Mutex::new(IndexCache::default()));
+static INDEX_CACHE_PATH: OnceCell<String> = OnceCell::new();
+
+/// Load the persisted index cache, if a path was configured.
+///
+/// Without a path the cache still works in memory for the current run but
+/// nothing is remembered across runs.
+pub async fn init_index_cache(path: Option<&str>) {
+    if let Some(path) = path {
+        let cache = IndexCache::load(path).await;
+        *INDEX_CACHE.lock().unwrap() = cache;
+        let _ = INDEX_CACHE_PATH.set(path.to_string());
+    }
+}
+
+/// Persist the index cache to the path given to [`init_index_cache`].
+///
+/// Failures are logged and otherwise ignored; losing the cache only costs a
+/// full download on the next run.
+pub async fn persist_index_cache() {
+    let Some(path) = INDEX_CACHE_PATH.get() else {
+        return;
+    };
+    let snapshot = INDEX_CACHE.lock().unwrap().clone();
+    match snapshot.save(path).await {
+        Ok(()) => debug!(path = %path, "Persisted index cache"),
+        Err(e) => warn!(path = %path, error = %e, "Failed to persist index cache"),
+    }
+}
+
+/// Maximum number of article pages remembered in the page cache.
+///
+/// When the cap is exceeded the oldest entries are evicted first.
+pub const MAX_PAGE_CACHE_ENTRIES: usize = 512;
+
+/// Cached validators and body of a single article page.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PageCacheEntry {
+    /// The `ETag` header returned with the last full response.
+    pub etag: Option<String>,
+    /// The `Last-Modified` header returned with the last full response.
+    pub last_modified: Option<String>,
+    /// Body of the last full response.
+    pub body: String,
+    /// Unix timestamp (seconds) of when the entry was stored.
+    pub stored_at: i64,
+}
+
+/// Per-URL cache of article pages and their validators.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PageCache {
+    /// Entries keyed by the page URL.
+    pub entries: BTreeMap<String, PageCacheEntry>,
+}
+
+impl PageCache {
+    /// Load a cache from disk, treating a missing or corrupt file as empty.
+    pub async fn load(path: &str) -> Self {
+        load_cache(path, "page cache").await
+    }
+
+    /// Write the cache to disk as JSON.
+    pub async fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string(self)?).await?;
+        Ok(())
+    }
+
+    /// Insert or replace an entry, evicting the oldest entries past the cap.
+    pub fn insert(&mut self, url: &str, entry: PageCacheEntry) {
+        self.entries.insert(url.to_string(), entry);
+        evict_oldest(&mut self.entries, MAX_PAGE_CACHE_ENTRIES, |e| e.stored_at);
+    }
+}
+
+static PAGE_CACHE: Lazy<Mutex<PageCache>> = Lazy::new(|| Mutex::new(PageCache::default()));
+static PAGE_CACHE_PATH: OnceCell<String> = OnceCell::new();
+
+/// Load the persisted page cache, if a path was configured.
+///
+/// Without a path the cache still works in memory for the current run but
+/// nothing is remembered across runs.
+pub async fn init_page_cache(path: Option<&str>) {
+    if let Some(path) = path {
+        let cache = PageCache::load(path).await;
+        *PAGE_CACHE.lock().unwrap() = cache;
+        let _ = PAGE_CACHE_PATH.set(path.to_string());
+    }
+}
+
+/// Persist the page cache to the path given to [`init_page_cache`].
+///
+/// Failures are logged and otherwise ignored, like for the index cache.
+pub async fn persist_page_cache() {
+    let Some(path) = PAGE_CACHE_PATH.get() else {
+        return;
+    };
+    let snapshot = PAGE_CACHE.lock().unwrap().clone();
+    match snapshot.save(path).await {
+        Ok(()) => debug!(path = %path, entries = snapshot.entries.len(), "Persisted page cache"),
+        Err(e) => warn!(path = %path, error = %e, "Failed to persist page cache"),
+    }
+}
+
+/// Fetch an index page and parse article URLs from it, using conditional requests.
+///
+/// `parse` receives the page HTML and the final URL (after redirects) and
+/// returns the article URLs found. It is only called when the server sends a
+/// full response; on `304 Not Modified` the URLs cached from the previous
+/// full response are returned instead.
+///
+/// # Arguments
+///
+/// * `client` - The HTTP client to send the request with
+/// * `source` - Source tag the page's bytes are counted under
+/// * `url` - The index page URL
+/// * `parse` - Extracts article URLs from `(html, final_url)`
+#[instrument(level = "info", skip(client, parse))]
+pub async fn cached_index<F>(client: &Client, source: &str, url: &str, parse: F) -> Result<Vec<String>, Box<dyn Error>>
+where
+    F: FnOnce(&str, &str) -> Vec<String>,
+{
+    cached_index_with(&INDEX_CACHE, client, source, url, parse).await
+}
+
+async fn cached_index_with<F>(
+    cache: &Mutex<IndexCache>,
+    client: &Client,
+    source: &str,
+    url: &str,
+    parse: F,
+) -> Result<Vec<String>, Box<dyn Error>>
+where
+    F: FnOnce(&str, &str) -> Vec<String>,
+{
+    let cached = cache.lock().unwrap().entries.get(url).cloned();
+    let validators = cached.as_ref().map(|e| (e.etag.as_deref(), e.last_modified.as_deref()));
+
+    let res = send_with_retry(&retry_policy(), url, || conditional_get(client, url, validators)).await?;
+    if res.status() == StatusCode::NOT_MODIFIED {
+        if let Some(entry) = cached {
+            info!(count = entry.urls.len(), "Index page not modified; reusing cached URLs");
+            return Ok(entry.urls);
+        }
+        warn!("Got 304 without a cached entry; treating as empty index");
+        return Ok(Vec::new());
+    }
+
+    let (etag, last_modified) = validators_of(res.headers());
+    let final_url = res.url().to_string();
+    let html = read_body(res, source).await?;
+
+    let urls = parse(&html, &final_url);
+
+    // Only remember pages that carried validators and actually yielded URLs;
+    // a 304 for an empty parse would pin us to a broken result.
+    if (etag.is_some() || last_modified.is_some()) && !urls.is_empty() {
+        cache.lock().unwrap().insert(
+            url,
+            IndexCacheEntry {
+                etag,
+                last_modified,
+                urls: urls.clone(),
+                stored_at: Utc::now().timestamp(),
+            },
+        );
+    }
+
+    Ok(urls)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_cached_index_reuses_urls_on_304() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v1\"")
+                    .set_body_string("<a href=\"/story\">Story</a>"),
+            )
+            .with_priority(2)
+            .mount(&server)
+            .await;
+
+        let cache = Mutex::new(IndexCache::default());
+        let client = Client::new();
+        let url = format!("{}/", server.uri());
+        let parses = AtomicUsize::new(0);
+        let parse = |html: &str, _final_url: &str| {
+            parses.fetch_add(1, Ordering::SeqCst);
+            assert!(html.contains("/story"));
+            vec!["https://example.com/story".to_string()]
+        };
+
+        let first = cached_index_with(&cache, &client, "test", &url, parse).await.unwrap();
+        let second = cached_index_with(&cache, &client, "test", &url, parse).await.unwrap();
+
+        assert_eq!(first, vec!["https://example.com/story".to_string()]);
+        assert_eq!(second, first);
+        assert_eq!(parses.load(Ordering::SeqCst), 1);
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cached_index_skips_cache_without_validators() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<html></html>"))
+            .mount(&server)
+            .await;
+
+        let cache = Mutex::new(IndexCache::default());
+        let urls = cached_index_with(&cache, &Client::new(), "test", &server.uri(), |_, _| {
+            vec!["https://example.com/a".to_string()]
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(urls.len(), 1);
+        assert!(cache.lock().unwrap().entries.is_empty());
+    }
+
+    #[test]
+    fn test_index_cache_evicts_oldest_past_cap() {
+        let mut cache = IndexCache::default();
+        for i in 0..(MAX_INDEX_CACHE_ENTRIES + 3) {
+            cache.insert(
+                &format!("https://example.com/{i}"),
+                IndexCacheEntry {
+                    stored_at: i as i64,
+                    ..Default::default()
+                },
+            );
+        }
+
+        assert_eq!(cache.entries.len(), MAX_INDEX_CACHE_ENTRIES);
+        assert!(!cache.entries.contains_key("https://example.com/0"));
+        assert!(cache
+            .entries
+            .contains_key(&format!("https://example.com/{}", MAX_INDEX_CACHE_ENTRIES + 2)));
+    }
+
+    #[tokio::test]
+    async fn test_index_cache_tolerates_corruption() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index_cache.json");
+        std::fs::write(&path, "{ not json").unwrap();
+
+        let cache = IndexCache::load(path.to_str().unwrap()).await;
+        assert!(cache.entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_index_cache_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index_cache.json");
+        let path = path.to_str().unwrap();
+
+        let mut cache = IndexCache::default();
+        cache.insert(
+            "https://lite.cnn.com",
+            IndexCacheEntry {
+                etag: Some("\"abc\"".to_string()),
+                last_modified: None,
+                urls: vec!["https://lite.cnn.com/story".to_string()],
+                stored_at: 1,
+            },
+        );
+        cache.save(path).await.unwrap();
+
+        let loaded = IndexCache::load(path).await;
+        let entry = &loaded.entries["https://lite.cnn.com"];
+        assert_eq!(entry.etag.as_deref(), Some("\"abc\""));
+        assert_eq!(entry.urls, vec!["https://lite.cnn.com/story".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_text_reuses_cached_body_on_304() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/story"))
+            .and(header("if-none-match", "\"v1\""))
+            .and(header("if-modified-since", "Tue, 06 May 2025 10:00:00 GMT"))
+            .respond_with(ResponseTemplate::new(304))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/story"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v1\"")
+                    .insert_header("Last-Modified", "Tue, 06 May 2025 10:00:00 GMT")
+                    .set_body_string("<p>Story body</p>"),
+            )
+            .with_priority(2)
+            .mount(&server)
+            .await;
+
+        let cache = Mutex::new(PageCache::default());
+        let client = Client::new();
+        let url = format!("{}/story", server.uri());
+
+        let first = fetch_text_with(&cache, &client, "test", &url, None).await.unwrap();
+        let second = fetch_text_with(&cache, &client, "test", &url, None).await.unwrap();
+
+        assert_eq!(first, "<p>Story body</p>");
+        assert_eq!(second, first);
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].headers.get("if-none-match").is_some());
+
+        // Survives a round trip through the page cache file
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("page_cache.json");
+        let snapshot = cache.lock().unwrap().clone();
+        snapshot.save(path.to_str().unwrap()).await.unwrap();
+        let loaded = PageCache::load(path.to_str().unwrap()).await;
+        assert_eq!(loaded.entries[&url].body, "<p>Story body</p>");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_text_without_validators_is_not_cached() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<p>Live</p>"))
+            .mount(&server)
+            .await;
+
+        let cache = Mutex::new(PageCache::default());
+        let body = fetch_text_with(&cache, &Client::new(), "test", &server.uri(), None).await.unwrap();
+        assert_eq!(body, "<p>Live</p>");
+        assert!(cache.lock().unwrap().entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_text_saves_snapshot_only_on_success() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/story"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<html><p>Story body</p></html>"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/gone"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let snapshots = dir.path().to_str().unwrap();
+        let cache = Mutex::new(PageCache::default());
+        let client = Client::new();
+
+        let url = format!("{}/story", server.uri());
+        let body = fetch_text_with(&cache, &client, "test", &url, Some(snapshots)).await.unwrap();
+        let snapshot = std::fs::read_to_string(snapshot_path(snapshots, &url)).unwrap();
+        assert!(!snapshot.is_empty());
+        assert_eq!(snapshot, body);
+
+        let gone = format!("{}/gone", server.uri());
+        assert!(fetch_text_with(&cache, &client, "test", &gone, Some(snapshots)).await.is_err());
+        assert!(!std::path::Path::new(&snapshot_path(snapshots, &gone)).exists());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    fn fast_retries() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 2,
+            base_delay: Duration::from_millis(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transient_status_is_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("article"))
+            .with_priority(2)
+            .mount(&server)
+            .await;
+
+        let client = Client::new();
+        let url = server.uri();
+        let res = send_with_retry(&fast_retries(), &url, || client.get(&url)).await.unwrap();
+        assert_eq!(res.text().await.unwrap(), "article");
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_status_is_not_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let client = Client::new();
+        let url = server.uri();
+        let res = send_with_retry(&fast_retries(), &url, || client.get(&url)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retries_are_bounded() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(429))
+            .mount(&server)
+            .await;
+
+        let client = Client::new();
+        let url = server.uri();
+        let res = send_with_retry(&fast_retries(), &url, || client.get(&url)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_overrides_backoff() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "1"))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("article"))
+            .with_priority(2)
+            .mount(&server)
+            .await;
+
+        let client = Client::new();
+        let url = server.uri();
+        let started = std::time::Instant::now();
+        let res = send_with_retry(&fast_retries(), &url, || client.get(&url)).await.unwrap();
+        assert_eq!(res.text().await.unwrap(), "article");
+        // Waited for the requested second, not the 1ms backoff
+        assert!(started.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_retry_after_parses_seconds_and_dates() {
+        let response = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(RETRY_AFTER, reqwest::header::HeaderValue::from_str(value).unwrap());
+            headers
+        };
+        assert_eq!(retry_after(&response("7")), Some(Duration::from_secs(7)));
+        assert_eq!(retry_after(&response("86400")), Some(Duration::from_secs(MAX_RETRY_AFTER_SECS)));
+        assert_eq!(retry_after(&response("Wed, 21 Oct 2015 07:28:00 GMT")), Some(Duration::ZERO));
+        let soon = (Utc::now() + chrono::Duration::seconds(30)).to_rfc2822();
+        let delay = retry_after(&response(&soon)).unwrap();
+        assert!(delay > Duration::from_secs(25) && delay <= Duration::from_secs(30));
+        assert_eq!(retry_after(&response("soon")), None);
+    }
+
+    #[tokio::test]
+    async fn test_response_cache_issues_one_request_for_concurrent_fetches() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/story"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<p>Story body</p>").set_delay(Duration::from_millis(100)))
+            .mount(&server)
+            .await;
+
+        let responses = ResponseCache::new(DEFAULT_RESPONSE_CACHE_BYTES);
+        let cache = Mutex::new(PageCache::default());
+        let client = Client::new();
+        let url = format!("{}/story", server.uri());
+        let fetch = || responses.get_or_fetch(&url, || fetch_text_with(&cache, &client, "test", &url, None));
+
+        let (first, second) = tokio::join!(fetch(), fetch());
+        assert_eq!(first.unwrap(), "<p>Story body</p>");
+        assert_eq!(second.unwrap(), "<p>Story body</p>");
+        assert_eq!(fetch().await.unwrap(), "<p>Story body</p>");
+
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+        assert_eq!(responses.hits(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_response_cache_evicts_least_recently_used_and_retries_failures() {
+        let responses = ResponseCache::new(10);
+        let fetches = AtomicUsize::new(0);
+        let fetch = |url: &'static str, body: &'static str| {
+            let fetches = &fetches;
+            let responses = &responses;
+            async move {
+                responses
+                    .get_or_fetch(url, || async move {
+                        fetches.fetch_add(1, Ordering::SeqCst);
+                        Ok(body.to_string())
+                    })
+                    .await
+                    .unwrap()
+            }
+        };
+
+        fetch("a", "aaaa").await;
+        fetch("b", "bbbb").await;
+        fetch("a", "aaaa").await;
+        // Over the cap: `b` is the least recently used
+        fetch("c", "cccc").await;
+        assert_eq!(fetches.load(Ordering::SeqCst), 3);
+        fetch("a", "aaaa").await;
+        fetch("c", "cccc").await;
+        assert_eq!(fetches.load(Ordering::SeqCst), 3);
+        fetch("b", "bbbb").await;
+        assert_eq!(fetches.load(Ordering::SeqCst), 4);
+        assert_eq!(responses.hits(), 3);
+
+        let failed = responses.get_or_fetch("d", || async { Err("unreachable".into()) }).await;
+        assert!(failed.is_err());
+        assert_eq!(fetch("d", "dd").await, "dd");
+
+        // A zero cap caches nothing
+        let off = ResponseCache::new(0);
+        for _ in 0..2 {
+            off.get_or_fetch("a", || async { Ok("aaaa".to_string()) }).await.unwrap();
+        }
+        assert_eq!(off.hits(), 0);
+    }
+
+    /// A keep-alive HTTP/1.1 server answering `ok`; returns its URL and the
+    /// number of connections it has accepted.
+    async fn counting_server() -> (String, std::sync::Arc<AtomicUsize>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let accepted = std::sync::Arc::new(AtomicUsize::new(0));
+        let counter = accepted.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 4096];
+                    while matches!(socket.read(&mut buf).await, Ok(n) if n > 0) {
+                        let reply = b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok";
+                        if socket.write_all(reply).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        (url, accepted)
+    }
+
+    async fn connections_for(pool: PoolSettings, pause: Duration) -> usize {
+        let (url, accepted) = counting_server().await;
+        let client = build_client(Duration::from_secs(5), &pool);
+        for _ in 0..3 {
+            assert_eq!(client.get(&url).send().await.unwrap().text().await.unwrap(), "ok");
+            tokio::time::sleep(pause).await;
+        }
+        accepted.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn test_client_applies_pool_settings() {
+        // Default settings reuse one connection for sequential requests
+        assert_eq!(connections_for(PoolSettings::default(), Duration::ZERO).await, 1);
+
+        // No idle connections kept: a new connection per request
+        let no_idle = PoolSettings {
+            max_idle_per_host: 0,
+            ..Default::default()
+        };
+        assert_eq!(connections_for(no_idle, Duration::ZERO).await, 3);
+
+        // Idle connections closed before the next request
+        let short_lived = PoolSettings {
+            idle_timeout: Duration::from_millis(50),
+            ..Default::default()
+        };
+        assert_eq!(connections_for(short_lived, Duration::from_millis(300)).await, 3);
+    }
+
+    #[tokio::test]
+    async fn test_requests_to_one_host_are_spaced_by_the_delay() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).respond_with(ResponseTemplate::new(200)).mount(&server).await;
+        let pacer = HostPacer::new(Duration::from_millis(150));
+        let client = Client::new();
+        let url = format!("{}/a", server.uri());
+
+        let mut sent = Vec::new();
+        for _ in 0..2 {
+            pacer.wait(&url).await;
+            sent.push(Instant::now());
+            client.get(&url).send().await.unwrap();
+        }
+        assert!(sent[1] - sent[0] >= Duration::from_millis(150), "{:?}", sent[1] - sent[0]);
+
+        // Another host doesn't wait; concurrent requests queue up in turn
+        let start = Instant::now();
+        pacer.wait("https://text.npr.org/1").await;
+        assert!(start.elapsed() < Duration::from_millis(100));
+        let start = Instant::now();
+        futures::future::join(pacer.wait("https://lite.cnn.com/1"), pacer.wait("https://lite.cnn.com/2")).await;
+        assert!(start.elapsed() >= Duration::from_millis(150));
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    fn gzip(body: &str) -> Vec<u8> {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(body.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_gzip_bodies_are_decoded_and_counted() {
+        let page = "<html><body><p>The council met on Tuesday to vote on the budget.</p></body></html>".repeat(20);
+        let compressed = gzip(&page);
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/gzip"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-encoding", "gzip")
+                    .set_body_bytes(compressed.clone()),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/plain"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("plain body"))
+            .mount(&server)
+            .await;
+
+        let stats = Mutex::new(BTreeMap::new());
+        let client = Client::new();
+        for (source, route, expected) in [("cnn", "gzip", &page), ("cnn", "gzip", &page), ("npr", "plain", &"plain body".to_string())] {
+            let res = conditional_get(&client, &format!("{}/{}", server.uri(), route), None).send().await.unwrap();
+            assert_eq!(&read_body_into(&stats, res, source, DEFAULT_MAX_BODY_BYTES).await.unwrap(), expected);
+        }
+        let requests = server.received_requests().await.unwrap();
+        assert!(requests.iter().all(|r| r.headers["accept-encoding"] == ACCEPT_ENCODING_VALUE));
+
+        let stats = stats.into_inner().unwrap();
+        let twice = |n: usize| 2 * n as u64;
+        assert_eq!(
+            stats["cnn"],
+            TransferBytes {
+                compressed: twice(compressed.len()),
+                decompressed: twice(page.len())
+            }
+        );
+        assert!(stats["cnn"].compressed < stats["cnn"].decompressed);
+        assert_eq!(stats["npr"], TransferBytes { compressed: 10, decompressed: 10 });
+    }
+
+    /// A server answering every request with an endless chunked body.
+    async fn endless_server() -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 4096];
+                    let _ = socket.read(&mut buf).await;
+                    let head = b"HTTP/1.1 200 OK\r\ntransfer-encoding: chunked\r\n\r\n";
+                    if socket.write_all(head).await.is_err() {
+                        return;
+                    }
+                    let chunk = format!("{:x}\r\n{}\r\n", 64 * 1024, "a".repeat(64 * 1024));
+                    while socket.write_all(chunk.as_bytes()).await.is_ok() {}
+                });
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_oversized_bodies_are_abandoned() {
+        let url = endless_server().await;
+        let client = build_client(Duration::from_secs(10), &PoolSettings::default());
+
+        let stats = Mutex::new(BTreeMap::new());
+        let res = client.get(&url).send().await.unwrap();
+        let e = read_body_into(&stats, res, "cnn", 200_000).await.unwrap_err();
+        assert_eq!(e.downcast_ref::<BodyTooLarge>(), Some(&BodyTooLarge { limit: 200_000 }));
+        assert_eq!(error_detail(e.as_ref()), Some("body_too_large"));
+        assert!(stats.lock().unwrap().is_empty());
+
+        // The shared helper stops at the default limit and counts the page
+        let before = bodies_too_large();
+        let res = client.get(&url).send().await.unwrap();
+        let e = read_body(res, "cnn").await.unwrap_err();
+        assert_eq!(e.downcast_ref::<BodyTooLarge>(), Some(&BodyTooLarge { limit: DEFAULT_MAX_BODY_BYTES }));
+        assert_eq!(crate::scrapers::paywall::skip_reason(e.as_ref()), crate::models::SkipReason::FetchError);
+        assert_eq!(bodies_too_large(), before + 1);
+
+        // A declared length over the limit is refused without reading
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("x".repeat(1000)))
+            .mount(&server)
+            .await;
+        let res = client.get(server.uri()).send().await.unwrap();
+        assert!(read_body_into(&stats, res, "npr", 999).await.unwrap_err().is::<BodyTooLarge>());
+        let res = client.get(server.uri()).send().await.unwrap();
+        assert_eq!(read_body_into(&stats, res, "npr", 1000).await.unwrap().len(), 1000);
+        assert_eq!(error_detail(&*Box::<dyn Error>::from("timed out")), None);
+    }
+
+    #[test]
+    fn test_slow_sources_keep_a_longer_timeout() {
+        assert!(source_timeout("nytimes").unwrap() >= Duration::from_secs(30));
+        assert_eq!(source_timeout("cnn"), None);
+    }
+
+    #[test]
+    fn test_decode_body_handles_deflate_and_brotli_and_rejects_unknown_encodings() {
+        use flate2::write::ZlibEncoder;
+        use std::io::Write;
+        let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"deflated").unwrap();
+        assert_eq!(decode_body("deflate", &encoder.finish().unwrap(), 100).unwrap(), b"deflated");
+        assert_eq!(decode_body("identity", b"as is", 100).unwrap(), b"as is");
+        let mut compressed = Vec::new();
+        brotli::BrotliCompress(&mut &b"brotli"[..], &mut compressed, &Default::default()).unwrap();
+        assert_eq!(decode_body("br", &compressed, 100).unwrap(), b"brotli");
+        assert!(decode_body("br", b"not brotli", 100).is_err());
+        assert!(decode_body("zstd", b"\x28\xb5\x2f\xfd", 100).is_err());
+        assert!(decode_body("gzip", b"not gzip", 100).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_compressed_bodies_over_the_limit_are_abandoned() {
+        // 10 MB of zeros compresses to about 10 KB, well under the limit on the wire
+        let bomb = gzip(&"\0".repeat(10 * 1024 * 1024));
+        let limit = 1024 * 1024;
+        assert!(bomb.len() < limit);
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-encoding", "gzip")
+                    .set_body_bytes(bomb.clone()),
+            )
+            .mount(&server)
+            .await;
+
+        let stats = Mutex::new(BTreeMap::new());
+        let res = Client::new().get(server.uri()).send().await.unwrap();
+        let e = read_body_into(&stats, res, "cnn", limit).await.unwrap_err();
+        assert_eq!(e.downcast_ref::<BodyTooLarge>(), Some(&BodyTooLarge { limit }));
+        assert!(stats.lock().unwrap().is_empty());
+
+        // Decoding stops right past the limit
+        let e = decode_body("gzip", &bomb, 100).unwrap_err();
+        assert!(e.is::<BodyTooLarge>());
+        assert_eq!(decode_body("gzip", &gzip(&"a".repeat(100)), 100).unwrap().len(), 100);
+    }
+}
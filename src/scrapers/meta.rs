@@ -0,0 +1,543 @@
+//! Publication timestamp and byline extraction shared by the HTML scrapers.
+//!
+//! The LLM is unreliable at reading publication times from article text and
+//! will happily invent one (`"14:30:00"`) for a piece that has none. Most
+//! outlets publish a machine-readable timestamp, so the scrapers extract it
+//! here and `main.rs` uses it to ground (or blank) the LLM's fields.
+//!
+//! # Sources
+//!
+//! Checked in priority order; the first parseable RFC 3339 timestamp wins:
+//!
+//! 1. JSON-LD `datePublished` on a `NewsArticle`-like object (including
+//!    objects inside arrays and `@graph`)
+//! 2. `<meta property="article:published_time">` and similar meta tags
+//! 3. `<time datetime="...">`
+//!
+//! Values that look like unrendered template placeholders (`[date]`) are
+//! ignored. Pages with none of these may still say when they were last
+//! updated; [`extract_modified`] reads JSON-LD `dateModified`, then
+//! `article:modified_time` and `og:updated_time`, for scrapers that fall
+//! back to it.
+//!
+//! # Authors
+//!
+//! [`extract_authors`] reads the byline the same way: JSON-LD `author` on
+//! the article node (a name, a `Person` object or a list of either), then
+//! author meta tags, then the site's visible byline element. Bylines such as
+//! `By Jane Doe and John Roe, CNN` are split into names, dropping the
+//! leading "By", outlet names and profile URLs. An article without a
+//! byline gets no authors, and the LLM may fill them in from the text.
+//!
+//! # Canonical URL
+//!
+//! [`extract_canonical`] reads `<link rel="canonical">`, then `og:url`, so
+//! an article indexed under a tracking or AMP URL is stored under the one
+//! the outlet considers its own (see [`NewsArticle::with_canonical`]).
+//!
+//! [`NewsArticle::with_canonical`]: crate::models::NewsArticle::with_canonical
+
+use crate::models::source_tag_of;
+use crate::utils::normalize_url;
+use chrono::{DateTime, FixedOffset};
+use scraper::{Html, Selector};
+use serde_json::Value;
+use tracing::debug;
+
+/// Meta tags carrying the publication time, in priority order.
+const PUBLISHED_META_SELECTORS: &[&str] = &[
+    r#"meta[property="article:published_time"]"#,
+    r#"meta[name="OriginalPublicationDate"]"#,
+    r#"meta[itemprop="datePublished"]"#,
+    r#"meta[name="date"]"#,
+];
+
+/// Meta tags carrying the last-modified time, in priority order.
+const MODIFIED_META_SELECTORS: &[&str] = &[
+    r#"meta[property="article:modified_time"]"#,
+    r#"meta[property="og:updated_time"]"#,
+];
+
+/// Meta tags carrying the author, in priority order.
+const AUTHOR_META_SELECTORS: &[&str] = &[
+    r#"meta[name="author"]"#,
+    r#"meta[property="article:author"]"#,
+    r#"meta[name="byl"]"#,
+];
+
+/// Names in a byline that are outlets or desks rather than people.
+const NOT_AUTHORS: &[&str] = &[
+    "cnn",
+    "npr",
+    "ap",
+    "associated press",
+    "the associated press",
+    "bbc",
+    "bbc news",
+    "al jazeera",
+    "al jazeera staff",
+    "the new york times",
+    "the washington post",
+    "reuters",
+    "staff",
+];
+
+/// JSON-LD `@type` values treated as articles.
+const ARTICLE_TYPES: &[&str] = &["NewsArticle", "Article", "ReportageNewsArticle", "Report", "BlogPosting"];
+
+/// Collapse runs of whitespace into single spaces.
+pub fn clean(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Whether a value looks like an unrendered template placeholder.
+pub fn looks_like_placeholder(s: &str) -> bool {
+    let t = s.trim();
+    t.contains('[') && t.contains(']')
+}
+
+fn parse_timestamp(raw: &str) -> Option<DateTime<FixedOffset>> {
+    let raw = clean(raw);
+    if looks_like_placeholder(&raw) {
+        return None;
+    }
+    DateTime::parse_from_rfc3339(&raw).ok()
+}
+
+fn is_article(v: &Value) -> bool {
+    match v.get("@type") {
+        Some(Value::String(t)) => ARTICLE_TYPES.contains(&t.as_str()),
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|t| ARTICLE_TYPES.contains(&t)),
+        // Untyped objects are accepted; many sites omit @type on nested nodes
+        _ => true,
+    }
+}
+
+/// Find the timestamp `key` (e.g. `datePublished`) on the first
+/// article-like node of a JSON-LD value that has it.
+fn jsonld_date(v: &Value, key: &str) -> Option<DateTime<FixedOffset>> {
+    match v {
+        Value::Array(items) => items.iter().find_map(|item| jsonld_date(item, key)),
+        Value::Object(obj) => {
+            if let Some(graph) = obj.get("@graph") {
+                return jsonld_date(graph, key);
+            }
+            if is_article(v)
+                && let Some(dt) = obj.get(key).and_then(Value::as_str).and_then(parse_timestamp)
+            {
+                return Some(dt);
+            }
+            obj.get("article").and_then(|article| jsonld_date(article, key))
+        }
+        _ => None,
+    }
+}
+
+fn from_jsonld_key(document: &Html, key: &str) -> Option<DateTime<FixedOffset>> {
+    let sel = Selector::parse(r#"script[type="application/ld+json"]"#).ok()?;
+    document.select(&sel).find_map(|script| {
+        let text = script.text().collect::<String>();
+        let value = serde_json::from_str::<Value>(text.trim()).ok()?;
+        jsonld_date(&value, key)
+    })
+}
+
+fn from_jsonld(document: &Html) -> Option<DateTime<FixedOffset>> {
+    from_jsonld_key(document, "datePublished")
+}
+
+fn from_meta_tags(document: &Html, selectors: &[&str]) -> Option<DateTime<FixedOffset>> {
+    selectors.iter().find_map(|css| {
+        let sel = Selector::parse(css).ok()?;
+        document
+            .select(&sel)
+            .find_map(|n| n.value().attr("content").and_then(parse_timestamp))
+    })
+}
+
+fn from_meta(document: &Html) -> Option<DateTime<FixedOffset>> {
+    from_meta_tags(document, PUBLISHED_META_SELECTORS)
+}
+
+fn from_time_element(document: &Html) -> Option<DateTime<FixedOffset>> {
+    let sel = Selector::parse("time[datetime]").ok()?;
+    document
+        .select(&sel)
+        .find_map(|t| t.value().attr("datetime").and_then(parse_timestamp))
+}
+
+/// Extract an article's publication timestamp from its HTML.
+///
+/// # Returns
+///
+/// The first timestamp found in JSON-LD, meta tags, or `<time>` elements
+/// (in that order), or `None` if the page carries no full timestamp.
+pub fn extract_published(document: &Html) -> Option<DateTime<FixedOffset>> {
+    let found = [
+        ("jsonld", from_jsonld as fn(&Html) -> Option<DateTime<FixedOffset>>),
+        ("meta", from_meta),
+        ("time[datetime]", from_time_element),
+    ]
+    .into_iter()
+    .find_map(|(source, extract)| extract(document).map(|dt| (source, dt)));
+
+    match found {
+        Some((source, dt)) => {
+            debug!(source, published = %dt.to_rfc3339(), "Extracted publication timestamp");
+            Some(dt)
+        }
+        None => {
+            debug!("No publication timestamp found");
+            None
+        }
+    }
+}
+
+/// Extract when an article was last updated, for pages that don't say when
+/// it was published.
+///
+/// # Returns
+///
+/// The JSON-LD `dateModified` of the article, else the first timestamp in
+/// its modified-time meta tags, or `None`.
+pub fn extract_modified(document: &Html) -> Option<DateTime<FixedOffset>> {
+    let found = from_jsonld_key(document, "dateModified").or_else(|| from_meta_tags(document, MODIFIED_META_SELECTORS));
+    if let Some(dt) = found {
+        debug!(modified = %dt.to_rfc3339(), "Extracted last-modified timestamp");
+    }
+    found
+}
+
+/// Split a byline into author names.
+///
+/// `By Jane Doe, John Roe and Ann Poe, CNN` gives the three names; outlet
+/// names, profile URLs and placeholders are dropped, and repeats are kept
+/// once.
+pub fn parse_byline(raw: &str) -> Vec<String> {
+    let raw = clean(raw);
+    let raw = ["by ", "written by "]
+        .iter()
+        .find_map(|prefix| raw.get(..prefix.len()).filter(|p| p.eq_ignore_ascii_case(prefix)).map(|_| &raw[prefix.len()..]))
+        .unwrap_or(&raw);
+    let mut names: Vec<String> = Vec::new();
+    for part in raw.split([',', '&', ';']).flat_map(|p| p.split(" and ")) {
+        let name = part.trim();
+        let ignored = name.is_empty()
+            || name.starts_with("http")
+            || looks_like_placeholder(name)
+            || NOT_AUTHORS.contains(&name.to_lowercase().as_str());
+        if !ignored && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Author names of a JSON-LD `author` value.
+fn jsonld_author_names(v: &Value) -> Vec<String> {
+    match v {
+        Value::String(name) => parse_byline(name),
+        Value::Object(obj) => obj.get("name").map(jsonld_author_names).unwrap_or_default(),
+        Value::Array(items) => {
+            let mut names: Vec<String> = Vec::new();
+            for name in items.iter().flat_map(jsonld_author_names) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            names
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Find `author` on the first article-like node of a JSON-LD value.
+fn jsonld_authors(v: &Value) -> Option<Vec<String>> {
+    match v {
+        Value::Array(items) => items.iter().find_map(jsonld_authors),
+        Value::Object(obj) => {
+            if let Some(graph) = obj.get("@graph") {
+                return jsonld_authors(graph);
+            }
+            if is_article(v)
+                && let Some(author) = obj.get("author")
+            {
+                let names = jsonld_author_names(author);
+                if !names.is_empty() {
+                    return Some(names);
+                }
+            }
+            obj.get("article").and_then(jsonld_authors)
+        }
+        _ => None,
+    }
+}
+
+fn authors_from_jsonld(document: &Html) -> Option<Vec<String>> {
+    let sel = Selector::parse(r#"script[type="application/ld+json"]"#).ok()?;
+    document.select(&sel).find_map(|script| {
+        let text = script.text().collect::<String>();
+        let value = serde_json::from_str::<Value>(text.trim()).ok()?;
+        jsonld_authors(&value)
+    })
+}
+
+fn authors_from_meta(document: &Html) -> Option<Vec<String>> {
+    AUTHOR_META_SELECTORS.iter().find_map(|css| {
+        let sel = Selector::parse(css).ok()?;
+        document
+            .select(&sel)
+            .filter_map(|n| n.value().attr("content"))
+            .map(parse_byline)
+            .find(|names| !names.is_empty())
+    })
+}
+
+fn authors_from_byline(document: &Html, css: &str) -> Option<Vec<String>> {
+    let sel = Selector::parse(css).ok()?;
+    document
+        .select(&sel)
+        .map(|el| clean(&el.text().collect::<String>()))
+        // A byline element shares its container with dates and credits
+        .filter(|text| text.get(..3).is_some_and(|p| p.eq_ignore_ascii_case("by ")))
+        .map(|text| parse_byline(&text))
+        .find(|names| !names.is_empty())
+}
+
+/// Extract an article's authors from its HTML.
+///
+/// `byline_css` selects the site's visible byline element, checked after
+/// JSON-LD and meta tags; only elements whose text starts with "By" count.
+///
+/// # Returns
+///
+/// The author names in byline order, or an empty list if the page has no
+/// byline.
+pub fn extract_authors(document: &Html, byline_css: Option<&str>) -> Vec<String> {
+    let found = authors_from_jsonld(document)
+        .map(|names| ("jsonld", names))
+        .or_else(|| authors_from_meta(document).map(|names| ("meta", names)))
+        .or_else(|| byline_css.and_then(|css| authors_from_byline(document, css)).map(|names| ("byline", names)));
+
+    match found {
+        Some((source, names)) => {
+            debug!(source, authors = ?names, "Extracted authors");
+            names
+        }
+        None => {
+            debug!("No byline found");
+            Vec::new()
+        }
+    }
+}
+
+/// Elements carrying the canonical URL and its attribute, in priority order.
+const CANONICAL_SELECTORS: &[(&str, &str)] = &[
+    (r#"link[rel~="canonical"]"#, "href"),
+    (r#"meta[property="og:url"]"#, "content"),
+];
+
+/// Extract the canonical URL of the page downloaded from `fetched`.
+///
+/// Relative URLs are resolved against `fetched`. A canonical URL on another
+/// outlet (a syndicated copy pointing at the original) or that isn't
+/// http(s) is ignored, as are placeholders.
+///
+/// # Returns
+///
+/// The canonical URL with tracking parameters stripped, or `None` if the
+/// page names none.
+pub fn extract_canonical(document: &Html, fetched: &str) -> Option<String> {
+    let base = url::Url::parse(fetched).ok()?;
+    let found = CANONICAL_SELECTORS.iter().find_map(|(css, attr)| {
+        let sel = Selector::parse(css).ok()?;
+        document.select(&sel).find_map(|el| {
+            let raw = el.value().attr(attr)?.trim();
+            if raw.is_empty() || looks_like_placeholder(raw) {
+                return None;
+            }
+            let url = base.join(raw).ok()?;
+            let same_outlet = source_tag_of(url.as_str()) == source_tag_of(fetched);
+            (matches!(url.scheme(), "http" | "https") && same_outlet).then(|| normalize_url(url.as_str()))
+        })
+    });
+    debug!(canonical = ?found, "Extracted canonical URL");
+    found
+}
+
+/// Human-readable publication text from the first element matching `css`.
+///
+/// Used as a last resort for the article header when no machine-readable
+/// timestamp exists (e.g. "Published On 18 Oct 2025").
+pub fn published_text(document: &Html, css: &str) -> Option<String> {
+    let sel = Selector::parse(css).ok()?;
+    let el = document.select(&sel).next()?;
+    let raw = clean(&el.text().collect::<String>());
+    (!raw.is_empty() && !looks_like_placeholder(&raw)).then_some(raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSONLD: &str = r#"<script type="application/ld+json">
+        {"@context":"https://schema.org","@type":"NewsArticle","datePublished":"2025-05-06T09:15:00+00:00"}
+    </script>"#;
+    const META: &str = r#"<meta property="article:published_time" content="2025-05-06T10:15:00Z">"#;
+    const TIME: &str = r#"<time datetime="2025-05-06T11:15:00-04:00">May 6</time>"#;
+
+    fn doc(head: &str, body: &str) -> Html {
+        Html::parse_document(&format!("<html><head>{head}</head><body>{body}</body></html>"))
+    }
+
+    fn ts(s: &str) -> Option<DateTime<FixedOffset>> {
+        Some(DateTime::parse_from_rfc3339(s).unwrap())
+    }
+
+    #[test]
+    fn test_jsonld_date_published() {
+        assert_eq!(extract_published(&doc(JSONLD, "")), ts("2025-05-06T09:15:00+00:00"));
+    }
+
+    #[test]
+    fn test_jsonld_graph_skips_non_articles() {
+        let head = r#"<script type="application/ld+json">
+            {"@graph":[
+                {"@type":"WebPage","datePublished":"2020-01-01T00:00:00Z"},
+                {"@type":["NewsArticle"],"datePublished":"2025-05-06T09:15:00Z"}
+            ]}
+        </script>"#;
+        assert_eq!(extract_published(&doc(head, "")), ts("2025-05-06T09:15:00Z"));
+    }
+
+    #[test]
+    fn test_meta_published_time() {
+        assert_eq!(extract_published(&doc(META, "")), ts("2025-05-06T10:15:00Z"));
+    }
+
+    #[test]
+    fn test_time_element() {
+        assert_eq!(extract_published(&doc("", TIME)), ts("2025-05-06T11:15:00-04:00"));
+    }
+
+    #[test]
+    fn test_precedence_order() {
+        let all = doc(&format!("{JSONLD}{META}"), TIME);
+        assert_eq!(extract_published(&all), ts("2025-05-06T09:15:00+00:00"));
+
+        let meta_and_time = doc(META, TIME);
+        assert_eq!(extract_published(&meta_and_time), ts("2025-05-06T10:15:00Z"));
+    }
+
+    #[test]
+    fn test_placeholders_and_date_only_values_are_ignored() {
+        let head = r#"<meta property="article:published_time" content="[published_time]">
+            <meta itemprop="datePublished" content="2025-05-06">"#;
+        assert_eq!(extract_published(&doc(head, "")), None);
+        assert_eq!(extract_published(&doc(head, TIME)), ts("2025-05-06T11:15:00-04:00"));
+    }
+
+    #[test]
+    fn test_modified_time_fallbacks() {
+        let jsonld = r#"<script type="application/ld+json">
+            {"@type":"NewsArticle","dateModified":"2025-05-06T12:00:00Z"}
+        </script>"#;
+        let og = r#"<meta property="og:updated_time" content="2025-05-06T13:00:00+03:00">"#;
+        assert_eq!(extract_published(&doc(jsonld, "")), None);
+        assert_eq!(extract_modified(&doc(jsonld, "")), ts("2025-05-06T12:00:00Z"));
+        assert_eq!(extract_modified(&doc(og, "")), ts("2025-05-06T13:00:00+03:00"));
+        assert_eq!(extract_modified(&doc(&format!("{og}{jsonld}"), "")), ts("2025-05-06T12:00:00Z"));
+        assert_eq!(extract_modified(&doc(META, TIME)), None);
+    }
+
+    #[test]
+    fn test_published_text_fallback() {
+        let d = doc("", r#"<div class="date">  Published On  18 Oct 2025 </div>"#);
+        assert_eq!(published_text(&d, ".date").as_deref(), Some("Published On 18 Oct 2025"));
+        assert_eq!(published_text(&d, "time"), None);
+    }
+
+    // Trimmed-down article pages of the outlets, bylines included
+    const CNN_LITE: &str = r#"<html><head><title>Talks resume - CNN</title></head><body>
+        <div class="layout-container--lite">
+            <h2 class="headline--lite">Ceasefire talks resume in Cairo</h2>
+            <p class="byline--lite">By Jane Doe, John Roe and Ann Poe, CNN</p>
+            <p class="timestamp--lite">Updated: 9:15 AM EDT, Tue May 6, 2025</p>
+            <div class="article--lite"><p>Negotiators met again on Tuesday.</p></div>
+        </div></body></html>"#;
+    const NPR_TEXT: &str = r#"<html><head>
+        <script type="application/ld+json">{"@context":"http://schema.org","@type":"NewsArticle",
+            "headline":"Probe launched","datePublished":"2025-05-06T09:15:00-04:00",
+            "author":{"@type":"Person","name":["Sam Example"]}}</script>
+        </head><body>
+        <div class="story-head"><h1 class="story-title">Probe launched</h1>
+            <p>By Sam Example</p><p>Tuesday, May 6, 2025 &bull; 9:15 AM EDT</p></div>
+        <div class="paragraphs-container"><p>Liftoff came at dawn.</p></div></body></html>"#;
+
+    #[test]
+    fn test_cnn_lite_byline() {
+        let document = Html::parse_document(CNN_LITE);
+        assert_eq!(extract_authors(&document, Some(".byline--lite")), vec!["Jane Doe", "John Roe", "Ann Poe"]);
+        // Without the site's byline selector there is nothing to go on
+        assert!(extract_authors(&document, None).is_empty());
+    }
+
+    #[test]
+    fn test_npr_text_byline() {
+        let document = Html::parse_document(NPR_TEXT);
+        assert_eq!(extract_authors(&document, Some(".story-head p")), vec!["Sam Example"]);
+
+        // The visible byline is used when the page has no JSON-LD
+        let plain = Html::parse_document(&NPR_TEXT.replace("application/ld+json", "text/plain"));
+        assert_eq!(extract_authors(&plain, Some(".story-head p")), vec!["Sam Example"]);
+    }
+
+    #[test]
+    fn test_jsonld_and_meta_authors() {
+        let head = r#"<script type="application/ld+json">
+            {"@graph":[{"@type":"WebPage","author":"Site Team"},
+                       {"@type":"NewsArticle","author":[{"@type":"Person","name":"Jane Doe"},"John Roe",
+                                                        {"@type":"Organization","name":"Reuters"}]}]}
+        </script>"#;
+        assert_eq!(extract_authors(&doc(head, ""), None), vec!["Jane Doe", "John Roe"]);
+
+        let meta = r#"<meta property="article:author" content="https://www.bbc.com/profiles/jd">
+            <meta name="byl" content="By Jane Doe &amp; John Roe">"#;
+        assert_eq!(extract_authors(&doc(meta, ""), None), vec!["Jane Doe", "John Roe"]);
+    }
+
+    #[test]
+    fn test_parse_byline() {
+        assert_eq!(parse_byline("BY  Jane Doe and the Associated Press"), vec!["Jane Doe"]);
+        assert_eq!(parse_byline("Jane Doe; Jane Doe"), vec!["Jane Doe"]);
+        assert!(parse_byline("By [author]").is_empty());
+        assert!(parse_byline("Al Jazeera Staff").is_empty());
+    }
+
+    #[test]
+    fn test_extract_canonical() {
+        let fetched = "https://apnews.com/article/vote-abc123?utm_source=homepage&utm_medium=x";
+        let html = Html::parse_document(
+            r#"<html><head>
+            <link rel="amphtml" href="https://apnews.com/amp/vote-abc123">
+            <link rel="canonical" href="/article/vote-abc123?utm_campaign=share">
+            <meta property="og:url" content="https://apnews.com/article/other">
+            </head><body></body></html>"#,
+        );
+        assert_eq!(extract_canonical(&html, fetched).as_deref(), Some("https://apnews.com/article/vote-abc123"));
+
+        // og:url is the fallback
+        let html = Html::parse_document(
+            r#"<head><meta property="og:url" content="https://apnews.com/article/vote-abc123#comments"></head>"#,
+        );
+        assert_eq!(extract_canonical(&html, fetched).as_deref(), Some("https://apnews.com/article/vote-abc123"));
+
+        // A syndicated copy pointing at another outlet keeps its own URL
+        let html = Html::parse_document(r#"<head><link rel="canonical" href="https://www.reuters.com/world/vote"></head>"#);
+        assert_eq!(extract_canonical(&html, fetched), None);
+        assert_eq!(extract_canonical(&Html::parse_document("<p>No head</p>"), fetched), None);
+    }
+}
@@ -15,22 +15,266 @@
 //! | AP News | [`apnews`] | Google News search | Uses Google to find recent articles |
 //! | Al Jazeera | [`aljazeera`] | HTML scraping | Multiple sections: news, climate, tech |
 //! | BBC News | [`bbcnews`] | HTML scraping | Homepage articles only |
-//! | New York Times | [`nyt`] | Top Stories API | Requires API key; uses proxy for content |
+//! | New York Times | [`nyt`] | Top Stories API | Requires API key; uses proxy for content, API abstract as fallback |
+//! | Washington Post | [`wapo`] | RSS feeds | AMP pages; paywalled stubs dropped |
+//!
+//! The same list is kept in machine-readable form in [`SCRAPERS`], which
+//! every run publishes as `sources.json` (see
+//! [`write_sources`](crate::outputs::json::write_sources)). A run indexes
+//! and fetches by walking [`SCRAPERS`] in order and calling
+//! [`ScraperInfo::index`] and [`ScraperInfo::fetch`] on each entry, so a new
+//! scraper is wired in by adding its entry and its [`Source`] arm.
 //!
 //! # Common Patterns
 //!
 //! Each scraper module exports:
-//! - `index_articles()`: Returns a list of article URLs
-//! - `fetch_articles(urls)`: Fetches content from the URLs, returns `Vec<NewsArticle>`
+//! - `index_articles()`: Returns a list of article URLs (NYT: API stories)
+//! - `fetch_articles(urls, sink, skips)`: Fetches content from the URLs, pushing
+//!   each `NewsArticle` to the sink as it arrives
+//!
+//! [`ScraperInfo`] wraps both behind one [`Listing`] type, so the run can
+//! filter and sample every source's index the same way.
 //!
 //! Scrapers use:
 //! - Concurrent fetching with `futures::stream` for performance
-//! - Graceful error handling (failed fetches are logged and skipped)
-//! - Date extraction from multiple sources (JSON-LD, meta tags, etc.)
+//! - Graceful error handling (failed fetches are logged, skipped and recorded
+//!   with a [`SkipReason`](crate::models::SkipReason))
+//! - Publication timestamps from JSON-LD, meta tags, and `<time>` via [`meta`]
+//! - One shared client and conditional homepage requests via the [`http`] helpers
+//! - A per-source time budget for indexing and fetching via [`budget`]
+//! - Per-source boilerplate stripping of extracted text via [`boilerplate`]
+//! - Detection of paywalled or subscription-gated pages via [`paywall`]
+//! - Index, headline and body selectors from [`selectors`], overridable
+//!   with `--selectors-file`
+//! - Optional `robots.txt` filtering of indexed URLs via [`robots`]
+
+use crate::models::{source_tag_of, NewsArticle, ScrapeMeta};
+use crate::scrapers::budget::ArticleSink;
+use crate::skips::SkipLog;
+use serde::Serialize;
+use std::error::Error;
+
+/// How a scraper discovers its articles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexMethod {
+    /// Links scraped from HTML pages.
+    Html,
+    /// A JSON API.
+    Api,
+    /// RSS feeds.
+    Rss,
+}
+
+/// The scraper module behind a [`SCRAPERS`] entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Cnn,
+    Npr,
+    ApNews,
+    AlJazeera,
+    BbcNews,
+    Nyt,
+    Wapo,
+}
+
+/// An indexed article waiting to be fetched.
+#[derive(Debug, Clone)]
+pub enum Listing {
+    /// The URL of an article page.
+    Url(String),
+    /// A story from the NYT Top Stories API, whose abstract is the fallback
+    /// when the page can't be fetched.
+    Story(nyt::TopStory),
+}
+
+impl Listing {
+    /// The article URL.
+    pub fn url(&self) -> &str {
+        match self {
+            Listing::Url(url) => url,
+            Listing::Story(story) => &story.url,
+        }
+    }
+}
+
+impl AsRef<str> for Listing {
+    fn as_ref(&self) -> &str {
+        self.url()
+    }
+}
+
+/// One registered scraper.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ScraperInfo {
+    /// The module that indexes and fetches for it.
+    #[serde(skip)]
+    pub source: Source,
+    /// Name used in logs, time budgets and run events.
+    pub name: &'static str,
+    /// Source tag of its article URLs (see [`source_tag_of`]).
+    pub tag: &'static str,
+    /// Display name of the outlet.
+    pub outlet: &'static str,
+    /// The page (or API) articles are indexed from.
+    pub homepage: &'static str,
+    pub method: IndexMethod,
+}
+
+/// Every scraper, in the order they run.
+pub const SCRAPERS: &[ScraperInfo] = &[
+    ScraperInfo {
+        source: Source::Cnn,
+        name: "cnn",
+        tag: "cnn",
+        outlet: "CNN",
+        homepage: "https://lite.cnn.com",
+        method: IndexMethod::Html,
+    },
+    ScraperInfo {
+        source: Source::Npr,
+        name: "npr",
+        tag: "npr",
+        outlet: "NPR",
+        homepage: "https://text.npr.org",
+        method: IndexMethod::Html,
+    },
+    ScraperInfo {
+        source: Source::ApNews,
+        name: "apnews",
+        tag: "apnews",
+        outlet: "AP News",
+        homepage: "https://apnews.com",
+        method: IndexMethod::Html,
+    },
+    ScraperInfo {
+        source: Source::AlJazeera,
+        name: "aljazeera",
+        tag: "aljazeera",
+        outlet: "Al Jazeera",
+        homepage: "https://www.aljazeera.com",
+        method: IndexMethod::Html,
+    },
+    ScraperInfo {
+        source: Source::BbcNews,
+        name: "bbcnews",
+        tag: "bbc",
+        outlet: "BBC News",
+        homepage: "https://www.bbc.com/news",
+        method: IndexMethod::Html,
+    },
+    ScraperInfo {
+        source: Source::Nyt,
+        name: "nyt",
+        tag: "nytimes",
+        outlet: "The New York Times",
+        homepage: "https://api.nytimes.com/svc/topstories/v2/home.json",
+        method: IndexMethod::Api,
+    },
+    ScraperInfo {
+        source: Source::Wapo,
+        name: "wapo",
+        tag: "washingtonpost",
+        outlet: "The Washington Post",
+        homepage: "https://feeds.washingtonpost.com/rss/national",
+        method: IndexMethod::Rss,
+    },
+];
+
+impl ScraperInfo {
+    /// Index the source's articles. NYT is indexed only with an API key.
+    pub async fn index(&self, nyt_api_key: Option<&str>) -> Result<Vec<Listing>, Box<dyn Error>> {
+        let urls = match self.source {
+            Source::Cnn => cnn::index_articles().await?,
+            Source::Npr => npr::index_articles().await?,
+            Source::ApNews => apnews::index_articles().await?,
+            Source::AlJazeera => aljazeera::index_articles().await?,
+            Source::BbcNews => bbcnews::index_articles().await?,
+            Source::Wapo => wapo::index_articles().await?,
+            Source::Nyt => {
+                let stories = nyt::index_articles(nyt_api_key).await?;
+                return Ok(stories.into_iter().map(Listing::Story).collect());
+            }
+        };
+        Ok(urls.into_iter().map(Listing::Url).collect())
+    }
+
+    /// Fetch the articles of `listings`, pushing each to `sink` as it
+    /// arrives and recording the ones left out in `skips`.
+    pub async fn fetch(&self, listings: Vec<Listing>, sink: &ArticleSink, skips: &SkipLog) {
+        let urls = |listings: Vec<Listing>| -> Vec<String> {
+            listings
+                .into_iter()
+                .map(|listing| match listing {
+                    Listing::Url(url) => url,
+                    Listing::Story(story) => story.url,
+                })
+                .collect()
+        };
+        match self.source {
+            Source::Cnn => cnn::fetch_articles(urls(listings), sink, skips).await,
+            Source::Npr => npr::fetch_articles(urls(listings), sink, skips).await,
+            Source::ApNews => apnews::fetch_articles(urls(listings), sink, skips).await,
+            Source::AlJazeera => aljazeera::fetch_articles(urls(listings), sink, skips).await,
+            Source::BbcNews => bbcnews::fetch_articles(urls(listings), sink, skips).await,
+            Source::Wapo => wapo::fetch_articles(urls(listings), sink, skips).await,
+            Source::Nyt => {
+                let stories = listings
+                    .into_iter()
+                    .filter_map(|listing| match listing {
+                        Listing::Story(story) => Some(story),
+                        Listing::Url(_) => None,
+                    })
+                    .collect();
+                nyt::fetch_articles(stories, sink, skips).await
+            }
+        }
+    }
+}
+
+/// Parse a source tag from the command line, e.g. `bbc`.
+pub fn parse_source_tag(raw: &str) -> Result<String, String> {
+    let tag = raw.trim().to_lowercase();
+    if !SCRAPERS.iter().any(|s| s.tag == tag) {
+        let tags: Vec<&str> = SCRAPERS.iter().map(|s| s.tag).collect();
+        return Err(format!("unknown source `{}` (expected one of {})", tag, tags.join(", ")));
+    }
+    Ok(tag)
+}
+
+/// Display name of the outlet an article URL belongs to.
+pub fn outlet_name(url: &str) -> Option<&'static str> {
+    let tag = source_tag_of(url)?;
+    SCRAPERS.iter().find(|s| s.tag == tag).map(|s| s.outlet)
+}
+
+/// Provenance of a scraped article: the scraper behind its URL, this build's
+/// version and that scraper's selector fingerprint. `None` for a URL no
+/// scraper handles.
+pub fn scrape_meta(article: &NewsArticle) -> Option<ScrapeMeta> {
+    let tag = source_tag_of(&article.source)?;
+    let scraper = SCRAPERS.iter().find(|s| s.tag == tag)?;
+    Some(ScrapeMeta {
+        scraper: scraper.name.to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        fetched_at: article.fetched_at,
+        content_length: article.content.chars().count(),
+        selector_hash: selectors::get(scraper.tag).fingerprint.clone(),
+        fetched_from: article.fetched_from.clone(),
+    })
+}
 
 pub mod apnews;
 pub mod cnn;
 pub mod npr;
 pub mod aljazeera;
 pub mod bbcnews;
+pub mod boilerplate;
+pub mod budget;
+pub mod http;
+pub mod meta;
 pub mod nyt;
+pub mod paywall;
+pub mod robots;
+pub mod selectors;
+pub mod wapo;
@@ -8,18 +8,26 @@
 //! Articles are linked from the homepage via `.topic-title` elements,
 //! resolved to URLs like `https://text.npr.org/1234567890`.
 
-use crate::models::NewsArticle;
+use crate::models::{NewsArticle, SkipReason};
+use crate::scrapers::budget::ArticleSink;
+use crate::scrapers::selectors::{self, Selectors};
+use crate::scrapers::{boilerplate, http, meta, paywall};
+use crate::skips::SkipLog;
+use chrono::Utc;
 use futures::stream::{self, StreamExt};
-use reqwest::get;
-use scraper::{Html, Selector};
+use scraper::Html;
 use std::error::Error;
 use tracing::{debug, error, info, instrument, warn};
 use url::Url;
 
+/// Lines of the story header, one of which is the `By ...` byline.
+const BYLINE_SELECTOR: &str = ".story-head p";
+
 /// Index NPR Text homepage to extract article URLs.
 ///
 /// Scrapes the NPR Text homepage and extracts all article links from elements
-/// matching `.topic-title[href]`.
+/// matching `.topic-title[href]`. Uses a conditional request so an unchanged
+/// homepage reuses the previously indexed URLs.
 ///
 /// # Returns
 ///
@@ -29,19 +37,22 @@ pub async fn index_articles() -> Result<Vec<String>, Box<dyn Error>> {
     let npr_page_url = "https://text.npr.org";
     let npr_base_url = Url::parse(npr_page_url)?;
 
-    let html = get(npr_page_url).await?.text().await?;
-    let document = Html::parse_document(&html);
-    let story_selector = Selector::parse(".topic-title").unwrap();
-    
-    let mut article_urls = Vec::new();
-    for element in document.select(&story_selector) {
-        if let Some(href) = element.value().attr("href") {
-            if let Ok(resolved) = npr_base_url.join(href) {
-                article_urls.push(resolved.to_string());
+    let article_urls = http::cached_index(&http::CLIENT, "npr", npr_page_url, |html, _| {
+        let document = Html::parse_document(html);
+        let story_selectors = &selectors::get("npr").index;
+
+        let mut article_urls = Vec::new();
+        for element in story_selectors.iter().flat_map(|sel| document.select(sel)) {
+            if let Some(href) = element.value().attr("href") {
+                if let Ok(resolved) = npr_base_url.join(href) {
+                    article_urls.push(resolved.to_string());
+                }
             }
         }
-    }
-    
+        article_urls
+    })
+    .await?;
+
     info!(
         count = article_urls.len(),
         source = npr_page_url,
@@ -60,13 +71,12 @@ pub async fn index_articles() -> Result<Vec<String>, Box<dyn Error>> {
 /// # Arguments
 ///
 /// * `urls` - Vector of article URLs to fetch
-///
-/// # Returns
-///
-/// A vector of successfully fetched [`NewsArticle`] objects.
+/// * `sink` - Receives each fetched [`NewsArticle`] as soon as it is ready,
+///   so articles survive if the source's time budget runs out
+/// * `skips` - Records the URLs that failed to fetch or had no content
 #[instrument(level = "info", skip_all)]
-pub async fn fetch_articles(urls: Vec<String>) -> Vec<NewsArticle> {
-    let articles: Vec<NewsArticle> = stream::iter(urls.clone())
+pub async fn fetch_articles(urls: Vec<String>, sink: &ArticleSink, skips: &SkipLog) {
+    stream::iter(urls.clone())
         .then(|url: String| async move {
             match fetch_article(&url).await {
                 Ok(Some(article)) => {
@@ -75,46 +85,59 @@ pub async fn fetch_articles(urls: Vec<String>) -> Vec<NewsArticle> {
                 }
                 Ok(None) => {
                     warn!(%url, "NPR fetch produced no content");
+                    skips.record(&url, SkipReason::EmptyContent);
                     None
                 }
                 Err(e) => {
                     error!(error = %e, %url, "NPR fetch failed");
+                    skips.record_fetch_error(&url, e.as_ref());
                     None
                 }
             }
         })
         .filter(|opt| std::future::ready(opt.is_some()))
         .map(|opt| opt.unwrap())
-        .collect()
+        .for_each(|article| {
+            sink.lock().unwrap().push(article);
+            std::future::ready(())
+        })
         .await;
     
-    info!(count = articles.len(), "Fetched NPR article contents");
-    articles
+    info!(count = sink.lock().unwrap().len(), "Fetched NPR article contents");
 }
 
-/// Fetch a single NPR article
-#[instrument(level = "info", skip_all, fields(%url))]
-async fn fetch_article(url: &str) -> Result<Option<NewsArticle>, Box<dyn Error>> {
-    let body = get(url).await?.text().await?;
-    let document = Html::parse_document(&body);
-
+/// Headline and body text of an article page, one line per matched element
+fn article_text(document: &Html, selectors: &Selectors) -> String {
     let mut content = String::new();
-    let headline_selector = Selector::parse(".story-head")?;
-    let article_selector = Selector::parse(".paragraphs-container")?;
-
-    for element in document
-        .select(&headline_selector)
-        .chain(document.select(&article_selector))
+    for element in selectors::first_match(&selectors.headline, document)
+        .into_iter()
+        .chain(selectors::first_match(&selectors.article, document))
     {
         let text = element.text().collect::<Vec<_>>().join(" ");
         content.push_str(&text);
         content.push_str("\n");
     }
+    content
+}
 
+/// Fetch a single NPR article
+#[instrument(level = "info", skip_all, fields(%url))]
+async fn fetch_article(url: &str) -> Result<Option<NewsArticle>, Box<dyn Error>> {
+    let body = http::fetch_text("npr", url).await?;
+    let document = Html::parse_document(&body);
+
+    let content = article_text(&document, selectors::get("npr"));
+    let content = boilerplate::strip("npr", &content);
+    paywall::check(&document, &content)?;
     let len = content.len();
     info!(bytes = len, "Parsed NPR article");
     Ok(Some(NewsArticle {
         source: url.to_string(),
-        content,
-    }))
+        content: content.into(),
+        published_at: meta::extract_published(&document),
+        authors: meta::extract_authors(&document, Some(BYLINE_SELECTOR)),
+        fetched_at: Some(Utc::now()),
+        fetched_from: None,
+    }
+    .with_canonical(meta::extract_canonical(&document, url))))
 }
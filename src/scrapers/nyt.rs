@@ -9,57 +9,64 @@
 //! <https://developer.nytimes.com/>
 //!
 //! Set via `--nyt-api-key` flag or `NYT_API_KEY` environment variable.
+//! Without a key (or with an empty one) NYT is skipped and the run carries
+//! on with the other sources.
 //!
 //! # Content Fetching
 //!
 //! Since NYT articles are paywalled, this scraper uses a proxy service
-//! (accessarticlenow.com) to fetch the full article content.
+//! (accessarticlenow.com) to fetch the full article content. The proxy
+//! throttles often; its `Retry-After` is honored by the shared retry loop,
+//! and NYT requests get a longer timeout than the other sources (see
+//! [`http::SLOW_SOURCE_TIMEOUTS`]).
+//! When a story still can't be fetched, it is built from the API's title,
+//! byline and abstract instead, prefixed with [`ABSTRACT_ONLY_NOTE`] so the
+//! model knows it is summarizing a short abstract. Stories without an
+//! abstract are dropped as before.
 
-use crate::models::NewsArticle;
+use crate::models::{NewsArticle, SkipReason};
+use crate::scrapers::budget::ArticleSink;
+use crate::scrapers::http::CLIENT;
+use crate::scrapers::{boilerplate, http, meta, paywall, selectors};
+use crate::skips::SkipLog;
+use chrono::{DateTime, Utc};
 use futures::stream::{self, StreamExt};
-use once_cell::sync::Lazy;
-use reqwest::Client;
 use scraper::{Html, Selector};
 use serde::Deserialize;
 use std::error::Error;
-use std::time::Duration;
 use tracing::{debug, error, info, instrument, warn};
 
-/// Global HTTP client with browser-like User-Agent and sensible timeouts.
-static CLIENT: Lazy<Client> = Lazy::new(|| {
-    Client::builder()
-        .user_agent(concat!(
-            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) ",
-            "AppleWebKit/537.36 (KHTML, like Gecko) ",
-            "Chrome/127.0.0.0 Safari/537.36"
-        ))
-        .timeout(Duration::from_secs(30))
-        .pool_idle_timeout(Duration::from_secs(10))
-        .redirect(reqwest::redirect::Policy::limited(10))
-        .build()
-        .expect("failed to build reqwest client")
-});
+/// Line opening the content of an article built from the API abstract.
+pub const ABSTRACT_ONLY_NOTE: &str =
+    "Note: the full article could not be fetched; this is only the New York Times abstract.";
+
+/// The visible byline on the article page.
+const BYLINE_SELECTOR: &str = r#"[data-testid="byline"], p.byline"#;
 
 #[derive(Debug, Deserialize)]
 struct NYTimesResponse {
-    results: Vec<NYTimesArticle>,
+    results: Vec<TopStory>,
 }
 
-#[derive(Debug, Deserialize)]
-struct NYTimesArticle {
-    url: String,
-    title: String,
+/// A story listed by the Top Stories API.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TopStory {
+    pub url: String,
+    pub title: String,
+    #[serde(default, rename = "abstract")]
+    pub summary: String,
+    #[serde(default)]
+    pub byline: String,
+    #[serde(default)]
+    pub published_date: Option<String>,
 }
 
 /// Index NYT articles via their Top Stories API
 #[instrument(level = "info")]
-pub async fn index_articles(api_key: Option<&str>) -> Result<Vec<(String, String)>, Box<dyn Error>> {
-    let api_key = match api_key {
-        Some(key) => key,
-        None => {
-            warn!("No NYT API key provided, skipping NYT articles");
-            return Ok(Vec::new());
-        }
+pub async fn index_articles(api_key: Option<&str>) -> Result<Vec<TopStory>, Box<dyn Error>> {
+    let Some(api_key) = api_key.filter(|key| !key.trim().is_empty()) else {
+        info!("No NYT API key provided; skipping NYT articles");
+        return Ok(Vec::new());
     };
     
     let api_url = format!(
@@ -69,7 +76,11 @@ pub async fn index_articles(api_key: Option<&str>) -> Result<Vec<(String, String
 
     info!("Fetching NYT top stories from API");
     
-    let response = CLIENT.get(&api_url).send().await?;
+    let mut request = CLIENT.get(&api_url);
+    if let Some(timeout) = http::source_timeout("nytimes") {
+        request = request.timeout(timeout);
+    }
+    let response = request.send().await?;
     
     if !response.status().is_success() {
         let status = response.status();
@@ -80,40 +91,41 @@ pub async fn index_articles(api_key: Option<&str>) -> Result<Vec<(String, String
 
     let nyt_response: NYTimesResponse = response.json().await?;
     
-    // Take first 30 URLs and titles
-    let articles: Vec<(String, String)> = nyt_response
-        .results
-        .into_iter()
-        .take(30)
-        .map(|article| (article.url, article.title))
-        .collect();
+    // Take first 30 stories
+    let articles: Vec<TopStory> = nyt_response.results.into_iter().take(30).collect();
 
     info!(
         count = articles.len(),
         source = "NYT Top Stories API",
         "Indexed NYT article URLs and titles"
     );
-    debug!(articles = ?articles, "NYT URLs and titles");
+    debug!(urls = ?articles.iter().map(|s| &s.url).collect::<Vec<_>>(), "NYT URLs");
 
     Ok(articles)
 }
 
-/// Fetch all NYT articles concurrently through removepaywalls.com
+/// Fetch all NYT articles concurrently through removepaywalls.com,
+/// falling back to the API abstract for stories the proxy won't serve
+///
+/// Stories with neither are recorded in `skips`: a page with under
+/// [`paywall::MIN_ARTICLE_BYTES`] of article text counts as too short, a gated one (see
+/// [`paywall`]) as paywalled.
 #[instrument(level = "info", skip_all)]
-pub async fn fetch_articles(articles: Vec<(String, String)>) -> Vec<NewsArticle> {
+pub async fn fetch_articles(stories: Vec<TopStory>, sink: &ArticleSink, skips: &SkipLog) {
     let concurrency = 4usize; // Lower concurrency to be respectful to removepaywalls.com
 
-    let articles: Vec<NewsArticle> = stream::iter(articles.into_iter())
-        .map(|(url, api_title)| async move {
-            let res = fetch_article(&url, &api_title).await;
-            (url, res)
+    stream::iter(stories.into_iter())
+        .map(|story| async move {
+            let res = fetch_article(&story.url, &story.title).await;
+            (story, res)
         })
         .buffer_unordered(concurrency)
-        .filter_map(|(url, res)| async move {
-            match res {
+        .filter_map(|(story, res)| async move {
+            let url = &story.url;
+            let error = match res {
                 Ok(Some(article)) => {
                     debug!(%url, "Fetched NYT article");
-                    Some(article)
+                    return Some(article);
                 }
                 Ok(None) => {
                     warn!(%url, "NYT fetch produced no content");
@@ -121,15 +133,56 @@ pub async fn fetch_articles(articles: Vec<(String, String)>) -> Vec<NewsArticle>
                 }
                 Err(e) => {
                     error!(error = %e, %url, "NYT fetch failed");
-                    None
+                    Some(e)
+                }
+            };
+            let fallback = fallback_article(&story);
+            match &fallback {
+                Some(_) => info!(%url, "Using the NYT API abstract instead"),
+                None => {
+                    warn!(%url, "No NYT abstract to fall back to");
+                    match error {
+                        Some(e) => skips.record_fetch_error(url, e.as_ref()),
+                        None => skips.record(url, SkipReason::TooShort),
+                    }
                 }
             }
+            fallback
+        })
+        .for_each(|article| {
+            sink.lock().unwrap().push(article);
+            std::future::ready(())
         })
-        .collect()
         .await;
 
-    info!(count = articles.len(), "Fetched NYT article contents");
-    articles
+    info!(count = sink.lock().unwrap().len(), "Fetched NYT article contents");
+}
+
+/// An article built from the API listing alone: title, byline and abstract
+/// under [`ABSTRACT_ONLY_NOTE`]. `None` if the story has no abstract.
+pub fn fallback_article(story: &TopStory) -> Option<NewsArticle> {
+    let summary = story.summary.trim();
+    if summary.is_empty() {
+        return None;
+    }
+    let mut content = format!("{}\n\n# {}\n\n", ABSTRACT_ONLY_NOTE, story.title.trim());
+    let byline = story.byline.trim();
+    if !byline.is_empty() {
+        content.push_str(&format!("{}\n\n", byline));
+    }
+    content.push_str(summary);
+    Some(NewsArticle {
+        source: story.url.clone(),
+        content: content.into(),
+        published_at: story
+            .published_date
+            .as_deref()
+            .and_then(|date| DateTime::parse_from_rfc3339(date).ok()),
+        authors: meta::parse_byline(&story.byline),
+        fetched_at: Some(Utc::now()),
+        fetched_from: None,
+    }
+    .with_canonical(None))
 }
 
 /// Fetch a single NYT article through accessarticlenow.com (the iframe backend)
@@ -140,20 +193,13 @@ async fn fetch_article(url: &str, api_title: &str) -> Result<Option<NewsArticle>
     
     info!(%proxy_url, "Fetching through accessarticlenow.com");
     
-    let body = CLIENT.get(&proxy_url).send().await?.text().await?;
+    let body = http::fetch_text("nytimes", &proxy_url).await?;
     let document = Html::parse_document(&body);
 
+    let selectors = selectors::get("nytimes");
+
     // Extract title
-    let title_selector = Selector::parse(r#"h1[data-testid="headline"]"#)
-        .or_else(|_| Selector::parse("h1.css-88wicj"))
-        .or_else(|_| Selector::parse("h1"))
-        .unwrap();
-    
-    let scraped_title = document
-        .select(&title_selector)
-        .next()
-        .map(|el| el.text().collect::<String>().trim().to_string())
-        .unwrap_or_default();
+    let scraped_title = selectors::first_text(&selectors.headline, &document).unwrap_or_default();
 
     // Use API title as fallback if scraped title is empty or blank
     let title = if scraped_title.is_empty() || scraped_title.trim().is_empty() {
@@ -193,69 +239,24 @@ async fn fetch_article(url: &str, api_title: &str) -> Result<Option<NewsArticle>
     // Try multiple strategies to extract article body
     let mut paragraphs_found = 0;
     
-    // Strategy 1: Look for section[name="articleBody"]
-    if let Ok(selector) = Selector::parse(r#"section[name="articleBody"]"#) {
-        if let Some(article_section) = document.select(&selector).next() {
-            debug!("Found section[name='articleBody']");
-            if let Ok(p_selector) = Selector::parse("p") {
-                for paragraph in article_section.select(&p_selector) {
-                    let text = paragraph
-                        .text()
-                        .collect::<String>()
-                        .trim()
-                        .to_string();
-                    
-                    if !text.is_empty() && text.len() > 10 {
-                        content.push_str(&text);
-                        content.push_str("\n\n");
-                        paragraphs_found += 1;
-                    }
-                }
+    // Strategies 1-3: the configured body paragraphs (articleBody section,
+    // .StoryBodyCompanionColumn, known paragraph classes), first match wins
+    for selector in &selectors.article {
+        for paragraph in document.select(selector) {
+            let text = paragraph
+                .text()
+                .collect::<String>()
+                .trim()
+                .to_string();
+
+            if !text.is_empty() && text.len() > 10 {
+                content.push_str(&text);
+                content.push_str("\n\n");
+                paragraphs_found += 1;
             }
         }
-    }
-    
-    // Strategy 2: If no paragraphs found, try .StoryBodyCompanionColumn
-    if paragraphs_found == 0 {
-        if let Ok(selector) = Selector::parse(".StoryBodyCompanionColumn") {
-            debug!("Trying .StoryBodyCompanionColumn");
-            if let Ok(p_selector) = Selector::parse("p") {
-                for container in document.select(&selector) {
-                    for paragraph in container.select(&p_selector) {
-                        let text = paragraph
-                            .text()
-                            .collect::<String>()
-                            .trim()
-                            .to_string();
-                        
-                        if !text.is_empty() && text.len() > 10 {
-                            content.push_str(&text);
-                            content.push_str("\n\n");
-                            paragraphs_found += 1;
-                        }
-                    }
-                }
-            }
-        }
-    }
-    
-    // Strategy 3: If still no paragraphs, try any p tag with css-ac37hb class
-    if paragraphs_found == 0 {
-        if let Ok(selector) = Selector::parse("p.css-ac37hb, p.evys1bk0") {
-            debug!("Trying p.css-ac37hb");
-            for paragraph in document.select(&selector) {
-                let text = paragraph
-                    .text()
-                    .collect::<String>()
-                    .trim()
-                    .to_string();
-                
-                if !text.is_empty() && text.len() > 10 {
-                    content.push_str(&text);
-                    content.push_str("\n\n");
-                    paragraphs_found += 1;
-                }
-            }
+        if paragraphs_found > 0 {
+            break;
         }
     }
     
@@ -282,15 +283,22 @@ async fn fetch_article(url: &str, api_title: &str) -> Result<Option<NewsArticle>
     
     debug!(paragraphs_found, "Extracted paragraphs");
 
+    let content = boilerplate::strip("nytimes", &content);
+    paywall::check(&document, &content)?;
     let len = content.len();
     info!(bytes = len, "Parsed NYT article");
 
-    if len > 200 {
+    if len > paywall::MIN_ARTICLE_BYTES {
         // Ensure we have substantial content
         Ok(Some(NewsArticle {
             source: url.to_string(),
-            content,
-        }))
+            content: content.into(),
+            published_at: meta::extract_published(&document),
+            authors: meta::extract_authors(&document, Some(BYLINE_SELECTOR)),
+            fetched_at: Some(Utc::now()),
+            fetched_from: None,
+        }
+        .with_canonical(meta::extract_canonical(&document, url))))
     } else {
         debug!(
             preview = %body.chars().take(600).collect::<String>().replace('\n', " "),
@@ -299,3 +307,59 @@ async fn fetch_article(url: &str, api_title: &str) -> Result<Option<NewsArticle>
         Ok(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE_RESPONSE: &str = r#"{
+        "status": "OK",
+        "results": [
+            {
+                "section": "world",
+                "title": "Ceasefire Talks Resume in Cairo",
+                "abstract": "Negotiators returned on Tuesday after a weeklong pause.",
+                "url": "https://www.nytimes.com/2025/05/06/world/middleeast/ceasefire-talks.html",
+                "byline": "By Jane Doe and John Roe",
+                "published_date": "2025-05-06T09:15:02-04:00"
+            },
+            {
+                "title": "Listed Without Abstract",
+                "abstract": "",
+                "url": "https://www.nytimes.com/2025/05/06/us/listed.html"
+            }
+        ]
+    }"#;
+
+    #[tokio::test]
+    async fn test_index_without_api_key_is_empty() {
+        assert!(matches!(index_articles(None).await, Ok(stories) if stories.is_empty()));
+        assert!(matches!(index_articles(Some("  ")).await, Ok(stories) if stories.is_empty()));
+    }
+
+    #[test]
+    fn test_fallback_article_uses_title_byline_and_abstract() {
+        let response: NYTimesResponse = serde_json::from_str(FIXTURE_RESPONSE).unwrap();
+        let article = fallback_article(&response.results[0]).unwrap();
+
+        assert_eq!(article.source, "https://www.nytimes.com/2025/05/06/world/middleeast/ceasefire-talks.html");
+        assert_eq!(
+            &*article.content,
+            format!(
+                "{}\n\n# Ceasefire Talks Resume in Cairo\n\nBy Jane Doe and John Roe\n\n\
+                 Negotiators returned on Tuesday after a weeklong pause.",
+                ABSTRACT_ONLY_NOTE
+            )
+        );
+        assert_eq!(article.published_at.unwrap().to_rfc3339(), "2025-05-06T09:15:02-04:00");
+        assert_eq!(article.authors, vec!["Jane Doe", "John Roe"]);
+    }
+
+    #[test]
+    fn test_fallback_article_needs_an_abstract() {
+        let response: NYTimesResponse = serde_json::from_str(FIXTURE_RESPONSE).unwrap();
+        let listed = &response.results[1];
+        assert_eq!((listed.byline.as_str(), listed.published_date.as_deref()), ("", None));
+        assert!(fallback_article(listed).is_none());
+    }
+}
@@ -0,0 +1,295 @@
+//! Detection of paywalled or subscription-gated article pages.
+//!
+//! A gated page still parses: the scrapers find a teaser paragraph and a
+//! "Subscribe to continue reading" box, and without a check that teaser would
+//! be summarized as if it were the article. Every scraper passes its parsed
+//! page and cleaned text through [`check`]; a gated page fails with
+//! [`Paywalled`], which `fetch_articles` records as
+//! [`SkipReason::Paywalled`] (see [`skip_reason`]). NYT falls back to the API
+//! abstract instead, like any other failed fetch.
+//!
+//! # Heuristics
+//!
+//! The article body is the cleaned text without the headline and the
+//! `Title:`/`Published:` lines the scrapers prepend. A page is gated when:
+//!
+//! | Signal | Condition |
+//! |--------|-----------|
+//! | Gate phrase | the page shows a gate phrase and the body is under [`TEASER_MAX_CHARS`] |
+//! | `noarchive` | `<meta name="robots">` says `noarchive` and the body is under [`TINY_BODY_CHARS`] |
+//! | Title ratio | the body is shorter than [`MIN_BODY_TO_TITLE_RATIO`] times the title |
+//!
+//! The body limits keep full articles that merely show a "Subscribe" footer,
+//! and short briefs with a proper paragraph or two, from being flagged. An
+//! empty body is left to the scrapers' own empty-content handling.
+//!
+//! Gate phrases are matched case-insensitively against the visible page
+//! text. [`DEFAULT_PHRASES`] ship built in; `--paywall-phrases` adds more.
+
+use crate::models::SkipReason;
+use once_cell::sync::OnceCell;
+use scraper::{Html, Selector};
+use std::error::Error;
+use std::fmt;
+use tracing::{debug, info};
+
+/// Gate phrases recognized out of the box.
+pub const DEFAULT_PHRASES: &[&str] = &[
+    "subscribe to continue reading",
+    "subscribe to keep reading",
+    "to continue reading, subscribe",
+    "this article is for subscribers",
+    "this content is for subscribers only",
+    "log in to continue reading",
+    "sign in to continue reading",
+    "create a free account to continue reading",
+    "you have reached your limit of free articles",
+    "you've reached your free article limit",
+];
+
+/// Longest body, in characters, that a gate phrase marks as a teaser.
+pub const TEASER_MAX_CHARS: usize = 1500;
+
+/// Longest body, in characters, that `noarchive` marks as a teaser.
+pub const TINY_BODY_CHARS: usize = 250;
+
+/// A body shorter than this many times the title is a teaser.
+pub const MIN_BODY_TO_TITLE_RATIO: usize = 3;
+
+/// Shortest article text, in bytes, scrapers of metered outlets (NYT, the
+/// Washington Post) accept as an article rather than a stub.
+pub const MIN_ARTICLE_BYTES: usize = 200;
+
+/// The page is behind a paywall or subscription gate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Paywalled;
+
+impl fmt::Display for Paywalled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("article is paywalled")
+    }
+}
+
+impl Error for Paywalled {}
+
+static PHRASES: OnceCell<Vec<String>> = OnceCell::new();
+
+fn with_defaults(extra: &[String]) -> Vec<String> {
+    DEFAULT_PHRASES
+        .iter()
+        .map(|p| p.to_string())
+        .chain(extra.iter().map(|p| p.trim().to_lowercase()).filter(|p| !p.is_empty()))
+        .collect()
+}
+
+/// Add gate phrases on top of [`DEFAULT_PHRASES`].
+///
+/// Must be called before the first [`check`] to take effect.
+pub fn init(extra: &[String]) {
+    if !extra.is_empty() {
+        info!(phrases = extra.len(), "Added paywall phrases");
+    }
+    let _ = PHRASES.set(with_defaults(extra));
+}
+
+/// Fail with [`Paywalled`] if `document` is a gated page whose extracted
+/// article text is `text`.
+pub fn check(document: &Html, text: &str) -> Result<(), Paywalled> {
+    let phrases = PHRASES.get_or_init(|| with_defaults(&[]));
+    match detect(phrases, document, text) {
+        Some(signal) => {
+            debug!(signal, "Page looks paywalled");
+            Err(Paywalled)
+        }
+        None => Ok(()),
+    }
+}
+
+/// The skip reason for a failed fetch: [`SkipReason::Paywalled`] for a
+/// [`Paywalled`] error, [`SkipReason::FetchError`] otherwise.
+pub fn skip_reason(e: &(dyn Error + 'static)) -> SkipReason {
+    if e.is::<Paywalled>() {
+        SkipReason::Paywalled
+    } else {
+        SkipReason::FetchError
+    }
+}
+
+/// The first signal that marks the page as gated.
+fn detect(phrases: &[String], document: &Html, text: &str) -> Option<&'static str> {
+    let title = page_title(document);
+    let body = body_chars(text, title.as_deref());
+    if body == 0 {
+        return None;
+    }
+
+    if body < TEASER_MAX_CHARS {
+        let visible = visible_text(document).to_lowercase();
+        let text = text.to_lowercase();
+        if phrases.iter().any(|p| visible.contains(p.as_str()) || text.contains(p.as_str())) {
+            return Some("phrase");
+        }
+    }
+    if body < TINY_BODY_CHARS && is_noarchive(document) {
+        return Some("noarchive");
+    }
+    if let Some(title) = &title
+        && body < title.chars().count() * MIN_BODY_TO_TITLE_RATIO
+    {
+        return Some("title_ratio");
+    }
+    None
+}
+
+fn meta_content(document: &Html, selector: &str) -> Option<String> {
+    let selector = Selector::parse(selector).ok()?;
+    document
+        .select(&selector)
+        .filter_map(|el| el.value().attr("content"))
+        .map(str::trim)
+        .find(|c| !c.is_empty())
+        .map(str::to_string)
+}
+
+/// The headline: `og:title`, else `<title>`, else the first `<h1>`.
+fn page_title(document: &Html) -> Option<String> {
+    meta_content(document, r#"meta[property="og:title"]"#).or_else(|| {
+        ["title", "h1"].iter().find_map(|sel| {
+            let selector = Selector::parse(sel).ok()?;
+            let title = document.select(&selector).next()?.text().collect::<String>();
+            let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+            (!title.is_empty()).then_some(title)
+        })
+    })
+}
+
+fn is_noarchive(document: &Html) -> bool {
+    let Ok(selector) = Selector::parse("meta[name]") else {
+        return false;
+    };
+    document.select(&selector).any(|el| {
+        el.value().attr("name").is_some_and(|n| n.eq_ignore_ascii_case("robots"))
+            && el
+                .value()
+                .attr("content")
+                .is_some_and(|c| c.split(',').any(|d| d.trim().eq_ignore_ascii_case("noarchive")))
+    })
+}
+
+/// Text outside `<script>`, `<style>` and the like.
+fn visible_text(document: &Html) -> String {
+    let mut text = String::new();
+    for node in document.root_element().descendants() {
+        let Some(t) = node.value().as_text() else {
+            continue;
+        };
+        let hidden = node.ancestors().any(|a| {
+            a.value()
+                .as_element()
+                .is_some_and(|e| matches!(e.name(), "script" | "style" | "noscript" | "template"))
+        });
+        if !hidden {
+            text.push_str(t);
+            text.push(' ');
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Characters of article body in `text`, leaving out the headline and the
+/// header lines the scrapers prepend.
+fn body_chars(text: &str, title: Option<&str>) -> usize {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| {
+            !line.is_empty()
+                && !line.starts_with("Title:")
+                && !line.starts_with("Published")
+                && !line.starts_with("# ")
+                && !title.is_some_and(|t| t.contains(*line))
+        })
+        .map(|line| line.chars().count())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GATED_FIXTURE: &str = r#"<html><head>
+<title>Inside the Fed's Quiet Debate Over Rate Cuts - The New York Times</title>
+<meta name="robots" content="noarchive, max-image-preview:large">
+<script>window.__config = {"gate": "Subscribe to continue reading"};</script>
+</head><body>
+<h1 data-testid="headline">Inside the Fed's Quiet Debate Over Rate Cuts</h1>
+<p>Officials are split over how quickly to lower borrowing costs as inflation cools and hiring slows across the country.</p>
+<div class="gateway"><h2>Subscribe to continue reading.</h2><p>Already a subscriber? Log in.</p></div>
+</body></html>"#;
+
+    const BRIEF_FIXTURE: &str = r#"<html><head>
+<title>Storm closes schools across the county | AP News</title>
+<meta name="robots" content="noarchive">
+<script>window.__config = {"gate": "Subscribe to continue reading"};</script>
+</head><body>
+<h1>Storm closes schools across the county</h1>
+<div class="RichTextStoryBody">
+<p>All public schools in the county will stay closed on Thursday after an overnight storm knocked down trees and power lines, district officials said.</p>
+<p>About 20,000 homes were still without electricity by morning. Utility crews expect to restore most service by Friday evening.</p>
+<p>Officials asked residents to stay off the roads while debris is cleared.</p>
+</div>
+<footer><a href="/subscribe">Subscribe</a></footer>
+</body></html>"#;
+
+    fn phrases() -> Vec<String> {
+        with_defaults(&[])
+    }
+
+    #[test]
+    fn test_gated_page_is_paywalled() {
+        let document = Html::parse_document(GATED_FIXTURE);
+        let text = "Published: 2025-05-06T08:00:00+00:00\n\n\
+Officials are split over how quickly to lower borrowing costs as inflation cools and hiring slows across the country.";
+        assert_eq!(detect(&phrases(), &document, text), Some("phrase"));
+
+        // Without the gate box, the teaser still trips the noarchive check
+        let without_gate = GATED_FIXTURE.replace("Subscribe to continue reading.", "");
+        assert_eq!(detect(&phrases(), &Html::parse_document(&without_gate), text), Some("noarchive"));
+
+        // A headline-sized teaser trips the title ratio
+        let document = Html::parse_document("<html><head><title>Inside the Fed's Quiet Debate Over Rate Cuts</title></head></html>");
+        assert_eq!(detect(&phrases(), &document, "Officials are split."), Some("title_ratio"));
+    }
+
+    #[test]
+    fn test_short_brief_is_not_paywalled() {
+        let document = Html::parse_document(BRIEF_FIXTURE);
+        let text = "Published: 2025-05-06T08:00:00+00:00\n\n\
+Storm closes schools across the county\n\n\
+All public schools in the county will stay closed on Thursday after an overnight storm knocked down trees and power lines, district officials said.\n\n\
+About 20,000 homes were still without electricity by morning. Utility crews expect to restore most service by Friday evening.\n\n\
+Officials asked residents to stay off the roads while debris is cleared.";
+        assert_eq!(detect(&phrases(), &document, text), None);
+        assert!(body_chars(text, page_title(&document).as_deref()) < TEASER_MAX_CHARS);
+        // Nor is an empty page: that's left to the scrapers
+        assert_eq!(detect(&phrases(), &Html::parse_document(GATED_FIXTURE), ""), None);
+    }
+
+    #[test]
+    fn test_configured_phrases_extend_defaults() {
+        let page = r#"<html><body><p>A short teaser paragraph about the city budget vote and what it means for residents.</p>
+<p>Members Only: join today for full access.</p></body></html>"#;
+        let document = Html::parse_document(page);
+        let text = "A short teaser paragraph about the city budget vote and what it means for residents.";
+        assert_eq!(detect(&phrases(), &document, text), None);
+        let configured = with_defaults(&[" MEMBERS ONLY: ".to_string()]);
+        assert_eq!(detect(&configured, &document, text), Some("phrase"));
+    }
+
+    #[test]
+    fn test_paywalled_errors_map_to_their_skip_reason() {
+        let paywalled: Box<dyn Error> = Paywalled.into();
+        assert_eq!(skip_reason(paywalled.as_ref()), SkipReason::Paywalled);
+        let other: Box<dyn Error> = "connection reset".into();
+        assert_eq!(skip_reason(other.as_ref()), SkipReason::FetchError);
+    }
+}
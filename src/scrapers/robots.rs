@@ -0,0 +1,318 @@
+//! Optional `robots.txt` compliance for indexed article URLs.
+//!
+//! With `--respect-robots`, every URL produced by the indexing phase is
+//! checked against its site's `robots.txt` before fetching, and disallowed
+//! URLs are dropped with a log line and recorded as [`SkipReason::Robots`]. Each site's `robots.txt` is fetched once
+//! per run and cached in memory.
+//!
+//! # Matching
+//!
+//! Rules come from the groups naming [`ROBOTS_AGENT`], falling back to the
+//! `*` group. The longest matching `Allow`/`Disallow` pattern wins (ties go
+//! to `Allow`), `*` matches any run of characters and a trailing `$` anchors
+//! the pattern to the end of the path.
+//!
+//! A missing `robots.txt` (4xx) allows everything. A site whose
+//! `robots.txt` cannot be fetched (network error, 5xx) is also allowed, with
+//! a warning, so a flaky server doesn't silently empty a source.
+
+use crate::models::SkipReason;
+use crate::scrapers::http::CLIENT;
+use crate::skips::SkipLog;
+use once_cell::sync::Lazy;
+use reqwest::Client;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::{debug, info, instrument, warn};
+
+/// Product token matched against `User-agent` lines.
+pub const ROBOTS_AGENT: &str = "awful_text_news";
+
+/// A single `Allow` or `Disallow` rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+/// The rules of one site's `robots.txt` that apply to us.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Robots {
+    rules: Vec<Rule>,
+}
+
+impl Robots {
+    /// Parse a `robots.txt` body, keeping the rules that apply to `agent`.
+    pub fn parse(body: &str, agent: &str) -> Self {
+        let agent = agent.to_lowercase();
+        // (user agents, rules) per group
+        let mut groups: Vec<(Vec<String>, Vec<Rule>)> = Vec::new();
+        let mut in_agent_lines = false;
+
+        for line in body.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim().to_lowercase().as_str() {
+                "user-agent" => {
+                    if !in_agent_lines {
+                        groups.push((Vec::new(), Vec::new()));
+                        in_agent_lines = true;
+                    }
+                    if let Some((agents, _)) = groups.last_mut() {
+                        agents.push(value.to_lowercase());
+                    }
+                }
+                key @ ("allow" | "disallow") => {
+                    in_agent_lines = false;
+                    // An empty Disallow allows everything
+                    if value.is_empty() {
+                        continue;
+                    }
+                    if let Some((_, rules)) = groups.last_mut() {
+                        rules.push(Rule {
+                            allow: key == "allow",
+                            pattern: value.to_string(),
+                        });
+                    }
+                }
+                _ => in_agent_lines = false,
+            }
+        }
+
+        let named: Vec<Rule> = groups
+            .iter()
+            .filter(|(agents, _)| agents.iter().any(|a| a != "*" && agent.contains(a.as_str())))
+            .flat_map(|(_, rules)| rules.iter().cloned())
+            .collect();
+        let rules = if named.is_empty() {
+            groups
+                .into_iter()
+                .filter(|(agents, _)| agents.iter().any(|a| a == "*"))
+                .flat_map(|(_, rules)| rules)
+                .collect()
+        } else {
+            named
+        };
+
+        Robots { rules }
+    }
+
+    /// Whether `path` (path and query of a URL) may be fetched.
+    pub fn allows(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|rule| pattern_matches(&rule.pattern, path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+}
+
+/// Match a robots pattern (with `*` and trailing `$`) against a path prefix.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        let last = i == parts.len() - 1;
+        let found = if last && anchored {
+            rest.ends_with(part).then(|| rest.len() - part.len())
+        } else {
+            rest.find(part)
+        };
+        match found {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty() || pattern.ends_with('*')
+}
+
+/// Path and query of a URL, as matched by robots rules.
+fn robots_path(url: &url::Url) -> String {
+    match url.query() {
+        Some(q) => format!("{}?{}", url.path(), q),
+        None => url.path().to_string(),
+    }
+}
+
+type RobotsCache = Mutex<HashMap<String, Arc<Robots>>>;
+
+static ROBOTS_CACHE: Lazy<RobotsCache> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Fetch (or reuse) the rules for a site origin such as `https://lite.cnn.com`.
+async fn robots_for(cache: &RobotsCache, client: &Client, origin: &str) -> Arc<Robots> {
+    if let Some(robots) = cache.lock().unwrap().get(origin) {
+        return Arc::clone(robots);
+    }
+
+    let robots_url = format!("{}/robots.txt", origin);
+    let robots = match client.get(&robots_url).send().await {
+        Ok(res) if res.status().is_success() => match res.text().await {
+            Ok(body) => Robots::parse(&body, ROBOTS_AGENT),
+            Err(e) => {
+                warn!(%robots_url, error = %e, "Failed to read robots.txt; allowing all");
+                Robots::default()
+            }
+        },
+        Ok(res) if res.status().is_client_error() => {
+            debug!(%robots_url, status = %res.status(), "No robots.txt; allowing all");
+            Robots::default()
+        }
+        Ok(res) => {
+            warn!(%robots_url, status = %res.status(), "robots.txt unavailable; allowing all");
+            Robots::default()
+        }
+        Err(e) => {
+            warn!(%robots_url, error = %e, "Failed to fetch robots.txt; allowing all");
+            Robots::default()
+        }
+    };
+
+    let robots = Arc::new(robots);
+    cache
+        .lock()
+        .unwrap()
+        .insert(origin.to_string(), Arc::clone(&robots));
+    robots
+}
+
+/// Drop indexed items whose URL is disallowed by its site's `robots.txt`.
+///
+/// `url_of` returns the URL of an item; items with unparseable URLs are
+/// kept. Skipped URLs are logged and recorded in `skips`.
+///
+/// # Arguments
+///
+/// * `source` - Outlet name used in log lines
+/// * `items` - The indexed items (URLs, or URL/title pairs)
+/// * `url_of` - Extracts the URL from an item
+/// * `skips` - The run's skip log
+#[instrument(level = "info", skip(items, url_of, skips))]
+pub async fn filter_allowed<T>(source: &str, items: Vec<T>, url_of: fn(&T) -> &str, skips: &SkipLog) -> Vec<T> {
+    filter_allowed_with(&ROBOTS_CACHE, &CLIENT, source, items, url_of, skips).await
+}
+
+async fn filter_allowed_with<T>(
+    cache: &RobotsCache,
+    client: &Client,
+    source: &str,
+    items: Vec<T>,
+    url_of: fn(&T) -> &str,
+    skips: &SkipLog,
+) -> Vec<T> {
+    let before = items.len();
+    let mut kept = Vec::with_capacity(before);
+
+    for item in items {
+        let Ok(url) = url::Url::parse(url_of(&item)) else {
+            kept.push(item);
+            continue;
+        };
+        let origin = url.origin().ascii_serialization();
+        let robots = robots_for(cache, client, &origin).await;
+        if robots.allows(&robots_path(&url)) {
+            kept.push(item);
+        } else {
+            info!(source, url = %url, "Skipping URL disallowed by robots.txt");
+            skips.record(url_of(&item), SkipReason::Robots);
+        }
+    }
+
+    if kept.len() < before {
+        info!(source, skipped = before - kept.len(), kept = kept.len(), "Applied robots.txt");
+    }
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const SAMPLE: &str = "\
+# Sample robots.txt
+User-agent: SomeOtherBot
+Disallow: /
+
+User-agent: *
+Disallow: /live/
+Disallow: /*.pdf$
+Allow: /live/transcripts/
+Disallow:
+";
+
+    #[test]
+    fn test_parse_star_group() {
+        let robots = Robots::parse(SAMPLE, ROBOTS_AGENT);
+        assert!(robots.allows("/2025/05/06/politics/story"));
+        assert!(!robots.allows("/live/updates"));
+        assert!(robots.allows("/live/transcripts/1"));
+        assert!(!robots.allows("/report.pdf"));
+        assert!(robots.allows("/report.pdf?download=1"));
+    }
+
+    #[test]
+    fn test_named_group_overrides_star() {
+        let body = "User-agent: *\nDisallow: /\n\nUser-agent: awful_text_news\nDisallow: /private\n";
+        let robots = Robots::parse(body, ROBOTS_AGENT);
+        assert!(robots.allows("/news"));
+        assert!(!robots.allows("/private/a"));
+    }
+
+    #[test]
+    fn test_empty_robots_allows_everything() {
+        assert!(Robots::parse("", ROBOTS_AGENT).allows("/anything"));
+    }
+
+    #[tokio::test]
+    async fn test_filter_skips_disallowed_urls_and_caches_robots() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/robots.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(SAMPLE))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let urls = vec![
+            format!("{}/2025/05/06/story", server.uri()),
+            format!("{}/live/updates", server.uri()),
+            format!("{}/2025/05/06/other", server.uri()),
+        ];
+        let cache = RobotsCache::default();
+        let skips = SkipLog::default();
+        let kept = filter_allowed_with(&cache, &Client::new(), "test", urls.clone(), String::as_str, &skips).await;
+
+        assert_eq!(kept, vec![urls[0].clone(), urls[2].clone()]);
+        let skipped = skips.entries();
+        assert_eq!(skipped.len(), 1);
+        assert_eq!((&skipped[0].source, skipped[0].reason), (&urls[1], SkipReason::Robots));
+    }
+
+    #[tokio::test]
+    async fn test_missing_robots_allows_all() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let items = vec![(format!("{}/live/updates", server.uri()), "Title".to_string())];
+        let cache = RobotsCache::default();
+        let skips = SkipLog::default();
+        let kept = filter_allowed_with(&cache, &Client::new(), "test", items, |(u, _)| u.as_str(), &skips).await;
+        assert_eq!(kept.len(), 1);
+        assert_eq!(skips.total(), 0);
+    }
+}
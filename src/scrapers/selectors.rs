@@ -0,0 +1,363 @@
+//! Per-source CSS selectors, overridable without a release.
+//!
+//! Each scraper reads its index, headline and article-body selectors from a
+//! [`SelectorConfig`] instead of inline literals. Defaults are compiled in
+//! ([`default_config`]); with `--selectors-file`, a YAML file overrides any
+//! of them per source tag, so a redesigned page can be followed by editing
+//! the file:
+//!
+//! ```yaml
+//! scrapers:
+//!   cnn:
+//!     article_selector: ".article--lite"
+//!   bbc:
+//!     article_selector:
+//!       - 'main div[data-component="text-block"] p'
+//!       - "article p"
+//! ```
+//!
+//! Every key takes one selector or a list of alternatives:
+//!
+//! | Key | Selects | Alternatives |
+//! |-----|---------|--------------|
+//! | `index_selector` | article links on the index page | all are harvested, in order |
+//! | `headline_selector` | the headline of an article page | the first that matches is used |
+//! | `article_selector` | the body of an article page | the first that matches is used |
+//!
+//! The file is checked at startup: an unknown source or key, a key the
+//! scraper doesn't use, or a selector that doesn't parse stops the run with
+//! the offending `scrapers.<source>.<key>` in the error.
+
+use crate::utils::content_hash;
+use once_cell::sync::OnceCell;
+use scraper::{ElementRef, Html, Selector};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use tracing::info;
+
+/// Selectors of one source, as plain CSS.
+///
+/// An empty list means the scraper doesn't use that selector (NYT indexes
+/// through its API, for instance).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelectorConfig {
+    pub index_selector: Vec<String>,
+    pub headline_selector: Vec<String>,
+    pub article_selector: Vec<String>,
+}
+
+/// One selector or a list of alternatives.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl From<OneOrMany> for Vec<String> {
+    fn from(value: OneOrMany) -> Self {
+        match value {
+            OneOrMany::One(selector) => vec![selector],
+            OneOrMany::Many(selectors) => selectors,
+        }
+    }
+}
+
+/// Overrides for one source, as written in the selectors file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SelectorOverrides {
+    index_selector: Option<OneOrMany>,
+    headline_selector: Option<OneOrMany>,
+    article_selector: Option<OneOrMany>,
+}
+
+/// The selectors file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SelectorFile {
+    #[serde(default)]
+    scrapers: BTreeMap<String, SelectorOverrides>,
+}
+
+/// Compiled selectors of one source.
+#[derive(Debug, Clone)]
+pub struct Selectors {
+    pub index: Vec<Selector>,
+    pub headline: Vec<Selector>,
+    pub article: Vec<Selector>,
+    /// Short hash of the selector strings, recorded with every article for
+    /// provenance; changes whenever an override changes a selector.
+    pub fingerprint: String,
+}
+
+/// Built-in selectors, keyed by source tag.
+pub fn default_config() -> BTreeMap<&'static str, SelectorConfig> {
+    let config = |index: &[&str], headline: &[&str], article: &[&str]| SelectorConfig {
+        index_selector: index.iter().map(|s| s.to_string()).collect(),
+        headline_selector: headline.iter().map(|s| s.to_string()).collect(),
+        article_selector: article.iter().map(|s| s.to_string()).collect(),
+    };
+    BTreeMap::from([
+        ("cnn", config(&[".card--lite a[href]"], &[".headline--lite"], &[".article--lite"])),
+        ("npr", config(&[".topic-title"], &[".story-head"], &[".paragraphs-container"])),
+        (
+            "apnews",
+            config(
+                // Links on the Google News results page
+                &["a[href]"],
+                &[],
+                &[
+                    ".RichTextStoryBody",
+                    ".RichTextBody",
+                    r#"div[data-t="article-body"]"#,
+                    r#"article[role="main"]"#,
+                    "article",
+                ],
+            ),
+        ),
+        (
+            "aljazeera",
+            config(
+                &["a.u-clickable-card__link.article-card__link[href]"],
+                &["h1"],
+                &["div.wysiwyg p", "div.article-p-wrapper p", "article p", "main p"],
+            ),
+        ),
+        (
+            "bbc",
+            config(
+                &[r#"a[data-testid="internal-link"][href]"#],
+                &[r#"h1[data-testid="headline"]"#, "h1"],
+                &[
+                    r#"main div[data-component="text-block"] p"#,
+                    r#"article div[data-component="text-block"] p"#,
+                    "article p",
+                    "main p",
+                ],
+            ),
+        ),
+        (
+            "nytimes",
+            config(
+                &[],
+                &[r#"h1[data-testid="headline"]"#, "h1.css-88wicj", "h1"],
+                &[
+                    r#"section[name="articleBody"] p"#,
+                    ".StoryBodyCompanionColumn p",
+                    "p.css-ac37hb, p.evys1bk0",
+                ],
+            ),
+        ),
+        (
+            "washingtonpost",
+            config(
+                // Indexed from RSS feeds
+                &[],
+                &[r#"h1[data-qa="headline"]"#, "h1"],
+                &[
+                    r#"div.article-body p[data-el="text"]"#,
+                    r#"[data-qa="article-body"] p"#,
+                    "article p",
+                ],
+            ),
+        ),
+    ])
+}
+
+fn compile_list(source: &str, key: &str, selectors: &[String]) -> Result<Vec<Selector>, String> {
+    selectors
+        .iter()
+        .map(|css| {
+            Selector::parse(css).map_err(|e| format!("scrapers.{}.{}: invalid selector {:?}: {}", source, key, css, e))
+        })
+        .collect()
+}
+
+fn fingerprint(config: &SelectorConfig) -> String {
+    let canonical = [
+        ("index_selector", &config.index_selector),
+        ("headline_selector", &config.headline_selector),
+        ("article_selector", &config.article_selector),
+    ]
+    .iter()
+    .map(|(key, selectors)| format!("{}={}", key, selectors.join("\n")))
+    .collect::<Vec<_>>()
+    .join("\n");
+    content_hash(&canonical)[..12].to_string()
+}
+
+fn compile(source: &str, config: &SelectorConfig) -> Result<Selectors, String> {
+    Ok(Selectors {
+        index: compile_list(source, "index_selector", &config.index_selector)?,
+        headline: compile_list(source, "headline_selector", &config.headline_selector)?,
+        article: compile_list(source, "article_selector", &config.article_selector)?,
+        fingerprint: fingerprint(config),
+    })
+}
+
+/// Parse a selectors file, apply it over the defaults and compile every
+/// source's selectors.
+///
+/// # Errors
+///
+/// A message naming the `scrapers.<source>.<key>` at fault for an unknown
+/// source, a key the scraper doesn't use, an empty list or a selector that
+/// doesn't parse; the YAML error for a malformed file.
+pub fn load(raw: &str) -> Result<BTreeMap<String, Selectors>, String> {
+    let file: SelectorFile = serde_yaml::from_str(raw).map_err(|e| e.to_string())?;
+    let mut configs = default_config();
+    for (source, overrides) in file.scrapers {
+        let Some(config) = configs.get_mut(source.as_str()) else {
+            let known: Vec<&str> = default_config().into_keys().collect();
+            return Err(format!("scrapers.{}: unknown source (expected one of {})", source, known.join(", ")));
+        };
+        let keys = [
+            ("index_selector", overrides.index_selector, &mut config.index_selector),
+            ("headline_selector", overrides.headline_selector, &mut config.headline_selector),
+            ("article_selector", overrides.article_selector, &mut config.article_selector),
+        ];
+        for (key, value, current) in keys {
+            let Some(value) = value else {
+                continue;
+            };
+            if current.is_empty() {
+                return Err(format!("scrapers.{}.{}: not used by this scraper", source, key));
+            }
+            let value: Vec<String> = value.into();
+            if value.is_empty() {
+                return Err(format!("scrapers.{}.{}: needs at least one selector", source, key));
+            }
+            *current = value;
+        }
+    }
+    configs
+        .iter()
+        .map(|(source, config)| Ok((source.to_string(), compile(source, config)?)))
+        .collect()
+}
+
+fn defaults() -> BTreeMap<String, Selectors> {
+    default_config()
+        .iter()
+        .map(|(source, config)| (source.to_string(), compile(source, config).expect("default selectors parse")))
+        .collect()
+}
+
+static SELECTORS: OnceCell<BTreeMap<String, Selectors>> = OnceCell::new();
+
+/// Load selector overrides from a YAML file on top of the defaults.
+///
+/// Must be called before the first [`get`] to take effect.
+///
+/// # Errors
+///
+/// The file can't be read or fails [`load`]'s checks.
+pub async fn init(path: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let selectors = match path {
+        Some(path) => {
+            let raw = tokio::fs::read_to_string(path)
+                .await
+                .map_err(|e| format!("selectors file {}: {}", path, e))?;
+            let selectors = load(&raw).map_err(|e| format!("selectors file {}: {}", path, e))?;
+            info!(path, "Loaded scraper selectors");
+            selectors
+        }
+        None => defaults(),
+    };
+    let _ = SELECTORS.set(selectors);
+    Ok(())
+}
+
+/// The selectors of `source` (a source tag such as `"cnn"`).
+///
+/// # Panics
+///
+/// If `source` has no entry in [`default_config`].
+pub fn get(source: &str) -> &'static Selectors {
+    SELECTORS
+        .get_or_init(defaults)
+        .get(source)
+        .unwrap_or_else(|| panic!("no selectors for source {:?}", source))
+}
+
+/// Elements matched by the first of `alternatives` that matches anything.
+pub fn first_match<'a>(alternatives: &[Selector], document: &'a Html) -> Vec<ElementRef<'a>> {
+    alternatives
+        .iter()
+        .map(|selector| document.select(selector).collect::<Vec<_>>())
+        .find(|elements| !elements.is_empty())
+        .unwrap_or_default()
+}
+
+/// Text of the first element matched by the first of `alternatives` that
+/// matches an element with text.
+pub fn first_text(alternatives: &[Selector], document: &Html) -> Option<String> {
+    alternatives.iter().find_map(|selector| {
+        let element = document.select(selector).next()?;
+        let text = element.text().collect::<Vec<_>>().join(" ").trim().to_string();
+        (!text.is_empty()).then_some(text)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_cover_every_scraper_and_parse() {
+        let selectors = defaults();
+        for scraper in crate::scrapers::SCRAPERS {
+            assert!(selectors.contains_key(scraper.tag), "{}", scraper.tag);
+        }
+        assert_eq!(selectors.len(), crate::scrapers::SCRAPERS.len());
+    }
+
+    #[test]
+    fn test_overrides_replace_only_the_given_keys() {
+        let selectors = load(
+            "scrapers:\n  cnn:\n    article_selector: \".article--new\"\n  bbc:\n    headline_selector: [\"h1.title\", \"h1\"]\n",
+        )
+        .unwrap();
+        let document = Html::parse_document(
+            r#"<div class="headline--lite">Old headline</div><div class="article--new">Body</div><h1>Plain</h1>"#,
+        );
+        let cnn = &selectors["cnn"];
+        assert_eq!(first_text(&cnn.headline, &document).as_deref(), Some("Old headline"));
+        assert_eq!(first_text(&cnn.article, &document).as_deref(), Some("Body"));
+        assert_eq!(selectors["bbc"].headline.len(), 2);
+        assert_eq!(first_text(&selectors["bbc"].headline, &document).as_deref(), Some("Plain"));
+        assert_eq!(selectors["bbc"].article.len(), 4);
+
+        // Only the overridden sources get a new fingerprint
+        let defaults = defaults();
+        assert_ne!(selectors["cnn"].fingerprint, defaults["cnn"].fingerprint);
+        assert_eq!(selectors["npr"].fingerprint, defaults["npr"].fingerprint);
+        assert_eq!(selectors["npr"].fingerprint.len(), 12);
+    }
+
+    #[test]
+    fn test_invalid_files_name_the_source_and_key() {
+        let error = |raw: &str| load(raw).err().unwrap();
+        assert!(error("scrapers:\n  cnn:\n    article_selector: \".a[\"\n").starts_with("scrapers.cnn.article_selector: invalid selector"));
+        assert!(error("scrapers:\n  foxnews:\n    article_selector: p\n").starts_with("scrapers.foxnews: unknown source"));
+        assert_eq!(
+            error("scrapers:\n  nytimes:\n    index_selector: a\n"),
+            "scrapers.nytimes.index_selector: not used by this scraper"
+        );
+        assert_eq!(
+            error("scrapers:\n  npr:\n    article_selector: []\n"),
+            "scrapers.npr.article_selector: needs at least one selector"
+        );
+        assert!(error("scrapers:\n  npr:\n    body: p\n").contains("unknown field `body`"));
+    }
+
+    #[test]
+    fn test_first_match_falls_through_to_later_alternatives() {
+        let alternatives = compile_list("test", "article_selector", &[".missing".to_string(), "p".to_string()]).unwrap();
+        let document = Html::parse_document("<p>One</p><p>Two</p>");
+        assert_eq!(first_match(&alternatives, &document).len(), 2);
+        assert!(first_match(&alternatives[..1], &document).is_empty());
+    }
+}
@@ -0,0 +1,272 @@
+//! Washington Post article scraper.
+//!
+//! The Post has no text-only site like CNN Lite or NPR Text, so articles are
+//! indexed from its public RSS feeds and each page is requested in its
+//! lightweight AMP rendering (`?outputType=amp`). The article is stored
+//! under the page's canonical URL.
+//!
+//! # URL Pattern
+//!
+//! Article URLs look like
+//! `https://www.washingtonpost.com/<section>/<yyyy>/<mm>/<dd>/<slug>/`;
+//! feed entries pointing at videos, podcasts or other sites are ignored.
+//!
+//! # Paywall
+//!
+//! Most Post articles are metered. A gated page either shows a subscribe
+//! box (caught by [`paywall::check`]) or serves only the first paragraph or
+//! two; an article under [`paywall::MIN_ARTICLE_BYTES`] of text is such a
+//! stub and is dropped as paywalled. No subscription is needed for the
+//! feeds or the pages the meter lets through.
+
+use crate::models::{NewsArticle, SkipReason};
+use crate::scrapers::budget::ArticleSink;
+use crate::scrapers::http::CLIENT;
+use crate::scrapers::selectors::{self, Selectors};
+use crate::scrapers::{boilerplate, http, meta, paywall};
+use crate::skips::SkipLog;
+use chrono::Utc;
+use futures::stream::{self, StreamExt};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use scraper::Html;
+use std::error::Error;
+use tracing::{debug, error, info, instrument, warn};
+
+/// The feeds articles are indexed from.
+const FEED_URLS: &[&str] = &[
+    "https://feeds.washingtonpost.com/rss/national",
+    "https://feeds.washingtonpost.com/rss/world",
+    "https://feeds.washingtonpost.com/rss/politics",
+    "https://feeds.washingtonpost.com/rss/business",
+];
+
+/// Articles kept per feed.
+const PER_FEED: usize = 10;
+
+/// The visible byline.
+const BYLINE_SELECTOR: &str = r#"[data-qa="author-name"], .author-name"#;
+
+/// `<link>` of a feed item.
+static ITEM_LINK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)<item\b.*?<link>\s*(?:<!\[CDATA\[)?\s*(.*?)\s*(?:\]\]>)?\s*</link>").unwrap());
+
+/// Index Washington Post articles from the RSS feeds.
+///
+/// Each feed is requested conditionally, so an unchanged feed reuses the
+/// previously indexed URLs. A feed that fails is logged and skipped.
+///
+/// # Returns
+///
+/// Up to [`PER_FEED`] article URLs per feed, de-duplicated across feeds.
+#[instrument(level = "info")]
+pub async fn index_articles() -> Result<Vec<String>, Box<dyn Error>> {
+    let mut all = Vec::<String>::new();
+
+    for feed in FEED_URLS {
+        let urls = match http::cached_index(&CLIENT, "washingtonpost", feed, |xml, _| feed_links(xml)).await {
+            Ok(urls) => urls,
+            Err(e) => {
+                warn!(feed = *feed, error = %e, "Washington Post feed failed; skipping it");
+                continue;
+            }
+        };
+        info!(feed = *feed, count = urls.len(), "Indexed Washington Post feed URLs");
+        for url in urls {
+            if !all.contains(&url) {
+                all.push(url);
+            }
+        }
+    }
+
+    info!(total = all.len(), "Total indexed Washington Post URLs");
+    debug!(urls = ?all, "Washington Post URLs");
+    Ok(all)
+}
+
+/// Article links of an RSS feed, in feed order, at most [`PER_FEED`].
+fn feed_links(xml: &str) -> Vec<String> {
+    let mut urls = Vec::new();
+    for cap in ITEM_LINK.captures_iter(xml) {
+        let url = cap[1].replace("&amp;", "&");
+        let url = url.split('?').next().unwrap_or_default().to_string();
+        if is_article_url(&url) && !urls.contains(&url) {
+            urls.push(url);
+        }
+        if urls.len() >= PER_FEED {
+            break;
+        }
+    }
+    urls
+}
+
+/// Whether `url` is a dated Post article page.
+fn is_article_url(url: &str) -> bool {
+    let Some(path) = url.strip_prefix("https://www.washingtonpost.com/") else {
+        return false;
+    };
+    let dated = path.split('/').any(|part| part.len() == 4 && part.starts_with("20") && part.parse::<u16>().is_ok());
+    dated && !path.starts_with("video/") && !path.starts_with("podcasts/")
+}
+
+/// The lightweight AMP rendering of an article page.
+fn amp_url(url: &str) -> String {
+    format!("{}?outputType=amp", url.trim_end_matches('?'))
+}
+
+/// Fetch all Washington Post articles concurrently.
+///
+/// Stubs and gated pages are recorded in `skips` as paywalled.
+#[instrument(level = "info", skip_all)]
+pub async fn fetch_articles(urls: Vec<String>, sink: &ArticleSink, skips: &SkipLog) {
+    let concurrency = 4usize;
+
+    stream::iter(urls.into_iter())
+        .map(|url| async move {
+            let res = fetch_article(&url).await;
+            (url, res)
+        })
+        .buffer_unordered(concurrency)
+        .filter_map(|(url, res)| async move {
+            match res {
+                Ok(Some(article)) => {
+                    debug!(%url, "Fetched Washington Post article");
+                    Some(article)
+                }
+                Ok(None) => {
+                    warn!(%url, "Washington Post fetch produced no content");
+                    skips.record(&url, SkipReason::EmptyContent);
+                    None
+                }
+                Err(e) => {
+                    error!(error = %e, %url, "Washington Post fetch failed");
+                    skips.record_fetch_error(&url, e.as_ref());
+                    None
+                }
+            }
+        })
+        .for_each(|article| {
+            sink.lock().unwrap().push(article);
+            std::future::ready(())
+        })
+        .await;
+
+    info!(count = sink.lock().unwrap().len(), "Fetched Washington Post article contents");
+}
+
+/// Headline and body paragraphs of an article page.
+fn article_text(document: &Html, selectors: &Selectors) -> String {
+    let title = selectors::first_text(&selectors.headline, document).unwrap_or_default();
+    let body: Vec<String> = selectors::first_match(&selectors.article, document)
+        .into_iter()
+        .map(|p| meta::clean(&p.text().collect::<String>()))
+        .filter(|p| !p.is_empty())
+        .collect();
+    if body.is_empty() {
+        return String::new();
+    }
+    if title.is_empty() {
+        body.join("\n\n")
+    } else {
+        format!("Title: {}\n\n{}", title, body.join("\n\n"))
+    }
+}
+
+/// Parse an article page downloaded for `url`.
+///
+/// `Ok(None)` for a page without article text; [`paywall::Paywalled`] for a
+/// gated page or a stub under [`paywall::MIN_ARTICLE_BYTES`].
+fn parse_article(url: &str, html: &str) -> Result<Option<NewsArticle>, Box<dyn Error>> {
+    let document = Html::parse_document(html);
+    let published_at = meta::extract_published(&document);
+
+    let mut content = article_text(&document, selectors::get("washingtonpost"));
+    if content.is_empty() {
+        return Ok(None);
+    }
+    if let Some(dt) = published_at {
+        content = format!("Published: {}\n\n{}", dt.to_rfc3339(), content);
+    }
+    let content = boilerplate::strip("washingtonpost", &content);
+    paywall::check(&document, &content)?;
+    let len = content.len();
+    info!(bytes = len, "Parsed Washington Post article");
+    if len < paywall::MIN_ARTICLE_BYTES {
+        debug!(bytes = len, "Article text is a stub; treating it as paywalled");
+        return Err(paywall::Paywalled.into());
+    }
+
+    Ok(Some(
+        NewsArticle {
+            source: url.to_string(),
+            content: content.into(),
+            published_at,
+            authors: meta::extract_authors(&document, Some(BYLINE_SELECTOR)),
+            fetched_at: Some(Utc::now()),
+            fetched_from: None,
+        }
+        .with_canonical(meta::extract_canonical(&document, url)),
+    ))
+}
+
+/// Fetch a single Washington Post article
+#[instrument(level = "info", skip_all, fields(%url))]
+async fn fetch_article(url: &str) -> Result<Option<NewsArticle>, Box<dyn Error>> {
+    let body = http::fetch_text("washingtonpost", &amp_url(url)).await?;
+    parse_article(url, &body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEED: &str = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel>
+<title>National</title><link>https://www.washingtonpost.com/national/</link>
+<item><title>Storm hits coast</title><link>https://www.washingtonpost.com/weather/2025/05/06/storm-coast/?utm_source=rss</link></item>
+<item><title>Watch</title><link><![CDATA[https://www.washingtonpost.com/video/2025/05/06/clip/]]></link></item>
+<item><title>Budget vote</title><link><![CDATA[ https://www.washingtonpost.com/politics/2025/05/06/budget-vote/ ]]></link></item>
+<item><title>Repeat</title><link>https://www.washingtonpost.com/weather/2025/05/06/storm-coast/</link></item>
+</channel></rss>"#;
+
+    fn page(paragraphs: &[&str]) -> String {
+        let body: String = paragraphs.iter().map(|p| format!(r#"<p data-el="text">{}</p>"#, p)).collect();
+        format!(
+            r#"<html><head><link rel="canonical" href="https://www.washingtonpost.com/politics/2025/05/06/budget-vote/"></head>
+<body><h1 data-qa="headline">Budget vote</h1><div class="article-body">{}</div></body></html>"#,
+            body
+        )
+    }
+
+    #[test]
+    fn test_feed_links_keep_dated_articles() {
+        assert_eq!(
+            feed_links(FEED),
+            vec![
+                "https://www.washingtonpost.com/weather/2025/05/06/storm-coast/",
+                "https://www.washingtonpost.com/politics/2025/05/06/budget-vote/",
+            ]
+        );
+        assert_eq!(
+            amp_url("https://www.washingtonpost.com/politics/2025/05/06/budget-vote/"),
+            "https://www.washingtonpost.com/politics/2025/05/06/budget-vote/?outputType=amp"
+        );
+    }
+
+    #[test]
+    fn test_stubs_are_dropped_as_paywalled() {
+        let url = "https://www.washingtonpost.com/politics/2025/05/06/budget-vote/";
+        let full = page(&[
+            "The House passed the spending bill late on Tuesday after a week of negotiations between party leaders.",
+            "The measure now goes to the Senate, where leaders said they expect a vote before the end of the month.",
+        ]);
+        let article = parse_article(url, &full).unwrap().unwrap();
+        assert!(article.content.starts_with("Title: Budget vote\n\nThe House passed"));
+        assert_eq!(article.source, url);
+
+        let stub = parse_article(url, &page(&["The House passed the spending bill."])).unwrap_err();
+        assert_eq!(paywall::skip_reason(stub.as_ref()), SkipReason::Paywalled);
+
+        assert!(parse_article(url, "<html><body><nav>Menu</nav></body></html>").unwrap().is_none());
+    }
+}
@@ -0,0 +1,144 @@
+//! Run-level record of articles left out of the edition.
+//!
+//! Articles drop out of a run at many points: a fetch fails, the page has
+//! no text, `robots.txt` disallows it, it repeats a story already covered,
+//! the source's time budget runs out, or the model's reply can't be used.
+//! Each of those sites records the article's URL and a [`SkipReason`] in the
+//! run's [`SkipLog`].
+//!
+//! The counts per reason are included in the `fetching.completed`,
+//! `processing.completed` and `application.completed` events, and
+//! `--skip-report path.json` writes the full list:
+//!
+//! Failed fetches carry a `detail` when the cause is known more precisely
+//! (see [`http::error_detail`]):
+//!
+//! ```json
+//! {
+//!   "total": 2,
+//!   "counts": { "fetch_error": 1, "llm_parse_error": 1 },
+//!   "skipped": [
+//!     { "source": "https://apnews.com/article/x", "reason": "fetch_error", "detail": "body_too_large" },
+//!     { "source": "https://text.npr.org/nx-s1-1", "reason": "llm_parse_error" }
+//!   ]
+//! }
+//! ```
+
+use crate::models::{SkipReason, SkippedArticle};
+use crate::scrapers::{http, paywall};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::sync::Mutex;
+use tracing::{debug, error, info};
+
+/// Articles skipped so far in the run, shared by the scrapers and the
+/// pipeline.
+#[derive(Debug, Default)]
+pub struct SkipLog {
+    entries: Mutex<Vec<SkippedArticle>>,
+}
+
+impl SkipLog {
+    /// Record that the article at `source` was skipped for `reason`.
+    pub fn record(&self, source: &str, reason: SkipReason) {
+        self.record_with_detail(source, reason, None);
+    }
+
+    /// Record that fetching the article at `source` failed with `error`,
+    /// as paywalled or as a fetch error with its detail.
+    pub fn record_fetch_error(&self, source: &str, error: &(dyn Error + 'static)) {
+        self.record_with_detail(source, paywall::skip_reason(error), http::error_detail(error));
+    }
+
+    fn record_with_detail(&self, source: &str, reason: SkipReason, detail: Option<&str>) {
+        debug!(source, reason = reason.as_str(), detail, "Skipped article");
+        self.entries.lock().unwrap().push(SkippedArticle {
+            source: source.to_string(),
+            reason,
+            detail: detail.map(str::to_string),
+        });
+    }
+
+    /// Whether `source` has been recorded already.
+    pub fn contains(&self, source: &str) -> bool {
+        self.entries.lock().unwrap().iter().any(|s| s.source == source)
+    }
+
+    /// Every skip recorded so far, in recording order.
+    pub fn entries(&self) -> Vec<SkippedArticle> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    /// Number of skips per reason; reasons that never occurred are left out.
+    pub fn counts(&self) -> BTreeMap<SkipReason, usize> {
+        let mut counts = BTreeMap::new();
+        for skipped in self.entries.lock().unwrap().iter() {
+            *counts.entry(skipped.reason).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Total number of skips recorded.
+    pub fn total(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+}
+
+/// The file written by `--skip-report`.
+#[derive(Debug, Serialize)]
+struct SkipReport {
+    total: usize,
+    counts: BTreeMap<SkipReason, usize>,
+    skipped: Vec<SkippedArticle>,
+}
+
+/// Write every skip of the run to `path` as JSON.
+///
+/// A failed write is logged; the edition doesn't depend on the report.
+pub async fn write_report(log: &SkipLog, path: &str) {
+    let report = SkipReport {
+        total: log.total(),
+        counts: log.counts(),
+        skipped: log.entries(),
+    };
+    let written = match serde_json::to_vec_pretty(&report) {
+        Ok(json) => tokio::fs::write(path, json).await.map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    match written {
+        Ok(()) => info!(path, total = report.total, "Wrote skip report"),
+        Err(e) => error!(path, error = %e, "Failed to write skip report"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_counts_and_report() {
+        let log = SkipLog::default();
+        log.record("https://text.npr.org/nx-s1-1", SkipReason::LlmParseError);
+        log.record("https://apnews.com/article/x", SkipReason::FetchError);
+        log.record_fetch_error("https://apnews.com/article/y", &http::BodyTooLarge { limit: 10 });
+
+        assert_eq!(log.total(), 3);
+        assert!(log.contains("https://apnews.com/article/y"));
+        assert!(!log.contains("https://apnews.com/article/z"));
+        let counts: Vec<(SkipReason, usize)> = log.counts().into_iter().collect();
+        // In taxonomy order, not recording order
+        assert_eq!(counts, vec![(SkipReason::FetchError, 2), (SkipReason::LlmParseError, 1)]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("skips.json");
+        write_report(&log, path.to_str().unwrap()).await;
+        let report: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(report["total"], 3);
+        assert_eq!(report["counts"], serde_json::json!({ "fetch_error": 2, "llm_parse_error": 1 }));
+        assert_eq!(report["skipped"][0]["source"], "https://text.npr.org/nx-s1-1");
+        assert_eq!(report["skipped"][0]["reason"], "llm_parse_error");
+        assert!(report["skipped"][1].get("detail").is_none());
+        assert_eq!(report["skipped"][2]["detail"], "body_too_large");
+    }
+}
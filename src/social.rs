@@ -0,0 +1,186 @@
+//! Posting the edition's headline to Mastodon (`social` feature).
+//!
+//! With `--mastodon-instance` and `--mastodon-token`, the end of a run posts
+//! one status to the instance's `/api/v1/statuses`: the editorial headline
+//! when the edition has one (`--editorial`), otherwise the title and link of
+//! the top story, or of the first article when there are no top stories. An
+//! edition with neither is not posted.
+//!
+//! ```text
+//! Ceasefire talks resume as aid stalls
+//!
+//! The Awful Times, 2025-05-06 evening edition
+//! ```
+//!
+//! Statuses are cut to [`MAX_STATUS_CHARS`], the default limit of Mastodon
+//! instances. Each carries an `Idempotency-Key` derived from its text, so a
+//! rerun of the same edition doesn't post it twice.
+//!
+//! Posting is best effort: a failed or rejected post is logged and never
+//! fails the run. Without the `social` feature the flags are accepted and
+//! ignored with a warning, like `--amqp-url` without `publish`.
+
+/// Longest status posted, in characters.
+#[cfg(feature = "social")]
+pub const MAX_STATUS_CHARS: usize = 500;
+
+/// Post the headline of `front_page` to the Mastodon `instance` with the
+/// access `token`; does nothing unless both are given.
+#[cfg(feature = "social")]
+pub async fn post_digest(instance: Option<&str>, token: Option<&str>, front_page: &crate::models::FrontPage) {
+    use tracing::{info, warn};
+
+    let (Some(instance), Some(token)) = (instance, token) else { return };
+    let Some(status) = digest_status(front_page) else {
+        info!("Nothing to post to Mastodon: the edition has no headline");
+        return;
+    };
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .unwrap_or_default();
+    match post_status(&client, instance, token, &status).await {
+        Ok(url) => info!(instance, url = url.as_deref().unwrap_or_default(), "Posted the edition to Mastodon"),
+        Err(e) => warn!(instance, error = %e, "Failed to post the edition to Mastodon"),
+    }
+}
+
+/// Post the headline of the edition (no-op when the `social` feature is disabled).
+///
+/// Warns if an instance was given, since it has no effect in this build.
+#[cfg(not(feature = "social"))]
+pub async fn post_digest(instance: Option<&str>, _token: Option<&str>, _front_page: &crate::models::FrontPage) {
+    if instance.is_some() {
+        tracing::warn!("--mastodon-instance is ignored: built without the `social` feature");
+    }
+}
+
+/// The status posted for `front_page`, or `None` if it has no headline.
+#[cfg(feature = "social")]
+pub fn digest_status(front_page: &crate::models::FrontPage) -> Option<String> {
+    let (headline, link) = match (&front_page.editorial, front_page.top.first(), front_page.articles.first()) {
+        (Some(editorial), _, _) => (editorial.headline.clone(), None),
+        (None, Some(top), _) => (top.title.clone(), top.source.clone()),
+        (None, None, Some(article)) => (article.title.clone(), article.source.clone()),
+        (None, None, None) => return None,
+    };
+    let footer = format!(
+        "The Awful Times, {} {} edition",
+        front_page.edition_date(),
+        front_page.time_of_day
+    );
+    let link = link.map(|url| format!("\n{}", url)).unwrap_or_default();
+
+    // Shorten the headline, never the link or the footer
+    let room = MAX_STATUS_CHARS.saturating_sub(link.chars().count() + footer.chars().count() + 2);
+    let headline = if headline.chars().count() > room {
+        let cut: String = headline.chars().take(room.saturating_sub(1)).collect();
+        format!("{}…", cut.trim_end())
+    } else {
+        headline
+    };
+    Some(format!("{}{}\n\n{}", headline, link, footer))
+}
+
+/// Post `status` publicly to `instance`; returns the URL of the new status.
+#[cfg(feature = "social")]
+async fn post_status(
+    client: &reqwest::Client,
+    instance: &str,
+    token: &str,
+    status: &str,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let endpoint = format!("{}/api/v1/statuses", instance.trim_end_matches('/'));
+    let response = client
+        .post(&endpoint)
+        .bearer_auth(token)
+        .header("Idempotency-Key", crate::utils::content_hash(status))
+        .json(&serde_json::json!({ "status": status, "visibility": "public" }))
+        .send()
+        .await?
+        .error_for_status()?;
+    let posted: serde_json::Value = response.json().await?;
+    Ok(posted["url"].as_str().map(str::to_string))
+}
+
+#[cfg(all(test, feature = "social"))]
+mod tests {
+    use super::*;
+    use crate::models::{Editorial, FeaturedStory, FrontPage};
+    use crate::test_support::{self, canned_article};
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn front_page() -> FrontPage {
+        let mut article: crate::models::AwfulNewsArticle =
+            serde_json::from_value(canned_article("Talks resume", "World News")).unwrap();
+        article.source = Some("https://lite.cnn.com/talks".to_string());
+        FrontPage {
+            articles: vec![article],
+            ..test_support::front_page("evening", "18:00:00")
+        }
+    }
+
+    #[test]
+    fn test_digest_status_prefers_editorial_then_top_story() {
+        let mut page = front_page();
+        let title = page.articles[0].title.clone();
+        assert_eq!(
+            digest_status(&page).unwrap(),
+            format!("{}\nhttps://lite.cnn.com/talks\n\nThe Awful Times, 2025-05-06 evening edition", title)
+        );
+
+        page.top = vec![FeaturedStory {
+            title: "Probe launched".to_string(),
+            source: Some("https://text.npr.org/probe".to_string()),
+            category: "Science & Technology".to_string(),
+            score: 3,
+        }];
+        assert!(digest_status(&page).unwrap().starts_with("Probe launched\nhttps://text.npr.org/probe\n\n"));
+
+        page.editorial = Some(Editorial {
+            headline: "x".repeat(600),
+            blurb: "A long day.".to_string(),
+        });
+        let status = digest_status(&page).unwrap();
+        assert_eq!(status.chars().count(), MAX_STATUS_CHARS);
+        assert!(status.ends_with("…\n\nThe Awful Times, 2025-05-06 evening edition"));
+
+        page.editorial = None;
+        page.top.clear();
+        page.articles.clear();
+        assert_eq!(digest_status(&page), None);
+    }
+
+    #[tokio::test]
+    async fn test_post_status_sends_the_headline_to_the_instance() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/statuses"))
+            .and(header("authorization", "Bearer secret"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "url": "https://social.example/@news/1" })),
+            )
+            .mount(&server)
+            .await;
+
+        let status = digest_status(&front_page()).unwrap();
+        let url = post_status(&reqwest::Client::new(), &format!("{}/", server.uri()), "secret", &status)
+            .await
+            .unwrap();
+        assert_eq!(url.as_deref(), Some("https://social.example/@news/1"));
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body, serde_json::json!({ "status": status, "visibility": "public" }));
+        assert_eq!(
+            requests[0].headers.get("idempotency-key").unwrap().to_str().unwrap(),
+            crate::utils::content_hash(&status)
+        );
+
+        // An unreachable instance is an error for the caller to log
+        let unreachable = post_status(&reqwest::Client::new(), "http://127.0.0.1:9", "secret", &status).await;
+        assert!(unreachable.is_err());
+    }
+}
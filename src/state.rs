@@ -0,0 +1,254 @@
+//! Persistent history of processed article URLs.
+//!
+//! Outlets keep updating the same URL throughout the day, so a story from
+//! the morning edition is often indexed again in the evening with nearly
+//! identical text. This module remembers, for every URL processed, a hash of
+//! the scraped content together with the date and edition it appeared in.
+//!
+//! Before processing, each fetched article is checked with [`decide`]:
+//!
+//! | Seen today? | Same content hash? | Decision |
+//! |-------------|--------------------|----------|
+//! | No | - | [`UpdateDecision::New`] |
+//! | Yes | Yes | [`UpdateDecision::Unchanged`] (skipped) |
+//! | Yes | No | [`UpdateDecision::Updated`] (processed, marked `updateOf`) |
+//!
+//! With `--only-new-since-last-run` the history is also consulted before
+//! fetching: indexed URLs processed in *any* earlier run are dropped by
+//! [`HistoryState::filter_unseen`], so hourly runs only spend requests and
+//! tokens on new stories. URLs skipped either way are recorded as
+//! [`SkipReason::Duplicate`].
+//!
+//! The history is stored as a small JSON file (see `--state-file`). A
+//! missing or corrupt file is treated as an empty history. Entries older
+//! than `--state-retention-days` are pruned on load ([`HistoryState::prune`])
+//! so the file stays small.
+
+use crate::models::SkipReason;
+use crate::skips::SkipLog;
+use crate::utils::normalize_url;
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use tokio::fs;
+use tracing::{debug, info, warn};
+
+/// What we know about a previously processed URL.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SeenArticle {
+    /// Hash of the scraped content (see [`crate::utils::content_hash`]).
+    pub content_hash: String,
+    /// Date (`YYYY-MM-DD`) of the edition the URL last appeared in.
+    pub date: String,
+    /// Edition label (`"morning"`, `"afternoon"`, `"evening"`).
+    pub edition: String,
+}
+
+/// History of processed URLs, persisted between runs.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct HistoryState {
+    /// Entries keyed by article URL.
+    pub articles: BTreeMap<String, SeenArticle>,
+}
+
+/// How to treat a fetched article given the history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateDecision {
+    /// Not processed today; process normally.
+    New,
+    /// Already processed today with identical content; skip it.
+    Unchanged,
+    /// Already processed today but the content changed; process and mark
+    /// it as an update of the given edition.
+    Updated {
+        /// Edition the earlier version appeared in.
+        previous_edition: String,
+    },
+}
+
+impl HistoryState {
+    /// Load the history from disk, treating a missing or corrupt file as empty.
+    pub async fn load(path: &str) -> Self {
+        match fs::read_to_string(path).await {
+            Ok(raw) => match serde_json::from_str::<HistoryState>(&raw) {
+                Ok(state) => {
+                    info!(path, entries = state.articles.len(), "Loaded history state");
+                    state
+                }
+                Err(e) => {
+                    warn!(path, error = %e, "History state is corrupt; starting empty");
+                    HistoryState::default()
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HistoryState::default(),
+            Err(e) => {
+                warn!(path, error = %e, "Failed to read history state; starting empty");
+                HistoryState::default()
+            }
+        }
+    }
+
+    /// Write the history to disk as JSON.
+    pub async fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json).await?;
+        Ok(())
+    }
+
+    /// Record that `url` was processed with the given content in an edition.
+    pub fn record(&mut self, url: &str, content_hash: &str, date: &str, edition: &str) {
+        self.articles.insert(
+            url.to_string(),
+            SeenArticle {
+                content_hash: content_hash.to_string(),
+                date: date.to_string(),
+                edition: edition.to_string(),
+            },
+        );
+    }
+
+    /// Drop entries last seen more than `retention_days` before `today`.
+    ///
+    /// Entries with an unparseable date are dropped too. Returns the number
+    /// of entries removed.
+    pub fn prune(&mut self, today: NaiveDate, retention_days: u64) -> usize {
+        let cutoff = today.checked_sub_days(Days::new(retention_days)).unwrap_or(NaiveDate::MIN);
+        let before = self.articles.len();
+        self.articles.retain(|_, seen| {
+            NaiveDate::parse_from_str(&seen.date, "%Y-%m-%d").is_ok_and(|date| date >= cutoff)
+        });
+        before - self.articles.len()
+    }
+
+    /// Keep only indexed items whose URL has not been processed before.
+    ///
+    /// `url_of` returns the URL of an item, matched as indexed and with
+    /// tracking parameters stripped (history is keyed by canonical URL);
+    /// skipped URLs are recorded in `skips`. Returns the kept items and the number skipped.
+    pub fn filter_unseen<T>(
+        &self,
+        source: &str,
+        items: Vec<T>,
+        url_of: fn(&T) -> &str,
+        skips: &SkipLog,
+    ) -> (Vec<T>, usize) {
+        let before = items.len();
+        let kept: Vec<T> = items
+            .into_iter()
+            .filter(|item| {
+                let url = url_of(item);
+                let seen = self.articles.contains_key(url) || self.articles.contains_key(&normalize_url(url));
+                if seen {
+                    debug!(source, url, "Processed in an earlier run; skipping");
+                    skips.record(url, SkipReason::Duplicate);
+                }
+                !seen
+            })
+            .collect();
+        let skipped = before - kept.len();
+        (kept, skipped)
+    }
+}
+
+/// Decide whether a fetched article should be processed.
+///
+/// Only entries from `today` count; a URL last seen on an earlier day is
+/// treated as new.
+pub fn decide(state: &HistoryState, url: &str, content_hash: &str, today: &str) -> UpdateDecision {
+    match state.articles.get(url) {
+        Some(seen) if seen.date == today => {
+            if seen.content_hash == content_hash {
+                UpdateDecision::Unchanged
+            } else {
+                UpdateDecision::Updated {
+                    previous_edition: seen.edition.clone(),
+                }
+            }
+        }
+        _ => UpdateDecision::New,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://text.npr.org/1";
+
+    fn state_with(date: &str) -> HistoryState {
+        let mut state = HistoryState::default();
+        state.record(URL, "abc", date, "morning");
+        state
+    }
+
+    #[test]
+    fn test_same_hash_is_unchanged() {
+        let state = state_with("2025-05-06");
+        assert_eq!(decide(&state, URL, "abc", "2025-05-06"), UpdateDecision::Unchanged);
+    }
+
+    #[test]
+    fn test_changed_hash_is_update() {
+        let state = state_with("2025-05-06");
+        assert_eq!(
+            decide(&state, URL, "def", "2025-05-06"),
+            UpdateDecision::Updated {
+                previous_edition: "morning".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_unknown_url_is_new() {
+        let state = state_with("2025-05-06");
+        assert_eq!(
+            decide(&state, "https://lite.cnn.com/2", "abc", "2025-05-06"),
+            UpdateDecision::New
+        );
+    }
+
+    #[test]
+    fn test_url_from_previous_day_is_new() {
+        let state = state_with("2025-05-05");
+        assert_eq!(decide(&state, URL, "abc", "2025-05-06"), UpdateDecision::New);
+    }
+
+    #[test]
+    fn test_filter_unseen_drops_urls_from_any_earlier_run() {
+        let state = state_with("2025-04-20");
+        let urls = vec![URL.to_string(), "https://text.npr.org/2".to_string()];
+        let skips = SkipLog::default();
+        let (kept, skipped) = state.filter_unseen("npr", urls, String::as_str, &skips);
+        assert_eq!(kept, vec!["https://text.npr.org/2".to_string()]);
+        assert_eq!(skipped, 1);
+
+        let pairs = vec![(URL.to_string(), "Title".to_string())];
+        let (kept, skipped) = state.filter_unseen("nyt", pairs, |(u, _)| u.as_str(), &skips);
+        assert!(kept.is_empty());
+        assert_eq!(skipped, 1);
+        assert_eq!(skips.counts().get(&SkipReason::Duplicate), Some(&2));
+    }
+
+    #[test]
+    fn test_prune_drops_entries_outside_retention() {
+        let mut state = HistoryState::default();
+        state.record("https://a", "1", "2025-05-06", "morning");
+        state.record("https://b", "2", "2025-04-06", "evening");
+        state.record("https://c", "3", "2025-04-05", "evening");
+        state.record("https://d", "4", "not a date", "evening");
+
+        let today = NaiveDate::from_ymd_opt(2025, 5, 6).unwrap();
+        assert_eq!(state.prune(today, 30), 2);
+        assert_eq!(state.articles.keys().collect::<Vec<_>>(), vec!["https://a", "https://b"]);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_state_loads_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        std::fs::write(&path, "{ not json").unwrap();
+        let state = HistoryState::load(path.to_str().unwrap()).await;
+        assert!(state.articles.is_empty());
+    }
+}
@@ -0,0 +1,357 @@
+//! Statistics rollups of the stored editions (the `stats` subcommand).
+//!
+//! Every date directory under `--json-output-dir` is scanned for edition
+//! files and run summaries (`{time_of_day}.run.json`, see
+//! [`RunSummary`]), and two rollups for dashboards are written:
+//!
+//! ```text
+//! json_output_dir/
+//! └── stats/
+//!     ├── rollup_30d.json   the last 30 days, ending today
+//!     └── rollup_all.json   every stored date
+//! ```
+//!
+//! Each rollup has the number of editions and articles per day, per source
+//! and per category, the average summary length in words, and the share of
+//! analyzed articles the LLM step failed on. Failure rates only cover runs
+//! that wrote a run summary; a day without any is `null`.
+//!
+//! Editions are read leniently (sharded ones put back together), so files written by older versions (without
+//! `source`, say) still count; unreadable or corrupt files are skipped with a
+//! warning. `--update-stats` rebuilds the rollups at the end of a scraping
+//! run.
+
+use crate::models::{source_tag_of, RunSummary};
+use crate::outputs::json::read_edition;
+use crate::outputs::sink::OutputSink;
+use crate::utils::all_edition_labels;
+use chrono::{Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use tokio::fs;
+use tracing::{debug, info, warn};
+
+/// Number of days in the recent rollup, ending today.
+pub const RECENT_DAYS: i64 = 30;
+
+/// Source of articles whose URL has no recognizable outlet.
+const UNKNOWN_SOURCE: &str = "unknown";
+
+/// The parts of a stored article the rollups need.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct StoredArticle {
+    pub source: Option<String>,
+    pub category: String,
+    #[serde(rename = "summaryOfNewsArticle")]
+    pub summary: String,
+}
+
+/// The parts of a stored edition the rollups need.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct StoredEdition {
+    pub articles: Vec<StoredArticle>,
+}
+
+/// The editions and run summaries stored under one date.
+#[derive(Debug, Default)]
+pub struct StoredDay {
+    pub date: NaiveDate,
+    pub editions: Vec<StoredEdition>,
+    pub runs: Vec<RunSummary>,
+}
+
+/// Counts of one day of a rollup.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct DayStats {
+    pub date: NaiveDate,
+    pub editions: usize,
+    pub articles: usize,
+    pub per_source: BTreeMap<String, usize>,
+    pub per_category: BTreeMap<String, usize>,
+    pub avg_summary_words: f64,
+    /// Failed / analyzed articles of the day's runs; `None` without run summaries.
+    pub failure_rate: Option<f64>,
+}
+
+/// Totals of a range of days, with the per-day counts.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct Rollup {
+    /// First date of the range; `None` for every stored date.
+    pub since: Option<NaiveDate>,
+    pub editions: usize,
+    pub articles: usize,
+    pub per_source: BTreeMap<String, usize>,
+    pub per_category: BTreeMap<String, usize>,
+    pub avg_summary_words: f64,
+    pub failure_rate: Option<f64>,
+    /// The days with editions or runs, oldest first.
+    pub days: Vec<DayStats>,
+}
+
+/// Running totals, shared by a day and a whole rollup.
+#[derive(Default)]
+struct Tally {
+    editions: usize,
+    articles: usize,
+    per_source: BTreeMap<String, usize>,
+    per_category: BTreeMap<String, usize>,
+    summary_words: usize,
+    analyzed: usize,
+    failed: usize,
+    runs: usize,
+}
+
+impl Tally {
+    fn add(&mut self, day: &StoredDay) {
+        for edition in &day.editions {
+            self.editions += 1;
+            for article in &edition.articles {
+                self.articles += 1;
+                let source = article.source.as_deref().and_then(source_tag_of);
+                *self.per_source.entry(source.unwrap_or_else(|| UNKNOWN_SOURCE.to_string())).or_default() += 1;
+                *self.per_category.entry(article.category.clone()).or_default() += 1;
+                self.summary_words += article.summary.split_whitespace().count();
+            }
+        }
+        for run in &day.runs {
+            self.runs += 1;
+            self.analyzed += run.analyzed;
+            self.failed += run.failed;
+        }
+    }
+
+    fn avg_summary_words(&self) -> f64 {
+        if self.articles == 0 {
+            return 0.0;
+        }
+        self.summary_words as f64 / self.articles as f64
+    }
+
+    fn failure_rate(&self) -> Option<f64> {
+        match (self.runs, self.analyzed) {
+            (0, _) => None,
+            (_, 0) => Some(0.0),
+            (_, analyzed) => Some(self.failed as f64 / analyzed as f64),
+        }
+    }
+}
+
+/// Roll up the stored `days` from `since` on (every day if `None`).
+pub fn rollup(days: &[StoredDay], since: Option<NaiveDate>) -> Rollup {
+    let mut days: Vec<&StoredDay> = days
+        .iter()
+        .filter(|day| since.is_none_or(|since| day.date >= since))
+        .filter(|day| !day.editions.is_empty() || !day.runs.is_empty())
+        .collect();
+    days.sort_by_key(|day| day.date);
+
+    let mut total = Tally::default();
+    let mut per_day = Vec::with_capacity(days.len());
+    for day in days {
+        let mut tally = Tally::default();
+        tally.add(day);
+        total.add(day);
+        per_day.push(DayStats {
+            date: day.date,
+            editions: tally.editions,
+            articles: tally.articles,
+            avg_summary_words: tally.avg_summary_words(),
+            failure_rate: tally.failure_rate(),
+            per_source: tally.per_source,
+            per_category: tally.per_category,
+        });
+    }
+
+    Rollup {
+        since,
+        editions: total.editions,
+        articles: total.articles,
+        avg_summary_words: total.avg_summary_words(),
+        failure_rate: total.failure_rate(),
+        per_source: total.per_source,
+        per_category: total.per_category,
+        days: per_day,
+    }
+}
+
+/// Read a stored JSON file, or `None` if it is missing, unreadable or
+/// corrupt (logged).
+async fn read_json<T: for<'de> Deserialize<'de>>(sink: &impl OutputSink, path: &str) -> Option<T> {
+    let raw = match sink.read_to_string(path).await {
+        Ok(raw) => raw?,
+        Err(e) => {
+            warn!(%path, error = %e, "Failed to read file; leaving it out of the statistics");
+            return None;
+        }
+    };
+    match serde_json::from_str(&raw) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!(%path, error = %e, "Corrupt file; leaving it out of the statistics");
+            None
+        }
+    }
+}
+
+/// Every date directory under `json_output_dir` with its editions and run
+/// summaries, oldest first.
+pub async fn scan(sink: &impl OutputSink, json_output_dir: &str) -> Result<Vec<StoredDay>, Box<dyn Error>> {
+    let mut dates = Vec::new();
+    let mut entries = fs::read_dir(json_output_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name();
+        if let Some(date) = name.to_str().and_then(|n| n.parse::<NaiveDate>().ok())
+            && entry.file_type().await.is_ok_and(|t| t.is_dir())
+        {
+            dates.push(date);
+        }
+    }
+    dates.sort();
+
+    let mut days = Vec::with_capacity(dates.len());
+    for date in dates {
+        let mut day = StoredDay {
+            date,
+            ..Default::default()
+        };
+        for label in all_edition_labels() {
+            let dir = format!("{}/{}", json_output_dir, date);
+            let path = format!("{}/{}.json", dir, label);
+            match read_edition(sink, &path).await {
+                Ok(Some(edition)) => day.editions.push(edition),
+                Ok(None) => {}
+                Err(e) => warn!(%path, error = %e, "Unreadable or corrupt edition; leaving it out of the statistics"),
+            }
+            if let Some(run) = read_json(sink, &format!("{}/{}.run.json", dir, label)).await {
+                day.runs.push(run);
+            }
+        }
+        debug!(%date, editions = day.editions.len(), runs = day.runs.len(), "Scanned date");
+        days.push(day);
+    }
+    Ok(days)
+}
+
+/// Scan `json_output_dir` and write `stats/rollup_30d.json` and
+/// `stats/rollup_all.json` under it.
+///
+/// # Returns
+///
+/// The paths of the written rollups.
+pub async fn run(sink: &impl OutputSink, json_output_dir: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let days = scan(sink, json_output_dir).await?;
+    let recent_since = Local::now().date_naive() - Duration::days(RECENT_DAYS - 1);
+
+    let mut paths = Vec::new();
+    for (name, since) in [("rollup_30d", Some(recent_since)), ("rollup_all", None)] {
+        let rollup = rollup(&days, since);
+        let path = format!("{}/stats/{}.json", json_output_dir, name);
+        sink.write(&path, serde_json::to_string(&rollup)?.as_bytes()).await?;
+        info!(%path, days = rollup.days.len(), articles = rollup.articles, "Wrote statistics rollup");
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::outputs::sink::FileSystem;
+    use serde_json::json;
+
+    fn article(source: Option<&str>, category: &str, summary: &str) -> StoredArticle {
+        StoredArticle {
+            source: source.map(str::to_string),
+            category: category.to_string(),
+            summary: summary.to_string(),
+        }
+    }
+
+    fn date(raw: &str) -> NaiveDate {
+        raw.parse().unwrap()
+    }
+
+    fn run(analyzed: usize, failed: usize) -> RunSummary {
+        RunSummary {
+            analyzed,
+            succeeded: analyzed - failed,
+            failed,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rollup_counts_days_sources_and_failures() {
+        let days = vec![
+            StoredDay {
+                date: date("2025-05-07"),
+                editions: vec![StoredEdition {
+                    articles: vec![article(Some("https://text.npr.org/1"), "World News", "One two three four")],
+                }],
+                runs: vec![],
+            },
+            StoredDay {
+                date: date("2025-05-06"),
+                editions: vec![
+                    StoredEdition {
+                        articles: vec![
+                            article(Some("https://lite.cnn.com/a"), "World News", "One two"),
+                            article(None, "Politics & Governance", "One two three four five six"),
+                        ],
+                    },
+                    StoredEdition::default(),
+                ],
+                runs: vec![run(10, 2), run(6, 2)],
+            },
+            StoredDay {
+                date: date("2025-05-01"),
+                ..Default::default()
+            },
+        ];
+
+        let all = rollup(&days, None);
+        assert_eq!((all.editions, all.articles), (3, 3));
+        assert_eq!(all.days.iter().map(|d| d.date).collect::<Vec<_>>(), vec![date("2025-05-06"), date("2025-05-07")]);
+        assert_eq!(all.per_source.get("cnn"), Some(&1));
+        assert_eq!(all.per_source.get("npr"), Some(&1));
+        assert_eq!(all.per_source.get(UNKNOWN_SOURCE), Some(&1));
+        assert_eq!(all.per_category.get("World News"), Some(&2));
+        assert_eq!(all.avg_summary_words, 4.0);
+        assert_eq!(all.failure_rate, Some(0.25));
+        assert_eq!(all.days[0].avg_summary_words, 4.0);
+        assert_eq!(all.days[1].failure_rate, None);
+
+        let recent = rollup(&days, Some(date("2025-05-07")));
+        assert_eq!((recent.editions, recent.articles, recent.failure_rate), (1, 1, None));
+        assert_eq!(recent.since, Some(date("2025-05-07")));
+        assert_eq!(rollup(&[], None), Rollup::default());
+    }
+
+    #[tokio::test]
+    async fn test_scan_reads_old_editions_and_skips_corrupt_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let json_dir = dir.path().to_str().unwrap();
+        let write = |path: &str, body: String| std::fs::write(dir.path().join(path), body).unwrap();
+        std::fs::create_dir_all(dir.path().join("2025-05-06")).unwrap();
+        std::fs::create_dir_all(dir.path().join("stats")).unwrap();
+        // An old edition: no source, no category
+        write(
+            "2025-05-06/morning.json",
+            json!({ "local_date": "2025-05-06", "articles": [{ "title": "Old", "summaryOfNewsArticle": "Old story." }] }).to_string(),
+        );
+        write("2025-05-06/evening.json", "{ not json".to_string());
+        write("2025-05-06/morning.run.json", json!({ "analyzed": 4, "failed": 1 }).to_string());
+
+        let paths = super::run(&FileSystem, json_dir).await.unwrap();
+        assert_eq!(paths, vec![format!("{}/stats/rollup_30d.json", json_dir), format!("{}/stats/rollup_all.json", json_dir)]);
+        let all: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&paths[1]).unwrap()).unwrap();
+        assert_eq!(all["editions"], 1);
+        assert_eq!(all["per_source"][UNKNOWN_SOURCE], 1);
+        assert_eq!(all["per_category"][""], 1);
+        assert_eq!(all["failure_rate"], 0.25);
+        assert_eq!(all["days"][0]["date"], "2025-05-06");
+    }
+}
@@ -0,0 +1,501 @@
+//! Test-only fixtures for end-to-end runs against a local LLM server.
+//!
+//! [`FixtureLlm`] is a tiny OpenAI-compatible chat-completions server that
+//! answers with canned replies, chosen by a marker string found in the
+//! request (typically a word planted in the fixture article's content).
+//! Together with [`write_config`] and [`write_template`] it lets tests drive
+//! the real `awful_aj` HTTP path, so template schema drift or config
+//! handling bugs show up that trait-level mocks would miss. The end-to-end
+//! tests parse their arguments like a run and go through
+//! [`crate::edition::process`], so they cover the same wiring from fetched
+//! articles to the files on disk.
+//!
+//! The crate is a single binary, so these helpers live here rather than
+//! under `tests/`.
+
+use crate::models::{AwfulNewsArticle, FrontPage, NewsArticle};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+/// Replies keyed by a marker that must appear in the request body.
+struct CannedCompletions {
+    replies: Vec<(String, String)>,
+}
+
+impl Respond for CannedCompletions {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let body = String::from_utf8_lossy(&request.body);
+        match self.replies.iter().find(|(marker, _)| body.contains(marker.as_str())) {
+            Some((_, content)) => ResponseTemplate::new(200).set_body_json(json!({
+                "id": "chatcmpl-fixture",
+                "object": "chat.completion",
+                "created": 0,
+                "model": "fixture",
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": content },
+                    "finish_reason": "stop"
+                }],
+                "usage": { "prompt_tokens": 0, "completion_tokens": 0, "total_tokens": 0 }
+            })),
+            None => ResponseTemplate::new(404).set_body_string("no canned reply for request"),
+        }
+    }
+}
+
+/// A local OpenAI-compatible server with canned chat completions.
+pub struct FixtureLlm {
+    /// The underlying mock server (for inspecting received requests).
+    pub server: MockServer,
+}
+
+impl FixtureLlm {
+    /// Start a server answering requests that contain `marker` with `reply`.
+    ///
+    /// A JSON string reply is sent as plain text; anything else as JSON.
+    pub async fn start(replies: Vec<(&str, Value)>) -> Self {
+        let replies = replies
+            .into_iter()
+            .map(|(marker, reply)| {
+                let content = match reply {
+                    Value::String(text) => text,
+                    other => other.to_string(),
+                };
+                (marker.to_string(), content)
+            })
+            .collect();
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(CannedCompletions { replies })
+            .mount(&server)
+            .await;
+        FixtureLlm { server }
+    }
+
+    /// The `api_base` to configure, e.g. `http://127.0.0.1:1234/v1`.
+    pub fn api_base(&self) -> String {
+        format!("{}/v1", self.server.uri())
+    }
+}
+
+/// Write a `config.yaml` pointing at `api_base` into `dir`.
+///
+/// Starts from the repository's `config.yaml` so new required settings are
+/// picked up automatically.
+pub fn write_config(dir: &Path, api_base: &str) -> PathBuf {
+    let config = include_str!("../config.yaml")
+        .lines()
+        .map(|line| {
+            if line.starts_with("api_base:") {
+                format!("api_base: {}", api_base)
+            } else if line.starts_with("session_db_url:") {
+                format!("session_db_url: \"{}\"", dir.join("aj.db").display())
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let path = dir.join("config.yaml");
+    std::fs::write(&path, config).unwrap();
+    path
+}
+
+/// Write the repository's `news_parser` template into `dir`.
+pub fn write_template(dir: &Path) -> PathBuf {
+    let path = dir.join("news_parser.yaml");
+    std::fs::write(&path, include_str!("../template/news_parser.yaml")).unwrap();
+    path
+}
+
+/// An article titled `title` in `category`, dated 2025-05-06, with every
+/// other field empty. Tests set what they need with struct update syntax:
+///
+/// ```ignore
+/// AwfulNewsArticle { source: Some(url), ..article("Talks resume", "World News") }
+/// ```
+pub fn article(title: &str, category: &str) -> AwfulNewsArticle {
+    AwfulNewsArticle {
+        dateOfPublication: "2025-05-06".to_string(),
+        title: title.to_string(),
+        category: category.to_string(),
+        ..Default::default()
+    }
+}
+
+/// An empty `time_of_day` edition of 2025-05-06, stamped `local_time`.
+/// Like [`article`], tests fill in the rest with struct update syntax:
+///
+/// ```ignore
+/// FrontPage { articles: vec![talks], ..front_page("morning", "08:00:00") }
+/// ```
+pub fn front_page(time_of_day: &str, local_time: &str) -> FrontPage {
+    FrontPage {
+        local_date: "2025-05-06".to_string(),
+        time_of_day: time_of_day.to_string(),
+        local_time: local_time.to_string(),
+        articles: vec![],
+        diff: None,
+        top: vec![],
+        editorial: None,
+        corrections: vec![],
+    }
+}
+
+/// A canned model reply for an article.
+pub fn canned_article(title: &str, category: &str) -> Value {
+    json!({
+        "title": title,
+        "dateOfPublication": "2025-05-06",
+        "timeOfPublication": "14:30:00",
+        "category": category,
+        "summaryOfNewsArticle": format!("Summary of {}.", title),
+        "keyTakeAways": [format!("{} matters", title)],
+        "namedEntities": [],
+        "importantDates": [],
+        "importantTimeframes": [],
+        "tags": ["fixture"]
+    })
+}
+
+/// Scraped articles standing in for the scrapers' output.
+///
+/// Each content carries a marker (`FIXTURE-CNN`, `FIXTURE-NPR`) for
+/// [`FixtureLlm`] to match on.
+pub fn fixture_articles() -> Vec<NewsArticle> {
+    vec![
+        NewsArticle {
+            source: "https://lite.cnn.com/2025/05/06/politics/talks".to_string(),
+            content: "# Talks resume\n\nFIXTURE-CNN Negotiators met again on Tuesday.".into(),
+            published_at: chrono::DateTime::parse_from_rfc3339("2025-05-06T09:15:00-04:00").ok(),
+            authors: vec!["Jane Doe".to_string()],
+            fetched_at: None,
+            fetched_from: None,
+        },
+        NewsArticle {
+            source: "https://text.npr.org/nx-s1-1".to_string(),
+            content: "FIXTURE-NPR A new probe was launched toward the outer planets.".into(),
+            published_at: None,
+            authors: vec![],
+            fetched_at: None,
+            fetched_from: None,
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Cli;
+    use crate::edition::{self, LlmSetup, Run};
+    use crate::fetch::Fetched;
+    use crate::models::FrontPage;
+    use crate::outputs::json::{self as json_output, ContentPolicy};
+    use crate::outputs::sink::FileSystem;
+    use crate::pipeline::{process_article, ProcessContext};
+    use crate::progress::Progress;
+    use crate::schema::ArticleField;
+    use awful_aj::template::ChatTemplate;
+    use crate::skips::SkipLog;
+    use crate::state::HistoryState;
+    use clap::Parser;
+    use std::collections::HashMap;
+
+    /// Summarize `fetched` and write its edition under `out` the way a run
+    /// with `flags` does, with the fixture `config` and `template` in place
+    /// of the ones from the awful_aj config directory. The history is read
+    /// from `--state-file`, if given.
+    async fn run_edition(
+        out: &Path,
+        config: &Path,
+        template: &ChatTemplate,
+        flags: &[&str],
+        front_page: FrontPage,
+        fetched: Fetched,
+    ) {
+        let json_dir = out.join("json");
+        let md_dir = out.join("markdown");
+        let base = [
+            "awful_text_news",
+            "-j",
+            json_dir.to_str().unwrap(),
+            "-m",
+            md_dir.to_str().unwrap(),
+            "--config",
+            config.to_str().unwrap(),
+        ];
+        let args = Cli::parse_from(base.iter().chain(flags));
+        let config = awful_aj::config::load_config(args.config_path().unwrap().to_str().unwrap()).unwrap();
+        let llm = LlmSetup::new(&args, config, template.clone()).unwrap();
+        let history = match &args.state_file {
+            Some(path) => Some(HistoryState::load(path).await),
+            None => None,
+        };
+        let skips = SkipLog::default();
+        let run = Run {
+            args: &args,
+            sink: &FileSystem,
+            json_mirrors: &[],
+            skips: &skips,
+            started: std::time::Instant::now(),
+        };
+        assert_eq!(edition::process(&run, &llm, front_page, fetched, history).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_end_to_end_against_fixture_llm() {
+        // The model names authors for both; only NPR lacks a scraped byline
+        let mut talks = canned_article("Talks resume", "Politics & Governance");
+        talks["authors"] = json!(["Model Guess"]);
+        let mut probe = canned_article("New probe launched", "Science & Technology");
+        probe["authors"] = json!(["Sam Example"]);
+        let llm = FixtureLlm::start(vec![("FIXTURE-CNN", talks), ("FIXTURE-NPR", probe)]).await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let config_path = write_config(dir.path(), &llm.api_base());
+        let template_path = write_template(dir.path());
+        let template: ChatTemplate = serde_yaml::from_str(&std::fs::read_to_string(template_path).unwrap()).unwrap();
+
+        let front_page = front_page("morning", "08:00:00");
+        let fetched = Fetched {
+            articles: fixture_articles(),
+            runs: HashMap::new(),
+            deduped_same_source: 0,
+            deduped_identical: 0,
+        };
+        run_edition(dir.path(), &config_path, &template, &["--journal"], front_page, fetched).await;
+        assert_eq!(llm.server.received_requests().await.unwrap().len(), 2);
+
+        let json_path = dir.path().join("json/2025-05-06/morning.json");
+        let written: FrontPage = serde_json::from_str(&std::fs::read_to_string(json_path).unwrap()).unwrap();
+        // Sorted by category: Politics & Governance before Science & Technology
+        let titles: Vec<&str> = written.articles.iter().map(|a| a.title.as_str()).collect();
+        assert_eq!(titles, vec!["Talks resume", "New probe launched"]);
+        let cnn = &written.articles[0];
+        assert_eq!(cnn.source.as_deref(), Some("https://lite.cnn.com/2025/05/06/politics/talks"));
+        // Publication time is grounded in the scraped timestamp, or cleared
+        assert_eq!(cnn.timeOfPublication, "09:15:00");
+        assert!(written.articles[1].timeOfPublication.is_empty());
+        // Scraped bylines win; the model fills in missing ones
+        assert_eq!(cnn.authors, vec!["Jane Doe"]);
+        assert_eq!(written.articles[1].authors, vec!["Sam Example"]);
+
+        let md = std::fs::read_to_string(dir.path().join("markdown/2025-05-06_morning.md")).unwrap();
+        assert!(md.contains("# Politics & Governance"));
+        assert!(md.contains("## Talks resume - <small>`cnn`</small>"));
+        assert!(md.contains("## New probe launched - <small>`npr`</small>"));
+        assert!(md.contains("Summary of New probe launched."));
+        assert!(md.contains("- _By Jane Doe_"));
+        let summary = std::fs::read_to_string(dir.path().join("markdown/SUMMARY.md")).unwrap();
+        assert!(summary.contains("(./2025-05-06_morning.md)"));
+
+        // Each article was streamed to the journal as it completed
+        let journal = std::fs::read_to_string(dir.path().join("json/2025-05-06/morning.ndjson")).unwrap();
+        assert_eq!(journal.lines().count(), 2);
+        let run: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.path().join("json/2025-05-06/morning.run.json")).unwrap()).unwrap();
+        assert_eq!(run["succeeded"], 2);
+        assert!(run["peak_additional_bytes"].is_u64());
+    }
+
+    #[tokio::test]
+    async fn test_processing_a_raw_dump_matches_single_shot() {
+        use crate::fetch;
+        use crate::outputs::json::SourceRun;
+        use crate::outputs::raw::write_raw_dump;
+
+        let llm = FixtureLlm::start(vec![
+            ("FIXTURE-CNN", canned_article("Talks resume", "Politics & Governance")),
+            ("FIXTURE-NPR", canned_article("New probe launched", "Science & Technology")),
+        ])
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = write_config(dir.path(), &llm.api_base());
+        let template_path = write_template(dir.path());
+        let template: ChatTemplate = serde_yaml::from_str(&std::fs::read_to_string(template_path).unwrap()).unwrap();
+
+        // Fetched late in the evening
+        let edition = front_page("evening", "23:50:00");
+        let mut scraped = fixture_articles();
+        for article in &mut scraped {
+            article.fetched_at = chrono::DateTime::parse_from_rfc3339("2025-05-06T23:49:12.5+00:00").ok().map(|t| t.to_utc());
+        }
+        let runs = HashMap::from([
+            ("cnn", SourceRun { ran: true, indexed: 4, fetched: 1 }),
+            ("npr", SourceRun { ran: true, indexed: 2, fetched: 1 }),
+        ]);
+        let fetched = Fetched {
+            articles: scraped.clone(),
+            runs: runs.clone(),
+            deduped_same_source: 0,
+            deduped_identical: 0,
+        };
+        let single_dir = dir.path().join("single");
+        run_edition(&single_dir, &config_path, &template, &[], edition.clone(), fetched).await;
+
+        // The network box dumps the articles...
+        let raw_dir = dir.path().join("raw");
+        write_raw_dump(&scraped, raw_dir.to_str().unwrap(), &edition, &runs, true).await.unwrap();
+
+        // ...and the GPU box processes them after midnight, into the same edition
+        let (front_page, fetched) = fetch::read_raw_dump(raw_dir.join("2025-05-06/evening").to_str().unwrap()).await.unwrap();
+        assert_eq!(fetched.runs, runs);
+        let split_dir = dir.path().join("split");
+        run_edition(&split_dir, &config_path, &template, &[], front_page, fetched).await;
+
+        let read = |root: &Path, file: &str| std::fs::read_to_string(root.join(file)).unwrap();
+        for file in ["json/2025-05-06/evening.json", "markdown/2025-05-06_evening.md"] {
+            assert_eq!(read(&split_dir, file), read(&single_dir, file), "{file} differs");
+        }
+        let written: FrontPage = serde_json::from_str(&read(&split_dir, "json/2025-05-06/evening.json")).unwrap();
+        assert_eq!(written.articles.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_history_records_articles_the_edition_filters_out() {
+        let llm = FixtureLlm::start(vec![
+            ("FIXTURE-CNN", canned_article("Talks resume", "Politics & Governance")),
+            ("FIXTURE-NPR", canned_article("New probe launched", "Science & Technology")),
+        ])
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = write_config(dir.path(), &llm.api_base());
+        let template: ChatTemplate = serde_yaml::from_str(include_str!("../template/news_parser.yaml")).unwrap();
+        let state_path = dir.path().join("state.json");
+
+        let front_page = front_page("morning", "08:00:00");
+        let fetched = Fetched {
+            articles: fixture_articles(),
+            runs: HashMap::new(),
+            deduped_same_source: 0,
+            deduped_identical: 0,
+        };
+        let flags = ["--state-file", state_path.to_str().unwrap(), "--exclude-categories", "Science & Technology"];
+        run_edition(dir.path(), &config_path, &template, &flags, front_page, fetched).await;
+
+        let written: FrontPage =
+            serde_json::from_str(&std::fs::read_to_string(dir.path().join("json/2025-05-06/morning.json")).unwrap()).unwrap();
+        assert_eq!(written.articles.len(), 1);
+        // The probe was summarized, so the next run skips it too
+        let history = HistoryState::load(state_path.to_str().unwrap()).await;
+        let urls: Vec<&str> = history.articles.keys().map(String::as_str).collect();
+        assert_eq!(urls, vec!["https://lite.cnn.com/2025/05/06/politics/talks", "https://text.npr.org/nx-s1-1"]);
+    }
+
+    #[tokio::test]
+    async fn test_fixture_llm_rejects_unknown_requests() {
+        let llm = FixtureLlm::start(vec![("FIXTURE-CNN", canned_article("T", "Law & Justice"))]).await;
+        let res = reqwest::Client::new()
+            .post(format!("{}/chat/completions", llm.api_base()))
+            .json(&json!({ "messages": [{ "role": "user", "content": "unrelated" }] }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_carried_over_failure_is_processed_and_tagged() {
+        use crate::carryover::{self, FailedArticle, FailureList};
+
+        let llm = FixtureLlm::start(vec![
+            ("FIXTURE-CNN", canned_article("Talks resume", "Politics & Governance")),
+            ("FIXTURE-NPR", canned_article("New probe launched", "Science & Technology")),
+        ])
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let config = awful_aj::config::load_config(write_config(dir.path(), &llm.api_base()).to_str().unwrap()).unwrap();
+        let template: ChatTemplate = serde_yaml::from_str(include_str!("../template/news_parser.yaml")).unwrap();
+
+        // The previous run failed on the NPR article
+        let [cnn, npr]: [NewsArticle; 2] = fixture_articles().try_into().unwrap();
+        let failures_path = carryover::failures_path(dir.path().join("state.json").to_str().unwrap());
+        let now = chrono::Utc::now();
+        FailureList {
+            articles: vec![FailedArticle { failed_at: now - chrono::Duration::hours(10), article: npr }],
+        }
+        .save(&failures_path)
+        .await
+        .unwrap();
+
+        let mut queue = vec![cnn];
+        let previous = FailureList::load(&failures_path).await;
+        let carried_over = carryover::merge_into_queue(&mut queue, previous.recent(now));
+        assert_eq!(queue.len(), 2);
+
+        let update_of = HashMap::new();
+        let progress = Progress::new(false, queue.len());
+        let ctx = ProcessContext {
+            config: &config,
+            template: &template,
+            template_vars: &Default::default(),
+            auth: None,
+            update_of: &update_of,
+            carried_over: &carried_over,
+            extract_fields: ArticleField::ALL,
+            reask: true,
+            reask_with_retries: false,
+            input_header: Default::default(),
+            limits: Default::default(),
+            concurrency: &Default::default(),
+            adaptive: &Default::default(),
+            progress: &progress,
+            skips: &Default::default(),
+        };
+        let mut processed = Vec::new();
+        for (i, article) in queue.iter().enumerate() {
+            processed.extend(process_article(&ctx, i, article).await);
+        }
+
+        let tagged: Vec<(&str, bool)> = processed.iter().map(|a| (a.title.as_str(), a.carriedOver)).collect();
+        assert_eq!(tagged, vec![("Talks resume", false), ("New probe launched", true)]);
+    }
+
+    #[tokio::test]
+    async fn test_post_midnight_evening_outputs_agree_on_prior_date() {
+        use crate::outputs::indexes;
+
+        let dir = tempfile::tempdir().unwrap();
+        let json_dir = dir.path().join("json");
+        let md_dir = dir.path().join("md");
+        std::fs::create_dir_all(&md_dir).unwrap();
+        let md_dir = md_dir.to_str().unwrap();
+
+        // An evening run that finished at 00:30 the next day
+        let mut article: crate::models::AwfulNewsArticle =
+            serde_json::from_value(canned_article("Late vote", "Politics & Governance")).unwrap();
+        article.source = Some("https://lite.cnn.com/2025/05/06/politics/vote".to_string());
+        let front_page = FrontPage {
+            local_date: "2025-05-07".to_string(),
+            articles: vec![article],
+            ..front_page("evening", "00:30:00.000000")
+        };
+
+        let json_path = json_output::write_frontpage(&FileSystem, &front_page, json_dir.to_str().unwrap(), ContentPolicy::Full, None, None)
+            .await
+            .unwrap()
+            .remove(0);
+        assert!(json_path.ends_with("/2025-05-06/evening.json"), "{}", json_path);
+
+        let markdown_filename = format!("{}.md", front_page.edition_stem());
+        assert_eq!(markdown_filename, "2025-05-06_evening.md");
+
+        indexes::update_edition_indexes(&FileSystem, md_dir, &front_page, &markdown_filename, &Default::default()).await.unwrap();
+
+        let toc = std::fs::read_to_string(dir.path().join("md/2025-05-06.md")).unwrap();
+        assert!(toc.starts_with("# Editions published on 2025-05-06"));
+        assert!(toc.contains("- [Evening (00:30:00.000000)](./2025-05-06_evening.md)"));
+        assert!(!dir.path().join("md/2025-05-07.md").exists());
+
+        let summary = std::fs::read_to_string(dir.path().join("md/SUMMARY.md")).unwrap();
+        assert!(summary.contains("    - [2025-05-06](./2025-05-06.md)\n        - [Evening (00:30:00.000000)](./2025-05-06_evening.md)"));
+        assert!(!summary.contains("2025-05-07"));
+
+        let daily = std::fs::read_to_string(dir.path().join("md/daily_news.md")).unwrap();
+        assert!(daily.contains("- [**2025-05-06**](./2025-05-06.md)"));
+        assert!(!daily.contains("2025-05-07"));
+    }
+}
@@ -0,0 +1,138 @@
+//! Top stories featured at the head of an edition.
+//!
+//! With `--top-n N` (default [`DEFAULT_TOP_N`], `0` disables), the N most
+//! important articles of an edition are stored in the edition's `top` array
+//! and rendered as a "Top Stories" block above the category sections.
+//! Featured articles still appear in full in their category sections.
+//!
+//! # Importance
+//!
+//! An article is important when the rest of the edition covers the same
+//! people, places and organizations:
+//!
+//! ```text
+//! score = RELATED_WEIGHT * related + shared_entities
+//! ```
+//!
+//! `related` is the number of other articles sharing at least one named
+//! entity with it, and `shared_entities` the number of its entities that
+//! another article mentions too. Ties keep edition order. Articles moved to
+//! "Also today" by `--max-per-category` are never featured.
+
+use crate::models::{AwfulNewsArticle, FeaturedStory};
+use std::collections::{BTreeSet, HashMap};
+
+/// Number of top stories featured by default.
+pub const DEFAULT_TOP_N: usize = 5;
+
+/// Weight of each related article relative to a shared entity.
+pub const RELATED_WEIGHT: usize = 3;
+
+/// Importance score of every article, in edition order.
+pub fn importance_scores(articles: &[AwfulNewsArticle]) -> Vec<usize> {
+    let names: Vec<BTreeSet<String>> = articles.iter().map(AwfulNewsArticle::entity_names).collect();
+    let mut mentions: HashMap<&str, usize> = HashMap::new();
+    for name in names.iter().flatten() {
+        *mentions.entry(name).or_default() += 1;
+    }
+
+    names
+        .iter()
+        .enumerate()
+        .map(|(i, own)| {
+            let related = names
+                .iter()
+                .enumerate()
+                .filter(|(j, other)| *j != i && !own.is_disjoint(other))
+                .count();
+            let shared_entities = own.iter().filter(|name| mentions[name.as_str()] > 1).count();
+            RELATED_WEIGHT * related + shared_entities
+        })
+        .collect()
+}
+
+/// The `n` most important articles, best first.
+pub fn select_top(articles: &[AwfulNewsArticle], n: usize) -> Vec<FeaturedStory> {
+    if n == 0 {
+        return Vec::new();
+    }
+    let scores = importance_scores(articles);
+    let mut ranked: Vec<usize> = (0..articles.len()).filter(|&i| !articles[i].overflow).collect();
+    // Stable: equal scores keep edition order
+    ranked.sort_by(|&a, &b| scores[b].cmp(&scores[a]));
+    ranked
+        .into_iter()
+        .take(n)
+        .map(|i| FeaturedStory {
+            title: articles[i].title.clone(),
+            source: articles[i].source.clone(),
+            category: articles[i].category.clone(),
+            score: scores[i],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use crate::models::NamedEntity;
+
+    fn article(title: &str, entities: &[&str]) -> AwfulNewsArticle {
+        AwfulNewsArticle {
+            source: Some(format!("https://text.npr.org/{}", title.to_lowercase())),
+            summaryOfNewsArticle: format!("{} summary.", title),
+            namedEntities: entities
+                .iter()
+                .map(|name| NamedEntity {
+                    name: name.to_string(),
+                    whatIsThisEntity: String::new(),
+                    whyIsThisEntityRelevantToTheArticle: String::new(),
+                    entityType: None,
+                })
+                .collect(),
+            ..test_support::article(title, "World News")
+        }
+    }
+
+    fn edition() -> Vec<AwfulNewsArticle> {
+        vec![
+            article("Quiet", &["Nobody"]),
+            article("Talks", &["Egypt", "Hamas", "Israel"]),
+            article("Aid", &["Egypt", "UN"]),
+            article("Strikes", &["Israel", "Hamas"]),
+            article("Vote", &["UN"]),
+            article("Probe", &["NASA"]),
+        ]
+    }
+
+    #[test]
+    fn test_top_block_has_n_stories_in_score_order() {
+        let articles = edition();
+        let top = select_top(&articles, 3);
+        assert_eq!(top.len(), 3);
+        let ranked: Vec<(&str, usize)> = top.iter().map(|s| (s.title.as_str(), s.score)).collect();
+        // Talks: Aid and Strikes related, all three entities shared
+        assert_eq!(ranked, vec![("Talks", 9), ("Aid", 8), ("Strikes", 5)]);
+        assert_eq!(top[0].source.as_deref(), Some("https://text.npr.org/talks"));
+
+        // Stored as the edition's `top` array
+        let json = serde_json::to_value(&top).unwrap();
+        let expected = serde_json::json!({
+            "title": "Aid",
+            "source": "https://text.npr.org/aid",
+            "category": "World News",
+            "score": 8
+        });
+        assert_eq!(json[1], expected);
+    }
+
+    #[test]
+    fn test_ties_keep_edition_order_and_overflow_is_not_featured() {
+        let mut articles = edition();
+        articles[1].overflow = true;
+        let titles: Vec<String> = select_top(&articles, 10).into_iter().map(|s| s.title).collect();
+        assert_eq!(titles, vec!["Aid", "Strikes", "Vote", "Quiet", "Probe"]);
+        assert!(select_top(&articles, 0).is_empty());
+    }
+}
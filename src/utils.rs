@@ -3,43 +3,98 @@
 //! This module provides helper functions used throughout the application:
 //! - Time classification for edition naming
 //! - String truncation and slugification for logging and URLs
-//! - JSON error detection for handling LLM response truncation
-//! - File system validation for output directories
+//! - JSON failure classification and repair for LLM responses
+//! - File system validation for output directories (writability and free
+//!   space), and a fallback location for outputs the primary directory can't
+//!   take
+//! - Content hashing for change detection across editions and duplicate
+//!   suppression within a source
 
-use chrono::{Local, NaiveTime};
+use crate::models::{NewsArticle, SkipReason};
+use crate::skips::SkipLog;
+use chrono::{Local, NaiveTime, Timelike};
+use clap::ValueEnum;
+use std::collections::HashMap;
+use sha2::{Digest, Sha256};
 use std::error::Error;
-use std::fs as stdfs;
+use std::future::Future;
+use std::io;
 use tokio::fs;
-use tracing::{info, instrument, warn};
+use tracing::{debug, error, info, instrument, warn};
 
-/// Classify current time into morning, afternoon, or evening.
-///
-/// This function is used to determine the "edition" name for news output.
-/// The time boundaries are:
-/// - **Morning**: 00:00 - 08:00
-/// - **Afternoon**: 08:00 - 16:00
-/// - **Evening**: 16:00 - 24:00
+/// How editions are named (`--edition-scheme`).
+///
+/// The label is the edition's `time_of_day`, its file stem suffix and its
+/// entry in the index files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum EditionScheme {
+    /// Three editions a day:
+    /// - **Morning**: 00:00 - 08:00
+    /// - **Afternoon**: 08:00 - 16:00
+    /// - **Evening**: 16:00 - 24:00
+    #[default]
+    Tod,
+    /// One edition per hour, named by the hour it starts: `00h` to `23h`.
+    Hourly,
+}
+
+impl EditionScheme {
+    /// Every edition label of the scheme, in publication order.
+    pub fn labels(self) -> Vec<String> {
+        match self {
+            EditionScheme::Tod => ["morning", "afternoon", "evening"].map(String::from).to_vec(),
+            EditionScheme::Hourly => (0..24).map(|hour| format!("{:02}h", hour)).collect(),
+        }
+    }
+
+    /// The label of an edition published at `time`.
+    pub fn label_at(self, time: NaiveTime) -> String {
+        match self {
+            EditionScheme::Tod => {
+                let afternoon_low = NaiveTime::from_hms_opt(8, 00, 0).unwrap();
+                let afternoon_high = NaiveTime::from_hms_opt(16, 00, 0).unwrap();
+                let which = if time < afternoon_low {
+                    "morning"
+                } else if time < afternoon_high {
+                    "afternoon"
+                } else {
+                    "evening"
+                };
+                which.to_string()
+            }
+            EditionScheme::Hourly => format!("{:02}h", time.hour()),
+        }
+    }
+
+    /// The scheme `label` belongs to, if any.
+    pub fn of_label(label: &str) -> Option<Self> {
+        [EditionScheme::Tod, EditionScheme::Hourly]
+            .into_iter()
+            .find(|scheme| scheme.labels().iter().any(|l| l == label))
+    }
+}
+
+/// Edition labels of every scheme, each scheme in publication order.
+///
+/// Used to find a date's editions on disk whichever scheme wrote them.
+pub fn all_edition_labels() -> Vec<String> {
+    [EditionScheme::Tod, EditionScheme::Hourly]
+        .into_iter()
+        .flat_map(EditionScheme::labels)
+        .collect()
+}
+
+/// Label of an edition published now under `scheme`.
 ///
 /// # Returns
 ///
-/// A string: `"morning"`, `"afternoon"`, or `"evening"`.
+/// E.g. `"morning"` (`tod`) or `"14h"` (`hourly`).
 #[instrument]
-pub fn time_of_day() -> String {
-    let morning_low = NaiveTime::from_hms_opt(0, 00, 0).unwrap();
-    let morning_high = NaiveTime::from_hms_opt(8, 00, 0).unwrap();
-    let afternoon_low = NaiveTime::from_hms_opt(8, 00, 0).unwrap();
-    let afternoon_high = NaiveTime::from_hms_opt(16, 00, 0).unwrap();
-
+pub fn time_of_day(scheme: EditionScheme) -> String {
     let tod = Local::now().time();
-    let which = if (tod >= morning_low) && (tod < morning_high) {
-        "morning"
-    } else if (tod >= afternoon_low) && (tod < afternoon_high) {
-        "afternoon"
-    } else {
-        "evening"
-    };
+    let which = scheme.label_at(tod);
     tracing::debug!(%tod, %which, "Computed time_of_day");
-    which.to_string()
+    which
 }
 
 /// Truncate a string for logging purposes.
@@ -71,22 +126,161 @@ pub fn truncate_for_log(s: &str, max: usize) -> String {
     }
 }
 
-/// Detect if a serde_json error indicates truncated/incomplete JSON.
+/// How an LLM response that failed to parse should be handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseFailureKind {
+    /// The output stops before the JSON is complete (token limit, dropped
+    /// stream). Re-asking is likely to help.
+    Truncated,
+    /// The JSON is complete but wrapped or lightly malformed (code fences,
+    /// surrounding prose, trailing commas). [`repair_json`] can fix it.
+    Repairable,
+    /// Garbage, or valid JSON that doesn't match the schema. Re-asking is
+    /// unlikely to help; skip the article.
+    Malformed,
+}
+
+/// Bracket depth and open-string state at the end of `raw`.
+///
+/// Braces and brackets inside string literals are ignored.
+fn json_balance(raw: &str) -> (i64, bool) {
+    let mut depth = 0i64;
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in raw.chars() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => depth += 1,
+            '}' | ']' => depth -= 1,
+            _ => {}
+        }
+    }
+    (depth, in_string)
+}
+
+/// Byte offset of a serde_json error's line/column position in `raw`.
+fn error_offset(e: &serde_json::Error, raw: &str) -> usize {
+    let preceding: usize = raw
+        .split_inclusive('\n')
+        .take(e.line().saturating_sub(1))
+        .map(str::len)
+        .sum();
+    preceding + e.column()
+}
+
+/// Classify why an LLM response failed to parse.
 ///
-/// When the LLM response is cut off (e.g., due to token limits), the
-/// resulting JSON will fail to parse with an EOF error. This function
-/// helps identify such cases for retry logic.
+/// Considers the serde error category, where the error occurred relative to
+/// the end of the input (a failure at the very end strongly implies
+/// truncation), and whether braces and quotes in the raw text are balanced.
 ///
 /// # Arguments
 ///
-/// * `e` - The serde_json error to classify
+/// * `e` - The error returned when parsing `raw`
+/// * `raw` - The raw model response
+pub fn classify_parse_failure(e: &serde_json::Error, raw: &str) -> ParseFailureKind {
+    use serde_json::error::Category;
+
+    let json_start = raw.find('{').unwrap_or(0);
+    let (depth, open_string) = json_balance(&raw[json_start..]);
+    let unbalanced = depth > 0 || open_string;
+
+    match e.classify() {
+        // EOF with balanced text means there was nothing (or nothing
+        // structured) to parse, not a cut-off response.
+        Category::Eof if unbalanced => ParseFailureKind::Truncated,
+        Category::Eof => ParseFailureKind::Malformed,
+        Category::Syntax => {
+            let at_end = error_offset(e, raw) + 1 >= raw.trim_end().len();
+            if at_end && unbalanced {
+                ParseFailureKind::Truncated
+            } else if repair_json(raw).is_some() {
+                ParseFailureKind::Repairable
+            } else if unbalanced {
+                ParseFailureKind::Truncated
+            } else {
+                ParseFailureKind::Malformed
+            }
+        }
+        Category::Data | Category::Io => ParseFailureKind::Malformed,
+    }
+}
+
+/// Remove commas that directly precede a closing brace or bracket.
+fn strip_trailing_commas(json: &str) -> String {
+    let mut out = String::with_capacity(json.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut pending_comma: Option<String> = None;
+    for c in json.chars() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            out.push(c);
+            continue;
+        }
+        if let Some(pending) = pending_comma.as_mut() {
+            if c.is_whitespace() {
+                pending.push(c);
+                continue;
+            }
+            let pending = pending_comma.take().unwrap();
+            if c == '}' || c == ']' {
+                out.push_str(&pending[1..]);
+            } else {
+                out.push_str(&pending);
+            }
+        }
+        match c {
+            ',' => pending_comma = Some(String::from(",")),
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    if let Some(pending) = pending_comma {
+        out.push_str(&pending);
+    }
+    out
+}
+
+/// Attempt to recover a complete JSON object from a wrapped or lightly
+/// malformed LLM response.
+///
+/// Strips Markdown code fences and any prose around the outermost object,
+/// and removes trailing commas.
 ///
 /// # Returns
 ///
-/// `true` if the error is an EOF (end-of-file) error, indicating truncation.
-pub fn looks_truncated(e: &serde_json::Error) -> bool {
-    use serde_json::error::Category;
-    matches!(e.classify(), Category::Eof)
+/// The repaired JSON if it differs from the input and parses, otherwise `None`.
+pub fn repair_json(raw: &str) -> Option<String> {
+    let start = raw.find('{')?;
+    let end = raw.rfind('}')?;
+    if end < start {
+        return None;
+    }
+    let candidate = strip_trailing_commas(&raw[start..=end]);
+    if candidate == raw.trim() {
+        return None;
+    }
+    serde_json::from_str::<serde_json::Value>(&candidate)
+        .ok()
+        .map(|_| candidate)
 }
 
 /// Convert a title to a URL-friendly slug.
@@ -142,10 +336,198 @@ pub fn upcase(s: &str) -> String {
     }
 }
 
+/// Hash article content for change detection.
+///
+/// Whitespace is normalized first so that reflowed text with identical
+/// words produces the same hash.
+///
+/// # Returns
+///
+/// A lowercase hex SHA-256 digest.
+pub fn content_hash(content: &str) -> String {
+    let normalized = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    format!("{:x}", Sha256::digest(normalized.as_bytes()))
+}
+
+/// Query parameters that only track where a click came from.
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "dclid", "msclkid", "mc_cid", "mc_eid", "igshid", "cmpid", "ocid", "smid", "smtyp", "ref_src",
+];
+
+/// Strip tracking from an article URL: `utm_*` and other
+/// [tracking parameters](TRACKING_PARAMS), and the fragment. Other query
+/// parameters keep their order. A URL that doesn't parse is returned as is.
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(
+///     normalize_url("https://apnews.com/article/x?utm_source=rss&page=2#top"),
+///     "https://apnews.com/article/x?page=2"
+/// );
+/// ```
+pub fn normalize_url(raw: &str) -> String {
+    let Ok(mut url) = url::Url::parse(raw.trim()) else {
+        return raw.to_string();
+    };
+    url.set_fragment(None);
+    let kept: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| {
+            let key = key.to_ascii_lowercase();
+            !key.starts_with("utm_") && !TRACKING_PARAMS.contains(&key.as_str())
+        })
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    if kept.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(kept);
+    }
+    url.to_string()
+}
+
+/// Which article of a duplicate set [`dedupe_same_source`] keeps (and
+/// [`dedupe_identical_content`], between copies from equally preferred sources).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum DuplicatePreference {
+    /// The shortest URL, usually the canonical one.
+    #[default]
+    ShortestUrl,
+    /// The longest content (duplicates differ only in whitespace, so this
+    /// keeps the copy with its paragraph breaks), then the shortest URL.
+    LongestContent,
+}
+
+impl DuplicatePreference {
+    /// Whether `candidate` should replace `kept` as the representative.
+    fn prefers(self, candidate: &NewsArticle, kept: &NewsArticle) -> bool {
+        let shorter_url = candidate.source.len() < kept.source.len();
+        match self {
+            DuplicatePreference::ShortestUrl => shorter_url,
+            DuplicatePreference::LongestContent => match candidate.content.len().cmp(&kept.content.len()) {
+                std::cmp::Ordering::Equal => shorter_url,
+                longer => longer.is_gt(),
+            },
+        }
+    }
+}
+
+/// Hash of an article's body, for spotting the same copy under different URLs.
+///
+/// Like [`content_hash`], but the header block the scrapers prepend (the
+/// leading `Title:`, `Published:` and `# ` lines) is left out first, so wire
+/// copy carried by two outlets hashes the same even though their headers
+/// differ. Lines like these further down are part of the body. Any other
+/// difference in the words, however small, gives a different hash.
+pub fn body_hash(content: &str) -> String {
+    let mut body = content.trim_start();
+    while ["Title:", "Published:", "# "].iter().any(|prefix| body.starts_with(prefix)) {
+        body = body.split_once('\n').map_or("", |(_, rest)| rest).trim_start();
+    }
+    content_hash(body)
+}
+
+/// Collapse articles that share a `hash`: of each set, the article
+/// `prefers` picks is kept at the position of the first one, and the others
+/// are recorded in `skips` as duplicates.
+fn dedupe_by(
+    articles: Vec<NewsArticle>,
+    hash: impl Fn(&NewsArticle) -> String,
+    prefers: impl Fn(&NewsArticle, &NewsArticle) -> bool,
+    skips: &SkipLog,
+) -> (Vec<NewsArticle>, usize) {
+    let before = articles.len();
+    let mut kept: Vec<NewsArticle> = Vec::with_capacity(before);
+    let mut by_hash: HashMap<String, usize> = HashMap::new();
+
+    for article in articles {
+        let hash = hash(&article);
+        match by_hash.get(&hash) {
+            Some(&i) => {
+                tracing::debug!(kept = %kept[i].source, duplicate = %article.source, "Dropping duplicate");
+                let dropped = if prefers(&article, &kept[i]) {
+                    std::mem::replace(&mut kept[i], article)
+                } else {
+                    article
+                };
+                skips.record(&dropped.source, SkipReason::Duplicate);
+            }
+            None => {
+                by_hash.insert(hash, kept.len());
+                kept.push(article);
+            }
+        }
+    }
+
+    let dropped = before - kept.len();
+    (kept, dropped)
+}
+
+/// Drop articles whose content duplicates an earlier article of the same source.
+///
+/// Outlets sometimes list one story under several URLs (e.g. a dated path
+/// and a canonical redirect). Articles resolving to the same canonical
+/// source URL are duplicates; the rest are compared by [`content_hash`], so
+/// only whitespace differences are ignored. Of each duplicate set the
+/// article chosen by `prefer` is kept, at the position of the first one;
+/// the others are recorded in `skips` as duplicates.
+///
+/// # Returns
+///
+/// The deduplicated articles and the number of articles dropped.
+pub fn dedupe_same_source(
+    articles: Vec<NewsArticle>,
+    prefer: DuplicatePreference,
+    skips: &SkipLog,
+) -> (Vec<NewsArticle>, usize) {
+    let (articles, same_url) = dedupe_by(articles, |a| a.source.clone(), |a, kept| prefer.prefers(a, kept), skips);
+    let (articles, same_content) =
+        dedupe_by(articles, |a| content_hash(&a.content), |a, kept| prefer.prefers(a, kept), skips);
+    (articles, same_url + same_content)
+}
+
+/// Drop articles whose body is identical to an earlier article's, across sources.
+///
+/// Syndicated wire copy runs at several outlets word for word. Articles are
+/// compared by [`body_hash`], a cheap exact match: rewritten or edited
+/// copies are left alone. Of each duplicate set the article from the source
+/// listed first in `prefer_sources` is kept (unlisted sources rank last),
+/// then the one chosen by `prefer`; the others are recorded in `skips` as
+/// duplicates.
+///
+/// # Returns
+///
+/// The deduplicated articles and the number of articles dropped.
+pub fn dedupe_identical_content(
+    articles: Vec<NewsArticle>,
+    prefer_sources: &[String],
+    prefer: DuplicatePreference,
+    skips: &SkipLog,
+) -> (Vec<NewsArticle>, usize) {
+    let rank = |article: &NewsArticle| {
+        article
+            .source_tag()
+            .and_then(|tag| prefer_sources.iter().position(|p| *p == tag))
+            .unwrap_or(prefer_sources.len())
+    };
+    dedupe_by(
+        articles,
+        |a| body_hash(&a.content),
+        |a, kept| match rank(a).cmp(&rank(kept)) {
+            std::cmp::Ordering::Equal => prefer.prefers(a, kept),
+            higher => higher.is_lt(),
+        },
+        skips,
+    )
+}
+
 /// Ensure a directory exists and is writable.
 ///
 /// This function creates the directory if it doesn't exist, then performs
-/// a write test by creating and immediately deleting a probe file.
+/// a write test by creating and immediately deleting a probe file. The probe
+/// name is random, so concurrent runs checking the same directory don't
+/// delete each other's probe.
 ///
 /// # Arguments
 ///
@@ -166,11 +548,10 @@ pub async fn ensure_writable_dir(path: &str) -> Result<(), Box<dyn Error>> {
     if let Err(e) = fs::create_dir_all(path).await {
         return Err(Box::new(e));
     }
-    // Try a small sync write using std fs (simpler error surface)
-    let probe_path = format!("{}/..__probe_write__", path.trim_end_matches('/'));
-    match stdfs::File::create(&probe_path) {
+    let probe_path = format!("{}/..__probe_write_{:016x}__", path.trim_end_matches('/'), rand::random::<u64>());
+    match fs::File::create(&probe_path).await {
         Ok(_) => {
-            let _ = stdfs::remove_file(&probe_path);
+            let _ = fs::remove_file(&probe_path).await;
             info!("Output directory is writable");
             Ok(())
         }
@@ -178,10 +559,250 @@ pub async fn ensure_writable_dir(path: &str) -> Result<(), Box<dyn Error>> {
     }
 }
 
+/// Bytes available to this user on the filesystem holding `path`.
+pub fn available_space(path: &str) -> io::Result<u64> {
+    fs2::available_space(path)
+}
+
+/// Check that the filesystem holding `path` has at least `min_free_mb` MiB
+/// available, as reported by `available` (normally [`available_space`]).
+///
+/// # Errors
+///
+/// Returns an error naming the directory and both amounts when space is
+/// short, or the reporter's error if the free space can't be read.
+pub fn ensure_free_space<F>(path: &str, min_free_mb: u64, available: F) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&str) -> io::Result<u64>,
+{
+    let free_mb = available(path).map_err(|e| format!("cannot read free space of {}: {}", path, e))? / (1024 * 1024);
+    if free_mb < min_free_mb {
+        return Err(format!("{} has {} MiB free, below the {} MiB minimum (--min-free-mb)", path, free_mb, min_free_mb).into());
+    }
+    debug!(path, free_mb, min_free_mb, "Enough free space");
+    Ok(())
+}
+
+/// Write `contents` to `path` through a temporary file renamed into place,
+/// so readers see either the old file or the new one, never a partial one.
+pub async fn write_atomic(path: &str, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let tmp = format!("{}.tmp", path);
+    fs::write(&tmp, contents).await?;
+    fs::rename(&tmp, path).await
+}
+
+/// Exit code of a run that had to write its edition to `--fallback-output-dir`
+/// (`EX_TEMPFAIL`): the edition exists, but not where it belongs.
+pub const FALLBACK_EXIT_CODE: u8 = 75;
+
+/// A copy of the edition to keep when its primary output can't be written.
+#[derive(Debug, Clone, Copy)]
+pub struct Fallback<'a> {
+    /// Directory to write into, created if missing.
+    pub dir: &'a str,
+    pub file_name: &'a str,
+    pub contents: &'a [u8],
+}
+
+/// Where [`write_with_fallback`] put the output.
+#[derive(Debug, PartialEq)]
+pub enum Written<T> {
+    /// The primary write succeeded, with its result.
+    Primary(T),
+    /// The primary write failed; the fallback copy is at this path.
+    Fallback(String),
+}
+
+/// Whether a failed write is worth retrying elsewhere: the disk is full or
+/// the directory isn't writable (`ENOSPC`, `EACCES`, `EROFS`).
+pub fn is_fallback_error(e: &(dyn Error + 'static)) -> bool {
+    e.downcast_ref::<io::Error>().is_some_and(|e| {
+        matches!(
+            e.kind(),
+            io::ErrorKind::StorageFull | io::ErrorKind::PermissionDenied | io::ErrorKind::ReadOnlyFilesystem
+        )
+    })
+}
+
+/// Run the `primary` write; if it fails for lack of space or permission,
+/// write the `fallback` copy instead.
+///
+/// Other failures, and any failure without a fallback, are returned as is.
+/// If the fallback write fails too, both errors are logged and the primary
+/// one is returned.
+pub async fn write_with_fallback<T, E>(
+    primary: impl Future<Output = Result<T, E>>,
+    fallback: Option<Fallback<'_>>,
+) -> Result<Written<T>, Box<dyn Error>>
+where
+    E: Into<Box<dyn Error>>,
+{
+    let e: Box<dyn Error> = match primary.await {
+        Ok(written) => return Ok(Written::Primary(written)),
+        Err(e) => e.into(),
+    };
+    let Some(fallback) = fallback.filter(|_| is_fallback_error(e.as_ref())) else {
+        return Err(e);
+    };
+    let path = format!("{}/{}", fallback.dir.trim_end_matches('/'), fallback.file_name);
+    let written = match fs::create_dir_all(fallback.dir).await {
+        Ok(()) => fs::write(&path, fallback.contents).await,
+        Err(e) => Err(e),
+    };
+    match written {
+        Ok(()) => Ok(Written::Fallback(path)),
+        Err(fallback_error) => {
+            error!(%path, error = %fallback_error, primary_error = %e, "Fallback write failed too");
+            Err(e)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use chrono::NaiveTime;
+    use std::fs as stdfs;
+
+    fn news(source: &str, content: &str) -> NewsArticle {
+        NewsArticle {
+            source: source.to_string(),
+            content: content.into(),
+            published_at: None,
+            authors: vec![],
+            fetched_at: None,
+            fetched_from: None,
+        }
+    }
+
+    #[test]
+    fn test_dedupe_same_source_exact_duplicate_keeps_shorter_url() {
+        let articles = vec![
+            news("https://lite.cnn.com/2025/05/06/politics/story/index.html", "Body  of the\nstory"),
+            news("https://lite.cnn.com/other", "Another story"),
+            news("https://lite.cnn.com/story", "Body of the story"),
+        ];
+        let skips = SkipLog::default();
+        let (kept, dropped) = dedupe_same_source(articles, DuplicatePreference::ShortestUrl, &skips);
+        assert_eq!(dropped, 1);
+        let sources: Vec<&str> = kept.iter().map(|a| a.source.as_str()).collect();
+        assert_eq!(sources, vec!["https://lite.cnn.com/story", "https://lite.cnn.com/other"]);
+        // The longer URL is the one recorded
+        let skipped = skips.entries();
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].source, "https://lite.cnn.com/2025/05/06/politics/story/index.html");
+        assert_eq!(skipped[0].reason, SkipReason::Duplicate);
+    }
+
+    #[test]
+    fn test_dedupe_same_source_near_duplicate_is_kept() {
+        let articles = vec![
+            news("https://lite.cnn.com/a", "Officials said on Tuesday the talks would resume."),
+            news("https://lite.cnn.com/b", "Officials said on Wednesday the talks would resume."),
+        ];
+        let (kept, dropped) = dedupe_same_source(articles, DuplicatePreference::ShortestUrl, &SkipLog::default());
+        assert_eq!(dropped, 0);
+        assert_eq!(kept.len(), 2);
+    }
+
+    #[test]
+    fn test_dedupe_same_source_distinct_articles() {
+        let articles = vec![news("https://text.npr.org/1", "One"), news("https://text.npr.org/2", "Two")];
+        let (kept, dropped) = dedupe_same_source(articles, DuplicatePreference::ShortestUrl, &SkipLog::default());
+        assert_eq!((kept.len(), dropped), (2, 0));
+    }
+
+    #[test]
+    fn test_dedupe_same_source_by_canonical_url() {
+        // Two tracking variants resolved to the same canonical URL, fetched a
+        // minute apart with an edit in between
+        let articles = vec![
+            news("https://text.npr.org/1", "Update: one").with_canonical(Some("https://text.npr.org/1".to_string())),
+            news("https://text.npr.org/1?utm_source=rss", "One").with_canonical(None),
+        ];
+        assert_eq!(articles[1].fetched_from.as_deref(), Some("https://text.npr.org/1?utm_source=rss"));
+        let skips = SkipLog::default();
+        let (kept, dropped) = dedupe_same_source(articles, DuplicatePreference::ShortestUrl, &skips);
+        assert_eq!((kept.len(), dropped), (1, 1));
+        assert_eq!(&*kept[0].content, "Update: one");
+    }
+
+    #[test]
+    fn test_normalize_url() {
+        assert_eq!(
+            normalize_url("https://apnews.com/article/x?utm_source=rss&page=2&UTM_Medium=y&fbclid=abc#top"),
+            "https://apnews.com/article/x?page=2"
+        );
+        assert_eq!(
+            normalize_url("https://www.nytimes.com/2025/05/06/us/vote.html?smid=url-share"),
+            "https://www.nytimes.com/2025/05/06/us/vote.html"
+        );
+        assert_eq!(normalize_url("https://text.npr.org/1"), "https://text.npr.org/1");
+        assert_eq!(normalize_url("not a url"), "not a url");
+    }
+
+    #[test]
+    fn test_dedupe_same_source_can_keep_longest_content() {
+        let articles = || {
+            vec![
+                news("https://lite.cnn.com/story", "Body of the story. More of it."),
+                news("https://lite.cnn.com/2025/05/06/politics/story/index.html", "Body of the story.\n\nMore of it."),
+            ]
+        };
+        let skips = SkipLog::default();
+        let (kept, dropped) = dedupe_same_source(articles(), DuplicatePreference::LongestContent, &skips);
+        assert_eq!(dropped, 1);
+        assert_eq!(kept[0].source, "https://lite.cnn.com/2025/05/06/politics/story/index.html");
+        assert_eq!(skips.entries()[0].source, "https://lite.cnn.com/story");
+
+        // The default keeps the shorter URL
+        let (kept, _) = dedupe_same_source(articles(), DuplicatePreference::default(), &SkipLog::default());
+        assert_eq!(kept[0].source, "https://lite.cnn.com/story");
+    }
+
+    #[test]
+    fn test_body_hash_matches_identical_bodies_only() {
+        let wire = "Officials said on Tuesday the talks would resume.\n\nA deal is expected by Friday.";
+        // The same body under different scraper headers
+        let apnews = format!("Published: 2025-05-06T08:00:00+00:00\n\n{}", wire);
+        let bbc = format!("Published: 2025-05-06T09:00:00+00:00\n\nTitle: Talks to resume\n\n{}", wire);
+        assert_eq!(body_hash(&apnews), body_hash(&bbc));
+        assert_eq!(body_hash(&apnews), body_hash(&wire.replace("\n\n", " ")));
+        // Near-identical copy is a different body
+        assert_ne!(body_hash(wire), body_hash(&wire.replace("Friday", "Saturday")));
+        assert_ne!(body_hash(wire), body_hash(&wire.replace("talks", "Talks")));
+        // Only the leading header block is left out
+        let quoting = format!("{}\n\nTitle: Talks to resume\n\n# Background", apnews);
+        assert_ne!(body_hash(&quoting), body_hash(&apnews));
+        assert_eq!(body_hash("# Talks to resume\n\nPublished: 2025-05-06\n\n"), body_hash(""));
+    }
+
+    #[test]
+    fn test_dedupe_identical_content_keeps_preferred_source() {
+        let body = "Published: 2025-05-06T08:00:00+00:00\n\nThe central bank held rates steady on Tuesday.";
+        let articles = vec![
+            news("https://www.bbc.com/news/articles/c1", body),
+            news("https://text.npr.org/1", "A different story."),
+            news("https://apnews.com/article/fed-holds-rates-steady", body),
+            news("https://www.aljazeera.com/news/2025/5/6/fed", "The central bank held rates steady on Tuesday. "),
+        ];
+        let skips = SkipLog::default();
+        let prefer_sources = vec!["apnews".to_string()];
+        let (kept, dropped) =
+            dedupe_identical_content(articles, &prefer_sources, DuplicatePreference::ShortestUrl, &skips);
+        assert_eq!(dropped, 2);
+        let sources: Vec<&str> = kept.iter().map(|a| a.source.as_str()).collect();
+        assert_eq!(sources, vec!["https://apnews.com/article/fed-holds-rates-steady", "https://text.npr.org/1"]);
+        assert!(skips.entries().iter().all(|s| s.reason == SkipReason::Duplicate));
+
+        // Without a preferred source, the shorter URL wins
+        let articles = vec![
+            news("https://www.bbc.com/news/articles/c1", body),
+            news("https://apnews.com/article/fed-holds-rates-steady", body),
+        ];
+        let (kept, _) = dedupe_identical_content(articles, &[], DuplicatePreference::ShortestUrl, &SkipLog::default());
+        assert_eq!(kept[0].source, "https://www.bbc.com/news/articles/c1");
+    }
 
     #[test]
     fn test_truncate_for_log_short_string() {
@@ -220,6 +841,35 @@ mod tests {
         assert_eq!(upcase("a"), "A");
     }
 
+    #[test]
+    fn test_content_hash_ignores_whitespace_layout() {
+        assert_eq!(content_hash("Hello  world\n"), content_hash("Hello world"));
+        assert_ne!(content_hash("Hello world"), content_hash("Hello there"));
+        assert_eq!(content_hash("").len(), 64);
+    }
+
+    #[test]
+    fn test_hourly_scheme_labels() {
+        let at = |h, m| EditionScheme::Hourly.label_at(NaiveTime::from_hms_opt(h, m, 0).unwrap());
+        assert_eq!(at(0, 5), "00h");
+        assert_eq!(at(9, 59), "09h");
+        assert_eq!(at(14, 0), "14h");
+        assert_eq!(at(23, 30), "23h");
+        let labels = EditionScheme::Hourly.labels();
+        assert_eq!((labels.len(), labels[14].as_str()), (24, "14h"));
+        assert_eq!(EditionScheme::of_label("14h"), Some(EditionScheme::Hourly));
+        assert_eq!(EditionScheme::of_label("evening"), Some(EditionScheme::Tod));
+        assert_eq!(EditionScheme::of_label("24h"), None);
+    }
+
+    #[test]
+    fn test_tod_scheme_labels() {
+        let at = |h, m| EditionScheme::Tod.label_at(NaiveTime::from_hms_opt(h, m, 0).unwrap());
+        assert_eq!(at(7, 59), "morning");
+        assert_eq!(at(8, 0), "afternoon");
+        assert_eq!(at(16, 0), "evening");
+    }
+
     #[test]
     fn test_time_of_day_morning() {
         // We can't easily test the actual time_of_day function without mocking time,
@@ -246,12 +896,115 @@ mod tests {
     }
 
     #[test]
-    fn test_looks_truncated() {
-        // Test EOF detection
-        let json_eof = r#"{"field": "value"#; // Missing closing brace
-        let result: Result<serde_json::Value, _> = serde_json::from_str(json_eof);
-        if let Err(e) = result {
-            assert!(looks_truncated(&e));
+    fn test_classify_parse_failure_table() {
+        use ParseFailureKind::*;
+
+        #[derive(serde::Deserialize)]
+        #[allow(dead_code)]
+        struct Shape {
+            title: String,
+            tags: Vec<String>,
         }
+
+        let cases: &[(&str, &str, ParseFailureKind)] = &[
+            ("missing closing brace", r#"{"field": "value""#, Truncated),
+            ("stopped mid-string", r#"{"title": "Senate passes the bi"#, Truncated),
+            ("stopped after comma", r#"{"title": "A", "tags": ["x","#, Truncated),
+            ("stopped after key", r#"{"title": "A", "tags""#, Truncated),
+            ("stopped mid-literal", r#"{"title": "A", "flag": tru"#, Truncated),
+            ("prose then truncated object", "Sure! Here is the JSON: {\"title\": \"A\", \"tags\": [", Truncated),
+            ("empty response", "", Malformed),
+            ("whitespace only", "   \n", Malformed),
+            ("code fenced", "```json\n{\"title\": \"A\", \"tags\": []}\n```", Repairable),
+            ("trailing prose", "{\"title\": \"A\", \"tags\": []}\nHope this helps!", Repairable),
+            ("trailing comma", r#"{"title": "A", "tags": ["x",],}"#, Repairable),
+            ("plain refusal", "I'm sorry, I can't help with that.", Malformed),
+            ("balanced but invalid", r#"{"title": "A" "tags": []}"#, Malformed),
+            ("wrong schema", r#"{"title": 5, "tags": []}"#, Malformed),
+        ];
+
+        for (name, raw, expected) in cases {
+            let err = serde_json::from_str::<Shape>(raw).err().unwrap_or_else(|| panic!("{name}: parsed"));
+            assert_eq!(classify_parse_failure(&err, raw), *expected, "{name}: {err}");
+        }
+    }
+
+    #[test]
+    fn test_repair_json() {
+        assert_eq!(
+            repair_json("```json\n{\"a\": [1, 2,], }\n```").as_deref(),
+            Some("{\"a\": [1, 2] }")
+        );
+        // Commas inside strings are left alone
+        assert_eq!(
+            repair_json("Here: {\"a\": \"x,]\"}").as_deref(),
+            Some("{\"a\": \"x,]\"}")
+        );
+        assert_eq!(repair_json("{\"a\": 1}"), None);
+        assert_eq!(repair_json("no json here"), None);
+    }
+
+    #[tokio::test]
+    async fn test_write_with_fallback_on_read_only_primary() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let primary = dir.path().join("json");
+        stdfs::create_dir(&primary).unwrap();
+        stdfs::set_permissions(&primary, stdfs::Permissions::from_mode(0o555)).unwrap();
+        let fallback_dir = dir.path().join("fallback");
+        let fallback = Fallback {
+            dir: fallback_dir.to_str().unwrap(),
+            file_name: "2025-05-06_evening.json",
+            contents: b"{\"articles\": []}",
+        };
+
+        let target = primary.join("evening.json");
+        // Root ignores permission bits; the simulated ENOSPC case below still runs
+        if stdfs::write(&target, "probe").is_err() {
+            let written = write_with_fallback(fs::write(&target, "{}"), Some(fallback)).await.unwrap();
+            let path = fallback_dir.join("2025-05-06_evening.json");
+            assert_eq!(written, Written::Fallback(path.to_str().unwrap().to_string()));
+            assert_eq!(stdfs::read_to_string(&path).unwrap(), "{\"articles\": []}");
+            // Without a fallback directory the error surfaces
+            assert!(write_with_fallback(fs::write(&target, "{}"), None).await.is_err());
+        }
+        stdfs::set_permissions(&primary, stdfs::Permissions::from_mode(0o755)).unwrap();
+
+        let full = async { Err::<(), _>(io::Error::from(io::ErrorKind::StorageFull)) };
+        let written = write_with_fallback(full, Some(fallback)).await.unwrap();
+        assert!(matches!(written, Written::Fallback(_)));
+        // Only space and permission errors fall back
+        let missing = fs::write(dir.path().join("missing/evening.json"), "{}");
+        assert!(write_with_fallback(missing, Some(fallback)).await.is_err());
+        let ok = fs::write(primary.join("evening.json"), "{}");
+        assert_eq!(write_with_fallback(ok, Some(fallback)).await.unwrap(), Written::Primary(()));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_writability_probes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        for _ in 0..20 {
+            let (a, b) = tokio::join!(ensure_writable_dir(path), ensure_writable_dir(path));
+            assert!(a.is_ok() && b.is_ok());
+        }
+        // Every probe cleaned up after itself
+        assert_eq!(stdfs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_free_space_threshold() {
+        let mib = 1024 * 1024;
+        let reporter = |free: u64| move |_: &str| -> io::Result<u64> { Ok(free) };
+        assert!(ensure_free_space("/out", 100, reporter(100 * mib)).is_ok());
+        assert!(ensure_free_space("/out", 0, reporter(0)).is_ok());
+
+        let e = ensure_free_space("/out", 100, reporter(100 * mib - 1)).unwrap_err();
+        assert_eq!(e.to_string(), "/out has 99 MiB free, below the 100 MiB minimum (--min-free-mb)");
+
+        let failing = |_: &str| -> io::Result<u64> { Err(io::Error::from(io::ErrorKind::Unsupported)) };
+        assert!(ensure_free_space("/out", 1, failing).unwrap_err().to_string().starts_with("cannot read free space of /out"));
+        assert!(available_space(".").is_ok());
     }
 }
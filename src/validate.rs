@@ -0,0 +1,322 @@
+//! Preflight checks for `--validate-only`.
+//!
+//! Runs every setup check a real run depends on, without scraping or
+//! calling the LLM, and reports them all at once instead of failing on the
+//! first problem halfway through a run:
+//!
+//! - output directories (JSON, Markdown and any optional mirrors/dumps) are
+//!   writable, and with `--min-free-mb` the JSON and Markdown ones have
+//!   enough free space
+//! - state and cache files can be written next to where they live
+//! - `config.yaml` and the `news_parser` template load
+//! - API keys and credentials for the selected features are present
+//! - the boilerplate rules file parses
+//! - the selectors file parses and every selector in it is valid
+//!
+//! Failures make the process exit non-zero; warnings (e.g. no NYT API key,
+//! so NYT is skipped) are reported but don't fail validation.
+
+use crate::cli::Cli;
+use crate::schema::{self, ArticleField};
+use crate::scrapers::boilerplate::SourceRules;
+use crate::scrapers::selectors;
+use crate::utils::{available_space, ensure_free_space, ensure_writable_dir};
+use awful_aj::{config, template};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Outcome of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// The check passed.
+    Ok,
+    /// Not fatal, but a feature will be degraded.
+    Warn,
+    /// The run would fail or misbehave.
+    Fail,
+}
+
+/// One line of the report.
+#[derive(Debug, Clone)]
+pub struct Check {
+    /// What was checked.
+    pub name: String,
+    /// The outcome.
+    pub status: Status,
+    /// Path, error or hint.
+    pub detail: String,
+}
+
+/// All checks of a validation run.
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Checks in the order they ran.
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    /// Add a check.
+    pub fn push(&mut self, name: impl Into<String>, status: Status, detail: impl Into<String>) {
+        self.checks.push(Check {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+        });
+    }
+
+    /// Whether no check failed.
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|c| c.status != Status::Fail)
+    }
+
+    /// Human-readable report, one check per line plus a summary.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for check in &self.checks {
+            let label = match check.status {
+                Status::Ok => "ok  ",
+                Status::Warn => "WARN",
+                Status::Fail => "FAIL",
+            };
+            writeln!(out, "[{}] {}: {}", label, check.name, check.detail).unwrap();
+        }
+        let count = |status| self.checks.iter().filter(|c| c.status == status).count();
+        writeln!(
+            out,
+            "{} checks: {} ok, {} warnings, {} failed",
+            self.checks.len(),
+            count(Status::Ok),
+            count(Status::Warn),
+            count(Status::Fail)
+        )
+        .unwrap();
+        out
+    }
+
+    async fn check_dir(&mut self, name: &str, path: &str) {
+        match ensure_writable_dir(path).await {
+            Ok(()) => self.push(name, Status::Ok, format!("{} is writable", path)),
+            Err(e) => self.push(name, Status::Fail, format!("{} is not writable: {}", path, e)),
+        }
+    }
+
+    /// A file we write at the end of a run: its directory must be writable.
+    async fn check_file_dir(&mut self, name: &str, file: &str) {
+        let dir = Path::new(file)
+            .parent()
+            .map(|p| p.to_string_lossy().into_owned())
+            .filter(|p| !p.is_empty())
+            .unwrap_or_else(|| ".".to_string());
+        match ensure_writable_dir(&dir).await {
+            Ok(()) => self.push(name, Status::Ok, format!("{} can be written", file)),
+            Err(e) => self.push(name, Status::Fail, format!("{} cannot be written: {}", file, e)),
+        }
+    }
+
+    fn check_config(&mut self, path: &str) {
+        match config::load_config(path) {
+            Ok(config) if config.api_base.trim().is_empty() => {
+                self.push("config", Status::Fail, format!("{} has an empty api_base", path))
+            }
+            Ok(config) => self.push(
+                "config",
+                Status::Ok,
+                format!("{} (model {} at {})", path, config.model, config.api_base),
+            ),
+            Err(e) => self.push("config", Status::Fail, format!("{} failed to load: {}", path, e)),
+        }
+    }
+
+    async fn check_boilerplate(&mut self, path: &str) {
+        let result = match tokio::fs::read_to_string(path).await {
+            Ok(raw) => serde_yaml::from_str::<BTreeMap<String, SourceRules>>(&raw)
+                .map(|rules| format!("{} ({} sources)", path, rules.len()))
+                .map_err(|e| format!("{} is invalid: {}", path, e)),
+            Err(e) => Err(format!("{} cannot be read: {}", path, e)),
+        };
+        match result {
+            Ok(detail) => self.push("boilerplate rules", Status::Ok, detail),
+            Err(detail) => self.push("boilerplate rules", Status::Fail, detail),
+        }
+    }
+
+    async fn check_selectors(&mut self, path: &str) {
+        let result = match tokio::fs::read_to_string(path).await {
+            Ok(raw) => selectors::load(&raw)
+                .map(|_| path.to_string())
+                .map_err(|e| format!("{} is invalid: {}", path, e)),
+            Err(e) => Err(format!("{} cannot be read: {}", path, e)),
+        };
+        match result {
+            Ok(detail) => self.push("selectors", Status::Ok, detail),
+            Err(detail) => self.push("selectors", Status::Fail, detail),
+        }
+    }
+}
+
+/// Run all preflight checks for `args`.
+pub async fn run(args: &Cli) -> Report {
+    let mut report = Report::default();
+
+    // ---- Output locations ----
+    report.check_dir("JSON output dir", &args.json_output_dir).await;
+    report.check_dir("Markdown output dir", &args.markdown_output_dir).await;
+    if let Some(min_free_mb) = args.min_free_mb {
+        for dir in [&args.json_output_dir, &args.markdown_output_dir] {
+            match ensure_free_space(dir, min_free_mb, available_space) {
+                Ok(()) => report.push("free space", Status::Ok, format!("{} has at least {} MiB free", dir, min_free_mb)),
+                Err(e) => report.push("free space", Status::Fail, e.to_string()),
+            }
+        }
+    }
+    for mirror in &args.json_mirror_dir {
+        match ensure_writable_dir(mirror).await {
+            Ok(()) => report.push("JSON mirror dir", Status::Ok, format!("{} is writable", mirror)),
+            // A failing mirror is skipped, not fatal
+            Err(e) => report.push("JSON mirror dir", Status::Warn, format!("{} is not writable: {}", mirror, e)),
+        }
+    }
+    let optional_dirs = [
+        ("raw dump dir", &args.raw_dump_dir),
+        ("snapshot dir", &args.snapshot_dir),
+        ("text output dir", &args.text_output_dir),
+        ("gemtext output dir", &args.gemtext_output_dir),
+    ];
+    for (name, dir) in optional_dirs {
+        if let Some(dir) = dir {
+            report.check_dir(name, dir).await;
+        }
+    }
+    if let Some(path) = &args.state_file {
+        report.check_file_dir("state file", path).await;
+    }
+    if let Some(path) = &args.index_cache_file {
+        report.check_file_dir("index cache file", path).await;
+    }
+    if let Some(path) = &args.page_cache_file {
+        report.check_file_dir("page cache file", path).await;
+    }
+
+    // ---- Config and template ----
+    match args.config_path() {
+        Ok(path) => report.check_config(&path.to_string_lossy()),
+        Err(e) => report.push("config", Status::Fail, format!("no config directory: {}", e)),
+    }
+    match template::load_template("news_parser").await {
+        Ok(mut template) => {
+            let restricted = if args.extract_fields.len() < ArticleField::ALL.len() {
+                schema::restrict_template(&mut template, &args.extract_fields)
+            } else {
+                Ok(())
+            };
+            match restricted {
+                Ok(()) => report.push("template", Status::Ok, "news_parser loaded"),
+                Err(e) => report.push("template", Status::Fail, format!("--extract-fields cannot be applied: {}", e)),
+            }
+        }
+        Err(e) => report.push("template", Status::Fail, format!("news_parser failed to load: {}", e)),
+    }
+
+    // ---- Keys and optional inputs ----
+    match &args.nyt_api_key {
+        Some(key) if !key.trim().is_empty() => report.push("NYT API key", Status::Ok, "present"),
+        _ => report.push("NYT API key", Status::Warn, "missing; NYT articles will be skipped"),
+    }
+    if let Some(path) = &args.boilerplate_file {
+        report.check_boilerplate(path).await;
+    }
+    if let Some(path) = &args.selectors_file {
+        report.check_selectors(path).await;
+    }
+    if args.amqp_url.is_some() && !cfg!(feature = "publish") {
+        report.push(
+            "message bus",
+            Status::Warn,
+            "--amqp-url is set but this build has no `publish` feature; events are not sent",
+        );
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_writable_dirs_pass_and_unwritable_fail() {
+        let dir = tempfile::tempdir().unwrap();
+        let json = dir.path().join("json");
+        // A regular file where a directory is expected
+        let blocker = dir.path().join("blocker");
+        std::fs::write(&blocker, "").unwrap();
+        let md = blocker.join("md");
+
+        let mut report = Report::default();
+        report.check_dir("JSON output dir", json.to_str().unwrap()).await;
+        report.check_dir("Markdown output dir", md.to_str().unwrap()).await;
+        assert_eq!(report.checks[0].status, Status::Ok);
+        assert_eq!(report.checks[1].status, Status::Fail);
+        assert!(!report.passed());
+    }
+
+    #[tokio::test]
+    async fn test_boilerplate_file_is_parsed() {
+        let dir = tempfile::tempdir().unwrap();
+        let good = dir.path().join("good.yaml");
+        std::fs::write(&good, "cnn:\n  phrases: [\"Read more\"]\n").unwrap();
+        let bad = dir.path().join("bad.yaml");
+        std::fs::write(&bad, "cnn: [not, rules").unwrap();
+
+        let mut report = Report::default();
+        report.check_boilerplate(good.to_str().unwrap()).await;
+        report.check_boilerplate(bad.to_str().unwrap()).await;
+        report.check_boilerplate(dir.path().join("missing.yaml").to_str().unwrap()).await;
+        let statuses: Vec<Status> = report.checks.iter().map(|c| c.status).collect();
+        assert_eq!(statuses, vec![Status::Ok, Status::Fail, Status::Fail]);
+    }
+
+    #[tokio::test]
+    async fn test_selectors_file_is_checked() {
+        let dir = tempfile::tempdir().unwrap();
+        let good = dir.path().join("good.yaml");
+        std::fs::write(&good, "scrapers:\n  cnn:\n    article_selector: \".article--lite\"\n").unwrap();
+        let bad = dir.path().join("bad.yaml");
+        std::fs::write(&bad, "scrapers:\n  cnn:\n    article_selector: \".a[\"\n").unwrap();
+
+        let mut report = Report::default();
+        report.check_selectors(good.to_str().unwrap()).await;
+        report.check_selectors(bad.to_str().unwrap()).await;
+        let statuses: Vec<Status> = report.checks.iter().map(|c| c.status).collect();
+        assert_eq!(statuses, vec![Status::Ok, Status::Fail]);
+        assert!(report.checks[1].detail.contains("scrapers.cnn.article_selector"));
+    }
+
+    #[test]
+    fn test_config_must_load_with_api_base() {
+        let dir = tempfile::tempdir().unwrap();
+        let good = crate::test_support::write_config(dir.path(), "http://127.0.0.1:5001/v1");
+        let empty_base = dir.path().join("empty.yaml");
+        let raw = std::fs::read_to_string(&good).unwrap().replace("api_base: http://127.0.0.1:5001/v1", "api_base: \"\"");
+        std::fs::write(&empty_base, raw).unwrap();
+
+        let mut report = Report::default();
+        report.check_config(good.to_str().unwrap());
+        report.check_config(empty_base.to_str().unwrap());
+        report.check_config(dir.path().join("missing.yaml").to_str().unwrap());
+        let statuses: Vec<Status> = report.checks.iter().map(|c| c.status).collect();
+        assert_eq!(statuses, vec![Status::Ok, Status::Fail, Status::Fail]);
+        assert!(report.checks[0].detail.contains("http://127.0.0.1:5001/v1"));
+        assert!(report.render().contains("[FAIL] config: "));
+    }
+
+    #[test]
+    fn test_render_summarizes_counts() {
+        let mut report = Report::default();
+        report.push("a", Status::Ok, "fine");
+        report.push("b", Status::Warn, "hmm");
+        assert!(report.passed());
+        assert_eq!(report.render(), "[ok  ] a: fine\n[WARN] b: hmm\n2 checks: 1 ok, 1 warnings, 0 failed\n");
+    }
+}
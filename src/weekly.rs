@@ -0,0 +1,443 @@
+//! The week in review (the `weekly` subcommand).
+//!
+//! Once a week, the editions of the seven days ending on `--week-ending`
+//! (default: today) are read back from the JSON output and the top stories
+//! of each category are collected into one aggregate edition. It is written
+//! to `{json_output_dir}/{week_end}/weekly.json` and
+//! `{markdown_output_dir}/{week_end}_weekly.md`, and listed under "Weekly"
+//! in `SUMMARY.md` and `daily_news.md`.
+//!
+//! # Selection
+//!
+//! Articles are matched across editions by source URL, falling back to the
+//! title, the same as the edition diff ([`crate::diff`]). Each story is
+//! scored as
+//!
+//! ```text
+//! score = COVERAGE_WEIGHT * coverage + shared_entities
+//! ```
+//!
+//! where `coverage` is the number of editions that carried the story and
+//! `shared_entities` the number of its named entities that other stories of
+//! the week mention too. A story keeps the title, category and summary of
+//! its latest edition. Within a category, stories rank by score, then by
+//! most recent edition, then by title, and the top `--top` are kept,
+//! so the same editions always give the same selection.
+//!
+//! Days without editions (or with unreadable ones) are skipped; a week
+//! without any edition still produces an empty weekly edition.
+//!
+//! # Overview
+//!
+//! With `weekly --overview`, the selected stories are sent to the LLM once
+//! with the `weekly_overview` template (from the config directory, or the
+//! built-in one) for an overview paragraph. A failed or empty answer is
+//! logged and the weekly edition is written without it.
+
+use crate::api::{ask_once, AdaptiveConcurrency, BasicAuth};
+use crate::cli::{Cli, WeeklyArgs};
+use crate::diff::story_key;
+use crate::models::{AwfulNewsArticle, EditionAnchors, FrontPage, WeeklyEdition, WeeklyStory};
+use crate::ordering::category_rank;
+use crate::outputs::json::{build_day_digest, write_mirrors, write_weekly};
+use crate::outputs::sink::OutputSink;
+use crate::outputs::{indexes, markdown};
+use awful_aj::config::{self, AwfulJadeConfig};
+use awful_aj::template::{self, ChatTemplate};
+use chrono::{Duration, Local, NaiveDate};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::Write;
+use tracing::{debug, error, info, warn};
+
+/// Number of days in a weekly edition, ending on the week's last date.
+pub const WEEK_DAYS: i64 = 7;
+
+/// Weight of each edition carrying a story, relative to a shared entity.
+pub const COVERAGE_WEIGHT: usize = 3;
+
+/// Stories kept per category unless `weekly --top` says otherwise.
+pub const DEFAULT_STORIES_PER_CATEGORY: usize = 3;
+
+/// The built-in overview template, used when the config directory has no
+/// `weekly_overview` template.
+const BUILTIN_OVERVIEW_TEMPLATE: &str = include_str!("../template/weekly_overview.yaml");
+
+/// The dates of the week ending on `week_end`, oldest first.
+pub fn week_dates(week_end: NaiveDate) -> Vec<NaiveDate> {
+    (0..WEEK_DAYS).rev().map(|back| week_end - Duration::days(back)).collect()
+}
+
+/// Every edition of the week ending on `week_end`, oldest first.
+pub async fn load_week(sink: &impl OutputSink, json_output_dir: &str, week_end: NaiveDate) -> Vec<FrontPage> {
+    let mut editions = Vec::new();
+    for date in week_dates(week_end) {
+        let digest = build_day_digest(sink, json_output_dir, &date.to_string()).await;
+        if digest.editions.is_empty() {
+            debug!(%date, "No editions on this day");
+        }
+        editions.extend(digest.editions);
+    }
+    editions
+}
+
+/// A story of the week while it is being collected.
+struct Candidate<'a> {
+    latest: &'a AwfulNewsArticle,
+    edition: &'a FrontPage,
+    /// Index of `edition` in the week, for "most recent first".
+    position: usize,
+    coverage: usize,
+    anchor: String,
+}
+
+/// Pick the top `per_category` stories of each category from the week's
+/// `editions` (oldest first).
+pub fn select_stories(editions: &[FrontPage], per_category: usize) -> Vec<WeeklyStory> {
+    let mut candidates: Vec<Candidate> = Vec::new();
+    let mut by_key: HashMap<&str, usize> = HashMap::new();
+    for (position, edition) in editions.iter().enumerate() {
+        let ids = EditionAnchors::new(&edition.articles);
+        // A story listed twice in one edition is covered once
+        let mut seen: HashSet<&str> = HashSet::new();
+        for article in &edition.articles {
+            let key = story_key(article);
+            if !seen.insert(key) {
+                continue;
+            }
+            let anchor = ids.id(article);
+            match by_key.get(key) {
+                Some(&i) => {
+                    let candidate = &mut candidates[i];
+                    candidate.latest = article;
+                    candidate.edition = edition;
+                    candidate.position = position;
+                    candidate.coverage += 1;
+                    candidate.anchor = anchor;
+                }
+                None => {
+                    by_key.insert(key, candidates.len());
+                    candidates.push(Candidate {
+                        latest: article,
+                        edition,
+                        position,
+                        coverage: 1,
+                        anchor,
+                    });
+                }
+            }
+        }
+    }
+
+    // Number of stories mentioning each entity
+    let mut mentions: HashMap<String, usize> = HashMap::new();
+    for candidate in &candidates {
+        for name in candidate.latest.entity_names() {
+            *mentions.entry(name).or_default() += 1;
+        }
+    }
+
+    let mut ranked: Vec<(usize, WeeklyStory)> = candidates
+        .into_iter()
+        .map(|c| {
+            let shared_entities = c.latest.entity_names().iter().filter(|name| mentions[*name] > 1).count();
+            let story = WeeklyStory {
+                title: c.latest.title.clone(),
+                category: c.latest.category.clone(),
+                source: c.latest.source.clone(),
+                summary: c.latest.summaryOfNewsArticle.clone(),
+                coverage: c.coverage,
+                shared_entities,
+                score: COVERAGE_WEIGHT * c.coverage + shared_entities,
+                edition_file: format!("{}.md", c.edition.edition_stem()),
+                anchor: c.anchor,
+            };
+            (c.position, story)
+        })
+        .collect();
+    ranked.sort_by(|(pos_a, a), (pos_b, b)| {
+        (category_rank(&a.category), &a.category, Reverse(a.score), Reverse(*pos_a), &a.title).cmp(&(
+            category_rank(&b.category),
+            &b.category,
+            Reverse(b.score),
+            Reverse(*pos_b),
+            &b.title,
+        ))
+    });
+
+    let mut kept: HashMap<String, usize> = HashMap::new();
+    ranked
+        .into_iter()
+        .map(|(_, story)| story)
+        .filter(|story| {
+            let count = kept.entry(story.category.clone()).or_default();
+            *count += 1;
+            *count <= per_category
+        })
+        .collect()
+}
+
+/// Build the weekly edition ending on `week_end` from the week's `editions`.
+pub fn build_weekly(editions: &[FrontPage], week_end: NaiveDate, per_category: usize) -> WeeklyEdition {
+    let mut days_covered: Vec<String> = Vec::new();
+    for edition in editions {
+        let date = edition.edition_date();
+        if !days_covered.contains(&date) {
+            days_covered.push(date);
+        }
+    }
+    WeeklyEdition {
+        week_start: week_dates(week_end)[0].to_string(),
+        week_end: week_end.to_string(),
+        days_covered,
+        overview: None,
+        stories: select_stories(editions, per_category),
+    }
+}
+
+/// The LLM input for the overview: one line per selected story.
+pub fn overview_input(weekly: &WeeklyEdition) -> String {
+    let mut input = format!("Week of {} to {}.\n\n", weekly.week_start, weekly.week_end);
+    for story in &weekly.stories {
+        writeln!(input, "- [{}] {}: {}", story.category, story.title, story.summary.trim()).unwrap();
+    }
+    input
+}
+
+/// Ask the LLM for the week's overview paragraph.
+///
+/// `None` if there is nothing to summarize or the request fails or comes
+/// back empty (logged).
+pub async fn write_overview(
+    config: &AwfulJadeConfig,
+    template: &ChatTemplate,
+    auth: Option<&BasicAuth>,
+    weekly: &WeeklyEdition,
+) -> Option<String> {
+    if weekly.stories.is_empty() {
+        return None;
+    }
+    let adaptive = AdaptiveConcurrency::new(1);
+    match ask_once(config, &overview_input(weekly), template, auth, &adaptive).await {
+        Ok(text) if !text.trim().is_empty() => Some(text.trim().to_string()),
+        Ok(_) => {
+            warn!("LLM returned an empty weekly overview; leaving it out");
+            None
+        }
+        Err(e) => {
+            warn!(error = %e, "Weekly overview failed; leaving it out");
+            None
+        }
+    }
+}
+
+/// The `weekly_overview` template from the config directory, or the
+/// built-in one.
+async fn overview_template() -> Result<ChatTemplate, Box<dyn Error>> {
+    match template::load_template("weekly_overview").await {
+        Ok(template) => Ok(template),
+        Err(e) => {
+            debug!(error = %e, "No weekly_overview template; using the built-in one");
+            Ok(serde_yaml::from_str(BUILTIN_OVERVIEW_TEMPLATE)?)
+        }
+    }
+}
+
+/// Build and write the weekly edition, and register it in the indexes.
+///
+/// # Returns
+///
+/// The path of the written Markdown file.
+pub async fn run(
+    sink: &impl OutputSink,
+    args: &Cli,
+    options: &WeeklyArgs,
+    json_mirrors: &[String],
+) -> Result<String, Box<dyn Error>> {
+    let week_end = options.week_ending.unwrap_or_else(|| Local::now().date_naive());
+    let editions = load_week(sink, &args.json_output_dir, week_end).await;
+    let mut weekly = build_weekly(&editions, week_end, options.top);
+    info!(
+        week_end = %weekly.week_end,
+        editions = editions.len(),
+        days = weekly.days_covered.len(),
+        stories = weekly.stories.len(),
+        "Selected the week's top stories"
+    );
+
+    if options.overview {
+        let setup = match args.config_path() {
+            Ok(path) => config::load_config(&path.to_string_lossy()),
+            Err(e) => Err(e),
+        };
+        match (setup, overview_template().await) {
+            (Ok(config), Ok(template)) => {
+                weekly.overview = write_overview(&config, &template, args.llm_auth().as_ref(), &weekly).await;
+            }
+            (Err(e), _) | (_, Err(e)) => warn!(error = %e, "Cannot ask for a weekly overview; leaving it out"),
+        }
+    }
+
+    let json_path = write_weekly(sink, &weekly, &args.json_output_dir).await?;
+    write_mirrors(std::slice::from_ref(&json_path), &args.json_output_dir, json_mirrors).await;
+
+    let markdown_filename = format!("{}_weekly.md", weekly.week_end);
+    let markdown_path = format!("{}/{}", args.markdown_output_dir, markdown_filename);
+    let md = markdown::weekly_to_markdown(&weekly, &args.markdown_options());
+    sink.write(&markdown_path, md.as_bytes()).await?;
+    info!(%json_path, %markdown_path, "Wrote weekly edition");
+
+    if let Err(e) = indexes::update_weekly_indexes(sink, &args.markdown_output_dir, &weekly.week_end, &markdown_filename).await {
+        error!(error = %e, "Failed to register the weekly edition in the indexes");
+    }
+    Ok(markdown_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use crate::models::NamedEntity;
+    use crate::outputs::json::{write_frontpage, ContentPolicy};
+    use crate::outputs::sink::FileSystem;
+    use crate::test_support::{write_config, FixtureLlm};
+    use serde_json::json;
+
+    fn article(source: &str, title: &str, category: &str, entities: &[&str]) -> AwfulNewsArticle {
+        AwfulNewsArticle {
+            source: Some(source.to_string()),
+            summaryOfNewsArticle: format!("{} summary.", title),
+            namedEntities: entities
+                .iter()
+                .map(|name| NamedEntity {
+                    name: name.to_string(),
+                    whatIsThisEntity: String::new(),
+                    whyIsThisEntityRelevantToTheArticle: String::new(),
+                    entityType: None,
+                })
+                .collect(),
+            ..test_support::article(title, category)
+        }
+    }
+
+    fn edition(date: &str, time_of_day: &str, articles: Vec<AwfulNewsArticle>) -> FrontPage {
+        FrontPage {
+            local_date: date.to_string(),
+            articles,
+            ..test_support::front_page(time_of_day, "18:00:00")
+        }
+    }
+
+    const POLITICS: &str = "Politics & Governance";
+    const SCIENCE: &str = "Science & Technology";
+
+    fn week() -> Vec<FrontPage> {
+        vec![
+            edition(
+                "2025-05-05",
+                "morning",
+                vec![
+                    article("https://lite.cnn.com/talks", "Talks begin", POLITICS, &["Cairo"]),
+                    article("https://text.npr.org/probe", "Probe launched", SCIENCE, &["NASA"]),
+                    article("https://apnews.com/article/vote", "Vote called", POLITICS, &[]),
+                ],
+            ),
+            edition(
+                "2025-05-07",
+                "evening",
+                vec![
+                    // Covered again, reworded
+                    article("https://lite.cnn.com/talks", "Talks stall", POLITICS, &["Cairo"]),
+                    article("https://www.bbc.com/news/envoy", "Envoy arrives", POLITICS, &["Cairo"]),
+                    article("https://www.bbc.com/news/budget", "Budget passes", POLITICS, &[]),
+                    article("https://text.npr.org/rover", "Rover lands", SCIENCE, &[]),
+                ],
+            ),
+        ]
+    }
+
+    fn titles(stories: &[WeeklyStory]) -> Vec<&str> {
+        stories.iter().map(|s| s.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_week_dates_end_on_the_given_day() {
+        let dates = week_dates(NaiveDate::from_ymd_opt(2025, 5, 11).unwrap());
+        assert_eq!(dates.len(), 7);
+        assert_eq!(dates[0].to_string(), "2025-05-05");
+        assert_eq!(dates[6].to_string(), "2025-05-11");
+    }
+
+    #[test]
+    fn test_selection_ranks_coverage_then_shared_entities() {
+        let stories = select_stories(&week(), 2);
+
+        // Categories in priority order, at most two stories each
+        assert_eq!(titles(&stories), vec!["Talks stall", "Envoy arrives", "Rover lands", "Probe launched"]);
+
+        let talks = &stories[0];
+        assert_eq!((talks.coverage, talks.shared_entities, talks.score), (2, 1, 2 * COVERAGE_WEIGHT + 1));
+        // Links to the latest edition carrying the story
+        assert_eq!(talks.edition_file, "2025-05-07_evening.md");
+        assert_eq!(talks.anchor, week()[1].articles[0].anchor_id());
+
+        // Shares "Cairo" with the talks; beats the equally covered budget story
+        assert_eq!((stories[1].coverage, stories[1].shared_entities), (1, 1));
+        // Science tie on score: the more recent edition first
+        assert_eq!(stories[2].edition_file, "2025-05-07_evening.md");
+
+        // Deterministic regardless of how often it's computed
+        assert_eq!(select_stories(&week(), 2), stories);
+    }
+
+    #[test]
+    fn test_build_weekly_of_a_sparse_week() {
+        let week_end = NaiveDate::from_ymd_opt(2025, 5, 11).unwrap();
+        let weekly = build_weekly(&week(), week_end, DEFAULT_STORIES_PER_CATEGORY);
+        assert_eq!((weekly.week_start.as_str(), weekly.week_end.as_str()), ("2025-05-05", "2025-05-11"));
+        assert_eq!(weekly.days_covered, vec!["2025-05-05", "2025-05-07"]);
+        assert_eq!(weekly.stories.len(), 5);
+
+        let empty = build_weekly(&[], week_end, DEFAULT_STORIES_PER_CATEGORY);
+        assert!(empty.days_covered.is_empty() && empty.stories.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_load_week_skips_missing_and_corrupt_days() {
+        let dir = tempfile::tempdir().unwrap();
+        let json_dir = dir.path().to_str().unwrap();
+        for page in week() {
+            write_frontpage(&FileSystem, &page, json_dir, ContentPolicy::Full, None, None).await.unwrap();
+        }
+        // Outside the week
+        write_frontpage(&FileSystem, &edition("2025-05-04", "evening", vec![]), json_dir, ContentPolicy::Full, None, None).await.unwrap();
+        std::fs::create_dir_all(dir.path().join("2025-05-09")).unwrap();
+        std::fs::write(dir.path().join("2025-05-09/morning.json"), "{ truncated").unwrap();
+
+        let editions = load_week(&FileSystem, json_dir, NaiveDate::from_ymd_opt(2025, 5, 11).unwrap()).await;
+        let stems: Vec<String> = editions.iter().map(FrontPage::edition_stem).collect();
+        assert_eq!(stems, vec!["2025-05-05_morning", "2025-05-07_evening"]);
+    }
+
+    #[tokio::test]
+    async fn test_overview_is_optional_and_failure_tolerant() {
+        let llm = FixtureLlm::start(vec![("Talks stall", json!("A week of stalled talks."))]).await;
+        let dir = tempfile::tempdir().unwrap();
+        let config = config::load_config(write_config(dir.path(), &llm.api_base()).to_str().unwrap()).unwrap();
+        let template: ChatTemplate = serde_yaml::from_str(BUILTIN_OVERVIEW_TEMPLATE).unwrap();
+        let week_end = NaiveDate::from_ymd_opt(2025, 5, 11).unwrap();
+
+        let weekly = build_weekly(&week(), week_end, DEFAULT_STORIES_PER_CATEGORY);
+        let overview = write_overview(&config, &template, None, &weekly).await;
+        assert_eq!(overview.as_deref(), Some("A week of stalled talks."));
+
+        // No canned reply for this week: the server errors and the overview is left out
+        let other = build_weekly(&week()[..1], week_end, DEFAULT_STORIES_PER_CATEGORY);
+        assert_eq!(write_overview(&config, &template, None, &other).await, None);
+        // Nothing to summarize: no request at all
+        let requests = llm.server.received_requests().await.unwrap().len();
+        assert_eq!(write_overview(&config, &template, None, &build_weekly(&[], week_end, 3)).await, None);
+        assert_eq!(llm.server.received_requests().await.unwrap().len(), requests);
+    }
+}